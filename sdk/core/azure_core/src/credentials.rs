@@ -209,12 +209,79 @@ impl AccessToken {
 }
 
 /// Options for getting a token from a [`TokenCredential`]
+///
+/// Insert [`TokenClaims`] or [`EnableCae`] into the context of `method_options` to request
+/// a token containing additional claims or supporting continuous access evaluation.
 #[derive(Clone, Default, SafeDebug)]
 pub struct TokenRequestOptions<'a> {
     /// Method options to be used when requesting a token.
     pub method_options: ClientMethodOptions<'a>,
 }
 
+impl TokenRequestOptions<'_> {
+    /// Gets the claims from the [`TokenClaims`] in the context, if any.
+    pub fn claims(&self) -> Option<&str> {
+        self.method_options
+            .context
+            .value::<TokenClaims>()
+            .map(TokenClaims::as_str)
+    }
+
+    /// Gets whether the context contains [`EnableCae`].
+    pub fn enable_cae(&self) -> bool {
+        self.method_options.context.value::<EnableCae>().is_some()
+    }
+}
+
+/// Additional claims a token must contain, as a JSON object, typically decoded from a claims challenge.
+///
+/// Credentials don't return cached tokens for requests specifying claims.
+///
+/// # Examples
+///
+/// ```
+/// use azure_core::credentials::{TokenClaims, TokenRequestOptions};
+///
+/// let mut options = TokenRequestOptions::default();
+/// options
+///     .method_options
+///     .context
+///     .insert(TokenClaims::new(r#"{"access_token":{"nbf":{"essential":true,"value":"1726077595"}}}"#));
+/// assert!(options.claims().is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenClaims(String);
+
+impl TokenClaims {
+    /// Create a new `TokenClaims` from a JSON object.
+    pub fn new(claims: impl Into<String>) -> Self {
+        Self(claims.into())
+    }
+
+    /// Gets the claims as a JSON object.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Indicates the caller can handle claims challenges from services supporting
+/// [continuous access evaluation](https://learn.microsoft.com/entra/identity/conditional-access/concept-continuous-access-evaluation) (CAE).
+///
+/// Credentials then advertise the `cp1` client capability, for which Microsoft Entra ID
+/// may issue tokens that a service can revoke before they expire.
+///
+/// # Examples
+///
+/// ```
+/// use azure_core::credentials::{EnableCae, TokenRequestOptions};
+///
+/// let mut options = TokenRequestOptions::default();
+/// options.method_options.context.insert(EnableCae);
+/// assert!(options.enable_cae());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EnableCae;
+
 /// Represents a credential that can acquire an Entra ID access token.
 ///
 /// See the [azure_identity](https://docs.rs/azure_identity/latest/azure_identity/)
@@ -2,7 +2,8 @@
 // Licensed under the MIT License.
 
 use crate::{
-    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    base64,
+    credentials::{AccessToken, EnableCae, TokenClaims, TokenCredential, TokenRequestOptions},
     error::ErrorKind,
     http::{
         headers::{Headers, AUTHORIZATION, WWW_AUTHENTICATE},
@@ -52,7 +53,7 @@ impl BearerTokenAuthorizationPolicy {
     /// Sets a callback to invoke upon receiving a 401 Unauthorized response with an authentication challenge.
     ///
     /// See [`OnChallenge`] for more details. When not set, `send` returns 401 responses without attempting to
-    /// handle their challenges.
+    /// handle their challenges, except claims challenges, which the policy always handles.
     pub fn with_on_challenge(mut self, on_challenge: Arc<dyn OnChallenge>) -> Self {
         self.on_challenge = Some(on_challenge);
         self
@@ -83,23 +84,37 @@ impl Policy for BearerTokenAuthorizationPolicy {
 
         if response.status() == StatusCode::Unauthorized {
             self.authorizer.invalidate_cache().await;
-            if let Some(ref callback) = self.on_challenge {
-                if response.headers().get_str(&WWW_AUTHENTICATE).is_ok() {
-                    callback
-                        .on_challenge(&ctx, request, self.authorizer.as_ref(), response.headers())
-                        .await?;
-                    request.body_mut().reset().await?;
-                    if let Some(span) = ctx.value::<Arc<dyn Span>>() {
-                        // this span covers the request which received the 401 response
-                        if span.is_recording() {
-                            span.set_attribute(
-                                ERROR_TYPE_ATTRIBUTE,
-                                response.status().to_string().into(),
-                            );
-                        }
+            let challenge = response.headers().get_optional_str(&WWW_AUTHENTICATE);
+            let retry = match challenge.and_then(claims_challenge) {
+                // a claims challenge indicates the service revoked the token or requires additional claims
+                Some(claims) => self.authorizer.reauthorize(&ctx, request, claims).await?,
+                None => match (&self.on_challenge, challenge) {
+                    (Some(callback), Some(_)) => {
+                        callback
+                            .on_challenge(
+                                &ctx,
+                                request,
+                                self.authorizer.as_ref(),
+                                response.headers(),
+                            )
+                            .await?;
+                        true
+                    }
+                    _ => false,
+                },
+            };
+            if retry {
+                request.body_mut().reset().await?;
+                if let Some(span) = ctx.value::<Arc<dyn Span>>() {
+                    // this span covers the request which received the 401 response
+                    if span.is_recording() {
+                        span.set_attribute(
+                            ERROR_TYPE_ATTRIBUTE,
+                            response.status().to_string().into(),
+                        );
                     }
-                    response = next[0].send(&ctx, request, &next[1..]).await?
                 }
+                response = next[0].send(&ctx, request, &next[1..]).await?
             }
         }
 
@@ -171,6 +186,8 @@ pub trait Authorizer: crate::private::Sealed + std::fmt::Debug + Send + Sync {
 struct BearerTokenAuthorizer {
     access_token: Arc<RwLock<Option<AccessToken>>>,
     credential: Arc<dyn TokenCredential>,
+    /// The scopes of the most recent token request, which the authorizer requests again to answer a claims challenge.
+    scopes: RwLock<Vec<String>>,
 }
 
 impl BearerTokenAuthorizer {
@@ -178,6 +195,7 @@ impl BearerTokenAuthorizer {
         Self {
             access_token: Arc::new(RwLock::new(None)),
             credential,
+            scopes: RwLock::new(Vec::new()),
         }
     }
 
@@ -185,6 +203,29 @@ impl BearerTokenAuthorizer {
         let mut access_token = self.access_token.write().await;
         *access_token = None;
     }
+
+    /// Authorizes a request with a new token containing the claims from a claims challenge.
+    ///
+    /// Returns `false` when the authorizer hasn't requested a token before, so doesn't know which scopes to request.
+    async fn reauthorize(
+        &self,
+        context: &Context<'_>,
+        request: &mut Request,
+        claims: String,
+    ) -> Result<bool> {
+        let scopes = self.scopes.read().await.clone();
+        if scopes.is_empty() {
+            return Ok(false);
+        }
+        let scopes: Vec<&str> = scopes.iter().map(String::as_str).collect();
+        let options = TokenRequestOptions {
+            method_options: ClientMethodOptions {
+                context: context.clone().with_value(TokenClaims::new(claims)),
+            },
+        };
+        self.authorize(request, &scopes, options).await?;
+        Ok(true)
+    }
 }
 
 impl crate::private::Sealed for BearerTokenAuthorizer {}
@@ -197,8 +238,29 @@ impl Authorizer for BearerTokenAuthorizer {
         scopes: &[&str],
         options: TokenRequestOptions<'_>,
     ) -> Result<()> {
+        // the policy handles claims challenges, so it always supports continuous access evaluation
+        let mut options = options;
+        options.method_options.context.insert(EnableCae);
+        {
+            let last_scopes = self.scopes.read().await;
+            if !last_scopes
+                .iter()
+                .map(String::as_str)
+                .eq(scopes.iter().copied())
+            {
+                drop(last_scopes);
+                *self.scopes.write().await = scopes.iter().map(ToString::to_string).collect();
+            }
+        }
+
         let access_token = self.access_token.read().await;
         match access_token.as_ref() {
+            _ if options.claims().is_some() => {
+                // a token with the required claims can't be cached, so always request a new one
+                drop(access_token);
+                let mut access_token = self.access_token.write().await;
+                *access_token = Some(self.credential.get_token(scopes, Some(options)).await?);
+            }
             None => {
                 // cache is empty. Upgrade the lock and acquire a token, provided another thread hasn't already done so
                 drop(access_token);
@@ -260,6 +322,72 @@ fn should_refresh(expires_on: &OffsetDateTime) -> bool {
     *expires_on <= OffsetDateTime::now_utc() + Duration::minutes(5)
 }
 
+/// Gets the decoded claims from a `WWW-Authenticate` header containing a claims challenge: a `Bearer`
+/// challenge having `error="insufficient_claims"` and base64 encoded `claims`.
+///
+/// Returns `None` when the header contains no claims challenge.
+fn claims_challenge(header: &str) -> Option<String> {
+    let challenge = parse_challenges(header)
+        .into_iter()
+        .find(|(scheme, params)| {
+            scheme.eq_ignore_ascii_case("Bearer")
+                && params
+                    .iter()
+                    .any(|(k, v)| k.eq_ignore_ascii_case("error") && v == "insufficient_claims")
+        })?;
+    let (_, claims) = challenge
+        .1
+        .into_iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("claims"))?;
+    let claims = base64::decode(&claims)
+        .or_else(|_| base64::decode_url_safe(&claims))
+        .ok()?;
+    String::from_utf8(claims).ok().filter(|c| !c.is_empty())
+}
+
+/// Parses the challenges in a `WWW-Authenticate` header into their schemes and parameters.
+fn parse_challenges(header: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut challenges: Vec<(String, Vec<(String, String)>)> = Vec::new();
+    let mut chars = header.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+        let mut token = String::new();
+        while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ',' && *c != '=') {
+            token.push(c);
+        }
+        if token.is_empty() {
+            if chars.next().is_none() {
+                break;
+            }
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'=').is_none() {
+            challenges.push((token, Vec::new()));
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => value.extend(chars.next()),
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != ',') {
+                value.push(c);
+            }
+        }
+        if let Some((_, params)) = challenges.last_mut() {
+            params.push((token, value));
+        }
+    }
+    challenges
+}
+
 #[derive(Debug, Default)]
 struct DefaultOnRequest {
     scopes: Vec<String>,
@@ -308,12 +436,13 @@ mod tests {
     use futures::FutureExt;
     use std::sync::{
         atomic::{AtomicUsize, Ordering},
-        Arc,
+        Arc, Mutex,
     };
 
     #[derive(Debug, Clone)]
     struct MockCredential {
         calls: Arc<AtomicUsize>,
+        claims: Arc<Mutex<Vec<Option<String>>>>,
         tokens: Arc<[AccessToken]>,
     }
 
@@ -321,6 +450,7 @@ mod tests {
         fn new(tokens: &[AccessToken]) -> Self {
             Self {
                 calls: Arc::new(AtomicUsize::new(0)),
+                claims: Arc::default(),
                 tokens: tokens.into(),
             }
         }
@@ -328,6 +458,11 @@ mod tests {
         fn get_token_calls(&self) -> usize {
             self.calls.load(Ordering::SeqCst)
         }
+
+        /// The claims of each get_token() call
+        fn claims(&self) -> Vec<Option<String>> {
+            self.claims.lock().unwrap().clone()
+        }
     }
 
     // ensure the number of get_token() calls matches the number of tokens
@@ -345,8 +480,14 @@ mod tests {
         async fn get_token(
             &self,
             _: &[&str],
-            _: Option<TokenRequestOptions<'_>>,
+            options: Option<TokenRequestOptions<'_>>,
         ) -> Result<AccessToken> {
+            let options = options.expect("the policy should specify options");
+            assert!(options.enable_cae(), "the policy should enable CAE");
+            self.claims
+                .lock()
+                .unwrap()
+                .push(options.claims().map(ToString::to_string));
             let i = self.calls.fetch_add(1, Ordering::SeqCst);
             self.tokens
                 .get(i)
@@ -928,4 +1069,225 @@ mod tests {
             }],
         );
     }
+
+    const CLAIMS: &str = r#"{"access_token":{"nbf":{"essential":true,"value":"1726077595"}}}"#;
+    const ENCODED_CLAIMS: &str =
+        "eyJhY2Nlc3NfdG9rZW4iOnsibmJmIjp7ImVzc2VudGlhbCI6dHJ1ZSwidmFsdWUiOiIxNzI2MDc3NTk1In19fQ==";
+
+    #[test]
+    fn parse_claims_challenge() {
+        let challenge = format!(
+            r#"Bearer realm="", authorization_uri="https://login.microsoftonline.com/common/oauth2/authorize", error="insufficient_claims", claims="{ENCODED_CLAIMS}""#
+        );
+        assert_eq!(Some(CLAIMS.to_string()), claims_challenge(&challenge));
+
+        // among other challenges, without padding
+        let challenge = format!(
+            r#"PoP realm="", nonce="abc", Bearer error="insufficient_claims", claims="{}""#,
+            ENCODED_CLAIMS.trim_end_matches('=')
+        );
+        assert_eq!(Some(CLAIMS.to_string()), claims_challenge(&challenge));
+
+        // case insensitive scheme and parameter names
+        let challenge = format!(r#"bearer Error="insufficient_claims",Claims="{ENCODED_CLAIMS}""#);
+        assert_eq!(Some(CLAIMS.to_string()), claims_challenge(&challenge));
+
+        for challenge in [
+            "",
+            "Bearer challenge",
+            r#"Bearer authorization="https://login.microsoftonline.com/tenant", resource="https://vault.azure.net""#,
+            &format!(r#"Bearer error="invalid_token", claims="{ENCODED_CLAIMS}""#),
+            &format!(r#"PoP error="insufficient_claims", claims="{ENCODED_CLAIMS}""#),
+            r#"Bearer error="insufficient_claims""#,
+            r#"Bearer error="insufficient_claims", claims="""#,
+            r#"Bearer error="insufficient_claims", claims="not base64!""#,
+        ] {
+            assert_eq!(None, claims_challenge(challenge), "{challenge}");
+        }
+    }
+
+    #[test]
+    fn parse_challenge_parameters() {
+        let challenges =
+            parse_challenges(r#"Basic realm="a \"quoted\" realm", Bearer scope=x,error="y""#);
+        assert_eq!(
+            vec![
+                (
+                    "Basic".to_string(),
+                    vec![("realm".to_string(), r#"a "quoted" realm"#.to_string())]
+                ),
+                (
+                    "Bearer".to_string(),
+                    vec![
+                        ("scope".to_string(), "x".to_string()),
+                        ("error".to_string(), "y".to_string())
+                    ]
+                ),
+            ],
+            challenges
+        );
+    }
+
+    fn claims_challenge_response() -> AsyncRawResponse {
+        AsyncRawResponse::from_bytes(
+            StatusCode::Unauthorized,
+            Headers::from(std::collections::HashMap::from([(
+                WWW_AUTHENTICATE,
+                HeaderValue::from(format!(
+                    r#"Bearer realm="", error="insufficient_claims", claims="{ENCODED_CLAIMS}""#
+                )),
+            )])),
+            Bytes::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn claims_challenge_with_retry() {
+        use crate::{http::Body, stream::BytesStream};
+        use futures::StreamExt;
+
+        let credential = Arc::new(MockCredential::new(&[
+            AccessToken {
+                token: Secret::new("first".to_string()),
+                expires_on: OffsetDateTime::now_utc() + Duration::seconds(3600),
+            },
+            AccessToken {
+                token: Secret::new("second".to_string()),
+                expires_on: OffsetDateTime::now_utc() + Duration::seconds(3600),
+            },
+        ]));
+        let policy = BearerTokenAuthorizationPolicy::new(credential.clone(), ["scope"]);
+
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let request_count_clone = request_count.clone();
+        let client = MockHttpClient::new(move |actual| {
+            let count = request_count_clone.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match actual.body() {
+                    Body::SeekableStream(stream) => {
+                        let mut stream = stream.clone();
+                        let mut collected = Vec::new();
+                        while let Some(chunk) = stream.next().await {
+                            collected.extend_from_slice(&chunk?);
+                        }
+                        assert_eq!(b"test data", collected.as_slice());
+                    }
+                    _ => unreachable!("body is a SeekableStream"),
+                }
+
+                let authz = actual.headers().get_str(&AUTHORIZATION)?;
+                if count == 0 {
+                    assert_eq!("Bearer first", authz);
+                    Ok(claims_challenge_response())
+                } else {
+                    assert_eq!("Bearer second", authz);
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        Headers::new(),
+                        Bytes::new(),
+                    ))
+                }
+            }
+            .boxed()
+        });
+        let transport = Arc::new(TransportPolicy::new(Transport::new(Arc::new(client))));
+
+        let mut req = Request::new("https://localhost".parse().unwrap(), Method::Get);
+        req.set_body(Body::SeekableStream(Box::new(BytesStream::new(
+            b"test data".as_slice(),
+        ))));
+        let response = policy
+            .send(
+                &Context::default(),
+                &mut req,
+                std::slice::from_ref(&(transport as Arc<dyn Policy>)),
+            )
+            .await
+            .expect("successful request");
+
+        assert_eq!(StatusCode::Ok, response.status());
+        assert_eq!(2, request_count.load(Ordering::SeqCst));
+        assert_eq!(vec![None, Some(CLAIMS.to_string())], credential.claims());
+    }
+
+    #[tokio::test]
+    async fn claims_challenge_retries_once() {
+        let on_challenge_calls = Arc::new(AtomicUsize::new(0));
+        let on_challenge = Arc::new(TestOnChallenge {
+            calls: on_challenge_calls.clone(),
+            error: None,
+        });
+        let credential = Arc::new(MockCredential::new(
+            &(0..2)
+                .map(|_| AccessToken {
+                    token: Secret::new("token".to_string()),
+                    expires_on: OffsetDateTime::now_utc() + Duration::seconds(3600),
+                })
+                .collect::<Vec<_>>(),
+        ));
+        let policy = BearerTokenAuthorizationPolicy::new(credential.clone(), ["scope"])
+            .with_on_challenge(on_challenge);
+
+        let request_count = Arc::new(AtomicUsize::new(0));
+        let request_count_clone = request_count.clone();
+        let client = MockHttpClient::new(move |_| {
+            request_count_clone.fetch_add(1, Ordering::SeqCst);
+            async { Ok(claims_challenge_response()) }.boxed()
+        });
+        let transport = Arc::new(TransportPolicy::new(Transport::new(Arc::new(client))));
+
+        let mut req = Request::new("https://localhost".parse().unwrap(), Method::Get);
+        let response = policy
+            .send(
+                &Context::default(),
+                &mut req,
+                std::slice::from_ref(&(transport as Arc<dyn Policy>)),
+            )
+            .await
+            .expect("successful request");
+
+        assert_eq!(StatusCode::Unauthorized, response.status());
+        assert_eq!(2, request_count.load(Ordering::SeqCst));
+        // the policy handles claims challenges itself
+        assert_eq!(0, on_challenge_calls.load(Ordering::SeqCst));
+        assert_eq!(vec![None, Some(CLAIMS.to_string())], credential.claims());
+    }
+
+    #[tokio::test]
+    async fn claims_challenge_bypasses_cache() {
+        let credential = Arc::new(MockCredential::new(&[
+            AccessToken {
+                token: Secret::new("first".to_string()),
+                expires_on: OffsetDateTime::now_utc() + Duration::seconds(3600),
+            },
+            AccessToken {
+                token: Secret::new("second".to_string()),
+                expires_on: OffsetDateTime::now_utc() + Duration::seconds(3600),
+            },
+        ]));
+        let policy = BearerTokenAuthorizationPolicy::new(credential.clone(), ["scope"]);
+
+        let mut req = Request::new("https://localhost".parse().unwrap(), Method::Get);
+        policy
+            .authorizer
+            .authorize(&mut req, &["scope"], TokenRequestOptions::default())
+            .await
+            .expect("authorized request");
+        assert_eq!(
+            "Bearer first",
+            req.headers().get_str(&AUTHORIZATION).unwrap()
+        );
+
+        // the cached token is valid, but doesn't have the required claims
+        assert!(policy
+            .authorizer
+            .reauthorize(&Context::default(), &mut req, CLAIMS.to_string())
+            .await
+            .expect("authorized request"));
+        assert_eq!(
+            "Bearer second",
+            req.headers().get_str(&AUTHORIZATION).unwrap()
+        );
+        assert_eq!(vec![None, Some(CLAIMS.to_string())], credential.claims());
+    }
 }
@@ -5,7 +5,7 @@ use crate::{
     error::Result,
     value::{AmqpOrderedMap, AmqpSymbol, AmqpValue},
 };
-use azure_core::{http::Url, time::Duration};
+use azure_core::{credentials::Secret, http::Url, time::Duration};
 use std::fmt::Debug;
 
 #[cfg(feature = "fe2o3_amqp")]
@@ -37,10 +37,55 @@ pub struct AmqpConnectionOptions {
     pub buffer_size: Option<usize>,
     /// Custom endpoint for the connection. Used to connect to a local AMQP proxy server.
     pub custom_endpoint: Option<Url>,
+    /// The transport used to carry the connection. Defaults to [`AmqpTransportType::AmqpTcp`].
+    pub transport_type: Option<AmqpTransportType>,
+    /// HTTP proxy through which the connection is opened.
+    ///
+    /// A proxy can only be used with [`AmqpTransportType::AmqpWebSockets`].
+    pub proxy: Option<AmqpProxyOptions>,
 }
 
 impl AmqpConnectionOptions {}
 
+/// The transport used to carry an AMQP connection.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum AmqpTransportType {
+    /// AMQP directly over TCP, on port 5671 for `amqps` URLs and port 5672 for `amqp` URLs.
+    #[default]
+    AmqpTcp,
+
+    /// AMQP over a WebSocket at `/$servicebus/websocket`, on port 443 for `amqps` URLs and
+    /// port 80 for `amqp` URLs.
+    ///
+    /// Use WebSockets when outbound traffic on the AMQP ports is blocked, or when connections
+    /// must go through an HTTP proxy. Requires the `websocket` feature.
+    AmqpWebSockets,
+}
+
+/// An HTTP proxy used to open AMQP connections over WebSockets.
+///
+/// The connection is tunnelled through the proxy with an HTTP `CONNECT` request.
+#[derive(Debug, Clone)]
+pub struct AmqpProxyOptions {
+    /// The URL of the proxy, for example `http://proxy.contoso.com:8080`.
+    pub url: Url,
+    /// The user name sent to the proxy with Basic authentication.
+    pub username: Option<String>,
+    /// The password sent to the proxy with Basic authentication.
+    pub password: Option<Secret>,
+}
+
+impl AmqpProxyOptions {
+    /// Creates proxy options for the proxy at `url`, without authentication.
+    pub fn new(url: Url) -> Self {
+        Self {
+            url,
+            username: None,
+            password: None,
+        }
+    }
+}
+
 /// Trait defining the asynchronous APIs for AMQP connection operations.
 #[async_trait::async_trait]
 pub trait AmqpConnectionApis {
@@ -250,6 +295,10 @@ mod tests {
                     .collect(),
             ),
             buffer_size: Some(1024),
+            transport_type: Some(AmqpTransportType::AmqpWebSockets),
+            proxy: Some(AmqpProxyOptions::new(
+                Url::parse("http://proxy.contoso.com:8080").unwrap(),
+            )),
         };
 
         assert_eq!(connection_options.max_frame_size, Some(1024));
@@ -281,6 +330,14 @@ mod tests {
             connection_options.custom_endpoint,
             Some(Url::parse("http://localhost:8080").unwrap())
         );
+        assert_eq!(
+            connection_options.transport_type,
+            Some(AmqpTransportType::AmqpWebSockets)
+        );
+        assert_eq!(
+            connection_options.proxy.map(|proxy| proxy.url),
+            Some(Url::parse("http://proxy.contoso.com:8080").unwrap())
+        );
     }
 
     // On macOS, there is a periodic issue where loopback TCP connections fail.
@@ -80,6 +80,10 @@ pub enum AmqpErrorCondition {
     SessionNotFound,
     /// Microsoft specific error conditions: entity already exists.
     EntityAlreadyExists,
+    /// Microsoft specific error conditions: out of order sequence.
+    OutOfOrderSequence,
+    /// Microsoft specific error conditions: producer epoch stolen.
+    ProducerEpochStolen,
     /// AMQP specific error conditions: connection redirect.
     ConnectionRedirect,
     /// AMQP specific error conditions: link redirect.
@@ -94,6 +98,12 @@ pub enum AmqpErrorCondition {
     SessionHandleInUse,
     /// AMQP specific error conditions: session unattached handle.
     SessionUnattachedHandle,
+    /// AMQP specific error conditions: transaction unknown id.
+    TransactionUnknownId,
+    /// AMQP specific error conditions: transaction rollback.
+    TransactionRollback,
+    /// AMQP specific error conditions: transaction timeout.
+    TransactionTimeout,
     /// Any other value not defined in `AmqpErrorCondition`.
     UnknownValue(String),
 }
@@ -136,6 +146,8 @@ impl<'a> ::std::convert::From<&'a AmqpErrorCondition> for &'a str {
             AmqpErrorCondition::MessageNotFound => "com.microsoft:message-not-found",
             AmqpErrorCondition::SessionNotFound => "com.microsoft:session-not-found",
             AmqpErrorCondition::EntityAlreadyExists => "com.microsoft:entity-already-exists",
+            AmqpErrorCondition::OutOfOrderSequence => "com.microsoft:out-of-order-sequence",
+            AmqpErrorCondition::ProducerEpochStolen => "com.microsoft:producer-epoch-stolen",
             AmqpErrorCondition::ConnectionRedirect => "amqp:connection:redirect",
             AmqpErrorCondition::LinkRedirect => "amqp:link:redirect",
             AmqpErrorCondition::TransferLimitExceeded => "amqp:link:transfer-limit-exceeded",
@@ -143,6 +155,9 @@ impl<'a> ::std::convert::From<&'a AmqpErrorCondition> for &'a str {
             AmqpErrorCondition::SessionErrantLink => "amqp:session:errant-link",
             AmqpErrorCondition::SessionHandleInUse => "amqp:session:handle-in-use",
             AmqpErrorCondition::SessionUnattachedHandle => "amqp:session:unattached-handle",
+            AmqpErrorCondition::TransactionUnknownId => "amqp:transaction:unknown-id",
+            AmqpErrorCondition::TransactionRollback => "amqp:transaction:rollback",
+            AmqpErrorCondition::TransactionTimeout => "amqp:transaction:timeout",
             AmqpErrorCondition::UnknownValue(s) => s.as_ref(),
         }
     }
@@ -187,6 +202,8 @@ impl ::std::str::FromStr for AmqpErrorCondition {
             "com.microsoft:message-not-found" => AmqpErrorCondition::MessageNotFound,
             "com.microsoft:session-not-found" => AmqpErrorCondition::SessionNotFound,
             "com.microsoft:entity-already-exists" => AmqpErrorCondition::EntityAlreadyExists,
+            "com.microsoft:out-of-order-sequence" => AmqpErrorCondition::OutOfOrderSequence,
+            "com.microsoft:producer-epoch-stolen" => AmqpErrorCondition::ProducerEpochStolen,
             "amqp:connection:redirect" => AmqpErrorCondition::ConnectionRedirect,
             "amqp:link:redirect" => AmqpErrorCondition::LinkRedirect,
             "amqp:link:transfer-limit-exceeded" => AmqpErrorCondition::TransferLimitExceeded,
@@ -194,6 +211,9 @@ impl ::std::str::FromStr for AmqpErrorCondition {
             "amqp:session:errant-link" => AmqpErrorCondition::SessionErrantLink,
             "amqp:session:handle-in-use" => AmqpErrorCondition::SessionHandleInUse,
             "amqp:session:unattached-handle" => AmqpErrorCondition::SessionUnattachedHandle,
+            "amqp:transaction:unknown-id" => AmqpErrorCondition::TransactionUnknownId,
+            "amqp:transaction:rollback" => AmqpErrorCondition::TransactionRollback,
+            "amqp:transaction:timeout" => AmqpErrorCondition::TransactionTimeout,
             _ => AmqpErrorCondition::UnknownValue(s.to_string()),
         })
     }
@@ -237,6 +257,8 @@ impl ::std::convert::AsRef<str> for AmqpErrorCondition {
             AmqpErrorCondition::MessageNotFound => "com.microsoft:message-not-found",
             AmqpErrorCondition::SessionNotFound => "com.microsoft:session-not-found",
             AmqpErrorCondition::EntityAlreadyExists => "com.microsoft:entity-already-exists",
+            AmqpErrorCondition::OutOfOrderSequence => "com.microsoft:out-of-order-sequence",
+            AmqpErrorCondition::ProducerEpochStolen => "com.microsoft:producer-epoch-stolen",
             AmqpErrorCondition::ConnectionRedirect => "amqp:connection:redirect",
             AmqpErrorCondition::LinkRedirect => "amqp:link:redirect",
             AmqpErrorCondition::TransferLimitExceeded => "amqp:link:transfer-limit-exceeded",
@@ -244,6 +266,9 @@ impl ::std::convert::AsRef<str> for AmqpErrorCondition {
             AmqpErrorCondition::SessionErrantLink => "amqp:session:errant-link",
             AmqpErrorCondition::SessionHandleInUse => "amqp:session:handle-in-use",
             AmqpErrorCondition::SessionUnattachedHandle => "amqp:session:unattached-handle",
+            AmqpErrorCondition::TransactionUnknownId => "amqp:transaction:unknown-id",
+            AmqpErrorCondition::TransactionRollback => "amqp:transaction:rollback",
+            AmqpErrorCondition::TransactionTimeout => "amqp:transaction:timeout",
             AmqpErrorCondition::UnknownValue(s) => s.as_str(),
         }
     }
@@ -305,6 +330,12 @@ impl ::std::fmt::Display for AmqpErrorCondition {
             AmqpErrorCondition::EntityAlreadyExists => {
                 f.write_str("com.microsoft:entity-already-exists")
             }
+            AmqpErrorCondition::OutOfOrderSequence => {
+                f.write_str("com.microsoft:out-of-order-sequence")
+            }
+            AmqpErrorCondition::ProducerEpochStolen => {
+                f.write_str("com.microsoft:producer-epoch-stolen")
+            }
             AmqpErrorCondition::ConnectionRedirect => f.write_str("amqp:connection:redirect"),
             AmqpErrorCondition::LinkRedirect => f.write_str("amqp:link:redirect"),
             AmqpErrorCondition::TransferLimitExceeded => {
@@ -318,6 +349,9 @@ impl ::std::fmt::Display for AmqpErrorCondition {
             AmqpErrorCondition::SessionUnattachedHandle => {
                 f.write_str("amqp:session:unattached-handle")
             }
+            AmqpErrorCondition::TransactionUnknownId => f.write_str("amqp:transaction:unknown-id"),
+            AmqpErrorCondition::TransactionRollback => f.write_str("amqp:transaction:rollback"),
+            AmqpErrorCondition::TransactionTimeout => f.write_str("amqp:transaction:timeout"),
             AmqpErrorCondition::UnknownValue(s) => f.write_str(s.as_str()),
         }
     }
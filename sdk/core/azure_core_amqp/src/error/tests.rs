@@ -119,6 +119,20 @@ fn test_amqp_error_condition_from_str() {
         AmqpErrorCondition::SessionUnattachedHandle
     );
 
+    // Test transaction error conditions
+    assert_eq!(
+        AmqpErrorCondition::from_str("amqp:transaction:unknown-id").unwrap(),
+        AmqpErrorCondition::TransactionUnknownId
+    );
+    assert_eq!(
+        AmqpErrorCondition::from_str("amqp:transaction:rollback").unwrap(),
+        AmqpErrorCondition::TransactionRollback
+    );
+    assert_eq!(
+        AmqpErrorCondition::from_str("amqp:transaction:timeout").unwrap(),
+        AmqpErrorCondition::TransactionTimeout
+    );
+
     // Test Microsoft-specific error conditions
     assert_eq!(
         AmqpErrorCondition::from_str("com.microsoft:server-busy").unwrap(),
@@ -188,6 +202,14 @@ fn test_amqp_error_condition_from_str() {
         AmqpErrorCondition::from_str("com.microsoft:entity-already-exists").unwrap(),
         AmqpErrorCondition::EntityAlreadyExists
     );
+    assert_eq!(
+        AmqpErrorCondition::from_str("com.microsoft:out-of-order-sequence").unwrap(),
+        AmqpErrorCondition::OutOfOrderSequence
+    );
+    assert_eq!(
+        AmqpErrorCondition::from_str("com.microsoft:producer-epoch-stolen").unwrap(),
+        AmqpErrorCondition::ProducerEpochStolen
+    );
 
     // Test Proton-specific error condition
     assert_eq!(
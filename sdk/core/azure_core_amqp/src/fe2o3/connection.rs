@@ -2,7 +2,7 @@
 // Licensed under the MIT license.
 
 use crate::{
-    connection::{AmqpConnectionApis, AmqpConnectionOptions},
+    connection::{AmqpConnectionApis, AmqpConnectionOptions, AmqpTransportType},
     error::{AmqpErrorKind, Result},
     fe2o3::error::{Fe2o3ConnectionError, Fe2o3ConnectionOpenError, Fe2o3TransportError},
     value::{AmqpOrderedMap, AmqpSymbol, AmqpValue},
@@ -116,13 +116,37 @@ impl AmqpConnectionApis for Fe2o3AmqpConnection {
                 builder = builder.hostname(url.host_str());
             }
 
-            self.connection
-                .set(Mutex::new(
+            let connection = match options.transport_type.unwrap_or_default() {
+                AmqpTransportType::AmqpTcp => {
+                    if options.proxy.is_some() {
+                        return Err(AmqpError::with_message(
+                            "A proxy can only be used with the WebSockets transport.",
+                        ));
+                    }
+                    builder.open(endpoint).await
+                }
+                #[cfg(feature = "websocket")]
+                AmqpTransportType::AmqpWebSockets => {
+                    let stream =
+                        crate::fe2o3::websocket::connect(&endpoint, options.proxy.as_ref()).await?;
+                    // TLS, if any, is established beneath the WebSocket.
                     builder
-                        .open(endpoint)
+                        .scheme("amqp")
+                        .hostname(url.host_str())
+                        .open_with_stream(stream)
                         .await
-                        .map_err(|e| AmqpError::from(Fe2o3ConnectionOpenError(e)))?,
-                ))
+                }
+                #[cfg(not(feature = "websocket"))]
+                AmqpTransportType::AmqpWebSockets => {
+                    return Err(AmqpError::with_message(
+                        "The WebSockets transport requires the `websocket` feature.",
+                    ))
+                }
+            }
+            .map_err(|e| AmqpError::from(Fe2o3ConnectionOpenError(e)))?;
+
+            self.connection
+                .set(Mutex::new(connection))
                 .map_err(|_| Self::connection_already_set())?;
             Ok(())
         }
@@ -62,6 +62,9 @@ impl From<&fe2o3_amqp_types::definitions::ErrorCondition> for AmqpErrorCondition
             fe2o3_amqp_types::definitions::ErrorCondition::LinkError(link_error) => {
                 AmqpErrorCondition::from(link_error)
             }
+            fe2o3_amqp_types::definitions::ErrorCondition::TransactionError(transaction_error) => {
+                AmqpErrorCondition::from(transaction_error)
+            }
             fe2o3_amqp_types::definitions::ErrorCondition::Custom(symbol) => {
                 AmqpErrorCondition::from(AmqpSymbol::from(symbol))
             }
@@ -99,6 +102,12 @@ impl From<&fe2o3_amqp_types::definitions::LinkError> for AmqpErrorCondition {
             .unwrap()
     }
 }
+impl From<&fe2o3_amqp_types::transaction::TransactionError> for AmqpErrorCondition {
+    fn from(e: &fe2o3_amqp_types::transaction::TransactionError) -> Self {
+        AmqpErrorCondition::from_str(fe2o3_amqp_types::primitives::Symbol::from(e).as_str())
+            .unwrap()
+    }
+}
 impl From<fe2o3_amqp_types::definitions::Error> for AmqpDescribedError {
     fn from(e: fe2o3_amqp_types::definitions::Error) -> Self {
         AmqpDescribedError::new(
@@ -299,4 +308,13 @@ mod tests {
     );
     test_amqp_error!(test_link_redirect, LinkError, Redirect, LinkRedirect);
     test_amqp_error!(test_stolen, LinkError, Stolen, LinkStolen);
+
+    #[test]
+    fn test_transaction_rollback() {
+        let error = fe2o3_amqp_types::definitions::ErrorCondition::TransactionError(
+            fe2o3_amqp_types::transaction::TransactionError::Rollback,
+        );
+        let amqp_error = AmqpErrorCondition::from(&error);
+        assert_eq!(amqp_error, AmqpErrorCondition::TransactionRollback);
+    }
 }
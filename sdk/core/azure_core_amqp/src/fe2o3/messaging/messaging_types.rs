@@ -75,6 +75,9 @@ impl From<fe2o3_amqp_types::messaging::Outcome> for AmqpOutcome {
             fe2o3_amqp_types::messaging::Outcome::Released(_) => AmqpOutcome::Released,
             fe2o3_amqp_types::messaging::Outcome::Rejected(_) => AmqpOutcome::Rejected,
             fe2o3_amqp_types::messaging::Outcome::Modified(_) => AmqpOutcome::Modified,
+            // A declared outcome is how a transaction coordinator accepts a declare. It never
+            // settles a message transfer.
+            fe2o3_amqp_types::messaging::Outcome::Declared(_) => AmqpOutcome::Accepted,
         }
     }
 }
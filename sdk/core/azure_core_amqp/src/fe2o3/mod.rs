@@ -8,4 +8,7 @@ pub(crate) mod messaging;
 pub(crate) mod receiver;
 pub(crate) mod sender;
 pub(crate) mod session;
+pub(crate) mod transaction;
 pub(crate) mod value;
+#[cfg(feature = "websocket")]
+pub(crate) mod websocket;
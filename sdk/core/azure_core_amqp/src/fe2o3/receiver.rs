@@ -210,6 +210,11 @@ impl Fe2o3AmqpReceiver {
         }
     }
 
+    /// Gets the attached fe2o3 receiver link.
+    pub(crate) fn link(&self) -> Result<&Mutex<fe2o3_amqp::Receiver>> {
+        self.receiver.get().ok_or_else(Self::receiver_not_set)
+    }
+
     fn receiver_already_attached() -> AmqpError {
         AmqpError::with_message("AMQP Receiver is already attached")
     }
@@ -133,13 +133,15 @@ impl AmqpSenderApis for Fe2o3AmqpSender {
     }
 
     async fn max_message_size(&self) -> Result<Option<u64>> {
+        Ok(self.link()?.lock().await.max_message_size())
+    }
+
+    async fn properties(&self) -> Result<Option<AmqpOrderedMap<AmqpSymbol, AmqpValue>>> {
         Ok(self
-            .sender
-            .get()
-            .ok_or_else(Self::could_not_get_message_sender)?
+            .link()?
             .lock()
             .await
-            .max_message_size())
+            .properties(|properties| properties.as_ref().map(Into::into)))
     }
 
     async fn send<M>(&self, message: M, options: Option<AmqpSendOptions>) -> Result<AmqpSendOutcome>
@@ -158,24 +160,10 @@ impl AmqpSenderApis for Fe2o3AmqpSender {
     where
         M: AsRef<AmqpMessage> + std::fmt::Debug + Send,
     {
-        let message = message.as_ref();
-        let message = fe2o3_amqp_types::messaging::Message::<
-            fe2o3_amqp_types::messaging::Body<fe2o3_amqp_types::primitives::Value>,
-        >::from(message);
-
-        let sendable = fe2o3_amqp::link::delivery::Sendable {
-            message,
-            message_format: options
-                .as_ref()
-                .and_then(|opt| opt.message_format)
-                .unwrap_or(0),
-            settled: options.as_ref().and_then(|opt| opt.settled),
-        };
+        let sendable = sendable(message.as_ref(), options);
 
         let outcome = self
-            .sender
-            .get()
-            .ok_or_else(Self::could_not_get_message_sender)?
+            .link()?
             .lock()
             .await
             .borrow_mut()
@@ -183,7 +171,13 @@ impl AmqpSenderApis for Fe2o3AmqpSender {
             .await
             .map_err(AmqpError::from)?;
 
-        Ok(match outcome {
+        Ok(outcome.into())
+    }
+}
+
+impl From<fe2o3_amqp_types::messaging::Outcome> for AmqpSendOutcome {
+    fn from(outcome: fe2o3_amqp_types::messaging::Outcome) -> Self {
+        match outcome {
             fe2o3_amqp_types::messaging::Outcome::Accepted(_) => AmqpSendOutcome::Accepted,
             fe2o3_amqp_types::messaging::Outcome::Rejected(rejected) => {
                 AmqpSendOutcome::Rejected(rejected.error.map(AmqpDescribedError::from))
@@ -192,7 +186,27 @@ impl AmqpSenderApis for Fe2o3AmqpSender {
             fe2o3_amqp_types::messaging::Outcome::Modified(ref m) => {
                 AmqpSendOutcome::Modified(m.into())
             }
-        })
+            // Only a transaction coordinator answers with a declared outcome, and only for a
+            // declare. A message transfer that gets one has still been accepted.
+            fe2o3_amqp_types::messaging::Outcome::Declared(_) => AmqpSendOutcome::Accepted,
+        }
+    }
+}
+
+/// Makes the fe2o3 sendable for a message.
+pub(crate) fn sendable(
+    message: &AmqpMessage,
+    options: Option<AmqpSendOptions>,
+) -> fe2o3_amqp::link::delivery::Sendable<
+    fe2o3_amqp_types::messaging::Body<fe2o3_amqp_types::primitives::Value>,
+> {
+    fe2o3_amqp::link::delivery::Sendable {
+        message: message.into(),
+        message_format: options
+            .as_ref()
+            .and_then(|opt| opt.message_format)
+            .unwrap_or(0),
+        settled: options.as_ref().and_then(|opt| opt.settled),
     }
 }
 
@@ -216,6 +230,13 @@ impl Fe2o3AmqpSender {
             sender: OnceLock::new(),
         }
     }
+
+    /// Gets the attached fe2o3 sender link.
+    pub(crate) fn link(&self) -> Result<&Mutex<fe2o3_amqp::Sender>> {
+        self.sender
+            .get()
+            .ok_or_else(Self::could_not_get_message_sender)
+    }
 }
 
 impl From<fe2o3_amqp::link::SendError> for AmqpError {
@@ -249,6 +270,7 @@ impl From<fe2o3_amqp::link::SenderAttachError> for AmqpError {
                 AmqpErrorKind::ConnectionDropped(Box::new(e)).into()
             }
             fe2o3_amqp::link::SenderAttachError::CoordinatorIsNotImplemented
+            | fe2o3_amqp::link::SenderAttachError::DesireTxnCapabilitiesNotSupported
             | fe2o3_amqp::link::SenderAttachError::DuplicatedLinkName
             | fe2o3_amqp::link::SenderAttachError::NonAttachFrameReceived
             | fe2o3_amqp::link::SenderAttachError::ExpectImmediateDetach
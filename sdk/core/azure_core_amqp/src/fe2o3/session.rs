@@ -150,20 +150,15 @@ impl From<fe2o3_amqp::session::BeginError> for AmqpError {
 impl From<fe2o3_amqp::session::Error> for AmqpError {
     fn from(e: fe2o3_amqp::session::Error) -> Self {
         match e {
-            fe2o3_amqp::session::Error::UnattachedHandle
-            | fe2o3_amqp::session::Error::RemoteAttachingLinkNameNotFound
-            | fe2o3_amqp::session::Error::HandleInUse
-            | fe2o3_amqp::session::Error::IllegalState
-            | fe2o3_amqp::session::Error::IllegalConnectionState
-            | fe2o3_amqp::session::Error::TransferFrameToSender => {
-                AmqpErrorKind::TransportImplementationError(Box::new(e)).into()
-            }
             fe2o3_amqp::session::Error::RemoteEnded => {
                 AmqpErrorKind::SessionClosedByRemote(Box::new(e)).into()
             }
             fe2o3_amqp::session::Error::RemoteEndedWithError(error) => {
                 AmqpErrorKind::AmqpDescribedError(error.into()).into()
             }
+            // The remaining errors are local session state errors. fe2o3-amqp adds variants
+            // when other crates in the build enable its acceptor and transaction features.
+            _ => AmqpErrorKind::TransportImplementationError(Box::new(e)).into(),
         }
     }
 }
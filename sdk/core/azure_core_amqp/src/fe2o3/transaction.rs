@@ -0,0 +1,283 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use super::sender::sendable;
+use crate::{
+    error::{AmqpDescribedError, AmqpError, AmqpErrorKind, Result},
+    messaging::{AmqpDelivery, AmqpMessage},
+    receiver::AmqpReceiver,
+    sender::{AmqpSendOptions, AmqpSendOutcome, AmqpSender},
+    session::AmqpSession,
+    transaction::AmqpTransactionApis,
+};
+use fe2o3_amqp::transaction::{
+    OwnedTransaction, TransactionDischarge, TransactionExt, TransactionalRetirement,
+};
+use std::borrow::BorrowMut;
+use std::sync::OnceLock;
+use tracing::{debug, trace};
+
+/// A transaction declared on its own controller link.
+///
+/// If the transaction is dropped without being discharged, the controller link is dropped with
+/// it. The transactional resource then rolls back the work associated with the transaction.
+#[derive(Default)]
+pub(crate) struct Fe2o3AmqpTransaction {
+    transaction: OnceLock<OwnedTransaction>,
+}
+
+impl Fe2o3AmqpTransaction {
+    pub fn new() -> Self {
+        Self {
+            transaction: OnceLock::new(),
+        }
+    }
+
+    fn declared(&self) -> Result<&OwnedTransaction> {
+        self.transaction
+            .get()
+            .ok_or_else(Self::transaction_not_declared)
+    }
+
+    fn transaction_already_declared() -> AmqpError {
+        AmqpError::with_message("AMQP transaction is already declared")
+    }
+
+    fn transaction_not_declared() -> AmqpError {
+        AmqpError::with_message("AMQP transaction is not declared")
+    }
+}
+
+#[async_trait::async_trait]
+impl AmqpTransactionApis for Fe2o3AmqpTransaction {
+    async fn declare(&self, session: &AmqpSession, name: String) -> Result<()> {
+        if self.transaction.get().is_some() {
+            return Err(Self::transaction_already_declared());
+        }
+        let transaction = OwnedTransaction::declare(
+            session.implementation.get()?.lock().await.borrow_mut(),
+            name,
+            None,
+        )
+        .await
+        .map_err(AmqpError::from)?;
+        debug!("Declared transaction {:?}", transaction.txn_id());
+        self.transaction
+            .set(transaction)
+            .map_err(|_| Self::transaction_already_declared())
+    }
+
+    fn transaction_id(&self) -> Result<Vec<u8>> {
+        Ok(self.declared()?.txn_id().to_vec())
+    }
+
+    async fn send_ref<M>(
+        &self,
+        sender: &AmqpSender,
+        message: M,
+        options: Option<AmqpSendOptions>,
+    ) -> Result<AmqpSendOutcome>
+    where
+        M: AsRef<AmqpMessage> + std::fmt::Debug + Send,
+    {
+        let transaction = self.declared()?;
+        let sendable = sendable(message.as_ref(), options);
+        let outcome = transaction
+            .post(
+                sender.implementation.link()?.lock().await.borrow_mut(),
+                sendable,
+            )
+            .await
+            .map_err(AmqpError::from)?;
+        Ok(outcome.into())
+    }
+
+    async fn accept_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        let transaction = self.declared()?;
+        trace!("Accepting delivery in transaction.");
+        transaction
+            .accept(
+                receiver.implementation.link()?.lock().await.borrow_mut(),
+                &delivery.0.delivery,
+            )
+            .await
+            .map_err(AmqpError::from)
+    }
+
+    async fn reject_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        let transaction = self.declared()?;
+        trace!("Rejecting delivery in transaction.");
+        transaction
+            .reject(
+                receiver.implementation.link()?.lock().await.borrow_mut(),
+                &delivery.0.delivery,
+                None,
+            )
+            .await
+            .map_err(AmqpError::from)
+    }
+
+    async fn release_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        let transaction = self.declared()?;
+        trace!("Releasing delivery in transaction.");
+        transaction
+            .release(
+                receiver.implementation.link()?.lock().await.borrow_mut(),
+                &delivery.0.delivery,
+            )
+            .await
+            .map_err(AmqpError::from)
+    }
+
+    async fn commit(mut self) -> Result<()> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(Self::transaction_not_declared)?;
+        transaction.commit().await.map_err(AmqpError::from)
+    }
+
+    async fn rollback(mut self) -> Result<()> {
+        let transaction = self
+            .transaction
+            .take()
+            .ok_or_else(Self::transaction_not_declared)?;
+        transaction.rollback().await.map_err(AmqpError::from)
+    }
+}
+
+impl From<fe2o3_amqp::transaction::ControllerSendError> for AmqpError {
+    fn from(e: fe2o3_amqp::transaction::ControllerSendError) -> Self {
+        match e {
+            fe2o3_amqp::transaction::ControllerSendError::LinkStateError(link_state_error) => {
+                AmqpError::from(link_state_error)
+            }
+            fe2o3_amqp::transaction::ControllerSendError::Detached(detach_error) => {
+                detach_error.into()
+            }
+            // The coordinator rejects a declare or discharge with the reason, for example
+            // `amqp:transaction:rollback` when a discharge can't commit.
+            fe2o3_amqp::transaction::ControllerSendError::Rejected(rejected) => {
+                match rejected.error {
+                    Some(error) => {
+                        AmqpErrorKind::AmqpDescribedError(AmqpDescribedError::from(error)).into()
+                    }
+                    None => AmqpErrorKind::SendRejected.into(),
+                }
+            }
+            fe2o3_amqp::transaction::ControllerSendError::NonTerminalDeliveryState => {
+                AmqpErrorKind::NonTerminalDeliveryState.into()
+            }
+            fe2o3_amqp::transaction::ControllerSendError::IllegalDeliveryState => {
+                AmqpErrorKind::IllegalDeliveryState.into()
+            }
+            fe2o3_amqp::transaction::ControllerSendError::MessageEncodeError => {
+                AmqpErrorKind::TransportImplementationError(Box::new(e)).into()
+            }
+        }
+    }
+}
+
+impl From<fe2o3_amqp::transaction::OwnedDeclareError> for AmqpError {
+    fn from(e: fe2o3_amqp::transaction::OwnedDeclareError) -> Self {
+        match e {
+            fe2o3_amqp::transaction::OwnedDeclareError::AttachError(attach_error) => {
+                attach_error.into()
+            }
+            fe2o3_amqp::transaction::OwnedDeclareError::ControllerSendError(send_error) => {
+                send_error.into()
+            }
+        }
+    }
+}
+
+impl From<fe2o3_amqp::transaction::OwnedDischargeError> for AmqpError {
+    fn from(e: fe2o3_amqp::transaction::OwnedDischargeError) -> Self {
+        match e {
+            fe2o3_amqp::transaction::OwnedDischargeError::ControllerSendError(send_error) => {
+                send_error.into()
+            }
+            fe2o3_amqp::transaction::OwnedDischargeError::DetachError(detach_error) => {
+                detach_error.into()
+            }
+        }
+    }
+}
+
+impl From<fe2o3_amqp::transaction::PostError> for AmqpError {
+    fn from(e: fe2o3_amqp::transaction::PostError) -> Self {
+        match e {
+            fe2o3_amqp::transaction::PostError::LinkStateError(link_state_error) => {
+                AmqpError::from(link_state_error)
+            }
+            fe2o3_amqp::transaction::PostError::Detached(detach_error) => detach_error.into(),
+            fe2o3_amqp::transaction::PostError::NonTerminalDeliveryState => {
+                AmqpErrorKind::NonTerminalDeliveryState.into()
+            }
+            fe2o3_amqp::transaction::PostError::IllegalDeliveryState => {
+                AmqpErrorKind::IllegalDeliveryState.into()
+            }
+            fe2o3_amqp::transaction::PostError::MessageEncodeError => {
+                AmqpErrorKind::TransportImplementationError(Box::new(e)).into()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AmqpErrorCondition;
+
+    #[tokio::test]
+    async fn undeclared_transaction_fails() {
+        let transaction = Fe2o3AmqpTransaction::new();
+        assert!(transaction.transaction_id().is_err());
+        assert!(transaction.commit().await.is_err());
+    }
+
+    // A discharge the coordinator can't commit comes back as a rejected outcome. The reason
+    // must stay reachable so callers can tell a rolled back transaction from a lost link.
+    #[test]
+    fn rejected_discharge_keeps_described_error() {
+        let error = fe2o3_amqp::transaction::ControllerSendError::Rejected(
+            fe2o3_amqp_types::messaging::Rejected {
+                error: Some(fe2o3_amqp_types::definitions::Error::new(
+                    fe2o3_amqp_types::transaction::TransactionError::Rollback,
+                    Some("The transaction was rolled back".to_string()),
+                    None,
+                )),
+            },
+        );
+        let amqp_error = AmqpError::from(error);
+        match amqp_error.kind() {
+            AmqpErrorKind::AmqpDescribedError(described) => {
+                assert_eq!(described.condition, AmqpErrorCondition::TransactionRollback);
+            }
+            _ => panic!("expected AmqpDescribedError, got {amqp_error:?}"),
+        }
+    }
+
+    #[test]
+    fn rejected_discharge_without_error_is_send_rejected() {
+        let error = fe2o3_amqp::transaction::ControllerSendError::Rejected(
+            fe2o3_amqp_types::messaging::Rejected { error: None },
+        );
+        assert!(matches!(
+            AmqpError::from(error).kind(),
+            AmqpErrorKind::SendRejected
+        ));
+    }
+}
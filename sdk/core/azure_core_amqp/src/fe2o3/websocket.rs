@@ -0,0 +1,476 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! AMQP over WebSockets.
+//!
+//! Azure messaging services accept AMQP connections tunnelled through a WebSocket at
+//! `/$servicebus/websocket`, which lets clients connect from networks that only allow HTTPS
+//! traffic. The AMQP byte stream is carried in binary WebSocket messages using the `AMQPWSB10`
+//! subprotocol, so the stream returned by [`connect`] can be handed to the AMQP connection in
+//! place of a TCP stream.
+
+use crate::{connection::AmqpProxyOptions, error::Result, AmqpError};
+use azure_core::{base64, http::Url};
+use futures::{Sink, Stream};
+use std::{
+    fmt, io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf},
+    net::TcpStream,
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{header::SEC_WEBSOCKET_PROTOCOL, HeaderValue},
+        protocol::WebSocketConfig,
+        Bytes, Error as WsError, Message,
+    },
+    MaybeTlsStream,
+};
+use tracing::debug;
+
+const WEBSOCKET_PATH: &str = "/$servicebus/websocket";
+const WEBSOCKET_SUBPROTOCOL: &str = "AMQPWSB10";
+
+const MAX_HTTP_HEAD_SIZE: usize = 16 * 1024;
+// Limits on what the server may send, well above the AMQP frame sizes the services negotiate.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+// The largest WebSocket message a single write is sent in.
+const MAX_WRITE_SIZE: usize = 64 * 1024;
+
+/// Opens a WebSocket to the AMQP endpoint of `url`, optionally through an HTTP proxy.
+///
+/// TLS is used for `amqps`, `wss` and `https` URLs, and the port defaults to 443 for those
+/// schemes and to 80 otherwise.
+pub(crate) async fn connect(
+    url: &Url,
+    proxy: Option<&AmqpProxyOptions>,
+) -> Result<WebSocketStream> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| AmqpError::with_message(format!("The URL {url} does not have a host.")))?;
+    let secure = match url.scheme() {
+        "amqps" | "wss" | "https" => true,
+        "amqp" | "ws" | "http" => false,
+        scheme => {
+            return Err(AmqpError::with_message(format!(
+                "The URL scheme '{scheme}' cannot be used with WebSockets."
+            )))
+        }
+    };
+    let port = url.port().unwrap_or(if secure { 443 } else { 80 });
+    // `host` keeps the brackets around IPv6 addresses, as an authority requires.
+    let authority = format!("{host}:{port}");
+
+    let tcp = match proxy {
+        Some(proxy) => connect_through_proxy(proxy, &authority).await?,
+        None => TcpStream::connect((socket_host(host), port))
+            .await
+            .map_err(azure_core::Error::from)?,
+    };
+    tcp.set_nodelay(true).map_err(azure_core::Error::from)?;
+
+    let scheme = if secure { "wss" } else { "ws" };
+    let mut request = format!("{scheme}://{authority}{WEBSOCKET_PATH}")
+        .into_client_request()
+        .map_err(|e| AmqpError::with_message(format!("Invalid WebSocket request: {e}")))?;
+    request.headers_mut().insert(
+        SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(WEBSOCKET_SUBPROTOCOL),
+    );
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(MAX_MESSAGE_SIZE))
+        .max_frame_size(Some(MAX_FRAME_SIZE));
+
+    debug!("Opening WebSocket to {authority}.");
+    let (inner, _) =
+        tokio_tungstenite::client_async_tls_with_config(request, tcp, Some(config), None)
+            .await
+            .map_err(|e| AmqpError::with_message(format!("The WebSocket upgrade failed: {e}")))?;
+    Ok(WebSocketStream {
+        inner,
+        payload: Bytes::new(),
+    })
+}
+
+/// Returns `host` in the form a socket address can be resolved from, without the brackets
+/// around an IPv6 address.
+fn socket_host(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Opens a tunnel to `authority` with an HTTP `CONNECT` request to the proxy.
+async fn connect_through_proxy(proxy: &AmqpProxyOptions, authority: &str) -> Result<TcpStream> {
+    if proxy.url.scheme() != "http" {
+        return Err(AmqpError::with_message(format!(
+            "Proxy URL scheme '{}' is not supported; only HTTP proxies are supported.",
+            proxy.url.scheme()
+        )));
+    }
+    let proxy_host = proxy.url.host_str().ok_or_else(|| {
+        AmqpError::with_message(format!("The proxy URL {} does not have a host.", proxy.url))
+    })?;
+    let proxy_port = proxy.url.port_or_known_default().unwrap_or(80);
+    let mut stream = TcpStream::connect((socket_host(proxy_host), proxy_port))
+        .await
+        .map_err(azure_core::Error::from)?;
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(username) = &proxy.username {
+        let password = proxy.password.as_ref().map(|p| p.secret()).unwrap_or("");
+        let credentials = base64::encode(format!("{username}:{password}"));
+        request.push_str(&format!("Proxy-Authorization: Basic {credentials}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(azure_core::Error::from)?;
+
+    let (status_line, remaining) = read_http_head(&mut stream).await?;
+    match status(&status_line) {
+        Some(status) if (200..300).contains(&status) => {}
+        _ => {
+            return Err(AmqpError::with_message(format!(
+                "The proxy refused to connect to {authority}: {status_line}"
+            )))
+        }
+    }
+    if !remaining.is_empty() {
+        return Err(AmqpError::with_message(
+            "The proxy sent unexpected data after its response.",
+        ));
+    }
+    debug!("Opened tunnel to {authority} through proxy {proxy_host}:{proxy_port}.");
+    Ok(stream)
+}
+
+/// Returns the status code of an HTTP response from its status line.
+fn status(status_line: &str) -> Option<u16> {
+    let mut parts = status_line.split_whitespace();
+    match parts.next() {
+        Some(version) if version.starts_with("HTTP/") => parts.next()?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads the head of an HTTP response from `stream`, and returns its status line with any
+/// bytes read past the end of the head.
+async fn read_http_head<S: AsyncRead + Unpin + ?Sized>(
+    stream: &mut S,
+) -> Result<(String, Vec<u8>)> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        if buffer.len() > MAX_HTTP_HEAD_SIZE {
+            return Err(AmqpError::with_message(
+                "The HTTP response headers are too large.",
+            ));
+        }
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .map_err(azure_core::Error::from)?;
+        if read == 0 {
+            return Err(AmqpError::with_message(
+                "The connection was closed before the HTTP response was received.",
+            ));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buffer[..end])
+        .map_err(|_| AmqpError::with_message("The HTTP response headers are not valid UTF-8."))?;
+    let status_line = head.split("\r\n").next().unwrap_or_default().to_string();
+    Ok((status_line, buffer[end + 4..].to_vec()))
+}
+
+/// The client end of a WebSocket, read and written as a byte stream.
+///
+/// Each write is sent in a binary message, and the payloads of received binary messages are
+/// returned by reads. Pings are answered by the WebSocket, and a close message from the server
+/// ends the stream.
+pub(crate) struct WebSocketStream {
+    inner: tokio_tungstenite::WebSocketStream<MaybeTlsStream<TcpStream>>,
+    // Received payload bytes that have not been returned by a read yet.
+    payload: Bytes,
+}
+
+impl fmt::Debug for WebSocketStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketStream")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+fn io_error(error: WsError) -> io::Error {
+    match error {
+        WsError::Io(e) => e,
+        e => io::Error::other(e),
+    }
+}
+
+impl AsyncRead for WebSocketStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.payload.is_empty() {
+                let len = this.payload.len().min(buf.remaining());
+                buf.put_slice(&this.payload.split_to(len));
+                return Poll::Ready(Ok(()));
+            }
+            match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(Ok(Message::Binary(payload))) => this.payload = payload,
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                Some(Ok(Message::Text(_))) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "The server sent a text WebSocket message on an AMQP connection.",
+                    )))
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(WsError::ConnectionClosed)) | None => {
+                    debug!("WebSocket closed by the server.");
+                    return Poll::Ready(Ok(()));
+                }
+                Some(Err(e)) => return Poll::Ready(Err(io_error(e))),
+            }
+        }
+    }
+}
+
+impl AsyncWrite for WebSocketStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(Pin::new(&mut this.inner).poll_ready(cx)).map_err(io_error)?;
+        let len = buf.len().min(MAX_WRITE_SIZE);
+        Pin::new(&mut this.inner)
+            .start_send(Message::Binary(Bytes::copy_from_slice(&buf[..len])))
+            .map_err(io_error)?;
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner)
+            .poll_flush(cx)
+            .map_err(io_error)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match ready!(Pin::new(&mut self.get_mut().inner).poll_close(cx)) {
+            Ok(()) | Err(WsError::ConnectionClosed) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(Err(io_error(e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+    use tokio::{io::copy_bidirectional, net::TcpListener};
+    use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+
+    #[test]
+    fn status_from_status_line() {
+        assert_eq!(status("HTTP/1.1 200 Connection established"), Some(200));
+        assert_eq!(
+            status("HTTP/1.0 407 Proxy Authentication Required"),
+            Some(407)
+        );
+        assert_eq!(status("CONNECT host:443 HTTP/1.1"), None);
+    }
+
+    #[test]
+    fn socket_host_removes_ipv6_brackets() {
+        assert_eq!(socket_host("[::1]"), "::1");
+        assert_eq!(socket_host("127.0.0.1"), "127.0.0.1");
+        assert_eq!(
+            socket_host("contoso.servicebus.windows.net"),
+            "contoso.servicebus.windows.net"
+        );
+    }
+
+    /// Checks the upgrade request and accepts the AMQP subprotocol.
+    // The error type is fixed by tungstenite's server callback.
+    #[allow(clippy::result_large_err)]
+    fn select_subprotocol(
+        request: &Request,
+        mut response: Response,
+    ) -> std::result::Result<Response, ErrorResponse> {
+        assert_eq!(request.uri().path(), WEBSOCKET_PATH);
+        assert_eq!(
+            request.headers().get(SEC_WEBSOCKET_PROTOCOL).unwrap(),
+            WEBSOCKET_SUBPROTOCOL
+        );
+        response.headers_mut().insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            HeaderValue::from_static(WEBSOCKET_SUBPROTOCOL),
+        );
+        Ok(response)
+    }
+
+    /// Accepts one WebSocket and echoes every binary message it receives, after pinging the
+    /// client. Returns the payload of the client's pong.
+    async fn run_echo_broker(listener: TcpListener) -> Vec<u8> {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_hdr_async(stream, select_subprotocol)
+            .await
+            .unwrap();
+        ws.send(Message::Ping(Bytes::from_static(b"ping")))
+            .await
+            .unwrap();
+
+        let mut pong = Vec::new();
+        while let Some(message) = ws.next().await {
+            match message.unwrap() {
+                Message::Binary(payload) => ws.send(Message::Binary(payload)).await.unwrap(),
+                Message::Pong(payload) => pong = payload.to_vec(),
+                Message::Close(_) => break,
+                message => panic!("unexpected message {message:?}"),
+            }
+        }
+        pong
+    }
+
+    async fn echo(stream: &mut WebSocketStream, data: &[u8]) -> Vec<u8> {
+        stream.write_all(data).await.unwrap();
+        stream.flush().await.unwrap();
+        let mut received = vec![0u8; data.len()];
+        stream.read_exact(&mut received).await.unwrap();
+        received
+    }
+
+    #[tokio::test]
+    async fn websocket_echo() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let broker = tokio::spawn(run_echo_broker(listener));
+
+        let url = Url::parse(&format!("amqp://127.0.0.1:{port}")).unwrap();
+        let mut stream = connect(&url, None).await.unwrap();
+
+        let data: Vec<u8> = (0..3 * MAX_WRITE_SIZE + 7).map(|i| i as u8).collect();
+        assert_eq!(
+            echo(&mut stream, b"AMQP\x00\x01\x00\x00").await,
+            b"AMQP\x00\x01\x00\x00"
+        );
+        assert_eq!(echo(&mut stream, &data).await, data);
+
+        stream.shutdown().await.unwrap();
+        assert_eq!(broker.await.unwrap(), b"ping");
+    }
+
+    /// Reads the head of an HTTP request from `stream`.
+    async fn read_request(stream: &mut TcpStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    /// Accepts one `CONNECT` request, checks its credentials, and forwards the tunnel to
+    /// `target_port`.
+    async fn run_proxy(listener: TcpListener, target_port: u16, expected_authorization: String) {
+        let (mut client, _) = listener.accept().await.unwrap();
+        let request = read_request(&mut client).await;
+        assert!(
+            request.starts_with(&format!("CONNECT 127.0.0.1:{target_port} HTTP/1.1\r\n")),
+            "{request}"
+        );
+        let authorization = format!("Proxy-Authorization: {expected_authorization}\r\n");
+        if !request.contains(&authorization) {
+            client
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await
+                .unwrap();
+            return;
+        }
+        let mut target = TcpStream::connect(("127.0.0.1", target_port))
+            .await
+            .unwrap();
+        client
+            .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+            .await
+            .unwrap();
+        let _ = copy_bidirectional(&mut client, &mut target).await;
+    }
+
+    #[tokio::test]
+    async fn websocket_through_proxy() {
+        let broker_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let broker_port = broker_listener.local_addr().unwrap().port();
+        let broker = tokio::spawn(run_echo_broker(broker_listener));
+
+        let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = proxy_listener.local_addr().unwrap().port();
+        let authorization = format!("Basic {}", base64::encode("user:password"));
+        tokio::spawn(run_proxy(proxy_listener, broker_port, authorization));
+
+        let proxy = AmqpProxyOptions {
+            url: Url::parse(&format!("http://127.0.0.1:{proxy_port}")).unwrap(),
+            username: Some("user".to_string()),
+            password: Some("password".into()),
+        };
+        let url = Url::parse(&format!("amqp://127.0.0.1:{broker_port}")).unwrap();
+        let mut stream = connect(&url, Some(&proxy)).await.unwrap();
+
+        assert_eq!(echo(&mut stream, b"hello").await, b"hello");
+        stream.shutdown().await.unwrap();
+        broker.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn proxy_authentication_failure() {
+        let proxy_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = proxy_listener.local_addr().unwrap().port();
+        tokio::spawn(run_proxy(proxy_listener, 1, "Basic other".to_string()));
+
+        let proxy =
+            AmqpProxyOptions::new(Url::parse(&format!("http://127.0.0.1:{proxy_port}")).unwrap());
+        let url = Url::parse("amqp://127.0.0.1:1").unwrap();
+        let err = connect(&url, Some(&proxy)).await.unwrap_err();
+        assert!(err.to_string().contains("407"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn upgrade_with_invalid_accept_fails() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            read_request(&mut stream).await;
+            stream
+                .write_all(
+                    b"HTTP/1.1 101 Switching Protocols\r\n\
+                      Upgrade: websocket\r\n\
+                      Connection: Upgrade\r\n\
+                      Sec-WebSocket-Accept: invalid\r\n\r\n",
+                )
+                .await
+                .unwrap();
+        });
+
+        let url = Url::parse(&format!("amqp://127.0.0.1:{port}")).unwrap();
+        let err = connect(&url, None).await.unwrap_err();
+        assert!(err.to_string().contains("Sec-WebSocket-Accept"), "{err}");
+    }
+}
@@ -21,10 +21,13 @@ mod receiver;
 mod sender;
 mod session;
 mod simple_value;
+mod transaction;
 mod value;
 
 pub use cbs::{AmqpClaimsBasedSecurity, AmqpClaimsBasedSecurityApis};
-pub use connection::{AmqpConnection, AmqpConnectionApis, AmqpConnectionOptions};
+pub use connection::{
+    AmqpConnection, AmqpConnectionApis, AmqpConnectionOptions, AmqpProxyOptions, AmqpTransportType,
+};
 pub use error::*;
 pub use management::{AmqpManagement, AmqpManagementApis};
 pub use messaging::{AmqpDelivery, AmqpDeliveryApis, AmqpMessage, AmqpSource, AmqpTarget};
@@ -33,6 +36,7 @@ pub use sender::{AmqpSendOptions, AmqpSendOutcome, AmqpSender, AmqpSenderApis, A
 pub use session::{AmqpSession, AmqpSessionApis, AmqpSessionOptions};
 pub use simple_value::AmqpSimpleValue;
 use std::fmt::Debug;
+pub use transaction::{AmqpTransaction, AmqpTransactionApis};
 pub use value::{AmqpDescribed, AmqpList, AmqpOrderedMap, AmqpSymbol, AmqpTimestamp, AmqpValue};
 
 /// Builders for AMQP types.
@@ -11,10 +11,11 @@ use super::{
         AmqpDelivery, AmqpDeliveryApis, AmqpMessage, AmqpSource, AmqpTarget, DeliveryNumber,
         DeliveryTag,
     },
-    receiver::{AmqpReceiverApis, AmqpReceiverOptions, ReceiverCreditMode},
-    sender::{AmqpSendOptions, AmqpSendOutcome, AmqpSenderApis, AmqpSenderOptions},
+    receiver::{AmqpReceiver, AmqpReceiverApis, AmqpReceiverOptions, ReceiverCreditMode},
+    sender::{AmqpSendOptions, AmqpSendOutcome, AmqpSender, AmqpSenderApis, AmqpSenderOptions},
     session::{AmqpSession, AmqpSessionApis, AmqpSessionOptions},
     simple_value::AmqpSimpleValue,
+    transaction::AmqpTransactionApis,
     value::{AmqpOrderedMap, AmqpSymbol, AmqpValue},
 };
 use azure_core::{
@@ -43,6 +44,9 @@ pub(crate) struct NoopAmqpDelivery {}
 #[derive(Default)]
 pub(crate) struct NoopAmqpClaimsBasedSecurity {}
 
+#[derive(Default)]
+pub(crate) struct NoopAmqpTransaction {}
+
 impl NoopAmqpConnection {
     pub fn new() -> Self {
         Self {}
@@ -233,6 +237,60 @@ impl AmqpReceiverApis for NoopAmqpReceiver {
     }
 }
 
+impl NoopAmqpTransaction {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl AmqpTransactionApis for NoopAmqpTransaction {
+    async fn declare(&self, session: &AmqpSession, name: String) -> Result<()> {
+        unimplemented!();
+    }
+    fn transaction_id(&self) -> Result<Vec<u8>> {
+        unimplemented!();
+    }
+    async fn send_ref<M>(
+        &self,
+        sender: &AmqpSender,
+        message: M,
+        options: Option<AmqpSendOptions>,
+    ) -> Result<AmqpSendOutcome>
+    where
+        M: AsRef<AmqpMessage> + std::fmt::Debug + Send,
+    {
+        unimplemented!();
+    }
+    async fn accept_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        unimplemented!();
+    }
+    async fn reject_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        unimplemented!();
+    }
+    async fn release_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        unimplemented!();
+    }
+    async fn commit(self) -> Result<()> {
+        unimplemented!();
+    }
+    async fn rollback(self) -> Result<()> {
+        unimplemented!();
+    }
+}
+
 impl AmqpDeliveryApis for NoopAmqpDelivery {
     fn message(&self) -> &AmqpMessage {
         unimplemented!();
@@ -113,7 +113,7 @@ pub trait AmqpReceiverApis {
 /// Struct representing the AMQP receiver functionality.
 #[derive(Default)]
 pub struct AmqpReceiver {
-    implementation: ReceiverImplementation,
+    pub(crate) implementation: ReceiverImplementation,
 }
 
 #[async_trait::async_trait]
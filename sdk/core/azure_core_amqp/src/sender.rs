@@ -69,6 +69,16 @@ pub trait AmqpSenderApis {
     /// Get the maximum message size for the sender.
     async fn max_message_size(&self) -> Result<Option<u64>>;
 
+    /// Get the properties of the sender link.
+    ///
+    /// Once the sender is attached, this includes the properties the remote peer returned in its
+    /// Attach frame. They replace local properties with the same key.
+    ///
+    /// The default implementation reports no properties.
+    async fn properties(&self) -> Result<Option<AmqpOrderedMap<AmqpSymbol, AmqpValue>>> {
+        Ok(None)
+    }
+
     /// Send a message.
     ///
     /// # Arguments
@@ -176,7 +186,7 @@ unsafe impl Send for AmqpSendOutcome {}
 
 /// An AMQP message sender.
 pub struct AmqpSender {
-    implementation: SenderImplementation,
+    pub(crate) implementation: SenderImplementation,
 }
 
 #[async_trait::async_trait]
@@ -199,6 +209,10 @@ impl AmqpSenderApis for AmqpSender {
     async fn max_message_size(&self) -> Result<Option<u64>> {
         self.implementation.max_message_size().await
     }
+
+    async fn properties(&self) -> Result<Option<AmqpOrderedMap<AmqpSymbol, AmqpValue>>> {
+        self.implementation.properties().await
+    }
     async fn send<M>(&self, message: M, options: Option<AmqpSendOptions>) -> Result<AmqpSendOutcome>
     where
         M: Into<AmqpMessage> + std::fmt::Debug + Send,
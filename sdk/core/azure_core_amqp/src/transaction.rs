@@ -0,0 +1,158 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use crate::{
+    error::Result,
+    messaging::{AmqpDelivery, AmqpMessage},
+    receiver::AmqpReceiver,
+    sender::{AmqpSendOptions, AmqpSendOutcome, AmqpSender},
+    session::AmqpSession,
+};
+
+#[cfg(feature = "fe2o3_amqp")]
+type TransactionImplementation = super::fe2o3::transaction::Fe2o3AmqpTransaction;
+
+#[cfg(not(feature = "fe2o3_amqp"))]
+type TransactionImplementation = super::noop::NoopAmqpTransaction;
+
+/// A trait for AMQP transaction operations.
+///
+/// A transaction is declared on a transaction controller link. Messages sent and deliveries
+/// settled as part of the transaction take effect together when the transaction is committed,
+/// and are undone when it is rolled back.
+///
+/// See also: [AMQP Transactions](https://docs.oasis-open.org/amqp/core/v1.0/os/amqp-core-transactions-v1.0-os.html)
+#[async_trait::async_trait]
+pub trait AmqpTransactionApis {
+    /// Attach a transaction controller to a session and declare a transaction.
+    ///
+    /// The transaction can be used with any sender or receiver on the same connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The AMQP session to attach the controller link to.
+    /// * `name` - The name of the controller link.
+    async fn declare(&self, session: &AmqpSession, name: String) -> Result<()>;
+
+    /// Get the transaction identifier the transactional resource assigned on declare.
+    fn transaction_id(&self) -> Result<Vec<u8>>;
+
+    /// Send a message as part of the transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The attached sender to send the message on.
+    /// * `message` - The message to send.
+    /// * `options` - The options for sending the message.
+    ///
+    /// # Returns
+    ///
+    /// The outcome of the send operation.
+    async fn send_ref<M>(
+        &self,
+        sender: &AmqpSender,
+        message: M,
+        options: Option<AmqpSendOptions>,
+    ) -> Result<AmqpSendOutcome>
+    where
+        M: AsRef<AmqpMessage> + std::fmt::Debug + Send;
+
+    /// Accept a delivery as part of the transaction.
+    async fn accept_delivery(&self, receiver: &AmqpReceiver, delivery: &AmqpDelivery)
+        -> Result<()>;
+
+    /// Reject a delivery as part of the transaction.
+    async fn reject_delivery(&self, receiver: &AmqpReceiver, delivery: &AmqpDelivery)
+        -> Result<()>;
+
+    /// Release a delivery as part of the transaction.
+    async fn release_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()>;
+
+    /// Commit the transaction and detach the controller link.
+    async fn commit(self) -> Result<()>;
+
+    /// Roll back the transaction and detach the controller link.
+    async fn rollback(self) -> Result<()>;
+}
+
+/// An AMQP transaction.
+#[derive(Default)]
+pub struct AmqpTransaction {
+    implementation: TransactionImplementation,
+}
+
+#[async_trait::async_trait]
+impl AmqpTransactionApis for AmqpTransaction {
+    async fn declare(&self, session: &AmqpSession, name: String) -> Result<()> {
+        self.implementation.declare(session, name).await
+    }
+
+    fn transaction_id(&self) -> Result<Vec<u8>> {
+        self.implementation.transaction_id()
+    }
+
+    async fn send_ref<M>(
+        &self,
+        sender: &AmqpSender,
+        message: M,
+        options: Option<AmqpSendOptions>,
+    ) -> Result<AmqpSendOutcome>
+    where
+        M: AsRef<AmqpMessage> + std::fmt::Debug + Send,
+    {
+        self.implementation.send_ref(sender, message, options).await
+    }
+
+    async fn accept_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        self.implementation
+            .accept_delivery(receiver, delivery)
+            .await
+    }
+
+    async fn reject_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        self.implementation
+            .reject_delivery(receiver, delivery)
+            .await
+    }
+
+    async fn release_delivery(
+        &self,
+        receiver: &AmqpReceiver,
+        delivery: &AmqpDelivery,
+    ) -> Result<()> {
+        self.implementation
+            .release_delivery(receiver, delivery)
+            .await
+    }
+
+    async fn commit(self) -> Result<()> {
+        self.implementation.commit().await
+    }
+
+    async fn rollback(self) -> Result<()> {
+        self.implementation.rollback().await
+    }
+}
+
+impl AmqpTransaction {
+    /// Construct a new AMQP transaction.
+    ///
+    /// The transaction must be declared before it can be used.
+    pub fn new() -> Self {
+        Self {
+            implementation: TransactionImplementation::new(),
+        }
+    }
+}
@@ -0,0 +1,765 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! The listener, connection, session and link plumbing of the test broker.
+
+use crate::{
+    eventhubs::{self, EventHub, IdempotentProducer, IDEMPOTENT_PRODUCER_CAPABILITY},
+    management::{self, Node, ReplyNodes},
+    servicebus::{self, Queue},
+};
+use fe2o3_amqp::{
+    acceptor::{
+        ConnectionAcceptor, LinkAcceptor, LinkEndpoint, ListenerSessionHandle,
+        SaslAnonymousMechanism, SessionAcceptor,
+    },
+    link::{Receiver, Sender},
+    types::{
+        definitions::{self, AmqpError, ErrorCondition, MessageFormat, Role, SenderSettleMode},
+        messaging::{message::__private::Deserializable, Body, Message, Source, Target},
+        performatives::Attach,
+        primitives::{Symbol, Timestamp, Value},
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tracing::{debug, warn};
+
+/// The message type stored and forwarded by the broker.
+pub(crate) type BrokerMessage = Message<Body<Value>>;
+
+const BATCH_MESSAGE_FORMAT: MessageFormat = 0x80013700;
+const CONTAINER_ID: &str = "azure-core-amqp-test-broker";
+const DEFAULT_LOCK_DURATION: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_DELIVERY_COUNT: u32 = 10;
+/// The largest message a link accepts, matching the standard tier of Event Hubs.
+const MAX_MESSAGE_SIZE: u64 = 1024 * 1024;
+
+/// An in-process AMQP broker that simulates Event Hubs and Service Bus entities.
+///
+/// The broker stops listening when it is dropped.
+#[derive(Debug)]
+pub struct TestBroker {
+    endpoint: String,
+    state: Arc<BrokerState>,
+    listener: JoinHandle<()>,
+}
+
+impl TestBroker {
+    /// Creates a builder used to configure the entities of a broker.
+    pub fn builder() -> TestBrokerBuilder {
+        TestBrokerBuilder::default()
+    }
+
+    /// Gets the endpoint clients connect to, such as `amqp://127.0.0.1:49152`.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Gets the audiences of every token put on the `$cbs` node, in the order they were put.
+    pub fn authorized_audiences(&self) -> Vec<String> {
+        self.state
+            .authorized_audiences
+            .lock()
+            .expect("authorized audiences lock poisoned")
+            .clone()
+    }
+
+    /// Gets the number of events stored in a partition of an event hub.
+    ///
+    /// Returns `None` if the event hub or partition does not exist.
+    pub fn event_count(&self, event_hub: &str, partition_id: &str) -> Option<usize> {
+        let event_hub = self.state.event_hub(event_hub)?;
+        let partition = event_hub.partition(partition_id.parse().ok()?)?;
+        Some(partition.len())
+    }
+
+    /// Gets the number of messages in a queue that have not been completed or dead-lettered.
+    ///
+    /// Returns `None` if the queue does not exist.
+    pub fn active_message_count(&self, queue: &str) -> Option<usize> {
+        Some(self.state.queue(queue)?.active_message_count())
+    }
+
+    /// Gets the number of messages in the dead-letter sub-queue of a queue.
+    ///
+    /// Returns `None` if the queue does not exist.
+    pub fn dead_letter_message_count(&self, queue: &str) -> Option<usize> {
+        Some(self.state.queue(queue)?.dead_letter_message_count())
+    }
+}
+
+impl Drop for TestBroker {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+/// Builds a [`TestBroker`].
+#[derive(Debug, Clone)]
+pub struct TestBrokerBuilder {
+    event_hubs: Vec<(String, usize)>,
+    queues: Vec<String>,
+    lock_duration: Duration,
+    max_delivery_count: u32,
+}
+
+impl Default for TestBrokerBuilder {
+    fn default() -> Self {
+        Self {
+            event_hubs: Vec::new(),
+            queues: Vec::new(),
+            lock_duration: DEFAULT_LOCK_DURATION,
+            max_delivery_count: DEFAULT_MAX_DELIVERY_COUNT,
+        }
+    }
+}
+
+impl TestBrokerBuilder {
+    /// Adds an event hub with partitions numbered from `0` to `partition_count - 1`.
+    pub fn with_event_hub(mut self, name: impl Into<String>, partition_count: usize) -> Self {
+        self.event_hubs.push((name.into(), partition_count));
+        self
+    }
+
+    /// Adds a Service Bus queue.
+    pub fn with_queue(mut self, name: impl Into<String>) -> Self {
+        self.queues.push(name.into());
+        self
+    }
+
+    /// Sets how long messages received in peek-lock mode stay locked. The default is 30 seconds.
+    pub fn with_lock_duration(mut self, lock_duration: Duration) -> Self {
+        self.lock_duration = lock_duration;
+        self
+    }
+
+    /// Sets how many times a message is delivered before it is dead-lettered. The default is 10.
+    pub fn with_max_delivery_count(mut self, max_delivery_count: u32) -> Self {
+        self.max_delivery_count = max_delivery_count;
+        self
+    }
+
+    /// Starts listening on an ephemeral port on the loopback interface.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn start(self) -> std::io::Result<TestBroker> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let endpoint = format!("amqp://{}", listener.local_addr()?);
+
+        let state = Arc::new(BrokerState {
+            event_hubs: self
+                .event_hubs
+                .into_iter()
+                .map(|(name, partition_count)| {
+                    (
+                        name.to_lowercase(),
+                        Arc::new(EventHub::new(name, partition_count)),
+                    )
+                })
+                .collect(),
+            queues: self
+                .queues
+                .into_iter()
+                .map(|name| {
+                    (
+                        name.to_lowercase(),
+                        Arc::new(Queue::new(
+                            name,
+                            self.lock_duration,
+                            self.max_delivery_count,
+                        )),
+                    )
+                })
+                .collect(),
+            authorized_audiences: Mutex::new(Vec::new()),
+        });
+
+        debug!("Test broker listening on {}", endpoint);
+        let listener = tokio::spawn(listen(listener, state.clone()));
+        Ok(TestBroker {
+            endpoint,
+            state,
+            listener,
+        })
+    }
+}
+
+/// The entities of a broker, shared by all of its connections.
+#[derive(Debug)]
+pub(crate) struct BrokerState {
+    event_hubs: HashMap<String, Arc<EventHub>>,
+    queues: HashMap<String, Arc<Queue>>,
+    authorized_audiences: Mutex<Vec<String>>,
+}
+
+impl BrokerState {
+    /// Gets an event hub by name, ignoring case as the service does.
+    pub(crate) fn event_hub(&self, name: &str) -> Option<&Arc<EventHub>> {
+        self.event_hubs.get(&name.to_lowercase())
+    }
+
+    /// Gets a queue by name, ignoring case as the service does.
+    pub(crate) fn queue(&self, name: &str) -> Option<&Arc<Queue>> {
+        self.queues.get(&name.to_lowercase())
+    }
+
+    /// Gets the queue management requests apply to when no entity is known from the session.
+    pub(crate) fn single_queue(&self) -> Option<&Arc<Queue>> {
+        match self.queues.len() {
+            1 => self.queues.values().next(),
+            _ => None,
+        }
+    }
+
+    /// Records the audience of a token put on the `$cbs` node.
+    pub(crate) fn authorize(&self, audience: String) {
+        self.authorized_audiences
+            .lock()
+            .expect("authorized audiences lock poisoned")
+            .push(audience);
+    }
+}
+
+/// Gets the messages in a delivery, unpacking batches.
+///
+/// A batch is a message with the batch message format whose data sections each hold an encoded
+/// message.
+pub(crate) fn unpack_batch(
+    message_format: Option<MessageFormat>,
+    message: BrokerMessage,
+) -> Result<Vec<BrokerMessage>, String> {
+    if message_format != Some(BATCH_MESSAGE_FORMAT) {
+        return Ok(vec![message]);
+    }
+    let Body::Data(batch) = message.body else {
+        return Err("A batch must have data sections".to_string());
+    };
+    batch
+        .into_iter()
+        .map(|data| {
+            serde_amqp::from_slice::<Deserializable<BrokerMessage>>(&data.0)
+                .map(|message| message.0)
+                .map_err(|e| format!("Failed to decode a message in a batch: {}", e))
+        })
+        .collect()
+}
+
+/// Gets a timestamp for a point in time.
+pub(crate) fn timestamp(time: SystemTime) -> Timestamp {
+    let milliseconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default();
+    Timestamp::from_milliseconds(milliseconds)
+}
+
+async fn listen(listener: TcpListener, state: Arc<BrokerState>) {
+    let acceptor = Arc::new(
+        ConnectionAcceptor::builder()
+            .container_id(CONTAINER_ID)
+            .sasl_acceptor(SaslAnonymousMechanism::new())
+            .build(),
+    );
+    loop {
+        match listener.accept().await {
+            Ok((stream, address)) => {
+                debug!("Accepted TCP connection from {}", address);
+                tokio::spawn(serve_connection(acceptor.clone(), stream, state.clone()));
+            }
+            Err(e) => {
+                warn!("Failed to accept TCP connection: {}", e);
+            }
+        }
+    }
+}
+
+async fn serve_connection(
+    acceptor: Arc<ConnectionAcceptor<(), SaslAnonymousMechanism>>,
+    stream: TcpStream,
+    state: Arc<BrokerState>,
+) {
+    let mut connection = match acceptor.accept(stream).await {
+        Ok(connection) => connection,
+        Err(e) => {
+            warn!("Failed to open AMQP connection: {}", e);
+            return;
+        }
+    };
+
+    // Management and CBS replies are routed by the reply-to address of the request, which is
+    // the target of a receiver the client attached on this connection.
+    let reply_nodes = ReplyNodes::default();
+    while let Some(incoming) = connection.next_incoming_session().await {
+        match SessionAcceptor::new()
+            .accept_incoming_session(incoming, &mut connection)
+            .await
+        {
+            Ok(session) => {
+                tokio::spawn(serve_session(session, state.clone(), reply_nodes.clone()));
+            }
+            Err(e) => warn!("Failed to begin session: {}", e),
+        }
+    }
+    debug!("AMQP connection closed");
+}
+
+/// The entity a session's links refer to.
+///
+/// Service Bus management requests are sent to the bare `$management` node, so the entity they
+/// apply to is taken from the other links on the same session.
+pub(crate) type SessionEntity = Arc<Mutex<Option<Arc<Queue>>>>;
+
+async fn serve_session(
+    mut session: ListenerSessionHandle,
+    state: Arc<BrokerState>,
+    reply_nodes: ReplyNodes,
+) {
+    let session_entity = SessionEntity::default();
+    while let Some(mut attach) = session.next_incoming_attach().await {
+        let route = Route::resolve(&state, &attach)
+            .map_err(|description| {
+                definitions::Error::new(AmqpError::NotFound, Some(description), None)
+            })
+            .and_then(|route| route.attach_producer(&attach));
+        if route.is_err() {
+            // Answering with a null terminus tells the client the link was refused; the error is
+            // sent in the detach that follows.
+            match attach.role {
+                Role::Sender => attach.target = None,
+                Role::Receiver => attach.source = None,
+            }
+        }
+        // Service Bus receivers in receive-and-delete mode ask for deliveries to be settled
+        // before they are sent.
+        let receive_and_delete = attach.snd_settle_mode == SenderSettleMode::Settled;
+        // Clients size their batches by the maximum message size of the link. Idempotent
+        // publishers also read the state of their producer group from the properties the broker
+        // answers the attach with.
+        let acceptor = LinkAcceptor::builder().max_message_size(MAX_MESSAGE_SIZE);
+        let acceptor = match &route {
+            Ok(Route::EventHub(_, _, Some(producer))) => acceptor
+                .add_offered_capabilities(IDEMPOTENT_PRODUCER_CAPABILITY)
+                .properties(producer.properties())
+                .build(),
+            _ => acceptor.build(),
+        };
+        let link = match acceptor.accept_incoming_attach(attach, &mut session).await {
+            Ok(link) => link,
+            Err(e) => {
+                warn!("Failed to accept link: {}", e);
+                continue;
+            }
+        };
+
+        match (route, link) {
+            (Ok(Route::Node(node)), LinkEndpoint::Receiver(receiver)) => {
+                tokio::spawn(management::serve_requests(
+                    node,
+                    receiver,
+                    state.clone(),
+                    session_entity.clone(),
+                    reply_nodes.clone(),
+                ));
+            }
+            (Ok(Route::Node(_)), LinkEndpoint::Sender(sender)) => {
+                let Some(address) = target_address(&sender) else {
+                    close_sender(
+                        sender,
+                        AmqpError::InvalidField,
+                        "A reply address is required",
+                    )
+                    .await;
+                    continue;
+                };
+                let replies = reply_nodes.register(address);
+                tokio::spawn(management::serve_replies(sender, replies));
+            }
+            (
+                Ok(Route::EventHub(event_hub, partition, producer)),
+                LinkEndpoint::Receiver(receiver),
+            ) => {
+                tokio::spawn(eventhubs::serve_publisher(
+                    event_hub, partition, producer, receiver,
+                ));
+            }
+            (Ok(Route::Partition(event_hub, partition)), LinkEndpoint::Sender(sender)) => {
+                tokio::spawn(eventhubs::serve_consumer(event_hub, partition, sender));
+            }
+            (Ok(Route::Queue(queue)), LinkEndpoint::Receiver(receiver)) => {
+                session_entity
+                    .lock()
+                    .expect("session entity lock poisoned")
+                    .replace(queue.clone());
+                tokio::spawn(servicebus::serve_sender(queue, receiver));
+            }
+            (Ok(Route::Queue(queue)), LinkEndpoint::Sender(sender)) => {
+                session_entity
+                    .lock()
+                    .expect("session entity lock poisoned")
+                    .replace(queue.clone());
+                tokio::spawn(servicebus::serve_receiver(
+                    queue,
+                    false,
+                    receive_and_delete,
+                    sender,
+                ));
+            }
+            (Ok(Route::DeadLetterQueue(queue)), LinkEndpoint::Sender(sender)) => {
+                tokio::spawn(servicebus::serve_receiver(
+                    queue,
+                    true,
+                    receive_and_delete,
+                    sender,
+                ));
+            }
+            (Ok(_), LinkEndpoint::Sender(sender)) => {
+                close_sender(
+                    sender,
+                    AmqpError::NotAllowed,
+                    "Cannot receive from this entity",
+                )
+                .await;
+            }
+            (Ok(_), LinkEndpoint::Receiver(receiver)) => {
+                close_receiver(
+                    receiver,
+                    AmqpError::NotAllowed,
+                    "Cannot send to this entity",
+                )
+                .await;
+            }
+            (Err(error), LinkEndpoint::Sender(sender)) => {
+                close_sender(
+                    sender,
+                    error.condition,
+                    error.description.unwrap_or_default(),
+                )
+                .await;
+            }
+            (Err(error), LinkEndpoint::Receiver(receiver)) => {
+                close_receiver(
+                    receiver,
+                    error.condition,
+                    error.description.unwrap_or_default(),
+                )
+                .await;
+            }
+        }
+    }
+    debug!("AMQP session ended");
+}
+
+/// The broker node or entity a link is attached to.
+enum Route {
+    /// The `$cbs` or `$management` node.
+    Node(Node),
+
+    /// An event hub, or one of its partitions, that events are published to, with the idempotent
+    /// publisher attached to the partition, if any.
+    EventHub(Arc<EventHub>, Option<usize>, Option<IdempotentProducer>),
+
+    /// A partition of an event hub that events are consumed from, with the starting position.
+    Partition(Arc<EventHub>, eventhubs::PartitionReader),
+
+    /// A queue.
+    Queue(Arc<Queue>),
+
+    /// The dead-letter sub-queue of a queue.
+    DeadLetterQueue(Arc<Queue>),
+}
+
+impl Route {
+    fn resolve(state: &BrokerState, attach: &Attach) -> Result<Route, String> {
+        // The client's role is in the attach: a client sender names the entity in the target, and
+        // a client receiver names it in the source.
+        let address = match attach.role {
+            Role::Sender => attach
+                .target
+                .as_ref()
+                .and_then(|target| Target::try_from(target.as_ref().clone()).ok())
+                .and_then(|target| target.address),
+            Role::Receiver => attach
+                .source
+                .as_ref()
+                .and_then(|source| source.address.clone()),
+        }
+        .ok_or_else(|| "The link does not have an address".to_string())?;
+
+        let path = entity_path(&address);
+        let segments: Vec<&str> = path.split('/').collect();
+        match segments.as_slice() {
+            ["$cbs"] => return Ok(Route::Node(Node::Cbs)),
+            ["$management"] | [_, "$management"] => return Ok(Route::Node(Node::Management)),
+            _ => {}
+        }
+
+        if let Some(event_hub) = state.event_hub(segments[0]) {
+            let partition = |id: &str| {
+                id.parse::<usize>()
+                    .ok()
+                    .filter(|id| event_hub.partition(*id).is_some())
+                    .ok_or_else(|| format!("The partition '{}' does not exist in '{}'", id, path))
+            };
+            return match (attach.role.clone(), &segments[1..]) {
+                (Role::Sender, []) => Ok(Route::EventHub(event_hub.clone(), None, None)),
+                (Role::Sender, [partitions, id])
+                    if partitions.eq_ignore_ascii_case("Partitions") =>
+                {
+                    Ok(Route::EventHub(
+                        event_hub.clone(),
+                        Some(partition(id)?),
+                        None,
+                    ))
+                }
+                (Role::Receiver, [consumer_groups, _, partitions, id])
+                    if consumer_groups.eq_ignore_ascii_case("ConsumerGroups")
+                        && partitions.eq_ignore_ascii_case("Partitions") =>
+                {
+                    let reader = eventhubs::PartitionReader::new(
+                        partition(id)?,
+                        selector_filter(attach.source.as_deref()),
+                    );
+                    Ok(Route::Partition(event_hub.clone(), reader))
+                }
+                _ => Err(format!("The entity '{}' could not be found", path)),
+            };
+        }
+
+        if let Some(queue) = state.queue(segments[0]) {
+            return match &segments[1..] {
+                [] => Ok(Route::Queue(queue.clone())),
+                [sub_queue] if sub_queue.eq_ignore_ascii_case("$DeadLetterQueue") => {
+                    Ok(Route::DeadLetterQueue(queue.clone()))
+                }
+                _ => Err(format!("The entity '{}' could not be found", path)),
+            };
+        }
+
+        Err(format!("The entity '{}' could not be found", path))
+    }
+
+    /// Registers an idempotent publisher when the attach desires the capability.
+    ///
+    /// Idempotent publishing is only supported on a partition.
+    fn attach_producer(self, attach: &Attach) -> Result<Route, definitions::Error> {
+        let idempotent = attach
+            .desired_capabilities
+            .as_ref()
+            .is_some_and(|capabilities| {
+                capabilities
+                    .0
+                    .contains(&Symbol::from(IDEMPOTENT_PRODUCER_CAPABILITY))
+            });
+        match self {
+            Route::EventHub(event_hub, Some(partition), _) if idempotent => {
+                let producer = event_hub.attach_producer(partition, attach.properties.as_ref())?;
+                Ok(Route::EventHub(event_hub, Some(partition), Some(producer)))
+            }
+            Route::EventHub(_, None, _) if idempotent => Err(definitions::Error::new(
+                AmqpError::NotAllowed,
+                Some("Idempotent publishing requires a partition".to_string()),
+                None,
+            )),
+            route => Ok(route),
+        }
+    }
+}
+
+/// Gets the entity path from a link address.
+///
+/// Event Hubs clients address links with URLs such as `amqps://namespace/eventhub/Partitions/0`,
+/// while Service Bus clients use bare paths such as `queue/$DeadLetterQueue`.
+fn entity_path(address: &str) -> &str {
+    let path = match address.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or("", |(_, path)| path),
+        None => address,
+    };
+    path.trim_matches('/')
+}
+
+/// Gets the expression of the selector filter on a source, if any.
+fn selector_filter(source: Option<&Source>) -> Option<String> {
+    source?
+        .filter
+        .as_ref()?
+        .values()
+        .find_map(|filter| match filter {
+            Value::Described(described) => match &described.value {
+                Value::String(expression) => Some(expression.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+}
+
+fn target_address(sender: &Sender) -> Option<String> {
+    sender.target().as_ref()?.address.clone()
+}
+
+async fn close_sender(
+    sender: Sender,
+    condition: impl Into<ErrorCondition>,
+    description: impl Into<String>,
+) {
+    let error = definitions::Error::new(condition, Some(description.into()), None);
+    if let Err(e) = sender.close_with_error(error).await {
+        debug!("Failed to close rejected sender: {}", e);
+    }
+}
+
+async fn close_receiver(
+    receiver: Receiver,
+    condition: impl Into<ErrorCondition>,
+    description: impl Into<String>,
+) {
+    let error = definitions::Error::new(condition, Some(description.into()), None);
+    if let Err(e) = receiver.close_with_error(error).await {
+        debug!("Failed to close rejected receiver: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fe2o3_amqp::{
+        connection::ConnectionHandle,
+        sasl_profile::SaslProfile,
+        session::SessionHandle,
+        types::{
+            messaging::{annotations::OwnedKey, AmqpValue},
+            primitives::Symbol,
+        },
+        Connection, Session,
+    };
+    use fe2o3_amqp_management::{client::MgmtClient, operations::ReadRequest};
+
+    async fn connect(broker: &TestBroker) -> (ConnectionHandle<()>, SessionHandle<()>) {
+        let mut connection = Connection::builder()
+            .container_id("test-client")
+            .sasl_profile(SaslProfile::Anonymous)
+            .open(broker.endpoint())
+            .await
+            .unwrap();
+        let session = Session::begin(&mut connection).await.unwrap();
+        (connection, session)
+    }
+
+    #[tokio::test]
+    async fn queue_round_trip() {
+        let broker = TestBroker::builder()
+            .with_queue("Queue")
+            .start()
+            .await
+            .unwrap();
+        let (_connection, mut session) = connect(&broker).await;
+
+        let mut sender = Sender::attach(&mut session, "sender", "queue")
+            .await
+            .unwrap();
+        sender
+            .send("hello")
+            .await
+            .unwrap()
+            .accepted_or_else(|o| o)
+            .unwrap();
+        assert_eq!(broker.active_message_count("queue"), Some(1));
+
+        let mut receiver = Receiver::attach(&mut session, "receiver", "amqps://namespace/queue")
+            .await
+            .unwrap();
+        let delivery = receiver.recv::<AmqpValue<String>>().await.unwrap();
+        assert_eq!(delivery.body().0, "hello");
+        let annotations = delivery.message().message_annotations.as_ref().unwrap();
+        assert!(annotations
+            .keys()
+            .any(|key| key == &OwnedKey::Symbol(Symbol::from("x-opt-lock-token"))));
+        receiver.accept(&delivery).await.unwrap();
+
+        // Settlement is applied when the broker sees the disposition.
+        for _ in 0..100 {
+            if broker.active_message_count("queue") == Some(0) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(broker.active_message_count("queue"), Some(0));
+    }
+
+    #[tokio::test]
+    async fn close_with_open_links() {
+        let broker = TestBroker::builder()
+            .with_queue("queue")
+            .start()
+            .await
+            .unwrap();
+        let (mut connection, mut session) = connect(&broker).await;
+        let _receiver = Receiver::attach(&mut session, "receiver", "queue")
+            .await
+            .unwrap();
+        connection.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn unknown_entities_are_not_found() {
+        let broker = TestBroker::builder().start().await.unwrap();
+        let (_connection, mut session) = connect(&broker).await;
+
+        assert!(Receiver::attach(&mut session, "receiver", "missing")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn event_hub_properties() {
+        let broker = TestBroker::builder()
+            .with_event_hub("eventhub", 2)
+            .start()
+            .await
+            .unwrap();
+        let (_connection, mut session) = connect(&broker).await;
+
+        let mut management = MgmtClient::builder()
+            .client_node_addr("test-management")
+            .attach(&mut session)
+            .await
+            .unwrap();
+        let response = management
+            .call(ReadRequest::name(
+                "eventhub",
+                eventhubs::EVENT_HUB_ENTITY_TYPE,
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.entity_attributes.get("partition_count"),
+            Some(&Value::Int(2))
+        );
+        assert_eq!(
+            response.entity_attributes.get("name"),
+            Some(&Value::String("eventhub".to_string()))
+        );
+    }
+
+    #[test]
+    fn entity_path_from_address() {
+        assert_eq!(
+            entity_path("amqps://namespace.servicebus.windows.net/eventhub/Partitions/0"),
+            "eventhub/Partitions/0"
+        );
+        assert_eq!(entity_path("amqps://namespace:5671"), "");
+        assert_eq!(
+            entity_path("queue/$DeadLetterQueue"),
+            "queue/$DeadLetterQueue"
+        );
+        assert_eq!(entity_path("/$cbs/"), "$cbs");
+    }
+}
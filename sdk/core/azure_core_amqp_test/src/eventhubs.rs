@@ -0,0 +1,817 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Event hubs and their partitions.
+
+use crate::{
+    broker::{timestamp, unpack_batch, BrokerMessage, BrokerState},
+    management::{string_property, Response},
+};
+use fe2o3_amqp::{
+    link::{delivery::Sendable, Receiver, Sender},
+    types::{
+        definitions::{self, AmqpError, ErrorCondition, Fields},
+        messaging::{annotations::OwnedKey, Body, MessageAnnotations},
+        primitives::{Array, OrderedMap, Symbol, Value},
+    },
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Notify;
+use tracing::debug;
+
+pub(crate) const EVENT_HUB_ENTITY_TYPE: &str = "com.microsoft:eventhub";
+pub(crate) const PARTITION_ENTITY_TYPE: &str = "com.microsoft:partition";
+
+const SEQUENCE_NUMBER_ANNOTATION: &str = "x-opt-sequence-number";
+const OFFSET_ANNOTATION: &str = "x-opt-offset";
+const ENQUEUED_TIME_ANNOTATION: &str = "x-opt-enqueued-time";
+const PARTITION_KEY_ANNOTATION: &str = "x-opt-partition-key";
+
+/// The capability a publisher desires on attach to publish idempotently to a partition.
+pub(crate) const IDEMPOTENT_PRODUCER_CAPABILITY: &str = "com.microsoft:idempotent-producer";
+
+// Idempotent publishers exchange their state in these link properties on attach, and stamp each
+// event with the sequence number under the same key.
+const PRODUCER_ID: &str = "com.microsoft:producer-id";
+const PRODUCER_EPOCH: &str = "com.microsoft:producer-epoch";
+const PRODUCER_SEQUENCE_NUMBER: &str = "com.microsoft:producer-sequence-number";
+
+const OUT_OF_ORDER_SEQUENCE: &str = "com.microsoft:out-of-order-sequence";
+const PRODUCER_EPOCH_STOLEN: &str = "com.microsoft:producer-epoch-stolen";
+
+/// An event hub.
+#[derive(Debug)]
+pub(crate) struct EventHub {
+    name: String,
+    created_at: SystemTime,
+    partitions: Vec<Partition>,
+    next_partition: AtomicUsize,
+}
+
+impl EventHub {
+    pub(crate) fn new(name: String, partition_count: usize) -> Self {
+        Self {
+            name,
+            created_at: SystemTime::now(),
+            partitions: (0..partition_count).map(|_| Partition::default()).collect(),
+            next_partition: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn partition(&self, id: usize) -> Option<&Partition> {
+        self.partitions.get(id)
+    }
+
+    /// Registers an idempotent publisher on a partition from the properties of its attach.
+    pub(crate) fn attach_producer(
+        &self,
+        partition: usize,
+        properties: Option<&Fields>,
+    ) -> Result<IdempotentProducer, definitions::Error> {
+        self.partitions[partition].attach_producer(properties)
+    }
+
+    /// Chooses the partition for events sent to the event hub rather than to a partition.
+    ///
+    /// Events with the same partition key always go to the same partition; other events are
+    /// distributed round-robin.
+    fn route(&self, partition_key: Option<&str>) -> usize {
+        match partition_key {
+            Some(partition_key) => {
+                let mut hasher = DefaultHasher::new();
+                partition_key.hash(&mut hasher);
+                (hasher.finish() % self.partitions.len() as u64) as usize
+            }
+            None => self.next_partition.fetch_add(1, Ordering::Relaxed) % self.partitions.len(),
+        }
+    }
+
+    fn properties(&self) -> Response {
+        let partition_ids = (0..self.partitions.len())
+            .map(|id| Value::String(id.to_string()))
+            .collect();
+        let mut properties = OrderedMap::new();
+        properties.insert(Value::from("name"), Value::from(self.name.clone()));
+        properties.insert(
+            Value::from("partition_count"),
+            Value::Int(self.partitions.len() as i32),
+        );
+        properties.insert(
+            Value::from("partition_ids"),
+            Value::Array(Array(partition_ids)),
+        );
+        properties.insert(
+            Value::from("created_at"),
+            Value::Timestamp(timestamp(self.created_at)),
+        );
+        Response::ok(Value::Map(properties))
+    }
+}
+
+/// A partition of an event hub.
+///
+/// Sequence numbers start at zero, and the offset of an event is its sequence number.
+#[derive(Debug, Default)]
+pub(crate) struct Partition {
+    events: Mutex<Vec<StoredEvent>>,
+    appended: Notify,
+    producer_groups: Mutex<HashMap<i64, ProducerGroup>>,
+}
+
+/// The state of the idempotent publishers of a partition that share a producer group id.
+#[derive(Debug, Clone, Copy)]
+struct ProducerGroup {
+    owner_level: i16,
+    last_sequence_number: i32,
+}
+
+/// An idempotent publisher attached to a partition.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IdempotentProducer {
+    producer_group_id: i64,
+    owner_level: i16,
+    last_sequence_number: i32,
+}
+
+impl IdempotentProducer {
+    /// Gets the link properties the broker answers the attach with.
+    pub(crate) fn properties(&self) -> Fields {
+        let mut properties = Fields::new();
+        properties.insert(
+            Symbol::from(PRODUCER_ID),
+            Value::Long(self.producer_group_id),
+        );
+        properties.insert(Symbol::from(PRODUCER_EPOCH), Value::Short(self.owner_level));
+        properties.insert(
+            Symbol::from(PRODUCER_SEQUENCE_NUMBER),
+            Value::Int(self.last_sequence_number),
+        );
+        properties
+    }
+}
+
+/// Gets the sequence number that follows another, wrapping to zero as the service does.
+fn next_sequence_number(sequence_number: i32) -> i32 {
+    sequence_number.checked_add(1).unwrap_or(0)
+}
+
+#[derive(Debug, Clone)]
+struct StoredEvent {
+    message: BrokerMessage,
+    enqueued_time: SystemTime,
+}
+
+impl Partition {
+    pub(crate) fn len(&self) -> usize {
+        self.events().len()
+    }
+
+    fn append(&self, mut message: BrokerMessage) {
+        let mut events = self.events();
+        let sequence_number = events.len() as i64;
+        let enqueued_time = SystemTime::now();
+        let annotations = message
+            .message_annotations
+            .get_or_insert_with(|| MessageAnnotations(OrderedMap::new()));
+        annotations.insert(
+            OwnedKey::from(SEQUENCE_NUMBER_ANNOTATION),
+            Value::Long(sequence_number),
+        );
+        annotations.insert(
+            OwnedKey::from(OFFSET_ANNOTATION),
+            Value::String(sequence_number.to_string()),
+        );
+        annotations.insert(
+            OwnedKey::from(ENQUEUED_TIME_ANNOTATION),
+            Value::Timestamp(timestamp(enqueued_time)),
+        );
+        events.push(StoredEvent {
+            message,
+            enqueued_time,
+        });
+        drop(events);
+        self.appended.notify_waiters();
+    }
+
+    /// Registers an idempotent publisher.
+    ///
+    /// A publisher that doesn't name a producer group is assigned a new one. The owner level and
+    /// last sequence number of the group are taken from the attach when present; a publisher with
+    /// a lower owner level than the group's is refused.
+    fn attach_producer(
+        &self,
+        properties: Option<&Fields>,
+    ) -> Result<IdempotentProducer, definitions::Error> {
+        let property = |name: &str| properties.and_then(|p| p.get(&Symbol::from(name)));
+        let invalid = |name: &str| {
+            definitions::Error::new(
+                AmqpError::InvalidField,
+                Some(format!("The '{}' link property has the wrong type", name)),
+                None,
+            )
+        };
+        let producer_group_id = match property(PRODUCER_ID) {
+            None => None,
+            Some(Value::Long(id)) => Some(*id),
+            Some(_) => return Err(invalid(PRODUCER_ID)),
+        };
+        let owner_level = match property(PRODUCER_EPOCH) {
+            None => None,
+            Some(Value::Short(owner_level)) => Some(*owner_level),
+            Some(_) => return Err(invalid(PRODUCER_EPOCH)),
+        };
+        let sequence_number = match property(PRODUCER_SEQUENCE_NUMBER) {
+            None => None,
+            Some(Value::Int(sequence_number)) => Some(*sequence_number),
+            Some(_) => return Err(invalid(PRODUCER_SEQUENCE_NUMBER)),
+        };
+
+        let mut groups = self.producer_groups();
+        let producer_group_id = producer_group_id.unwrap_or_else(|| {
+            (1..)
+                .find(|id| !groups.contains_key(id))
+                .unwrap_or_default()
+        });
+        let current = groups.get(&producer_group_id).copied();
+        if let (Some(current), Some(owner_level)) = (current, owner_level) {
+            if owner_level < current.owner_level {
+                return Err(definitions::Error::new(
+                    ErrorCondition::Custom(Symbol::from(PRODUCER_EPOCH_STOLEN)),
+                    Some(format!(
+                        "The producer group {} has a higher owner level",
+                        producer_group_id
+                    )),
+                    None,
+                ));
+            }
+        }
+        let group = ProducerGroup {
+            owner_level: owner_level
+                .or(current.map(|group| group.owner_level))
+                .unwrap_or_default(),
+            last_sequence_number: sequence_number
+                .or(current.map(|group| group.last_sequence_number))
+                .unwrap_or(-1),
+        };
+        groups.insert(producer_group_id, group);
+        Ok(IdempotentProducer {
+            producer_group_id,
+            owner_level: group.owner_level,
+            last_sequence_number: group.last_sequence_number,
+        })
+    }
+
+    /// Appends the events of an idempotent publisher.
+    ///
+    /// The events must carry consecutive sequence numbers that follow the last one published by
+    /// the producer group; otherwise none of them are appended. A resend of the last events
+    /// published, as a retry after a lost acknowledgement produces, is accepted without
+    /// appending them again.
+    fn append_idempotent(
+        &self,
+        producer: &IdempotentProducer,
+        events: Vec<BrokerMessage>,
+    ) -> Result<(), definitions::Error> {
+        let mut groups = self.producer_groups();
+        let group = groups
+            .get_mut(&producer.producer_group_id)
+            .expect("idempotent producers are registered on attach");
+        if group.owner_level > producer.owner_level {
+            return Err(definitions::Error::new(
+                ErrorCondition::Custom(Symbol::from(PRODUCER_EPOCH_STOLEN)),
+                Some("A publisher with a higher owner level has attached".to_string()),
+                None,
+            ));
+        }
+
+        let sequence_numbers: Vec<Option<i32>> = events
+            .iter()
+            .map(|event| match producer_sequence_number(event) {
+                Some(Value::Int(sequence_number)) => Some(*sequence_number),
+                _ => None,
+            })
+            .collect();
+        let consecutive_from = |first: i32| {
+            let mut expected = first;
+            sequence_numbers.iter().all(|sequence_number| {
+                let matches = *sequence_number == Some(expected);
+                expected = next_sequence_number(expected);
+                matches
+            })
+        };
+        let last = sequence_numbers.last().copied().flatten();
+        if consecutive_from(next_sequence_number(group.last_sequence_number)) {
+            group.last_sequence_number = last.unwrap_or(group.last_sequence_number);
+        } else if last == Some(group.last_sequence_number)
+            && sequence_numbers
+                .first()
+                .copied()
+                .flatten()
+                .is_some_and(consecutive_from)
+        {
+            debug!(
+                "Ignoring events already published by producer group {}",
+                producer.producer_group_id
+            );
+            return Ok(());
+        } else {
+            return Err(definitions::Error::new(
+                ErrorCondition::Custom(Symbol::from(OUT_OF_ORDER_SEQUENCE)),
+                Some(format!(
+                    "Expected sequence number {}, but the events have {:?}",
+                    next_sequence_number(group.last_sequence_number),
+                    sequence_numbers
+                )),
+                None,
+            ));
+        }
+        drop(groups);
+        events.into_iter().for_each(|event| self.append(event));
+        Ok(())
+    }
+
+    fn producer_groups(&self) -> std::sync::MutexGuard<'_, HashMap<i64, ProducerGroup>> {
+        self.producer_groups
+            .lock()
+            .expect("producer groups lock poisoned")
+    }
+
+    fn read_from(&self, index: usize) -> Vec<BrokerMessage> {
+        self.events()
+            .get(index..)
+            .unwrap_or_default()
+            .iter()
+            .map(|event| event.message.clone())
+            .collect()
+    }
+
+    fn properties(&self, event_hub: &str, id: &str) -> Response {
+        let events = self.events();
+        let last_sequence_number = events.len() as i64 - 1;
+        let last_enqueued_time = events
+            .last()
+            .map(|event| event.enqueued_time)
+            .unwrap_or(UNIX_EPOCH);
+
+        let mut properties = OrderedMap::new();
+        properties.insert(Value::from("name"), Value::from(event_hub));
+        properties.insert(Value::from("partition"), Value::from(id));
+        properties.insert(Value::from("type"), Value::from(PARTITION_ENTITY_TYPE));
+        properties.insert(
+            Value::from("begin_sequence_number"),
+            Value::Long(if events.is_empty() { -1 } else { 0 }),
+        );
+        properties.insert(
+            Value::from("last_enqueued_sequence_number"),
+            Value::Long(last_sequence_number),
+        );
+        properties.insert(
+            Value::from("last_enqueued_sequence_number_epoch"),
+            Value::Int(0),
+        );
+        properties.insert(
+            Value::from("last_enqueued_offset"),
+            Value::String(last_sequence_number.to_string()),
+        );
+        properties.insert(
+            Value::from("last_enqueued_time_utc"),
+            Value::Timestamp(timestamp(last_enqueued_time)),
+        );
+        properties.insert(
+            Value::from("is_partition_empty"),
+            Value::Bool(events.is_empty()),
+        );
+        Response::ok(Value::Map(properties))
+    }
+
+    fn events(&self) -> std::sync::MutexGuard<'_, Vec<StoredEvent>> {
+        self.events.lock().expect("partition lock poisoned")
+    }
+
+    /// Gets the index of the first event after the position in a selector filter expression.
+    ///
+    /// Without a filter, or with a filter that cannot be parsed, reading starts after the last
+    /// event, as the service does for `@latest`.
+    fn start_index(&self, filter: Option<&str>) -> usize {
+        let events = self.events();
+        let Some(position) = filter.and_then(FilterPosition::parse) else {
+            return events.len();
+        };
+        let after = |index: usize| {
+            if position.inclusive {
+                index
+            } else {
+                index + 1
+            }
+        };
+        match position.value.as_str() {
+            "-1" => 0,
+            "@latest" => events.len(),
+            value => match position.annotation.as_str() {
+                ENQUEUED_TIME_ANNOTATION => value
+                    .parse::<u128>()
+                    .map(|milliseconds| {
+                        events
+                            .iter()
+                            .position(|event| {
+                                let enqueued = event
+                                    .enqueued_time
+                                    .duration_since(UNIX_EPOCH)
+                                    .map(|elapsed| elapsed.as_millis())
+                                    .unwrap_or_default();
+                                enqueued > milliseconds
+                                    || (position.inclusive && enqueued == milliseconds)
+                            })
+                            .unwrap_or(events.len())
+                    })
+                    .unwrap_or(events.len()),
+                _ => value
+                    .parse::<usize>()
+                    .map(after)
+                    .unwrap_or(events.len())
+                    .min(events.len()),
+            },
+        }
+    }
+}
+
+/// A position parsed from a selector filter such as `amqp.annotation.x-opt-offset >= '5'`.
+#[derive(Debug, PartialEq, Eq)]
+struct FilterPosition {
+    annotation: String,
+    inclusive: bool,
+    value: String,
+}
+
+impl FilterPosition {
+    fn parse(expression: &str) -> Option<Self> {
+        let (annotation, comparison) = expression.split_once('>')?;
+        let annotation = annotation.trim().strip_prefix("amqp.annotation.")?;
+        let (inclusive, value) = match comparison.strip_prefix('=') {
+            Some(value) => (true, value),
+            None => (false, comparison),
+        };
+        let value = value.trim().strip_prefix('\'')?.strip_suffix('\'')?;
+        Some(Self {
+            annotation: annotation.to_string(),
+            inclusive,
+            value: value.to_string(),
+        })
+    }
+}
+
+/// The partition a consumer link reads from and where it starts reading.
+#[derive(Debug)]
+pub(crate) struct PartitionReader {
+    partition: usize,
+    filter: Option<String>,
+}
+
+impl PartitionReader {
+    pub(crate) fn new(partition: usize, filter: Option<String>) -> Self {
+        Self { partition, filter }
+    }
+}
+
+/// Handles a `com.microsoft:eventhub` read request.
+pub(crate) fn read_event_hub(state: &BrokerState, request: &BrokerMessage) -> Response {
+    match string_property(request, "name").and_then(|name| state.event_hub(name)) {
+        Some(event_hub) => event_hub.properties(),
+        None => Response::error(404, "The event hub could not be found"),
+    }
+}
+
+/// Handles a `com.microsoft:partition` read request.
+pub(crate) fn read_partition(state: &BrokerState, request: &BrokerMessage) -> Response {
+    let Some(event_hub) = string_property(request, "name").and_then(|name| state.event_hub(name))
+    else {
+        return Response::error(404, "The event hub could not be found");
+    };
+    let id = string_property(request, "partition").unwrap_or_default();
+    match id.parse().ok().and_then(|id| event_hub.partition(id)) {
+        Some(partition) => partition.properties(&event_hub.name, id),
+        None => Response::error(404, format!("The partition '{}' could not be found", id)),
+    }
+}
+
+/// Stores the events published on a link to an event hub or one of its partitions.
+pub(crate) async fn serve_publisher(
+    event_hub: Arc<EventHub>,
+    partition: Option<usize>,
+    producer: Option<IdempotentProducer>,
+    mut receiver: Receiver,
+) {
+    loop {
+        let delivery = match receiver.recv::<Body<Value>>().await {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                debug!("Event hub publisher link closed: {}", e);
+                break;
+            }
+        };
+        let message_format = *delivery.message_format();
+        let (info, message) = delivery.into_parts();
+        let partition = partition.unwrap_or_else(|| event_hub.route(partition_key(&message)));
+        let partition = &event_hub.partitions[partition];
+        let result = match (unpack_batch(message_format, message), &producer) {
+            (Ok(events), None) => {
+                events.into_iter().for_each(|event| partition.append(event));
+                receiver.accept(info).await
+            }
+            (Ok(events), Some(producer)) => {
+                match partition.append_idempotent(producer, events) {
+                    Ok(()) => receiver.accept(info).await,
+                    // The service detaches a publisher once a newer one owns its producer group,
+                    // settling the delivery first so that the send doesn't wait for an outcome.
+                    Err(error)
+                        if error.condition
+                            == ErrorCondition::Custom(Symbol::from(PRODUCER_EPOCH_STOLEN)) =>
+                    {
+                        if let Err(e) = receiver.reject(info, error.clone()).await {
+                            debug!("Failed to settle published events: {}", e);
+                        }
+                        if let Err(e) = receiver.close_with_error(error).await {
+                            debug!("Failed to close stolen publisher link: {}", e);
+                        }
+                        break;
+                    }
+                    Err(error) => receiver.reject(info, error).await,
+                }
+            }
+            (Err(e), _) => {
+                let error = definitions::Error::new(AmqpError::DecodeError, Some(e), None);
+                receiver.reject(info, error).await
+            }
+        };
+        if let Err(e) = result {
+            debug!("Failed to settle published events: {}", e);
+            break;
+        }
+    }
+}
+
+/// Sends the events of a partition to a consumer link, waiting for new events once it has
+/// caught up.
+pub(crate) async fn serve_consumer(
+    event_hub: Arc<EventHub>,
+    reader: PartitionReader,
+    mut sender: Sender,
+) {
+    let partition = &event_hub.partitions[reader.partition];
+    let mut next = partition.start_index(reader.filter.as_deref());
+    loop {
+        // Register for notifications before reading so an event appended in between isn't missed.
+        let appended = partition.appended.notified();
+        let events = partition.read_from(next);
+        if events.is_empty() {
+            tokio::select! {
+                _ = appended => continue,
+                e = sender.on_detach() => {
+                    debug!("Event hub consumer link closed: {}", e);
+                    break;
+                }
+            }
+        }
+        for event in events {
+            let sendable = Sendable::builder().message(event).settled(true).build();
+            if let Err(e) = sender.send_batchable(sendable).await {
+                debug!("Failed to send event: {}", e);
+                return;
+            }
+            next += 1;
+        }
+    }
+}
+
+fn producer_sequence_number(message: &BrokerMessage) -> Option<&Value> {
+    message
+        .message_annotations
+        .as_ref()?
+        .get(&OwnedKey::from(PRODUCER_SEQUENCE_NUMBER))
+}
+
+fn partition_key(message: &BrokerMessage) -> Option<&str> {
+    let annotations = message.message_annotations.as_ref()?;
+    match annotations.get(&OwnedKey::Symbol(Symbol::from(PARTITION_KEY_ANNOTATION)))? {
+        Value::String(partition_key) => Some(partition_key),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fe2o3_amqp::types::messaging::{Data, Message};
+
+    fn event(body: &str) -> BrokerMessage {
+        Message::builder()
+            .body(Body::Data(
+                vec![Data(body.as_bytes().to_vec().into())].into(),
+            ))
+            .build()
+    }
+
+    #[test]
+    fn parses_filter_positions() {
+        assert_eq!(
+            FilterPosition::parse("amqp.annotation.x-opt-offset > '-1'"),
+            Some(FilterPosition {
+                annotation: OFFSET_ANNOTATION.to_string(),
+                inclusive: false,
+                value: "-1".to_string(),
+            })
+        );
+        assert_eq!(
+            FilterPosition::parse("amqp.annotation.x-opt-sequence-number >='12'"),
+            Some(FilterPosition {
+                annotation: SEQUENCE_NUMBER_ANNOTATION.to_string(),
+                inclusive: true,
+                value: "12".to_string(),
+            })
+        );
+        assert_eq!(FilterPosition::parse("x-opt-offset > 5"), None);
+    }
+
+    #[test]
+    fn start_index_from_filter() {
+        let partition = Partition::default();
+        (0..5).for_each(|i| partition.append(event(&i.to_string())));
+
+        assert_eq!(partition.start_index(None), 5);
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-offset > '-1'")),
+            0
+        );
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-offset > '@latest'")),
+            5
+        );
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-sequence-number >='2'")),
+            2
+        );
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-offset >'2'")),
+            3
+        );
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-offset >'42'")),
+            5
+        );
+        assert_eq!(
+            partition.start_index(Some("amqp.annotation.x-opt-enqueued-time >'0'")),
+            0
+        );
+    }
+
+    #[test]
+    fn routes_partition_keys_consistently() {
+        let event_hub = EventHub::new("eventhub".to_string(), 4);
+        let partition = event_hub.route(Some("key"));
+        assert!((0..10).all(|_| event_hub.route(Some("key")) == partition));
+
+        let round_robin: Vec<usize> = (0..4).map(|_| event_hub.route(None)).collect();
+        assert_eq!(round_robin, vec![0, 1, 2, 3]);
+    }
+
+    fn sequenced_event(sequence_number: i32) -> BrokerMessage {
+        let mut event = event("sequenced");
+        let mut annotations = MessageAnnotations(OrderedMap::new());
+        annotations.insert(
+            OwnedKey::from(PRODUCER_SEQUENCE_NUMBER),
+            Value::Int(sequence_number),
+        );
+        event.message_annotations = Some(annotations);
+        event
+    }
+
+    #[test]
+    fn attach_producer_assigns_producer_groups() {
+        let partition = Partition::default();
+        let first = partition.attach_producer(None).unwrap();
+        let second = partition.attach_producer(None).unwrap();
+        assert_ne!(first.producer_group_id, second.producer_group_id);
+        assert_eq!(first.owner_level, 0);
+        assert_eq!(first.last_sequence_number, -1);
+
+        let mut properties = Fields::new();
+        properties.insert(
+            Symbol::from(PRODUCER_ID),
+            Value::Long(first.producer_group_id),
+        );
+        properties.insert(Symbol::from(PRODUCER_EPOCH), Value::Short(2));
+        properties.insert(Symbol::from(PRODUCER_SEQUENCE_NUMBER), Value::Int(41));
+        let resumed = partition.attach_producer(Some(&properties)).unwrap();
+        assert_eq!(resumed.producer_group_id, first.producer_group_id);
+        assert_eq!(resumed.owner_level, 2);
+        assert_eq!(resumed.last_sequence_number, 41);
+        assert_eq!(
+            resumed
+                .properties()
+                .get(&Symbol::from(PRODUCER_SEQUENCE_NUMBER)),
+            Some(&Value::Int(41))
+        );
+
+        properties.insert(Symbol::from(PRODUCER_EPOCH), Value::Short(1));
+        let error = partition.attach_producer(Some(&properties)).unwrap_err();
+        assert_eq!(
+            &error.condition,
+            &ErrorCondition::Custom(Symbol::from(PRODUCER_EPOCH_STOLEN))
+        );
+
+        properties.insert(Symbol::from(PRODUCER_EPOCH), Value::Int(3));
+        let error = partition.attach_producer(Some(&properties)).unwrap_err();
+        assert_eq!(
+            &error.condition,
+            &ErrorCondition::from(AmqpError::InvalidField)
+        );
+    }
+
+    #[test]
+    fn append_idempotent_checks_sequence_numbers() {
+        let partition = Partition::default();
+        let producer = partition.attach_producer(None).unwrap();
+
+        partition
+            .append_idempotent(&producer, vec![sequenced_event(0), sequenced_event(1)])
+            .unwrap();
+        assert_eq!(partition.len(), 2);
+
+        // A resend of the last events published is acknowledged but not appended again.
+        partition
+            .append_idempotent(&producer, vec![sequenced_event(0), sequenced_event(1)])
+            .unwrap();
+        assert_eq!(partition.len(), 2);
+
+        // Events that skip or overlap sequence numbers are refused as a whole.
+        let error = partition
+            .append_idempotent(&producer, vec![sequenced_event(1), sequenced_event(2)])
+            .unwrap_err();
+        assert_eq!(
+            &error.condition,
+            &ErrorCondition::Custom(Symbol::from(OUT_OF_ORDER_SEQUENCE))
+        );
+        assert!(partition
+            .append_idempotent(&producer, vec![event("unsequenced")])
+            .is_err());
+        assert_eq!(partition.len(), 2);
+
+        partition
+            .append_idempotent(&producer, vec![sequenced_event(2)])
+            .unwrap();
+        assert_eq!(partition.len(), 3);
+    }
+
+    #[test]
+    fn append_idempotent_refuses_stolen_producers() {
+        let partition = Partition::default();
+        let producer = partition.attach_producer(None).unwrap();
+        let mut properties = producer.properties();
+        properties.insert(Symbol::from(PRODUCER_EPOCH), Value::Short(1));
+        partition.attach_producer(Some(&properties)).unwrap();
+
+        let error = partition
+            .append_idempotent(&producer, vec![sequenced_event(0)])
+            .unwrap_err();
+        assert_eq!(
+            &error.condition,
+            &ErrorCondition::Custom(Symbol::from(PRODUCER_EPOCH_STOLEN))
+        );
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_to_zero() {
+        assert_eq!(next_sequence_number(-1), 0);
+        assert_eq!(next_sequence_number(7), 8);
+        assert_eq!(next_sequence_number(i32::MAX), 0);
+    }
+
+    #[test]
+    fn appended_events_are_annotated() {
+        let partition = Partition::default();
+        partition.append(event("first"));
+        partition.append(event("second"));
+
+        let events = partition.read_from(1);
+        assert_eq!(events.len(), 1);
+        let annotations = events[0].message_annotations.as_ref().unwrap();
+        assert_eq!(
+            annotations.get(&OwnedKey::from(SEQUENCE_NUMBER_ANNOTATION)),
+            Some(&Value::Long(1))
+        );
+        assert_eq!(
+            annotations.get(&OwnedKey::from(OFFSET_ANNOTATION)),
+            Some(&Value::String("1".to_string()))
+        );
+        assert!(partition.read_from(2).is_empty());
+        assert!(partition.read_from(5).is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! An in-process AMQP broker for testing Azure messaging clients without a live namespace.
+//!
+//! [`TestBroker`] listens on a local TCP port and speaks enough of the Event Hubs and Service Bus
+//! AMQP protocols for the clients in this repository to run against it:
+//!
+//! * Claims-based security: every `put-token` request on the `$cbs` node is accepted, and the
+//!   authorized audiences are recorded so tests can assert on them.
+//! * Event Hubs: events sent to an event hub or one of its partitions are stored in memory, and
+//!   receivers on `<event hub>/ConsumerGroups/<consumer group>/Partitions/<id>` read them from the
+//!   position given by the selector filter. Event hub and partition properties are returned from
+//!   the `$management` node. Publishers that attach to a partition with the
+//!   `com.microsoft:idempotent-producer` capability are assigned a producer group, and their events
+//!   are refused unless they carry the next producer sequence number.
+//! * Service Bus: messages sent to a queue are delivered to receivers in peek-lock mode with a
+//!   lock that expires after the configured lock duration. Accepting a delivery completes the
+//!   message, releasing or modifying it abandons it, and rejecting it moves it to the
+//!   `<queue>/$DeadLetterQueue` sub-queue. Messages that exceed the maximum delivery count are
+//!   dead-lettered. Receivers that attach with a settled sender settle mode receive and delete.
+//!   The `$management` node supports `com.microsoft:renew-lock` and `com.microsoft:peek-message`.
+//!
+//! Sessions, topics, transactions and scheduled messages are not simulated.
+//!
+//! Connections are not encrypted and authenticate with SASL ANONYMOUS, so clients must be pointed
+//! at [`TestBroker::endpoint`] with a custom endpoint option.
+//!
+//! ```no_run
+//! use azure_core_amqp_test::TestBroker;
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let broker = TestBroker::builder()
+//!     .with_event_hub("eventhub", 4)
+//!     .with_queue("queue")
+//!     .start()
+//!     .await?;
+//! println!("Connect to {}", broker.endpoint());
+//! # Ok(())
+//! # }
+//! ```
+
+mod broker;
+mod eventhubs;
+mod management;
+mod servicebus;
+
+pub use broker::{TestBroker, TestBrokerBuilder};
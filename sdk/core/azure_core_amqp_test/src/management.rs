@@ -0,0 +1,249 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! The `$cbs` and `$management` request-response nodes.
+
+use crate::{
+    broker::{BrokerMessage, BrokerState, SessionEntity},
+    eventhubs, servicebus,
+};
+use fe2o3_amqp::{
+    link::{Receiver, Sender},
+    types::{
+        messaging::{AmqpValue, ApplicationProperties, Body, Message, Properties},
+        primitives::{SimpleValue, Value},
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+const STATUS_CODE: &str = "status-code";
+const STATUS_DESCRIPTION: &str = "status-description";
+
+/// A request-response node of the broker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Node {
+    /// The claims-based security node, `$cbs`.
+    Cbs,
+
+    /// The management node, `$management`.
+    Management,
+}
+
+/// The links responses are sent on, keyed by the address clients put in the `reply-to` property.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplyNodes(Arc<Mutex<HashMap<String, mpsc::UnboundedSender<BrokerMessage>>>>);
+
+impl ReplyNodes {
+    /// Registers a reply address, replacing any link previously registered for it.
+    pub(crate) fn register(&self, address: String) -> mpsc::UnboundedReceiver<BrokerMessage> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.0
+            .lock()
+            .expect("reply nodes lock poisoned")
+            .insert(address, sender);
+        receiver
+    }
+
+    fn send(&self, address: &str, response: BrokerMessage) -> bool {
+        self.0
+            .lock()
+            .expect("reply nodes lock poisoned")
+            .get(address)
+            .is_some_and(|sender| sender.send(response).is_ok())
+    }
+}
+
+/// The response to a management request.
+#[derive(Debug)]
+pub(crate) struct Response {
+    status_code: u16,
+    description: Option<String>,
+    body: Value,
+}
+
+impl Response {
+    /// A successful response with a body.
+    pub(crate) fn ok(body: Value) -> Self {
+        Self {
+            status_code: 200,
+            description: None,
+            body,
+        }
+    }
+
+    /// A failed response.
+    pub(crate) fn error(status_code: u16, description: impl Into<String>) -> Self {
+        Self {
+            status_code,
+            description: Some(description.into()),
+            body: Value::Null,
+        }
+    }
+
+    fn into_message(self, request: &BrokerMessage) -> BrokerMessage {
+        let mut application_properties = ApplicationProperties::builder()
+            .insert(STATUS_CODE, SimpleValue::Int(self.status_code.into()));
+        if let Some(description) = self.description {
+            application_properties = application_properties.insert(STATUS_DESCRIPTION, description);
+        }
+        Message::builder()
+            .properties(Properties {
+                correlation_id: request
+                    .properties
+                    .as_ref()
+                    .and_then(|properties| properties.message_id.clone()),
+                ..Default::default()
+            })
+            .application_properties(application_properties.build())
+            .body(Body::Value(AmqpValue(self.body)))
+            .build()
+    }
+}
+
+/// Gets an application property of a request.
+pub(crate) fn property<'a>(request: &'a BrokerMessage, key: &str) -> Option<&'a SimpleValue> {
+    request.application_properties.as_ref()?.get(key)
+}
+
+/// Gets a string application property of a request.
+pub(crate) fn string_property<'a>(request: &'a BrokerMessage, key: &str) -> Option<&'a str> {
+    match property(request, key)? {
+        SimpleValue::String(value) => Some(value),
+        SimpleValue::Symbol(value) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Gets an integer application property of a request, whatever its width.
+pub(crate) fn integer_property(request: &BrokerMessage, key: &str) -> Option<i64> {
+    match *property(request, key)? {
+        SimpleValue::Ubyte(value) => Some(value.into()),
+        SimpleValue::Ushort(value) => Some(value.into()),
+        SimpleValue::Uint(value) => Some(value.into()),
+        SimpleValue::Ulong(value) => value.try_into().ok(),
+        SimpleValue::Byte(value) => Some(value.into()),
+        SimpleValue::Short(value) => Some(value.into()),
+        SimpleValue::Int(value) => Some(value.into()),
+        SimpleValue::Long(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Reads requests sent to a node and routes the responses to the requester's reply link.
+pub(crate) async fn serve_requests(
+    node: Node,
+    mut receiver: Receiver,
+    state: Arc<BrokerState>,
+    session_entity: SessionEntity,
+    reply_nodes: ReplyNodes,
+) {
+    loop {
+        let delivery = match receiver.recv::<Body<Value>>().await {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                debug!("{:?} request link closed: {}", node, e);
+                break;
+            }
+        };
+        let (info, request) = delivery.into_parts();
+        if let Err(e) = receiver.accept(info).await {
+            debug!("Failed to accept {:?} request: {}", node, e);
+            break;
+        }
+
+        let response = match node {
+            Node::Cbs => put_token(&state, &request),
+            Node::Management => manage(&state, &session_entity, &request),
+        };
+        let reply_to = request
+            .properties
+            .as_ref()
+            .and_then(|properties| properties.reply_to.clone());
+        let response = response.into_message(&request);
+        match reply_to {
+            Some(reply_to) if reply_nodes.send(&reply_to, response) => {}
+            reply_to => warn!(
+                "Dropping {:?} response: no reply link attached for {:?}",
+                node, reply_to
+            ),
+        }
+    }
+}
+
+/// Sends the responses routed to a reply link.
+pub(crate) async fn serve_replies(
+    mut sender: Sender,
+    mut responses: mpsc::UnboundedReceiver<BrokerMessage>,
+) {
+    loop {
+        // Clients detach their reply link without sending anything on it, so watch for the detach
+        // while waiting for the next response.
+        let response = tokio::select! {
+            response = responses.recv() => match response {
+                Some(response) => response,
+                None => break,
+            },
+            e = sender.on_detach() => {
+                debug!("Reply link closed: {}", e);
+                break;
+            }
+        };
+        if let Err(e) = sender.send(response).await {
+            debug!("Reply link closed: {}", e);
+            break;
+        }
+    }
+}
+
+/// Accepts every token, recording the audience it was put for.
+fn put_token(state: &BrokerState, request: &BrokerMessage) -> Response {
+    if string_property(request, "operation") != Some("put-token") {
+        return Response::error(501, "Only the put-token operation is supported");
+    }
+    let Some(audience) = string_property(request, "name") else {
+        return Response::error(400, "The name property is required");
+    };
+    state.authorize(audience.to_string());
+    Response {
+        status_code: 202,
+        description: None,
+        body: Value::Null,
+    }
+}
+
+fn manage(
+    state: &BrokerState,
+    session_entity: &SessionEntity,
+    request: &BrokerMessage,
+) -> Response {
+    // Entity reads use the READ operation with the entity type in the type property, and Service
+    // Bus clients in this repository send their operations the same way.
+    let operation = match string_property(request, "operation") {
+        Some("READ") => string_property(request, "type"),
+        operation => operation,
+    };
+    match operation {
+        Some(eventhubs::EVENT_HUB_ENTITY_TYPE) => eventhubs::read_event_hub(state, request),
+        Some(eventhubs::PARTITION_ENTITY_TYPE) => eventhubs::read_partition(state, request),
+        Some(operation) if operation.starts_with(servicebus::OPERATION_PREFIX) => {
+            let queue = session_entity
+                .lock()
+                .expect("session entity lock poisoned")
+                .clone()
+                .or_else(|| state.single_queue().cloned());
+            match queue {
+                Some(queue) => servicebus::manage(&queue, operation, request),
+                None => Response::error(
+                    400,
+                    "The entity could not be determined from the management link's session",
+                ),
+            }
+        }
+        operation => Response::error(501, format!("Unsupported operation {:?}", operation)),
+    }
+}
@@ -0,0 +1,741 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Service Bus queues, message locks and settlement.
+
+use crate::{
+    broker::{timestamp, unpack_batch, BrokerMessage},
+    management::{integer_property, string_property, Response},
+};
+use fe2o3_amqp::{
+    link::{delivery::Sendable, Receiver, Sender},
+    types::{
+        definitions::{self, AmqpError, Fields},
+        messaging::{
+            annotations::OwnedKey, message::__private::Serializable, ApplicationProperties, Body,
+            Header, MessageAnnotations, Outcome,
+        },
+        primitives::{Array, Binary, OrderedMap, SimpleValue, Value},
+    },
+};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime},
+};
+use tokio::{sync::Notify, time::Instant};
+use tracing::debug;
+use uuid::Uuid;
+
+/// The prefix of Service Bus management operations.
+pub(crate) const OPERATION_PREFIX: &str = "com.microsoft:";
+
+const RENEW_LOCK_OPERATION: &str = "com.microsoft:renew-lock";
+const PEEK_MESSAGE_OPERATION: &str = "com.microsoft:peek-message";
+
+const SEQUENCE_NUMBER_ANNOTATION: &str = "x-opt-sequence-number";
+const ENQUEUED_TIME_ANNOTATION: &str = "x-opt-enqueued-time";
+const LOCKED_UNTIL_ANNOTATION: &str = "x-opt-locked-until";
+const LOCK_TOKEN_ANNOTATION: &str = "x-opt-lock-token";
+
+const DEAD_LETTER_REASON: &str = "DeadLetterReason";
+const DEAD_LETTER_ERROR_DESCRIPTION: &str = "DeadLetterErrorDescription";
+const MAX_DELIVERY_COUNT_EXCEEDED: &str = "MaxDeliveryCountExceeded";
+
+/// A queue and its dead-letter sub-queue.
+#[derive(Debug)]
+pub(crate) struct Queue {
+    name: String,
+    lock_duration: Duration,
+    max_delivery_count: u32,
+    state: Mutex<QueueState>,
+    changed: Notify,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    next_sequence_number: i64,
+    active: BTreeMap<i64, QueuedMessage>,
+    dead_letter: BTreeMap<i64, QueuedMessage>,
+}
+
+#[derive(Debug)]
+struct QueuedMessage {
+    message: BrokerMessage,
+    delivery_count: u32,
+    lock: Option<Lock>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Lock {
+    token: Uuid,
+    expires_at: Instant,
+    locked_until: SystemTime,
+}
+
+/// A message handed to a receiver.
+#[derive(Debug)]
+struct Lease {
+    sequence_number: i64,
+    lock_token: Option<Uuid>,
+    message: BrokerMessage,
+}
+
+/// What a receiver does next.
+#[derive(Debug)]
+enum Next {
+    /// Deliver a message.
+    Deliver(Box<Lease>),
+
+    /// Wait until the queue changes or, if set, a lock expires.
+    Wait(Option<Instant>),
+}
+
+/// How a locked message was settled.
+#[derive(Debug)]
+enum Settlement {
+    Complete,
+    Abandon(Option<Fields>),
+    DeadLetter(Option<definitions::Error>),
+}
+
+impl From<Outcome> for Settlement {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Accepted(_) => Settlement::Complete,
+            Outcome::Rejected(rejected) => Settlement::DeadLetter(rejected.error),
+            Outcome::Modified(modified) => Settlement::Abandon(modified.message_annotations),
+            _ => Settlement::Abandon(None),
+        }
+    }
+}
+
+impl Queue {
+    pub(crate) fn new(name: String, lock_duration: Duration, max_delivery_count: u32) -> Self {
+        Self {
+            name,
+            lock_duration,
+            max_delivery_count,
+            state: Mutex::new(QueueState::default()),
+            changed: Notify::new(),
+        }
+    }
+
+    pub(crate) fn active_message_count(&self) -> usize {
+        self.state().active.len()
+    }
+
+    pub(crate) fn dead_letter_message_count(&self) -> usize {
+        self.state().dead_letter.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().expect("queue lock poisoned")
+    }
+
+    fn enqueue(&self, mut message: BrokerMessage) {
+        let mut state = self.state();
+        let sequence_number = state.next_sequence_number;
+        state.next_sequence_number += 1;
+        let annotations = message
+            .message_annotations
+            .get_or_insert_with(|| MessageAnnotations(OrderedMap::new()));
+        annotations.insert(
+            OwnedKey::from(SEQUENCE_NUMBER_ANNOTATION),
+            Value::Long(sequence_number),
+        );
+        annotations.insert(
+            OwnedKey::from(ENQUEUED_TIME_ANNOTATION),
+            Value::Timestamp(timestamp(SystemTime::now())),
+        );
+        state.active.insert(
+            sequence_number,
+            QueuedMessage {
+                message,
+                delivery_count: 0,
+                lock: None,
+            },
+        );
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    /// Locks the first available message for a receiver, or removes it in receive-and-delete mode.
+    fn next(&self, dead_letter: bool, receive_and_delete: bool) -> Next {
+        let mut state = self.state();
+        self.expire_locks(&mut state);
+
+        let messages = if dead_letter {
+            &mut state.dead_letter
+        } else {
+            &mut state.active
+        };
+        let available = messages
+            .iter()
+            .find(|(_, queued)| queued.lock.is_none())
+            .map(|(sequence_number, _)| *sequence_number);
+        let Some(sequence_number) = available else {
+            let next_expiry = messages
+                .values()
+                .filter_map(|queued| queued.lock.map(|lock| lock.expires_at))
+                .min();
+            return Next::Wait(next_expiry);
+        };
+
+        if receive_and_delete {
+            let queued = messages
+                .remove(&sequence_number)
+                .expect("available message is queued");
+            let mut message = queued.message;
+            set_delivery_count(&mut message, queued.delivery_count);
+            return Next::Deliver(Box::new(Lease {
+                sequence_number,
+                lock_token: None,
+                message,
+            }));
+        }
+
+        let lock = Lock {
+            token: Uuid::new_v4(),
+            expires_at: Instant::now() + self.lock_duration,
+            locked_until: SystemTime::now() + self.lock_duration,
+        };
+        let queued = messages
+            .get_mut(&sequence_number)
+            .expect("available message is queued");
+        queued.lock = Some(lock);
+        let mut message = queued.message.clone();
+        set_delivery_count(&mut message, queued.delivery_count);
+        queued.delivery_count += 1;
+
+        let annotations = message
+            .message_annotations
+            .get_or_insert_with(|| MessageAnnotations(OrderedMap::new()));
+        annotations.insert(
+            OwnedKey::from(LOCKED_UNTIL_ANNOTATION),
+            Value::Timestamp(timestamp(lock.locked_until)),
+        );
+        annotations.insert(
+            OwnedKey::from(LOCK_TOKEN_ANNOTATION),
+            Value::Uuid(lock.token.into()),
+        );
+        Next::Deliver(Box::new(Lease {
+            sequence_number,
+            lock_token: Some(lock.token),
+            message,
+        }))
+    }
+
+    /// Settles a locked message. Settlements for locks that have been lost are ignored.
+    fn settle(
+        &self,
+        dead_letter: bool,
+        sequence_number: i64,
+        lock_token: Uuid,
+        settlement: Settlement,
+    ) {
+        let mut state = self.state();
+        let messages = if dead_letter {
+            &mut state.dead_letter
+        } else {
+            &mut state.active
+        };
+        let Some(queued) = messages.get_mut(&sequence_number) else {
+            return;
+        };
+        if queued.lock.map(|lock| lock.token) != Some(lock_token) {
+            debug!(
+                "Ignoring settlement of message {} in {}: the lock was lost",
+                sequence_number, self.name
+            );
+            return;
+        }
+
+        match settlement {
+            Settlement::Complete => {
+                messages.remove(&sequence_number);
+            }
+            Settlement::Abandon(annotations) => {
+                queued.lock = None;
+                if let Some(annotations) = annotations {
+                    let message_annotations = queued
+                        .message
+                        .message_annotations
+                        .get_or_insert_with(|| MessageAnnotations(OrderedMap::new()));
+                    for (key, value) in annotations {
+                        message_annotations.insert(OwnedKey::Symbol(key), value);
+                    }
+                }
+                if !dead_letter && queued.delivery_count >= self.max_delivery_count {
+                    let queued = messages
+                        .remove(&sequence_number)
+                        .expect("abandoned message is queued");
+                    state.dead_letter(sequence_number, queued, MAX_DELIVERY_COUNT_EXCEEDED, None);
+                }
+            }
+            Settlement::DeadLetter(error) => {
+                if dead_letter {
+                    // Messages can't be dead-lettered again, so they are only unlocked.
+                    queued.lock = None;
+                } else {
+                    let queued = messages
+                        .remove(&sequence_number)
+                        .expect("rejected message is queued");
+                    let info = error.and_then(|error| error.info);
+                    let property = |key: &str| match info.as_ref()?.get(key)? {
+                        Value::String(value) => Some(value.clone()),
+                        _ => None,
+                    };
+                    let reason = property(DEAD_LETTER_REASON).unwrap_or_default();
+                    let description = property(DEAD_LETTER_ERROR_DESCRIPTION);
+                    state.dead_letter(sequence_number, queued, &reason, description);
+                }
+            }
+        }
+        drop(state);
+        self.changed.notify_waiters();
+    }
+
+    /// Unlocks messages whose locks have expired, dead-lettering those delivered too often.
+    fn expire_locks(&self, state: &mut QueueState) {
+        let now = Instant::now();
+        let expired =
+            |queued: &QueuedMessage| queued.lock.is_some_and(|lock| lock.expires_at <= now);
+        for queued in state
+            .dead_letter
+            .values_mut()
+            .filter(|queued| expired(queued))
+        {
+            queued.lock = None;
+        }
+        let exhausted: Vec<i64> = state
+            .active
+            .iter_mut()
+            .filter(|(_, queued)| expired(queued))
+            .filter_map(|(sequence_number, queued)| {
+                queued.lock = None;
+                (queued.delivery_count >= self.max_delivery_count).then_some(*sequence_number)
+            })
+            .collect();
+        for sequence_number in exhausted {
+            let queued = state
+                .active
+                .remove(&sequence_number)
+                .expect("expired message is queued");
+            state.dead_letter(sequence_number, queued, MAX_DELIVERY_COUNT_EXCEEDED, None);
+        }
+    }
+
+    fn renew_lock(&self, lock_token: Uuid) -> Option<SystemTime> {
+        let mut state = self.state();
+        self.expire_locks(&mut state);
+        let state = &mut *state;
+        let queued = state
+            .active
+            .values_mut()
+            .chain(state.dead_letter.values_mut())
+            .find(|queued| queued.lock.is_some_and(|lock| lock.token == lock_token))?;
+        let lock = queued.lock.as_mut()?;
+        lock.expires_at = Instant::now() + self.lock_duration;
+        lock.locked_until = SystemTime::now() + self.lock_duration;
+        Some(lock.locked_until)
+    }
+
+    fn peek(&self, from_sequence_number: i64, count: usize) -> Vec<BrokerMessage> {
+        self.state()
+            .active
+            .range(from_sequence_number..)
+            .take(count)
+            .map(|(_, queued)| {
+                let mut message = queued.message.clone();
+                set_delivery_count(&mut message, queued.delivery_count);
+                message
+            })
+            .collect()
+    }
+}
+
+impl QueueState {
+    fn dead_letter(
+        &mut self,
+        sequence_number: i64,
+        mut queued: QueuedMessage,
+        reason: &str,
+        description: Option<String>,
+    ) {
+        queued.lock = None;
+        let properties = queued
+            .message
+            .application_properties
+            .get_or_insert_with(|| ApplicationProperties(OrderedMap::new()));
+        properties.insert(
+            DEAD_LETTER_REASON.to_string(),
+            SimpleValue::String(reason.to_string()),
+        );
+        if let Some(description) = description {
+            properties.insert(
+                DEAD_LETTER_ERROR_DESCRIPTION.to_string(),
+                SimpleValue::String(description),
+            );
+        }
+        self.dead_letter.insert(sequence_number, queued);
+    }
+}
+
+fn set_delivery_count(message: &mut BrokerMessage, delivery_count: u32) {
+    message
+        .header
+        .get_or_insert_with(Header::default)
+        .delivery_count = delivery_count;
+}
+
+/// Handles a Service Bus management request for a queue.
+pub(crate) fn manage(queue: &Queue, operation: &str, request: &BrokerMessage) -> Response {
+    match operation {
+        RENEW_LOCK_OPERATION => renew_lock(queue, request),
+        PEEK_MESSAGE_OPERATION => peek(queue, request),
+        operation => Response::error(501, format!("Unsupported operation {}", operation)),
+    }
+}
+
+/// Renews the locks in the `lock-token` application property or the `lock-tokens` body field.
+fn renew_lock(queue: &Queue, request: &BrokerMessage) -> Response {
+    let lock_tokens: Vec<Uuid> = match string_property(request, "lock-token") {
+        Some(lock_token) => lock_token.parse().into_iter().collect(),
+        None => match &request.body {
+            Body::Value(value) => match &value.0 {
+                Value::Map(map) => match map.get(&Value::from("lock-tokens")) {
+                    Some(Value::Array(lock_tokens)) => lock_tokens
+                        .iter()
+                        .filter_map(|lock_token| match lock_token {
+                            Value::Uuid(lock_token) => Some(Uuid::from(lock_token.clone())),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                },
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        },
+    };
+    if lock_tokens.is_empty() {
+        return Response::error(400, "A lock token is required");
+    }
+
+    let mut expirations = Vec::new();
+    for lock_token in lock_tokens {
+        match queue.renew_lock(lock_token) {
+            Some(locked_until) => expirations.push(Value::Timestamp(timestamp(locked_until))),
+            None => {
+                return Response::error(
+                    410,
+                    format!("The lock for {} has expired or was lost", lock_token),
+                );
+            }
+        }
+    }
+
+    let mut body = OrderedMap::new();
+    // The service returns the `expirations` array; `expiration` is what the clients in this
+    // repository read for a single lock.
+    body.insert(Value::from("expiration"), expirations[0].clone());
+    body.insert(Value::from("expirations"), Value::Array(Array(expirations)));
+    Response::ok(Value::Map(body))
+}
+
+/// Returns encoded copies of the messages from `from-sequence-number`, or from the first message.
+fn peek(queue: &Queue, request: &BrokerMessage) -> Response {
+    let from_sequence_number = integer_property(request, "from-sequence-number").unwrap_or(0);
+    let count = integer_property(request, "message-count")
+        .unwrap_or(1)
+        .max(0) as usize;
+
+    let mut messages = Vec::new();
+    for message in queue.peek(from_sequence_number, count) {
+        let encoded = match serde_amqp::to_vec(&Serializable(message)) {
+            Ok(encoded) => encoded,
+            Err(e) => return Response::error(500, format!("Failed to encode a message: {}", e)),
+        };
+        let mut entry = OrderedMap::new();
+        entry.insert(Value::from("message"), Value::Binary(Binary::from(encoded)));
+        messages.push(Value::Map(entry));
+    }
+
+    let mut body = OrderedMap::new();
+    body.insert(Value::from("messages"), Value::Array(Array(messages)));
+    Response::ok(Value::Map(body))
+}
+
+/// Enqueues the messages sent on a link to a queue.
+pub(crate) async fn serve_sender(queue: Arc<Queue>, mut receiver: Receiver) {
+    loop {
+        let delivery = match receiver.recv::<Body<Value>>().await {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                debug!("Queue sender link closed: {}", e);
+                break;
+            }
+        };
+        let message_format = *delivery.message_format();
+        let (info, message) = delivery.into_parts();
+        let result = match unpack_batch(message_format, message) {
+            Ok(messages) => {
+                messages
+                    .into_iter()
+                    .for_each(|message| queue.enqueue(message));
+                receiver.accept(info).await
+            }
+            Err(e) => {
+                let error = definitions::Error::new(AmqpError::DecodeError, Some(e), None);
+                receiver.reject(info, error).await
+            }
+        };
+        if let Err(e) = result {
+            debug!("Failed to settle sent messages: {}", e);
+            break;
+        }
+    }
+}
+
+/// Delivers the messages of a queue, or its dead-letter sub-queue, to a receiver link.
+pub(crate) async fn serve_receiver(
+    queue: Arc<Queue>,
+    dead_letter: bool,
+    receive_and_delete: bool,
+    mut sender: Sender,
+) {
+    loop {
+        // Register for notifications before looking so a change in between isn't missed.
+        let changed = queue.changed.notified();
+        let lease = match queue.next(dead_letter, receive_and_delete) {
+            Next::Deliver(lease) => *lease,
+            Next::Wait(next_expiry) => {
+                let lock_expired = async {
+                    match next_expiry {
+                        Some(next_expiry) => tokio::time::sleep_until(next_expiry).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                tokio::select! {
+                    _ = changed => {}
+                    _ = lock_expired => {}
+                    e = sender.on_detach() => {
+                        debug!("Queue receiver link closed: {}", e);
+                        break;
+                    }
+                }
+                continue;
+            }
+        };
+
+        let sendable = Sendable::builder()
+            .message(lease.message)
+            .settled(receive_and_delete)
+            .build();
+        let outcome = match sender.send_batchable(sendable).await {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                debug!("Failed to deliver message: {}", e);
+                if let Some(lock_token) = lease.lock_token {
+                    queue.settle(
+                        dead_letter,
+                        lease.sequence_number,
+                        lock_token,
+                        Settlement::Abandon(None),
+                    );
+                }
+                break;
+            }
+        };
+        if let Some(lock_token) = lease.lock_token {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                match outcome.await {
+                    Ok(outcome) => queue.settle(
+                        dead_letter,
+                        lease.sequence_number,
+                        lock_token,
+                        outcome.into(),
+                    ),
+                    // The message stays locked until the lock expires.
+                    Err(e) => debug!("Delivery was not settled: {}", e),
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fe2o3_amqp::types::messaging::{Message, Modified, Rejected};
+
+    fn message(body: &str) -> BrokerMessage {
+        Message::builder()
+            .body(Body::Value(fe2o3_amqp::types::messaging::AmqpValue(
+                Value::from(body),
+            )))
+            .build()
+    }
+
+    fn lease(queue: &Queue) -> Lease {
+        match queue.next(false, false) {
+            Next::Deliver(lease) => *lease,
+            next => panic!("expected a delivery, got {:?}", next),
+        }
+    }
+
+    #[test]
+    fn completed_messages_are_removed() {
+        let queue = Queue::new("queue".to_string(), Duration::from_secs(30), 10);
+        queue.enqueue(message("first"));
+        queue.enqueue(message("second"));
+
+        let first = lease(&queue);
+        let second = lease(&queue);
+        assert_eq!((first.sequence_number, second.sequence_number), (0, 1));
+        assert!(matches!(queue.next(false, false), Next::Wait(Some(_))));
+
+        queue.settle(false, 0, first.lock_token.unwrap(), Settlement::Complete);
+        assert_eq!(queue.active_message_count(), 1);
+    }
+
+    #[test]
+    fn abandoned_messages_are_redelivered_then_dead_lettered() {
+        let queue = Queue::new("queue".to_string(), Duration::from_secs(30), 2);
+        queue.enqueue(message("poison"));
+
+        let first = lease(&queue);
+        assert_eq!(first.message.header.as_ref().unwrap().delivery_count, 0);
+        let annotations = Fields::from_iter([("x-opt-custom".into(), Value::from("abandoned"))]);
+        queue.settle(
+            false,
+            0,
+            first.lock_token.unwrap(),
+            Outcome::Modified(Modified {
+                delivery_failed: Some(true),
+                undeliverable_here: None,
+                message_annotations: Some(annotations),
+            })
+            .into(),
+        );
+
+        let second = lease(&queue);
+        assert_eq!(second.message.header.as_ref().unwrap().delivery_count, 1);
+        assert!(second
+            .message
+            .message_annotations
+            .as_ref()
+            .unwrap()
+            .contains_key(&OwnedKey::from("x-opt-custom")));
+        queue.settle(
+            false,
+            0,
+            second.lock_token.unwrap(),
+            Settlement::Abandon(None),
+        );
+
+        assert_eq!(queue.active_message_count(), 0);
+        assert_eq!(queue.dead_letter_message_count(), 1);
+        match queue.next(true, true) {
+            Next::Deliver(lease) => {
+                let properties = lease.message.application_properties.unwrap();
+                assert_eq!(
+                    properties.get(DEAD_LETTER_REASON),
+                    Some(&SimpleValue::String(
+                        MAX_DELIVERY_COUNT_EXCEEDED.to_string()
+                    ))
+                );
+            }
+            next => panic!("expected a delivery, got {:?}", next),
+        }
+        assert_eq!(queue.dead_letter_message_count(), 0);
+    }
+
+    #[test]
+    fn rejected_messages_are_dead_lettered_with_reason() {
+        let queue = Queue::new("queue".to_string(), Duration::from_secs(30), 10);
+        queue.enqueue(message("invalid"));
+
+        let lease = lease(&queue);
+        let info = Fields::from_iter([
+            (DEAD_LETTER_REASON.into(), Value::from("Invalid")),
+            (
+                DEAD_LETTER_ERROR_DESCRIPTION.into(),
+                Value::from("Missing field"),
+            ),
+        ]);
+        let error = definitions::Error::new(AmqpError::InternalError, None, info);
+        queue.settle(
+            false,
+            0,
+            lease.lock_token.unwrap(),
+            Outcome::Rejected(Rejected { error: Some(error) }).into(),
+        );
+
+        assert_eq!(queue.active_message_count(), 0);
+        let state = queue.state();
+        let properties = state.dead_letter[&0]
+            .message
+            .application_properties
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            properties.get(DEAD_LETTER_ERROR_DESCRIPTION),
+            Some(&SimpleValue::String("Missing field".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_locks_are_released_and_renewed_locks_are_not() {
+        let queue = Queue::new("queue".to_string(), Duration::from_millis(50), 10);
+        queue.enqueue(message("first"));
+        queue.enqueue(message("second"));
+
+        let first = lease(&queue);
+        let second = lease(&queue);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(queue.renew_lock(second.lock_token.unwrap()).is_some());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+
+        // The first lock expired, so its message is delivered again and settling it with the old
+        // lock token is ignored.
+        let redelivered = lease(&queue);
+        assert_eq!(redelivered.sequence_number, first.sequence_number);
+        queue.settle(false, 0, first.lock_token.unwrap(), Settlement::Complete);
+        assert_eq!(queue.active_message_count(), 2);
+        assert!(queue.renew_lock(first.lock_token.unwrap()).is_none());
+    }
+
+    #[test]
+    fn receive_and_delete_removes_messages() {
+        let queue = Queue::new("queue".to_string(), Duration::from_secs(30), 10);
+        queue.enqueue(message("first"));
+
+        match queue.next(false, true) {
+            Next::Deliver(lease) => assert!(lease.lock_token.is_none()),
+            next => panic!("expected a delivery, got {:?}", next),
+        }
+        assert_eq!(queue.active_message_count(), 0);
+        assert!(matches!(queue.next(false, true), Next::Wait(None)));
+    }
+
+    #[test]
+    fn peek_starts_at_sequence_number() {
+        let queue = Queue::new("queue".to_string(), Duration::from_secs(30), 10);
+        (0..5).for_each(|i| queue.enqueue(message(&i.to_string())));
+
+        let peeked = queue.peek(2, 2);
+        assert_eq!(peeked.len(), 2);
+        assert_eq!(
+            peeked[0]
+                .message_annotations
+                .as_ref()
+                .unwrap()
+                .get(&OwnedKey::from(SEQUENCE_NUMBER_ANNOTATION)),
+            Some(&Value::Long(2))
+        );
+        assert_eq!(queue.peek(4, 10).len(), 1);
+    }
+}
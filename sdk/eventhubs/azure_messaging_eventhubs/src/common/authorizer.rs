@@ -739,7 +739,7 @@ mod tests {
         let connection_manager = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             mock_credential.clone(),
             Default::default(),
             None,
@@ -805,7 +805,7 @@ mod tests {
         let connection_manager = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             mock_credential.clone(),
             Default::default(),
             None,
@@ -875,7 +875,7 @@ mod tests {
         let recoverable_connection = Arc::new(RecoverableConnection::new(
             host.clone(),
             None,
-            None,
+            Default::default(),
             mock_credential.clone(),
             Default::default(),
             None,
@@ -1006,7 +1006,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url.clone(),
             None,
-            None,
+            Default::default(),
             credential.clone(),
             Default::default(),
             None,
@@ -1102,7 +1102,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url.clone(),
             None,
-            None,
+            Default::default(),
             credential.clone(),
             Default::default(),
             None,
@@ -1207,7 +1207,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url.clone(),
             None,
-            None,
+            Default::default(),
             credential.clone(),
             Default::default(),
             None,
@@ -1339,7 +1339,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url.clone(),
             None,
-            None,
+            Default::default(),
             credential.clone(),
             Default::default(),
             None,
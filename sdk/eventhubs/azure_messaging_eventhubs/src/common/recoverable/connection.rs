@@ -15,7 +15,7 @@ use crate::{
     },
     error::Result,
     models::AmqpValue,
-    producer::DEFAULT_EVENTHUBS_APPLICATION,
+    producer::{partition_publishing::PartitionPublisher, DEFAULT_EVENTHUBS_APPLICATION},
     RetryOptions,
 };
 use async_lock::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard, OnceCell, RwLock};
@@ -23,9 +23,9 @@ use azure_core::{credentials::TokenCredential, http::Url, time::Duration, Uuid};
 use azure_core_amqp::{
     error::{AmqpErrorCondition, AmqpErrorKind},
     AmqpClaimsBasedSecurity, AmqpConnection, AmqpConnectionApis, AmqpConnectionOptions, AmqpError,
-    AmqpManagement, AmqpManagementApis, AmqpReceiver, AmqpReceiverApis, AmqpReceiverOptions,
-    AmqpSender, AmqpSenderApis, AmqpSession, AmqpSessionApis, AmqpSessionOptions, AmqpSource,
-    AmqpSymbol,
+    AmqpManagement, AmqpManagementApis, AmqpProxyOptions, AmqpReceiver, AmqpReceiverApis,
+    AmqpReceiverOptions, AmqpSender, AmqpSenderApis, AmqpSession, AmqpSessionApis,
+    AmqpSessionOptions, AmqpSource, AmqpSymbol, AmqpTransportType,
 };
 #[cfg(test)]
 use std::sync::Mutex;
@@ -45,6 +45,17 @@ use tracing::{debug, info, instrument, trace, warn};
 /// scenarios for high availability.
 const GEODR_REPLICATION_CAPABILITY: &str = "com.microsoft.georeplication";
 
+/// How the AMQP connection reaches the Event Hubs service.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ConnectionTransport {
+    /// An endpoint, such as an AMQP proxy, to open the connection to instead of the namespace.
+    pub custom_endpoint: Option<Url>,
+    /// The transport that carries the connection.
+    pub transport_type: Option<AmqpTransportType>,
+    /// The HTTP proxy that WebSocket connections are opened through.
+    pub proxy: Option<AmqpProxyOptions>,
+}
+
 /// The recoverable connection is responsible for managing the connection to the Event Hubs service.
 /// It also handles authorization and connection recovery.
 ///
@@ -78,7 +89,7 @@ const GEODR_REPLICATION_CAPABILITY: &str = "com.microsoft.georeplication";
 pub(crate) struct RecoverableConnection {
     pub(super) url: Url,
     application_id: Option<String>,
-    custom_endpoint: Option<Url>,
+    transport: ConnectionTransport,
     // The management client is a single cached instance, held in a `OnceCell`
     // for the same reason the per-path caches are: the expensive build (connect
     // + session begin + CBS authorize + link attach) must not run while a lock
@@ -282,7 +293,7 @@ impl RecoverableConnection {
     pub fn new(
         url: Url,
         application_id: Option<String>,
-        custom_endpoint: Option<Url>,
+        transport: ConnectionTransport,
         credential: Arc<dyn TokenCredential>,
         retry_options: RetryOptions,
         cbs_token_type: Option<&'static str>,
@@ -298,7 +309,7 @@ impl RecoverableConnection {
                 url,
                 application_id,
                 connection_name,
-                custom_endpoint,
+                transport,
                 retry_options,
                 cbs_lock: AsyncMutex::new(()),
                 connections: AsyncMutex::new(None),
@@ -586,11 +597,44 @@ impl RecoverableConnection {
     /// # Notes
     ///
     /// This sender integrates retry operations into the send operation.
-    pub(crate) async fn get_sender(self: &Arc<Self>, path: Url) -> Result<RecoverableSender> {
+    ///
+    /// With a partition publisher, the sender publishes idempotently: it attaches with the
+    /// publisher's producer group, and the publisher's state is refreshed from the service on
+    /// every attach.
+    pub(crate) async fn get_sender(
+        self: &Arc<Self>,
+        path: Url,
+        publisher: Option<Arc<PartitionPublisher>>,
+    ) -> Result<RecoverableSender> {
         // Ensure we can create a sender for the Event Hub path.
-        self.ensure_sender(&path).await?;
+        self.ensure_sender(&path, publisher.as_ref()).await?;
+
+        Ok(RecoverableSender::new(
+            Arc::downgrade(self),
+            path,
+            publisher,
+        ))
+    }
 
-        Ok(RecoverableSender::new(Arc::downgrade(self), path))
+    /// Detaches the sender for a path, so that the next send attaches a new one.
+    pub(crate) async fn close_sender(self: &Arc<Self>, path: &Url) -> Result<()> {
+        let Some(GenerationalCell { cell, .. }) = self.sender_instances.write().await.remove(path)
+        else {
+            return Ok(());
+        };
+        // A send in flight holds a clone of the sender. The map entry is already removed, so the
+        // sender is dropped once the send completes; it just can't be detached by value here.
+        let Some(sender) = Arc::try_unwrap(cell).ok().and_then(OnceCell::into_inner) else {
+            trace!(path = %path, "close_sender skipped detach; attach in flight");
+            return Ok(());
+        };
+        if let Ok(sender) = Arc::try_unwrap(sender) {
+            trace!("Detaching sender: {:?}", path);
+            sender.detach().await?;
+        } else {
+            trace!(path = %path, "close_sender skipped detach; send in flight");
+        }
+        Ok(())
     }
 
     pub(crate) async fn get_receiver(
@@ -842,7 +886,9 @@ impl RecoverableConnection {
                         .collect(),
                     ),
                     desired_capabilities: Some(vec![GEODR_REPLICATION_CAPABILITY.into()]),
-                    custom_endpoint: self.custom_endpoint.clone(),
+                    custom_endpoint: self.transport.custom_endpoint.clone(),
+                    transport_type: self.transport.transport_type,
+                    proxy: self.transport.proxy.clone(),
                     ..Default::default()
                 }),
             )
@@ -1004,6 +1050,7 @@ impl RecoverableConnection {
     pub(super) async fn ensure_sender(
         self: &Arc<Self>,
         path: &Url,
+        publisher: Option<&Arc<PartitionPublisher>>,
     ) -> azure_core_amqp::Result<Arc<AmqpSender>> {
         // Resolve the per-path cell while holding the map lock only briefly, then
         // attach (authorize + session begin + link attach) without holding it, so
@@ -1028,7 +1075,7 @@ impl RecoverableConnection {
                                 .unwrap_or(&DEFAULT_EVENTHUBS_APPLICATION.to_string())
                         ),
                         path.to_string(),
-                        None,
+                        publisher.map(|publisher| publisher.attach_options()),
                     )
                     .await
                 {
@@ -1040,6 +1087,12 @@ impl RecoverableConnection {
                     );
                     return Err(e);
                 }
+                if let Some(publisher) = publisher {
+                    // The service returns the state of the producer group in the link properties.
+                    publisher
+                        .update_from_attach(sender.properties().await?)
+                        .map_err(|e| AmqpError::from(azure_core::Error::from(e)))?;
+                }
                 info!(
                     connection_id = %self.get_connection_id(),
                     path = %path,
@@ -1514,7 +1567,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             Url::parse("amqps://example.com").unwrap(),
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1549,7 +1602,7 @@ mod tests {
         let connection_manager = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1572,7 +1625,7 @@ mod tests {
         let connection_manager = RecoverableConnection::new(
             url,
             Some(app_id.clone()),
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1592,7 +1645,7 @@ mod tests {
         let connection_manager = Arc::new(RecoverableConnection::new(
             url.clone(),
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1612,7 +1665,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1666,7 +1719,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1695,7 +1748,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1739,7 +1792,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1784,7 +1837,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1839,7 +1892,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1942,7 +1995,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -1991,13 +2044,19 @@ mod tests {
         let connection_manager = RecoverableConnection::new(
             url,
             None,
-            Some(custom_endpoint.clone()),
+            ConnectionTransport {
+                custom_endpoint: Some(custom_endpoint.clone()),
+                ..Default::default()
+            },
             Arc::new(MockCredential),
             Default::default(),
             None,
         );
 
-        assert_eq!(connection_manager.custom_endpoint, Some(custom_endpoint));
+        assert_eq!(
+            connection_manager.transport.custom_endpoint,
+            Some(custom_endpoint)
+        );
     }
 
     #[test]
@@ -2362,7 +2421,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -2438,7 +2497,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
@@ -2487,7 +2546,7 @@ mod tests {
         RecoverableConnection::new(
             url,
             None,
-            None,
+            Default::default(),
             Arc::new(MockCredential),
             Default::default(),
             None,
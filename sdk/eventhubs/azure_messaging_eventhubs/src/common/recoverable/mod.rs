@@ -7,7 +7,7 @@ mod management;
 pub(crate) mod receiver;
 mod sender;
 
-pub(crate) use connection::RecoverableConnection;
+pub(crate) use connection::{ConnectionTransport, RecoverableConnection};
 pub(crate) use sender::RecoverableSender;
 
 /// How many times a generation-guarded cache fill retries when a recovery races
@@ -5,10 +5,12 @@ use super::RecoverableConnection;
 use crate::common::retry::ErrorRecoveryAction;
 
 use crate::common::recover_azure_operation;
+use crate::producer::partition_publishing::PartitionPublisher;
 use azure_core::{error::ErrorKind as AzureErrorKind, http::Url};
 use azure_core_amqp::{
-    error::Result, AmqpError, AmqpErrorKind, AmqpMessage, AmqpSendOptions, AmqpSendOutcome,
-    AmqpSenderApis, AmqpSenderOptions, AmqpSession, AmqpTarget,
+    error::Result, AmqpError, AmqpErrorKind, AmqpMessage, AmqpOrderedMap, AmqpSendOptions,
+    AmqpSendOutcome, AmqpSender, AmqpSenderApis, AmqpSenderOptions, AmqpSession, AmqpSymbol,
+    AmqpTarget, AmqpValue,
 };
 use std::sync::{Arc, Weak};
 use tracing::{instrument, warn};
@@ -21,6 +23,7 @@ use tracing::{instrument, warn};
 pub(crate) struct RecoverableSender {
     recoverable_connection: Weak<RecoverableConnection>,
     path: Url,
+    publisher: Option<Arc<PartitionPublisher>>,
 }
 
 impl RecoverableSender {
@@ -30,13 +33,41 @@ impl RecoverableSender {
     ///
     /// * `recoverable_connection` - The recoverable connection to use for sending messages.
     /// * `path` - The URL path of the sender.
-    pub fn new(recoverable_connection: Weak<RecoverableConnection>, path: Url) -> Self {
+    /// * `publisher` - The publisher of the partition, when the sender publishes idempotently.
+    pub fn new(
+        recoverable_connection: Weak<RecoverableConnection>,
+        path: Url,
+        publisher: Option<Arc<PartitionPublisher>>,
+    ) -> Self {
         Self {
             recoverable_connection,
             path,
+            publisher,
         }
     }
 
+    fn connection(&self) -> Result<Arc<RecoverableConnection>> {
+        self.recoverable_connection.upgrade().ok_or_else(|| {
+            AmqpError::from(azure_core::Error::with_message(
+                AzureErrorKind::Other,
+                "Missing connection",
+            ))
+        })
+    }
+
+    async fn ensure_sender(&self) -> Result<Arc<AmqpSender>> {
+        self.connection()?
+            .ensure_sender(&self.path, self.publisher.as_ref())
+            .await
+            .map_err(|e| {
+                AmqpError::from(azure_core::Error::with_error(
+                    AzureErrorKind::Other,
+                    e,
+                    "Could not ensure sender",
+                ))
+            })
+    }
+
     fn should_retry_send_operation(e: &AmqpError) -> ErrorRecoveryAction {
         RecoverableConnection::should_retry_amqp_error(e)
     }
@@ -70,13 +101,16 @@ impl AmqpSenderApis for RecoverableSender {
                     #[cfg(test)]
                     connection.get_forced_error()?;
 
-                    let sender = connection.ensure_sender(&path).await.map_err(|e| {
-                        AmqpError::from(azure_core::Error::with_error(
-                            AzureErrorKind::Other,
-                            e,
-                            "Could not ensure sender",
-                        ))
-                    })?;
+                    let sender = connection
+                        .ensure_sender(&path, self.publisher.as_ref())
+                        .await
+                        .map_err(|e| {
+                            AmqpError::from(azure_core::Error::with_error(
+                                AzureErrorKind::Other,
+                                e,
+                                "Could not ensure sender",
+                            ))
+                        })?;
                     let outcome = sender.send_ref(message_clone.as_ref(), options).await?;
                     // We want to handle retries on the outcome - for instance, if we're throttled, the server rejects the send operation.
                     match outcome {
@@ -159,24 +193,10 @@ impl AmqpSenderApis for RecoverableSender {
     }
 
     async fn max_message_size(&self) -> Result<Option<u64>> {
-        self.recoverable_connection
-            .upgrade()
-            .ok_or_else(|| {
-                AmqpError::from(azure_core::Error::with_message(
-                    AzureErrorKind::Other,
-                    "Missing connection",
-                ))
-            })?
-            .ensure_sender(&self.path)
-            .await
-            .map_err(|e| {
-                AmqpError::from(azure_core::Error::with_error(
-                    AzureErrorKind::Other,
-                    e,
-                    "Could not ensure sender",
-                ))
-            })?
-            .max_message_size()
-            .await
+        self.ensure_sender().await?.max_message_size().await
+    }
+
+    async fn properties(&self) -> Result<Option<AmqpOrderedMap<AmqpSymbol, AmqpValue>>> {
+        self.ensure_sender().await?.properties().await
     }
 }
@@ -12,10 +12,17 @@ use azure_core_amqp::{
     error::AmqpErrorKind, AmqpDeliveryApis as _, AmqpError, AmqpReceiverApis as _,
     AmqpReceiverOptions, AmqpSource,
 };
-use futures::Stream;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use futures::{
+    channel::oneshot,
+    future::{select, Either, FutureExt as _, Shared},
+    Stream,
+};
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 use tracing::{debug, trace, warn, Instrument};
 
@@ -141,6 +148,10 @@ pub struct EventReceiver {
     // `close_receiver` could not detach by-value because an in-flight
     // receive holds a strong Arc on the AMQP receiver.
     closed: AtomicBool,
+    // Completed by `request_close()` so a receive that is waiting for the
+    // next delivery stops waiting.
+    close_sender: Mutex<Option<oneshot::Sender<()>>>,
+    close_requested: Shared<oneshot::Receiver<()>>,
 }
 
 impl EventReceiver {
@@ -152,6 +163,7 @@ impl EventReceiver {
         partition_id: String,
         timeout: Option<Duration>,
     ) -> Self {
+        let (close_sender, close_requested) = oneshot::channel();
         Self {
             source_url,
             connection,
@@ -160,6 +172,8 @@ impl EventReceiver {
             partition_id,
             timeout,
             closed: AtomicBool::new(false),
+            close_sender: Mutex::new(Some(close_sender)),
+            close_requested: close_requested.shared(),
         }
     }
 
@@ -237,11 +251,19 @@ impl EventReceiver {
                 ).instrument(span.clone()).await
                     .map_err(|e| translate_attach_error(e, &self.partition_id, &self.source_url))?;
 
-                let delivery = receiver
-                    .receive_delivery()
-                    .instrument(span.clone())
-                    .await
-                    .map_err(|e| translate_receive_error(e, &self.partition_id, &self.source_url))?;
+                let receive = pin!(receiver.receive_delivery().instrument(span.clone()));
+                let delivery = match select(receive, self.close_requested.clone()).await {
+                    Either::Left((delivery, _)) => delivery
+                        .map_err(|e| translate_receive_error(e, &self.partition_id, &self.source_url)),
+                    Either::Right(_) => {
+                        span.in_scope(|| debug!(
+                            partition_id = %self.partition_id,
+                            source_url = %self.source_url,
+                            "Event stream terminating: receive interrupted by request_close()."
+                        ));
+                        Err(EventHubsError::from(ErrorKind::ConsumerDisconnected(None)))
+                    }
+                }?;
 
                 // Now that we have a delivery, we can process it.
                 let message = delivery.into_message();
@@ -269,9 +291,18 @@ impl EventReceiver {
     /// Used by `EventProcessor` to revoke a partition while the consumer
     /// still holds an `Arc<PartitionClient>`. Sets the close flag before
     /// the detach so the next `stream_events()` poll resolves with
-    /// `ConsumerDisconnected` regardless of detach outcome.
+    /// `ConsumerDisconnected` regardless of detach outcome, and wakes a
+    /// receive that is waiting for the next delivery.
     pub(crate) async fn request_close(&self) -> Result<()> {
         self.closed.store(true, Ordering::Release);
+        if let Some(close_sender) = self
+            .close_sender
+            .lock()
+            .map_err(|_| EventHubsError::with_message("Could not lock close sender."))?
+            .take()
+        {
+            let _ = close_sender.send(());
+        }
         self.connection.close_receiver(&self.source_url).await
     }
 }
@@ -384,7 +415,7 @@ mod tests {
         let connection = RecoverableConnection::new(
             Url::parse("amqps://example.servicebus.windows.net").unwrap(),
             None,
-            None,
+            Default::default(),
             Arc::new(azure_core_test::credentials::MockCredential),
             Default::default(),
             None,
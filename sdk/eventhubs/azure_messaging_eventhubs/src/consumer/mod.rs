@@ -6,7 +6,10 @@
 pub(crate) mod event_receiver;
 
 use crate::{
-    common::{recoverable::RecoverableConnection, ManagementInstance},
+    common::{
+        recoverable::{ConnectionTransport, RecoverableConnection},
+        ManagementInstance,
+    },
     error::Result,
     models::{ConsumerClientDetails, EventHubPartitionProperties, EventHubProperties},
     EventHubsError, RetryOptions,
@@ -43,7 +46,7 @@ struct ConsumerClientOptions {
     application_id: Option<String>,
     instance_id: Option<String>,
     retry_options: Option<RetryOptions>,
-    custom_endpoint: Option<Url>,
+    transport: ConnectionTransport,
     cbs_token_type: Option<&'static str>,
 }
 
@@ -97,7 +100,7 @@ impl ConsumerClient {
             recoverable_connection: RecoverableConnection::new(
                 url.clone(),
                 options.application_id,
-                options.custom_endpoint,
+                options.transport,
                 credential,
                 retry_options,
                 options.cbs_token_type,
@@ -193,7 +196,7 @@ impl ConsumerClient {
                 application_id: None,
                 instance_id: None,
                 retry_options: None,
-                custom_endpoint: None,
+                transport: ConnectionTransport::default(),
                 cbs_token_type: None,
             },
         )
@@ -610,6 +613,7 @@ pub mod builders {
         },
         Result,
     };
+    use azure_core_amqp::{AmqpProxyOptions, AmqpTransportType};
     use std::sync::Arc;
 
     /// A builder for creating a [`ConsumerClient`].
@@ -637,6 +641,8 @@ pub mod builders {
         instance_id: Option<String>,
         retry_options: Option<RetryOptions>,
         custom_endpoint: Option<String>,
+        transport_type: Option<AmqpTransportType>,
+        proxy: Option<AmqpProxyOptions>,
     }
 
     impl ConsumerClientBuilder {
@@ -709,6 +715,23 @@ pub mod builders {
             self
         }
 
+        /// Sets the transport used to connect to the Event Hub.
+        ///
+        /// Use [`AmqpTransportType::AmqpWebSockets`] to connect over port 443 when the AMQP port
+        /// is blocked. WebSockets require the `websocket` feature.
+        pub fn with_transport_type(mut self, transport_type: AmqpTransportType) -> Self {
+            self.transport_type = Some(transport_type);
+            self
+        }
+
+        /// Sets the HTTP proxy used to connect to the Event Hub.
+        ///
+        /// A proxy can only be used with [`AmqpTransportType::AmqpWebSockets`].
+        pub fn with_proxy(mut self, proxy: AmqpProxyOptions) -> Self {
+            self.proxy = Some(proxy);
+            self
+        }
+
         /// Opens a connection to the Event Hub.
         ///
         /// This method establishes a connection to the Event Hubs instance associated
@@ -764,7 +787,11 @@ pub mod builders {
                     application_id: self.application_id,
                     instance_id: self.instance_id,
                     retry_options: self.retry_options,
-                    custom_endpoint,
+                    transport: ConnectionTransport {
+                        custom_endpoint,
+                        transport_type: self.transport_type,
+                        proxy: self.proxy,
+                    },
                     cbs_token_type: None,
                 },
             )?;
@@ -835,7 +862,11 @@ pub mod builders {
                     application_id: self.application_id,
                     instance_id: self.instance_id,
                     retry_options: self.retry_options,
-                    custom_endpoint,
+                    transport: ConnectionTransport {
+                        custom_endpoint,
+                        transport_type: self.transport_type,
+                        proxy: self.proxy,
+                    },
                     cbs_token_type: Some(SAS_TOKEN_TYPE),
                 },
             )?;
@@ -51,6 +51,18 @@ pub enum ErrorKind {
     /// `matches!(err.kind, ErrorKind::ConsumerDisconnected(_))`.
     /// Mirrors `EventHubsException.FailureReason.ConsumerDisconnected` (.NET).
     ConsumerDisconnected(Option<AmqpDescribedError>),
+
+    /// An idempotent send was refused because the sequence numbers of its events don't follow
+    /// the last sequence number the producer group published to the partition.
+    ///
+    /// Another producer published as the same producer group, or the publishing state was reset.
+    /// Mirrors `EventHubsException.FailureReason.InvalidClientState` (.NET).
+    SequenceOutOfOrder(Option<AmqpDescribedError>),
+
+    /// An idempotent producer was disconnected by the broker because another producer attached
+    /// to the partition as the same producer group with a higher owner level.
+    /// Mirrors `EventHubsException.FailureReason.ProducerDisconnected` (.NET).
+    ProducerDisconnected(Option<AmqpDescribedError>),
 }
 
 /// Represents an error that can occur in the Event Hubs module.
@@ -102,6 +114,16 @@ impl std::fmt::Display for EventHubsError {
                     e
                 )
             }
+            ErrorKind::SequenceOutOfOrder(e) => {
+                write!(f, "Publishing sequence number out of order: {:?}", e)
+            }
+            ErrorKind::ProducerDisconnected(e) => {
+                write!(
+                    f,
+                    "Producer disconnected by broker (producer group taken over): {:?}",
+                    e
+                )
+            }
         }
     }
 }
@@ -130,25 +152,33 @@ const MAX_ERROR_CHAIN_DEPTH: usize = 16;
 /// by the `ensure_*` wrappers. Both must be recognized, so the whole chain is
 /// walked instead of only the top-level kind.
 pub(crate) fn find_link_stolen(error: &AmqpError) -> Option<&AmqpDescribedError> {
+    find_described_error(error, AmqpErrorCondition::LinkStolen)
+}
+
+/// Returns the described error with `condition` if `error` is one, or wraps
+/// one in its [`std::error::Error::source`] chain.
+pub(crate) fn find_described_error(
+    error: &AmqpError,
+    condition: AmqpErrorCondition,
+) -> Option<&AmqpDescribedError> {
     use std::error::Error as _;
-    fn described(e: &AmqpError) -> Option<&AmqpDescribedError> {
+    fn described<'a>(
+        e: &'a AmqpError,
+        condition: &AmqpErrorCondition,
+    ) -> Option<&'a AmqpDescribedError> {
         match e.kind() {
-            AmqpErrorKind::AmqpDescribedError(d)
-                if matches!(d.condition, AmqpErrorCondition::LinkStolen) =>
-            {
-                Some(d)
-            }
+            AmqpErrorKind::AmqpDescribedError(d) if &d.condition == condition => Some(d),
             _ => None,
         }
     }
-    if let Some(d) = described(error) {
+    if let Some(d) = described(error, &condition) {
         return Some(d);
     }
     let mut cause: Option<&(dyn std::error::Error + 'static)> = error.source();
     for _ in 0..MAX_ERROR_CHAIN_DEPTH {
         let c = cause?;
         if let Some(amqp) = c.downcast_ref::<AmqpError>() {
-            if let Some(d) = described(amqp) {
+            if let Some(d) = described(amqp, &condition) {
                 return Some(d);
             }
         }
@@ -454,6 +484,8 @@ mod tests {
             EventHubsError::from(ErrorKind::AmqpError(AmqpError::from(
                 azure_core_amqp::AmqpErrorKind::SimpleMessage(Cow::Borrowed("AMQP")),
             ))),
+            EventHubsError::from(ErrorKind::SequenceOutOfOrder(None)),
+            EventHubsError::from(ErrorKind::ProducerDisconnected(None)),
         ];
 
         for error in errors {
@@ -0,0 +1,411 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Callbacks used when the [`EventProcessor`](crate::EventProcessor) runs the partitions it owns.
+
+use super::{models::Checkpoint, partition_client::PartitionClient};
+use crate::{
+    error::{ErrorKind, Result},
+    models::ReceivedEventData,
+    EventHubsError,
+};
+use azure_core::time::{Duration, OffsetDateTime};
+use futures::{
+    future::{select, Either},
+    StreamExt,
+};
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tracing::{debug, warn};
+
+/// The reason the event processor stopped processing a partition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PartitionCloseReason {
+    /// The event processor is shutting down.
+    Shutdown,
+
+    /// Another event processor claimed the partition.
+    OwnershipLost,
+
+    /// Receiving events from the partition failed. The error was reported to
+    /// [`EventProcessorHandler::on_error`] first, and the partition is processed again after the
+    /// next load balancing cycle.
+    Error,
+}
+
+/// The partition the event processor is about to start processing.
+#[derive(Clone, Debug)]
+pub struct PartitionInitializingContext {
+    /// The id of the partition.
+    pub partition_id: String,
+}
+
+/// The partition the event processor stopped processing.
+#[derive(Clone, Debug)]
+pub struct PartitionClosingContext {
+    /// The id of the partition.
+    pub partition_id: String,
+
+    /// The reason processing stopped.
+    pub reason: PartitionCloseReason,
+}
+
+/// A batch of events received from a partition.
+pub struct EventBatchContext {
+    /// The id of the partition the events were received from.
+    pub partition_id: String,
+
+    /// The events, in the order they were received. A batch is never empty.
+    pub events: Vec<ReceivedEventData>,
+}
+
+/// An error that occurred while processing a partition.
+#[derive(Debug)]
+pub struct ProcessingErrorContext {
+    /// The id of the partition.
+    pub partition_id: String,
+
+    /// The error.
+    pub error: EventHubsError,
+}
+
+/// Processes the events of the partitions an [`EventProcessor`](crate::EventProcessor) owns.
+///
+/// When a handler is set with
+/// [`EventProcessorBuilder::with_event_handler`](crate::builders::EventProcessorBuilder::with_event_handler),
+/// the processor receives from every partition it claims in a task of its own and calls the
+/// handler, instead of handing out partition clients from
+/// [`next_partition_client`](crate::EventProcessor::next_partition_client). The calls for one
+/// partition are never concurrent; the calls for different partitions are.
+#[async_trait::async_trait]
+pub trait EventProcessorHandler: Send + Sync {
+    /// Called before the processor starts receiving from a partition.
+    ///
+    /// Returning an error reports it to [`on_error`](EventProcessorHandler::on_error) and releases
+    /// the partition, which is claimed again in the next load balancing cycle.
+    async fn on_partition_initializing(
+        &self,
+        _context: PartitionInitializingContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called with each batch of events received from a partition.
+    ///
+    /// When this method succeeds, the events count as processed and are checkpointed according
+    /// to the processor's [`CheckpointPolicy`]. Returning an error reports it to
+    /// [`on_error`](EventProcessorHandler::on_error); the events are not checkpointed, and the
+    /// processor continues with the next batch.
+    async fn on_events(&self, context: &EventBatchContext) -> Result<()>;
+
+    /// Called after the processor stopped receiving from a partition.
+    async fn on_partition_closing(&self, _context: PartitionClosingContext) {}
+
+    /// Called when receiving, processing or checkpointing the events of a partition fails.
+    async fn on_error(&self, context: ProcessingErrorContext);
+}
+
+/// When the event processor writes checkpoints for the events its
+/// [`EventProcessorHandler`] processed.
+///
+/// A checkpoint is written when either threshold is reached. If neither is set, a checkpoint is
+/// written after every batch. The checkpoint that is pending when the processor shuts down, or
+/// stops processing a partition because receiving failed, is always written; it is discarded when
+/// the partition is claimed by another processor, which may already have checkpointed later
+/// events.
+#[derive(Clone, Debug, Default)]
+pub struct CheckpointPolicy {
+    /// Write a checkpoint once at least this many events were processed since the last one.
+    pub event_count: Option<usize>,
+
+    /// Write a checkpoint once this much time passed since the last one, if any events were
+    /// processed since.
+    pub interval: Option<Duration>,
+}
+
+/// Tracks the events processed since the last checkpoint of a partition.
+struct CheckpointTracker {
+    policy: CheckpointPolicy,
+    pending: Option<Checkpoint>,
+    pending_events: usize,
+    last_checkpoint: OffsetDateTime,
+}
+
+impl CheckpointTracker {
+    fn new(policy: CheckpointPolicy, now: OffsetDateTime) -> Self {
+        Self {
+            policy,
+            pending: None,
+            pending_events: 0,
+            last_checkpoint: now,
+        }
+    }
+
+    /// Records that `event_count` events were processed, the last of them checkpointed by
+    /// `checkpoint`.
+    fn record(&mut self, checkpoint: Option<Checkpoint>, event_count: usize) {
+        if checkpoint.is_some() {
+            self.pending = checkpoint;
+        }
+        self.pending_events += event_count;
+    }
+
+    fn is_due(&self, now: OffsetDateTime) -> bool {
+        if self.pending.is_none() {
+            return false;
+        }
+        match (self.policy.event_count, self.policy.interval) {
+            (None, None) => true,
+            (event_count, interval) => {
+                event_count.is_some_and(|event_count| self.pending_events >= event_count)
+                    || interval.is_some_and(|interval| now - self.last_checkpoint >= interval)
+            }
+        }
+    }
+
+    /// The time left until a pending checkpoint is due by the interval, if there is one.
+    fn time_until_due(&self, now: OffsetDateTime) -> Option<Duration> {
+        self.pending.as_ref()?;
+        let interval = self.policy.interval?;
+        Some((self.last_checkpoint + interval - now).max(Duration::ZERO))
+    }
+
+    fn take(&mut self, now: OffsetDateTime) -> Option<Checkpoint> {
+        self.pending_events = 0;
+        self.last_checkpoint = now;
+        self.pending.take()
+    }
+}
+
+/// Options for the tasks that process partitions with an [`EventProcessorHandler`].
+#[derive(Clone)]
+pub(crate) struct PartitionProcessing {
+    pub handler: Arc<dyn EventProcessorHandler>,
+    pub checkpoint_policy: CheckpointPolicy,
+    pub max_batch_size: usize,
+    pub shutting_down: Arc<AtomicBool>,
+}
+
+impl PartitionProcessing {
+    /// Receives from a partition and calls the handler until the partition is closed, then
+    /// releases the partition client.
+    pub(crate) async fn run(self, client: Arc<PartitionClient>) {
+        let partition_id = client.get_partition_id().to_string();
+        let initializing = PartitionInitializingContext {
+            partition_id: partition_id.clone(),
+        };
+        if let Err(error) = self.handler.on_partition_initializing(initializing).await {
+            self.report_error(&partition_id, error).await;
+            client.release().await;
+            return;
+        }
+
+        let reason = self.receive(&client, &partition_id).await;
+        debug!(partition_id = %partition_id, ?reason, "Stopped processing partition.");
+        client.release().await;
+        self.handler
+            .on_partition_closing(PartitionClosingContext {
+                partition_id,
+                reason,
+            })
+            .await;
+    }
+
+    async fn receive(&self, client: &PartitionClient, partition_id: &str) -> PartitionCloseReason {
+        let mut tracker =
+            CheckpointTracker::new(self.checkpoint_policy.clone(), OffsetDateTime::now_utc());
+        let mut batches = client.stream_events().ready_chunks(self.max_batch_size);
+        let reason = loop {
+            let next = match tracker.time_until_due(OffsetDateTime::now_utc()) {
+                Some(wait) => {
+                    match select(batches.next(), pin!(azure_core::sleep::sleep(wait))).await {
+                        Either::Left((batch, _)) => batch,
+                        Either::Right(_) => {
+                            self.checkpoint(client, &mut tracker).await;
+                            continue;
+                        }
+                    }
+                }
+                None => batches.next().await,
+            };
+            let Some(results) = next else {
+                break self.close_reason(None);
+            };
+
+            // Process the events received before an error, then stop at the error.
+            let mut events = Vec::with_capacity(results.len());
+            let mut error = None;
+            for result in results {
+                match result {
+                    Ok(event) => events.push(event),
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
+            }
+            if !events.is_empty() {
+                self.process(client, partition_id, events, &mut tracker)
+                    .await;
+            }
+            if let Some(error) = error {
+                let reason = self.close_reason(Some(&error));
+                if reason == PartitionCloseReason::Error {
+                    self.report_error(partition_id, error).await;
+                }
+                break reason;
+            }
+        };
+
+        if reason != PartitionCloseReason::OwnershipLost {
+            self.checkpoint(client, &mut tracker).await;
+        }
+        reason
+    }
+
+    async fn process(
+        &self,
+        client: &PartitionClient,
+        partition_id: &str,
+        events: Vec<ReceivedEventData>,
+        tracker: &mut CheckpointTracker,
+    ) {
+        let context = EventBatchContext {
+            partition_id: partition_id.to_string(),
+            events,
+        };
+        if let Err(error) = self.handler.on_events(&context).await {
+            self.report_error(partition_id, error).await;
+            return;
+        }
+        let last = context
+            .events
+            .last()
+            .and_then(|event| client.checkpoint_for(event));
+        tracker.record(last, context.events.len());
+        if tracker.is_due(OffsetDateTime::now_utc()) {
+            self.checkpoint(client, tracker).await;
+        }
+    }
+
+    async fn checkpoint(&self, client: &PartitionClient, tracker: &mut CheckpointTracker) {
+        let Some(checkpoint) = tracker.take(OffsetDateTime::now_utc()) else {
+            return;
+        };
+        if let Err(error) = client.store_checkpoint(checkpoint.clone()).await {
+            // Keep the checkpoint so it is written with the next one that is due.
+            tracker.record(Some(checkpoint), 0);
+            self.report_error(client.get_partition_id(), error).await;
+        }
+    }
+
+    fn close_reason(&self, error: Option<&EventHubsError>) -> PartitionCloseReason {
+        if self.shutting_down.load(Ordering::Acquire) {
+            return PartitionCloseReason::Shutdown;
+        }
+        match error {
+            Some(EventHubsError {
+                kind: ErrorKind::ConsumerDisconnected(_),
+            })
+            | None => PartitionCloseReason::OwnershipLost,
+            Some(_) => PartitionCloseReason::Error,
+        }
+    }
+
+    async fn report_error(&self, partition_id: &str, error: EventHubsError) {
+        warn!(partition_id = %partition_id, err = ?error, "Error processing partition.");
+        self.handler
+            .on_error(ProcessingErrorContext {
+                partition_id: partition_id.to_string(),
+                error,
+            })
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkpoint(sequence_number: i64) -> Option<Checkpoint> {
+        Some(Checkpoint {
+            partition_id: "0".to_string(),
+            sequence_number: Some(sequence_number),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn default_policy_checkpoints_every_batch() {
+        let now = OffsetDateTime::now_utc();
+        let mut tracker = CheckpointTracker::new(CheckpointPolicy::default(), now);
+        assert!(!tracker.is_due(now));
+
+        tracker.record(checkpoint(3), 4);
+        assert!(tracker.is_due(now));
+        assert_eq!(tracker.take(now).unwrap().sequence_number, Some(3));
+        assert!(!tracker.is_due(now));
+        assert!(tracker.take(now).is_none());
+    }
+
+    #[test]
+    fn event_count_policy_waits_for_enough_events() {
+        let now = OffsetDateTime::now_utc();
+        let mut tracker = CheckpointTracker::new(
+            CheckpointPolicy {
+                event_count: Some(10),
+                interval: None,
+            },
+            now,
+        );
+        tracker.record(checkpoint(5), 6);
+        assert!(!tracker.is_due(now));
+        assert!(tracker.time_until_due(now).is_none());
+
+        tracker.record(checkpoint(9), 4);
+        assert!(tracker.is_due(now));
+        assert_eq!(tracker.take(now).unwrap().sequence_number, Some(9));
+    }
+
+    #[test]
+    fn interval_policy_waits_for_the_interval() {
+        let start = OffsetDateTime::now_utc();
+        let mut tracker = CheckpointTracker::new(
+            CheckpointPolicy {
+                event_count: None,
+                interval: Some(Duration::seconds(10)),
+            },
+            start,
+        );
+        assert!(tracker.time_until_due(start).is_none());
+
+        tracker.record(checkpoint(1), 1);
+        let later = start + Duration::seconds(4);
+        assert!(!tracker.is_due(later));
+        assert_eq!(tracker.time_until_due(later), Some(Duration::seconds(6)));
+
+        let due = start + Duration::seconds(10);
+        assert!(tracker.is_due(due));
+        assert_eq!(
+            tracker.time_until_due(start + Duration::seconds(12)),
+            Some(Duration::ZERO)
+        );
+        tracker.take(due);
+        assert!(!tracker.is_due(due + Duration::seconds(20)));
+    }
+
+    #[test]
+    fn events_without_annotations_keep_the_pending_checkpoint() {
+        let now = OffsetDateTime::now_utc();
+        let mut tracker = CheckpointTracker::new(CheckpointPolicy::default(), now);
+        tracker.record(checkpoint(2), 1);
+        tracker.record(None, 1);
+        assert_eq!(tracker.take(now).unwrap().sequence_number, Some(2));
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation. All Rights reserved
 // Licensed under the MIT license.
 
+pub(crate) mod handler;
 pub(crate) mod load_balancer;
 pub(crate) mod models;
 pub(crate) mod partition_client;
@@ -86,7 +86,7 @@ impl PartitionClient {
     pub fn stream_events(&self) -> impl Stream<Item = Result<ReceivedEventData>> + '_ {
         if let Some(event_receiver) = self.event_receiver.get() {
             Box::pin(event_receiver.stream_events())
-                as Pin<Box<dyn Stream<Item = Result<ReceivedEventData>> + '_>>
+                as Pin<Box<dyn Stream<Item = Result<ReceivedEventData>> + Send + '_>>
         } else {
             warn!(
                 partition_id = %self.partition_id,
@@ -153,14 +153,21 @@ impl PartitionClient {
     /// # Errors
     /// Returns an error if the sequence number or offset is invalid, or if updating the checkpoint fails.
     pub async fn update_checkpoint(&self, event_data: &ReceivedEventData) -> Result<()> {
+        let Some(checkpoint) = self.checkpoint_for(event_data) else {
+            // No message annotations. Nothing to do.
+            return Ok(());
+        };
+        self.store_checkpoint(checkpoint).await
+    }
+
+    /// Creates the checkpoint that records `event_data` as processed, or `None` if the event has
+    /// no message annotations to take the sequence number and offset from.
+    pub(crate) fn checkpoint_for(&self, event_data: &ReceivedEventData) -> Option<Checkpoint> {
         let mut offset_option = None;
         let mut sequence_number_option = None;
 
         let event_data_message = event_data.raw_amqp_message();
-        let Some(message_annotations) = event_data_message.message_annotations.as_ref() else {
-            // No message annotations. Nothing to do.
-            return Ok(());
-        };
+        let message_annotations = event_data_message.message_annotations.as_ref()?;
         for (key, value) in message_annotations.0.iter() {
             let AmqpAnnotationKey::Symbol(symbol) = key else {
                 continue;
@@ -179,20 +186,24 @@ impl PartitionClient {
             }
         }
 
-        debug!(
-            partition_id = %self.partition_id,
-            sequence_number = ?sequence_number_option,
-            offset = ?offset_option,
-            "Updating checkpoint for partition."
-        );
-        let checkpoint = Checkpoint {
+        Some(Checkpoint {
             fully_qualified_namespace: self.client_details.fully_qualified_namespace.clone(),
             event_hub_name: self.client_details.eventhub_name.clone(),
             consumer_group: self.client_details.consumer_group.clone(),
             partition_id: self.partition_id.clone(),
             offset: offset_option,
             sequence_number: sequence_number_option,
-        };
+        })
+    }
+
+    /// Writes a checkpoint for this partition to the checkpoint store.
+    pub(crate) async fn store_checkpoint(&self, checkpoint: Checkpoint) -> Result<()> {
+        debug!(
+            partition_id = %self.partition_id,
+            sequence_number = ?checkpoint.sequence_number,
+            offset = ?checkpoint.offset,
+            "Updating checkpoint for partition."
+        );
         self.checkpoint_store
             .update_checkpoint(checkpoint)
             .await
@@ -205,6 +216,30 @@ impl PartitionClient {
             })
     }
 
+    /// Closes the partition client when it is shared, as it is by the processor's partition
+    /// tasks: the receiver is closed and the client is removed from the processor's consumers
+    /// map even if another reference to the client is still alive.
+    pub(crate) async fn release(self: Arc<Self>) {
+        match Arc::try_unwrap(self) {
+            Ok(client) => {
+                let partition_id = client.partition_id.clone();
+                if let Err(e) = client.close().await {
+                    warn!(
+                        partition_id = %partition_id,
+                        err = ?e,
+                        "Failed to close partition client."
+                    );
+                }
+            }
+            Err(client) => {
+                client.request_close_receiver().await;
+                if let Some(consumers) = client.consumers.upgrade() {
+                    let _ = consumers.remove_partition_client(&client.partition_id);
+                }
+            }
+        }
+    }
+
     pub(crate) fn set_event_receiver(&self, event_receiver: EventReceiver) -> Result<()> {
         // Set the event receiver
         self.event_receiver.set(event_receiver).map_err(|_| {
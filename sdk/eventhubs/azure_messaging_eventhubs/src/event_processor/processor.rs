@@ -3,6 +3,7 @@
 
 //use async_channel::{bounded, Receiver, Sender};
 use super::{
+    handler::PartitionProcessing,
     load_balancer::LoadBalancer,
     models::{Checkpoint, StartPositions},
     partition_client::PartitionClient,
@@ -14,13 +15,19 @@ use crate::{
 };
 //use async_io::Timer;
 use async_lock::Mutex as AsyncMutex;
-use azure_core::{error::ErrorKind as AzureErrorKind, time::Duration, Error};
+use azure_core::{
+    async_runtime::{get_async_runtime, SpawnedTask},
+    error::ErrorKind as AzureErrorKind,
+    time::Duration,
+    Error,
+};
 use futures::{
     channel::mpsc::{channel, Receiver, Sender},
     SinkExt, StreamExt,
 };
 use std::{
     sync::{
+        atomic::Ordering,
         Arc,
         Mutex as SyncMutex, // Mutex for blocking operations
     },
@@ -52,6 +59,15 @@ const PROCESSOR_OWNER_LEVEL: i64 = 0;
 /// To use a different epoch, open receivers directly via
 /// `ConsumerClient::open_receiver_on_partition`.
 ///
+/// By default, the partitions the processor claims are handed out by
+/// [`next_partition_client`](EventProcessor::next_partition_client), and the
+/// application receives from them and updates checkpoints itself. When an
+/// [`EventProcessorHandler`](crate::EventProcessorHandler) is set with
+/// [`EventProcessorBuilder::with_event_handler`](builders::EventProcessorBuilder::with_event_handler),
+/// the processor instead receives from each partition in a task of its own,
+/// calls the handler with batches of events, and writes checkpoints according
+/// to its [`CheckpointPolicy`](crate::CheckpointPolicy).
+///
 /// For more information on Event Processors and scenarios in which you would
 /// use an Event Processor, see the [Event Processor documentation](https://learn.microsoft.com/azure/event-hubs/event-processor-balance-partition-load).
 ///
@@ -67,6 +83,8 @@ pub struct EventProcessor {
     start_positions: StartPositions,
     is_running: std::sync::Mutex<bool>,
     partition_ids: Vec<String>,
+    partition_processing: Option<PartitionProcessing>,
+    partition_tasks: SyncMutex<HashMap<String, SpawnedTask>>,
 }
 
 struct EventProcessorOptions {
@@ -76,6 +94,7 @@ struct EventProcessorOptions {
     start_positions: StartPositions,
     prefetch: u32,
     partition_ids: Vec<String>,
+    partition_processing: Option<PartitionProcessing>,
 }
 
 pub(crate) struct ProcessorConsumersMap {
@@ -213,6 +232,8 @@ impl EventProcessor {
             next_partition_clients: AsyncMutex::new(receiver),
             is_running: std::sync::Mutex::new(false),
             partition_ids: options.partition_ids,
+            partition_processing: options.partition_processing,
+            partition_tasks: SyncMutex::new(HashMap::new()),
         }))
     }
 
@@ -277,7 +298,7 @@ impl EventProcessor {
             .map(String::as_str)
             .collect::<Vec<&str>>();
 
-        loop {
+        let result = loop {
             let result = self.dispatch(partition_ids, &consumers).await;
             match result {
                 Ok(_) => {
@@ -285,15 +306,49 @@ impl EventProcessor {
                 }
                 Err(e) => {
                     error!(err = ?e, "Error dispatching event processor.");
-                    return Err(e);
+                    break Err(e);
                 }
             }
             debug!("Event processor sleeping for {:?}", self.update_interval);
             azure_core::sleep::sleep(self.update_interval).await;
             debug!("Event processor woke up from sleep.");
-            if self.is_shutdown()? {
-                info!("Event processor shutting down.");
-                break Ok(());
+            match self.is_shutdown() {
+                Ok(false) => {}
+                Ok(true) => {
+                    info!("Event processor shutting down.");
+                    break Ok(());
+                }
+                Err(e) => break Err(e),
+            }
+        };
+        self.stop_partition_tasks(&consumers).await;
+        result
+    }
+
+    /// Stops the tasks processing partitions with the event handler, and waits for them to
+    /// write their pending checkpoints and close their partitions.
+    async fn stop_partition_tasks(&self, consumers: &ProcessorConsumersMap) {
+        let Some(partition_processing) = &self.partition_processing else {
+            return;
+        };
+        partition_processing
+            .shutting_down
+            .store(true, Ordering::Release);
+        if let Ok(partition_ids) = consumers.get_active_partition_ids() {
+            if let Err(e) = consumers.revoke_partition_clients(&partition_ids).await {
+                warn!(err = ?e, "Failed to close partition receivers on shutdown.");
+            }
+        }
+        let tasks: Vec<SpawnedTask> = match self.partition_tasks.lock() {
+            Ok(mut tasks) => tasks.drain().map(|(_, task)| task).collect(),
+            Err(_) => {
+                warn!("Could not lock partition tasks on shutdown.");
+                return;
+            }
+        };
+        for task in tasks {
+            if let Err(e) = task.await {
+                warn!(err = ?e, "Partition processing task failed.");
             }
         }
     }
@@ -479,6 +534,19 @@ impl EventProcessor {
             return Err(e);
         }
 
+        if let Some(partition_processing) = &self.partition_processing {
+            debug!(partition_id = %partition_id, "Starting task to process partition.");
+            let task = get_async_runtime()
+                .spawn(Box::pin(partition_processing.clone().run(partition_client)));
+            // A task left in the map for the partition has finished: its partition client was
+            // removed from the consumers map, which is what allowed this one to be added.
+            self.partition_tasks
+                .lock()
+                .map_err(|_| EventHubsError::with_message("Could not lock partition tasks."))?
+                .insert(partition_id, task);
+            return Ok(());
+        }
+
         debug!(partition_id = %partition_id, "Adding partition client to queue.");
 
         // Send the partition client to the next partition client receiver
@@ -502,8 +570,17 @@ impl EventProcessor {
     /// Retrieves the next partition client for processing events.
     ///
     /// This method returns the next available partition client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the processor was built with an event handler, which processes the
+    /// partitions instead.
     pub async fn next_partition_client(&self) -> Result<Arc<PartitionClient>> {
-        // Implement the function or remove it if not needed
+        if self.partition_processing.is_some() {
+            return Err(EventHubsError::with_message(
+                "Partitions are processed by the event handler, not handed out as partition clients.",
+            ));
+        }
         debug!("next_partition_client: Waiting to receive the next partition client.");
 
         {
@@ -636,12 +713,17 @@ impl EventProcessor {
 }
 
 pub mod builders {
-    use super::{CheckpointStore, EventProcessor};
-    use crate::{error::Result, event_processor::models::StartPositions, ConsumerClient};
+    use super::{CheckpointStore, EventProcessor, PartitionProcessing};
+    use crate::ConsumerClient;
+    use crate::{
+        error::Result, event_processor::models::StartPositions, CheckpointPolicy, EventHubsError,
+        EventProcessorHandler,
+    };
     use azure_core::time::Duration;
-    use std::sync::Arc;
+    use std::sync::{atomic::AtomicBool, Arc};
 
     const DEFAULT_PREFETCH: u32 = 300;
+    const DEFAULT_MAX_BATCH_SIZE: usize = 100;
     const DEFAULT_UPDATE_INTERVAL: Duration = Duration::seconds(30);
     const DEFAULT_PARTITION_EXPIRATION_DURATION: Duration = Duration::seconds(60);
 
@@ -682,6 +764,9 @@ pub mod builders {
         prefetch: Option<u32>,
         load_balancing_strategy: Option<super::ProcessorStrategy>,
         partition_expiration_duration: Option<Duration>,
+        event_handler: Option<Arc<dyn EventProcessorHandler>>,
+        checkpoint_policy: Option<CheckpointPolicy>,
+        max_batch_size: Option<usize>,
     }
     /// Returns an error if `partition_expiration_duration` is not strictly
     /// greater than `update_interval`. When expiration is shorter than the
@@ -758,6 +843,35 @@ pub mod builders {
             self
         }
 
+        /// Sets the handler the event processor calls with the events of the partitions it owns.
+        ///
+        /// With a handler, [`EventProcessor::run`] receives from every partition it claims in a
+        /// task of its own, and stops the tasks before it returns.
+        /// [`EventProcessor::next_partition_client`] cannot be used.
+        pub fn with_event_handler(mut self, event_handler: Arc<dyn EventProcessorHandler>) -> Self {
+            self.event_handler = Some(event_handler);
+            self
+        }
+
+        /// Sets when checkpoints are written for the events the event handler processed.
+        ///
+        /// By default, a checkpoint is written after every batch. This option only applies when
+        /// an event handler is set.
+        pub fn with_checkpoint_policy(mut self, checkpoint_policy: CheckpointPolicy) -> Self {
+            self.checkpoint_policy = Some(checkpoint_policy);
+            self
+        }
+
+        /// Sets the maximum number of events passed to the event handler in one batch.
+        ///
+        /// A batch holds the events that have already been received when the handler is called,
+        /// up to this number. The default is 100. This option only applies when an event handler
+        /// is set.
+        pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+            self.max_batch_size = Some(max_batch_size);
+            self
+        }
+
         /// Builds the event processor with the specified consumer client and checkpoint store.
         /// Returns a `Result` containing the constructed `EventProcessor`.
         pub async fn build(
@@ -772,6 +886,19 @@ pub mod builders {
 
             validate_expiration_vs_update_interval(partition_expiration_duration, update_interval)?;
 
+            let max_batch_size = self.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+            if max_batch_size == 0 {
+                return Err(EventHubsError::with_message(
+                    "max_batch_size must be greater than zero.",
+                ));
+            }
+            let partition_processing = self.event_handler.map(|handler| PartitionProcessing {
+                handler,
+                checkpoint_policy: self.checkpoint_policy.unwrap_or_default(),
+                max_batch_size,
+                shutting_down: Arc::new(AtomicBool::new(false)),
+            });
+
             // Retrieve the set of partitions from the consumer client
             // and limit the number of partitions to the specified max_partition_count.
             let mut eh_properties = consumer_client.get_eventhub_properties().await?;
@@ -791,6 +918,7 @@ pub mod builders {
                     start_positions: self.start_positions.unwrap_or_default(),
                     prefetch: self.prefetch.unwrap_or(DEFAULT_PREFETCH),
                     partition_ids: eh_properties.partition_ids,
+                    partition_processing,
                 },
             )
         }
@@ -836,6 +964,7 @@ mod tests {
                 start_positions: StartPositions::default(),
                 prefetch: 300,
                 partition_ids: partition_ids.iter().map(|id| id.to_string()).collect(),
+                partition_processing: None,
             },
         )
         .expect("the processor must build");
@@ -0,0 +1,583 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use crate::{
+    models::{Checkpoint, Ownership},
+    CheckpointStore,
+};
+use azure_core::{
+    error::ErrorKind as AzureErrorKind, http::Etag, time::OffsetDateTime, Error, Result, Uuid,
+};
+use fd_lock::RwLock as FileLock;
+use futures::channel::oneshot;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, trace, warn};
+
+/// The characters kept as they are in the file and directory names of the store. Every other
+/// character is percent-encoded, so a name cannot contain a path separator.
+const NAME_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'$');
+
+const RECORD_EXTENSION: &str = "json";
+const LOCK_EXTENSION: &str = "lock";
+const CHECKPOINT_DIRECTORY: &str = "checkpoint";
+const OWNERSHIP_DIRECTORY: &str = "ownership";
+
+/// How long to wait before trying again to lock an ownership that another process has locked.
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(10);
+
+/// How long to wait for another process to release the lock of an ownership before failing.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A checkpoint store that keeps checkpoints and ownerships in files in a local directory.
+///
+/// The store is meant for event processors that run on a single machine without a storage
+/// account, and that need their progress to survive a restart. Processes on the same machine
+/// can share a directory:
+///
+/// * Every record is written to a temporary file that is then renamed over the record, so a
+///   reader never sees a partially written record.
+/// * An ownership is claimed while holding an advisory lock on a file next to it, so only one
+///   process can claim a partition from a given ownership.
+/// * The ETag of an ownership is a version number that increases with every claim.
+///
+/// File operations run on a separate thread, so they do not block the async runtime, and a
+/// claim fails if another process holds the lock of its partition for more than 10 seconds.
+///
+/// The directory is laid out the same way as the blob names of the blob checkpoint store:
+/// `<namespace>/<event hub>/<consumer group>/checkpoint/<partition id>.json` and
+/// `<namespace>/<event hub>/<consumer group>/ownership/<partition id>.json`.
+///
+/// The store does not share a directory safely across machines, for example on a network file
+/// system, because advisory locks are not reliable there.
+#[derive(Clone, Debug)]
+pub struct FileCheckpointStore {
+    directory: PathBuf,
+    lock_timeout: Duration,
+}
+
+/// A checkpoint, as stored in its file.
+#[derive(Debug, Deserialize, Serialize)]
+struct CheckpointRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence_number: Option<i64>,
+}
+
+/// An ownership, as stored in its file.
+#[derive(Debug, Deserialize, Serialize)]
+struct OwnershipRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner_id: Option<String>,
+    version: u64,
+    last_modified_time_ms: i64,
+}
+
+impl OwnershipRecord {
+    fn etag(&self) -> Etag {
+        Etag::from(self.version.to_string())
+    }
+
+    fn last_modified_time(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp_nanos(
+            i128::from(self.last_modified_time_ms) * 1_000_000,
+        )
+        .ok()
+    }
+}
+
+fn encode_name(name: &str) -> Result<String> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(Error::with_message(
+            AzureErrorKind::Other,
+            format!("{name:?} cannot be used as a name in the checkpoint store"),
+        ));
+    }
+    Ok(utf8_percent_encode(name, NAME_ENCODE_SET).to_string())
+}
+
+fn io_error(error: io::Error, action: &str, path: &Path) -> Error {
+    Error::with_error(
+        AzureErrorKind::Io,
+        error,
+        format!("Failed to {action} {}", path.display()),
+    )
+}
+
+/// Reads a record, returning `None` if it does not exist.
+fn read_record<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(io_error(e, "read", path)),
+    };
+    serde_json::from_slice(&contents).map(Some).map_err(|e| {
+        Error::with_error(
+            AzureErrorKind::DataConversion,
+            e,
+            format!("Failed to parse {}", path.display()),
+        )
+    })
+}
+
+/// Writes a record by renaming a temporary file over it.
+fn write_record<T: Serialize>(path: &Path, record: &T) -> Result<()> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(directory).map_err(|e| io_error(e, "create", directory))?;
+
+    let contents = serde_json::to_vec(record)?;
+    let mut temporary_name = path.file_name().unwrap_or_default().to_os_string();
+    temporary_name.push(format!(".{}.tmp", Uuid::new_v4()));
+    let temporary_path = directory.join(temporary_name);
+
+    let result = File::create(&temporary_path)
+        .and_then(|mut file| {
+            file.write_all(&contents)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temporary_path, path));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temporary_path);
+        return Err(io_error(e, "write", path));
+    }
+    Ok(())
+}
+
+/// Reads the records in a directory, returning each with its decoded name.
+fn read_records<T: DeserializeOwned>(directory: &Path) -> Result<Vec<(String, T)>> {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(e, "list", directory)),
+    };
+
+    let mut records = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| io_error(e, "list", directory))?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some(RECORD_EXTENSION) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let name = percent_decode_str(name).decode_utf8_lossy().into_owned();
+        // A record removed since the directory was listed is skipped.
+        if let Some(record) = read_record(&path)? {
+            records.push((name, record));
+        }
+    }
+    records.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(records)
+}
+
+/// Runs blocking file operations on a separate thread.
+async fn run_blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || tx.send(f()));
+    rx.await.map_err(|e| {
+        Error::with_error(
+            AzureErrorKind::Io,
+            e,
+            "Checkpoint store operation was cancelled",
+        )
+    })?
+}
+
+impl FileCheckpointStore {
+    /// Creates a checkpoint store that keeps its files in `directory`.
+    ///
+    /// The directory and its subdirectories are created when the first record is written.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            lock_timeout: LOCK_TIMEOUT,
+        }
+    }
+
+    fn consumer_group_directory(
+        &self,
+        namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+        kind: &str,
+    ) -> Result<PathBuf> {
+        Ok(self
+            .directory
+            .join(encode_name(namespace)?)
+            .join(encode_name(event_hub_name)?)
+            .join(encode_name(consumer_group)?)
+            .join(kind))
+    }
+
+    fn record_path(directory: &Path, partition_id: &str, extension: &str) -> Result<PathBuf> {
+        Ok(directory.join(format!("{}.{extension}", encode_name(partition_id)?)))
+    }
+
+    /// Claims one ownership while holding the lock of its partition.
+    ///
+    /// Returns `None` if the claim was lost: the ownership changed since the caller read it, or
+    /// the caller claimed a partition that is already owned without presenting an ETag.
+    fn claim(&self, ownership: &Ownership) -> Result<Option<Ownership>> {
+        let directory = self.consumer_group_directory(
+            &ownership.fully_qualified_namespace,
+            &ownership.event_hub_name,
+            &ownership.consumer_group,
+            OWNERSHIP_DIRECTORY,
+        )?;
+        let path = Self::record_path(&directory, &ownership.partition_id, RECORD_EXTENSION)?;
+        let lock_path = Self::record_path(&directory, &ownership.partition_id, LOCK_EXTENSION)?;
+
+        fs::create_dir_all(&directory).map_err(|e| io_error(e, "create", &directory))?;
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| io_error(e, "open", &lock_path))?;
+        let mut lock = FileLock::new(lock_file);
+        let deadline = Instant::now() + self.lock_timeout;
+        let _guard = loop {
+            match lock.try_write() {
+                Ok(guard) => break guard,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if Instant::now() >= deadline {
+                        return Err(Error::with_message(
+                            AzureErrorKind::Io,
+                            format!(
+                                "Timed out after {:?} waiting for another claim to release {}",
+                                self.lock_timeout,
+                                lock_path.display()
+                            ),
+                        ));
+                    }
+                    trace!(
+                        partition_id = %ownership.partition_id,
+                        "Ownership is locked by another claim, waiting."
+                    );
+                    thread::sleep(LOCK_RETRY_DELAY);
+                }
+                Err(e) => return Err(io_error(e, "lock", &lock_path)),
+            }
+        };
+
+        let current = read_record::<OwnershipRecord>(&path)?;
+        let current_etag = current.as_ref().map(OwnershipRecord::etag);
+        if ownership.etag != current_etag {
+            info!(
+                event = "claim-conflict",
+                partition_id = %ownership.partition_id,
+                owner_id = ?ownership.owner_id,
+                etag = ?ownership.etag,
+                current_etag = ?current_etag,
+                "Lost ownership claim: precondition (etag) failed"
+            );
+            return Ok(None);
+        }
+
+        let now = OffsetDateTime::now_utc();
+        let record = OwnershipRecord {
+            owner_id: ownership.owner_id.clone(),
+            version: current.map_or(1, |current| current.version + 1),
+            last_modified_time_ms: (now.unix_timestamp_nanos() / 1_000_000) as i64,
+        };
+        write_record(&path, &record)?;
+        Ok(Some(Ownership {
+            etag: Some(record.etag()),
+            last_modified_time: record.last_modified_time(),
+            ..ownership.clone()
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn claim_ownership(&self, ownerships: &[Ownership]) -> Result<Vec<Ownership>> {
+        debug!("Claiming ownership for {} partitions", ownerships.len());
+        let store = self.clone();
+        let ownerships = ownerships.to_vec();
+        run_blocking(move || {
+            let mut claimed_ownerships = Vec::new();
+            for ownership in &ownerships {
+                match store.claim(ownership) {
+                    Ok(Some(claimed)) => claimed_ownerships.push(claimed),
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!(
+                            partition_id = %ownership.partition_id,
+                            error = %e,
+                            "Error claiming ownership"
+                        );
+                        return Err(e);
+                    }
+                }
+            }
+            debug!("Returning {} ownerships", claimed_ownerships.len());
+            Ok(claimed_ownerships)
+        })
+        .await
+    }
+
+    async fn list_checkpoints(
+        &self,
+        namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<Checkpoint>> {
+        let directory = self.consumer_group_directory(
+            namespace,
+            event_hub_name,
+            consumer_group,
+            CHECKPOINT_DIRECTORY,
+        )?;
+        trace!(
+            "list_checkpoints: list checkpoints in {}",
+            directory.display()
+        );
+        let checkpoints: Vec<Checkpoint> =
+            run_blocking(move || read_records::<CheckpointRecord>(&directory))
+                .await?
+                .into_iter()
+                .map(|(partition_id, record)| Checkpoint {
+                    fully_qualified_namespace: namespace.to_string(),
+                    event_hub_name: event_hub_name.to_string(),
+                    consumer_group: consumer_group.to_string(),
+                    partition_id,
+                    offset: record.offset,
+                    sequence_number: record.sequence_number,
+                })
+                .collect();
+        trace!("list_checkpoints: found {} checkpoints", checkpoints.len());
+        Ok(checkpoints)
+    }
+
+    async fn list_ownerships(
+        &self,
+        namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<Ownership>> {
+        let directory = self.consumer_group_directory(
+            namespace,
+            event_hub_name,
+            consumer_group,
+            OWNERSHIP_DIRECTORY,
+        )?;
+        trace!(
+            "list_ownerships: list ownerships in {}",
+            directory.display()
+        );
+        let ownerships: Vec<Ownership> =
+            run_blocking(move || read_records::<OwnershipRecord>(&directory))
+                .await?
+                .into_iter()
+                .map(|(partition_id, record)| Ownership {
+                    fully_qualified_namespace: namespace.to_string(),
+                    event_hub_name: event_hub_name.to_string(),
+                    consumer_group: consumer_group.to_string(),
+                    partition_id,
+                    etag: Some(record.etag()),
+                    last_modified_time: record.last_modified_time(),
+                    owner_id: record.owner_id,
+                })
+                .collect();
+        trace!("list_ownerships: found {} ownerships", ownerships.len());
+        Ok(ownerships)
+    }
+
+    async fn update_checkpoint(&self, checkpoint: Checkpoint) -> Result<()> {
+        trace!(
+            "update_checkpoint: update checkpoint for {}",
+            checkpoint.partition_id
+        );
+        let directory = self.consumer_group_directory(
+            &checkpoint.fully_qualified_namespace,
+            &checkpoint.event_hub_name,
+            &checkpoint.consumer_group,
+            CHECKPOINT_DIRECTORY,
+        )?;
+        let path = Self::record_path(&directory, &checkpoint.partition_id, RECORD_EXTENSION)?;
+        let record = CheckpointRecord {
+            offset: checkpoint.offset,
+            sequence_number: checkpoint.sequence_number,
+        };
+        run_blocking(move || write_record(&path, &record)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory under the system temporary directory that is removed when dropped.
+    struct TestDirectory(PathBuf);
+
+    impl TestDirectory {
+        fn new() -> Self {
+            Self(std::env::temp_dir().join(format!("eventhubs-checkpoints-{}", Uuid::new_v4())))
+        }
+    }
+
+    impl Drop for TestDirectory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn ownership(partition_id: &str, owner_id: &str) -> Ownership {
+        Ownership {
+            fully_qualified_namespace: "test.servicebus.windows.net".to_string(),
+            event_hub_name: "eventhub".to_string(),
+            consumer_group: "$Default".to_string(),
+            partition_id: partition_id.to_string(),
+            owner_id: Some(owner_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpoints_survive_a_new_store() -> Result<()> {
+        let directory = TestDirectory::new();
+        FileCheckpointStore::new(&directory.0)
+            .update_checkpoint(Checkpoint {
+                fully_qualified_namespace: "test.servicebus.windows.net".to_string(),
+                event_hub_name: "eventhub".to_string(),
+                consumer_group: "$Default".to_string(),
+                partition_id: "0".to_string(),
+                offset: Some("100".to_string()),
+                sequence_number: Some(10),
+            })
+            .await?;
+
+        let checkpoints = FileCheckpointStore::new(&directory.0)
+            .list_checkpoints("test.servicebus.windows.net", "eventhub", "$Default")
+            .await?;
+        assert_eq!(checkpoints.len(), 1);
+        assert_eq!(checkpoints[0].partition_id, "0");
+        assert_eq!(checkpoints[0].offset.as_deref(), Some("100"));
+        assert_eq!(checkpoints[0].sequence_number, Some(10));
+
+        // Only the record is left behind, not the temporary file it was written to.
+        let files: Vec<_> = fs::read_dir(
+            directory
+                .0
+                .join("test.servicebus.windows.net/eventhub/$Default/checkpoint"),
+        )
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+        assert_eq!(files, ["0.json"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn claims_across_stores_use_version_etags() -> Result<()> {
+        let directory = TestDirectory::new();
+        let first = FileCheckpointStore::new(&directory.0);
+        let second = FileCheckpointStore::new(&directory.0);
+
+        let claimed = first.claim_ownership(&[ownership("0", "first")]).await?;
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].etag, Some(Etag::from("1")));
+        assert!(claimed[0].last_modified_time.is_some());
+
+        // Without the current ETag, the partition cannot be claimed.
+        assert!(second
+            .claim_ownership(&[ownership("0", "second")])
+            .await?
+            .is_empty());
+
+        let listed = second
+            .list_ownerships("test.servicebus.windows.net", "eventhub", "$Default")
+            .await?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].owner_id.as_deref(), Some("first"));
+        let stolen = second
+            .claim_ownership(&[Ownership {
+                owner_id: Some("second".to_string()),
+                ..listed[0].clone()
+            }])
+            .await?;
+        assert_eq!(stolen.len(), 1);
+        assert_eq!(stolen[0].etag, Some(Etag::from("2")));
+
+        // The first store's ETag is now stale.
+        assert!(first.claim_ownership(&claimed).await?.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn claim_waits_for_a_locked_ownership() -> Result<()> {
+        let directory = TestDirectory::new();
+        let store = FileCheckpointStore::new(&directory.0);
+        store.claim_ownership(&[ownership("0", "first")]).await?;
+
+        let lock_path = directory
+            .0
+            .join("test.servicebus.windows.net/eventhub/$Default/ownership/0.lock");
+        let mut lock = FileLock::new(File::open(&lock_path).unwrap());
+        let guard = lock.try_write().unwrap();
+
+        let listed = store
+            .list_ownerships("test.servicebus.windows.net", "eventhub", "$Default")
+            .await?;
+        let claim = store.claim_ownership(&listed);
+        let release = async {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            drop(guard);
+        };
+        let (claimed, _) = futures::join!(claim, release);
+        assert_eq!(claimed?[0].etag, Some(Etag::from("2")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn claim_times_out_waiting_for_a_lock() -> Result<()> {
+        let directory = TestDirectory::new();
+        let store = FileCheckpointStore {
+            lock_timeout: Duration::from_millis(100),
+            ..FileCheckpointStore::new(&directory.0)
+        };
+        store.claim_ownership(&[ownership("0", "first")]).await?;
+
+        let lock_path = directory
+            .0
+            .join("test.servicebus.windows.net/eventhub/$Default/ownership/0.lock");
+        let mut lock = FileLock::new(File::open(&lock_path).unwrap());
+        let _guard = lock.try_write().unwrap();
+
+        let listed = store
+            .list_ownerships("test.servicebus.windows.net", "eventhub", "$Default")
+            .await?;
+        let error = store
+            .claim_ownership(&listed)
+            .await
+            .expect_err("claim should time out");
+        assert_eq!(error.kind(), &AzureErrorKind::Io);
+        Ok(())
+    }
+
+    #[test]
+    fn names_cannot_leave_the_directory() {
+        assert_eq!(encode_name("$Default").unwrap(), "$Default");
+        assert_eq!(encode_name("a/b").unwrap(), "a%2Fb");
+        assert_eq!(encode_name("..\\x").unwrap(), "..%5Cx");
+        assert!(encode_name("..").is_err());
+        assert!(encode_name("").is_err());
+    }
+}
@@ -7,7 +7,7 @@ use azure_core::{
 };
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tracing::{error, trace};
+use tracing::{error, info, trace};
 
 /// An in-memory checkpoint store for Event Hubs.
 /// This store is used to manage checkpoints and ownerships in memory.
@@ -25,6 +25,17 @@ impl Default for InMemoryCheckpointStore {
     }
 }
 
+/// The outcome of an ownership update.
+enum OwnershipUpdate {
+    /// The ownership was updated.
+    Updated(Ownership),
+    /// The caller presented an ETag other than the one the store holds.
+    EtagMismatch {
+        key: String,
+        actual_etag: Option<Etag>,
+    },
+}
+
 macro_rules! check_non_empty_parameter(
     ($field:expr) => {
         if $field.is_empty() {
@@ -52,6 +63,29 @@ impl InMemoryCheckpointStore {
     /// makes the caller's ETag stale, so the caller must keep the returned
     /// record for its next claim.
     pub fn update_ownership(&self, ownership: &Ownership) -> Result<Ownership> {
+        match self.try_update_ownership(ownership)? {
+            OwnershipUpdate::Updated(updated_ownership) => Ok(updated_ownership),
+            OwnershipUpdate::EtagMismatch { key, actual_etag } => {
+                // The call returns `Err` from here, so this logs at the
+                // error level, the same as the other failure path in this
+                // file.
+                error!(
+                    partition_id = %ownership.partition_id,
+                    expected_etag = ?ownership.etag,
+                    actual_etag = ?actual_etag,
+                    "ETag mismatch claiming ownership for key {}",
+                    key
+                );
+                Err(Error::with_message(
+                    AzureErrorKind::Other,
+                    format!("ETag mismatch for partition {key}"),
+                ))
+            }
+        }
+    }
+
+    /// Updates the ownership if the caller presents the ETag the store holds.
+    fn try_update_ownership(&self, ownership: &Ownership) -> Result<OwnershipUpdate> {
         trace!("Update ownership for partition {}", ownership.partition_id);
 
         check_non_empty_parameter!(ownership.fully_qualified_namespace);
@@ -72,22 +106,11 @@ impl InMemoryCheckpointStore {
         // no record to match against.
         let is_renewal = match store.get(&key) {
             Some(existing) => {
-                let actual_etag = existing.etag.clone();
-                if ownership.etag != actual_etag {
-                    // The call returns `Err` from here, so this logs at the
-                    // error level, the same as the other failure path in this
-                    // file.
-                    error!(
-                        partition_id = %ownership.partition_id,
-                        expected_etag = ?ownership.etag,
-                        actual_etag = ?actual_etag,
-                        "ETag mismatch claiming ownership for key {}",
-                        key
-                    );
-                    return Err(Error::with_message(
-                        AzureErrorKind::Other,
-                        format!("ETag mismatch for partition {key}"),
-                    ));
+                if ownership.etag != existing.etag {
+                    return Ok(OwnershipUpdate::EtagMismatch {
+                        actual_etag: existing.etag.clone(),
+                        key,
+                    });
                 }
                 true
             }
@@ -107,7 +130,7 @@ impl InMemoryCheckpointStore {
         } else {
             trace!("Inserted new ownership for key {}", key);
         }
-        Ok(updated_ownership)
+        Ok(OwnershipUpdate::Updated(updated_ownership))
     }
 }
 
@@ -145,9 +168,20 @@ impl CheckpointStore for InMemoryCheckpointStore {
         trace!("Claim ownership for {} partitions", ownerships.len());
         let mut claimed_ownerships = Vec::new();
         for ownership in ownerships {
-            let ownership = self.update_ownership(ownership)?;
-            if ownership.etag.is_some() {
-                claimed_ownerships.push(ownership);
+            // A claim that presents a stale ETag lost to another owner. It is
+            // left out of the result, the way the blob store leaves it out,
+            // so the other claims still succeed.
+            match self.try_update_ownership(ownership)? {
+                OwnershipUpdate::Updated(claimed) => claimed_ownerships.push(claimed),
+                OwnershipUpdate::EtagMismatch { key, actual_etag } => info!(
+                    event = "claim-conflict",
+                    partition_id = %ownership.partition_id,
+                    owner_id = ?ownership.owner_id,
+                    etag = ?ownership.etag,
+                    actual_etag = ?actual_etag,
+                    key = %key,
+                    "Lost ownership claim: precondition (etag) failed"
+                ),
             }
         }
         Ok(claimed_ownerships)
@@ -6,6 +6,8 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "file_checkpoint_store")]
+mod file_checkpoint_store;
 #[cfg(feature = "in_memory_checkpoint_store")]
 mod in_memory_checkpoint_store;
 
@@ -15,6 +17,8 @@ mod consumer;
 pub mod error;
 mod event_processor;
 mod producer;
+#[cfg(feature = "test")]
+pub mod test;
 
 /// Types sent to and received from the Event Hubs service.
 pub mod models;
@@ -23,6 +27,11 @@ pub use consumer::{
 };
 pub use producer::{
     batch::{EventDataBatch, EventDataBatchOptions},
+    buffered::{
+        BufferedProducerClient, EnqueueEventOptions, SendEventsFailedContext, SendEventsHandler,
+        SendEventsSucceededContext,
+    },
+    partition_publishing::PartitionPublishingOptions,
     ProducerClient, SendBatchOptions, SendEventOptions, SendMessageOptions,
 };
 
@@ -31,16 +40,26 @@ pub mod processor {
     pub use crate::event_processor::partition_client::PartitionClient;
     pub use crate::event_processor::CheckpointStore;
 }
-pub use event_processor::{processor::EventProcessor, CheckpointStore, ProcessorStrategy};
+pub use event_processor::{
+    handler::{
+        CheckpointPolicy, EventBatchContext, EventProcessorHandler, PartitionCloseReason,
+        PartitionClosingContext, PartitionInitializingContext, ProcessingErrorContext,
+    },
+    processor::EventProcessor,
+    CheckpointStore, ProcessorStrategy,
+};
 /// Builders for producer client and consumer client.
 pub mod builders {
     pub use crate::consumer::builders::ConsumerClientBuilder;
     pub use crate::event_processor::processor::builders::EventProcessorBuilder;
+    pub use crate::producer::buffered::builders::BufferedProducerClientBuilder;
     pub use crate::producer::builders::ProducerClientBuilder;
 }
 pub use common::connection_string::ConnectionString;
 pub use common::retry::RetryOptions;
 pub use error::{EventHubsError, Result};
 
+#[cfg(feature = "file_checkpoint_store")]
+pub use file_checkpoint_store::FileCheckpointStore;
 #[cfg(feature = "in_memory_checkpoint_store")]
 pub use in_memory_checkpoint_store::InMemoryCheckpointStore;
@@ -17,6 +17,12 @@ pub use azure_core_amqp::AmqpValue;
 /// the `Map`, `List`, `Array`, and `Described` types).
 pub use azure_core_amqp::AmqpSimpleValue;
 
+/// The transport used to connect to Event Hubs.
+pub use azure_core_amqp::AmqpTransportType;
+
+/// An HTTP proxy used to connect to Event Hubs over WebSockets.
+pub use azure_core_amqp::AmqpProxyOptions;
+
 /// An event received from an Event Hub.
 pub use event_data::ReceivedEventData;
 
@@ -131,6 +137,28 @@ pub struct EventHubPartitionProperties {
     pub is_empty: bool,
 }
 
+/// The state of idempotent publishing to a partition.
+///
+/// Returned by [`crate::ProducerClient::get_partition_publishing_properties`]. To resume
+/// publishing as the same producer group in a new producer, pass these values back in
+/// [`crate::PartitionPublishingOptions`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartitionPublishingProperties {
+    /// Whether the producer publishes to the partition idempotently.
+    pub is_idempotent_publishing_enabled: bool,
+
+    /// The identifier of the producer group the producer publishes as.
+    pub producer_group_id: Option<i64>,
+
+    /// The owner level of the producer.
+    pub owner_level: Option<i16>,
+
+    /// The sequence number of the last event the producer group published to the partition.
+    ///
+    /// This is `None` if no events have been published.
+    pub last_published_sequence_number: Option<i32>,
+}
+
 /// Uniquely identifies a message.
 ///
 /// This type can be used to uniquely identify a message within a message broker or messaging system.
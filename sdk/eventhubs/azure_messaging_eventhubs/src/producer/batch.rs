@@ -1,7 +1,10 @@
 // Copyright (c) Microsoft Corporation. All Rights reserved
 // Licensed under the MIT license.
 
-use super::ProducerClient;
+use super::{
+    partition_publishing::{stamp_placeholder, PartitionPublisher},
+    ProducerClient,
+};
 use crate::{error::ErrorKind, error::Result, models::EventData, EventHubsError};
 use azure_core::{http::Url, Uuid};
 use azure_core_amqp::{AmqpMessage, AmqpSymbol};
@@ -13,6 +16,9 @@ pub struct AddEventDataOptions {}
 
 struct EventDataBatchState {
     serialized_messages: Vec<Vec<u8>>,
+    /// The messages of a batch published idempotently, which are serialized again once they are
+    /// stamped with their sequence numbers.
+    messages: Vec<AmqpMessage>,
     size_in_bytes: u64,
     batch_envelope: Option<AmqpMessage>,
 }
@@ -72,6 +78,7 @@ impl<'a> EventDataBatch<'a> {
             producer,
             batch_state: Mutex::new(EventDataBatchState {
                 serialized_messages: Vec::new(),
+                messages: Vec::new(),
                 size_in_bytes: 0,
                 batch_envelope: None,
             }),
@@ -269,6 +276,12 @@ impl<'a> EventDataBatch<'a> {
                 partition_key.clone(),
             );
         }
+        let idempotent = self.producer.idempotent_publishing.is_some();
+        if idempotent {
+            // The sequence numbers are stamped when the batch is sent, so the message is measured
+            // with the largest publishing state it can be stamped with.
+            stamp_placeholder(&mut message);
+        }
 
         let mut batch_state = self.batch_state.lock().unwrap();
         let message_len = AmqpMessage::serialize(&message)?.len();
@@ -301,6 +314,9 @@ impl<'a> EventDataBatch<'a> {
         }
         batch_state.size_in_bytes += actual_message_size;
         batch_state.serialized_messages.push(serialized_message);
+        if idempotent {
+            batch_state.messages.push(message);
+        }
 
         Ok(true)
     }
@@ -328,6 +344,39 @@ impl<'a> EventDataBatch<'a> {
         batch_envelope
     }
 
+    /// Gets the batch envelope of a batch published idempotently, with the messages stamped with
+    /// the next sequence numbers of the partition.
+    ///
+    /// Returns the envelope and the sequence number of the last message.
+    pub(crate) fn get_stamped_messages(
+        &self,
+        publisher: &PartitionPublisher,
+    ) -> Result<(AmqpMessage, Option<i32>)> {
+        let mut batch_state = self.batch_state.lock().unwrap();
+
+        let mut batch_envelope = batch_state.batch_envelope.take().expect(
+            "Batch envelope is missing when getting messages; \
+             send_batch was called on an empty batch (add at least one event before sending).",
+        );
+        let mut messages = std::mem::take(&mut batch_state.messages);
+        let last_sequence_number = publisher.stamp(&mut messages, Some(&mut batch_envelope))?;
+        let serialized_messages = messages
+            .iter()
+            .map(AmqpMessage::serialize)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        batch_envelope.set_message_body(serialized_messages);
+
+        // Reset the batch state for the next batch
+        batch_state.size_in_bytes = 0;
+        batch_state.serialized_messages.clear();
+
+        Ok((batch_envelope, last_sequence_number))
+    }
+
+    pub(crate) fn partition_id(&self) -> Option<&str> {
+        self.partition_id.as_deref()
+    }
+
     pub(crate) fn get_batch_path(&self) -> Result<Url> {
         Self::batch_path(self.producer.base_url(), self.partition_id.as_deref())
     }
@@ -426,7 +475,7 @@ mod tests {
             Arc::new(MockCredential),
             None,
             RetryOptions::default(),
-            None,
+            Default::default(),
             None,
         )
     }
@@ -0,0 +1,759 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use super::{
+    batch::{EventDataBatch, EventDataBatchOptions},
+    partition_resolver::partition_index_for_key,
+    ProducerClient,
+};
+use crate::{
+    error::Result,
+    models::{AmqpMessage, EventData},
+    EventHubsError,
+};
+use async_lock::Mutex as AsyncMutex;
+use azure_core::{
+    async_runtime::{get_async_runtime, SpawnedTask},
+    sleep,
+    time::Duration,
+};
+use azure_core_amqp::AmqpSymbol;
+use futures::future::join_all;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::Instant,
+};
+use tracing::{debug, warn};
+
+const PARTITION_KEY_ANNOTATION: &str = "x-opt-partition-key";
+
+/// The events that were published by a [`BufferedProducerClient`].
+#[derive(Debug)]
+pub struct SendEventsSucceededContext {
+    /// The partition the events were published to.
+    pub partition_id: String,
+
+    /// The events that were published, in the order they were enqueued.
+    pub events: Vec<EventData>,
+}
+
+/// The events that a [`BufferedProducerClient`] failed to publish.
+#[derive(Debug)]
+pub struct SendEventsFailedContext {
+    /// The partition the events were being published to.
+    pub partition_id: String,
+
+    /// The events that were not published, in the order they were enqueued.
+    pub events: Vec<EventData>,
+
+    /// The error that prevented the events from being published.
+    pub error: EventHubsError,
+}
+
+/// Receives the outcome of publishing events enqueued with a [`BufferedProducerClient`].
+///
+/// Every enqueued event is reported exactly once, either to
+/// [`on_send_succeeded`](SendEventsHandler::on_send_succeeded) or to
+/// [`on_send_failed`](SendEventsHandler::on_send_failed). Events are published in batches, so
+/// each call reports all of the events in one batch.
+#[async_trait::async_trait]
+pub trait SendEventsHandler: Send + Sync {
+    /// Called after a batch of events has been published.
+    async fn on_send_succeeded(&self, context: SendEventsSucceededContext);
+
+    /// Called when a batch of events could not be published.
+    ///
+    /// Sends are retried according to the [`RetryOptions`](crate::RetryOptions) of the
+    /// [`ProducerClient`] before they are reported as failed. Failed events are not enqueued
+    /// again.
+    async fn on_send_failed(&self, context: SendEventsFailedContext);
+}
+
+/// Options used when enqueuing an event with a [`BufferedProducerClient`].
+///
+/// At most one of `partition_id` and `partition_key` may be set. If neither is set, events are
+/// distributed across partitions in turn.
+#[derive(Default, Debug, Clone)]
+pub struct EnqueueEventOptions {
+    /// The id of the partition to which the event should be published.
+    pub partition_id: Option<String>,
+
+    /// The partition key of the event.
+    ///
+    /// The partition for the key is computed on the client with the same hash the Event Hubs
+    /// service uses, so events with the same partition key are published to the same partition
+    /// in the order they were enqueued.
+    pub partition_key: Option<String>,
+}
+
+struct BufferedEvent {
+    event: EventData,
+    partition_key: Option<String>,
+}
+
+impl BufferedEvent {
+    fn to_amqp_message(&self) -> AmqpMessage {
+        let mut message = AmqpMessage::from(self.event.clone());
+        if let Some(partition_key) = &self.partition_key {
+            message.add_message_annotation(
+                AmqpSymbol::from(PARTITION_KEY_ANNOTATION),
+                partition_key.clone(),
+            );
+        }
+        message
+    }
+}
+
+#[derive(Default)]
+struct PartitionEvents {
+    events: VecDeque<BufferedEvent>,
+    oldest_enqueued: Option<Instant>,
+}
+
+#[derive(Default)]
+struct PartitionBuffer {
+    events: Mutex<PartitionEvents>,
+    // Held while a partition is being published so that its events stay in order.
+    publish_lock: AsyncMutex<()>,
+}
+
+impl PartitionBuffer {
+    /// Adds an event to the buffer and returns the number of buffered events.
+    fn push(&self, event: BufferedEvent) -> usize {
+        let mut events = self.events.lock().unwrap();
+        events.oldest_enqueued.get_or_insert_with(Instant::now);
+        events.events.push_back(event);
+        events.events.len()
+    }
+
+    fn take(&self) -> VecDeque<BufferedEvent> {
+        let mut events = self.events.lock().unwrap();
+        events.oldest_enqueued = None;
+        std::mem::take(&mut events.events)
+    }
+
+    fn len(&self) -> usize {
+        self.events.lock().unwrap().events.len()
+    }
+
+    fn is_expired(&self, max_wait_time: std::time::Duration) -> bool {
+        self.events
+            .lock()
+            .unwrap()
+            .oldest_enqueued
+            .is_some_and(|oldest| oldest.elapsed() >= max_wait_time)
+    }
+}
+
+struct BufferedProducer {
+    producer: Arc<ProducerClient>,
+    handler: Arc<dyn SendEventsHandler>,
+    partition_ids: Vec<String>,
+    partitions: HashMap<String, PartitionBuffer>,
+    max_wait_time: std::time::Duration,
+    max_event_buffer_length_per_partition: usize,
+    next_partition: AtomicUsize,
+    // Held by the background flush task while it publishes, so that stopping the task never
+    // cancels a publish after it has taken events out of their buffers.
+    flushing: AsyncMutex<()>,
+}
+
+impl BufferedProducer {
+    fn resolve_partition(&self, options: &EnqueueEventOptions) -> Result<&str> {
+        match (&options.partition_id, &options.partition_key) {
+            (Some(_), Some(_)) => Err(EventHubsError::with_message(
+                "An event cannot be enqueued with both a partition id and a partition key.",
+            )),
+            (Some(partition_id), None) => self
+                .partition_ids
+                .iter()
+                .find(|id| *id == partition_id)
+                .map(String::as_str)
+                .ok_or_else(|| {
+                    EventHubsError::with_message(format!(
+                        "Partition '{partition_id}' does not exist in the Event Hub."
+                    ))
+                }),
+            (None, Some(partition_key)) => Ok(&self.partition_ids
+                [partition_index_for_key(partition_key, self.partition_ids.len())]),
+            (None, None) => {
+                let next = self.next_partition.fetch_add(1, Ordering::Relaxed);
+                Ok(&self.partition_ids[next % self.partition_ids.len()])
+            }
+        }
+    }
+
+    fn buffered_event_count(&self) -> usize {
+        self.partitions.values().map(PartitionBuffer::len).sum()
+    }
+
+    /// Publishes the buffered events of every partition.
+    async fn publish_all(&self) {
+        join_all(
+            self.partition_ids
+                .iter()
+                .map(|partition_id| self.publish_partition(partition_id)),
+        )
+        .await;
+    }
+
+    /// Publishes the buffered events of partitions whose oldest event has waited for the maximum
+    /// wait time.
+    async fn publish_expired(&self) {
+        join_all(
+            self.partition_ids
+                .iter()
+                .filter(|partition_id| {
+                    self.partitions[*partition_id].is_expired(self.max_wait_time)
+                })
+                .map(|partition_id| self.publish_partition(partition_id)),
+        )
+        .await;
+    }
+
+    /// Publishes the buffered events of a partition in as few batches as possible, and reports
+    /// the outcome of each batch to the handler.
+    async fn publish_partition(&self, partition_id: &str) {
+        let partition = &self.partitions[partition_id];
+        let _publishing = partition.publish_lock.lock().await;
+        let mut events = partition.take();
+        if events.is_empty() {
+            return;
+        }
+        debug!(
+            partition_id,
+            count = events.len(),
+            "Publishing buffered events."
+        );
+
+        let mut batch: Option<(EventDataBatch<'_>, Vec<EventData>)> = None;
+        while let Some(buffered) = events.pop_front() {
+            let message = buffered.to_amqp_message();
+            loop {
+                let (current, current_events) = match &mut batch {
+                    Some(batch) => (&batch.0, &mut batch.1),
+                    None => match self.create_batch(partition_id).await {
+                        Ok(created) => {
+                            let (current, current_events) = batch.insert((created, Vec::new()));
+                            (&*current, current_events)
+                        }
+                        Err(error) => {
+                            // Nothing more can be published to this partition for now.
+                            let failed = std::iter::once(buffered.event)
+                                .chain(events.drain(..).map(|buffered| buffered.event))
+                                .collect();
+                            self.report_failed(partition_id, failed, error).await;
+                            return;
+                        }
+                    },
+                };
+                match current.try_add_amqp_message(message.clone(), None) {
+                    Ok(true) => {
+                        current_events.push(buffered.event);
+                        break;
+                    }
+                    Ok(false) if current_events.is_empty() => {
+                        let error = EventHubsError::with_message(
+                            "The event is too large to fit in a batch.",
+                        );
+                        self.report_failed(partition_id, vec![buffered.event], error)
+                            .await;
+                        break;
+                    }
+                    Ok(false) => {
+                        // The batch is full; publish it and add the event to a new batch.
+                        if let Some((full, full_events)) = batch.take() {
+                            self.send_batch(partition_id, full, full_events).await;
+                        }
+                    }
+                    Err(error) => {
+                        self.report_failed(partition_id, vec![buffered.event], error)
+                            .await;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some((current, current_events)) = batch {
+            if !current_events.is_empty() {
+                self.send_batch(partition_id, current, current_events).await;
+            }
+        }
+    }
+
+    async fn create_batch(&self, partition_id: &str) -> Result<EventDataBatch<'_>> {
+        self.producer
+            .create_batch(Some(EventDataBatchOptions {
+                partition_id: Some(partition_id.to_string()),
+                ..Default::default()
+            }))
+            .await
+    }
+
+    async fn send_batch(
+        &self,
+        partition_id: &str,
+        batch: EventDataBatch<'_>,
+        events: Vec<EventData>,
+    ) {
+        match self.producer.send_batch(batch, None).await {
+            Ok(()) => {
+                self.handler
+                    .on_send_succeeded(SendEventsSucceededContext {
+                        partition_id: partition_id.to_string(),
+                        events,
+                    })
+                    .await;
+            }
+            Err(error) => self.report_failed(partition_id, events, error).await,
+        }
+    }
+
+    async fn report_failed(
+        &self,
+        partition_id: &str,
+        events: Vec<EventData>,
+        error: EventHubsError,
+    ) {
+        warn!(
+            partition_id,
+            count = events.len(),
+            %error,
+            "Failed to publish buffered events."
+        );
+        self.handler
+            .on_send_failed(SendEventsFailedContext {
+                partition_id: partition_id.to_string(),
+                events,
+                error,
+            })
+            .await;
+    }
+}
+
+/// Publishes events in the background, batching them per partition.
+///
+/// Events enqueued with [`enqueue_event`](BufferedProducerClient::enqueue_event) are buffered per
+/// partition. The buffered events of a partition are published when the partition holds the
+/// maximum number of buffered events, when its oldest event has waited for the maximum wait time,
+/// or when [`flush`](BufferedProducerClient::flush) is called. The outcome of publishing every
+/// event is reported to a [`SendEventsHandler`].
+///
+/// Events that are still buffered when the client is dropped are not published; call
+/// [`close`](BufferedProducerClient::close) to publish them first.
+///
+/// # Examples
+///
+/// ```no_run
+/// use azure_messaging_eventhubs::{
+///     BufferedProducerClient, EnqueueEventOptions, ProducerClient, SendEventsFailedContext,
+///     SendEventsHandler, SendEventsSucceededContext,
+/// };
+/// use azure_identity::DeveloperToolsCredential;
+/// use std::{error::Error, sync::Arc};
+///
+/// struct Handler;
+///
+/// #[async_trait::async_trait]
+/// impl SendEventsHandler for Handler {
+///     async fn on_send_succeeded(&self, context: SendEventsSucceededContext) {
+///         println!("Published {} events to partition {}", context.events.len(), context.partition_id);
+///     }
+///
+///     async fn on_send_failed(&self, context: SendEventsFailedContext) {
+///         eprintln!("Failed to publish {} events: {}", context.events.len(), context.error);
+///     }
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+///     let fully_qualified_namespace = std::env::var("EVENT_HUB_NAMESPACE")?;
+///     let eventhub_name = std::env::var("EVENT_HUB_NAME")?;
+///     let my_credentials = DeveloperToolsCredential::new(None)?;
+///     let producer = ProducerClient::builder()
+///         .open(&fully_qualified_namespace, &eventhub_name, my_credentials.clone())
+///         .await?;
+///
+///     let buffered_producer = BufferedProducerClient::builder()
+///         .build(Arc::new(producer), Arc::new(Handler))
+///         .await?;
+///
+///     buffered_producer
+///         .enqueue_event(
+///             "Hello, World!",
+///             Some(EnqueueEventOptions {
+///                 partition_key: Some("device-1".to_string()),
+///                 ..Default::default()
+///             }),
+///         )
+///         .await?;
+///
+///     buffered_producer.close().await;
+///     Ok(())
+/// }
+/// ```
+pub struct BufferedProducerClient {
+    inner: Arc<BufferedProducer>,
+    flush_task: Mutex<Option<SpawnedTask>>,
+}
+
+impl BufferedProducerClient {
+    pub(crate) fn new(
+        producer: Arc<ProducerClient>,
+        handler: Arc<dyn SendEventsHandler>,
+        partition_ids: Vec<String>,
+        max_wait_time: std::time::Duration,
+        max_event_buffer_length_per_partition: usize,
+    ) -> Self {
+        let inner = Arc::new(BufferedProducer {
+            producer,
+            handler,
+            partitions: partition_ids
+                .iter()
+                .map(|partition_id| (partition_id.clone(), PartitionBuffer::default()))
+                .collect(),
+            partition_ids,
+            max_wait_time,
+            max_event_buffer_length_per_partition,
+            next_partition: AtomicUsize::new(0),
+            flushing: AsyncMutex::new(()),
+        });
+        let flush_task = get_async_runtime().spawn(Box::pin(Self::run_flush_task(
+            Arc::downgrade(&inner),
+            max_wait_time / 2,
+        )));
+        Self {
+            inner,
+            flush_task: Mutex::new(Some(flush_task)),
+        }
+    }
+
+    /// Returns a builder which can be used to create a new instance of [`BufferedProducerClient`].
+    pub fn builder() -> builders::BufferedProducerClientBuilder {
+        builders::BufferedProducerClientBuilder::new()
+    }
+
+    /// Adds an event to the buffer of the partition it will be published to.
+    ///
+    /// If the partition already holds the maximum number of buffered events, its events are
+    /// published before this method returns.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to publish.
+    /// * `options` - The options used to choose the partition of the event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if both a partition id and a partition key are specified, or if the
+    /// partition id does not exist. Failures to publish the event are reported to the
+    /// [`SendEventsHandler`] instead.
+    pub async fn enqueue_event(
+        &self,
+        event: impl Into<EventData>,
+        options: Option<EnqueueEventOptions>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        let partition_id = self.inner.resolve_partition(&options)?;
+        let buffered = self.inner.partitions[partition_id].push(BufferedEvent {
+            event: event.into(),
+            partition_key: options.partition_key,
+        });
+        if buffered >= self.inner.max_event_buffer_length_per_partition {
+            self.inner.publish_partition(partition_id).await;
+        }
+        Ok(())
+    }
+
+    /// Gets the number of events that are buffered and have not been published yet.
+    pub fn buffered_event_count(&self) -> usize {
+        self.inner.buffered_event_count()
+    }
+
+    /// Publishes all buffered events, and waits for their outcomes to be reported.
+    pub async fn flush(&self) {
+        self.inner.publish_all().await;
+    }
+
+    /// Stops publishing in the background, and publishes all buffered events.
+    ///
+    /// The [`ProducerClient`] the client was built with is not closed.
+    pub async fn close(self) {
+        let flush_task = self.flush_task.lock().unwrap().take();
+        if let Some(flush_task) = flush_task {
+            // Wait for a publish in progress, so that its events are reported.
+            let _flushing = self.inner.flushing.lock().await;
+            flush_task.abort();
+        }
+        self.inner.publish_all().await;
+    }
+
+    async fn run_flush_task(producer: Weak<BufferedProducer>, check_interval: std::time::Duration) {
+        let check_interval = Duration::try_from(check_interval).unwrap_or(Duration::seconds(1));
+        loop {
+            sleep(check_interval).await;
+            let Some(producer) = producer.upgrade() else {
+                return;
+            };
+            let _flushing = producer.flushing.lock().await;
+            producer.publish_expired().await;
+        }
+    }
+}
+
+impl Drop for BufferedProducerClient {
+    fn drop(&mut self) {
+        let buffered = self.inner.buffered_event_count();
+        if buffered > 0 {
+            warn!(
+                buffered,
+                "BufferedProducerClient dropped with buffered events that were not published."
+            );
+        }
+        // A publish in progress is left to finish, and the task ends once it can no longer reach
+        // the producer.
+        if let Some(flush_task) = self.flush_task.lock().unwrap().take() {
+            if let Some(_flushing) = self.inner.flushing.try_lock() {
+                flush_task.abort();
+            }
+        }
+    }
+}
+
+/// Builders for the buffered producer client.
+pub mod builders {
+    use super::{BufferedProducerClient, SendEventsHandler};
+    use crate::{error::Result, EventHubsError, ProducerClient};
+    use azure_core::time::Duration;
+    use std::sync::Arc;
+
+    const DEFAULT_MAX_WAIT_TIME: Duration = Duration::seconds(1);
+    const DEFAULT_MAX_EVENT_BUFFER_LENGTH_PER_PARTITION: usize = 1500;
+
+    /// Builder for creating a [`BufferedProducerClient`].
+    #[derive(Default)]
+    pub struct BufferedProducerClientBuilder {
+        max_wait_time: Option<Duration>,
+        max_event_buffer_length_per_partition: Option<usize>,
+    }
+
+    impl BufferedProducerClientBuilder {
+        pub(super) fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets the longest time an event is buffered before its partition is published.
+        ///
+        /// The default maximum wait time is 1 second.
+        pub fn with_max_wait_time(mut self, max_wait_time: Duration) -> Self {
+            self.max_wait_time = Some(max_wait_time);
+            self
+        }
+
+        /// Sets the number of events buffered for a partition at which they are published.
+        ///
+        /// When a partition holds this many events, enqueuing another event for it waits for the
+        /// buffered events to be published. The default is 1500 events.
+        pub fn with_max_event_buffer_length_per_partition(
+            mut self,
+            max_event_buffer_length_per_partition: usize,
+        ) -> Self {
+            self.max_event_buffer_length_per_partition =
+                Some(max_event_buffer_length_per_partition);
+            self
+        }
+
+        /// Builds the buffered producer client.
+        ///
+        /// # Arguments
+        ///
+        /// * `producer` - The producer client used to publish events.
+        /// * `handler` - The handler that receives the outcome of publishing each event.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the options are invalid, or if the partitions of the Event Hub
+        /// cannot be retrieved.
+        pub async fn build(
+            self,
+            producer: Arc<ProducerClient>,
+            handler: Arc<dyn SendEventsHandler>,
+        ) -> Result<BufferedProducerClient> {
+            let max_wait_time = self.max_wait_time.unwrap_or(DEFAULT_MAX_WAIT_TIME);
+            let max_wait_time = std::time::Duration::try_from(max_wait_time)
+                .ok()
+                .filter(|max_wait_time| !max_wait_time.is_zero())
+                .ok_or_else(|| {
+                    EventHubsError::with_message(format!(
+                        "max_wait_time ({max_wait_time:?}) must be greater than zero."
+                    ))
+                })?;
+            let max_event_buffer_length_per_partition = self
+                .max_event_buffer_length_per_partition
+                .unwrap_or(DEFAULT_MAX_EVENT_BUFFER_LENGTH_PER_PARTITION);
+            if max_event_buffer_length_per_partition == 0 {
+                return Err(EventHubsError::with_message(
+                    "max_event_buffer_length_per_partition must be greater than zero.",
+                ));
+            }
+
+            let properties = producer.get_eventhub_properties().await?;
+            if properties.partition_ids.is_empty() {
+                return Err(EventHubsError::with_message(
+                    "The Event Hub does not have any partitions.",
+                ));
+            }
+
+            Ok(BufferedProducerClient::new(
+                producer,
+                handler,
+                properties.partition_ids,
+                max_wait_time,
+                max_event_buffer_length_per_partition,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{producer::partition_resolver::partition_index_for_key, RetryOptions};
+    use azure_core::http::Url;
+    use azure_core_test::credentials::MockCredential;
+
+    struct NoopHandler;
+
+    #[async_trait::async_trait]
+    impl SendEventsHandler for NoopHandler {
+        async fn on_send_succeeded(&self, _context: SendEventsSucceededContext) {}
+        async fn on_send_failed(&self, _context: SendEventsFailedContext) {}
+    }
+
+    // A client that never opens a connection, with a buffer that is never published during the
+    // test.
+    fn offline_buffered_producer(partition_count: usize) -> BufferedProducerClient {
+        let producer = ProducerClient::new(
+            Url::parse("amqps://test.servicebus.windows.net").unwrap(),
+            "eventhub".to_string(),
+            Arc::new(MockCredential),
+            None,
+            RetryOptions::default(),
+            Default::default(),
+            None,
+        );
+        BufferedProducerClient::new(
+            Arc::new(producer),
+            Arc::new(NoopHandler),
+            (0..partition_count).map(|id| id.to_string()).collect(),
+            std::time::Duration::from_secs(3600),
+            100,
+        )
+    }
+
+    #[tokio::test]
+    async fn partition_key_routes_to_hashed_partition() {
+        let client = offline_buffered_producer(4);
+        let options = EnqueueEventOptions {
+            partition_key: Some("device-1".to_string()),
+            ..Default::default()
+        };
+        let expected = partition_index_for_key("device-1", 4).to_string();
+        assert_eq!(client.inner.resolve_partition(&options).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn partition_id_must_exist() {
+        let client = offline_buffered_producer(2);
+        let options = EnqueueEventOptions {
+            partition_id: Some("1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(client.inner.resolve_partition(&options).unwrap(), "1");
+
+        let options = EnqueueEventOptions {
+            partition_id: Some("7".to_string()),
+            ..Default::default()
+        };
+        assert!(client.inner.resolve_partition(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn partition_id_and_key_are_exclusive() {
+        let client = offline_buffered_producer(2);
+        let options = EnqueueEventOptions {
+            partition_id: Some("1".to_string()),
+            partition_key: Some("key".to_string()),
+        };
+        assert!(client.inner.resolve_partition(&options).is_err());
+    }
+
+    #[tokio::test]
+    async fn events_without_partition_are_distributed() {
+        let client = offline_buffered_producer(3);
+        let options = EnqueueEventOptions::default();
+        let partitions: Vec<_> = (0..6)
+            .map(|_| {
+                client
+                    .inner
+                    .resolve_partition(&options)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(partitions, ["0", "1", "2", "0", "1", "2"]);
+    }
+
+    #[tokio::test]
+    async fn enqueued_events_are_buffered() {
+        let client = offline_buffered_producer(2);
+        for _ in 0..3 {
+            client.enqueue_event("event", None).await.unwrap();
+        }
+        client
+            .enqueue_event(
+                "keyed event",
+                Some(EnqueueEventOptions {
+                    partition_key: Some("key".to_string()),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(client.buffered_event_count(), 4);
+        let partition_id = partition_index_for_key("key", 2).to_string();
+        let buffered = client.inner.partitions[&partition_id].take();
+        let keyed = buffered
+            .iter()
+            .find(|buffered| buffered.partition_key.is_some())
+            .unwrap();
+        let message = keyed.to_amqp_message();
+        assert!(message
+            .message_annotations
+            .unwrap()
+            .0
+            .iter()
+            .any(|(key, _)| *key == PARTITION_KEY_ANNOTATION));
+    }
+
+    #[tokio::test]
+    async fn close_waits_for_background_publish() {
+        let client = offline_buffered_producer(2);
+        let inner = client.inner.clone();
+        // Stands in for the flush task publishing events it has taken out of their buffers.
+        let flushing = inner.flushing.lock().await;
+
+        let close = tokio::spawn(client.close());
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!close.is_finished());
+
+        drop(flushing);
+        close.await.unwrap();
+    }
+}
@@ -3,11 +3,14 @@
 
 use crate::{
     common::{
-        recoverable::{RecoverableConnection, RecoverableSender},
+        recoverable::{ConnectionTransport, RecoverableConnection, RecoverableSender},
         ManagementInstance,
     },
     error::Result,
-    models::{AmqpMessage, EventData, EventHubPartitionProperties, EventHubProperties},
+    models::{
+        AmqpMessage, EventData, EventHubPartitionProperties, EventHubProperties,
+        PartitionPublishingProperties,
+    },
     EventHubsError, RetryOptions,
 };
 use azure_core::{
@@ -19,11 +22,17 @@ use azure_core_amqp::{
     error::AmqpErrorKind, AmqpError, AmqpSendOptions, AmqpSendOutcome, AmqpSenderApis,
 };
 use batch::{EventDataBatch, EventDataBatchOptions};
+use partition_publishing::{translate_publish_error, IdempotentPublishing, PartitionPublisher};
 use std::{fmt::Debug, sync::Arc};
 use tracing::{trace, warn};
 
 /// Types used to collect messages into a "batch" before submitting them to an Event Hub.
 pub(crate) mod batch;
+/// A producer that buffers events and publishes them in batches in the background.
+pub(crate) mod buffered;
+/// Idempotent publishing to partitions.
+pub(crate) mod partition_publishing;
+mod partition_resolver;
 
 pub(crate) const DEFAULT_EVENTHUBS_APPLICATION: &str = "DefaultApplicationName";
 
@@ -61,6 +70,7 @@ pub struct ProducerClient {
     connection: Arc<RecoverableConnection>,
     eventhub: String,
     endpoint: Url,
+    idempotent_publishing: Option<IdempotentPublishing>,
 }
 
 /// Options used when sending an event to an Event Hub.
@@ -98,23 +108,33 @@ impl ProducerClient {
         credential: Arc<dyn azure_core::credentials::TokenCredential>,
         application_id: Option<String>,
         retry_options: RetryOptions,
-        custom_endpoint: Option<Url>,
+        transport: ConnectionTransport,
         cbs_token_type: Option<&'static str>,
     ) -> Self {
         Self {
             connection: RecoverableConnection::new(
                 endpoint.clone(),
                 application_id,
-                custom_endpoint,
+                transport,
                 credential,
                 retry_options,
                 cbs_token_type,
             ),
             eventhub,
             endpoint,
+            idempotent_publishing: None,
         }
     }
 
+    /// Enables idempotent publishing to partitions.
+    pub(crate) fn with_idempotent_publishing(
+        mut self,
+        idempotent_publishing: Option<IdempotentPublishing>,
+    ) -> Self {
+        self.idempotent_publishing = idempotent_publishing;
+        self
+    }
+
     /// Returns a builder which can be used to create a new instance of [`ProducerClient`].
     ///
     /// # Arguments
@@ -225,22 +245,30 @@ impl ProducerClient {
         M: Into<AmqpMessage> + Debug + Send,
     {
         let options = options.unwrap_or_default();
+        let publisher = self.publisher(options.partition_id.as_deref(), None)?;
         let mut target = self.endpoint.clone();
         if let Some(partition_id) = options.partition_id {
             let target_url = format!("{}/Partitions/{}", self.base_url(), partition_id);
             target = Url::parse(&target_url).map_err(azure_core::Error::from)?;
         }
-        let sender = self.connection.get_sender(target.clone()).await?;
-
-        let outcome = sender
-            .send(
-                message,
-                Some(AmqpSendOptions {
-                    message_format: None,
-                    ..Default::default()
-                }),
-            )
+        let sender = self
+            .ensure_sender(target.clone(), publisher.clone())
             .await?;
+
+        let send_options = AmqpSendOptions {
+            message_format: None,
+            ..Default::default()
+        };
+        let outcome = match publisher {
+            None => sender.send(message, Some(send_options)).await?,
+            Some(publisher) => {
+                let _publishing = publisher.lock().await;
+                let mut message = message.into();
+                let last = publisher.stamp(std::slice::from_mut(&mut message), None)?;
+                self.send_idempotent(&sender, &target, &publisher, message, send_options, last)
+                    .await?
+            }
+        };
         match outcome {
             AmqpSendOutcome::Accepted => Ok(()),
             AmqpSendOutcome::Rejected(reason) => {
@@ -330,13 +358,17 @@ impl ProducerClient {
         &self,
         batch_options: Option<EventDataBatchOptions>,
     ) -> Result<EventDataBatch<'_>> {
-        let path = EventDataBatch::batch_path(
-            self.base_url(),
+        let partition_id = batch_options
+            .as_ref()
+            .and_then(|o| o.partition_id.as_deref());
+        let publisher = self.publisher(
+            partition_id,
             batch_options
                 .as_ref()
-                .and_then(|o| o.partition_id.as_deref()),
+                .and_then(|o| o.partition_key.as_deref()),
         )?;
-        let sender = self.ensure_sender(path.clone()).await?;
+        let path = EventDataBatch::batch_path(self.base_url(), partition_id)?;
+        let sender = self.ensure_sender(path.clone(), publisher).await?;
         let link_max_size = sender.max_message_size().await?.ok_or_else(|| {
             warn!(
                 path = %path,
@@ -403,18 +435,26 @@ impl ProducerClient {
         #[allow(unused_variables)] options: Option<SendBatchOptions>,
     ) -> Result<()> {
         let path = batch.get_batch_path()?;
-        let sender = self.connection.get_sender(path.clone()).await?;
-
-        let messages = batch.get_messages();
-        let outcome = sender
-            .send(
-                messages,
-                Some(AmqpSendOptions {
-                    message_format: Some(Self::BATCH_MESSAGE_FORMAT),
-                    ..Default::default()
-                }),
-            )
-            .await?;
+        let publisher = self.publisher(batch.partition_id(), None)?;
+        let sender = self.ensure_sender(path.clone(), publisher.clone()).await?;
+
+        let send_options = AmqpSendOptions {
+            message_format: Some(Self::BATCH_MESSAGE_FORMAT),
+            ..Default::default()
+        };
+        let outcome = match publisher {
+            None => {
+                sender
+                    .send(batch.get_messages(), Some(send_options))
+                    .await?
+            }
+            Some(publisher) => {
+                let _publishing = publisher.lock().await;
+                let (messages, last) = batch.get_stamped_messages(&publisher)?;
+                self.send_idempotent(&sender, &path, &publisher, messages, send_options, last)
+                    .await?
+            }
+        };
         match outcome {
             AmqpSendOutcome::Accepted => Ok(()),
             AmqpSendOutcome::Rejected(reason) => {
@@ -530,6 +570,51 @@ impl ProducerClient {
             .await
     }
 
+    /// Gets the state of idempotent publishing to a partition.
+    ///
+    /// When idempotent partitions are enabled and the producer hasn't published to the partition
+    /// yet, a sender is attached to the partition to read its state from the service.
+    ///
+    /// # Arguments
+    /// * `partition_id` - The id of the partition.
+    ///
+    /// # Returns
+    /// A `Result` containing the publishing properties of the partition. When idempotent
+    /// partitions are not enabled, `is_idempotent_publishing_enabled` is `false` and the other
+    /// properties are `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use azure_messaging_eventhubs::ProducerClient;
+    /// use azure_identity::DeveloperToolsCredential;
+    /// use std::error::Error;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    ///     let fully_qualified_namespace = std::env::var("EVENT_HUB_NAMESPACE")?;
+    ///     let eventhub_name = std::env::var("EVENT_HUB_NAME")?;
+    ///     let my_credentials = DeveloperToolsCredential::new(None)?;
+    ///     let producer = ProducerClient::builder()
+    ///         .with_idempotent_partitions(true)
+    ///         .open(&fully_qualified_namespace, &eventhub_name, my_credentials.clone()).await?;
+    ///     let publishing_properties = producer.get_partition_publishing_properties("0").await?;
+    ///     println!("Producer group: {:?}", publishing_properties.producer_group_id);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn get_partition_publishing_properties(
+        &self,
+        partition_id: &str,
+    ) -> Result<PartitionPublishingProperties> {
+        let Some(publisher) = self.publisher(Some(partition_id), None)? else {
+            return Ok(PartitionPublishingProperties::default());
+        };
+        let path = EventDataBatch::batch_path(self.base_url(), Some(partition_id))?;
+        self.ensure_sender(path, Some(publisher.clone())).await?;
+        Ok(publisher.properties())
+    }
+
     /// Forces an error on the connection.
     #[cfg(test)]
     pub fn force_error(&self, error: AmqpError) -> Result<()> {
@@ -540,8 +625,68 @@ impl ProducerClient {
         &self.endpoint
     }
 
-    async fn ensure_sender(&self, target: Url) -> Result<RecoverableSender> {
-        self.connection.get_sender(target).await
+    async fn ensure_sender(
+        &self,
+        target: Url,
+        publisher: Option<Arc<PartitionPublisher>>,
+    ) -> Result<RecoverableSender> {
+        self.connection
+            .get_sender(target, publisher)
+            .await
+            .map_err(translate_publish_error)
+    }
+
+    /// Gets the publisher of the partition events are sent to, when idempotent partitions are
+    /// enabled.
+    ///
+    /// Idempotent publishing is tracked per partition, so events must be sent to a partition by
+    /// id rather than routed by the service.
+    fn publisher(
+        &self,
+        partition_id: Option<&str>,
+        partition_key: Option<&str>,
+    ) -> Result<Option<Arc<PartitionPublisher>>> {
+        let Some(idempotent_publishing) = self.idempotent_publishing.as_ref() else {
+            return Ok(None);
+        };
+        if partition_key.is_some() {
+            return Err(EventHubsError::with_message(
+                "A partition key cannot be used when idempotent partitions are enabled.",
+            ));
+        }
+        let partition_id = partition_id.ok_or_else(|| {
+            EventHubsError::with_message(
+                "A partition id is required when idempotent partitions are enabled.",
+            )
+        })?;
+        Ok(Some(idempotent_publishing.publisher(partition_id)))
+    }
+
+    /// Sends events stamped with sequence numbers, and records them as published once the
+    /// service accepts them.
+    ///
+    /// If the events aren't known to be published, the sender is closed so that the next send
+    /// attaches again and takes the last published sequence number from the service.
+    async fn send_idempotent(
+        &self,
+        sender: &RecoverableSender,
+        path: &Url,
+        publisher: &PartitionPublisher,
+        message: AmqpMessage,
+        send_options: AmqpSendOptions,
+        last_sequence_number: Option<i32>,
+    ) -> Result<AmqpSendOutcome> {
+        let result = sender.send(message, Some(send_options)).await;
+        if matches!(result, Ok(AmqpSendOutcome::Accepted)) {
+            publisher.commit(last_sequence_number);
+        } else if let Err(e) = self.connection.close_sender(path).await {
+            warn!(
+                path = %path,
+                err = %e,
+                "Failed to close the sender after an idempotent send failed."
+            );
+        }
+        result.map_err(|e| translate_publish_error(e.into()))
     }
 
     async fn ensure_connection(&self) -> Result<()> {
@@ -551,17 +696,22 @@ impl ProducerClient {
 }
 
 pub mod builders {
-    use super::ProducerClient;
+    use super::{
+        partition_publishing::{IdempotentPublishing, PartitionPublishingOptions},
+        ProducerClient,
+    };
     use crate::{
         common::{
             connection_string::{resolve_eventhub, ConnectionString},
+            recoverable::ConnectionTransport,
             sas_credential::SasCredential,
             SAS_TOKEN_TYPE,
         },
         Result, RetryOptions,
     };
     use azure_core::{http::Url, Error};
-    use std::sync::Arc;
+    use azure_core_amqp::{AmqpProxyOptions, AmqpTransportType};
+    use std::{collections::HashMap, sync::Arc};
 
     /// A builder for creating a [`ProducerClient`].
     ///
@@ -590,6 +740,18 @@ pub mod builders {
 
         /// The custom endpoint for the Event Hub.
         custom_endpoint: Option<String>,
+
+        /// The transport used to connect to the Event Hub.
+        transport_type: Option<AmqpTransportType>,
+
+        /// The HTTP proxy used to connect to the Event Hub.
+        proxy: Option<AmqpProxyOptions>,
+
+        /// Whether events are published to partitions idempotently.
+        idempotent_partitions: bool,
+
+        /// The options for idempotent publishing to each partition.
+        partition_publishing_options: HashMap<String, PartitionPublishingOptions>,
     }
 
     impl ProducerClientBuilder {
@@ -645,6 +807,64 @@ pub mod builders {
             self
         }
 
+        /// Sets the transport used to connect to the Event Hub.
+        ///
+        /// Use [`AmqpTransportType::AmqpWebSockets`] to connect over port 443 when the AMQP port
+        /// is blocked. WebSockets require the `websocket` feature.
+        pub fn with_transport_type(mut self, transport_type: AmqpTransportType) -> Self {
+            self.transport_type = Some(transport_type);
+            self
+        }
+
+        /// Sets the HTTP proxy used to connect to the Event Hub.
+        ///
+        /// A proxy can only be used with [`AmqpTransportType::AmqpWebSockets`].
+        pub fn with_proxy(mut self, proxy: AmqpProxyOptions) -> Self {
+            self.proxy = Some(proxy);
+            self
+        }
+
+        /// Enables idempotent publishing to partitions.
+        ///
+        /// Each event is stamped with a sequence number that the service checks, so a send that
+        /// is retried after its outcome was lost doesn't publish the events twice. Events must be
+        /// sent to a partition by id; partition keys and automatic routing can't be used.
+        ///
+        /// # Arguments
+        ///
+        /// * `enable` - Whether to publish to partitions idempotently.
+        ///
+        /// # Returns
+        ///
+        /// The updated [`ProducerClientBuilder`].
+        pub fn with_idempotent_partitions(mut self, enable: bool) -> Self {
+            self.idempotent_partitions = enable;
+            self
+        }
+
+        /// Sets the options for idempotent publishing to a partition.
+        ///
+        /// The options are only used when idempotent partitions are enabled with
+        /// [`with_idempotent_partitions`](Self::with_idempotent_partitions).
+        ///
+        /// # Arguments
+        ///
+        /// * `partition_id` - The id of the partition the options apply to.
+        /// * `options` - The options for publishing to the partition.
+        ///
+        /// # Returns
+        ///
+        /// The updated [`ProducerClientBuilder`].
+        pub fn with_partition_publishing_options(
+            mut self,
+            partition_id: String,
+            options: PartitionPublishingOptions,
+        ) -> Self {
+            self.partition_publishing_options
+                .insert(partition_id, options);
+            self
+        }
+
         /// Opens the connection to the Event Hub.
         ///
         /// # Arguments
@@ -675,8 +895,16 @@ pub mod builders {
                 credential,
                 self.application_id,
                 self.retry_options.unwrap_or_default(),
-                custom_endpoint,
+                ConnectionTransport {
+                    custom_endpoint,
+                    transport_type: self.transport_type,
+                    proxy: self.proxy,
+                },
                 None,
+            )
+            .with_idempotent_publishing(
+                self.idempotent_partitions
+                    .then(|| IdempotentPublishing::new(self.partition_publishing_options)),
             );
 
             // Open a connection to the Event Hub to ensure that the client is ready to send messages.
@@ -750,8 +978,16 @@ pub mod builders {
                 credential,
                 self.application_id,
                 self.retry_options.unwrap_or_default(),
-                custom_endpoint,
+                ConnectionTransport {
+                    custom_endpoint,
+                    transport_type: self.transport_type,
+                    proxy: self.proxy,
+                },
                 Some(SAS_TOKEN_TYPE),
+            )
+            .with_idempotent_publishing(
+                self.idempotent_partitions
+                    .then(|| IdempotentPublishing::new(self.partition_publishing_options)),
             );
 
             client.ensure_connection().await?;
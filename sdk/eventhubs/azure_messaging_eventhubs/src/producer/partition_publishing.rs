@@ -0,0 +1,513 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Idempotent publishing to partitions.
+//!
+//! An idempotent producer attaches to a partition as a member of a producer group and stamps
+//! each event with a sequence number. The service refuses events whose sequence numbers don't
+//! follow the last ones published by the group, so a send that is retried after its outcome was
+//! lost doesn't publish the events twice.
+
+use crate::{
+    error::{ErrorKind, Result},
+    models::{AmqpMessage, PartitionPublishingProperties},
+    EventHubsError,
+};
+use azure_core_amqp::{
+    error::AmqpErrorCondition, message::AmqpAnnotationKey, AmqpOrderedMap, AmqpSenderOptions,
+    AmqpSymbol, AmqpValue,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The capability a sender desires on attach to publish idempotently.
+const IDEMPOTENT_PRODUCER_CAPABILITY: &str = "com.microsoft:idempotent-producer";
+
+// The publishing state is exchanged in these link properties on attach, and each event is
+// stamped with it under the same keys.
+const PRODUCER_ID: &str = "com.microsoft:producer-id";
+const PRODUCER_EPOCH: &str = "com.microsoft:producer-epoch";
+const PRODUCER_SEQUENCE_NUMBER: &str = "com.microsoft:producer-sequence-number";
+
+/// The options for publishing to a partition with idempotent publishing enabled.
+///
+/// Options that aren't set are assigned by the service when the producer first publishes to the
+/// partition. To resume publishing as a producer group that was used before, for example after
+/// the application restarts, set them from the [`PartitionPublishingProperties`] the earlier
+/// producer reported.
+///
+/// # Examples
+///
+/// ```
+/// use azure_messaging_eventhubs::PartitionPublishingOptions;
+///
+/// let options = PartitionPublishingOptions {
+///     producer_group_id: Some(42),
+///     owner_level: Some(1),
+///     starting_sequence_number: Some(100),
+/// };
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PartitionPublishingOptions {
+    /// The identifier of the producer group to publish as.
+    pub producer_group_id: Option<i64>,
+
+    /// The owner level of the producer.
+    ///
+    /// A producer that attaches with a higher owner level takes the producer group over, and the
+    /// service disconnects the producers with a lower level.
+    pub owner_level: Option<i16>,
+
+    /// The sequence number of the last event the producer group published.
+    ///
+    /// The next event published is stamped with the sequence number that follows it.
+    pub starting_sequence_number: Option<i32>,
+}
+
+/// The idempotent publishing state of the partitions a producer publishes to.
+pub(crate) struct IdempotentPublishing {
+    options: HashMap<String, PartitionPublishingOptions>,
+    publishers: Mutex<HashMap<String, Arc<PartitionPublisher>>>,
+}
+
+impl IdempotentPublishing {
+    pub(crate) fn new(options: HashMap<String, PartitionPublishingOptions>) -> Self {
+        Self {
+            options,
+            publishers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the publisher of a partition, creating it from the partition's options on first use.
+    pub(crate) fn publisher(&self, partition_id: &str) -> Arc<PartitionPublisher> {
+        self.publishers
+            .lock()
+            .unwrap()
+            .entry(partition_id.to_string())
+            .or_insert_with(|| {
+                Arc::new(PartitionPublisher::new(
+                    self.options.get(partition_id).cloned().unwrap_or_default(),
+                ))
+            })
+            .clone()
+    }
+}
+
+/// The publishing state of a producer for one partition.
+pub(crate) struct PartitionPublisher {
+    // Held from the time events are stamped until their send completes, so that events reach the
+    // partition in the order of their sequence numbers.
+    publish_lock: async_lock::Mutex<()>,
+    state: Mutex<PublisherState>,
+}
+
+struct PublisherState {
+    producer_group_id: Option<i64>,
+    owner_level: Option<i16>,
+    last_published_sequence_number: Option<i32>,
+    /// Whether the service has returned the state on attach.
+    initialized: bool,
+}
+
+impl PartitionPublisher {
+    fn new(options: PartitionPublishingOptions) -> Self {
+        Self {
+            publish_lock: async_lock::Mutex::new(()),
+            state: Mutex::new(PublisherState {
+                producer_group_id: options.producer_group_id,
+                owner_level: options.owner_level,
+                last_published_sequence_number: options.starting_sequence_number,
+                initialized: false,
+            }),
+        }
+    }
+
+    /// Waits for the sends of earlier events to the partition to complete.
+    pub(crate) async fn lock(&self) -> async_lock::MutexGuard<'_, ()> {
+        self.publish_lock.lock().await
+    }
+
+    /// Gets the options to attach a sender to the partition with.
+    ///
+    /// The starting sequence number is only sent on the first attach. Later attaches take the
+    /// last sequence number from the service, since a send whose outcome was lost may have been
+    /// published.
+    pub(crate) fn attach_options(&self) -> AmqpSenderOptions {
+        let state = self.state.lock().unwrap();
+        let mut properties = AmqpOrderedMap::new();
+        if let Some(producer_group_id) = state.producer_group_id {
+            properties.insert(
+                AmqpSymbol::from(PRODUCER_ID),
+                AmqpValue::Long(producer_group_id),
+            );
+        }
+        if let Some(owner_level) = state.owner_level {
+            properties.insert(
+                AmqpSymbol::from(PRODUCER_EPOCH),
+                AmqpValue::Short(owner_level),
+            );
+        }
+        if let (false, Some(sequence_number)) =
+            (state.initialized, state.last_published_sequence_number)
+        {
+            properties.insert(
+                AmqpSymbol::from(PRODUCER_SEQUENCE_NUMBER),
+                AmqpValue::Int(sequence_number),
+            );
+        }
+        AmqpSenderOptions {
+            desired_capabilities: Some(vec![AmqpSymbol::from(IDEMPOTENT_PRODUCER_CAPABILITY)]),
+            properties: Some(properties),
+            ..Default::default()
+        }
+    }
+
+    /// Updates the state from the link properties the service returned on attach.
+    pub(crate) fn update_from_attach(
+        &self,
+        properties: Option<AmqpOrderedMap<AmqpSymbol, AmqpValue>>,
+    ) -> Result<()> {
+        let property = |name: &str| {
+            properties
+                .as_ref()
+                .and_then(|properties| properties.get(&AmqpSymbol::from(name)))
+        };
+        let (
+            Some(AmqpValue::Long(producer_group_id)),
+            Some(AmqpValue::Short(owner_level)),
+            Some(AmqpValue::Int(sequence_number)),
+        ) = (
+            property(PRODUCER_ID),
+            property(PRODUCER_EPOCH),
+            property(PRODUCER_SEQUENCE_NUMBER),
+        )
+        else {
+            return Err(EventHubsError::with_message(
+                "The service did not return the idempotent publishing state of the partition.",
+            ));
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state.producer_group_id = Some(*producer_group_id);
+        state.owner_level = Some(*owner_level);
+        // Sequence numbers wrap to zero, so a negative number means nothing has been published.
+        state.last_published_sequence_number = (*sequence_number >= 0).then_some(*sequence_number);
+        state.initialized = true;
+        Ok(())
+    }
+
+    /// Stamps events with the sequence numbers that follow the last one published.
+    ///
+    /// The batch envelope, if any, is stamped with the sequence number of the first event. The
+    /// sequence numbers aren't recorded as published until [`PartitionPublisher::commit`] is
+    /// called with the last one returned.
+    pub(crate) fn stamp(
+        &self,
+        events: &mut [AmqpMessage],
+        envelope: Option<&mut AmqpMessage>,
+    ) -> Result<Option<i32>> {
+        let state = self.state.lock().unwrap();
+        let (Some(producer_group_id), Some(owner_level), true) = (
+            state.producer_group_id,
+            state.owner_level,
+            state.initialized,
+        ) else {
+            return Err(EventHubsError::with_message(
+                "The idempotent publishing state of the partition is not initialized.",
+            ));
+        };
+        let first = next_sequence_number(state.last_published_sequence_number);
+        drop(state);
+
+        let mut sequence_number = first;
+        let mut last = None;
+        for event in events {
+            stamp_message(event, producer_group_id, owner_level, sequence_number);
+            last = Some(sequence_number);
+            sequence_number = next_sequence_number(Some(sequence_number));
+        }
+        if let Some(envelope) = envelope {
+            stamp_message(envelope, producer_group_id, owner_level, first);
+        }
+        Ok(last)
+    }
+
+    /// Records that the events stamped up to a sequence number were published.
+    pub(crate) fn commit(&self, last_published_sequence_number: Option<i32>) {
+        if let Some(sequence_number) = last_published_sequence_number {
+            self.state.lock().unwrap().last_published_sequence_number = Some(sequence_number);
+        }
+    }
+
+    /// Gets the publishing properties of the partition.
+    pub(crate) fn properties(&self) -> PartitionPublishingProperties {
+        let state = self.state.lock().unwrap();
+        PartitionPublishingProperties {
+            is_idempotent_publishing_enabled: true,
+            producer_group_id: state.producer_group_id,
+            owner_level: state.owner_level,
+            last_published_sequence_number: state.last_published_sequence_number,
+        }
+    }
+}
+
+/// Stamps an event with placeholder publishing state, so that its size is measured with the
+/// largest annotations the event can be published with.
+pub(crate) fn stamp_placeholder(message: &mut AmqpMessage) {
+    stamp_message(message, i64::MAX, i16::MAX, i32::MAX);
+}
+
+/// Gets the sequence number that follows another, wrapping to zero as the service does.
+fn next_sequence_number(last: Option<i32>) -> i32 {
+    last.map_or(0, |last| last.checked_add(1).unwrap_or(0))
+}
+
+fn stamp_message(
+    message: &mut AmqpMessage,
+    producer_group_id: i64,
+    owner_level: i16,
+    sequence_number: i32,
+) {
+    set_annotation(message, PRODUCER_ID, AmqpValue::Long(producer_group_id));
+    set_annotation(message, PRODUCER_EPOCH, AmqpValue::Short(owner_level));
+    set_annotation(
+        message,
+        PRODUCER_SEQUENCE_NUMBER,
+        AmqpValue::Int(sequence_number),
+    );
+}
+
+// `add_message_annotation` appends, so an annotation is removed before it is set again.
+fn set_annotation(message: &mut AmqpMessage, name: &str, value: AmqpValue) {
+    if let Some(annotations) = message.message_annotations.as_mut() {
+        annotations
+            .0
+            .remove(&AmqpAnnotationKey::Symbol(AmqpSymbol::from(name)));
+    }
+    message.add_message_annotation(AmqpSymbol::from(name), value);
+}
+
+/// Translates the failure of an idempotent send into the error kinds of the publishing
+/// conditions.
+pub(crate) fn translate_publish_error(error: EventHubsError) -> EventHubsError {
+    let found = |condition| match &error.kind {
+        ErrorKind::AmqpError(amqp_error) => {
+            crate::error::find_described_error(amqp_error, condition).cloned()
+        }
+        _ => None,
+    };
+    if let Some(described) = found(AmqpErrorCondition::OutOfOrderSequence) {
+        return ErrorKind::SequenceOutOfOrder(Some(described)).into();
+    }
+    if let Some(described) = found(AmqpErrorCondition::ProducerEpochStolen) {
+        return ErrorKind::ProducerDisconnected(Some(described)).into();
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventData;
+    use azure_core_amqp::{AmqpDescribedError, AmqpError, AmqpErrorKind};
+
+    fn service_properties(
+        producer_group_id: i64,
+        owner_level: i16,
+        sequence_number: i32,
+    ) -> AmqpOrderedMap<AmqpSymbol, AmqpValue> {
+        let mut properties = AmqpOrderedMap::new();
+        properties.insert(
+            AmqpSymbol::from(PRODUCER_ID),
+            AmqpValue::Long(producer_group_id),
+        );
+        properties.insert(
+            AmqpSymbol::from(PRODUCER_EPOCH),
+            AmqpValue::Short(owner_level),
+        );
+        properties.insert(
+            AmqpSymbol::from(PRODUCER_SEQUENCE_NUMBER),
+            AmqpValue::Int(sequence_number),
+        );
+        properties
+    }
+
+    fn annotation(message: &AmqpMessage, name: &str) -> Option<AmqpValue> {
+        let annotations = message.message_annotations.as_ref()?;
+        assert_eq!(
+            annotations
+                .0
+                .iter()
+                .filter(|(key, _)| **key == AmqpAnnotationKey::Symbol(AmqpSymbol::from(name)))
+                .count(),
+            1,
+            "{name} must be set once"
+        );
+        annotations
+            .0
+            .get(&AmqpAnnotationKey::Symbol(AmqpSymbol::from(name)))
+            .cloned()
+    }
+
+    #[test]
+    fn sequence_numbers_wrap_to_zero() {
+        assert_eq!(next_sequence_number(None), 0);
+        assert_eq!(next_sequence_number(Some(41)), 42);
+        assert_eq!(next_sequence_number(Some(i32::MAX)), 0);
+    }
+
+    #[test]
+    fn first_attach_requests_the_options() {
+        let publisher = PartitionPublisher::new(PartitionPublishingOptions {
+            producer_group_id: Some(7),
+            owner_level: Some(2),
+            starting_sequence_number: Some(99),
+        });
+        let options = publisher.attach_options();
+        assert_eq!(
+            options.desired_capabilities,
+            Some(vec![AmqpSymbol::from(IDEMPOTENT_PRODUCER_CAPABILITY)])
+        );
+        assert_eq!(options.properties, Some(service_properties(7, 2, 99)));
+
+        let options = PartitionPublisher::new(Default::default()).attach_options();
+        assert_eq!(options.properties.map(|p| p.len()), Some(0));
+    }
+
+    #[test]
+    fn later_attaches_take_the_sequence_number_from_the_service() {
+        let publisher = PartitionPublisher::new(PartitionPublishingOptions {
+            starting_sequence_number: Some(99),
+            ..Default::default()
+        });
+        publisher
+            .update_from_attach(Some(service_properties(7, 0, 99)))
+            .unwrap();
+
+        let properties = publisher.attach_options().properties.unwrap();
+        assert_eq!(
+            properties.get(&AmqpSymbol::from(PRODUCER_ID)),
+            Some(&AmqpValue::Long(7))
+        );
+        assert!(!properties.contains_key(&AmqpSymbol::from(PRODUCER_SEQUENCE_NUMBER)));
+    }
+
+    #[test]
+    fn update_from_attach_requires_the_state() {
+        let publisher = PartitionPublisher::new(Default::default());
+        assert!(publisher.update_from_attach(None).is_err());
+
+        let mut properties = service_properties(1, 0, 0);
+        properties.remove(&AmqpSymbol::from(PRODUCER_EPOCH));
+        assert!(publisher.update_from_attach(Some(properties)).is_err());
+        assert!(publisher
+            .stamp(&mut [AmqpMessage::default()], None)
+            .is_err());
+
+        publisher
+            .update_from_attach(Some(service_properties(1, 0, -1)))
+            .unwrap();
+        assert_eq!(
+            publisher.properties(),
+            PartitionPublishingProperties {
+                is_idempotent_publishing_enabled: true,
+                producer_group_id: Some(1),
+                owner_level: Some(0),
+                last_published_sequence_number: None,
+            }
+        );
+    }
+
+    #[test]
+    fn stamps_consecutive_sequence_numbers() {
+        let publisher = PartitionPublisher::new(Default::default());
+        publisher
+            .update_from_attach(Some(service_properties(3, 1, 10)))
+            .unwrap();
+
+        let mut events = vec![AmqpMessage::default(), AmqpMessage::default()];
+        stamp_placeholder(&mut events[0]);
+        let mut envelope = AmqpMessage::default();
+        let last = publisher.stamp(&mut events, Some(&mut envelope)).unwrap();
+        assert_eq!(last, Some(12));
+        assert_eq!(
+            annotation(&events[0], PRODUCER_SEQUENCE_NUMBER),
+            Some(AmqpValue::Int(11))
+        );
+        assert_eq!(
+            annotation(&events[0], PRODUCER_ID),
+            Some(AmqpValue::Long(3))
+        );
+        assert_eq!(
+            annotation(&events[1], PRODUCER_SEQUENCE_NUMBER),
+            Some(AmqpValue::Int(12))
+        );
+        assert_eq!(
+            annotation(&envelope, PRODUCER_SEQUENCE_NUMBER),
+            Some(AmqpValue::Int(11))
+        );
+        assert_eq!(
+            annotation(&envelope, PRODUCER_EPOCH),
+            Some(AmqpValue::Short(1))
+        );
+
+        // Until the send is committed, the same sequence numbers are stamped again.
+        assert_eq!(publisher.stamp(&mut events, None).unwrap(), Some(12));
+        publisher.commit(last);
+        assert_eq!(
+            publisher.properties().last_published_sequence_number,
+            Some(12)
+        );
+        assert_eq!(publisher.stamp(&mut events[..1], None).unwrap(), Some(13));
+    }
+
+    #[test]
+    fn placeholder_is_at_least_as_large_as_any_state() {
+        let mut placeholder = AmqpMessage::from(EventData::from("event"));
+        stamp_placeholder(&mut placeholder);
+        let mut stamped = AmqpMessage::from(EventData::from("event"));
+        stamp_message(&mut stamped, 1, 0, 0);
+        assert!(
+            AmqpMessage::serialize(&placeholder).unwrap().len()
+                >= AmqpMessage::serialize(&stamped).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn publishers_are_created_once_per_partition() {
+        let publishing = IdempotentPublishing::new(HashMap::from([(
+            "0".to_string(),
+            PartitionPublishingOptions {
+                owner_level: Some(5),
+                ..Default::default()
+            },
+        )]));
+        let publisher = publishing.publisher("0");
+        assert!(Arc::ptr_eq(&publisher, &publishing.publisher("0")));
+        assert_eq!(publisher.properties().owner_level, Some(5));
+        assert_eq!(publishing.publisher("1").properties().owner_level, None);
+    }
+
+    #[test]
+    fn translates_publishing_conditions() {
+        let described = |condition| {
+            EventHubsError::from(AmqpError::from(AmqpErrorKind::AmqpDescribedError(
+                AmqpDescribedError::new(condition, None, Default::default()),
+            )))
+        };
+        assert!(matches!(
+            translate_publish_error(described(AmqpErrorCondition::OutOfOrderSequence)).kind,
+            ErrorKind::SequenceOutOfOrder(Some(_))
+        ));
+        assert!(matches!(
+            translate_publish_error(described(AmqpErrorCondition::ProducerEpochStolen)).kind,
+            ErrorKind::ProducerDisconnected(Some(_))
+        ));
+        assert!(matches!(
+            translate_publish_error(described(AmqpErrorCondition::ServerBusyError)).kind,
+            ErrorKind::AmqpError(_)
+        ));
+    }
+}
@@ -0,0 +1,138 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+// cspell: ignore hashlittle
+
+//! Client-side partition assignment for partition keys.
+//!
+//! The Event Hubs service assigns an event with a partition key to a partition by hashing the key
+//! with Bob Jenkins' lookup3 `hashlittle2` function. Computing the same hash on the client lets the
+//! buffered producer publish directly to the partition the service would have chosen, so events
+//! with the same partition key stay in order.
+
+/// Returns the index of the partition, out of `partition_count`, that the service assigns to
+/// events with `partition_key`.
+pub(crate) fn partition_index_for_key(partition_key: &str, partition_count: usize) -> usize {
+    debug_assert!(partition_count > 0);
+    let hash_code = i32::from(generate_hash_code(partition_key));
+    let partition_count = i32::try_from(partition_count).unwrap_or(i32::MAX);
+    (hash_code % partition_count).unsigned_abs() as usize
+}
+
+/// Computes the 16-bit hash code of a partition key, as the service does.
+pub(crate) fn generate_hash_code(partition_key: &str) -> i16 {
+    let (hash1, hash2) = compute_hash(partition_key.as_bytes(), 0, 0);
+    // Truncation to 16 bits is part of the service's algorithm.
+    (hash1 ^ hash2) as i16
+}
+
+/// Bob Jenkins' lookup3 `hashlittle2`, returning the primary and secondary hashes.
+fn compute_hash(data: &[u8], seed1: u32, seed2: u32) -> (u32, u32) {
+    let mut a = 0xdead_beef_u32
+        .wrapping_add(data.len() as u32)
+        .wrapping_add(seed1);
+    let mut b = a;
+    let mut c = a.wrapping_add(seed2);
+
+    let mut remaining = data;
+    while remaining.len() > 12 {
+        a = a.wrapping_add(read_u32(&remaining[0..4]));
+        b = b.wrapping_add(read_u32(&remaining[4..8]));
+        c = c.wrapping_add(read_u32(&remaining[8..12]));
+        mix(&mut a, &mut b, &mut c);
+        remaining = &remaining[12..];
+    }
+
+    if remaining.is_empty() {
+        return (c, b);
+    }
+
+    // The final block is zero padded to 12 bytes.
+    let mut tail = [0u8; 12];
+    tail[..remaining.len()].copy_from_slice(remaining);
+    a = a.wrapping_add(read_u32(&tail[0..4]));
+    b = b.wrapping_add(read_u32(&tail[4..8]));
+    c = c.wrapping_add(read_u32(&tail[8..12]));
+    final_mix(&mut a, &mut b, &mut c);
+
+    (c, b)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(4);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(6);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(8);
+    *b = b.wrapping_add(*a);
+    *a = a.wrapping_sub(*c);
+    *a ^= c.rotate_left(16);
+    *c = c.wrapping_add(*b);
+    *b = b.wrapping_sub(*a);
+    *b ^= a.rotate_left(19);
+    *a = a.wrapping_add(*c);
+    *c = c.wrapping_sub(*b);
+    *c ^= b.rotate_left(4);
+    *b = b.wrapping_add(*a);
+}
+
+fn final_mix(a: &mut u32, b: &mut u32, c: &mut u32) {
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(14));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(11));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(25));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(16));
+    *a ^= *c;
+    *a = a.wrapping_sub(c.rotate_left(4));
+    *b ^= *a;
+    *b = b.wrapping_sub(a.rotate_left(14));
+    *c ^= *b;
+    *c = c.wrapping_sub(b.rotate_left(24));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected values are from the driver in Bob Jenkins' reference lookup3.c.
+    #[test]
+    fn compute_hash_matches_reference() {
+        assert_eq!(compute_hash(b"", 0, 0), (0xdeadbeef, 0xdeadbeef));
+        assert_eq!(compute_hash(b"", 0, 0xdeadbeef), (0xbd5b7dde, 0xdeadbeef));
+        assert_eq!(
+            compute_hash(b"", 0xdeadbeef, 0xdeadbeef),
+            (0x9c093ccd, 0xbd5b7dde)
+        );
+        let data = b"Four score and seven years ago";
+        assert_eq!(compute_hash(data, 0, 0), (0x17770551, 0xce7226e6));
+        assert_eq!(compute_hash(data, 0, 1), (0xe3607cae, 0xbd371de4));
+        assert_eq!(compute_hash(data, 1, 0), (0xcd628161, 0x6cbea4b3));
+    }
+
+    #[test]
+    fn empty_key_hashes_to_zero() {
+        assert_eq!(generate_hash_code(""), 0);
+        assert_eq!(partition_index_for_key("", 4), 0);
+    }
+
+    #[test]
+    fn partition_index_is_stable_and_in_range() {
+        for partition_count in [1, 2, 4, 32] {
+            for key in ["a", "device-1", "another partition key", "🦀"] {
+                let index = partition_index_for_key(key, partition_count);
+                assert!(index < partition_count);
+                assert_eq!(index, partition_index_for_key(key, partition_count));
+            }
+        }
+    }
+}
@@ -0,0 +1,499 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Shared utilities for testing implementations of the Event Hubs traits.
+//!
+//! [`CheckpointStoreConformance`] checks that a [`CheckpointStore`] behaves the way the
+//! [`EventProcessor`](crate::EventProcessor) expects. The checkpoint stores in this repository
+//! all run it, and a custom store can run it from its own tests:
+//!
+//! ```no_run
+//! use azure_messaging_eventhubs::{test::CheckpointStoreConformance, InMemoryCheckpointStore};
+//!
+//! # async fn conformance() -> azure_core::Result<()> {
+//! let store = InMemoryCheckpointStore::new();
+//! CheckpointStoreConformance::new(&store).run().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    models::{Checkpoint, Ownership},
+    CheckpointStore,
+};
+use azure_core::{http::Etag, Result, Uuid};
+use futures::future::join_all;
+
+const CONSUMER_GROUP: &str = "$Default";
+
+/// Checks that a [`CheckpointStore`] meets the contract the event processor relies on.
+///
+/// Each check panics with a description of the first difference it finds, and returns the
+/// errors of the store unchanged. Every instance uses a new, randomly named namespace, so the
+/// checks can run against a store that holds other records, and can run concurrently.
+///
+/// The contract is the one the blob checkpoint store implements:
+///
+/// * A claim of a partition that has no ownership record succeeds only without an ETag.
+/// * A claim of a partition that has an ownership record succeeds only with its current ETag.
+/// * A claim that fails is left out of the result of `claim_ownership`, rather than failing
+///   the other claims.
+/// * Every successful claim returns a new ETag and the time the store recorded the claim.
+/// * Listing returns the records of one namespace, Event Hub, and consumer group.
+/// * The last checkpoint written for a partition is the one listed.
+pub struct CheckpointStoreConformance<'a> {
+    store: &'a dyn CheckpointStore,
+    namespace: String,
+}
+
+impl<'a> CheckpointStoreConformance<'a> {
+    /// Creates the checks for a checkpoint store.
+    pub fn new(store: &'a dyn CheckpointStore) -> Self {
+        Self {
+            store,
+            namespace: format!("conformance-{}.servicebus.windows.net", Uuid::new_v4()),
+        }
+    }
+
+    /// Runs every check.
+    pub async fn run(&self) -> Result<()> {
+        self.claim_new_ownership().await?;
+        self.claim_ownership_race().await?;
+        self.claim_with_mismatched_etag().await?;
+        self.claim_expired_ownership().await?;
+        self.list_by_consumer_group().await?;
+        self.checkpoint_round_trip().await?;
+        Ok(())
+    }
+
+    /// Checks that a partition without an ownership record can be claimed, and that the claim
+    /// is listed.
+    pub async fn claim_new_ownership(&self) -> Result<()> {
+        const EVENT_HUB: &str = "claim-new";
+
+        assert!(
+            self.store.claim_ownership(&[]).await?.is_empty(),
+            "claiming no partitions must claim none"
+        );
+
+        let claimed = self
+            .store
+            .claim_ownership(&[
+                self.ownership(EVENT_HUB, "0", "owner"),
+                self.ownership(EVENT_HUB, "1", "owner"),
+            ])
+            .await?;
+        assert_eq!(
+            partition_ids(&claimed),
+            ["0", "1"],
+            "both partitions must be claimed"
+        );
+        for ownership in &claimed {
+            assert_claimed(ownership, "owner");
+        }
+
+        let listed = self.list_ownerships(EVENT_HUB).await?;
+        assert_eq!(
+            partition_ids(&listed),
+            ["0", "1"],
+            "both claims must be listed"
+        );
+        for ownership in &listed {
+            let claim = claimed
+                .iter()
+                .find(|claim| claim.partition_id == ownership.partition_id)
+                .expect("the listed partition was claimed");
+            assert_eq!(
+                ownership.owner_id, claim.owner_id,
+                "the listed owner must be the claimed one"
+            );
+            assert_eq!(
+                ownership.etag, claim.etag,
+                "the listed ETag must be the one the claim returned"
+            );
+            assert!(
+                ownership.last_modified_time.is_some(),
+                "a listed ownership must have a last modified time"
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that when several owners claim the same partition at once, exactly one of them
+    /// wins, for a partition without an ownership record and for one with a record.
+    pub async fn claim_ownership_race(&self) -> Result<()> {
+        const EVENT_HUB: &str = "claim-race";
+        const OWNERS: usize = 4;
+
+        let claims: Vec<_> = (0..OWNERS)
+            .map(|i| vec![self.ownership(EVENT_HUB, "0", &format!("owner-{i}"))])
+            .collect();
+        let winners = self.claim_concurrently(&claims).await?;
+        assert_eq!(
+            winners.len(),
+            1,
+            "exactly one claim of a new partition must win, got {winners:?}"
+        );
+
+        let listed = self.list_ownerships(EVENT_HUB).await?;
+        assert_eq!(listed.len(), 1, "a partition must have one ownership");
+        assert_eq!(
+            listed[0].owner_id, winners[0].owner_id,
+            "the listed owner must be the winner"
+        );
+
+        // Every owner now presents the same, current ETag, as owners that listed the
+        // ownership at the same time would.
+        let claims: Vec<_> = (0..OWNERS)
+            .map(|i| {
+                vec![Ownership {
+                    owner_id: Some(format!("owner-{i}")),
+                    ..listed[0].clone()
+                }]
+            })
+            .collect();
+        let winners = self.claim_concurrently(&claims).await?;
+        assert_eq!(
+            winners.len(),
+            1,
+            "exactly one claim with the same ETag must win, got {winners:?}"
+        );
+        assert_ne!(
+            winners[0].etag, listed[0].etag,
+            "the winning claim must change the ETag"
+        );
+        Ok(())
+    }
+
+    /// Checks that a claim with an ETag other than the current one loses, without failing the
+    /// other claims of the same call.
+    pub async fn claim_with_mismatched_etag(&self) -> Result<()> {
+        const EVENT_HUB: &str = "claim-etag";
+
+        let first = self
+            .claim_one(self.ownership(EVENT_HUB, "0", "first"))
+            .await?
+            .expect("a new partition can be claimed");
+
+        // An owner that never saw the ownership cannot take it without an ETag.
+        let claimed = self
+            .store
+            .claim_ownership(&[
+                self.ownership(EVENT_HUB, "0", "second"),
+                self.ownership(EVENT_HUB, "1", "second"),
+            ])
+            .await?;
+        assert_eq!(
+            partition_ids(&claimed),
+            ["1"],
+            "a claim without an ETag of an owned partition must lose, and only it"
+        );
+
+        // An ETag the store never returned loses.
+        let claimed = self
+            .store
+            .claim_ownership(&[Ownership {
+                owner_id: Some("second".to_string()),
+                etag: Some(Etag::from(format!("\"{}\"", Uuid::new_v4()))),
+                ..first.clone()
+            }])
+            .await?;
+        assert!(
+            claimed.is_empty(),
+            "a claim with an unknown ETag must lose, got {claimed:?}"
+        );
+
+        // Renewing makes the previous ETag stale.
+        let renewed = self
+            .claim_one(first.clone())
+            .await?
+            .expect("the owner can renew with the current ETag");
+        assert_ne!(renewed.etag, first.etag, "a renewal must change the ETag");
+        let stale = self
+            .claim_one(Ownership {
+                owner_id: Some("second".to_string()),
+                ..first
+            })
+            .await?;
+        assert!(
+            stale.is_none(),
+            "a claim with a stale ETag must lose, got {stale:?}"
+        );
+
+        let listed = self.list_ownerships(EVENT_HUB).await?;
+        let owner = listed
+            .iter()
+            .find(|ownership| ownership.partition_id == "0")
+            .and_then(|ownership| ownership.owner_id.as_deref());
+        assert_eq!(
+            owner,
+            Some("first"),
+            "a lost claim must not change the owner"
+        );
+        Ok(())
+    }
+
+    /// Checks that an ownership another owner has stopped renewing, or has relinquished, can
+    /// be claimed with the ETag it is listed with, and that the previous owner loses it.
+    ///
+    /// Whether an ownership has expired is decided by the event processor from the last
+    /// modified time of the ownership, so the store must report it and refresh it on every
+    /// claim.
+    pub async fn claim_expired_ownership(&self) -> Result<()> {
+        const EVENT_HUB: &str = "claim-expired";
+
+        let expired = self
+            .claim_one(self.ownership(EVENT_HUB, "0", "expired"))
+            .await?
+            .expect("a new partition can be claimed");
+        let listed = self.list_ownerships(EVENT_HUB).await?;
+        let listed_time = listed[0]
+            .last_modified_time
+            .expect("a listed ownership must have a last modified time");
+
+        let taken = self
+            .claim_one(Ownership {
+                owner_id: Some("taker".to_string()),
+                ..listed[0].clone()
+            })
+            .await?
+            .expect("an expired ownership can be claimed with its listed ETag");
+        assert_claimed(&taken, "taker");
+        assert!(
+            taken.last_modified_time.expect("checked by assert_claimed") >= listed_time,
+            "a claim must refresh the last modified time"
+        );
+
+        let renewal = self.claim_one(expired).await?;
+        assert!(
+            renewal.is_none(),
+            "the previous owner must lose the partition, got {renewal:?}"
+        );
+
+        // Relinquishing is a claim without an owner, which another owner can then claim.
+        let relinquished = self
+            .claim_one(Ownership {
+                owner_id: None,
+                ..taken
+            })
+            .await?
+            .expect("the owner can relinquish the partition");
+        let claimed = self
+            .claim_one(Ownership {
+                owner_id: Some("next".to_string()),
+                ..relinquished
+            })
+            .await?
+            .expect("a relinquished partition can be claimed");
+        assert_claimed(&claimed, "next");
+        Ok(())
+    }
+
+    /// Checks that ownerships and checkpoints are listed by namespace, Event Hub, and consumer
+    /// group, including for names that are prefixes of each other.
+    pub async fn list_by_consumer_group(&self) -> Result<()> {
+        const EVENT_HUB: &str = "list";
+        let other_namespace = format!("{}x", self.namespace);
+        let scopes = [
+            (self.namespace.as_str(), EVENT_HUB, CONSUMER_GROUP),
+            (self.namespace.as_str(), "list-other", CONSUMER_GROUP),
+            (self.namespace.as_str(), EVENT_HUB, "other"),
+            (other_namespace.as_str(), EVENT_HUB, CONSUMER_GROUP),
+        ];
+
+        for (i, (namespace, event_hub_name, consumer_group)) in scopes.iter().enumerate() {
+            // Each scope has its own partitions, so a listing that leaks is caught.
+            let partitions = [format!("{i}"), format!("{i}-1")];
+            for partition_id in &partitions {
+                let ownership = Ownership {
+                    fully_qualified_namespace: namespace.to_string(),
+                    event_hub_name: event_hub_name.to_string(),
+                    consumer_group: consumer_group.to_string(),
+                    partition_id: partition_id.clone(),
+                    owner_id: Some("owner".to_string()),
+                    ..Default::default()
+                };
+                self.claim_one(ownership)
+                    .await?
+                    .expect("a new partition can be claimed");
+                self.store
+                    .update_checkpoint(Checkpoint {
+                        fully_qualified_namespace: namespace.to_string(),
+                        event_hub_name: event_hub_name.to_string(),
+                        consumer_group: consumer_group.to_string(),
+                        partition_id: partition_id.clone(),
+                        offset: Some("0".to_string()),
+                        sequence_number: Some(0),
+                    })
+                    .await?;
+            }
+        }
+
+        for (i, (namespace, event_hub_name, consumer_group)) in scopes.iter().enumerate() {
+            let expected = [format!("{i}"), format!("{i}-1")];
+
+            let ownerships = self
+                .store
+                .list_ownerships(namespace, event_hub_name, consumer_group)
+                .await?;
+            assert_eq!(
+                partition_ids(&ownerships),
+                expected,
+                "the ownerships of {namespace}/{event_hub_name}/{consumer_group} must be listed"
+            );
+            for ownership in &ownerships {
+                assert_eq!(ownership.fully_qualified_namespace, *namespace);
+                assert_eq!(ownership.event_hub_name, *event_hub_name);
+                assert_eq!(ownership.consumer_group, *consumer_group);
+            }
+
+            let mut checkpoints = self
+                .store
+                .list_checkpoints(namespace, event_hub_name, consumer_group)
+                .await?;
+            checkpoints.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
+            let checkpoint_partitions: Vec<_> = checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.partition_id.as_str())
+                .collect();
+            assert_eq!(
+                checkpoint_partitions, expected,
+                "the checkpoints of {namespace}/{event_hub_name}/{consumer_group} must be listed"
+            );
+            for checkpoint in &checkpoints {
+                assert_eq!(checkpoint.fully_qualified_namespace, *namespace);
+                assert_eq!(checkpoint.event_hub_name, *event_hub_name);
+                assert_eq!(checkpoint.consumer_group, *consumer_group);
+            }
+        }
+
+        assert!(
+            self.list_ownerships("list-none").await?.is_empty(),
+            "an Event Hub without ownerships must list none"
+        );
+        assert!(
+            self.store
+                .list_checkpoints(&self.namespace, "list-none", CONSUMER_GROUP)
+                .await?
+                .is_empty(),
+            "an Event Hub without checkpoints must list none"
+        );
+        Ok(())
+    }
+
+    /// Checks that a checkpoint is listed as it was written, and that writing it again
+    /// replaces it.
+    pub async fn checkpoint_round_trip(&self) -> Result<()> {
+        const EVENT_HUB: &str = "checkpoint";
+
+        let checkpoint = |partition_id: &str, offset: &str, sequence_number: i64| Checkpoint {
+            fully_qualified_namespace: self.namespace.clone(),
+            event_hub_name: EVENT_HUB.to_string(),
+            consumer_group: CONSUMER_GROUP.to_string(),
+            partition_id: partition_id.to_string(),
+            offset: Some(offset.to_string()),
+            sequence_number: Some(sequence_number),
+        };
+        let expected = [checkpoint("0", "4096", 10), checkpoint("1", "8192", 20)];
+        self.store
+            .update_checkpoint(checkpoint("0", "0", 0))
+            .await?;
+        for checkpoint in &expected {
+            self.store.update_checkpoint(checkpoint.clone()).await?;
+        }
+
+        let mut listed = self
+            .store
+            .list_checkpoints(&self.namespace, EVENT_HUB, CONSUMER_GROUP)
+            .await?;
+        listed.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
+        assert_eq!(
+            listed.len(),
+            expected.len(),
+            "each partition must have one checkpoint, got {listed:?}"
+        );
+        for (listed, expected) in listed.iter().zip(&expected) {
+            assert_eq!(listed.partition_id, expected.partition_id);
+            assert_eq!(
+                listed.offset, expected.offset,
+                "the last offset written for partition {} must be listed",
+                expected.partition_id
+            );
+            assert_eq!(
+                listed.sequence_number, expected.sequence_number,
+                "the last sequence number written for partition {} must be listed",
+                expected.partition_id
+            );
+        }
+        Ok(())
+    }
+
+    fn ownership(&self, event_hub_name: &str, partition_id: &str, owner_id: &str) -> Ownership {
+        Ownership {
+            fully_qualified_namespace: self.namespace.clone(),
+            event_hub_name: event_hub_name.to_string(),
+            consumer_group: CONSUMER_GROUP.to_string(),
+            partition_id: partition_id.to_string(),
+            owner_id: Some(owner_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    async fn list_ownerships(&self, event_hub_name: &str) -> Result<Vec<Ownership>> {
+        let mut ownerships = self
+            .store
+            .list_ownerships(&self.namespace, event_hub_name, CONSUMER_GROUP)
+            .await?;
+        ownerships.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
+        Ok(ownerships)
+    }
+
+    /// Claims one ownership, returning `None` if the claim lost.
+    async fn claim_one(&self, ownership: Ownership) -> Result<Option<Ownership>> {
+        let partition_id = ownership.partition_id.clone();
+        let mut claimed = self.store.claim_ownership(&[ownership]).await?;
+        assert!(
+            claimed.len() <= 1,
+            "one claim must return at most one ownership, got {claimed:?}"
+        );
+        if let Some(ownership) = &claimed.first() {
+            assert_eq!(
+                ownership.partition_id, partition_id,
+                "a claim must return the claimed partition"
+            );
+        }
+        Ok(claimed.pop())
+    }
+
+    /// Runs the claims at the same time and returns the ownerships that won.
+    async fn claim_concurrently(&self, claims: &[Vec<Ownership>]) -> Result<Vec<Ownership>> {
+        let results = join_all(claims.iter().map(|claim| self.store.claim_ownership(claim))).await;
+        let mut winners = Vec::new();
+        for result in results {
+            winners.extend(result?);
+        }
+        Ok(winners)
+    }
+}
+
+fn partition_ids(ownerships: &[Ownership]) -> Vec<&str> {
+    let mut partition_ids: Vec<_> = ownerships
+        .iter()
+        .map(|ownership| ownership.partition_id.as_str())
+        .collect();
+    partition_ids.sort();
+    partition_ids
+}
+
+fn assert_claimed(ownership: &Ownership, owner_id: &str) {
+    assert_eq!(
+        ownership.owner_id.as_deref(),
+        Some(owner_id),
+        "a claim must return the claimed owner"
+    );
+    assert!(ownership.etag.is_some(), "a claim must return an ETag");
+    assert!(
+        ownership.last_modified_time.is_some(),
+        "a claim must return the last modified time"
+    );
+}
@@ -13,7 +13,8 @@ use std::sync::Arc;
 
 use azure_messaging_eventhubs::{
     models::{Checkpoint, Ownership},
-    InMemoryCheckpointStore,
+    test::CheckpointStoreConformance,
+    FileCheckpointStore, InMemoryCheckpointStore,
 };
 use tracing::info;
 
@@ -201,3 +202,20 @@ async fn checkpoints() -> azure_core::Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn in_memory_checkpoint_store_conformance() -> azure_core::Result<()> {
+    common::setup();
+    let store = InMemoryCheckpointStore::new();
+    CheckpointStoreConformance::new(&store).run().await
+}
+
+#[tokio::test]
+async fn file_checkpoint_store_conformance() -> azure_core::Result<()> {
+    common::setup();
+    let directory = std::env::temp_dir().join(get_random_name("eventhubs-checkpoints-"));
+    let store = FileCheckpointStore::new(&directory);
+    let result = CheckpointStoreConformance::new(&store).run().await;
+    let _ = std::fs::remove_dir_all(&directory);
+    result
+}
@@ -0,0 +1,382 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Tests that run the Event Hubs clients against the local AMQP test broker.
+
+use azure_core::{
+    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    time::{Duration, OffsetDateTime},
+};
+use azure_core_amqp_test::TestBroker;
+use azure_messaging_eventhubs::{
+    error::ErrorKind, models::StartPositions, CheckpointPolicy, CheckpointStore, ConsumerClient,
+    EventBatchContext, EventDataBatchOptions, EventProcessor, EventProcessorHandler,
+    InMemoryCheckpointStore, OpenReceiverOptions, PartitionCloseReason, PartitionClosingContext,
+    PartitionPublishingOptions, ProcessingErrorContext, ProducerClient, SendEventOptions,
+    StartLocation, StartPosition,
+};
+use futures::StreamExt;
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+const NAMESPACE: &str = "test.servicebus.windows.net";
+const EVENT_HUB: &str = "eventhub";
+
+/// Issues tokens that outlive the tests, so the clients don't refresh them while a test runs.
+#[derive(Debug)]
+struct TestCredential;
+
+#[async_trait::async_trait]
+impl TokenCredential for TestCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        _options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        Ok(AccessToken::new(
+            format!("TEST TOKEN {}", scopes.join(" ")),
+            OffsetDateTime::now_utc() + Duration::hours(1),
+        ))
+    }
+}
+
+#[tokio::test]
+async fn send_and_receive() -> Result<(), Box<dyn Error>> {
+    let broker = TestBroker::builder()
+        .with_event_hub(EVENT_HUB, 2)
+        .start()
+        .await?;
+
+    let producer = ProducerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, EVENT_HUB, Arc::new(TestCredential))
+        .await?;
+    let properties = producer.get_eventhub_properties().await?;
+    assert_eq!(properties.name, EVENT_HUB);
+    assert_eq!(properties.partition_ids, ["0", "1"]);
+
+    for body in ["first", "second"] {
+        producer
+            .send_event(
+                body,
+                Some(SendEventOptions {
+                    partition_id: Some("1".to_string()),
+                }),
+            )
+            .await?;
+    }
+    assert_eq!(broker.event_count(EVENT_HUB, "1"), Some(2));
+    assert_eq!(broker.event_count(EVENT_HUB, "0"), Some(0));
+
+    let partition = producer.get_partition_properties("1").await?;
+    assert_eq!(partition.last_enqueued_sequence_number, 1);
+
+    let consumer = ConsumerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, EVENT_HUB.to_string(), Arc::new(TestCredential))
+        .await?;
+    let receiver = consumer
+        .open_receiver_on_partition(
+            "1".to_string(),
+            Some(OpenReceiverOptions {
+                start_position: Some(StartPosition {
+                    location: StartLocation::Earliest,
+                    ..Default::default()
+                }),
+                receive_timeout: Some(Duration::seconds(5)),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    let events: Vec<_> = receiver.stream_events().take(2).collect().await;
+    let mut sequence_numbers = Vec::new();
+    let mut bodies = Vec::new();
+    for event in events {
+        let event = event?;
+        sequence_numbers.push(event.sequence_number());
+        bodies.push(event.event_data().body().map(<[u8]>::to_vec));
+    }
+    assert_eq!(sequence_numbers, [Some(0), Some(1)]);
+    assert_eq!(bodies, [Some(b"first".to_vec()), Some(b"second".to_vec())]);
+
+    receiver.close().await?;
+    consumer.close().await?;
+    producer.close().await?;
+    Ok(())
+}
+
+async fn open_idempotent_producer(
+    broker: &TestBroker,
+    options: Option<PartitionPublishingOptions>,
+) -> azure_messaging_eventhubs::Result<ProducerClient> {
+    let mut builder = ProducerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .with_idempotent_partitions(true);
+    if let Some(options) = options {
+        builder = builder.with_partition_publishing_options("0".to_string(), options);
+    }
+    builder
+        .open(NAMESPACE, EVENT_HUB, Arc::new(TestCredential))
+        .await
+}
+
+fn to_partition(partition_id: &str) -> Option<SendEventOptions> {
+    Some(SendEventOptions {
+        partition_id: Some(partition_id.to_string()),
+    })
+}
+
+#[tokio::test]
+async fn publish_idempotently() -> Result<(), Box<dyn Error>> {
+    let broker = TestBroker::builder()
+        .with_event_hub(EVENT_HUB, 2)
+        .start()
+        .await?;
+
+    let producer = open_idempotent_producer(&broker, None).await?;
+    let properties = producer.get_partition_publishing_properties("0").await?;
+    assert!(properties.is_idempotent_publishing_enabled);
+    assert!(properties.producer_group_id.is_some());
+    assert_eq!(properties.owner_level, Some(0));
+    assert_eq!(properties.last_published_sequence_number, None);
+
+    producer.send_event("first", to_partition("0")).await?;
+    producer.send_event("second", to_partition("0")).await?;
+    let batch = producer
+        .create_batch(Some(EventDataBatchOptions {
+            partition_id: Some("0".to_string()),
+            ..Default::default()
+        }))
+        .await?;
+    for body in ["third", "fourth", "fifth"] {
+        assert!(batch.try_add_event_data(body, None)?);
+    }
+    producer.send_batch(batch, None).await?;
+    assert_eq!(broker.event_count(EVENT_HUB, "0"), Some(5));
+    let properties = producer.get_partition_publishing_properties("0").await?;
+    assert_eq!(properties.last_published_sequence_number, Some(4));
+
+    // Events are tracked per partition, so they can't be routed by the service.
+    assert!(producer.send_event("routed", None).await.is_err());
+    assert!(producer
+        .create_batch(Some(EventDataBatchOptions {
+            partition_id: Some("0".to_string()),
+            partition_key: Some("key".to_string()),
+            ..Default::default()
+        }))
+        .await
+        .is_err());
+
+    // A producer with a higher owner level takes the producer group over.
+    let successor = open_idempotent_producer(
+        &broker,
+        Some(PartitionPublishingOptions {
+            producer_group_id: properties.producer_group_id,
+            owner_level: Some(1),
+            starting_sequence_number: properties.last_published_sequence_number,
+        }),
+    )
+    .await?;
+    successor.send_event("sixth", to_partition("0")).await?;
+    assert_eq!(
+        successor
+            .get_partition_publishing_properties("0")
+            .await?
+            .last_published_sequence_number,
+        Some(5)
+    );
+    let error = producer
+        .send_event("stolen", to_partition("0"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error.kind, ErrorKind::ProducerDisconnected(_)),
+        "{error:?}"
+    );
+
+    // A producer that resets the sequence number of the group puts the successor out of order.
+    // The successor's next send takes the sequence number from the service again.
+    let reset = open_idempotent_producer(
+        &broker,
+        Some(PartitionPublishingOptions {
+            producer_group_id: properties.producer_group_id,
+            owner_level: Some(1),
+            starting_sequence_number: Some(1),
+        }),
+    )
+    .await?;
+    reset.get_partition_publishing_properties("0").await?;
+    let error = successor
+        .send_event("out of order", to_partition("0"))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error.kind, ErrorKind::SequenceOutOfOrder(_)),
+        "{error:?}"
+    );
+    successor.send_event("seventh", to_partition("0")).await?;
+    assert_eq!(
+        successor
+            .get_partition_publishing_properties("0")
+            .await?
+            .last_published_sequence_number,
+        Some(2)
+    );
+    assert_eq!(broker.event_count(EVENT_HUB, "0"), Some(7));
+
+    // Without idempotent partitions, nothing is tracked.
+    let plain = ProducerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, EVENT_HUB, Arc::new(TestCredential))
+        .await?;
+    assert_eq!(
+        plain.get_partition_publishing_properties("0").await?,
+        Default::default()
+    );
+
+    plain.close().await?;
+    reset.close().await?;
+    successor.close().await?;
+    producer.close().await?;
+    Ok(())
+}
+
+/// Forwards the bodies of the events it processes, and records why partitions were closed.
+struct ForwardingHandler {
+    bodies: mpsc::UnboundedSender<(String, Vec<u8>)>,
+    closed: Mutex<Vec<(String, PartitionCloseReason)>>,
+}
+
+#[async_trait::async_trait]
+impl EventProcessorHandler for ForwardingHandler {
+    async fn on_events(
+        &self,
+        context: &EventBatchContext,
+    ) -> azure_messaging_eventhubs::Result<()> {
+        for event in &context.events {
+            let body = event.event_data().body().unwrap_or_default().to_vec();
+            let _ = self.bodies.send((context.partition_id.clone(), body));
+        }
+        Ok(())
+    }
+
+    async fn on_partition_closing(&self, context: PartitionClosingContext) {
+        self.closed
+            .lock()
+            .unwrap()
+            .push((context.partition_id, context.reason));
+    }
+
+    async fn on_error(&self, context: ProcessingErrorContext) {
+        panic!(
+            "unexpected error on partition {}: {:?}",
+            context.partition_id, context.error
+        );
+    }
+}
+
+#[tokio::test]
+async fn process_events_with_handler() -> Result<(), Box<dyn Error>> {
+    let broker = TestBroker::builder()
+        .with_event_hub(EVENT_HUB, 2)
+        .start()
+        .await?;
+
+    let producer = ProducerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, EVENT_HUB, Arc::new(TestCredential))
+        .await?;
+    for body in ["first", "second", "third"] {
+        producer
+            .send_event(
+                body,
+                Some(SendEventOptions {
+                    partition_id: Some("0".to_string()),
+                }),
+            )
+            .await?;
+    }
+
+    let consumer = ConsumerClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, EVENT_HUB.to_string(), Arc::new(TestCredential))
+        .await?;
+    let (sender, mut bodies) = mpsc::unbounded_channel();
+    let handler = Arc::new(ForwardingHandler {
+        bodies: sender,
+        closed: Mutex::new(Vec::new()),
+    });
+    let checkpoint_store = Arc::new(InMemoryCheckpointStore::new());
+    let processor = EventProcessor::builder()
+        .with_event_handler(handler.clone())
+        .with_checkpoint_policy(CheckpointPolicy {
+            event_count: Some(3),
+            ..Default::default()
+        })
+        .with_start_positions(StartPositions {
+            default: StartPosition {
+                location: StartLocation::Earliest,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .with_update_interval(Duration::seconds(1))
+        .with_partition_expiration_duration(Duration::seconds(10))
+        .build(consumer, checkpoint_store.clone())
+        .await?;
+    assert!(processor.next_partition_client().await.is_err());
+
+    let running = tokio::spawn({
+        let processor = processor.clone();
+        async move { processor.run().await }
+    });
+
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        let (partition_id, body) =
+            tokio::time::timeout(std::time::Duration::from_secs(10), bodies.recv())
+                .await?
+                .expect("the handler should forward the events");
+        assert_eq!(partition_id, "0");
+        received.push(body);
+    }
+    assert_eq!(
+        received,
+        [b"first".to_vec(), b"second".to_vec(), b"third".to_vec()]
+    );
+
+    let mut sequence_number = None;
+    for _ in 0..50 {
+        let checkpoints = checkpoint_store
+            .list_checkpoints(NAMESPACE, EVENT_HUB, "$Default")
+            .await?;
+        sequence_number = checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.partition_id == "0")
+            .and_then(|checkpoint| checkpoint.sequence_number);
+        if sequence_number.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(sequence_number, Some(2));
+
+    processor.shutdown().await?;
+    tokio::time::timeout(std::time::Duration::from_secs(10), running).await???;
+    let mut closed = handler.closed.lock().unwrap().clone();
+    closed.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        closed,
+        [
+            ("0".to_string(), PartitionCloseReason::Shutdown),
+            ("1".to_string(), PartitionCloseReason::Shutdown),
+        ]
+    );
+
+    producer.close().await?;
+    Ok(())
+}
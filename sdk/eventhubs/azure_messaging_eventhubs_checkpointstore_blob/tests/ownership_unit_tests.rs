@@ -5,7 +5,9 @@
 
 use azure_core::{http::Etag, time::OffsetDateTime, Result};
 use azure_core_test::{recorded, Recording, TestContext};
-use azure_messaging_eventhubs::{models::Ownership, CheckpointStore};
+use azure_messaging_eventhubs::{
+    models::Ownership, test::CheckpointStoreConformance, CheckpointStore,
+};
 mod checkpoint_unit_tests;
 use checkpoint_unit_tests::create_test_checkpoint_store;
 use tracing::trace;
@@ -342,3 +344,12 @@ async fn claim_ownership_no_owner_id(ctx: TestContext) -> Result<()> {
 
     Ok(())
 }
+
+// The conformance checks use random names, so they run live and are not recorded.
+#[recorded::test(live)]
+async fn checkpoint_store_conformance(ctx: TestContext) -> Result<()> {
+    let checkpoint_store = create_test_checkpoint_store(ctx.recording())?;
+    CheckpointStoreConformance::new(checkpoint_store.as_ref())
+        .run()
+        .await
+}
@@ -0,0 +1,434 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Azure Cosmos DB implementation of the Event Hubs checkpoint store.
+use azure_core::{
+    error::ErrorKind,
+    http::{Etag, StatusCode},
+    time::OffsetDateTime,
+    Error, Result,
+};
+use azure_data_cosmos::{
+    clients::ContainerClient,
+    models::ItemResponse,
+    options::{ContentResponseOnWrite, ItemWriteOptions, OperationOptions, Precondition},
+    CosmosError, FeedScope,
+};
+use azure_messaging_eventhubs::{
+    models::{Checkpoint, Ownership},
+    CheckpointStore,
+};
+use futures::TryStreamExt;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+/// The partition key path of the container that holds the checkpoint store documents.
+///
+/// The container must be created with this partition key path before the store is used.
+pub const PARTITION_KEY_PATH: &str = "/partitionKey";
+
+const LIST_QUERY: &str = "SELECT * FROM c";
+
+/// Azure Cosmos DB implementation of the [`CheckpointStore`] trait.
+///
+/// Checkpoints and ownerships are stored as documents in a Cosmos DB container partitioned by
+/// [`PARTITION_KEY_PATH`]. The documents of one Event Hub consumer group share a logical
+/// partition for checkpoints and another for ownerships, so listing them is a single-partition
+/// query. Ownership claims use the document's etag as a precondition, so only one event
+/// processor can claim a partition from a given ownership.
+#[derive(Clone)]
+pub struct CosmosCheckpointStore {
+    container_client: Arc<ContainerClient>,
+}
+
+/// A checkpoint, as stored in the container.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckpointDocument {
+    id: String,
+    partition_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sequence_number: Option<i64>,
+}
+
+/// An ownership, as stored in the container.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnershipDocument {
+    id: String,
+    partition_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner_id: Option<String>,
+    #[serde(rename = "_etag", default, skip_serializing)]
+    etag: Option<String>,
+    #[serde(rename = "_ts", default, skip_serializing)]
+    timestamp: Option<i64>,
+}
+
+/// Converts a Cosmos DB error into the error returned by the checkpoint store.
+///
+/// `azure_data_cosmos` is built on the released `azure_core`, so its own conversion produces an
+/// error type Event Hubs does not use. The [`CosmosError`] is kept as the source.
+fn cosmos_error(error: CosmosError) -> Error {
+    let kind = match error.response() {
+        Some(_) => ErrorKind::HttpResponse {
+            status: StatusCode::from(u16::from(error.status().status_code())),
+            error_code: None,
+            raw_response: None,
+        },
+        None => ErrorKind::Other,
+    };
+    Error::new(kind, error)
+}
+
+impl OwnershipDocument {
+    fn last_modified_time(&self) -> Option<OffsetDateTime> {
+        self.timestamp
+            .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+    }
+}
+
+impl CosmosCheckpointStore {
+    /// Creates a new Cosmos DB checkpoint store.
+    ///
+    /// # Arguments
+    ///
+    /// * `container_client` - The client for a container partitioned by [`PARTITION_KEY_PATH`].
+    pub fn new(container_client: ContainerClient) -> Arc<Self> {
+        Arc::new(Self {
+            container_client: Arc::new(container_client),
+        })
+    }
+
+    async fn query_partition<T: DeserializeOwned + Send + 'static>(
+        &self,
+        partition_key: &str,
+    ) -> Result<Vec<T>> {
+        let documents = self
+            .container_client
+            .query_items::<T>(
+                LIST_QUERY,
+                FeedScope::partition(partition_key.to_string()),
+                None,
+            )
+            .await
+            .map_err(cosmos_error)?
+            .try_collect()
+            .await
+            .map_err(cosmos_error)?;
+        Ok(documents)
+    }
+
+    /// Writes an ownership document, creating it when the ownership has no etag and replacing
+    /// it only if it is unchanged otherwise.
+    async fn write_ownership(
+        &self,
+        document: &OwnershipDocument,
+        etag: Option<Etag>,
+    ) -> std::result::Result<ItemResponse, CosmosError> {
+        let mut operation = OperationOptions::default();
+        operation.content_response_on_write = Some(ContentResponseOnWrite::Enabled);
+        let options = ItemWriteOptions::default().with_operation_options(operation);
+        match etag {
+            Some(etag) => {
+                debug!(
+                    "{:?} claiming ownership for {} with etag {:?}",
+                    document.owner_id, document.id, etag
+                );
+                self.container_client
+                    .replace_item(
+                        document.partition_key.clone(),
+                        &document.id,
+                        document,
+                        Some(options.with_precondition(Precondition::IfMatch(
+                            etag.as_ref().to_string().into(),
+                        ))),
+                    )
+                    .await
+            }
+            None => {
+                debug!("Claiming ownership for {} without etag", document.id);
+                self.container_client
+                    .create_item(
+                        document.partition_key.clone(),
+                        &document.id,
+                        document,
+                        Some(options),
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckpointStore for CosmosCheckpointStore {
+    /// Claims ownership of the specified partitions.
+    #[tracing::instrument(level = "debug", skip_all, fields(partition_count = ownerships.len()), err)]
+    async fn claim_ownership(&self, ownerships: &[Ownership]) -> Result<Vec<Ownership>> {
+        debug!("Claiming ownership for {} partitions", ownerships.len());
+
+        let mut new_ownerships = Vec::new();
+        for ownership in ownerships {
+            let document = OwnershipDocument {
+                id: ownership.partition_id.clone(),
+                partition_key: Ownership::get_ownership_prefix_name(
+                    &ownership.fully_qualified_namespace,
+                    &ownership.event_hub_name,
+                    &ownership.consumer_group,
+                )?,
+                owner_id: ownership.owner_id.clone(),
+                etag: None,
+                timestamp: None,
+            };
+
+            let response = match self
+                .write_ownership(&document, ownership.etag.clone())
+                .await
+            {
+                Ok(response) => response,
+                Err(e) if e.status().is_precondition_failed() || e.status().is_not_found() => {
+                    info!(
+                        event = "claim-conflict",
+                        partition_id = %ownership.partition_id,
+                        owner_id = ?ownership.owner_id,
+                        etag = ?ownership.etag,
+                        "Lost ownership claim: precondition (etag) failed"
+                    );
+                    continue;
+                }
+                Err(e) if e.status().is_conflict() => {
+                    info!(
+                        event = "claim-conflict",
+                        partition_id = %ownership.partition_id,
+                        owner_id = ?ownership.owner_id,
+                        etag = ?ownership.etag,
+                        "Lost ownership claim: document already exists"
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!(
+                        partition_id = %ownership.partition_id,
+                        error = %e,
+                        "Error claiming ownership for document"
+                    );
+                    return Err(cosmos_error(e));
+                }
+            };
+
+            let etag = response
+                .headers()
+                .etag()
+                .map(|etag| Etag::from(etag.as_ref().to_string()));
+            let last_modified_time = response
+                .into_model::<OwnershipDocument>()
+                .ok()
+                .and_then(|document| document.last_modified_time());
+            if let Some(etag) = etag {
+                if !etag.as_ref().is_empty() {
+                    new_ownerships.push(Ownership {
+                        etag: Some(etag),
+                        last_modified_time,
+                        ..ownership.clone()
+                    });
+                }
+            }
+        }
+
+        debug!("Returning {} ownerships", new_ownerships.len());
+        Ok(new_ownerships)
+    }
+
+    /// Lists all checkpoints for the specified Event Hub and consumer group.
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            fully_qualified_namespace = %namespace,
+            eventhub = %event_hub_name,
+            consumer_group = %consumer_group,
+        ),
+        err,
+    )]
+    async fn list_checkpoints(
+        &self,
+        namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<Checkpoint>> {
+        let partition_key =
+            Checkpoint::get_checkpoint_blob_prefix_name(namespace, event_hub_name, consumer_group)?;
+        debug!("Listing checkpoints in partition {}", partition_key);
+
+        let checkpoints: Vec<Checkpoint> = self
+            .query_partition::<CheckpointDocument>(&partition_key)
+            .await?
+            .into_iter()
+            .map(|document| Checkpoint {
+                fully_qualified_namespace: namespace.to_string(),
+                event_hub_name: event_hub_name.to_string(),
+                consumer_group: consumer_group.to_string(),
+                partition_id: document.id,
+                offset: document.offset,
+                sequence_number: document.sequence_number,
+            })
+            .collect();
+
+        debug!("Found {} checkpoints", checkpoints.len());
+        Ok(checkpoints)
+    }
+
+    /// Lists all ownerships for the specified Event Hub and consumer group.
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            fully_qualified_namespace = %namespace,
+            eventhub = %event_hub_name,
+            consumer_group = %consumer_group,
+        ),
+        err,
+    )]
+    async fn list_ownerships(
+        &self,
+        namespace: &str,
+        event_hub_name: &str,
+        consumer_group: &str,
+    ) -> Result<Vec<Ownership>> {
+        let partition_key =
+            Ownership::get_ownership_prefix_name(namespace, event_hub_name, consumer_group)?;
+        debug!("Listing ownerships in partition {}", partition_key);
+
+        let ownerships: Vec<Ownership> = self
+            .query_partition::<OwnershipDocument>(&partition_key)
+            .await?
+            .into_iter()
+            .map(|document| Ownership {
+                fully_qualified_namespace: namespace.to_string(),
+                event_hub_name: event_hub_name.to_string(),
+                consumer_group: consumer_group.to_string(),
+                last_modified_time: document.last_modified_time(),
+                partition_id: document.id,
+                owner_id: document.owner_id,
+                etag: document.etag.map(Etag::from),
+            })
+            .collect();
+
+        debug!("Found {} ownerships", ownerships.len());
+        Ok(ownerships)
+    }
+
+    /// Updates the checkpoint for a specific partition.
+    #[tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(
+            partition_id = %checkpoint.partition_id,
+            eventhub = %checkpoint.event_hub_name,
+            consumer_group = %checkpoint.consumer_group,
+            sequence_number = ?checkpoint.sequence_number,
+            offset = ?checkpoint.offset,
+        ),
+    )]
+    async fn update_checkpoint(&self, checkpoint: Checkpoint) -> Result<()> {
+        debug!(
+            partition_id = %checkpoint.partition_id,
+            sequence_number = ?checkpoint.sequence_number,
+            offset = ?checkpoint.offset,
+            "Updating checkpoint"
+        );
+        // Validates every name, including the partition id.
+        Checkpoint::get_checkpoint_blob_name(
+            &checkpoint.fully_qualified_namespace,
+            &checkpoint.event_hub_name,
+            &checkpoint.consumer_group,
+            &checkpoint.partition_id,
+        )?;
+        let document = CheckpointDocument {
+            partition_key: Checkpoint::get_checkpoint_blob_prefix_name(
+                &checkpoint.fully_qualified_namespace,
+                &checkpoint.event_hub_name,
+                &checkpoint.consumer_group,
+            )?,
+            id: checkpoint.partition_id,
+            offset: checkpoint.offset,
+            sequence_number: checkpoint.sequence_number,
+        };
+        if let Err(e) = self
+            .container_client
+            .upsert_item(
+                document.partition_key.clone(),
+                &document.id,
+                &document,
+                None,
+            )
+            .await
+        {
+            error!(
+                partition_id = %document.id,
+                error = %e,
+                "Failed to persist checkpoint to Cosmos DB"
+            );
+            return Err(cosmos_error(e));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ownership_document_round_trips_system_properties() {
+        let document: OwnershipDocument = serde_json::from_value(serde_json::json!({
+            "id": "0",
+            "partitionKey": "namespace/eventhub/$default/ownership/",
+            "ownerId": "owner",
+            "_etag": "\"00000000-0000\"",
+            "_ts": 1_700_000_000,
+        }))
+        .unwrap();
+        assert_eq!(document.owner_id.as_deref(), Some("owner"));
+        assert_eq!(document.etag.as_deref(), Some("\"00000000-0000\""));
+        assert_eq!(
+            document
+                .last_modified_time()
+                .map(OffsetDateTime::unix_timestamp),
+            Some(1_700_000_000)
+        );
+
+        // The system properties are owned by the service and never written back.
+        let written = serde_json::to_value(&document).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!({
+                "id": "0",
+                "partitionKey": "namespace/eventhub/$default/ownership/",
+                "ownerId": "owner",
+            })
+        );
+    }
+
+    #[test]
+    fn checkpoint_document_omits_missing_positions() {
+        let document = CheckpointDocument {
+            id: "1".to_string(),
+            partition_key: "namespace/eventhub/$default/checkpoint/".to_string(),
+            offset: None,
+            sequence_number: Some(42),
+        };
+        assert_eq!(
+            serde_json::to_value(&document).unwrap(),
+            serde_json::json!({
+                "id": "1",
+                "partitionKey": "namespace/eventhub/$default/checkpoint/",
+                "sequenceNumber": 42,
+            })
+        );
+    }
+}
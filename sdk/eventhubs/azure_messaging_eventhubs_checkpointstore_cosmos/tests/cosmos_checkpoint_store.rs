@@ -0,0 +1,220 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Tests that run the Cosmos DB checkpoint store against the Cosmos DB in-memory emulator.
+
+use azure_core::{http::Url, Result};
+use azure_data_cosmos::{
+    options::Region, AccountEndpoint, AccountReference, ContainerClient, CosmosClientBuilder,
+    CosmosRuntimeBuilder, RoutingStrategy,
+};
+use azure_data_cosmos_driver::in_memory_emulator::{
+    ConsistencyLevel, ContainerConfig, InMemoryEmulatorHttpClient, VirtualAccountConfig,
+    VirtualRegion,
+};
+use azure_messaging_eventhubs::{
+    models::{Checkpoint, Ownership},
+    test::CheckpointStoreConformance,
+    CheckpointStore,
+};
+use azure_messaging_eventhubs_checkpointstore_cosmos::{
+    checkpoint_store::PARTITION_KEY_PATH, CosmosCheckpointStore,
+};
+use std::sync::Arc;
+
+const EMULATOR_GATEWAY_URL: &str = "https://eastus.emulator.local";
+const DATABASE: &str = "eventhubs";
+const CONTAINER: &str = "checkpoints";
+const NAMESPACE: &str = "test.servicebus.windows.net";
+const EVENT_HUB: &str = "eventhub";
+const CONSUMER_GROUP: &str = "$Default";
+
+/// Creates a checkpoint store over a container of a fresh in-memory emulator account.
+async fn create_checkpoint_store() -> Arc<CosmosCheckpointStore> {
+    let config = VirtualAccountConfig::new(vec![VirtualRegion::new(
+        "East US",
+        Url::parse(EMULATOR_GATEWAY_URL).unwrap(),
+    )])
+    .unwrap()
+    .with_consistency(ConsistencyLevel::Session);
+    let emulator = Arc::new(InMemoryEmulatorHttpClient::new(config));
+    let store = emulator.store();
+    store.create_database(DATABASE);
+    store.create_container_with_config(
+        DATABASE,
+        CONTAINER,
+        serde_json::from_value(serde_json::json!({
+            "paths": [PARTITION_KEY_PATH],
+            "kind": "Hash",
+            "version": 2
+        }))
+        .unwrap(),
+        ContainerConfig::new()
+            .with_partition_count(1)
+            .with_throughput(400)
+            .build()
+            .unwrap(),
+    );
+
+    let account = AccountReference::with_authentication_key(
+        EMULATOR_GATEWAY_URL.parse::<AccountEndpoint>().unwrap(),
+        "dGVzdGtleQ==",
+    );
+    let client = CosmosClientBuilder::new()
+        .with_runtime(
+            CosmosRuntimeBuilder::from(emulator.runtime_builder())
+                .build()
+                .await
+                .unwrap(),
+        )
+        .build(account, RoutingStrategy::ProximityTo(Region::EAST_US))
+        .await
+        .unwrap();
+    let container: ContainerClient = client
+        .database_client(DATABASE)
+        .container_client(CONTAINER)
+        .await
+        .unwrap();
+    CosmosCheckpointStore::new(container)
+}
+
+fn ownership(partition_id: &str, owner_id: &str) -> Ownership {
+    Ownership {
+        fully_qualified_namespace: NAMESPACE.to_string(),
+        event_hub_name: EVENT_HUB.to_string(),
+        consumer_group: CONSUMER_GROUP.to_string(),
+        partition_id: partition_id.to_string(),
+        owner_id: Some(owner_id.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn update_and_list_checkpoints() -> Result<()> {
+    let checkpoint_store = create_checkpoint_store().await;
+    assert!(checkpoint_store
+        .list_checkpoints(NAMESPACE, EVENT_HUB, CONSUMER_GROUP)
+        .await?
+        .is_empty());
+
+    for (partition_id, sequence_number) in [("0", 10), ("1", 20), ("0", 30)] {
+        checkpoint_store
+            .update_checkpoint(Checkpoint {
+                fully_qualified_namespace: NAMESPACE.to_string(),
+                event_hub_name: EVENT_HUB.to_string(),
+                consumer_group: CONSUMER_GROUP.to_string(),
+                partition_id: partition_id.to_string(),
+                offset: Some(format!("{sequence_number}00")),
+                sequence_number: Some(sequence_number),
+            })
+            .await?;
+    }
+
+    let mut checkpoints = checkpoint_store
+        .list_checkpoints(NAMESPACE, EVENT_HUB, CONSUMER_GROUP)
+        .await?;
+    checkpoints.sort_by(|a, b| a.partition_id.cmp(&b.partition_id));
+    let positions: Vec<_> = checkpoints
+        .iter()
+        .map(|checkpoint| {
+            assert_eq!(checkpoint.fully_qualified_namespace, NAMESPACE);
+            assert_eq!(checkpoint.event_hub_name, EVENT_HUB);
+            assert_eq!(checkpoint.consumer_group, CONSUMER_GROUP);
+            (
+                checkpoint.partition_id.as_str(),
+                checkpoint.offset.as_deref(),
+                checkpoint.sequence_number,
+            )
+        })
+        .collect();
+    assert_eq!(
+        positions,
+        [("0", Some("3000"), Some(30)), ("1", Some("2000"), Some(20))]
+    );
+
+    // Checkpoints of another consumer group are kept apart.
+    assert!(checkpoint_store
+        .list_checkpoints(NAMESPACE, EVENT_HUB, "other")
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn claim_ownership_with_etags() -> Result<()> {
+    let checkpoint_store = create_checkpoint_store().await;
+
+    let claimed = checkpoint_store
+        .claim_ownership(&[ownership("0", "first"), ownership("1", "first")])
+        .await?;
+    assert_eq!(claimed.len(), 2);
+    for ownership in &claimed {
+        assert!(ownership.etag.is_some());
+        assert!(ownership.last_modified_time.is_some());
+    }
+
+    // Without an etag, a partition that is already owned cannot be claimed.
+    let claimed_again = checkpoint_store
+        .claim_ownership(&[ownership("0", "second")])
+        .await?;
+    assert!(claimed_again.is_empty());
+
+    // With the current etag, it can, and the old etag is then stale.
+    let listed = checkpoint_store
+        .list_ownerships(NAMESPACE, EVENT_HUB, CONSUMER_GROUP)
+        .await?;
+    let current = listed
+        .iter()
+        .find(|ownership| ownership.partition_id == "0")
+        .expect("partition 0 should be owned");
+    assert_eq!(current.owner_id.as_deref(), Some("first"));
+    assert_eq!(current.etag, claimed[0].etag);
+
+    let stolen = checkpoint_store
+        .claim_ownership(&[Ownership {
+            owner_id: Some("second".to_string()),
+            ..current.clone()
+        }])
+        .await?;
+    assert_eq!(stolen.len(), 1);
+    assert_ne!(stolen[0].etag, current.etag);
+
+    let stale = checkpoint_store
+        .claim_ownership(&[Ownership {
+            owner_id: Some("first".to_string()),
+            ..current.clone()
+        }])
+        .await?;
+    assert!(stale.is_empty());
+
+    let mut owners: Vec<_> = checkpoint_store
+        .list_ownerships(NAMESPACE, EVENT_HUB, CONSUMER_GROUP)
+        .await?
+        .into_iter()
+        .map(|ownership| (ownership.partition_id, ownership.owner_id))
+        .collect();
+    owners.sort();
+    assert_eq!(
+        owners,
+        [
+            ("0".to_string(), Some("second".to_string())),
+            ("1".to_string(), Some("first".to_string())),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn claim_ownership_empty_list() -> Result<()> {
+    let checkpoint_store = create_checkpoint_store().await;
+    assert!(checkpoint_store.claim_ownership(&[]).await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn checkpoint_store_conformance() -> Result<()> {
+    let checkpoint_store = create_checkpoint_store().await;
+    CheckpointStoreConformance::new(checkpoint_store.as_ref())
+        .run()
+        .await
+}
@@ -2,7 +2,7 @@
 // Licensed under the MIT License.
 
 use crate::env::Env;
-use crate::{ImdsId, ImdsManagedIdentityCredential};
+use crate::{ImdsId, ImdsIdParams, ImdsManagedIdentityCredential};
 use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
 use azure_core::error::{ErrorKind, ResultExt};
 use azure_core::http::headers::HeaderName;
@@ -55,7 +55,12 @@ impl AppServiceManagedIdentityCredential {
                 client_options,
                 None,
                 env,
-            ),
+            )
+            // App Service names the resource ID parameter differently than IMDS
+            .with_id_params(ImdsIdParams {
+                resource_id: "mi_res_id",
+                ..Default::default()
+            }),
         }))
     }
 }
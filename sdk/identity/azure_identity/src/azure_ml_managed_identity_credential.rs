@@ -0,0 +1,86 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::env::Env;
+use crate::{ImdsId, ImdsIdParams, ImdsManagedIdentityCredential};
+use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
+use azure_core::error::{ErrorKind, ResultExt};
+use azure_core::http::headers::HeaderName;
+use azure_core::http::ClientOptions;
+use azure_core::http::Url;
+use std::{any::type_name, fmt, sync::Arc};
+
+const ENDPOINT_ENV: &str = "MSI_ENDPOINT";
+const API_VERSION: &str = "2017-09-01";
+const SECRET_HEADER: HeaderName = HeaderName::from_static("secret");
+const SECRET_ENV: &str = "MSI_SECRET";
+/// The client ID of the identity assigned to an Azure ML compute, used when no user-assigned identity is specified.
+const DEFAULT_CLIENT_ID_ENV: &str = "DEFAULT_IDENTITY_CLIENT_ID";
+
+/// Authenticates a managed identity on Azure Machine Learning compute.
+pub(crate) struct AzureMlManagedIdentityCredential {
+    credential: ImdsManagedIdentityCredential,
+}
+
+impl fmt::Debug for AzureMlManagedIdentityCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>()).finish_non_exhaustive()
+    }
+}
+
+impl AzureMlManagedIdentityCredential {
+    pub fn new(
+        id: ImdsId,
+        client_options: ClientOptions,
+        env: Env,
+    ) -> azure_core::Result<Arc<Self>> {
+        let endpoint = &env
+            .var(ENDPOINT_ENV)
+            .with_context_fn(ErrorKind::Credential, || {
+                format!(
+                    "Azure ML credential requires {} environment variable",
+                    ENDPOINT_ENV
+                )
+            })?;
+        let endpoint = Url::parse(endpoint).with_context_fn(ErrorKind::Credential, || {
+            format!(
+                "Azure ML credential {} environment variable must be a valid URL, but is '{endpoint}'",
+                ENDPOINT_ENV
+            )
+        })?;
+        // Azure ML identifies the compute's own identity by client ID as well
+        let id = match id {
+            ImdsId::SystemAssigned => env
+                .var(DEFAULT_CLIENT_ID_ENV)
+                .map_or(ImdsId::SystemAssigned, ImdsId::ClientId),
+            id => id,
+        };
+        Ok(Arc::new(Self {
+            credential: ImdsManagedIdentityCredential::new(
+                endpoint,
+                API_VERSION,
+                SECRET_HEADER,
+                SECRET_ENV,
+                id,
+                client_options,
+                None,
+                env,
+            )
+            .with_id_params(ImdsIdParams {
+                client_id: "clientid",
+                ..Default::default()
+            }),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for AzureMlManagedIdentityCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        self.credential.get_token(scopes, options).await
+    }
+}
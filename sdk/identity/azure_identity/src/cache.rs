@@ -1,19 +1,75 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
+use crate::env::Env;
+use crate::token_cache_persistence::{PersistentTokenCache, TokenCachePersistenceOptions};
 use async_lock::RwLock;
 use azure_core::credentials::{AccessToken, TokenRequestOptions};
 use azure_core::time::{Duration, OffsetDateTime};
 use std::collections::HashMap;
 use std::future::Future;
-use tracing::trace;
+use tracing::{trace, warn};
 
+/// A token, and when to refresh it if that's before it expires.
+#[derive(Clone, Debug)]
+pub(crate) struct CachedToken {
+    pub token: AccessToken,
+    pub refresh_on: Option<OffsetDateTime>,
+}
+
+impl From<AccessToken> for CachedToken {
+    fn from(token: AccessToken) -> Self {
+        Self {
+            token,
+            refresh_on: None,
+        }
+    }
+}
+
+/// Whether a token supports continuous access evaluation, and its partition and scopes.
+type TokenKey = (bool, Vec<String>);
+
+#[derive(Debug)]
+pub(crate) struct TokenCache {
+    tokens: RwLock<HashMap<TokenKey, CachedToken>>,
+    persistence: Option<Persistence>,
+}
+
+/// A persistent cache shared with other processes, and the part of its keys that identifies the
+/// credential's tenant and client.
 #[derive(Debug)]
-pub(crate) struct TokenCache(RwLock<HashMap<Vec<String>, AccessToken>>);
+struct Persistence {
+    cache: PersistentTokenCache,
+    partition: String,
+}
 
 impl TokenCache {
     pub(crate) fn new() -> Self {
-        Self(RwLock::new(HashMap::new()))
+        Self {
+            tokens: RwLock::new(HashMap::new()),
+            persistence: None,
+        }
+    }
+
+    /// Creates a cache that also stores tokens in a persistent cache, when `options` configures one.
+    ///
+    /// The `partition` separates the tokens of this credential from those of others in the persistent cache,
+    /// for example by authority, tenant, and client ID.
+    pub(crate) fn with_persistence(
+        options: Option<TokenCachePersistenceOptions>,
+        env: &Env,
+        partition: String,
+    ) -> azure_core::Result<Self> {
+        let Some(options) = options else {
+            return Ok(Self::new());
+        };
+        Ok(Self {
+            tokens: RwLock::new(HashMap::new()),
+            persistence: Some(Persistence {
+                cache: PersistentTokenCache::new(options, env)?,
+                partition,
+            }),
+        })
     }
 
     pub(crate) async fn get_token<'a, C, F>(
@@ -26,32 +82,137 @@ impl TokenCache {
         C: FnOnce(&'a [&'a str], Option<TokenRequestOptions<'a>>) -> F + Send,
         F: Future<Output = azure_core::Result<AccessToken>> + Send,
     {
-        let token_cache = self.0.read().await;
-        let scopes_owned = scopes.iter().map(ToString::to_string).collect::<Vec<_>>();
-        if let Some(token) = token_cache.get(&scopes_owned) {
+        self.get_refreshable_token(scopes, options, |s, o| async move {
+            callback(s, o).await.map(CachedToken::from)
+        })
+        .await
+    }
+
+    /// Gets a token from the cache or the callback, refreshing a cached token when it's about to expire or its
+    /// refresh time has passed.
+    pub(crate) async fn get_refreshable_token<'a, C, F>(
+        &self,
+        scopes: &'a [&'a str],
+        options: Option<TokenRequestOptions<'a>>,
+        callback: C,
+    ) -> azure_core::Result<AccessToken>
+    where
+        C: FnOnce(&'a [&'a str], Option<TokenRequestOptions<'a>>) -> F + Send,
+        F: Future<Output = azure_core::Result<CachedToken>> + Send,
+    {
+        self.get(None, scopes, options, callback).await
+    }
+
+    /// Like [`TokenCache::get_refreshable_token`], for a cache shared by credentials that get tokens for
+    /// different principals. The `partition` identifies the principal.
+    pub(crate) async fn get_partitioned_token<'a, C, F>(
+        &self,
+        partition: &str,
+        scopes: &'a [&'a str],
+        options: Option<TokenRequestOptions<'a>>,
+        callback: C,
+    ) -> azure_core::Result<AccessToken>
+    where
+        C: FnOnce(&'a [&'a str], Option<TokenRequestOptions<'a>>) -> F + Send,
+        F: Future<Output = azure_core::Result<CachedToken>> + Send,
+    {
+        self.get(Some(partition), scopes, options, callback).await
+    }
+
+    async fn get<'a, C, F>(
+        &self,
+        partition: Option<&str>,
+        scopes: &'a [&'a str],
+        options: Option<TokenRequestOptions<'a>>,
+        callback: C,
+    ) -> azure_core::Result<AccessToken>
+    where
+        C: FnOnce(&'a [&'a str], Option<TokenRequestOptions<'a>>) -> F + Send,
+        F: Future<Output = azure_core::Result<CachedToken>> + Send,
+    {
+        // tokens for callers supporting CAE may be revoked, so other callers mustn't get them, and a request
+        // with claims (from a claims challenge) needs a new token because cached tokens don't have them
+        let (claims, cae) = options.as_ref().map_or((false, false), |options| {
+            (options.claims().is_some(), options.enable_cae())
+        });
+        let key = (
+            cae,
+            partition
+                .iter()
+                .chain(scopes)
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        );
+        let token_cache = self.tokens.read().await;
+        if let Some(token) = token_cache.get(&key).filter(|_| !claims) {
             if !should_refresh(token) {
                 trace!("returning cached token");
-                return Ok(token.clone());
+                return Ok(token.token.clone());
             }
         }
 
         // otherwise, drop the read lock and get a write lock to refresh the token
         drop(token_cache);
-        let mut token_cache = self.0.write().await;
+        let mut token_cache = self.tokens.write().await;
 
         // check again in case another thread refreshed the token while we were
         // waiting on the write lock
-        if let Some(token) = token_cache.get(&scopes_owned) {
+        let mut cached = token_cache.get(&key).filter(|_| !claims).cloned();
+        if let Some(token) = &cached {
             if !should_refresh(token) {
                 trace!("returning token that was updated while waiting on write lock");
-                return Ok(token.clone());
+                return Ok(token.token.clone());
+            }
+        }
+
+        // another process may have cached a token
+        let persistent_key = self.persistence.as_ref().map(|persistence| {
+            let mut prefix = persistence.partition.clone();
+            if let Some(partition) = partition {
+                prefix = format!("{prefix}|{partition}");
+            }
+            if cae {
+                prefix.push_str("|cae");
+            }
+            persistent_key(&prefix, scopes)
+        });
+        if let (Some(persistence), Some(persistent_key), false) =
+            (&self.persistence, &persistent_key, claims)
+        {
+            match persistence.cache.get(persistent_key).await {
+                Ok(Some(token)) if !should_refresh(&token) => {
+                    trace!("returning token from persistent cache");
+                    token_cache.insert(key, token.clone());
+                    return Ok(token.token);
+                }
+                Ok(Some(token)) => cached = Some(token),
+                Ok(None) => {}
+                Err(err) => warn!("failed to read persistent token cache: {err}"),
             }
         }
 
         trace!("token cache miss");
-        let token = callback(scopes, options).await?;
-        token_cache.insert(scopes_owned, token.clone());
-        Ok(token)
+        let token = match callback(scopes, options).await {
+            Ok(token) => token,
+            // a token refreshed early is still usable if refreshing it fails
+            Err(err) => match cached.filter(|token| !is_expiring(&token.token)) {
+                Some(token) => {
+                    warn!("failed to refresh token, returning cached token: {err}");
+                    return Ok(token.token);
+                }
+                None => return Err(err),
+            },
+        };
+        if let (Some(persistence), Some(persistent_key)) = (&self.persistence, &persistent_key) {
+            if let Err(err) = persistence.cache.set(persistent_key, &token).await {
+                warn!("failed to write persistent token cache: {err}");
+            }
+        }
+        // partitioned caches could otherwise accumulate tokens indefinitely
+        let now = OffsetDateTime::now_utc();
+        token_cache.retain(|_, token| token.token.expires_on > now);
+        token_cache.insert(key, token.clone());
+        Ok(token.token)
     }
 }
 
@@ -61,16 +222,30 @@ impl Default for TokenCache {
     }
 }
 
-fn should_refresh(token: &AccessToken) -> bool {
+fn should_refresh(token: &CachedToken) -> bool {
+    is_expiring(&token.token)
+        || token
+            .refresh_on
+            .is_some_and(|refresh_on| refresh_on <= OffsetDateTime::now_utc())
+}
+
+fn is_expiring(token: &AccessToken) -> bool {
     token.expires_on <= OffsetDateTime::now_utc() + Duration::seconds(300)
 }
 
+/// The key of a token in a persistent cache, which doesn't depend on the order of the scopes.
+fn persistent_key(partition: &str, scopes: &[&str]) -> String {
+    let mut scopes = scopes.to_vec();
+    scopes.sort_unstable();
+    format!("{partition}|{}", scopes.join(" "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use async_lock::Mutex;
     use azure_core::{
-        credentials::Secret,
+        credentials::{EnableCae, Secret, TokenClaims},
         time::{Duration, OffsetDateTime},
     };
 
@@ -174,4 +349,107 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_refresh_on() -> azure_core::Result<()> {
+        let resource = &[STORAGE_TOKEN_SCOPE];
+        let expires_on = OffsetDateTime::now_utc() + Duration::seconds(3600);
+        let cache = TokenCache::new();
+
+        // a token past its refresh time is refreshed although it hasn't expired
+        let token = cache
+            .get_refreshable_token(resource, None, |_, _| async move {
+                Ok(CachedToken {
+                    token: AccessToken::new(Secret::new("first"), expires_on),
+                    refresh_on: Some(OffsetDateTime::now_utc()),
+                })
+            })
+            .await?;
+        assert_eq!(token.token.secret(), "first");
+
+        let token = cache
+            .get_refreshable_token(resource, None, |_, _| async move {
+                Ok(CachedToken {
+                    token: AccessToken::new(Secret::new("second"), expires_on),
+                    refresh_on: Some(OffsetDateTime::now_utc()),
+                })
+            })
+            .await?;
+        assert_eq!(token.token.secret(), "second");
+
+        // when refreshing fails, the cached token is still valid
+        let token = cache
+            .get_refreshable_token(resource, None, |_, _| async move {
+                Err(azure_core::Error::with_message(
+                    azure_core::error::ErrorKind::Other,
+                    "refresh failed",
+                ))
+            })
+            .await?;
+        assert_eq!(token.token.secret(), "second");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cae() -> azure_core::Result<()> {
+        let resource = &[STORAGE_TOKEN_SCOPE];
+        let expires_on = OffsetDateTime::now_utc() + Duration::seconds(3600);
+        let mock_credential =
+            MockCredential::new(AccessToken::new(Secret::new("token"), expires_on));
+        let cache = TokenCache::new();
+        let cae = || {
+            let mut options = TokenRequestOptions::default();
+            options.method_options.context.insert(EnableCae);
+            Some(options)
+        };
+        let claims = || {
+            let mut options = cae();
+            if let Some(options) = options.as_mut() {
+                options
+                    .method_options
+                    .context
+                    .insert(TokenClaims::new("{}"));
+            }
+            options
+        };
+
+        let token = cache
+            .get_token(resource, None, |s, o| mock_credential.get_token(s, o))
+            .await?;
+        assert!(token.token.secret().ends_with(":1"));
+
+        // tokens for callers supporting CAE are cached separately
+        let token = cache
+            .get_token(resource, cae(), |s, o| mock_credential.get_token(s, o))
+            .await?;
+        assert!(token.token.secret().ends_with(":2"));
+
+        // requests with claims bypass the cache and replace the cached token
+        let token = cache
+            .get_token(resource, claims(), |s, o| mock_credential.get_token(s, o))
+            .await?;
+        assert!(token.token.secret().ends_with(":3"));
+        let token = cache
+            .get_token(resource, cae(), |s, o| mock_credential.get_token(s, o))
+            .await?;
+        assert!(token.token.secret().ends_with(":3"));
+        let token = cache
+            .get_token(resource, None, |s, o| mock_credential.get_token(s, o))
+            .await?;
+        assert!(token.token.secret().ends_with(":1"));
+
+        // a request with claims fails rather than returning a cached token when getting a new token fails
+        cache
+            .get_refreshable_token(resource, claims(), |_, _| async move {
+                Err(azure_core::Error::with_message(
+                    azure_core::error::ErrorKind::Other,
+                    "refresh failed",
+                ))
+            })
+            .await
+            .expect_err("expected an error");
+
+        Ok(())
+    }
 }
@@ -1,7 +1,7 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
-use crate::{get_authority_host, validate_not_empty, validate_tenant_id, TokenCache};
+use crate::{get_authority_host, validate_not_empty, validate_tenant_id, CachedToken, TokenCache};
 use azure_core::{
     credentials::{AccessToken, TokenCredential, TokenRequestOptions},
     error::{ErrorKind, ResultExt},
@@ -13,7 +13,7 @@ use azure_core::{
 use std::{any::type_name, fmt, str, sync::Arc};
 use url::form_urlencoded;
 
-const ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
+pub(crate) const ASSERTION_TYPE: &str = "urn:ietf:params:oauth:client-assertion-type:jwt-bearer";
 
 /// Authenticates an application with client assertions.
 ///
@@ -126,7 +126,7 @@ impl<C: ClientAssertion> ClientAssertionCredential<C> {
         &self,
         scopes: &[&str],
         options: Option<TokenRequestOptions<'_>>,
-    ) -> azure_core::Result<AccessToken> {
+    ) -> azure_core::Result<CachedToken> {
         let mut req = Request::new(self.endpoint.clone(), Method::Post);
         req.insert_header(
             headers::CONTENT_TYPE,
@@ -137,12 +137,14 @@ impl<C: ClientAssertion> ClientAssertionCredential<C> {
             .assertion
             .secret(Some(options.method_options.to_owned()))
             .await?;
+        let claims = crate::claims_parameter(Some(&options))?;
         let encoded: String = form_urlencoded::Serializer::new(String::new())
             .append_pair("client_assertion", assertion.as_str())
             .append_pair("client_assertion_type", ASSERTION_TYPE)
             .append_pair("client_id", self.client_id.as_str())
             .append_pair("grant_type", "client_credentials")
             .append_pair("scope", &scopes.join(" "))
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
             .finish();
         req.set_body(encoded);
 
@@ -171,7 +173,7 @@ impl<C: ClientAssertion> TokenCredential for ClientAssertionCredential<C> {
         options: Option<TokenRequestOptions<'_>>,
     ) -> azure_core::Result<AccessToken> {
         self.cache
-            .get_token(scopes, options, |s, o| self.get_token_impl(s, o))
+            .get_refreshable_token(scopes, options, |s, o| self.get_token_impl(s, o))
             .await
             .map_err(|err| crate::authentication_error(self.name, err))
     }
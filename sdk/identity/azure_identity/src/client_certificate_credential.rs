@@ -3,7 +3,7 @@
 
 use crate::{
     authentication_error, env::Env, get_authority_host, validate_not_empty, validate_tenant_id,
-    TokenCache,
+    CachedToken, ClientAssertion, TokenCache, TokenCachePersistenceOptions,
 };
 use azure_core::{
     base64,
@@ -12,7 +12,7 @@ use azure_core::{
     http::{
         headers::{self, content_type},
         request::Request,
-        ClientOptions, Method, Pipeline, PipelineSendOptions, Url,
+        ClientMethodOptions, ClientOptions, Method, Pipeline, PipelineSendOptions, Url,
     },
     time::OffsetDateTime,
     Uuid,
@@ -43,6 +43,11 @@ pub struct ClientCertificateCredentialOptions {
     /// The password for the certificate.
     pub password: Option<Secret>,
 
+    /// Persists tokens so other credentials and processes can use them.
+    ///
+    /// Defaults to `None`, in which case the credential caches tokens in memory only.
+    pub token_cache_persistence: Option<TokenCachePersistenceOptions>,
+
     #[cfg(test)]
     pub(crate) env: Option<Env>,
 }
@@ -56,10 +61,9 @@ impl fmt::Debug for ClientCertificateCredentialOptions {
 /// Authenticates an application with a certificate.
 pub struct ClientCertificateCredential {
     client_id: String,
-    key: PKey<Private>,
+    assertion: CertificateAssertion,
     endpoint: Url,
     pipeline: Pipeline,
-    header: String,
     cache: TokenCache,
 }
 
@@ -92,47 +96,29 @@ impl ClientCertificateCredential {
 
         let options = options.unwrap_or_default();
 
-        let (key, cert, ca_chain) =
-            parse_certificate(certificate.bytes(), options.password.as_ref())?;
-        let thumbprint = cert
-            .digest(MessageDigest::sha1())
-            .with_context(ErrorKind::Credential, "failed to compute thumbprint")?
-            .to_vec();
-        let thumbprint = base64::encode(thumbprint);
-
         #[cfg(test)]
         let env = options.env.unwrap_or_default();
         #[cfg(not(test))]
         let env = Env::default();
 
-        let send_x5c = env
-            .var(AZURE_CLIENT_SEND_CERTIFICATE_CHAIN_ENV_KEY)
-            .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
-            .unwrap_or(false);
-        let header = if send_x5c {
-            let base_signature = get_encoded_cert(&cert)?;
-            let x5c = match &ca_chain {
-                Some(chain) => {
-                    let chain = chain
-                        .iter()
-                        .map(get_encoded_cert)
-                        .collect::<azure_core::Result<Vec<String>>>()?
-                        .join(",");
-                    format!("{base_signature},{chain}")
-                }
-                None => base_signature,
-            };
-            format!(r#"{{"alg":"RS256","typ":"JWT","x5c":[{x5c}],"x5t":"{thumbprint}"}}"#)
-        } else {
-            format!(r#"{{"alg":"RS256","typ":"JWT","x5t":"{thumbprint}"}}"#)
-        };
-
         let authority_host = get_authority_host(None, options.client_options.cloud.as_deref())?;
         let endpoint = authority_host
             .join(&format!("/{tenant_id}/oauth2/v2.0/token"))
             .with_context_fn(ErrorKind::DataConversion, || {
                 format!("tenant_id '{tenant_id}' could not be URL encoded")
             })?;
+        let assertion = CertificateAssertion::new(
+            client_id.clone(),
+            endpoint.clone(),
+            &certificate,
+            options.password.as_ref(),
+            &env,
+        )?;
+        let cache = TokenCache::with_persistence(
+            options.token_cache_persistence,
+            &env,
+            format!("{authority_host}|{tenant_id}|{client_id}"),
+        )?;
 
         let pipeline = Pipeline::new(
             option_env!("CARGO_PKG_NAME"),
@@ -145,43 +131,20 @@ impl ClientCertificateCredential {
 
         Ok(Arc::new(ClientCertificateCredential {
             client_id,
-            key,
+            assertion,
             endpoint,
             pipeline,
-            header: ClientCertificateCredential::as_jwt_part(header.as_bytes()),
-            cache: TokenCache::new(),
+            cache,
         }))
     }
 
-    fn sign(jwt: &str, pkey: &PKey<Private>) -> Result<Vec<u8>, ErrorStack> {
-        let mut signer = Signer::new(MessageDigest::sha256(), pkey)?;
-        signer.update(jwt.as_bytes())?;
-        signer.sign_to_vec()
-    }
-
-    fn as_jwt_part(part: &[u8]) -> String {
-        base64::encode_url_safe(part)
-    }
-
     async fn get_token_impl(
         &self,
         scopes: &[&str],
         options: Option<TokenRequestOptions<'_>>,
-    ) -> azure_core::Result<AccessToken> {
-        let uuid = Uuid::new_v4();
-        let current_time = OffsetDateTime::now_utc().unix_timestamp();
-        let expiry_time = current_time + DEFAULT_ASSERTION_LIFETIME;
-        let payload = format!(
-            r#"{{"aud":"{}","exp":{},"iss": "{}", "jti": "{}", "nbf": {}, "sub": "{}"}}"#,
-            self.endpoint, expiry_time, self.client_id, uuid, current_time, self.client_id
-        );
-        let payload = ClientCertificateCredential::as_jwt_part(payload.as_bytes());
-
-        let jwt = format!("{}.{}", self.header, payload);
-        let signature = ClientCertificateCredential::sign(&jwt, &self.key)
-            .with_context(ErrorKind::Credential, "failed to sign JWT")?;
-        let sig = ClientCertificateCredential::as_jwt_part(&signature);
-        let client_assertion = format!("{}.{}", jwt, sig);
+    ) -> azure_core::Result<CachedToken> {
+        let client_assertion = self.assertion.secret(None).await?;
+        let claims = crate::claims_parameter(options.as_ref())?;
 
         let encoded = {
             let mut encoded = &mut form_urlencoded::Serializer::new(String::new());
@@ -193,7 +156,8 @@ impl ClientCertificateCredential {
                     "urn:ietf:params:oauth:client-assertion-type:jwt-bearer",
                 )
                 .append_pair("client_assertion", client_assertion.as_str())
-                .append_pair("grant_type", "client_credentials");
+                .append_pair("grant_type", "client_credentials")
+                .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)));
             encoded.finish()
         };
 
@@ -222,6 +186,101 @@ impl ClientCertificateCredential {
     }
 }
 
+/// Signs client assertions with a certificate, for a client to authenticate at a token endpoint.
+pub(crate) struct CertificateAssertion {
+    audience: Url,
+    client_id: String,
+    header: String,
+    key: PKey<Private>,
+}
+
+impl fmt::Debug for CertificateAssertion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client_id", &self.client_id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CertificateAssertion {
+    /// Parses a PKCS12 certificate to sign assertions for a client authenticating at `audience`.
+    ///
+    /// The assertions include the certificate chain when `AZURE_CLIENT_SEND_CERTIFICATE_CHAIN` is set.
+    pub(crate) fn new(
+        client_id: String,
+        audience: Url,
+        certificate: &SecretBytes,
+        password: Option<&Secret>,
+        env: &Env,
+    ) -> azure_core::Result<Self> {
+        let (key, cert, ca_chain) = parse_certificate(certificate.bytes(), password)?;
+        let thumbprint = cert
+            .digest(MessageDigest::sha1())
+            .with_context(ErrorKind::Credential, "failed to compute thumbprint")?
+            .to_vec();
+        let thumbprint = base64::encode(thumbprint);
+
+        let send_x5c = env
+            .var(AZURE_CLIENT_SEND_CERTIFICATE_CHAIN_ENV_KEY)
+            .map(|s| s == "1" || s.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let header = if send_x5c {
+            let base_signature = get_encoded_cert(&cert)?;
+            let x5c = match &ca_chain {
+                Some(chain) => {
+                    let chain = chain
+                        .iter()
+                        .map(get_encoded_cert)
+                        .collect::<azure_core::Result<Vec<String>>>()?
+                        .join(",");
+                    format!("{base_signature},{chain}")
+                }
+                None => base_signature,
+            };
+            format!(r#"{{"alg":"RS256","typ":"JWT","x5c":[{x5c}],"x5t":"{thumbprint}"}}"#)
+        } else {
+            format!(r#"{{"alg":"RS256","typ":"JWT","x5t":"{thumbprint}"}}"#)
+        };
+
+        Ok(Self {
+            audience,
+            client_id,
+            header: CertificateAssertion::as_jwt_part(header.as_bytes()),
+            key,
+        })
+    }
+
+    fn sign(jwt: &str, pkey: &PKey<Private>) -> Result<Vec<u8>, ErrorStack> {
+        let mut signer = Signer::new(MessageDigest::sha256(), pkey)?;
+        signer.update(jwt.as_bytes())?;
+        signer.sign_to_vec()
+    }
+
+    fn as_jwt_part(part: &[u8]) -> String {
+        base64::encode_url_safe(part)
+    }
+}
+
+#[async_trait::async_trait]
+impl ClientAssertion for CertificateAssertion {
+    async fn secret(&self, _: Option<ClientMethodOptions<'_>>) -> azure_core::Result<String> {
+        let uuid = Uuid::new_v4();
+        let current_time = OffsetDateTime::now_utc().unix_timestamp();
+        let expiry_time = current_time + DEFAULT_ASSERTION_LIFETIME;
+        let payload = format!(
+            r#"{{"aud":"{}","exp":{},"iss": "{}", "jti": "{}", "nbf": {}, "sub": "{}"}}"#,
+            self.audience, expiry_time, self.client_id, uuid, current_time, self.client_id
+        );
+        let payload = CertificateAssertion::as_jwt_part(payload.as_bytes());
+
+        let jwt = format!("{}.{}", self.header, payload);
+        let signature = CertificateAssertion::sign(&jwt, &self.key)
+            .with_context(ErrorKind::Credential, "failed to sign JWT")?;
+        let sig = CertificateAssertion::as_jwt_part(&signature);
+        Ok(format!("{}.{}", jwt, sig))
+    }
+}
+
 /// Parse a PKCS12 certificate into key, certificate, and optional CA chain.
 fn parse_certificate(
     cert_bytes: &[u8],
@@ -288,7 +347,7 @@ impl TokenCredential for ClientCertificateCredential {
             ));
         }
         self.cache
-            .get_token(scopes, options, |s, o| self.get_token_impl(s, o))
+            .get_refreshable_token(scopes, options, |s, o| self.get_token_impl(s, o))
             .await
             .map_err(|err| authentication_error(stringify!(ClientCertificateCredential), err))
     }
@@ -1,7 +1,10 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
-use crate::{authentication_error, get_authority_host, TokenCache};
+use crate::{
+    authentication_error, env::Env, get_authority_host, CachedToken, TokenCache,
+    TokenCachePersistenceOptions,
+};
 use azure_core::credentials::TokenRequestOptions;
 use azure_core::http::PipelineSendOptions;
 use azure_core::Result;
@@ -22,6 +25,11 @@ use url::form_urlencoded;
 pub struct ClientSecretCredentialOptions {
     /// Options for the credential's HTTP pipeline.
     pub client_options: ClientOptions,
+
+    /// Persists tokens so other credentials and processes can use them.
+    ///
+    /// Defaults to `None`, in which case the credential caches tokens in memory only.
+    pub token_cache_persistence: Option<TokenCachePersistenceOptions>,
 }
 
 impl fmt::Debug for ClientSecretCredentialOptions {
@@ -74,6 +82,11 @@ impl ClientSecretCredential {
             .with_context_fn(ErrorKind::DataConversion, || {
                 format!("tenant_id '{tenant_id}' could not be URL encoded")
             })?;
+        let cache = TokenCache::with_persistence(
+            options.token_cache_persistence,
+            &Env::default(),
+            format!("{authority_host}|{tenant_id}|{client_id}"),
+        )?;
 
         let pipeline = Pipeline::new(
             option_env!("CARGO_PKG_NAME"),
@@ -85,7 +98,7 @@ impl ClientSecretCredential {
         );
 
         Ok(Arc::new(Self {
-            cache: TokenCache::new(),
+            cache,
             client_id,
             endpoint,
             pipeline,
@@ -97,17 +110,19 @@ impl ClientSecretCredential {
         &self,
         scopes: &[&str],
         options: Option<TokenRequestOptions<'_>>,
-    ) -> Result<AccessToken> {
+    ) -> Result<CachedToken> {
         let mut req = Request::new(self.endpoint.clone(), Method::Post);
         req.insert_header(
             headers::CONTENT_TYPE,
             content_type::APPLICATION_X_WWW_FORM_URLENCODED,
         );
+        let claims = crate::claims_parameter(options.as_ref())?;
         let body = form_urlencoded::Serializer::new(String::new())
             .append_pair("client_id", &self.client_id)
             .append_pair("client_secret", self.secret.secret())
             .append_pair("grant_type", "client_credentials")
             .append_pair("scope", &scopes.join(" "))
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
             .finish();
         req.set_body(body);
 
@@ -143,7 +158,7 @@ impl TokenCredential for ClientSecretCredential {
             ));
         }
         self.cache
-            .get_token(scopes, options, |s, o| self.get_token_impl(s, o))
+            .get_refreshable_token(scopes, options, |s, o| self.get_token_impl(s, o))
             .await
             .map_err(|err| authentication_error(stringify!(ClientSecretCredential), err))
     }
@@ -154,6 +169,7 @@ mod tests {
     use super::*;
     use crate::tests::*;
     use azure_core::{
+        credentials::{EnableCae, TokenClaims},
         http::{headers::Headers, AsyncRawResponse, RawResponse, StatusCode, Transport},
         Bytes, Result,
     };
@@ -192,6 +208,7 @@ mod tests {
                         cloud: Some(Arc::new(cloud)),
                         ..Default::default()
                     },
+                    ..Default::default()
                 }),
             )
             .expect("valid credential");
@@ -228,6 +245,7 @@ mod tests {
                     transport: Some(Transport::new(Arc::new(sts))),
                     ..Default::default()
                 },
+                ..Default::default()
             }),
         )
         .expect("valid credential");
@@ -277,6 +295,7 @@ mod tests {
                     transport: Some(Transport::new(Arc::new(sts))),
                     ..Default::default()
                 },
+                ..Default::default()
             }),
         )
         .expect("valid credential");
@@ -305,6 +324,115 @@ mod tests {
         assert_eq!(token.expires_on, cached_token.expires_on);
     }
 
+    #[tokio::test]
+    async fn claims() {
+        let claims = r#"{"access_token":{"nbf":{"essential":true,"value":"1726077595"}}}"#;
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sts = MockSts::new(
+            vec![token_response(), token_response(), token_response()],
+            Some(Arc::new({
+                let requests = requests.clone();
+                move |req: &Request| {
+                    let azure_core::http::Body::Bytes(body) = req.body() else {
+                        panic!("unexpected body type");
+                    };
+                    requests.lock().unwrap().push(
+                        form_urlencoded::parse(body)
+                            .find(|(k, _)| k == "claims")
+                            .map(|(_, v)| v.to_string()),
+                    );
+                    Ok(())
+                }
+            })),
+        );
+        let cred = ClientSecretCredential::new(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            FAKE_SECRET.into(),
+            Some(ClientSecretCredentialOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(Arc::new(sts))),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .expect("valid credential");
+
+        cred.get_token(LIVE_TEST_SCOPES, None).await.expect("token");
+        let mut cae = TokenRequestOptions::default();
+        cae.method_options.context.insert(EnableCae);
+        cred.get_token(LIVE_TEST_SCOPES, Some(cae.clone()))
+            .await
+            .expect("token");
+        cred.get_token(LIVE_TEST_SCOPES, Some(cae.clone()))
+            .await
+            .expect("cached token");
+        // a request with claims bypasses the cache
+        let mut with_claims = cae;
+        with_claims
+            .method_options
+            .context
+            .insert(TokenClaims::new(claims));
+        cred.get_token(LIVE_TEST_SCOPES, Some(with_claims))
+            .await
+            .expect("token");
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(3, requests.len());
+        assert_eq!(None, requests[0]);
+        assert_eq!(Some(crate::CAE_CLIENT_CAPABILITIES), requests[1].as_deref());
+        let actual: serde_json::Value =
+            serde_json::from_str(requests[2].as_deref().expect("claims")).unwrap();
+        assert_eq!(
+            serde_json::json!({"access_token": {
+                "nbf": {"essential": true, "value": "1726077595"},
+                "xms_cc": {"values": ["cp1"]},
+            }}),
+            actual
+        );
+    }
+
+    #[tokio::test]
+    async fn persistent_cache() {
+        let directory = std::env::temp_dir().join(azure_core::Uuid::new_v4().to_string());
+        let persistence = TokenCachePersistenceOptions {
+            directory: Some(directory.clone()),
+            allow_unencrypted_storage: true,
+            ..Default::default()
+        };
+        let new_credential = |responses| {
+            let sts = MockSts::new(responses, None);
+            ClientSecretCredential::new(
+                FAKE_TENANT_ID,
+                FAKE_CLIENT_ID.to_string(),
+                FAKE_SECRET.into(),
+                Some(ClientSecretCredentialOptions {
+                    client_options: ClientOptions {
+                        transport: Some(Transport::new(Arc::new(sts))),
+                        ..Default::default()
+                    },
+                    token_cache_persistence: Some(persistence.clone()),
+                }),
+            )
+            .expect("valid credential")
+        };
+
+        let token = new_credential(vec![token_response()])
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+
+        // sts will return an error if the second credential sends a request
+        let cached_token = new_credential(vec![])
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("cached token");
+        assert_eq!(token.token.secret(), cached_token.token.secret());
+
+        std::fs::remove_dir_all(directory).expect("remove cache directory");
+    }
+
     #[test]
     fn invalid_tenant_id() {
         ClientSecretCredential::new(
@@ -0,0 +1,111 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{env::Env, scopes_to_resource, token_from_response, TokenCache};
+use azure_core::{
+    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    error::{ErrorKind, ResultExt},
+    http::{
+        headers::{self, content_type},
+        request::Request,
+        ClientOptions, Method, Pipeline, PipelineSendOptions, Url,
+    },
+};
+use std::{any::type_name, fmt, sync::Arc};
+
+const ENDPOINT_ENV: &str = "MSI_ENDPOINT";
+
+/// Authenticates the signed-in user of Azure Cloud Shell through its managed identity endpoint.
+pub(crate) struct CloudShellManagedIdentityCredential {
+    pipeline: Pipeline,
+    endpoint: Url,
+    cache: TokenCache,
+}
+
+impl fmt::Debug for CloudShellManagedIdentityCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CloudShellManagedIdentityCredential {
+    pub fn new(client_options: ClientOptions, env: Env) -> azure_core::Result<Arc<Self>> {
+        let endpoint = &env
+            .var(ENDPOINT_ENV)
+            .with_context_fn(ErrorKind::Credential, || {
+                format!(
+                    "Cloud Shell credential requires {} environment variable",
+                    ENDPOINT_ENV
+                )
+            })?;
+        let endpoint = Url::parse(endpoint).with_context_fn(ErrorKind::Credential, || {
+            format!(
+                "Cloud Shell credential {} environment variable must be a valid URL, but is '{endpoint}'",
+                ENDPOINT_ENV
+            )
+        })?;
+        let pipeline = Pipeline::new(
+            option_env!("CARGO_PKG_NAME"),
+            option_env!("CARGO_PKG_VERSION"),
+            client_options,
+            Vec::default(),
+            Vec::default(),
+            None,
+        );
+        Ok(Arc::new(Self {
+            pipeline,
+            endpoint,
+            cache: TokenCache::new(),
+        }))
+    }
+
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        let resource = scopes_to_resource(scopes)?;
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("resource", resource)
+            .finish();
+
+        let mut req = Request::new(self.endpoint.clone(), Method::Post);
+        req.insert_header("metadata", "true");
+        req.insert_header(
+            headers::CONTENT_TYPE,
+            content_type::APPLICATION_X_WWW_FORM_URLENCODED,
+        );
+        req.set_body(body);
+
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut req,
+                Some(PipelineSendOptions {
+                    skip_checks: true,
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        token_from_response(rsp)
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for CloudShellManagedIdentityCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        self.cache
+            .get_token(scopes, options, |s, o| self.get_token(s, o))
+            .await
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{
+    authentication_error,
+    env::Env,
+    public_client::{scope, PublicClient},
+    CachedToken, TokenCache, TokenCachePersistenceOptions,
+};
+use azure_core::{
+    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    error::{Error, ErrorKind},
+    http::{ClientOptions, StatusCode},
+    sleep::sleep,
+    time::{Duration, OffsetDateTime},
+    Result,
+};
+use serde::Deserialize;
+use std::{any::type_name, fmt, sync::Arc};
+use tracing::trace;
+use url::form_urlencoded;
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// How much to increase the polling interval when Entra ID asks the credential to slow down.
+const SLOW_DOWN_INCREMENT: Duration = Duration::seconds(5);
+
+/// Shows the user how to authenticate with a device code.
+pub type DeviceCodePrompt = Arc<dyn Fn(&DeviceCodeInfo) + Send + Sync>;
+
+/// A device code and instructions for the user, which [`DeviceCodeCredential`] passes to its prompt.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct DeviceCodeInfo {
+    /// The code the user enters at `verification_uri`.
+    pub user_code: String,
+
+    /// The URL at which the user authenticates.
+    pub verification_uri: String,
+
+    /// Instructions for the user from Microsoft Entra ID, including `user_code` and `verification_uri`.
+    pub message: String,
+
+    /// When the device code expires.
+    pub expires_on: OffsetDateTime,
+}
+
+/// Options for constructing a new [`DeviceCodeCredential`].
+#[derive(Clone, Default)]
+pub struct DeviceCodeCredentialOptions {
+    /// Options for the credential's HTTP pipeline.
+    pub client_options: ClientOptions,
+
+    /// The client (application) ID of the application users authenticate to.
+    ///
+    /// Defaults to the Azure development application, which needs no registration.
+    pub client_id: Option<String>,
+
+    /// The tenant users authenticate in.
+    ///
+    /// Defaults to `organizations`, which allows work and school accounts of any tenant.
+    pub tenant_id: Option<String>,
+
+    /// Shows the user how to authenticate.
+    ///
+    /// Defaults to writing [`DeviceCodeInfo::message`] to stderr.
+    pub prompt: Option<DeviceCodePrompt>,
+
+    /// Persists tokens so other credentials and processes can use them.
+    ///
+    /// The persistent cache holds tokens for one user of each tenant and client. Defaults to `None`, in which
+    /// case the credential caches tokens in memory only.
+    pub token_cache_persistence: Option<TokenCachePersistenceOptions>,
+}
+
+impl fmt::Debug for DeviceCodeCredentialOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client_id", &self.client_id)
+            .field("tenant_id", &self.tenant_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Authenticates a user with a device code, which they enter in a browser on any device.
+///
+/// This suits applications that can't open a browser, such as command line tools on remote machines.
+/// The credential prompts the user when it has no valid token for the requested scopes and can't
+/// refresh one, and waits until they authenticate or the device code expires.
+pub struct DeviceCodeCredential {
+    cache: TokenCache,
+    client: PublicClient,
+    prompt: DeviceCodePrompt,
+}
+
+impl fmt::Debug for DeviceCodeCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client", &self.client)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The response from the `devicecode` endpoint.
+#[derive(Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    expires_in: i64,
+    interval: i64,
+    message: String,
+}
+
+/// The part of an error response that tells whether to keep polling.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct PollingErrorResponse {
+    error: String,
+}
+
+impl DeviceCodeCredential {
+    /// Create a new `DeviceCodeCredential`.
+    ///
+    /// # Arguments
+    /// - `options`: Options for configuring the credential. If `None`, the credential uses its default options.
+    ///
+    pub fn new(options: Option<DeviceCodeCredentialOptions>) -> Result<Arc<Self>> {
+        let options = options.unwrap_or_default();
+        let client =
+            PublicClient::new(options.tenant_id, options.client_id, options.client_options)?;
+        let cache = TokenCache::with_persistence(
+            options.token_cache_persistence,
+            &Env::default(),
+            client.cache_partition(),
+        )?;
+
+        let prompt: DeviceCodePrompt = match options.prompt {
+            Some(prompt) => prompt,
+            None => Arc::new(|info: &DeviceCodeInfo| eprintln!("{}", info.message)),
+        };
+
+        Ok(Arc::new(Self {
+            cache,
+            client,
+            prompt,
+        }))
+    }
+
+    async fn get_token_impl(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<CachedToken> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let claims = crate::claims_parameter(Some(&options))?;
+        if let Some(token) = self.client.refresh(&ctx, scopes, claims.as_deref()).await {
+            return Ok(token);
+        }
+
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", self.client.client_id())
+            .append_pair("scope", &scope(scopes))
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
+            .finish();
+        let rsp = self
+            .client
+            .send_form(&ctx, self.client.endpoint("devicecode")?, body)
+            .await?;
+        if !rsp.status().is_success() {
+            return crate::handle_entra_response(rsp);
+        }
+        let device_code: DeviceCodeResponse = crate::deserialize(&rsp)?;
+        let expires_on = OffsetDateTime::now_utc() + Duration::seconds(device_code.expires_in);
+        (self.prompt)(&DeviceCodeInfo {
+            user_code: device_code.user_code,
+            verification_uri: device_code.verification_uri,
+            message: device_code.message,
+            expires_on,
+        });
+
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", self.client.client_id())
+            .append_pair("device_code", &device_code.device_code)
+            .append_pair("grant_type", DEVICE_CODE_GRANT_TYPE)
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
+            .finish();
+        let token_url = self.client.endpoint("token")?;
+        let mut interval = Duration::seconds(device_code.interval);
+        loop {
+            sleep(interval).await;
+            let rsp = self
+                .client
+                .send_form(&ctx, token_url.clone(), body.clone())
+                .await?;
+            if rsp.status() == StatusCode::BadRequest {
+                let error: PollingErrorResponse = crate::deserialize(&rsp).unwrap_or_default();
+                match error.error.as_str() {
+                    "authorization_pending" if OffsetDateTime::now_utc() < expires_on => {
+                        trace!("waiting for the user to authenticate");
+                        continue;
+                    }
+                    "slow_down" if OffsetDateTime::now_utc() < expires_on => {
+                        interval += SLOW_DOWN_INCREMENT;
+                        continue;
+                    }
+                    "authorization_pending" | "slow_down" => {
+                        return Err(Error::with_message(
+                            ErrorKind::Credential,
+                            "the device code expired before the user authenticated",
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            return self.client.redeem(rsp).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for DeviceCodeCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<AccessToken> {
+        if scopes.is_empty() {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "no scopes specified",
+            ));
+        }
+        self.cache
+            .get_refreshable_token(scopes, options, |s, o| self.get_token_impl(s, o))
+            .await
+            .map_err(|err| authentication_error(stringify!(DeviceCodeCredential), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, Request, Transport},
+        Bytes,
+    };
+    use std::sync::Mutex;
+
+    fn device_code_response() -> AsyncRawResponse {
+        AsyncRawResponse::from_bytes(
+            StatusCode::Ok,
+            Headers::default(),
+            Bytes::from_static(
+                br#"{"device_code":"fake-device-code","user_code":"ABC123","verification_uri":"https://microsoft.com/devicelogin","expires_in":900,"interval":0,"message":"enter ABC123"}"#,
+            ),
+        )
+    }
+
+    fn error_response(error: &str) -> AsyncRawResponse {
+        AsyncRawResponse::from_bytes(
+            StatusCode::BadRequest,
+            Headers::default(),
+            Bytes::from(format!(
+                r#"{{"error":"{error}","error_description":"{error} description","error_codes":[70016]}}"#
+            )),
+        )
+    }
+
+    fn refreshable_token_response() -> AsyncRawResponse {
+        AsyncRawResponse::from_bytes(
+            StatusCode::Ok,
+            Headers::default(),
+            Bytes::from(format!(
+                r#"{{"access_token":"{FAKE_TOKEN}","expires_in":3600,"refresh_token":"fake-refresh-token","token_type":"Bearer"}}"#,
+            )),
+        )
+    }
+
+    fn body(req: &Request) -> String {
+        let bytes: Bytes = req.body().into();
+        String::from_utf8(bytes.to_vec()).expect("UTF-8 body")
+    }
+
+    fn new_credential(
+        responses: Vec<AsyncRawResponse>,
+        prompts: Arc<Mutex<Vec<DeviceCodeInfo>>>,
+        requests: Arc<Mutex<Vec<(String, String)>>>,
+    ) -> Arc<DeviceCodeCredential> {
+        let sts = MockSts::new(
+            responses,
+            Some(Arc::new(move |req: &Request| {
+                requests
+                    .lock()
+                    .unwrap()
+                    .push((req.url().path().to_string(), body(req)));
+                Ok(())
+            })),
+        );
+        DeviceCodeCredential::new(Some(DeviceCodeCredentialOptions {
+            client_options: ClientOptions {
+                transport: Some(Transport::new(Arc::new(sts))),
+                ..Default::default()
+            },
+            client_id: Some(FAKE_CLIENT_ID.to_string()),
+            tenant_id: Some(FAKE_TENANT_ID.to_string()),
+            prompt: Some(Arc::new(move |info: &DeviceCodeInfo| {
+                prompts.lock().unwrap().push(info.clone())
+            })),
+            ..Default::default()
+        }))
+        .expect("valid credential")
+    }
+
+    #[tokio::test]
+    async fn get_token_success() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let credential = new_credential(
+            vec![
+                device_code_response(),
+                error_response("authorization_pending"),
+                refreshable_token_response(),
+                token_response(),
+            ],
+            prompts.clone(),
+            requests.clone(),
+        );
+
+        let token = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+        assert_eq!(FAKE_TOKEN, token.token.secret());
+        {
+            let prompts = prompts.lock().unwrap();
+            assert_eq!(1, prompts.len());
+            assert_eq!("ABC123", prompts[0].user_code);
+            assert_eq!("enter ABC123", prompts[0].message);
+        }
+        {
+            let requests = requests.lock().unwrap();
+            assert_eq!(3, requests.len());
+            assert_eq!(
+                format!("/{FAKE_TENANT_ID}/oauth2/v2.0/devicecode"),
+                requests[0].0
+            );
+            assert!(requests[0].1.contains("offline_access"));
+            for (path, body) in &requests[1..] {
+                assert_eq!(&format!("/{FAKE_TENANT_ID}/oauth2/v2.0/token"), path);
+                assert!(body.contains("device_code=fake-device-code"));
+            }
+        }
+
+        // the credential redeems its refresh token for other scopes instead of prompting again
+        credential
+            .get_token(&["https://vault.azure.net/.default"], None)
+            .await
+            .expect("token");
+        assert_eq!(1, prompts.lock().unwrap().len());
+        let requests = requests.lock().unwrap();
+        assert_eq!(4, requests.len());
+        assert!(requests[3].1.contains("grant_type=refresh_token"));
+        assert!(requests[3].1.contains("refresh_token=fake-refresh-token"));
+    }
+
+    #[tokio::test]
+    async fn authorization_declined() {
+        let credential = new_credential(
+            vec![
+                device_code_response(),
+                error_response("authorization_declined"),
+            ],
+            Arc::default(),
+            Arc::default(),
+        );
+
+        let err = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect_err("declined");
+        assert!(matches!(err.kind(), ErrorKind::Credential));
+        assert!(
+            err.to_string()
+                .contains("authorization_declined description"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn no_scopes() {
+        DeviceCodeCredential::new(None)
+            .expect("valid credential")
+            .get_token(&[], None)
+            .await
+            .expect_err("no scopes specified");
+    }
+}
@@ -7,7 +7,7 @@ use azure_core::{
     error::{Error, ErrorKind},
     http::{
         headers::HeaderName, request::Request, ClientOptions, Method, Pipeline, PipelineOptions,
-        PipelineSendOptions, StatusCode, Url,
+        PipelineSendOptions, RawResponse, StatusCode, Url,
     },
     json::from_json,
     time::OffsetDateTime,
@@ -44,6 +44,25 @@ impl From<UserAssignedId> for ImdsId {
     }
 }
 
+/// The names of the query parameters that identify a user-assigned identity, which differ between
+/// managed identity sources.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ImdsIdParams {
+    pub client_id: &'static str,
+    pub object_id: &'static str,
+    pub resource_id: &'static str,
+}
+
+impl Default for ImdsIdParams {
+    fn default() -> Self {
+        Self {
+            client_id: "client_id",
+            object_id: "object_id",
+            resource_id: "msi_res_id",
+        }
+    }
+}
+
 /// Attempts authentication using a managed identity that has been assigned to the deployment environment.
 ///
 /// This authentication type works in Azure VMs, App Service and Azure Functions applications, as well as the Azure Cloud Shell
@@ -56,6 +75,7 @@ pub(crate) struct ImdsManagedIdentityCredential {
     secret_header: HeaderName,
     secret_env: String,
     id: ImdsId,
+    id_params: ImdsIdParams,
     cache: TokenCache,
     env: Env,
 }
@@ -95,11 +115,18 @@ impl ImdsManagedIdentityCredential {
             secret_header: secret_header.to_owned(),
             secret_env: secret_env.to_owned(),
             id,
+            id_params: ImdsIdParams::default(),
             cache: TokenCache::new(),
             env,
         }
     }
 
+    /// Sets the names of the query parameters that identify a user-assigned identity.
+    pub fn with_id_params(mut self, id_params: ImdsIdParams) -> Self {
+        self.id_params = id_params;
+        self
+    }
+
     async fn get_token(
         &self,
         scopes: &[&str],
@@ -114,9 +141,15 @@ impl ImdsManagedIdentityCredential {
 
         match self.id {
             ImdsId::SystemAssigned => (),
-            ImdsId::ClientId(ref client_id) => query_items.push(("client_id", client_id)),
-            ImdsId::ObjectId(ref object_id) => query_items.push(("object_id", object_id)),
-            ImdsId::MsiResId(ref msi_res_id) => query_items.push(("msi_res_id", msi_res_id)),
+            ImdsId::ClientId(ref client_id) => {
+                query_items.push((self.id_params.client_id, client_id))
+            }
+            ImdsId::ObjectId(ref object_id) => {
+                query_items.push((self.id_params.object_id, object_id))
+            }
+            ImdsId::MsiResId(ref msi_res_id) => {
+                query_items.push((self.id_params.resource_id, msi_res_id))
+            }
         }
 
         let mut url = self.endpoint.clone();
@@ -145,35 +178,7 @@ impl ImdsManagedIdentityCredential {
             )
             .await?;
 
-        let status = rsp.status();
-        if !status.is_success() {
-            let message = match status {
-                StatusCode::BadRequest => {
-                    "The requested identity has not been assigned to this resource".to_string()
-                }
-                StatusCode::BadGateway | StatusCode::GatewayTimeout => {
-                    "The request failed due to a gateway error".to_string()
-                }
-                _ => {
-                    let body = String::from_utf8_lossy(rsp.body());
-                    format!("The request failed: {body}")
-                }
-            };
-            return Err(Error::new(
-                ErrorKind::HttpResponse {
-                    error_code: None,
-                    raw_response: Some(Box::new(rsp)),
-                    status,
-                },
-                message,
-            ));
-        }
-
-        let token_response: MsiTokenResponse = from_json(rsp.into_body())?;
-        Ok(AccessToken::new(
-            token_response.access_token,
-            token_response.expires_on,
-        ))
+        token_from_response(rsp)
     }
 }
 
@@ -194,8 +199,18 @@ fn expires_on_string<'de, D>(deserializer: D) -> std::result::Result<OffsetDateT
 where
     D: Deserializer<'de>,
 {
-    let v = String::deserialize(deserializer)?;
-    let as_i64 = v.parse::<i64>().map_err(de::Error::custom)?;
+    // Most sources return a string, but Service Fabric returns a number.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ExpiresOn {
+        Number(i64),
+        String(String),
+    }
+
+    let as_i64 = match ExpiresOn::deserialize(deserializer)? {
+        ExpiresOn::Number(v) => v,
+        ExpiresOn::String(v) => v.parse::<i64>().map_err(de::Error::custom)?,
+    };
     OffsetDateTime::from_unix_timestamp(as_i64).map_err(de::Error::custom)
 }
 
@@ -203,7 +218,7 @@ where
 ///
 /// Directly based on the `azure-sdk-for-python` implementation:
 /// ref: <https://github.com/Azure/azure-sdk-for-python/blob/d6aeefef46c94b056419613f1a5cc9eaa3af0d22/sdk/identity/azure-identity/azure/identity/_internal/__init__.py#L22>
-fn scopes_to_resource<'a>(scopes: &'a [&'a str]) -> azure_core::Result<&'a str> {
+pub(crate) fn scopes_to_resource<'a>(scopes: &'a [&'a str]) -> azure_core::Result<&'a str> {
     if scopes.len() != 1 {
         return Err(Error::with_message(
             ErrorKind::Credential,
@@ -221,7 +236,41 @@ fn scopes_to_resource<'a>(scopes: &'a [&'a str]) -> azure_core::Result<&'a str>
     Ok(scope.strip_suffix("/.default").unwrap_or(*scope))
 }
 
-// NOTE: expires_on is a String version of unix epoch time, not an integer.
+/// Gets the access token from the response of a managed identity endpoint.
+pub(crate) fn token_from_response(rsp: RawResponse) -> azure_core::Result<AccessToken> {
+    let status = rsp.status();
+    if !status.is_success() {
+        let message = match status {
+            StatusCode::BadRequest => {
+                "The requested identity has not been assigned to this resource".to_string()
+            }
+            StatusCode::BadGateway | StatusCode::GatewayTimeout => {
+                "The request failed due to a gateway error".to_string()
+            }
+            _ => {
+                let body = String::from_utf8_lossy(rsp.body());
+                format!("The request failed: {body}")
+            }
+        };
+        return Err(Error::new(
+            ErrorKind::HttpResponse {
+                error_code: None,
+                raw_response: Some(Box::new(rsp)),
+                status,
+            },
+            message,
+        ));
+    }
+
+    let token_response: MsiTokenResponse = from_json(rsp.into_body())?;
+    Ok(AccessToken::new(
+        token_response.access_token,
+        token_response.expires_on,
+    ))
+}
+
+// NOTE: expires_on is usually a String version of unix epoch time, not an integer. Service Fabric
+// returns an integer.
 // https://learn.microsoft.com/azure/app-service/overview-managed-identity?tabs=dotnet#rest-protocol-examples
 #[derive(Debug, Clone, Deserialize)]
 #[allow(unused)]
@@ -250,6 +299,10 @@ mod tests {
         let expected = datetime!(2020-4-15 21:5:35 UTC);
         let parsed: TestExpires = from_json(as_string)?;
         assert_eq!(expected, parsed.date);
+
+        let as_number = r#"{"date": 1586984735}"#;
+        let parsed: TestExpires = from_json(as_number)?;
+        assert_eq!(expected, parsed.date);
         Ok(())
     }
 }
@@ -0,0 +1,573 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{
+    authentication_error,
+    env::Env,
+    process::{new_executor, Executor},
+    public_client::{scope, PublicClient},
+    CachedToken, TokenCache, TokenCachePersistenceOptions,
+};
+use azure_core::{
+    base64,
+    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    error::{Error, ErrorKind, ResultExt},
+    http::{ClientOptions, Url},
+    Result, Uuid,
+};
+use futures::channel::oneshot;
+use sha2::{Digest, Sha256};
+use std::{
+    any::type_name,
+    ffi::OsStr,
+    fmt,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+use url::form_urlencoded;
+
+/// How long the credential waits for the user to authenticate in the browser.
+const AUTHORIZATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+const LISTENER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+const SUCCESS_PAGE: &str =
+    "<html><body>Authentication complete. You can close this window.</body></html>";
+
+const FAILURE_PAGE: &str =
+    "<html><body>Authentication failed. You can close this window.</body></html>";
+
+/// Options for constructing a new [`InteractiveBrowserCredential`].
+#[derive(Clone, Default)]
+pub struct InteractiveBrowserCredentialOptions {
+    /// Options for the credential's HTTP pipeline.
+    pub client_options: ClientOptions,
+
+    /// The client (application) ID of the application users authenticate to.
+    ///
+    /// Defaults to the Azure development application, which needs no registration.
+    pub client_id: Option<String>,
+
+    /// The tenant users authenticate in.
+    ///
+    /// Defaults to `organizations`, which allows work and school accounts of any tenant.
+    pub tenant_id: Option<String>,
+
+    /// The redirect URI of the application, which must be `http://localhost` with an optional port.
+    ///
+    /// Defaults to `http://localhost` with a port chosen by the operating system.
+    pub redirect_uri: Option<Url>,
+
+    /// The username to prefill in the sign-in page.
+    pub login_hint: Option<String>,
+
+    /// An implementation of [`Executor`] to run the command that opens the browser.
+    ///
+    /// If `None`, one is created using [`new_executor`]; alternatively,
+    /// you can supply your own implementation using a different asynchronous runtime.
+    pub executor: Option<Arc<dyn Executor>>,
+
+    /// Persists tokens so other credentials and processes can use them.
+    ///
+    /// The persistent cache holds tokens for one user of each tenant and client. Defaults to `None`, in which
+    /// case the credential caches tokens in memory only.
+    pub token_cache_persistence: Option<TokenCachePersistenceOptions>,
+}
+
+impl fmt::Debug for InteractiveBrowserCredentialOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client_id", &self.client_id)
+            .field("tenant_id", &self.tenant_id)
+            .field("redirect_uri", &self.redirect_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Authenticates a user in the system browser with the authorization code flow.
+///
+/// The credential opens the browser to the Microsoft Entra sign-in page and receives the authorization code
+/// at a loopback redirect URI, which it redeems using PKCE. It opens the browser when it has no valid token
+/// for the requested scopes and can't refresh one.
+pub struct InteractiveBrowserCredential {
+    cache: TokenCache,
+    client: PublicClient,
+    executor: Arc<dyn Executor>,
+    login_hint: Option<String>,
+    redirect_uri: Url,
+}
+
+impl fmt::Debug for InteractiveBrowserCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client", &self.client)
+            .field("redirect_uri", &self.redirect_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The query of the request the browser sends to the redirect URI.
+#[derive(Debug, Default)]
+struct AuthorizationResponse {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+impl InteractiveBrowserCredential {
+    /// Create a new `InteractiveBrowserCredential`.
+    ///
+    /// # Arguments
+    /// - `options`: Options for configuring the credential. If `None`, the credential uses its default options.
+    ///
+    pub fn new(options: Option<InteractiveBrowserCredentialOptions>) -> Result<Arc<Self>> {
+        let options = options.unwrap_or_default();
+        let redirect_uri = match options.redirect_uri {
+            Some(uri) => uri,
+            None => Url::parse("http://localhost")?,
+        };
+        if redirect_uri.scheme() != "http" || redirect_uri.host_str() != Some("localhost") {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                format!("redirect URI must be http://localhost with an optional port, but is '{redirect_uri}'"),
+            ));
+        }
+        let client =
+            PublicClient::new(options.tenant_id, options.client_id, options.client_options)?;
+        let cache = TokenCache::with_persistence(
+            options.token_cache_persistence,
+            &Env::default(),
+            client.cache_partition(),
+        )?;
+
+        Ok(Arc::new(Self {
+            cache,
+            client,
+            executor: options.executor.unwrap_or(new_executor()),
+            login_hint: options.login_hint,
+            redirect_uri,
+        }))
+    }
+
+    async fn get_token_impl(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<CachedToken> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let claims = crate::claims_parameter(Some(&options))?;
+        if let Some(token) = self.client.refresh(&ctx, scopes, claims.as_deref()).await {
+            return Ok(token);
+        }
+
+        let verifier = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let challenge = base64::encode_url_safe(Sha256::digest(verifier.as_bytes()));
+        let state = Uuid::new_v4().to_string();
+        let scope = scope(scopes);
+
+        let (redirect_uri, response) = listen(self.redirect_uri.clone())?;
+        let mut authorize_url = self.client.endpoint("authorize")?;
+        {
+            let mut query = authorize_url.query_pairs_mut();
+            query
+                .append_pair("client_id", self.client.client_id())
+                .append_pair("code_challenge", &challenge)
+                .append_pair("code_challenge_method", "S256")
+                .append_pair("prompt", "select_account")
+                .append_pair("redirect_uri", redirect_uri.as_str())
+                .append_pair("response_mode", "query")
+                .append_pair("response_type", "code")
+                .append_pair("scope", &scope)
+                .append_pair("state", &state);
+            if let Some(login_hint) = &self.login_hint {
+                query.append_pair("login_hint", login_hint);
+            }
+            if let Some(claims) = &claims {
+                query.append_pair("claims", claims);
+            }
+        }
+        self.open_browser(&authorize_url).await?;
+
+        let response = response
+            .await
+            .map_err(|_| {
+                Error::with_message(
+                    ErrorKind::Credential,
+                    "stopped waiting for the authorization code",
+                )
+            })?
+            .with_context(
+                ErrorKind::Credential,
+                "failed to receive the authorization code",
+            )?;
+        if let Some(error) = response.error {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                match response.error_description {
+                    Some(description) => format!("{error}: {description}"),
+                    None => error,
+                },
+            ));
+        }
+        if response.state.as_deref() != Some(state.as_str()) {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "the state of the authorization response doesn't match the request",
+            ));
+        }
+        let code = response.code.ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::Credential,
+                "the authorization response has no code",
+            )
+        })?;
+
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", self.client.client_id())
+            .append_pair("code", &code)
+            .append_pair("code_verifier", &verifier)
+            .append_pair("grant_type", "authorization_code")
+            .append_pair("redirect_uri", redirect_uri.as_str())
+            .append_pair("scope", &scope)
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
+            .finish();
+        let rsp = self
+            .client
+            .send_form(&ctx, self.client.endpoint("token")?, body)
+            .await?;
+        self.client.redeem(rsp).await
+    }
+
+    async fn open_browser(&self, url: &Url) -> Result<()> {
+        let url = OsStr::new(url.as_str());
+        let (program, args) = if cfg!(windows) {
+            (
+                OsStr::new("rundll32"),
+                vec![OsStr::new("url.dll,FileProtocolHandler"), url],
+            )
+        } else if cfg!(target_os = "macos") {
+            (OsStr::new("open"), vec![url])
+        } else {
+            (OsStr::new("xdg-open"), vec![url])
+        };
+        match self.executor.run(program, &args).await {
+            Ok(output) if output.status.success() => Ok(()),
+            Ok(output) => Err(Error::with_message(
+                ErrorKind::Credential,
+                format!(
+                    "failed to open a browser: {}",
+                    String::from_utf8_lossy(&output.stderr)
+                ),
+            )),
+            Err(err) => Err(Error::with_error(
+                ErrorKind::Credential,
+                err,
+                "failed to open a browser",
+            )),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for InteractiveBrowserCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<AccessToken> {
+        if scopes.is_empty() {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "no scopes specified",
+            ));
+        }
+        self.cache
+            .get_refreshable_token(scopes, options, |s, o| self.get_token_impl(s, o))
+            .await
+            .map_err(|err| authentication_error(stringify!(InteractiveBrowserCredential), err))
+    }
+}
+
+/// Listens for the browser's request to the redirect URI in a separate thread.
+///
+/// Returns the redirect URI, with the port the listener bound when the given URI has none, and a receiver
+/// for the query of the request. The thread stops when it receives the request, after [`AUTHORIZATION_TIMEOUT`],
+/// or when the receiver is dropped.
+fn listen(
+    mut redirect_uri: Url,
+) -> Result<(Url, oneshot::Receiver<io::Result<AuthorizationResponse>>)> {
+    let listener = TcpListener::bind(("127.0.0.1", redirect_uri.port().unwrap_or(0)))
+        .and_then(|listener| {
+            listener.set_nonblocking(true)?;
+            Ok(listener)
+        })
+        .with_context(ErrorKind::Credential, "failed to listen for the redirect")?;
+    if redirect_uri.port().is_none() {
+        let port = listener
+            .local_addr()
+            .with_context(ErrorKind::Credential, "failed to listen for the redirect")?
+            .port();
+        redirect_uri.set_port(Some(port)).map_err(|_| {
+            Error::with_message(ErrorKind::Credential, "failed to set redirect URI port")
+        })?;
+    }
+
+    let (tx, rx) = oneshot::channel();
+    let path = redirect_uri.path().to_string();
+    let deadline = Instant::now() + AUTHORIZATION_TIMEOUT;
+    thread::spawn(move || loop {
+        if tx.is_canceled() {
+            return;
+        }
+        if Instant::now() > deadline {
+            let _ = tx.send(Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "timed out waiting for the user to authenticate",
+            )));
+            return;
+        }
+        match listener.accept() {
+            Ok((stream, _)) => match receive(stream, &path) {
+                Ok(Some(response)) => {
+                    let _ = tx.send(Ok(response));
+                    return;
+                }
+                // not the redirect, or a connection that failed before sending it
+                Ok(None) | Err(_) => {}
+            },
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(LISTENER_POLL_INTERVAL)
+            }
+            Err(err) => {
+                let _ = tx.send(Err(err));
+                return;
+            }
+        }
+    });
+
+    Ok((redirect_uri, rx))
+}
+
+/// Reads a request to the listener, returning its query if it's for the redirect URI's path.
+fn receive(mut stream: TcpStream, path: &str) -> io::Result<Option<AuthorizationResponse>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+
+    // only the request line matters, which browsers send in the first packet
+    let mut buf = [0u8; 8192];
+    let mut len = 0;
+    while len < buf.len() {
+        let read = stream.read(&mut buf[len..])?;
+        len += read;
+        if read == 0 || buf[..len].windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+    }
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let target = request
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|line| line.split(' ').next());
+    let Some(url) = target.and_then(|target| {
+        Url::parse("http://localhost")
+            .and_then(|base| base.join(target))
+            .ok()
+    }) else {
+        return respond(&mut stream, "400 Bad Request", "").map(|_| None);
+    };
+    if url.path() != path {
+        return respond(&mut stream, "404 Not Found", "").map(|_| None);
+    }
+
+    let mut response = AuthorizationResponse::default();
+    for (name, value) in url.query_pairs() {
+        let value = Some(value.into_owned());
+        match name.as_ref() {
+            "code" => response.code = value,
+            "state" => response.state = value,
+            "error" => response.error = value,
+            "error_description" => response.error_description = value,
+            _ => {}
+        }
+    }
+    let page = if response.code.is_some() {
+        SUCCESS_PAGE
+    } else {
+        FAILURE_PAGE
+    };
+    respond(&mut stream, "200 OK", page)?;
+    Ok(Some(response))
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use azure_core::{
+        http::{Request, Transport},
+        Bytes,
+    };
+    use std::sync::Mutex;
+
+    /// Simulates the browser, sending the redirect with the given query, or with the authorization request's
+    /// state and a code when the query is `None`.
+    fn browser(query: Option<&'static str>, authorize_url: Arc<Mutex<Option<Url>>>) -> RunCallback {
+        Arc::new(move |_: &OsStr, args: &[&OsStr]| {
+            let url = Url::parse(args.last().unwrap().to_str().unwrap()).unwrap();
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, v)| v.into_owned())
+                    .unwrap()
+            };
+            let redirect_uri = Url::parse(&param("redirect_uri")).unwrap();
+            let query = match query {
+                Some(query) => query.to_string(),
+                None => format!("code=fake-code&state={}", param("state")),
+            };
+            let mut stream =
+                TcpStream::connect(("127.0.0.1", redirect_uri.port().unwrap())).unwrap();
+            write!(
+                stream,
+                "GET {}?{query} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+                redirect_uri.path()
+            )
+            .unwrap();
+            let mut page = String::new();
+            stream.read_to_string(&mut page).unwrap();
+            assert!(page.starts_with("HTTP/1.1 200 OK"), "{page}");
+            *authorize_url.lock().unwrap() = Some(url);
+        })
+    }
+
+    fn new_credential(sts: MockSts, on_run: RunCallback) -> Arc<InteractiveBrowserCredential> {
+        InteractiveBrowserCredential::new(Some(InteractiveBrowserCredentialOptions {
+            client_options: ClientOptions {
+                transport: Some(Transport::new(Arc::new(sts))),
+                ..Default::default()
+            },
+            client_id: Some(FAKE_CLIENT_ID.to_string()),
+            tenant_id: Some(FAKE_TENANT_ID.to_string()),
+            executor: Some(MockExecutor::with_output(0, "", "", Some(on_run))),
+            ..Default::default()
+        }))
+        .expect("valid credential")
+    }
+
+    #[tokio::test]
+    async fn get_token_success() {
+        let authorize_url = Arc::new(Mutex::new(None));
+        let token_request = Arc::new(Mutex::new(None));
+        let sts = MockSts::new(vec![token_response()], {
+            let token_request = token_request.clone();
+            Some(Arc::new(move |req: &Request| {
+                let body: Bytes = req.body().into();
+                *token_request.lock().unwrap() =
+                    Some((req.url().clone(), String::from_utf8(body.to_vec()).unwrap()));
+                Ok(())
+            }))
+        });
+        let credential = new_credential(sts, browser(None, authorize_url.clone()));
+
+        let token = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+        assert_eq!(FAKE_TOKEN, token.token.secret());
+
+        let authorize_url = authorize_url.lock().unwrap().clone().unwrap();
+        assert_eq!(
+            format!("{FAKE_PUBLIC_CLOUD_AUTHORITY}/oauth2/v2.0/authorize"),
+            authorize_url[..url::Position::AfterPath]
+        );
+        let (token_url, body) = token_request.lock().unwrap().clone().unwrap();
+        assert_eq!(
+            format!("{FAKE_PUBLIC_CLOUD_AUTHORITY}/oauth2/v2.0/token"),
+            token_url.as_str()
+        );
+        let body: Vec<(String, String)> = form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+        let param = |name: &str| {
+            body.iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.clone())
+                .unwrap()
+        };
+        assert_eq!("fake-code", param("code"));
+        assert_eq!("authorization_code", param("grant_type"));
+        let challenge = authorize_url
+            .query_pairs()
+            .find(|(n, _)| n == "code_challenge")
+            .map(|(_, v)| v.into_owned())
+            .unwrap();
+        assert_eq!(
+            challenge,
+            base64::encode_url_safe(Sha256::digest(param("code_verifier").as_bytes()))
+        );
+        assert!(authorize_url
+            .query_pairs()
+            .any(|(n, v)| n == "redirect_uri" && v == param("redirect_uri")));
+    }
+
+    #[tokio::test]
+    async fn authorization_error() {
+        let credential = new_credential(
+            MockSts::new(vec![], None),
+            browser(
+                Some("error=access_denied&error_description=the+user+declined"),
+                Arc::default(),
+            ),
+        );
+
+        let err = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect_err("authorization error");
+        assert!(matches!(err.kind(), ErrorKind::Credential));
+        assert!(
+            err.to_string().contains("access_denied: the user declined"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn state_mismatch() {
+        let credential = new_credential(
+            MockSts::new(vec![], None),
+            browser(Some("code=fake-code&state=wrong"), Arc::default()),
+        );
+
+        let err = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect_err("state mismatch");
+        assert!(err.to_string().contains("state"), "{err}");
+    }
+
+    #[test]
+    fn invalid_redirect_uri() {
+        for uri in ["https://localhost", "http://contoso.com"] {
+            InteractiveBrowserCredential::new(Some(InteractiveBrowserCredentialOptions {
+                redirect_uri: Some(Url::parse(uri).unwrap()),
+                ..Default::default()
+            }))
+            .expect_err(uri);
+        }
+    }
+}
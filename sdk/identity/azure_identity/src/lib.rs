@@ -9,17 +9,25 @@ mod app_service_managed_identity_credential;
 mod azure_arc_credential;
 mod azure_cli_credential;
 mod azure_developer_cli_credential;
+mod azure_ml_managed_identity_credential;
 mod azure_pipelines_credential;
 mod cache;
 mod client_assertion_credential;
 #[cfg(feature = "client_certificate")]
 mod client_certificate_credential;
 mod client_secret_credential;
+mod cloud_shell_managed_identity_credential;
 mod developer_tools_credential;
+mod device_code_credential;
 mod env;
 mod imds_managed_identity_credential;
+mod interactive_browser_credential;
 mod managed_identity_credential;
+mod on_behalf_of_credential;
 mod process;
+mod public_client;
+mod service_fabric_managed_identity_credential;
+mod token_cache_persistence;
 mod virtual_machine_managed_identity_credential;
 mod workload_identity_credential;
 
@@ -31,20 +39,27 @@ pub use client_assertion_credential::*;
 pub use client_certificate_credential::*;
 pub use client_secret_credential::*;
 pub use developer_tools_credential::*;
+pub use device_code_credential::*;
+pub use interactive_browser_credential::*;
 pub use managed_identity_credential::*;
+pub use on_behalf_of_credential::*;
 pub use process::{new_executor, Executor};
+pub use token_cache_persistence::{TokenCacheEncryption, TokenCachePersistenceOptions};
 pub use workload_identity_credential::*;
 
 pub(crate) use app_service_managed_identity_credential::*;
-pub(crate) use cache::TokenCache;
+pub(crate) use azure_ml_managed_identity_credential::*;
+pub(crate) use cache::{CachedToken, TokenCache};
+pub(crate) use cloud_shell_managed_identity_credential::*;
 pub(crate) use imds_managed_identity_credential::*;
+pub(crate) use service_fabric_managed_identity_credential::*;
 pub(crate) use virtual_machine_managed_identity_credential::*;
 
 use crate::env::Env;
 use azure_core::{
     cloud::CloudConfiguration,
-    credentials::AccessToken,
-    error::ErrorKind,
+    credentials::{AccessToken, TokenRequestOptions},
+    error::{ErrorKind, ResultExt},
     http::{RawResponse, Url},
     time::{Duration, OffsetDateTime},
     Error, Result,
@@ -67,6 +82,8 @@ struct EntraIdTokenResponse {
     // (real values are unsigned)
     expires_in: i64,
     ext_expires_in: i64,
+    /// Seconds until the token should be refreshed, when Entra ID recommends refreshing before expiration
+    refresh_in: i64,
     access_token: String,
 }
 
@@ -77,14 +94,19 @@ where
     serde_json::from_slice(res.body().as_ref()).map_err(Into::into)
 }
 
-fn handle_entra_response(response: RawResponse) -> Result<AccessToken> {
+fn handle_entra_response(response: RawResponse) -> Result<CachedToken> {
     let status = response.status();
     if status.is_success() {
         let token_response: EntraIdTokenResponse = deserialize(&response)?;
-        return Ok(AccessToken::new(
-            token_response.access_token,
-            OffsetDateTime::now_utc() + Duration::seconds(token_response.expires_in),
-        ));
+        let now = OffsetDateTime::now_utc();
+        return Ok(CachedToken {
+            token: AccessToken::new(
+                token_response.access_token,
+                now + Duration::seconds(token_response.expires_in),
+            ),
+            refresh_on: (token_response.refresh_in > 0)
+                .then(|| now + Duration::seconds(token_response.refresh_in)),
+        });
     }
 
     let error_response: EntraIdErrorResponse<'_> = deserialize(&response)?;
@@ -112,6 +134,46 @@ fn handle_entra_response(response: RawResponse) -> Result<AccessToken> {
     ))
 }
 
+/// The claims requesting tokens for clients supporting continuous access evaluation (the `cp1` capability).
+const CAE_CLIENT_CAPABILITIES: &str = r#"{"access_token":{"xms_cc":{"values":["cp1"]}}}"#;
+
+/// Gets the value of the `claims` parameter of a request to Entra ID, if it needs one.
+///
+/// This merges the claims from a claims challenge, if any, with the client capabilities claim when
+/// the caller enables continuous access evaluation.
+fn claims_parameter(options: Option<&TokenRequestOptions<'_>>) -> Result<Option<String>> {
+    let Some(options) = options else {
+        return Ok(None);
+    };
+    let mut claims = match options.claims() {
+        Some(claims) => serde_json::from_str(claims)
+            .with_context(ErrorKind::DataConversion, "claims must be a JSON object")?,
+        None if options.enable_cae() => serde_json::Value::Object(Default::default()),
+        None => return Ok(None),
+    };
+    if options.enable_cae() {
+        let capabilities = serde_json::from_str(CAE_CLIENT_CAPABILITIES)?;
+        merge_claims(&mut claims, capabilities);
+    }
+    Ok(Some(claims.to_string()))
+}
+
+fn merge_claims(claims: &mut serde_json::Value, other: serde_json::Value) {
+    match (claims, other) {
+        (serde_json::Value::Object(claims), serde_json::Value::Object(other)) => {
+            for (key, value) in other {
+                match claims.get_mut(&key) {
+                    Some(existing) => merge_claims(existing, value),
+                    None => {
+                        claims.insert(key, value);
+                    }
+                }
+            }
+        }
+        (claims, other) => *claims = other,
+    }
+}
+
 fn validate_not_empty<C>(value: &str, message: C) -> Result<()>
 where
     C: Into<Cow<'static, str>>,
@@ -267,6 +329,7 @@ mod tests {
     use async_trait::async_trait;
     use azure_core::{
         cloud::{CloudConfiguration, CustomConfiguration},
+        credentials::{EnableCae, TokenClaims},
         error::ErrorKind,
         http::{headers::Headers, AsyncRawResponse, RawResponse, Request, StatusCode},
         Bytes, Error, Result,
@@ -497,4 +560,67 @@ mod tests {
         let inner = err.into_inner().expect("expected inner error");
         assert_eq!(inner.to_string(), "bad news");
     }
+
+    #[test]
+    fn entra_refresh_in() {
+        let response = RawResponse::from_bytes(
+            StatusCode::Ok,
+            Headers::default(),
+            Bytes::from_static(
+                br#"{"access_token":"***","expires_in":7200,"refresh_in":3600,"token_type":"Bearer"}"#,
+            ),
+        );
+
+        let token = handle_entra_response(response).expect("token");
+        let refresh_on = token.refresh_on.expect("refresh_on");
+        assert!(refresh_on < token.token.expires_on);
+        assert_eq!(token.token.expires_on - refresh_on, Duration::seconds(3600));
+    }
+
+    #[test]
+    fn claims() {
+        let options = |claims: Option<&str>, enable_cae| {
+            let mut options = TokenRequestOptions::default();
+            if let Some(claims) = claims {
+                options
+                    .method_options
+                    .context
+                    .insert(TokenClaims::new(claims));
+            }
+            if enable_cae {
+                options.method_options.context.insert(EnableCae);
+            }
+            options
+        };
+        let challenge = r#"{"access_token":{"nbf":{"essential":true,"value":"1726077595"}}}"#;
+
+        assert_eq!(None, claims_parameter(None).unwrap());
+        assert_eq!(None, claims_parameter(Some(&options(None, false))).unwrap());
+        assert_eq!(
+            Some(CAE_CLIENT_CAPABILITIES.to_string()),
+            claims_parameter(Some(&options(None, true))).unwrap()
+        );
+        assert_eq!(
+            Some(challenge.to_string()),
+            claims_parameter(Some(&options(Some(challenge), false))).unwrap()
+        );
+
+        let merged: serde_json::Value = serde_json::from_str(
+            &claims_parameter(Some(&options(Some(challenge), true)))
+                .unwrap()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::json!({"access_token": {
+                "nbf": {"essential": true, "value": "1726077595"},
+                "xms_cc": {"values": ["cp1"]},
+            }}),
+            merged
+        );
+
+        let err =
+            claims_parameter(Some(&options(Some("not JSON"), true))).expect_err("invalid claims");
+        assert_eq!(ErrorKind::DataConversion, *err.kind());
+    }
 }
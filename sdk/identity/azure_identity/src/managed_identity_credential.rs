@@ -3,8 +3,9 @@
 
 use crate::azure_arc_credential::{is_arc_agent_present, AzureArcCredential};
 use crate::{
-    authentication_error, env::Env, AppServiceManagedIdentityCredential, ImdsId,
-    VirtualMachineManagedIdentityCredential,
+    authentication_error, env::Env, AppServiceManagedIdentityCredential,
+    AzureMlManagedIdentityCredential, CloudShellManagedIdentityCredential, ImdsId,
+    ServiceFabricManagedIdentityCredential, VirtualMachineManagedIdentityCredential,
 };
 use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
 use azure_core::http::ClientOptions;
@@ -23,7 +24,8 @@ pub enum UserAssignedId {
     ResourceId(String),
 }
 
-/// Authenticates a managed identity from Azure App Service, Azure Virtual Machine, or Azure Arc.
+/// Authenticates a managed identity from Azure App Service, Azure Virtual Machine, Azure Arc, Azure Cloud Shell,
+/// Azure Machine Learning, or Service Fabric.
 pub struct ManagedIdentityCredential {
     credential: Arc<dyn TokenCredential>,
 }
@@ -75,16 +77,6 @@ impl ManagedIdentityCredential {
 
         let credential: Arc<dyn TokenCredential> = match source {
             ManagedIdentitySource::AppService => {
-                // App Service does accept resource IDs, however this crate's current implementation sends
-                // them in the wrong query parameter: https://github.com/Azure/azure-sdk-for-rust/issues/2407
-                if let ImdsId::MsiResId(_) = id {
-                    return Err(azure_core::Error::with_message_fn(
-                        azure_core::error::ErrorKind::Credential,
-                        || {
-                            "User-assigned resource IDs aren't supported for App Service. Use a client or object ID instead.".to_string()
-                        },
-                    ));
-                }
                 AppServiceManagedIdentityCredential::new(id, options.client_options, env)?
             }
             ManagedIdentitySource::Imds => {
@@ -101,11 +93,39 @@ impl ManagedIdentityCredential {
                 }
                 AzureArcCredential::new(id, options.client_options, env)?
             }
-            _ => {
-                return Err(azure_core::Error::with_message_fn(
-                    azure_core::error::ErrorKind::Credential,
-                    || format!("{} managed identity isn't supported", source.as_str()),
-                ));
+            ManagedIdentitySource::AzureML => {
+                if matches!(&id, ImdsId::ObjectId(_) | ImdsId::MsiResId(_)) {
+                    return Err(azure_core::Error::with_message_fn(
+                        azure_core::error::ErrorKind::Credential,
+                        || {
+                            "User-assigned object and resource IDs aren't supported for Azure ML. Use a client ID instead.".to_string()
+                        },
+                    ));
+                }
+                AzureMlManagedIdentityCredential::new(id, options.client_options, env)?
+            }
+            ManagedIdentitySource::CloudShell => {
+                if !matches!(&id, ImdsId::SystemAssigned) {
+                    return Err(azure_core::Error::with_message_fn(
+                        azure_core::error::ErrorKind::Credential,
+                        || {
+                            "User-assigned managed identities aren't supported for Cloud Shell."
+                                .to_string()
+                        },
+                    ));
+                }
+                CloudShellManagedIdentityCredential::new(options.client_options, env)?
+            }
+            ManagedIdentitySource::ServiceFabric => {
+                if !matches!(&id, ImdsId::SystemAssigned) {
+                    return Err(azure_core::Error::with_message_fn(
+                        azure_core::error::ErrorKind::Credential,
+                        || {
+                            "User-assigned managed identities can't be specified at runtime for Service Fabric. Configure the identity in the application manifest instead.".to_string()
+                        },
+                    ));
+                }
+                ServiceFabricManagedIdentityCredential::new(options.client_options, env)?
             }
         };
 
@@ -423,7 +443,11 @@ mod tests {
         }
     }
 
-    fn run_unsupported_source_test(env: Env, expected_source: ManagedIdentitySource) {
+    fn run_unsupported_id_test(
+        env: Env,
+        expected_source: ManagedIdentitySource,
+        user_assigned_id: UserAssignedId,
+    ) {
         let actual_source = get_source(&env);
         assert_eq!(
             std::mem::discriminant(&actual_source),
@@ -431,6 +455,7 @@ mod tests {
         );
         let result = ManagedIdentityCredential::new(Some(ManagedIdentityCredentialOptions {
             env,
+            user_assigned_id: Some(user_assigned_id),
             ..Default::default()
         }));
         assert!(
@@ -542,17 +567,13 @@ mod tests {
 
     #[tokio::test]
     async fn app_service_resource_id() {
-        let result = ManagedIdentityCredential::new(Some(ManagedIdentityCredentialOptions {
-            env: Env::from(&[(IDENTITY_ENDPOINT, "..."), (IDENTITY_HEADER, "x-id-header")][..]),
+        run_app_service_test(Some(ManagedIdentityCredentialOptions {
             user_assigned_id: Some(UserAssignedId::ResourceId(
                 "expected resource ID".to_string(),
             )),
             ..Default::default()
-        }));
-        assert!(
-            matches!(result, Err(ref e) if *e.kind() == azure_core::error::ErrorKind::Credential),
-            "Expected constructor error"
-        );
+        }))
+        .await;
     }
 
     #[tokio::test]
@@ -682,19 +703,98 @@ mod tests {
         let _ = fs::remove_file(token_path); // try our best to clean up the temp file
     }
 
+    async fn run_azure_ml_test(
+        options: Option<ManagedIdentityCredentialOptions>,
+        expected_client_id: &str,
+    ) {
+        let endpoint = "http://localhost:46808/MSI/auth";
+        let secret = "secret value";
+        let mut model_request = Request::new(endpoint.parse().unwrap(), Method::Get);
+        model_request.insert_header("secret", secret);
+        model_request.url_mut().query_pairs_mut().extend_pairs([
+            ("api-version", "2017-09-01"),
+            ("resource", LIVE_TEST_RESOURCE),
+            ("clientid", expected_client_id),
+        ]);
+        run_supported_source_test(
+            Env::from(
+                &[
+                    (MSI_ENDPOINT, endpoint),
+                    (MSI_SECRET, secret),
+                    ("DEFAULT_IDENTITY_CLIENT_ID", "default client ID"),
+                ][..],
+            ),
+            options,
+            ManagedIdentitySource::AzureML,
+            vec![MockRequestResponse {
+                request: model_request,
+                response_status: StatusCode::Ok,
+                response_headers: Headers::default(),
+                response_format: format!(
+                    r#"{{"access_token":"*","expires_on":"{}","resource":"{}","token_type":"Bearer"}}"#,
+                    EXPIRES_ON, LIVE_TEST_RESOURCE
+                ),
+            }],
+            None,
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn azure_ml() {
+        run_azure_ml_test(None, "default client ID").await;
+    }
+
+    #[tokio::test]
+    async fn azure_ml_client_id() {
+        run_azure_ml_test(
+            Some(ManagedIdentityCredentialOptions {
+                user_assigned_id: Some(UserAssignedId::ClientId("expected client ID".to_string())),
+                ..Default::default()
+            }),
+            "expected client ID",
+        )
+        .await;
+    }
+
     #[test]
-    fn azure_ml() {
-        run_unsupported_source_test(
-            Env::from(&[(MSI_ENDPOINT, "..."), (MSI_SECRET, "...")][..]),
+    fn azure_ml_resource_id() {
+        run_unsupported_id_test(
+            Env::from(&[(MSI_ENDPOINT, "http://localhost"), (MSI_SECRET, "...")][..]),
             ManagedIdentitySource::AzureML,
+            UserAssignedId::ResourceId("expected resource ID".to_string()),
         );
     }
 
+    #[tokio::test]
+    async fn cloudshell() {
+        let endpoint = "http://localhost:50342/oauth2/token";
+        let mut model_request = Request::new(endpoint.parse().unwrap(), Method::Post);
+        model_request.insert_header("metadata", "true");
+        run_supported_source_test(
+            Env::from(&[(MSI_ENDPOINT, endpoint)][..]),
+            None,
+            ManagedIdentitySource::CloudShell,
+            vec![MockRequestResponse {
+                request: model_request,
+                response_status: StatusCode::Ok,
+                response_headers: Headers::default(),
+                response_format: format!(
+                    r#"{{"access_token":"*","expires_on":"{}","resource":"{}","token_type":"Bearer"}}"#,
+                    EXPIRES_ON, LIVE_TEST_RESOURCE
+                ),
+            }],
+            None,
+        )
+        .await;
+    }
+
     #[test]
-    fn cloudshell() {
-        run_unsupported_source_test(
+    fn cloudshell_client_id() {
+        run_unsupported_id_test(
             Env::from(&[(MSI_ENDPOINT, "http://localhost")][..]),
             ManagedIdentitySource::CloudShell,
+            UserAssignedId::ClientId("expected client ID".to_string()),
         );
     }
 
@@ -820,17 +920,84 @@ mod tests {
         }
     }
 
+    fn service_fabric_env(thumbprint: &str) -> Env {
+        Env::from(
+            &[
+                (
+                    IDENTITY_ENDPOINT,
+                    "https://localhost:2377/metadata/identity/oauth2/token",
+                ),
+                (IDENTITY_HEADER, "secret value"),
+                (IDENTITY_SERVER_THUMBPRINT, thumbprint),
+            ][..],
+        )
+    }
+
+    #[tokio::test]
+    async fn service_fabric() {
+        let mut model_request = Request::new(
+            "https://localhost:2377/metadata/identity/oauth2/token"
+                .parse()
+                .unwrap(),
+            Method::Get,
+        );
+        model_request.insert_header("secret", "secret value");
+        model_request.url_mut().query_pairs_mut().extend_pairs([
+            ("api-version", "2019-07-01-preview"),
+            ("resource", LIVE_TEST_RESOURCE),
+        ]);
+        run_supported_source_test(
+            service_fabric_env("0123456789ABCDEF0123456789ABCDEF01234567"),
+            None,
+            ManagedIdentitySource::ServiceFabric,
+            vec![MockRequestResponse {
+                request: model_request,
+                response_status: StatusCode::Ok,
+                response_headers: Headers::default(),
+                // Service Fabric returns expires_on as a number
+                response_format: format!(
+                    r#"{{"access_token":"*","expires_on":{},"resource":"{}","token_type":"Bearer"}}"#,
+                    EXPIRES_ON, LIVE_TEST_RESOURCE
+                ),
+            }],
+            None,
+        )
+        .await;
+    }
+
     #[test]
-    fn service_fabric() {
-        run_unsupported_source_test(
-            Env::from(
-                &[
-                    (IDENTITY_ENDPOINT, "http://localhost"),
-                    (IDENTITY_HEADER, "..."),
-                    (IDENTITY_SERVER_THUMBPRINT, "..."),
-                ][..],
-            ),
+    fn service_fabric_client_id() {
+        run_unsupported_id_test(
+            service_fabric_env("0123456789ABCDEF0123456789ABCDEF01234567"),
             ManagedIdentitySource::ServiceFabric,
+            UserAssignedId::ClientId("expected client ID".to_string()),
+        );
+    }
+
+    #[test]
+    fn service_fabric_invalid_thumbprint() {
+        let result = ManagedIdentityCredential::new(Some(ManagedIdentityCredentialOptions {
+            env: service_fabric_env("not a thumbprint"),
+            ..Default::default()
+        }));
+        assert!(
+            matches!(result, Err(ref e) if *e.kind() == azure_core::error::ErrorKind::Credential),
+            "Expected constructor error"
+        );
+    }
+
+    #[test]
+    fn service_fabric_pinned_transport() {
+        let result = ManagedIdentityCredential::new(Some(ManagedIdentityCredentialOptions {
+            env: service_fabric_env("01:23:45:67:89:ab:cd:ef:01:23:45:67:89:ab:cd:ef:01:23:45:67"),
+            ..Default::default()
+        }));
+        #[cfg(feature = "service_fabric")]
+        result.expect("credential");
+        #[cfg(not(feature = "service_fabric"))]
+        assert!(
+            matches!(result, Err(ref e) if *e.kind() == azure_core::error::ErrorKind::Credential),
+            "Expected an error without the service_fabric feature"
         );
     }
 }
@@ -0,0 +1,500 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{
+    authentication_error, client_assertion_credential::ASSERTION_TYPE, get_authority_host,
+    validate_not_empty, validate_tenant_id, CachedToken, ClientAssertion, TokenCache,
+};
+#[cfg(feature = "client_certificate")]
+use crate::{client_certificate_credential::CertificateAssertion, env::Env};
+#[cfg(feature = "client_certificate")]
+use azure_core::credentials::SecretBytes;
+use azure_core::{
+    base64,
+    credentials::{AccessToken, Secret, TokenCredential, TokenRequestOptions},
+    error::{Error, ErrorKind, ResultExt},
+    http::{
+        headers::{self, content_type},
+        ClientOptions, Method, Pipeline, PipelineSendOptions, Request, Url,
+    },
+    Result,
+};
+use sha2::{Digest, Sha256};
+use std::{any::type_name, fmt, sync::Arc};
+use url::form_urlencoded;
+
+const ON_BEHALF_OF_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// Options for constructing a new [`OnBehalfOfCredential`].
+#[derive(Default)]
+pub struct OnBehalfOfCredentialOptions {
+    /// Options for the credential's HTTP pipeline.
+    pub client_options: ClientOptions,
+}
+
+impl fmt::Debug for OnBehalfOfCredentialOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>()).finish_non_exhaustive()
+    }
+}
+
+/// Authenticates a user with the [on-behalf-of flow](https://learn.microsoft.com/entra/identity-platform/v2-oauth2-on-behalf-of-flow),
+/// exchanging a token a service received from the user for tokens to call other services as that user.
+///
+/// Create a credential for each user assertion, then use [`OnBehalfOfCredential::with_user_assertion`] to create
+/// credentials for other users. Credentials created this way share an HTTP pipeline and a token cache, in which
+/// each user's tokens are keyed by a hash of their assertion.
+pub struct OnBehalfOfCredential {
+    client: Arc<OnBehalfOfClient>,
+    user_assertion: Secret,
+    user_assertion_hash: String,
+}
+
+impl fmt::Debug for OnBehalfOfCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("client_id", &self.client.client_id)
+            .field("endpoint", &self.client.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The application exchanging user assertions, shared by the credentials of its users.
+struct OnBehalfOfClient {
+    authentication: ClientAuthentication,
+    cache: TokenCache,
+    client_id: String,
+    endpoint: Url,
+    pipeline: Pipeline,
+}
+
+/// How the application authenticates when it exchanges a user assertion.
+enum ClientAuthentication {
+    Assertion(Arc<dyn ClientAssertion>),
+    Secret(Secret),
+}
+
+impl OnBehalfOfCredential {
+    /// Create a new `OnBehalfOfCredential` for an application that authenticates with a client secret.
+    ///
+    /// # Arguments
+    /// - `tenant_id`: The tenant (directory) ID of the application.
+    /// - `client_id`: The client (application) ID of the application.
+    /// - `secret`: The client secret of the application.
+    /// - `user_assertion`: The token the application received from the user.
+    /// - `options`: Options for configuring the credential. If `None`, the credential uses its default options.
+    ///
+    pub fn with_secret(
+        tenant_id: &str,
+        client_id: String,
+        secret: Secret,
+        user_assertion: Secret,
+        options: Option<OnBehalfOfCredentialOptions>,
+    ) -> Result<Arc<Self>> {
+        validate_not_empty(secret.secret(), "no secret specified")?;
+        let endpoint = token_endpoint(tenant_id, options.as_ref())?;
+        Self::new(
+            client_id,
+            endpoint,
+            ClientAuthentication::Secret(secret),
+            user_assertion,
+            options,
+        )
+    }
+
+    /// Create a new `OnBehalfOfCredential` for an application that authenticates with a certificate.
+    ///
+    /// # Arguments
+    /// - `tenant_id`: The tenant (directory) ID of the application.
+    /// - `client_id`: The client (application) ID of the application.
+    /// - `certificate`: The PKCS12 certificate bytes with its RSA private key.
+    /// - `password`: The password for the certificate, if it has one.
+    /// - `user_assertion`: The token the application received from the user.
+    /// - `options`: Options for configuring the credential. If `None`, the credential uses its default options.
+    ///
+    #[cfg(feature = "client_certificate")]
+    pub fn with_certificate(
+        tenant_id: &str,
+        client_id: String,
+        certificate: SecretBytes,
+        password: Option<Secret>,
+        user_assertion: Secret,
+        options: Option<OnBehalfOfCredentialOptions>,
+    ) -> Result<Arc<Self>> {
+        let endpoint = token_endpoint(tenant_id, options.as_ref())?;
+        let assertion = CertificateAssertion::new(
+            client_id.clone(),
+            endpoint.clone(),
+            &certificate,
+            password.as_ref(),
+            &Env::default(),
+        )?;
+        Self::new(
+            client_id,
+            endpoint,
+            ClientAuthentication::Assertion(Arc::new(assertion)),
+            user_assertion,
+            options,
+        )
+    }
+
+    /// Create a new `OnBehalfOfCredential` for an application that authenticates with client assertions.
+    ///
+    /// # Arguments
+    /// - `tenant_id`: The tenant (directory) ID of the application.
+    /// - `client_id`: The client (application) ID of the application.
+    /// - `assertion`: an implementation of [`ClientAssertion`] that provides assertions for the application.
+    /// - `user_assertion`: The token the application received from the user.
+    /// - `options`: Options for configuring the credential. If `None`, the credential uses its default options.
+    ///
+    pub fn with_client_assertion<C: ClientAssertion + 'static>(
+        tenant_id: &str,
+        client_id: String,
+        assertion: C,
+        user_assertion: Secret,
+        options: Option<OnBehalfOfCredentialOptions>,
+    ) -> Result<Arc<Self>> {
+        let endpoint = token_endpoint(tenant_id, options.as_ref())?;
+        Self::new(
+            client_id,
+            endpoint,
+            ClientAuthentication::Assertion(Arc::new(assertion)),
+            user_assertion,
+            options,
+        )
+    }
+
+    /// Create a credential for another user, sharing this credential's application authentication,
+    /// HTTP pipeline, and token cache.
+    ///
+    /// # Arguments
+    /// - `user_assertion`: The token the application received from the user.
+    ///
+    pub fn with_user_assertion(&self, user_assertion: Secret) -> Result<Arc<Self>> {
+        validate_not_empty(user_assertion.secret(), "no user assertion specified")?;
+        Ok(Arc::new(Self {
+            client: self.client.clone(),
+            user_assertion_hash: hash(&user_assertion),
+            user_assertion,
+        }))
+    }
+
+    fn new(
+        client_id: String,
+        endpoint: Url,
+        authentication: ClientAuthentication,
+        user_assertion: Secret,
+        options: Option<OnBehalfOfCredentialOptions>,
+    ) -> Result<Arc<Self>> {
+        validate_not_empty(&client_id, "no client ID specified")?;
+        validate_not_empty(user_assertion.secret(), "no user assertion specified")?;
+        let options = options.unwrap_or_default();
+        let pipeline = Pipeline::new(
+            option_env!("CARGO_PKG_NAME"),
+            option_env!("CARGO_PKG_VERSION"),
+            options.client_options,
+            Vec::default(),
+            Vec::default(),
+            None,
+        );
+
+        Ok(Arc::new(Self {
+            client: Arc::new(OnBehalfOfClient {
+                authentication,
+                cache: TokenCache::new(),
+                client_id,
+                endpoint,
+                pipeline,
+            }),
+            user_assertion_hash: hash(&user_assertion),
+            user_assertion,
+        }))
+    }
+
+    async fn get_token_impl(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<CachedToken> {
+        let options = options.unwrap_or_default();
+        let client_authentication = match &self.client.authentication {
+            ClientAuthentication::Assertion(assertion) => vec![
+                (
+                    "client_assertion",
+                    assertion
+                        .secret(Some(options.method_options.to_owned()))
+                        .await?,
+                ),
+                ("client_assertion_type", ASSERTION_TYPE.to_string()),
+            ],
+            ClientAuthentication::Secret(secret) => {
+                vec![("client_secret", secret.secret().to_string())]
+            }
+        };
+        let claims = crate::claims_parameter(Some(&options))?;
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("assertion", self.user_assertion.secret())
+            .append_pair("client_id", &self.client.client_id)
+            .append_pair("grant_type", ON_BEHALF_OF_GRANT_TYPE)
+            .append_pair("requested_token_use", "on_behalf_of")
+            .append_pair("scope", &scopes.join(" "))
+            .extend_pairs(client_authentication)
+            .extend_pairs(claims.as_deref().map(|claims| ("claims", claims)))
+            .finish();
+
+        let mut req = Request::new(self.client.endpoint.clone(), Method::Post);
+        req.insert_header(
+            headers::CONTENT_TYPE,
+            content_type::APPLICATION_X_WWW_FORM_URLENCODED,
+        );
+        req.set_body(body);
+
+        let ctx = options.method_options.context.to_borrowed();
+        let res = self
+            .client
+            .pipeline
+            .send(
+                &ctx,
+                &mut req,
+                Some(PipelineSendOptions {
+                    skip_checks: true,
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        crate::handle_entra_response(res)
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for OnBehalfOfCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> Result<AccessToken> {
+        if scopes.is_empty() {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "no scopes specified",
+            ));
+        }
+        self.client
+            .cache
+            .get_partitioned_token(&self.user_assertion_hash, scopes, options, |s, o| {
+                self.get_token_impl(s, o)
+            })
+            .await
+            .map_err(|err| authentication_error(stringify!(OnBehalfOfCredential), err))
+    }
+}
+
+fn token_endpoint(tenant_id: &str, options: Option<&OnBehalfOfCredentialOptions>) -> Result<Url> {
+    validate_tenant_id(tenant_id)?;
+    let cloud = options.and_then(|options| options.client_options.cloud.as_deref());
+    get_authority_host(None, cloud)?
+        .join(&format!("/{tenant_id}/oauth2/v2.0/token"))
+        .with_context_fn(ErrorKind::DataConversion, || {
+            format!("tenant_id '{tenant_id}' could not be URL encoded")
+        })
+}
+
+/// Hashes a user assertion to key the user's tokens in the cache without keeping the assertion there.
+fn hash(user_assertion: &Secret) -> String {
+    base64::encode_url_safe(Sha256::digest(user_assertion.secret().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+    use azure_core::{
+        http::{ClientMethodOptions, Transport},
+        Bytes,
+    };
+    use std::{collections::HashMap, sync::Mutex};
+
+    const FAKE_SECRET: &str = "fake secret";
+    const FAKE_USER_ASSERTION: &str = "fake user assertion";
+
+    type Requests = Arc<Mutex<Vec<HashMap<String, String>>>>;
+
+    fn mock_sts(responses: usize, requests: Requests) -> MockSts {
+        let expected_url = format!("{FAKE_PUBLIC_CLOUD_AUTHORITY}/oauth2/v2.0/token");
+        MockSts::new(
+            (0..responses).map(|_| token_response()).collect(),
+            Some(Arc::new(move |req: &Request| {
+                assert_eq!(Method::Post, req.method());
+                assert_eq!(expected_url, req.url().as_str());
+                let body: Bytes = req.body().into();
+                requests.lock().unwrap().push(
+                    form_urlencoded::parse(&body)
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                );
+                Ok(())
+            })),
+        )
+    }
+
+    fn options(sts: MockSts) -> Option<OnBehalfOfCredentialOptions> {
+        Some(OnBehalfOfCredentialOptions {
+            client_options: ClientOptions {
+                transport: Some(Transport::new(Arc::new(sts))),
+                ..Default::default()
+            },
+        })
+    }
+
+    #[derive(Debug)]
+    struct FakeAssertion;
+
+    #[async_trait::async_trait]
+    impl ClientAssertion for FakeAssertion {
+        async fn secret(&self, _: Option<ClientMethodOptions<'_>>) -> Result<String> {
+            Ok("fake client assertion".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn secret() {
+        let requests = Requests::default();
+        let credential = OnBehalfOfCredential::with_secret(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            FAKE_SECRET.into(),
+            FAKE_USER_ASSERTION.into(),
+            options(mock_sts(1, requests.clone())),
+        )
+        .expect("valid credential");
+
+        let token = credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+        assert_eq!(FAKE_TOKEN, token.token.secret());
+
+        let requests = requests.lock().unwrap();
+        let params = &requests[0];
+        assert_eq!(FAKE_USER_ASSERTION, params["assertion"]);
+        assert_eq!(FAKE_CLIENT_ID, params["client_id"]);
+        assert_eq!(FAKE_SECRET, params["client_secret"]);
+        assert_eq!(ON_BEHALF_OF_GRANT_TYPE, params["grant_type"]);
+        assert_eq!("on_behalf_of", params["requested_token_use"]);
+        assert_eq!(LIVE_TEST_SCOPES.join(" "), params["scope"]);
+        assert!(!params.contains_key("client_assertion"));
+    }
+
+    #[tokio::test]
+    async fn client_assertion() {
+        let requests = Requests::default();
+        let credential = OnBehalfOfCredential::with_client_assertion(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            FakeAssertion,
+            FAKE_USER_ASSERTION.into(),
+            options(mock_sts(1, requests.clone())),
+        )
+        .expect("valid credential");
+
+        credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+
+        let requests = requests.lock().unwrap();
+        let params = &requests[0];
+        assert_eq!("fake client assertion", params["client_assertion"]);
+        assert_eq!(ASSERTION_TYPE, params["client_assertion_type"]);
+        assert_eq!(FAKE_USER_ASSERTION, params["assertion"]);
+        assert!(!params.contains_key("client_secret"));
+    }
+
+    #[cfg(feature = "client_certificate")]
+    #[tokio::test]
+    async fn certificate() {
+        let requests = Requests::default();
+        let certificate = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/certificate.pfx"
+        ))
+        .expect("test certificate");
+        let credential = OnBehalfOfCredential::with_certificate(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            certificate.into(),
+            None,
+            FAKE_USER_ASSERTION.into(),
+            options(mock_sts(1, requests.clone())),
+        )
+        .expect("valid credential");
+
+        credential
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+
+        let requests = requests.lock().unwrap();
+        let params = &requests[0];
+        assert_eq!(3, params["client_assertion"].split('.').count());
+        assert_eq!(ASSERTION_TYPE, params["client_assertion_type"]);
+    }
+
+    #[tokio::test]
+    async fn cache_per_user_assertion() {
+        let requests = Requests::default();
+        let alice = OnBehalfOfCredential::with_secret(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            FAKE_SECRET.into(),
+            "alice".into(),
+            options(mock_sts(2, requests.clone())),
+        )
+        .expect("valid credential");
+        let bob = alice
+            .with_user_assertion("bob".into())
+            .expect("valid credential");
+
+        alice
+            .get_token(LIVE_TEST_SCOPES, None)
+            .await
+            .expect("token");
+        bob.get_token(LIVE_TEST_SCOPES, None).await.expect("token");
+        assert_eq!(2, requests.lock().unwrap().len());
+
+        // mock_sts will return an error if a credential sends another request
+        for credential in [
+            alice.clone(),
+            bob,
+            alice
+                .with_user_assertion("alice".into())
+                .expect("valid credential"),
+        ] {
+            credential
+                .get_token(LIVE_TEST_SCOPES, None)
+                .await
+                .expect("cached token");
+        }
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            vec!["alice", "bob"],
+            requests
+                .iter()
+                .map(|params| params["assertion"].as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn empty_user_assertion() {
+        let credential = OnBehalfOfCredential::with_secret(
+            FAKE_TENANT_ID,
+            FAKE_CLIENT_ID.to_string(),
+            FAKE_SECRET.into(),
+            "".into(),
+            None,
+        );
+        credential.expect_err("empty user assertion");
+    }
+}
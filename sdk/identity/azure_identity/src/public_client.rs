@@ -0,0 +1,167 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{get_authority_host, validate_not_empty, validate_tenant_id, CachedToken};
+use async_lock::Mutex;
+use azure_core::{
+    credentials::Secret,
+    error::{ErrorKind, ResultExt},
+    http::{
+        headers::{self, content_type},
+        ClientOptions, Context, Method, Pipeline, PipelineSendOptions, RawResponse, Request, Url,
+    },
+    Result,
+};
+use serde::Deserialize;
+use tracing::warn;
+use url::form_urlencoded;
+
+/// The client ID of the Azure developer sign-on application, which user credentials use by default.
+const DEVELOPER_SIGN_ON_CLIENT_ID: &str = "04b07795-8ddb-461a-bbee-02f9e1bf7b46";
+
+/// The tenant user credentials authenticate in by default, which allows work and school accounts of any tenant.
+const DEFAULT_TENANT_ID: &str = "organizations";
+
+/// The part of a token response that credentials for users keep to get tokens without prompting again.
+#[derive(Deserialize)]
+struct RefreshTokenResponse {
+    refresh_token: Option<Secret>,
+}
+
+/// A public client application, which authenticates users in a tenant and redeems their refresh tokens.
+#[derive(Debug)]
+pub(crate) struct PublicClient {
+    authority_host: Url,
+    client_id: String,
+    tenant_id: String,
+    pipeline: Pipeline,
+    refresh_token: Mutex<Option<Secret>>,
+}
+
+impl PublicClient {
+    pub fn new(
+        tenant_id: Option<String>,
+        client_id: Option<String>,
+        client_options: ClientOptions,
+    ) -> Result<Self> {
+        let tenant_id = tenant_id.unwrap_or_else(|| DEFAULT_TENANT_ID.to_string());
+        validate_tenant_id(&tenant_id)?;
+        let client_id = client_id.unwrap_or_else(|| DEVELOPER_SIGN_ON_CLIENT_ID.to_string());
+        validate_not_empty(&client_id, "no client ID specified")?;
+        let authority_host = get_authority_host(None, client_options.cloud.as_deref())?;
+
+        let pipeline = Pipeline::new(
+            option_env!("CARGO_PKG_NAME"),
+            option_env!("CARGO_PKG_VERSION"),
+            client_options,
+            Vec::default(),
+            Vec::default(),
+            None,
+        );
+
+        Ok(Self {
+            authority_host,
+            client_id,
+            tenant_id,
+            pipeline,
+            refresh_token: Mutex::new(None),
+        })
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    /// The part of a persistent token cache's keys that identifies this client.
+    pub fn cache_partition(&self) -> String {
+        format!(
+            "{}|{}|{}",
+            self.authority_host, self.tenant_id, self.client_id
+        )
+    }
+
+    /// Gets the URL of an OAuth 2.0 endpoint such as `token` or `authorize`.
+    pub fn endpoint(&self, name: &str) -> Result<Url> {
+        let tenant_id = &self.tenant_id;
+        self.authority_host
+            .join(&format!("/{tenant_id}/oauth2/v2.0/{name}"))
+            .with_context_fn(ErrorKind::DataConversion, || {
+                format!("tenant_id '{tenant_id}' could not be URL encoded")
+            })
+    }
+
+    /// Sends a form to an endpoint, returning the response whatever its status.
+    pub async fn send_form(
+        &self,
+        ctx: &Context<'_>,
+        url: Url,
+        body: String,
+    ) -> Result<RawResponse> {
+        let mut req = Request::new(url, Method::Post);
+        req.insert_header(
+            headers::CONTENT_TYPE,
+            content_type::APPLICATION_X_WWW_FORM_URLENCODED,
+        );
+        req.set_body(body);
+        self.pipeline
+            .send(
+                ctx,
+                &mut req,
+                Some(PipelineSendOptions {
+                    skip_checks: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+    }
+
+    /// Gets the token from a token endpoint's response, keeping its refresh token for [`PublicClient::refresh`].
+    pub async fn redeem(&self, response: RawResponse) -> Result<CachedToken> {
+        if response.status().is_success() {
+            let refresh_token: RefreshTokenResponse = crate::deserialize(&response)?;
+            if let Some(refresh_token) = refresh_token.refresh_token {
+                *self.refresh_token.lock().await = Some(refresh_token);
+            }
+        }
+        crate::handle_entra_response(response)
+    }
+
+    /// Gets a token with the refresh token from an earlier authentication, if there is one.
+    ///
+    /// Returns `None` when there's no refresh token or Entra ID rejects it, in which case the user
+    /// must authenticate again. `claims` is the value of the request's `claims` parameter, if any.
+    pub async fn refresh(
+        &self,
+        ctx: &Context<'_>,
+        scopes: &[&str],
+        claims: Option<&str>,
+    ) -> Option<CachedToken> {
+        let refresh_token = self.refresh_token.lock().await.clone()?;
+        let body = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id)
+            .append_pair("grant_type", "refresh_token")
+            .append_pair("refresh_token", refresh_token.secret())
+            .append_pair("scope", &scope(scopes))
+            .extend_pairs(claims.map(|claims| ("claims", claims)))
+            .finish();
+        let result = async {
+            let response = self.send_form(ctx, self.endpoint("token")?, body).await?;
+            self.redeem(response).await
+        };
+        match result.await {
+            Ok(token) => Some(token),
+            Err(err) => {
+                warn!("failed to redeem refresh token: {err}");
+                *self.refresh_token.lock().await = None;
+                None
+            }
+        }
+    }
+}
+
+/// Formats scopes for a user authentication request, which also requests a refresh token.
+pub(crate) fn scope(scopes: &[&str]) -> String {
+    let mut scope = scopes.join(" ");
+    scope.push_str(" offline_access");
+    scope
+}
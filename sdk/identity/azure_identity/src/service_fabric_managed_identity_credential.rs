@@ -0,0 +1,259 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::env::Env;
+use crate::{ImdsId, ImdsManagedIdentityCredential};
+use azure_core::credentials::{AccessToken, TokenCredential, TokenRequestOptions};
+use azure_core::error::{ErrorKind, ResultExt};
+use azure_core::http::headers::HeaderName;
+use azure_core::http::ClientOptions;
+use azure_core::http::Url;
+use std::{any::type_name, fmt, str, sync::Arc};
+use tracing::warn;
+
+const ENDPOINT_ENV: &str = "IDENTITY_ENDPOINT";
+const API_VERSION: &str = "2019-07-01-preview";
+const SECRET_HEADER: HeaderName = HeaderName::from_static("secret");
+const SECRET_ENV: &str = "IDENTITY_HEADER";
+const THUMBPRINT_ENV: &str = "IDENTITY_SERVER_THUMBPRINT";
+
+/// Authenticates the managed identity of a Service Fabric application.
+///
+/// The Service Fabric managed identity endpoint uses a self-signed certificate, so instead of validating the certificate
+/// chain, the credential requires the server certificate to have the thumbprint in `IDENTITY_SERVER_THUMBPRINT`.
+/// This requires the `service_fabric` feature. When [`ClientOptions::transport`] is set, the credential uses that
+/// transport as is, and it must validate the server.
+pub(crate) struct ServiceFabricManagedIdentityCredential {
+    credential: ImdsManagedIdentityCredential,
+}
+
+impl fmt::Debug for ServiceFabricManagedIdentityCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>()).finish_non_exhaustive()
+    }
+}
+
+impl ServiceFabricManagedIdentityCredential {
+    pub fn new(client_options: ClientOptions, env: Env) -> azure_core::Result<Arc<Self>> {
+        let endpoint = &env
+            .var(ENDPOINT_ENV)
+            .with_context_fn(ErrorKind::Credential, || {
+                format!(
+                    "Service Fabric credential requires {} environment variable",
+                    ENDPOINT_ENV
+                )
+            })?;
+        let endpoint = Url::parse(endpoint).with_context_fn(ErrorKind::Credential, || {
+            format!(
+                "Service Fabric credential {} environment variable must be a valid URL, but is '{endpoint}'",
+                ENDPOINT_ENV
+            )
+        })?;
+        let thumbprint = env
+            .var(THUMBPRINT_ENV)
+            .with_context_fn(ErrorKind::Credential, || {
+                format!(
+                    "Service Fabric credential requires {} environment variable",
+                    THUMBPRINT_ENV
+                )
+            })?;
+        let client_options = match client_options.transport {
+            Some(_) => {
+                warn!(
+                    "Service Fabric managed identity uses the transport in ClientOptions, which must validate the server certificate against {}",
+                    THUMBPRINT_ENV
+                );
+                client_options
+            }
+            None => ClientOptions {
+                transport: Some(pinned::transport(&thumbprint)?),
+                ..client_options
+            },
+        };
+        Ok(Arc::new(Self {
+            credential: ImdsManagedIdentityCredential::new(
+                endpoint,
+                API_VERSION,
+                SECRET_HEADER,
+                SECRET_ENV,
+                // Service Fabric authenticates the identity configured for the application; it can't be chosen at runtime
+                ImdsId::SystemAssigned,
+                client_options,
+                None,
+                env,
+            ),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenCredential for ServiceFabricManagedIdentityCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        self.credential.get_token(scopes, options).await
+    }
+}
+
+/// Parses a certificate thumbprint, the hex-encoded SHA-1 hash of the certificate, ignoring case and colons.
+fn parse_thumbprint(thumbprint: &str) -> azure_core::Result<Vec<u8>> {
+    let hex: Vec<u8> = thumbprint.bytes().filter(|b| *b != b':').collect();
+    let invalid = || {
+        azure_core::Error::with_message_fn(ErrorKind::Credential, || {
+            format!(
+                "{THUMBPRINT_ENV} must be a hex-encoded SHA-1 thumbprint, but is '{thumbprint}'"
+            )
+        })
+    };
+    if hex.len() != 40 {
+        return Err(invalid());
+    }
+    hex.chunks(2)
+        .map(|pair| {
+            str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+#[cfg(feature = "service_fabric")]
+mod pinned {
+    use super::parse_thumbprint;
+    use azure_core::{
+        error::{ErrorKind, ResultExt},
+        http::Transport,
+    };
+    use rustls::{
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+        pki_types::{CertificateDer, ServerName, UnixTime},
+        DigitallySignedStruct, SignatureScheme,
+    };
+    use sha1::{Digest, Sha1};
+    use std::{sync::Arc, time::Duration};
+
+    /// Creates a transport that trusts only a server certificate with the given thumbprint.
+    pub(super) fn transport(thumbprint: &str) -> azure_core::Result<Transport> {
+        // use the process's crypto provider if the application installed one, else the one reqwest uses
+        let provider = CryptoProvider::get_default()
+            .cloned()
+            .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+        let verifier = ThumbprintVerifier {
+            thumbprint: parse_thumbprint(thumbprint)?,
+            provider: provider.clone(),
+        };
+        let tls = rustls::ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .with_context(ErrorKind::Credential, "failed to configure TLS")?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        let client = reqwest::ClientBuilder::new()
+            .tls_backend_preconfigured(tls)
+            .connect_timeout(Duration::from_secs(20))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .with_context(ErrorKind::Credential, "failed to create HTTP client")?;
+        Ok(Transport::new(Arc::new(client)))
+    }
+
+    #[derive(Debug)]
+    struct ThumbprintVerifier {
+        thumbprint: Vec<u8>,
+        provider: Arc<CryptoProvider>,
+    }
+
+    impl ServerCertVerifier for ThumbprintVerifier {
+        fn verify_server_cert(
+            &self,
+            end_entity: &CertificateDer<'_>,
+            _intermediates: &[CertificateDer<'_>],
+            _server_name: &ServerName<'_>,
+            _ocsp_response: &[u8],
+            _now: UnixTime,
+        ) -> Result<ServerCertVerified, rustls::Error> {
+            if Sha1::digest(end_entity.as_ref()).as_slice() == self.thumbprint.as_slice() {
+                Ok(ServerCertVerified::assertion())
+            } else {
+                Err(rustls::Error::General(
+                    "the Service Fabric server certificate doesn't match IDENTITY_SERVER_THUMBPRINT"
+                        .to_string(),
+                ))
+            }
+        }
+
+        fn verify_tls12_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls12_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn verify_tls13_signature(
+            &self,
+            message: &[u8],
+            cert: &CertificateDer<'_>,
+            dss: &DigitallySignedStruct,
+        ) -> Result<HandshakeSignatureValid, rustls::Error> {
+            verify_tls13_signature(
+                message,
+                cert,
+                dss,
+                &self.provider.signature_verification_algorithms,
+            )
+        }
+
+        fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+            self.provider
+                .signature_verification_algorithms
+                .supported_schemes()
+        }
+    }
+}
+
+#[cfg(not(feature = "service_fabric"))]
+mod pinned {
+    use super::parse_thumbprint;
+    use azure_core::{error::ErrorKind, http::Transport};
+
+    pub(super) fn transport(thumbprint: &str) -> azure_core::Result<Transport> {
+        parse_thumbprint(thumbprint)?;
+        Err(azure_core::Error::with_message(
+            ErrorKind::Credential,
+            "Service Fabric managed identity requires the `service_fabric` feature, or a transport in ClientOptions that validates the server certificate",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_thumbprints() {
+        let expected: Vec<u8> = (0..20).collect();
+        assert_eq!(
+            parse_thumbprint("000102030405060708090A0B0C0D0E0F10111213").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_thumbprint("00:01:02:03:04:05:06:07:08:09:0a:0b:0c:0d:0e:0f:10:11:12:13")
+                .unwrap(),
+            expected
+        );
+        for invalid in ["", "0001", "zz0102030405060708090A0B0C0D0E0F10111213"] {
+            parse_thumbprint(invalid).expect_err(invalid);
+        }
+    }
+}
@@ -0,0 +1,535 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{cache::CachedToken, env::Env};
+use azure_core::{
+    credentials::{AccessToken, Secret},
+    error::{Error, ErrorKind, ResultExt},
+    time::OffsetDateTime,
+    Uuid,
+};
+use futures::channel::oneshot;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::type_name,
+    collections::HashMap,
+    fmt,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread, time,
+};
+use tracing::warn;
+
+const DEFAULT_CACHE_NAME: &str = "azure_identity";
+const CACHE_DIRECTORY: &str = ".IdentityService";
+const LOCK_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+/// Encrypts and decrypts a persistent token cache.
+///
+/// Implement this trait to protect cached tokens at rest, for example with a key stored in the
+/// operating system's keyring.
+pub trait TokenCacheEncryption: Send + Sync {
+    /// Encrypts the serialized cache before it's written.
+    fn encrypt(&self, plaintext: &[u8]) -> azure_core::Result<Vec<u8>>;
+
+    /// Decrypts the serialized cache after it's read.
+    fn decrypt(&self, ciphertext: &[u8]) -> azure_core::Result<Vec<u8>>;
+}
+
+/// Options for persisting a credential's tokens, so other processes can use them.
+///
+/// Credentials that share a persistent cache share tokens for the same tenant, client, and scopes.
+#[derive(Clone)]
+pub struct TokenCachePersistenceOptions {
+    /// The name of the cache, which separates it from the caches of other applications.
+    ///
+    /// Defaults to `azure_identity`.
+    pub name: String,
+
+    /// The directory of the cache.
+    ///
+    /// Defaults to `.IdentityService` in the user's local application data directory on Windows
+    /// and in the home directory on other platforms.
+    pub directory: Option<PathBuf>,
+
+    /// Encrypts the cache at rest.
+    pub encryption: Option<Arc<dyn TokenCacheEncryption>>,
+
+    /// Whether to store tokens in plain text when `encryption` is `None`.
+    ///
+    /// Defaults to `false`, in which case creating a credential without `encryption` fails.
+    pub allow_unencrypted_storage: bool,
+}
+
+impl Default for TokenCachePersistenceOptions {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_CACHE_NAME.to_string(),
+            directory: None,
+            encryption: None,
+            allow_unencrypted_storage: false,
+        }
+    }
+}
+
+impl fmt::Debug for TokenCachePersistenceOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("name", &self.name)
+            .field("directory", &self.directory)
+            .field("encrypted", &self.encryption.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A token cache in a file, shared by processes through a lock file.
+///
+/// File operations run on a separate thread, so a cache locked by another process doesn't block
+/// the async runtime.
+#[derive(Clone)]
+#[cfg_attr(not(feature = "token_cache_persistence"), allow(dead_code))]
+pub(crate) struct PersistentTokenCache {
+    path: PathBuf,
+    lock_path: PathBuf,
+    lock_timeout: time::Duration,
+    encryption: Option<Arc<dyn TokenCacheEncryption>>,
+}
+
+impl fmt::Debug for PersistentTokenCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct(type_name::<Self>())
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct CacheContents {
+    tokens: HashMap<String, PersistedToken>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct PersistedToken {
+    secret: String,
+    expires_on: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    refresh_on: Option<i64>,
+}
+
+impl PersistentTokenCache {
+    pub fn new(options: TokenCachePersistenceOptions, env: &Env) -> azure_core::Result<Self> {
+        if cfg!(not(feature = "token_cache_persistence")) {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "persistent token caching requires the `token_cache_persistence` feature",
+            ));
+        }
+        if options.encryption.is_none() && !options.allow_unencrypted_storage {
+            return Err(Error::with_message(
+                ErrorKind::Credential,
+                "persistent token cache requires encryption or allow_unencrypted_storage",
+            ));
+        }
+        if options.name.is_empty()
+            || !options
+                .name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '.' || c == '-' || c == '_')
+        {
+            return Err(Error::with_message_fn(ErrorKind::Credential, || {
+                format!("invalid token cache name '{}'", options.name)
+            }));
+        }
+        let directory = match options.directory {
+            Some(directory) => directory,
+            None => default_directory(env)?,
+        };
+        Ok(Self {
+            path: directory.join(format!("{}.cache", options.name)),
+            lock_path: directory.join(format!("{}.lock", options.name)),
+            lock_timeout: LOCK_TIMEOUT,
+            encryption: options.encryption,
+        })
+    }
+
+    /// Gets the token stored under a key, if it hasn't expired.
+    pub async fn get(&self, key: &str) -> azure_core::Result<Option<CachedToken>> {
+        let cache = self.clone();
+        let key = key.to_string();
+        run_blocking(move || {
+            cache.locked(false, || {
+                let now = OffsetDateTime::now_utc();
+                Ok(cache
+                    .read()?
+                    .tokens
+                    .remove(&key)
+                    .and_then(|token| token.into_cached_token())
+                    .filter(|token| token.token.expires_on > now))
+            })
+        })
+        .await
+    }
+
+    /// Stores a token under a key, removing expired tokens.
+    pub async fn set(&self, key: &str, token: &CachedToken) -> azure_core::Result<()> {
+        let cache = self.clone();
+        let key = key.to_string();
+        let token = PersistedToken {
+            secret: token.token.token.secret().to_string(),
+            expires_on: token.token.expires_on.unix_timestamp(),
+            refresh_on: token.refresh_on.map(OffsetDateTime::unix_timestamp),
+        };
+        run_blocking(move || {
+            cache.locked(true, || {
+                let now = OffsetDateTime::now_utc().unix_timestamp();
+                let mut contents = cache.read()?;
+                contents.tokens.retain(|_, token| token.expires_on > now);
+                contents.tokens.insert(key, token);
+                cache.write(&contents)
+            })
+        })
+        .await
+    }
+
+    /// Reads the cache. A cache that can't be decrypted or parsed is treated as empty, so it's
+    /// replaced on the next write.
+    fn read(&self) -> azure_core::Result<CacheContents> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CacheContents::default()),
+            Err(e) => return Err(io_error(e, "read", &self.path)),
+        };
+        let data = match &self.encryption {
+            Some(encryption) => match encryption.decrypt(&data) {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!(
+                        "ignoring token cache {} that couldn't be decrypted: {e}",
+                        self.path.display()
+                    );
+                    return Ok(CacheContents::default());
+                }
+            },
+            None => data,
+        };
+        Ok(serde_json::from_slice(&data).unwrap_or_else(|e| {
+            warn!(
+                "ignoring token cache {} that couldn't be parsed: {e}",
+                self.path.display()
+            );
+            CacheContents::default()
+        }))
+    }
+
+    /// Writes the cache to a temporary file and renames it, so readers never see a partial write.
+    fn write(&self, contents: &CacheContents) -> azure_core::Result<()> {
+        let data = serde_json::to_vec(contents)?;
+        let data = match &self.encryption {
+            Some(encryption) => encryption.encrypt(&data)?,
+            None => data,
+        };
+
+        let directory = self.path.parent().unwrap_or(Path::new("."));
+        let mut temporary_name = self.path.file_name().unwrap_or_default().to_os_string();
+        temporary_name.push(format!(".{}.tmp", Uuid::new_v4()));
+        let temporary_path = directory.join(temporary_name);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            // only the user can read the cache
+            options.mode(0o600);
+        }
+        let result = options
+            .open(&temporary_path)
+            .and_then(|mut file| {
+                file.write_all(&data)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temporary_path, &self.path));
+        if let Err(e) = result {
+            let _ = fs::remove_file(&temporary_path);
+            return Err(io_error(e, "write", &self.path));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "token_cache_persistence")]
+mod file_lock {
+    use super::{io_error, PersistentTokenCache};
+    use azure_core::error::{Error, ErrorKind};
+    use fd_lock::RwLock;
+    use std::{
+        fs::{self, File, OpenOptions},
+        io, thread,
+        time::{Duration, Instant},
+    };
+    use tracing::trace;
+
+    const LOCK_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+    impl PersistentTokenCache {
+        /// Runs `f` while holding the lock file, shared with other readers unless `exclusive`.
+        pub(super) fn locked<T>(
+            &self,
+            exclusive: bool,
+            f: impl FnOnce() -> azure_core::Result<T>,
+        ) -> azure_core::Result<T> {
+            let mut lock = RwLock::new(self.open_lock_file()?);
+            let deadline = Instant::now() + self.lock_timeout;
+            if exclusive {
+                let _guard = loop {
+                    match lock.try_write() {
+                        Ok(guard) => break guard,
+                        Err(e) => self.wait_for_lock(e, deadline)?,
+                    }
+                };
+                f()
+            } else {
+                let _guard = loop {
+                    match lock.try_read() {
+                        Ok(guard) => break guard,
+                        Err(e) => self.wait_for_lock(e, deadline)?,
+                    }
+                };
+                f()
+            }
+        }
+
+        /// Waits to retry locking the cache after another process held the lock, until `deadline`.
+        fn wait_for_lock(&self, error: io::Error, deadline: Instant) -> azure_core::Result<()> {
+            if error.kind() != io::ErrorKind::WouldBlock {
+                return Err(io_error(error, "lock", &self.lock_path));
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::with_message_fn(ErrorKind::Io, || {
+                    format!(
+                        "timed out after {:?} waiting for another process to release token cache lock file {}",
+                        self.lock_timeout,
+                        self.lock_path.display()
+                    )
+                }));
+            }
+            trace!("token cache is locked by another process, waiting");
+            thread::sleep(LOCK_RETRY_DELAY);
+            Ok(())
+        }
+
+        pub(super) fn open_lock_file(&self) -> azure_core::Result<File> {
+            if let Some(directory) = self.lock_path.parent() {
+                fs::create_dir_all(directory).map_err(|e| io_error(e, "create", directory))?;
+            }
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&self.lock_path)
+                .map_err(|e| io_error(e, "open", &self.lock_path))
+        }
+    }
+}
+
+#[cfg(not(feature = "token_cache_persistence"))]
+mod file_lock {
+    use super::PersistentTokenCache;
+
+    impl PersistentTokenCache {
+        /// A cache is never created without the `token_cache_persistence` feature.
+        pub(super) fn locked<T>(
+            &self,
+            _exclusive: bool,
+            _f: impl FnOnce() -> azure_core::Result<T>,
+        ) -> azure_core::Result<T> {
+            unreachable!("persistent token caching requires the `token_cache_persistence` feature")
+        }
+    }
+}
+
+impl PersistedToken {
+    fn into_cached_token(self) -> Option<CachedToken> {
+        Some(CachedToken {
+            token: AccessToken::new(
+                Secret::new(self.secret),
+                OffsetDateTime::from_unix_timestamp(self.expires_on).ok()?,
+            ),
+            refresh_on: match self.refresh_on {
+                Some(refresh_on) => Some(OffsetDateTime::from_unix_timestamp(refresh_on).ok()?),
+                None => None,
+            },
+        })
+    }
+}
+
+/// Runs blocking file operations on a separate thread.
+async fn run_blocking<T, F>(f: F) -> azure_core::Result<T>
+where
+    F: FnOnce() -> azure_core::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    thread::spawn(move || tx.send(f()));
+    rx.await
+        .map_err(|e| Error::with_error(ErrorKind::Io, e, "token cache operation was cancelled"))?
+}
+
+fn default_directory(env: &Env) -> azure_core::Result<PathBuf> {
+    #[cfg(windows)]
+    let base = env.var("LOCALAPPDATA");
+    #[cfg(not(windows))]
+    let base = env.var("HOME");
+    let base = base.with_context(
+        ErrorKind::Credential,
+        "couldn't find the default token cache directory; specify a directory instead",
+    )?;
+    Ok(Path::new(&base).join(CACHE_DIRECTORY))
+}
+
+fn io_error(error: io::Error, operation: &str, path: &Path) -> Error {
+    Error::with_error(
+        ErrorKind::Io,
+        error,
+        format!("failed to {operation} token cache file {}", path.display()),
+    )
+}
+
+#[cfg(all(test, feature = "token_cache_persistence"))]
+mod tests {
+    use super::*;
+    use azure_core::time::Duration;
+    use fd_lock::RwLock as FileLock;
+
+    /// Reverses the bytes, to check that the cache goes through the encryption.
+    struct ReverseEncryption;
+
+    impl TokenCacheEncryption for ReverseEncryption {
+        fn encrypt(&self, plaintext: &[u8]) -> azure_core::Result<Vec<u8>> {
+            Ok(plaintext.iter().rev().copied().collect())
+        }
+
+        fn decrypt(&self, ciphertext: &[u8]) -> azure_core::Result<Vec<u8>> {
+            Ok(ciphertext.iter().rev().copied().collect())
+        }
+    }
+
+    fn cache(
+        directory: &Path,
+        encryption: Option<Arc<dyn TokenCacheEncryption>>,
+    ) -> PersistentTokenCache {
+        PersistentTokenCache::new(
+            TokenCachePersistenceOptions {
+                directory: Some(directory.to_path_buf()),
+                allow_unencrypted_storage: encryption.is_none(),
+                encryption,
+                ..Default::default()
+            },
+            &Env::from(&[][..]),
+        )
+        .expect("cache")
+    }
+
+    fn token(secret: &str, expires_in: Duration) -> CachedToken {
+        let now = OffsetDateTime::now_utc().replace_nanosecond(0).unwrap();
+        CachedToken {
+            token: AccessToken::new(Secret::new(secret.to_string()), now + expires_in),
+            refresh_on: Some(now + expires_in / 2),
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trip_encrypted() -> azure_core::Result<()> {
+        let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let cache = cache(&directory, Some(Arc::new(ReverseEncryption)));
+        assert!(cache.get("key").await?.is_none());
+
+        let expected = token("secret", Duration::hours(1));
+        cache.set("key", &expected).await?;
+        cache
+            .set("expired", &token("expired", Duration::seconds(-1)))
+            .await?;
+
+        // another instance, as in another process, reads the same cache
+        let actual = super::tests::cache(&directory, Some(Arc::new(ReverseEncryption)))
+            .get("key")
+            .await?
+            .expect("cached token");
+        assert_eq!(actual.token.token.secret(), "secret");
+        assert_eq!(actual.token.expires_on, expected.token.expires_on);
+        assert_eq!(actual.refresh_on, expected.refresh_on);
+        assert!(cache.get("expired").await?.is_none());
+
+        let data = fs::read(directory.join("azure_identity.cache")).unwrap();
+        assert!(!String::from_utf8_lossy(&data).contains("\"secret\""));
+
+        // a cache that can't be decrypted is treated as empty
+        let unencrypted = super::tests::cache(&directory, None);
+        assert!(unencrypted.get("key").await?.is_none());
+        unencrypted.set("other", &expected).await?;
+        assert!(unencrypted.get("other").await?.is_some());
+
+        let _ = fs::remove_dir_all(directory);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn lock_timeout() -> azure_core::Result<()> {
+        let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let mut cache = cache(&directory, None);
+        cache.lock_timeout = time::Duration::from_millis(200);
+
+        // another process holds the lock
+        let mut lock = FileLock::new(cache.open_lock_file()?);
+        let guard = lock.write().expect("lock");
+
+        let error = cache.get("key").await.expect_err("locked cache");
+        assert_eq!(error.kind(), &ErrorKind::Io);
+        assert!(error.to_string().contains("timed out"));
+        cache
+            .set("key", &token("secret", Duration::hours(1)))
+            .await
+            .expect_err("locked cache");
+
+        drop(guard);
+        cache
+            .set("key", &token("secret", Duration::hours(1)))
+            .await?;
+        assert!(cache.get("key").await?.is_some());
+
+        let _ = fs::remove_dir_all(directory);
+        Ok(())
+    }
+
+    #[test]
+    fn requires_encryption_or_opt_in() {
+        let env = Env::from(&[("HOME", "/home/user"), ("LOCALAPPDATA", "C:\\Users\\user")][..]);
+        PersistentTokenCache::new(TokenCachePersistenceOptions::default(), &env)
+            .expect_err("no encryption");
+        let cache = PersistentTokenCache::new(
+            TokenCachePersistenceOptions {
+                allow_unencrypted_storage: true,
+                ..Default::default()
+            },
+            &env,
+        )
+        .expect("cache");
+        assert!(cache
+            .path
+            .ends_with(Path::new(".IdentityService").join("azure_identity.cache")));
+
+        for name in ["", "../escape", "a/b"] {
+            PersistentTokenCache::new(
+                TokenCachePersistenceOptions {
+                    name: name.to_string(),
+                    allow_unencrypted_storage: true,
+                    ..Default::default()
+                },
+                &env,
+            )
+            .expect_err(name);
+        }
+    }
+}
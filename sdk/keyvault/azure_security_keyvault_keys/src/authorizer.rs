@@ -90,16 +90,10 @@ impl OnRequest for KeyVaultAuthorizer {
             }
             Ok(())
         } else {
+            let mut options = TokenRequestOptions::default();
+            options.method_options.context = ctx.to_owned();
             authorizer
-                .authorize(
-                    request,
-                    &[scope.as_str()],
-                    TokenRequestOptions {
-                        method_options: azure_core::http::ClientMethodOptions {
-                            context: ctx.to_owned(),
-                        },
-                    },
-                )
+                .authorize(request, &[scope.as_str()], options)
                 .await
         }
     }
@@ -164,11 +158,8 @@ impl OnChallenge for KeyVaultAuthorizer {
             );
             request.insert_header(CONTENT_TYPE, "application/json");
         }
-        let options = TokenRequestOptions {
-            method_options: azure_core::http::ClientMethodOptions {
-                context: context.to_owned(),
-            },
-        };
+        let mut options = TokenRequestOptions::default();
+        options.method_options.context = context.to_owned();
         authorizer
             .authorize(request, &[scope.as_str()], options)
             .await
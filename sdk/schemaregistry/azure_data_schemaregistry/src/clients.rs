@@ -0,0 +1,614 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Clients used to communicate with the service.
+
+use crate::models::{
+    Schema, SchemaFormat, SchemaProperties, SchemaRegistryClientGetSchemaByVersionOptions,
+    SchemaRegistryClientGetSchemaOptions, SchemaRegistryClientGetSchemaPropertiesOptions,
+    SchemaRegistryClientRegisterSchemaOptions,
+};
+use azure_core::{
+    credentials::TokenCredential,
+    error::{CheckSuccessOptions, ErrorKind},
+    fmt::SafeDebug,
+    http::{
+        headers::{ACCEPT, CONTENT_TYPE},
+        policies::{auth::BearerTokenAuthorizationPolicy, Policy},
+        ClientOptions, Context, Method, Pipeline, PipelineSendOptions, RawResponse, Request, Url,
+    },
+    tracing, Error, Result,
+};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{Arc, Mutex},
+};
+
+/// The default API version of the Schema Registry service.
+pub const DEFAULT_API_VERSION: &str = "2023-07-01";
+
+/// The scope of the tokens that authorize requests to Schema Registry.
+const SCHEMA_REGISTRY_SCOPE: &str = "https://eventhubs.azure.net/.default";
+
+/// The content types of the schema formats, for the `Accept` header of the requests that get a
+/// schema.
+const ACCEPT_SCHEMA: &str =
+    "application/json; serialization=Avro, application/json; serialization=Json, text/plain; charset=utf-8";
+
+/// The number of entries each cache of the client holds before it is cleared.
+const MAX_CACHE_ENTRIES: usize = 128;
+
+/// Options used when creating a [`SchemaRegistryClient`].
+#[derive(Clone, SafeDebug)]
+pub struct SchemaRegistryClientOptions {
+    /// The API version to use for this operation.
+    pub api_version: String,
+    /// Allows customization of the client.
+    pub client_options: ClientOptions,
+}
+
+impl Default for SchemaRegistryClientOptions {
+    fn default() -> Self {
+        Self {
+            api_version: String::from(DEFAULT_API_VERSION),
+            client_options: ClientOptions::default(),
+        }
+    }
+}
+
+/// A cache of immutable service responses.
+///
+/// Schemas never change once registered, so entries never go stale. The cache is cleared when
+/// it is full, which keeps it bounded without tracking how recently entries were used.
+struct Cache<K, V>(Mutex<HashMap<K, V>>);
+
+impl<K: Eq + Hash, V: Clone> Cache<K, V> {
+    fn new() -> Self {
+        Self(Mutex::new(HashMap::new()))
+    }
+
+    fn get(&self, key: &K) -> Option<V> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: K, value: V) {
+        let mut entries = self.0.lock().unwrap();
+        if entries.len() >= MAX_CACHE_ENTRIES && !entries.contains_key(&key) {
+            entries.clear();
+        }
+        entries.insert(key, value);
+    }
+}
+
+/// The key of a schema definition in a group.
+type DefinitionKey = (String, String, SchemaFormat, String);
+
+/// A client for the Schema Registry of an Event Hubs namespace.
+///
+/// The client caches the schemas and schema properties it gets from the service, so that
+/// encoders that look up the same schema for every message send a request only the first time.
+#[tracing::client]
+pub struct SchemaRegistryClient {
+    pub(crate) api_version: String,
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    schemas_by_id: Cache<String, Schema>,
+    schemas_by_version: Cache<(String, String, i32), Schema>,
+    properties_by_definition: Cache<DefinitionKey, SchemaProperties>,
+}
+
+impl SchemaRegistryClient {
+    /// Creates a new `SchemaRegistryClient`, using Entra ID authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `fully_qualified_namespace` - The fully qualified name of the Event Hubs namespace, such as
+    ///   `my-namespace.servicebus.windows.net`, or its `https` endpoint.
+    /// * `credential` - An implementation of [`TokenCredential`](azure_core::credentials::TokenCredential) that can provide an
+    ///   Entra ID token to use when authenticating.
+    /// * `options` - Optional configuration for the client.
+    #[tracing::new("Microsoft.EventHub")]
+    pub fn new(
+        fully_qualified_namespace: &str,
+        credential: Arc<dyn TokenCredential>,
+        options: Option<SchemaRegistryClientOptions>,
+    ) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        let endpoint = if fully_qualified_namespace.contains("://") {
+            Url::parse(fully_qualified_namespace)?
+        } else {
+            Url::parse(&format!("https://{fully_qualified_namespace}"))?
+        };
+        if !endpoint.scheme().starts_with("http") {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                format!("{endpoint} must use http(s)"),
+            ));
+        }
+        let auth_policy: Arc<dyn Policy> = Arc::new(BearerTokenAuthorizationPolicy::new(
+            credential,
+            vec![SCHEMA_REGISTRY_SCOPE],
+        ));
+        Ok(Self {
+            endpoint,
+            api_version: options.api_version,
+            pipeline: Pipeline::new(
+                option_env!("CARGO_PKG_NAME"),
+                option_env!("CARGO_PKG_VERSION"),
+                options.client_options,
+                Vec::new(),
+                vec![auth_policy],
+                None,
+            ),
+            schemas_by_id: Cache::new(),
+            schemas_by_version: Cache::new(),
+            properties_by_definition: Cache::new(),
+        })
+    }
+
+    /// Returns the Url associated with this client.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Gets a schema by its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema_id` - The ID of the schema.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("SchemaRegistry.getSchemaById")]
+    pub async fn get_schema(
+        &self,
+        schema_id: &str,
+        options: Option<SchemaRegistryClientGetSchemaOptions<'_>>,
+    ) -> Result<Schema> {
+        check_non_empty("schema_id", schema_id)?;
+        if let Some(schema) = self.schemas_by_id.get(&schema_id.to_string()) {
+            return Ok(schema);
+        }
+        let options = options.unwrap_or_default();
+        let url = self.url(&["$schemaGroups", "$schemas", schema_id]);
+        let mut request = Request::new(url, Method::Get);
+        request.insert_header(ACCEPT, ACCEPT_SCHEMA);
+        let rsp = self
+            .send(&options.method_options.context, &mut request, &[200])
+            .await?;
+        let schema = schema_from_response(rsp)?;
+        self.cache_schema(&schema);
+        Ok(schema)
+    }
+
+    /// Gets a version of a schema by the name of its group, its name, and its version.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_name` - The name of the schema group.
+    /// * `name` - The name of the schema.
+    /// * `version` - The version of the schema.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("SchemaRegistry.getSchemaByVersion")]
+    pub async fn get_schema_by_version(
+        &self,
+        group_name: &str,
+        name: &str,
+        version: i32,
+        options: Option<SchemaRegistryClientGetSchemaByVersionOptions<'_>>,
+    ) -> Result<Schema> {
+        check_non_empty("group_name", group_name)?;
+        check_non_empty("name", name)?;
+        let key = (group_name.to_string(), name.to_string(), version);
+        if let Some(schema) = self.schemas_by_version.get(&key) {
+            return Ok(schema);
+        }
+        let options = options.unwrap_or_default();
+        let version = version.to_string();
+        let url = self.url(&[
+            "$schemaGroups",
+            group_name,
+            "schemas",
+            name,
+            "versions",
+            &version,
+        ]);
+        let mut request = Request::new(url, Method::Get);
+        request.insert_header(ACCEPT, ACCEPT_SCHEMA);
+        let rsp = self
+            .send(&options.method_options.context, &mut request, &[200])
+            .await?;
+        let schema = schema_from_response(rsp)?;
+        self.cache_schema(&schema);
+        Ok(schema)
+    }
+
+    /// Gets the properties of a schema that is registered with the same definition.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_name` - The name of the schema group.
+    /// * `name` - The name of the schema.
+    /// * `definition` - The definition of the schema.
+    /// * `format` - The format of the schema.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("SchemaRegistry.getSchemaPropertiesByContent")]
+    pub async fn get_schema_properties(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+        format: SchemaFormat,
+        options: Option<SchemaRegistryClientGetSchemaPropertiesOptions<'_>>,
+    ) -> Result<SchemaProperties> {
+        check_non_empty("group_name", group_name)?;
+        check_non_empty("name", name)?;
+        let key = definition_key(group_name, name, definition, &format);
+        if let Some(properties) = self.properties_by_definition.get(&key) {
+            return Ok(properties);
+        }
+        let options = options.unwrap_or_default();
+        let url = self.url(&[
+            "$schemaGroups",
+            group_name,
+            "schemas",
+            &format!("{name}:get-id"),
+        ]);
+        let mut request = Request::new(url, Method::Post);
+        request.insert_header(CONTENT_TYPE, format.content_type());
+        request.set_body(definition.to_string());
+        let rsp = self
+            .send(&options.method_options.context, &mut request, &[200, 204])
+            .await?;
+        let properties = SchemaProperties::from_headers(rsp.headers())?;
+        self.cache_properties(key, definition, &properties);
+        Ok(properties)
+    }
+
+    /// Registers a schema, creating a new version of it if its definition changed.
+    ///
+    /// Registering a definition that is already registered returns the properties of the
+    /// existing version.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_name` - The name of the schema group.
+    /// * `name` - The name of the schema.
+    /// * `definition` - The definition of the schema.
+    /// * `format` - The format of the schema.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("SchemaRegistry.registerSchema")]
+    pub async fn register_schema(
+        &self,
+        group_name: &str,
+        name: &str,
+        definition: &str,
+        format: SchemaFormat,
+        options: Option<SchemaRegistryClientRegisterSchemaOptions<'_>>,
+    ) -> Result<SchemaProperties> {
+        check_non_empty("group_name", group_name)?;
+        check_non_empty("name", name)?;
+        let key = definition_key(group_name, name, definition, &format);
+        if let Some(properties) = self.properties_by_definition.get(&key) {
+            return Ok(properties);
+        }
+        let options = options.unwrap_or_default();
+        let url = self.url(&["$schemaGroups", group_name, "schemas", name]);
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header(CONTENT_TYPE, format.content_type());
+        request.set_body(definition.to_string());
+        let rsp = self
+            .send(&options.method_options.context, &mut request, &[200, 204])
+            .await?;
+        let properties = SchemaProperties::from_headers(rsp.headers())?;
+        self.cache_properties(key, definition, &properties);
+        Ok(properties)
+    }
+
+    /// Returns the URL of a path under the endpoint, with the API version.
+    fn url(&self, segments: &[&str]) -> Url {
+        let mut url = self.endpoint.clone();
+        url.path_segments_mut()
+            .expect("the endpoint is an http(s) URL")
+            .pop_if_empty()
+            .extend(segments);
+        url.query_pairs_mut()
+            .append_pair("api-version", &self.api_version);
+        url
+    }
+
+    async fn send(
+        &self,
+        ctx: &Context<'_>,
+        request: &mut Request,
+        success_codes: &'static [u16],
+    ) -> Result<RawResponse> {
+        self.pipeline
+            .send(
+                ctx,
+                request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions { success_codes },
+                    ..Default::default()
+                }),
+            )
+            .await
+    }
+
+    fn cache_schema(&self, schema: &Schema) {
+        let properties = &schema.properties;
+        self.schemas_by_id
+            .insert(properties.id.clone(), schema.clone());
+        self.schemas_by_version.insert(
+            (
+                properties.group_name.clone(),
+                properties.name.clone(),
+                properties.version,
+            ),
+            schema.clone(),
+        );
+    }
+
+    fn cache_properties(
+        &self,
+        key: DefinitionKey,
+        definition: &str,
+        properties: &SchemaProperties,
+    ) {
+        self.properties_by_definition
+            .insert(key, properties.clone());
+        // The registered definition is the schema of its ID, so decoding a message this client
+        // encoded does not need to get the schema again.
+        self.cache_schema(&Schema {
+            definition: definition.to_string(),
+            properties: properties.clone(),
+        });
+    }
+}
+
+fn check_non_empty(name: &str, value: &str) -> Result<()> {
+    if value.is_empty() {
+        return Err(Error::with_message(
+            ErrorKind::Other,
+            format!("parameter {name} cannot be empty"),
+        ));
+    }
+    Ok(())
+}
+
+fn definition_key(
+    group_name: &str,
+    name: &str,
+    definition: &str,
+    format: &SchemaFormat,
+) -> DefinitionKey {
+    (
+        group_name.to_string(),
+        name.to_string(),
+        format.clone(),
+        definition.to_string(),
+    )
+}
+
+fn schema_from_response(rsp: RawResponse) -> Result<Schema> {
+    let properties = SchemaProperties::from_headers(rsp.headers())?;
+    let definition = rsp.into_body().into_string()?;
+    Ok(Schema {
+        definition,
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, StatusCode, Transport},
+        Bytes,
+    };
+    use azure_core_test::{credentials::MockCredential, http::MockHttpClient};
+    use futures::FutureExt as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const DEFINITION: &str =
+        r#"{"type":"record","name":"User","fields":[{"name":"name","type":"string"}]}"#;
+
+    fn schema_headers(id: &str, version: i32) -> Headers {
+        let mut headers = Headers::new();
+        headers.insert("schema-id", id.to_string());
+        headers.insert("schema-group-name", "group");
+        headers.insert("schema-name", "User");
+        headers.insert("schema-version", version.to_string());
+        headers.insert("content-type", SchemaFormat::Avro.content_type());
+        headers
+    }
+
+    fn create_client(
+        requests: Arc<AtomicUsize>,
+        respond: impl Fn(&Request) -> AsyncRawResponse + Send + Sync + 'static,
+    ) -> SchemaRegistryClient {
+        let mock_client = Arc::new(MockHttpClient::new(move |req| {
+            requests.fetch_add(1, Ordering::SeqCst);
+            let rsp = respond(req);
+            async move { Ok(rsp) }.boxed()
+        }));
+        SchemaRegistryClient::new(
+            "test.servicebus.windows.net",
+            MockCredential::new().unwrap(),
+            Some(SchemaRegistryClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_schema_caches_by_id() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client = create_client(requests.clone(), |req| {
+            assert_eq!(req.method(), Method::Get);
+            assert_eq!(req.url().path(), "/$schemaGroups/$schemas/abc123");
+            assert_eq!(
+                req.url().query(),
+                Some(format!("api-version={DEFAULT_API_VERSION}").as_str())
+            );
+            AsyncRawResponse::from_bytes(
+                StatusCode::Ok,
+                schema_headers("abc123", 3),
+                Bytes::from_static(DEFINITION.as_bytes()),
+            )
+        });
+
+        let schema = client.get_schema("abc123", None).await?;
+        assert_eq!(schema.definition, DEFINITION);
+        assert_eq!(
+            schema.properties,
+            SchemaProperties {
+                id: "abc123".to_string(),
+                format: SchemaFormat::Avro,
+                group_name: "group".to_string(),
+                name: "User".to_string(),
+                version: 3,
+            }
+        );
+
+        assert_eq!(client.get_schema("abc123", None).await?, schema);
+        assert_eq!(
+            client
+                .get_schema_by_version("group", "User", 3, None)
+                .await?,
+            schema
+        );
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_schema_by_version() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client = create_client(requests.clone(), |req| {
+            assert_eq!(
+                req.url().path(),
+                "/$schemaGroups/group/schemas/User/versions/2"
+            );
+            AsyncRawResponse::from_bytes(
+                StatusCode::Ok,
+                schema_headers("def456", 2),
+                Bytes::from_static(DEFINITION.as_bytes()),
+            )
+        });
+
+        let schema = client
+            .get_schema_by_version("group", "User", 2, None)
+            .await?;
+        assert_eq!(schema.properties.id, "def456");
+        assert_eq!(client.get_schema("def456", None).await?, schema);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn register_schema_caches_by_definition() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client = create_client(requests.clone(), |req| {
+            assert_eq!(req.method(), Method::Put);
+            assert_eq!(req.url().path(), "/$schemaGroups/group/schemas/User");
+            assert_eq!(
+                req.headers().get_optional_str(&CONTENT_TYPE),
+                Some(SchemaFormat::Avro.content_type())
+            );
+            AsyncRawResponse::from_bytes(
+                StatusCode::NoContent,
+                schema_headers("abc123", 1),
+                Bytes::new(),
+            )
+        });
+
+        let properties = client
+            .register_schema("group", "User", DEFINITION, SchemaFormat::Avro, None)
+            .await?;
+        assert_eq!(properties.id, "abc123");
+        assert_eq!(properties.version, 1);
+
+        // The registered definition answers both lookups without another request.
+        assert_eq!(
+            client
+                .register_schema("group", "User", DEFINITION, SchemaFormat::Avro, None)
+                .await?,
+            properties
+        );
+        let schema = client.get_schema("abc123", None).await?;
+        assert_eq!(schema.definition, DEFINITION);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_schema_properties() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client = create_client(requests.clone(), |req| {
+            assert_eq!(req.method(), Method::Post);
+            assert_eq!(req.url().path(), "/$schemaGroups/group/schemas/User:get-id");
+            AsyncRawResponse::from_bytes(
+                StatusCode::NoContent,
+                schema_headers("abc123", 1),
+                Bytes::new(),
+            )
+        });
+
+        let properties = client
+            .get_schema_properties("group", "User", DEFINITION, SchemaFormat::Avro, None)
+            .await?;
+        assert_eq!(properties.id, "abc123");
+        client
+            .get_schema_properties("group", "User", DEFINITION, SchemaFormat::Avro, None)
+            .await?;
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn errors_are_not_cached() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let client = create_client(requests.clone(), |_| {
+            AsyncRawResponse::from_bytes(StatusCode::NotFound, Headers::new(), Bytes::new())
+        });
+
+        for _ in 0..2 {
+            let error = client.get_schema("missing", None).await.unwrap_err();
+            assert_eq!(error.http_status(), Some(StatusCode::NotFound));
+        }
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn endpoint_from_namespace() {
+        let credential = MockCredential::new().unwrap();
+        let client =
+            SchemaRegistryClient::new("test.servicebus.windows.net", credential.clone(), None)
+                .unwrap();
+        assert_eq!(
+            client.endpoint().as_str(),
+            "https://test.servicebus.windows.net/"
+        );
+        let client =
+            SchemaRegistryClient::new("http://localhost:8080", credential.clone(), None).unwrap();
+        assert_eq!(client.endpoint().as_str(), "http://localhost:8080/");
+        assert!(SchemaRegistryClient::new("ftp://localhost", credential, None).is_err());
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let cache = Cache::new();
+        for i in 0..MAX_CACHE_ENTRIES {
+            cache.insert(i, i);
+        }
+        assert_eq!(cache.get(&0), Some(0));
+        cache.insert(MAX_CACHE_ENTRIES, MAX_CACHE_ENTRIES);
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.get(&MAX_CACHE_ENTRIES), Some(MAX_CACHE_ENTRIES));
+    }
+}
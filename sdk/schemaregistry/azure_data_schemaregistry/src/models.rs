@@ -0,0 +1,167 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Types sent to and received from the Schema Registry service.
+
+use azure_core::{
+    error::ErrorKind,
+    fmt::SafeDebug,
+    http::{
+        headers::{HeaderName, Headers, CONTENT_TYPE},
+        ClientMethodOptions,
+    },
+    Error, Result,
+};
+use std::fmt;
+
+pub(crate) const SCHEMA_ID: HeaderName = HeaderName::from_static("schema-id");
+pub(crate) const SCHEMA_GROUP_NAME: HeaderName = HeaderName::from_static("schema-group-name");
+pub(crate) const SCHEMA_NAME: HeaderName = HeaderName::from_static("schema-name");
+pub(crate) const SCHEMA_VERSION: HeaderName = HeaderName::from_static("schema-version");
+
+/// The format of a schema.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SchemaFormat {
+    /// An Apache Avro schema.
+    Avro,
+    /// A JSON schema.
+    Json,
+    /// A schema in a format the service does not interpret.
+    Custom,
+}
+
+impl SchemaFormat {
+    /// The content type that the service uses for schemas of this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            SchemaFormat::Avro => "application/json; serialization=Avro",
+            SchemaFormat::Json => "application/json; serialization=Json",
+            SchemaFormat::Custom => "text/plain; charset=utf-8",
+        }
+    }
+
+    /// Gets the format from the content type of a schema.
+    fn from_content_type(content_type: &str) -> Result<Self> {
+        let mut parts = content_type.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or_default();
+        if media_type.eq_ignore_ascii_case("text/plain") {
+            return Ok(SchemaFormat::Custom);
+        }
+        if media_type.eq_ignore_ascii_case("application/json") {
+            for parameter in parts {
+                if let Some((name, value)) = parameter.split_once('=') {
+                    if name.trim().eq_ignore_ascii_case("serialization") {
+                        let value = value.trim();
+                        if value.eq_ignore_ascii_case("avro") {
+                            return Ok(SchemaFormat::Avro);
+                        }
+                        if value.eq_ignore_ascii_case("json") {
+                            return Ok(SchemaFormat::Json);
+                        }
+                    }
+                }
+            }
+        }
+        Err(Error::with_message(
+            ErrorKind::DataConversion,
+            format!("unknown schema content type {content_type}"),
+        ))
+    }
+}
+
+impl fmt::Display for SchemaFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SchemaFormat::Avro => "Avro",
+            SchemaFormat::Json => "Json",
+            SchemaFormat::Custom => "Custom",
+        })
+    }
+}
+
+/// The properties of a registered schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaProperties {
+    /// The ID that identifies the schema in the namespace.
+    pub id: String,
+    /// The format of the schema.
+    pub format: SchemaFormat,
+    /// The name of the schema group that holds the schema.
+    pub group_name: String,
+    /// The name of the schema.
+    pub name: String,
+    /// The version of the schema in its group.
+    pub version: i32,
+}
+
+impl SchemaProperties {
+    /// Reads the properties from the headers of a response.
+    pub(crate) fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            id: headers.get_str(&SCHEMA_ID)?.to_string(),
+            format: SchemaFormat::from_content_type(headers.get_str(&CONTENT_TYPE)?)?,
+            group_name: headers.get_str(&SCHEMA_GROUP_NAME)?.to_string(),
+            name: headers.get_str(&SCHEMA_NAME)?.to_string(),
+            version: headers.get_as(&SCHEMA_VERSION)?,
+        })
+    }
+}
+
+/// A registered schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// The definition of the schema, in the syntax of its format.
+    pub definition: String,
+    /// The properties of the schema.
+    pub properties: SchemaProperties,
+}
+
+/// Options to be passed to [`SchemaRegistryClient::get_schema()`](crate::SchemaRegistryClient::get_schema())
+#[derive(Clone, Default, SafeDebug)]
+pub struct SchemaRegistryClientGetSchemaOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options to be passed to [`SchemaRegistryClient::get_schema_by_version()`](crate::SchemaRegistryClient::get_schema_by_version())
+#[derive(Clone, Default, SafeDebug)]
+pub struct SchemaRegistryClientGetSchemaByVersionOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options to be passed to [`SchemaRegistryClient::get_schema_properties()`](crate::SchemaRegistryClient::get_schema_properties())
+#[derive(Clone, Default, SafeDebug)]
+pub struct SchemaRegistryClientGetSchemaPropertiesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options to be passed to [`SchemaRegistryClient::register_schema()`](crate::SchemaRegistryClient::register_schema())
+#[derive(Clone, Default, SafeDebug)]
+pub struct SchemaRegistryClientRegisterSchemaOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_format_from_content_type() {
+        for format in [SchemaFormat::Avro, SchemaFormat::Json, SchemaFormat::Custom] {
+            assert_eq!(
+                SchemaFormat::from_content_type(format.content_type()).unwrap(),
+                format
+            );
+        }
+        assert_eq!(
+            SchemaFormat::from_content_type("application/json;serialization=avro").unwrap(),
+            SchemaFormat::Avro
+        );
+        assert!(SchemaFormat::from_content_type("application/json").is_err());
+        assert!(SchemaFormat::from_content_type("application/xml").is_err());
+    }
+}
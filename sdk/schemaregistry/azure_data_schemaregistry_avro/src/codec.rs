@@ -0,0 +1,591 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! The Avro binary encoding.
+
+use crate::{
+    schema::{AvroSchema, Enum, Fixed, Record, Schema},
+    AvroValue,
+};
+use azure_core::{error::ErrorKind, Error, Result};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// Encodes a value of a schema.
+pub(crate) fn encode(schema: &AvroSchema, value: &AvroValue) -> Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    Encoder { schema }.encode(&schema.root, value, &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Decodes a value of a schema, which must use all of the input.
+pub(crate) fn decode(schema: &AvroSchema, mut input: &[u8]) -> Result<AvroValue> {
+    let value = Decoder { schema }.decode(&schema.root, &mut input)?;
+    if !input.is_empty() {
+        return Err(invalid_data(format!(
+            "{} bytes left over after the value",
+            input.len()
+        )));
+    }
+    Ok(value)
+}
+
+fn invalid_data(message: impl Into<String>) -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        format!("invalid Avro data: {}", message.into()),
+    )
+}
+
+fn mismatch(schema: &Schema, value: &AvroValue) -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        format!(
+            "value {value:?} does not match the schema type {}",
+            type_name(schema)
+        ),
+    )
+}
+
+fn type_name(schema: &Schema) -> &str {
+    match schema {
+        Schema::Null => "null",
+        Schema::Boolean => "boolean",
+        Schema::Int => "int",
+        Schema::Long => "long",
+        Schema::Float => "float",
+        Schema::Double => "double",
+        Schema::Bytes => "bytes",
+        Schema::String => "string",
+        Schema::Record(Record { name, .. })
+        | Schema::Enum(Enum { name, .. })
+        | Schema::Fixed(Fixed { name, .. })
+        | Schema::Ref(name) => name,
+        Schema::Array(_) => "array",
+        Schema::Map(_) => "map",
+        Schema::Union(_) => "union",
+    }
+}
+
+fn write_long(value: i64, buffer: &mut Vec<u8>) {
+    let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+    while zigzag >= 0x80 {
+        buffer.push((zigzag as u8) | 0x80);
+        zigzag >>= 7;
+    }
+    buffer.push(zigzag as u8);
+}
+
+fn write_bytes(bytes: &[u8], buffer: &mut Vec<u8>) {
+    write_long(bytes.len() as i64, buffer);
+    buffer.extend_from_slice(bytes);
+}
+
+fn read_long(input: &mut &[u8]) -> Result<i64> {
+    let mut zigzag = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = input
+            .split_first()
+            .ok_or_else(|| invalid_data("unexpected end of input"))?;
+        *input = rest;
+        zigzag |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+        }
+    }
+    Err(invalid_data("a variable-length integer is too long"))
+}
+
+fn read_int(input: &mut &[u8]) -> Result<i32> {
+    i32::try_from(read_long(input)?).map_err(|_| invalid_data("an int is out of range"))
+}
+
+fn read_slice<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(invalid_data("unexpected end of input"));
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+fn read_bytes<'a>(input: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len =
+        usize::try_from(read_long(input)?).map_err(|_| invalid_data("a length is negative"))?;
+    read_slice(input, len)
+}
+
+fn read_string(input: &mut &[u8]) -> Result<String> {
+    String::from_utf8(read_bytes(input)?.to_vec())
+        .map_err(|e| Error::with_error(ErrorKind::DataConversion, e, "invalid Avro string"))
+}
+
+/// Reads the item count of the next block of an array or map, which is zero after the last one.
+fn read_block_count(input: &mut &[u8]) -> Result<usize> {
+    let count = read_long(input)?;
+    if count < 0 {
+        // A negative count is followed by the size of the block in bytes.
+        read_long(input)?;
+    }
+    usize::try_from(count.unsigned_abs()).map_err(|_| invalid_data("a block count is too large"))
+}
+
+struct Encoder<'a> {
+    schema: &'a AvroSchema,
+}
+
+impl Encoder<'_> {
+    fn encode(&self, schema: &Schema, value: &AvroValue, buffer: &mut Vec<u8>) -> Result<()> {
+        let schema = self.schema.resolve(schema)?;
+        match (schema, value) {
+            (Schema::Null, AvroValue::Null) => {}
+            (Schema::Boolean, AvroValue::Boolean(value)) => buffer.push(u8::from(*value)),
+            (Schema::Int, AvroValue::Int(value)) => write_long(i64::from(*value), buffer),
+            (Schema::Long, AvroValue::Long(value)) => write_long(*value, buffer),
+            (Schema::Float, AvroValue::Float(value)) => {
+                buffer.extend_from_slice(&value.to_le_bytes())
+            }
+            (Schema::Double, AvroValue::Double(value)) => {
+                buffer.extend_from_slice(&value.to_le_bytes())
+            }
+            (Schema::Bytes, AvroValue::Bytes(value)) => write_bytes(value, buffer),
+            (Schema::String, AvroValue::String(value)) => write_bytes(value.as_bytes(), buffer),
+            (Schema::Fixed(fixed), AvroValue::Fixed(value)) if value.len() == fixed.size => {
+                buffer.extend_from_slice(value)
+            }
+            (Schema::Enum(schema), AvroValue::Enum(symbol)) => {
+                let index = schema
+                    .symbols
+                    .iter()
+                    .position(|candidate| candidate == symbol)
+                    .ok_or_else(|| mismatch(&Schema::Enum(schema.clone()), value))?;
+                write_long(index as i64, buffer);
+            }
+            (Schema::Array(items), AvroValue::Array(values)) => {
+                if !values.is_empty() {
+                    write_long(values.len() as i64, buffer);
+                    for value in values {
+                        self.encode(items, value, buffer)?;
+                    }
+                }
+                write_long(0, buffer);
+            }
+            (Schema::Map(values_schema), AvroValue::Map(values)) => {
+                if !values.is_empty() {
+                    write_long(values.len() as i64, buffer);
+                    // Sorting the keys makes the encoding of a map the same every time.
+                    let mut entries: Vec<_> = values.iter().collect();
+                    entries.sort_by_key(|(key, _)| *key);
+                    for (key, value) in entries {
+                        write_bytes(key.as_bytes(), buffer);
+                        self.encode(values_schema, value, buffer)?;
+                    }
+                }
+                write_long(0, buffer);
+            }
+            (Schema::Record(record), AvroValue::Record(fields)) => {
+                if let Some((name, _)) = fields
+                    .iter()
+                    .find(|(name, _)| !record.fields.iter().any(|field| &field.name == name))
+                {
+                    return Err(Error::with_message(
+                        ErrorKind::DataConversion,
+                        format!("record {} has no field {name}", record.name),
+                    ));
+                }
+                for field in &record.fields {
+                    match fields.iter().find(|(name, _)| *name == field.name) {
+                        Some((_, value)) => self.encode(&field.schema, value, buffer)?,
+                        None => {
+                            let default = field.default.as_ref().ok_or_else(|| {
+                                Error::with_message(
+                                    ErrorKind::DataConversion,
+                                    format!(
+                                        "field {} of record {} has no value and no default",
+                                        field.name, record.name
+                                    ),
+                                )
+                            })?;
+                            let value = self.default_value(&field.schema, default)?;
+                            self.encode(&field.schema, &value, buffer)?;
+                        }
+                    }
+                }
+            }
+            (Schema::Union(branches), value) => {
+                let index = branches
+                    .iter()
+                    .position(|branch| self.matches(branch, value))
+                    .ok_or_else(|| mismatch(schema, value))?;
+                write_long(index as i64, buffer);
+                self.encode(&branches[index], value, buffer)?;
+            }
+            (schema, value) => return Err(mismatch(schema, value)),
+        }
+        Ok(())
+    }
+
+    /// Whether a value is of a schema type, for choosing the branch of a union.
+    fn matches(&self, schema: &Schema, value: &AvroValue) -> bool {
+        let Ok(schema) = self.schema.resolve(schema) else {
+            return false;
+        };
+        match (schema, value) {
+            (Schema::Null, AvroValue::Null)
+            | (Schema::Boolean, AvroValue::Boolean(_))
+            | (Schema::Int, AvroValue::Int(_))
+            | (Schema::Long, AvroValue::Long(_))
+            | (Schema::Float, AvroValue::Float(_))
+            | (Schema::Double, AvroValue::Double(_))
+            | (Schema::Bytes, AvroValue::Bytes(_))
+            | (Schema::String, AvroValue::String(_))
+            | (Schema::Array(_), AvroValue::Array(_))
+            | (Schema::Map(_), AvroValue::Map(_)) => true,
+            (Schema::Fixed(fixed), AvroValue::Fixed(value)) => value.len() == fixed.size,
+            (Schema::Enum(schema), AvroValue::Enum(symbol)) => schema.symbols.contains(symbol),
+            (Schema::Record(record), AvroValue::Record(fields)) => {
+                fields
+                    .iter()
+                    .all(|(name, _)| record.fields.iter().any(|field| &field.name == name))
+                    && record.fields.iter().all(|field| {
+                        field.default.is_some()
+                            || fields.iter().any(|(name, _)| *name == field.name)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// Converts the JSON default of a field to a value of its schema.
+    fn default_value(&self, schema: &Schema, json: &JsonValue) -> Result<AvroValue> {
+        let schema = self.schema.resolve(schema)?;
+        let invalid = || {
+            Error::with_message(
+                ErrorKind::DataConversion,
+                format!(
+                    "default {json} does not match the schema type {}",
+                    type_name(schema)
+                ),
+            )
+        };
+        Ok(match (schema, json) {
+            (Schema::Null, JsonValue::Null) => AvroValue::Null,
+            (Schema::Boolean, JsonValue::Bool(value)) => AvroValue::Boolean(*value),
+            (Schema::Int, JsonValue::Number(value)) => AvroValue::Int(
+                value
+                    .as_i64()
+                    .and_then(|value| i32::try_from(value).ok())
+                    .ok_or_else(invalid)?,
+            ),
+            (Schema::Long, JsonValue::Number(value)) => {
+                AvroValue::Long(value.as_i64().ok_or_else(invalid)?)
+            }
+            (Schema::Float, JsonValue::Number(value)) => {
+                AvroValue::Float(value.as_f64().ok_or_else(invalid)? as f32)
+            }
+            (Schema::Double, JsonValue::Number(value)) => {
+                AvroValue::Double(value.as_f64().ok_or_else(invalid)?)
+            }
+            // The default of bytes and fixed is a string whose code points are the bytes.
+            (Schema::Bytes, JsonValue::String(value)) => {
+                AvroValue::Bytes(code_points_to_bytes(value).ok_or_else(invalid)?)
+            }
+            (Schema::Fixed(_), JsonValue::String(value)) => {
+                AvroValue::Fixed(code_points_to_bytes(value).ok_or_else(invalid)?)
+            }
+            (Schema::String, JsonValue::String(value)) => AvroValue::String(value.clone()),
+            (Schema::Enum(_), JsonValue::String(value)) => AvroValue::Enum(value.clone()),
+            (Schema::Array(items), JsonValue::Array(values)) => AvroValue::Array(
+                values
+                    .iter()
+                    .map(|value| self.default_value(items, value))
+                    .collect::<Result<_>>()?,
+            ),
+            (Schema::Map(values_schema), JsonValue::Object(values)) => AvroValue::Map(
+                values
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((key.clone(), self.default_value(values_schema, value)?))
+                    })
+                    .collect::<Result<HashMap<_, _>>>()?,
+            ),
+            (Schema::Record(record), JsonValue::Object(values)) => AvroValue::Record(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let json = values
+                            .get(&field.name)
+                            .or(field.default.as_ref())
+                            .ok_or_else(invalid)?;
+                        Ok((field.name.clone(), self.default_value(&field.schema, json)?))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            // The default of a union is a value of its first branch.
+            (Schema::Union(branches), json) => {
+                self.default_value(branches.first().ok_or_else(invalid)?, json)?
+            }
+            _ => return Err(invalid()),
+        })
+    }
+}
+
+fn code_points_to_bytes(value: &str) -> Option<Vec<u8>> {
+    value.chars().map(|c| u8::try_from(c).ok()).collect()
+}
+
+struct Decoder<'a> {
+    schema: &'a AvroSchema,
+}
+
+impl Decoder<'_> {
+    fn decode(&self, schema: &Schema, input: &mut &[u8]) -> Result<AvroValue> {
+        let schema = self.schema.resolve(schema)?;
+        Ok(match schema {
+            Schema::Null => AvroValue::Null,
+            Schema::Boolean => match read_slice(input, 1)?[0] {
+                0 => AvroValue::Boolean(false),
+                1 => AvroValue::Boolean(true),
+                byte => return Err(invalid_data(format!("{byte} is not a boolean"))),
+            },
+            Schema::Int => AvroValue::Int(read_int(input)?),
+            Schema::Long => AvroValue::Long(read_long(input)?),
+            Schema::Float => AvroValue::Float(f32::from_le_bytes(
+                read_slice(input, 4)?.try_into().expect("four bytes"),
+            )),
+            Schema::Double => AvroValue::Double(f64::from_le_bytes(
+                read_slice(input, 8)?.try_into().expect("eight bytes"),
+            )),
+            Schema::Bytes => AvroValue::Bytes(read_bytes(input)?.to_vec()),
+            Schema::String => AvroValue::String(read_string(input)?),
+            Schema::Fixed(fixed) => AvroValue::Fixed(read_slice(input, fixed.size)?.to_vec()),
+            Schema::Enum(schema) => {
+                let index = read_int(input)?;
+                let symbol = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| schema.symbols.get(index))
+                    .ok_or_else(|| {
+                        invalid_data(format!("{index} is not a symbol of enum {}", schema.name))
+                    })?;
+                AvroValue::Enum(symbol.clone())
+            }
+            Schema::Array(items) => {
+                let mut values = Vec::new();
+                loop {
+                    let count = read_block_count(input)?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        values.push(self.decode(items, input)?);
+                    }
+                }
+                AvroValue::Array(values)
+            }
+            Schema::Map(values_schema) => {
+                let mut values = HashMap::new();
+                loop {
+                    let count = read_block_count(input)?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        let key = read_string(input)?;
+                        values.insert(key, self.decode(values_schema, input)?);
+                    }
+                }
+                AvroValue::Map(values)
+            }
+            Schema::Record(record) => AvroValue::Record(
+                record
+                    .fields
+                    .iter()
+                    .map(|field| Ok((field.name.clone(), self.decode(&field.schema, input)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            Schema::Union(branches) => {
+                let index = read_long(input)?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| branches.get(index))
+                    .ok_or_else(|| invalid_data(format!("{index} is not a branch of the union")))?;
+                self.decode(branch, input)?
+            }
+            Schema::Ref(name) => {
+                return Err(invalid_data(format!("unresolved reference to {name}")))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(definition: &str, value: AvroValue) -> Vec<u8> {
+        let schema = AvroSchema::parse(definition).unwrap();
+        let encoded = encode(&schema, &value).unwrap();
+        assert_eq!(decode(&schema, &encoded).unwrap(), value);
+        encoded
+    }
+
+    #[test]
+    fn encodes_primitives_as_the_specification() {
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(0)), [0x00]);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(-1)), [0x01]);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(1)), [0x02]);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(-64)), [0x7f]);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(64)), [0x80, 0x01]);
+        assert_eq!(round_trip(r#""int""#, AvroValue::Int(i32::MIN)).len(), 5);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(i64::MAX)).len(), 10);
+        assert_eq!(round_trip(r#""long""#, AvroValue::Long(i64::MIN)).len(), 10);
+        assert_eq!(
+            round_trip(r#""string""#, "foo".into()),
+            [0x06, b'f', b'o', b'o']
+        );
+        assert_eq!(round_trip(r#""boolean""#, true.into()), [0x01]);
+        assert_eq!(round_trip(r#""null""#, AvroValue::Null), [] as [u8; 0]);
+        assert_eq!(
+            round_trip(r#""float""#, 1.5f32.into()),
+            1.5f32.to_le_bytes()
+        );
+        assert_eq!(
+            round_trip(r#""double""#, (-2.25f64).into()),
+            (-2.25f64).to_le_bytes()
+        );
+        assert_eq!(
+            round_trip(r#""bytes""#, vec![1u8, 2].into()),
+            [0x04, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn encodes_complex_types_as_the_specification() {
+        // The array example from the specification: a count of 2, the items 3 and 27, and the
+        // end of the blocks.
+        assert_eq!(
+            round_trip(
+                r#"{"type": "array", "items": "long"}"#,
+                AvroValue::Array(vec![AvroValue::Long(3), AvroValue::Long(27)])
+            ),
+            [0x04, 0x06, 0x36, 0x00]
+        );
+        // The union example: the second branch, then the long 2.
+        assert_eq!(
+            round_trip(r#"["null", "string"]"#, "a".into()),
+            [0x02, 0x02, b'a']
+        );
+        assert_eq!(round_trip(r#"["null", "string"]"#, AvroValue::Null), [0x00]);
+        assert_eq!(
+            round_trip(
+                r#"{"type": "enum", "name": "E", "symbols": ["A", "B", "C"]}"#,
+                AvroValue::Enum("C".to_string())
+            ),
+            [0x04]
+        );
+        assert_eq!(
+            round_trip(
+                r#"{"type": "fixed", "name": "F", "size": 2}"#,
+                AvroValue::Fixed(vec![7, 8])
+            ),
+            [7, 8]
+        );
+        round_trip(
+            r#"{"type": "map", "values": "int"}"#,
+            AvroValue::Map(HashMap::from([
+                ("a".to_string(), AvroValue::Int(1)),
+                ("b".to_string(), AvroValue::Int(2)),
+            ])),
+        );
+    }
+
+    #[test]
+    fn records_use_schema_order_and_defaults() {
+        let schema = AvroSchema::parse(
+            r#"{
+                "type": "record",
+                "name": "Node",
+                "fields": [
+                    {"name": "value", "type": "int"},
+                    {"name": "label", "type": "string", "default": "none"},
+                    {"name": "next", "type": ["null", "Node"], "default": null}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        // The fields are given out of order, and `label` is left to its default.
+        let value = AvroValue::Record(vec![
+            (
+                "next".to_string(),
+                AvroValue::Record(vec![("value".to_string(), AvroValue::Int(2))]),
+            ),
+            ("value".to_string(), AvroValue::Int(1)),
+        ]);
+        let encoded = encode(&schema, &value).unwrap();
+        let decoded = decode(&schema, &encoded).unwrap();
+        assert_eq!(
+            decoded,
+            AvroValue::Record(vec![
+                ("value".to_string(), AvroValue::Int(1)),
+                ("label".to_string(), "none".into()),
+                (
+                    "next".to_string(),
+                    AvroValue::Record(vec![
+                        ("value".to_string(), AvroValue::Int(2)),
+                        ("label".to_string(), "none".into()),
+                        ("next".to_string(), AvroValue::Null),
+                    ])
+                ),
+            ])
+        );
+        assert_eq!(decoded.field("label"), Some(&"none".into()));
+
+        let missing = AvroValue::Record(vec![("label".to_string(), "x".into())]);
+        assert!(encode(&schema, &missing).is_err());
+        let unknown = AvroValue::Record(vec![
+            ("value".to_string(), AvroValue::Int(1)),
+            ("other".to_string(), AvroValue::Int(1)),
+        ]);
+        assert!(encode(&schema, &unknown).is_err());
+    }
+
+    #[test]
+    fn rejects_mismatched_values() {
+        let schema = AvroSchema::parse(r#""long""#).unwrap();
+        let error = encode(&schema, &AvroValue::Int(1)).unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::DataConversion);
+
+        let schema = AvroSchema::parse(r#"["null", "int"]"#).unwrap();
+        assert!(encode(&schema, &"a".into()).is_err());
+
+        let schema =
+            AvroSchema::parse(r#"{"type": "enum", "name": "E", "symbols": ["A"]}"#).unwrap();
+        assert!(encode(&schema, &AvroValue::Enum("B".to_string())).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        let schema = AvroSchema::parse(r#""string""#).unwrap();
+        // A length longer than the input.
+        assert!(decode(&schema, &[0x08, b'a']).is_err());
+        // Bytes left over.
+        assert!(decode(&schema, &[0x02, b'a', b'b']).is_err());
+        // A variable-length integer that does not end.
+        let schema = AvroSchema::parse(r#""long""#).unwrap();
+        assert!(decode(&schema, &[0xff; 11]).is_err());
+        let schema = AvroSchema::parse(r#"["null", "int"]"#).unwrap();
+        assert!(decode(&schema, &[0x04]).is_err());
+    }
+
+    #[test]
+    fn decodes_blocks_with_sizes() {
+        let schema = AvroSchema::parse(r#"{"type": "array", "items": "int"}"#).unwrap();
+        // A block with a negative count of -2 and a size of 2 bytes, then the end of the blocks.
+        assert_eq!(
+            decode(&schema, &[0x03, 0x04, 0x02, 0x04, 0x00]).unwrap(),
+            AvroValue::Array(vec![AvroValue::Int(1), AvroValue::Int(2)])
+        );
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{codec, schema::AvroSchema, AvroValue};
+use azure_core::{error::ErrorKind, Error, Result};
+use azure_data_schemaregistry::{models::SchemaFormat, SchemaRegistryClient};
+use azure_messaging_eventhubs::models::{EventData, ReceivedEventData};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tracing::debug;
+
+/// The prefix of the content type of Avro-encoded data, which is followed by the schema ID.
+const AVRO_CONTENT_TYPE_PREFIX: &str = "avro/binary+";
+
+/// The number of parsed schemas the encoder holds before its cache is cleared.
+const MAX_CACHE_ENTRIES: usize = 128;
+
+/// Options used when creating an [`AvroEncoder`].
+#[derive(Clone, Debug, Default)]
+pub struct AvroEncoderOptions {
+    /// The schema group of the schemas used to encode values.
+    ///
+    /// Required to encode; decoding finds schemas by ID, which is unique across groups.
+    pub group_name: Option<String>,
+
+    /// Whether to register the schemas used to encode values.
+    ///
+    /// When `false`, the default, a schema must already be registered in the group.
+    pub auto_register: bool,
+}
+
+/// Avro-encoded data and its content type, which names the ID of the schema it was encoded with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MessageContent {
+    /// The encoded data.
+    pub content: Vec<u8>,
+    /// The content type of the data, `avro/binary+<schema ID>`.
+    pub content_type: String,
+}
+
+impl From<MessageContent> for EventData {
+    fn from(value: MessageContent) -> Self {
+        EventData::builder()
+            .with_body(value.content)
+            .with_content_type(value.content_type)
+            .build()
+    }
+}
+
+/// Encodes and decodes Avro data with schemas from Schema Registry.
+///
+/// The encoded data does not include its schema. Instead, the content type names the ID of
+/// the schema, which the decoder gets from Schema Registry. Schemas and schema IDs are cached,
+/// so only the first use of a schema makes a request to the service.
+pub struct AvroEncoder {
+    client: Arc<SchemaRegistryClient>,
+    options: AvroEncoderOptions,
+    schemas_by_definition: Mutex<HashMap<String, Arc<AvroSchema>>>,
+    schemas_by_id: Mutex<HashMap<String, Arc<AvroSchema>>>,
+}
+
+impl AvroEncoder {
+    /// Creates a new `AvroEncoder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The Schema Registry client used to get and register schemas.
+    /// * `options` - Optional configuration for the encoder.
+    pub fn new(client: Arc<SchemaRegistryClient>, options: Option<AvroEncoderOptions>) -> Self {
+        Self {
+            client,
+            options: options.unwrap_or_default(),
+            schemas_by_definition: Mutex::new(HashMap::new()),
+            schemas_by_id: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Encodes a value with a schema.
+    ///
+    /// The schema must be a named type, such as a record, and is registered or looked up in
+    /// the schema group of the encoder under its full name.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to encode.
+    /// * `schema` - The JSON definition of the Avro schema of the value.
+    pub async fn encode(&self, value: &AvroValue, schema: &str) -> Result<MessageContent> {
+        let group_name = self.options.group_name.as_deref().ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::Other,
+                "a group name is required to encode with Schema Registry",
+            )
+        })?;
+        let parsed = match cache_get(&self.schemas_by_definition, schema) {
+            Some(parsed) => parsed,
+            None => {
+                let parsed = Arc::new(AvroSchema::parse(schema)?);
+                cache_insert(&self.schemas_by_definition, schema, parsed.clone());
+                parsed
+            }
+        };
+        let name = parsed.full_name().ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::DataConversion,
+                "the schema must be a named type to be registered",
+            )
+        })?;
+        let content = codec::encode(&parsed, value)?;
+
+        let properties = if self.options.auto_register {
+            self.client
+                .register_schema(group_name, name, schema, SchemaFormat::Avro, None)
+                .await?
+        } else {
+            self.client
+                .get_schema_properties(group_name, name, schema, SchemaFormat::Avro, None)
+                .await?
+        };
+        cache_insert(&self.schemas_by_id, &properties.id, parsed);
+
+        Ok(MessageContent {
+            content,
+            content_type: format!("{AVRO_CONTENT_TYPE_PREFIX}{}", properties.id),
+        })
+    }
+
+    /// Encodes a value with a schema into the body of an event.
+    ///
+    /// See [`AvroEncoder::encode`].
+    pub async fn encode_event(&self, value: &AvroValue, schema: &str) -> Result<EventData> {
+        Ok(self.encode(value, schema).await?.into())
+    }
+
+    /// Decodes data with the schema named by its content type.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The encoded data.
+    /// * `content_type` - The content type of the data, `avro/binary+<schema ID>`.
+    pub async fn decode(&self, content: &[u8], content_type: &str) -> Result<AvroValue> {
+        let schema_id = schema_id(content_type)?;
+        let schema = match cache_get(&self.schemas_by_id, schema_id) {
+            Some(schema) => schema,
+            None => {
+                debug!("Getting Avro schema {schema_id}");
+                let schema = self.client.get_schema(schema_id, None).await?;
+                if schema.properties.format != SchemaFormat::Avro {
+                    return Err(Error::with_message(
+                        ErrorKind::DataConversion,
+                        format!("schema {schema_id} is not an Avro schema"),
+                    ));
+                }
+                let parsed = Arc::new(AvroSchema::parse(&schema.definition)?);
+                cache_insert(&self.schemas_by_id, schema_id, parsed.clone());
+                parsed
+            }
+        };
+        codec::decode(&schema, content)
+    }
+
+    /// Decodes the body of a received event.
+    ///
+    /// See [`AvroEncoder::decode`].
+    pub async fn decode_event(&self, event: &ReceivedEventData) -> Result<AvroValue> {
+        self.decode_event_data(event.event_data()).await
+    }
+
+    /// Decodes the body of an event.
+    ///
+    /// See [`AvroEncoder::decode`].
+    pub async fn decode_event_data(&self, event: &EventData) -> Result<AvroValue> {
+        let content_type = event.content_type().ok_or_else(|| {
+            Error::with_message(ErrorKind::DataConversion, "the event has no content type")
+        })?;
+        self.decode(event.body().unwrap_or_default(), content_type)
+            .await
+    }
+}
+
+/// Returns the schema ID of an Avro content type.
+fn schema_id(content_type: &str) -> Result<&str> {
+    content_type
+        .strip_prefix(AVRO_CONTENT_TYPE_PREFIX)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::DataConversion,
+                format!("{content_type} is not an Avro content type with a schema ID"),
+            )
+        })
+}
+
+fn cache_get(
+    cache: &Mutex<HashMap<String, Arc<AvroSchema>>>,
+    key: &str,
+) -> Option<Arc<AvroSchema>> {
+    cache.lock().unwrap().get(key).cloned()
+}
+
+fn cache_insert(
+    cache: &Mutex<HashMap<String, Arc<AvroSchema>>>,
+    key: &str,
+    schema: Arc<AvroSchema>,
+) {
+    let mut entries = cache.lock().unwrap();
+    if entries.len() >= MAX_CACHE_ENTRIES && !entries.contains_key(key) {
+        entries.clear();
+    }
+    entries.insert(key.to_string(), schema);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{
+        http::{
+            headers::Headers, AsyncRawResponse, ClientOptions, Method, Request, StatusCode,
+            Transport,
+        },
+        Bytes,
+    };
+    use azure_core_test::{credentials::MockCredential, http::MockHttpClient};
+    use azure_data_schemaregistry::SchemaRegistryClientOptions;
+    use azure_messaging_eventhubs::models::AmqpMessage;
+    use futures::FutureExt as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const DEFINITION: &str = r#"{"type":"record","name":"User","namespace":"com.example","fields":[{"name":"name","type":"string"},{"name":"age","type":["null","int"],"default":null}]}"#;
+
+    fn schema_headers() -> Headers {
+        let mut headers = Headers::new();
+        headers.insert("schema-id", "abc123");
+        headers.insert("schema-group-name", "group");
+        headers.insert("schema-name", "com.example.User");
+        headers.insert("schema-version", "1");
+        headers.insert("content-type", SchemaFormat::Avro.content_type());
+        headers
+    }
+
+    fn create_encoder(requests: Arc<AtomicUsize>, auto_register: bool) -> AvroEncoder {
+        let mock_client = Arc::new(MockHttpClient::new(move |req: &Request| {
+            requests.fetch_add(1, Ordering::SeqCst);
+            let rsp = match (req.method(), req.url().path()) {
+                (Method::Put, "/$schemaGroups/group/schemas/com.example.User") => {
+                    AsyncRawResponse::from_bytes(
+                        StatusCode::NoContent,
+                        schema_headers(),
+                        Bytes::new(),
+                    )
+                }
+                (Method::Post, "/$schemaGroups/group/schemas/com.example.User:get-id") => {
+                    AsyncRawResponse::from_bytes(
+                        StatusCode::NoContent,
+                        schema_headers(),
+                        Bytes::new(),
+                    )
+                }
+                (Method::Get, "/$schemaGroups/$schemas/abc123") => AsyncRawResponse::from_bytes(
+                    StatusCode::Ok,
+                    schema_headers(),
+                    Bytes::from_static(DEFINITION.as_bytes()),
+                ),
+                _ => {
+                    AsyncRawResponse::from_bytes(StatusCode::NotFound, Headers::new(), Bytes::new())
+                }
+            };
+            async move { Ok(rsp) }.boxed()
+        }));
+        let client = SchemaRegistryClient::new(
+            "test.servicebus.windows.net",
+            MockCredential::new().unwrap(),
+            Some(SchemaRegistryClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        AvroEncoder::new(
+            Arc::new(client),
+            Some(AvroEncoderOptions {
+                group_name: Some("group".to_string()),
+                auto_register,
+            }),
+        )
+    }
+
+    fn user() -> AvroValue {
+        AvroValue::Record(vec![
+            ("name".to_string(), "Ada".into()),
+            ("age".to_string(), Some(36).into()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn encode_registers_and_decodes_with_schema_id() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let encoder = create_encoder(requests.clone(), true);
+
+        let content = encoder.encode(&user(), DEFINITION).await?;
+        assert_eq!(content.content_type, "avro/binary+abc123");
+        assert_eq!(content.content, [0x06, b'A', b'd', b'a', 0x02, 0x48]);
+        assert_eq!(
+            encoder
+                .decode(&content.content, &content.content_type)
+                .await?,
+            user()
+        );
+        // The schema is registered once, and the decoder uses the schema it was registered with.
+        encoder.encode(&user(), DEFINITION).await?;
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn decode_gets_schema_by_id() -> Result<()> {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let producer = create_encoder(requests.clone(), false);
+        let event = producer.encode_event(&user(), DEFINITION).await?;
+        assert_eq!(event.content_type(), Some("avro/binary+abc123"));
+
+        let consumer = create_encoder(requests.clone(), false);
+        let received = ReceivedEventData::from(AmqpMessage::from(event));
+        assert_eq!(consumer.decode_event(&received).await?, user());
+        assert_eq!(consumer.decode_event(&received).await?, user());
+        // One request to look up the ID, and one to get the schema by ID.
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn decode_rejects_other_content_types() {
+        let encoder = create_encoder(Arc::new(AtomicUsize::new(0)), false);
+        for content_type in ["application/json", "avro/binary+", "avro/binary"] {
+            let error = encoder.decode(&[], content_type).await.unwrap_err();
+            assert_eq!(*error.kind(), ErrorKind::DataConversion, "{content_type}");
+        }
+        let event = EventData::builder().with_body(vec![0u8]).build();
+        assert!(encoder.decode_event_data(&event).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn encode_requires_named_schema_and_group() {
+        let encoder = create_encoder(Arc::new(AtomicUsize::new(0)), true);
+        assert!(encoder.encode(&"a".into(), r#""string""#).await.is_err());
+
+        let client = Arc::clone(&encoder.client);
+        let encoder = AvroEncoder::new(client, None);
+        assert!(encoder.encode(&user(), DEFINITION).await.is_err());
+    }
+}
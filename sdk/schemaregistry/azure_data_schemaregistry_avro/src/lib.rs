@@ -0,0 +1,13 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+#![doc = include_str!("../README.md")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod codec;
+mod encoder;
+mod schema;
+mod value;
+
+pub use encoder::{AvroEncoder, AvroEncoderOptions, MessageContent};
+pub use value::AvroValue;
@@ -0,0 +1,376 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Parsing of Avro schemas.
+//!
+//! Only the parts of a schema that determine the binary encoding are kept. Attributes such as
+//! `doc`, `aliases`, and `logicalType` are accepted and ignored, so a value of a logical type is
+//! the value of its underlying type.
+
+use azure_core::{error::ErrorKind, Error, Result};
+use serde_json::{Map, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+
+/// An Avro schema.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Schema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Record(Record),
+    Enum(Enum),
+    Fixed(Fixed),
+    Array(Box<Schema>),
+    Map(Box<Schema>),
+    Union(Vec<Schema>),
+    /// A reference by full name to a named type that is defined elsewhere in the schema.
+    Ref(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Record {
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Field {
+    pub name: String,
+    pub schema: Schema,
+    pub default: Option<JsonValue>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Enum {
+    pub name: String,
+    pub symbols: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Fixed {
+    pub name: String,
+    pub size: usize,
+}
+
+/// A parsed schema, with the named types it defines.
+#[derive(Clone, Debug)]
+pub(crate) struct AvroSchema {
+    pub root: Schema,
+    names: HashMap<String, Schema>,
+}
+
+impl AvroSchema {
+    /// Parses the JSON definition of a schema.
+    pub fn parse(definition: &str) -> Result<Self> {
+        let json: JsonValue = serde_json::from_str(definition).map_err(|e| {
+            Error::with_error(ErrorKind::DataConversion, e, "invalid Avro schema JSON")
+        })?;
+        let mut parser = Parser::default();
+        let root = parser.parse(&json, None)?;
+        Ok(Self {
+            root,
+            names: parser.names,
+        })
+    }
+
+    /// The full name of the schema, if it is a named type.
+    pub fn full_name(&self) -> Option<&str> {
+        match &self.root {
+            Schema::Record(Record { name, .. })
+            | Schema::Enum(Enum { name, .. })
+            | Schema::Fixed(Fixed { name, .. }) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Resolves a reference to the named type it refers to.
+    pub fn resolve<'a>(&'a self, schema: &'a Schema) -> Result<&'a Schema> {
+        match schema {
+            Schema::Ref(name) => self
+                .names
+                .get(name)
+                .ok_or_else(|| invalid_schema(format!("unknown type {name}"))),
+            schema => Ok(schema),
+        }
+    }
+}
+
+pub(crate) fn invalid_schema(message: impl Into<String>) -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        format!("invalid Avro schema: {}", message.into()),
+    )
+}
+
+#[derive(Default)]
+struct Parser {
+    names: HashMap<String, Schema>,
+    /// The named types whose definition is being parsed, which their own fields may refer to.
+    defining: HashSet<String>,
+}
+
+impl Parser {
+    fn parse(&mut self, json: &JsonValue, namespace: Option<&str>) -> Result<Schema> {
+        match json {
+            JsonValue::String(name) => self.parse_name(name, namespace),
+            JsonValue::Array(branches) => {
+                let branches = branches
+                    .iter()
+                    .map(|branch| self.parse(branch, namespace))
+                    .collect::<Result<Vec<_>>>()?;
+                if branches
+                    .iter()
+                    .any(|branch| matches!(branch, Schema::Union(_)))
+                {
+                    return Err(invalid_schema("a union cannot contain a union"));
+                }
+                Ok(Schema::Union(branches))
+            }
+            JsonValue::Object(object) => self.parse_object(object, namespace),
+            _ => Err(invalid_schema(format!("unexpected {json}"))),
+        }
+    }
+
+    fn parse_name(&mut self, name: &str, namespace: Option<&str>) -> Result<Schema> {
+        if let Some(schema) = primitive(name) {
+            return Ok(schema);
+        }
+        let full_name = full_name(name, None, namespace);
+        for candidate in [full_name.as_str(), name] {
+            if self.names.contains_key(candidate) || self.defining.contains(candidate) {
+                return Ok(Schema::Ref(candidate.to_string()));
+            }
+        }
+        Err(invalid_schema(format!("unknown type {name}")))
+    }
+
+    fn parse_object(
+        &mut self,
+        object: &Map<String, JsonValue>,
+        namespace: Option<&str>,
+    ) -> Result<Schema> {
+        let type_name = match object.get("type") {
+            Some(JsonValue::String(type_name)) => type_name.as_str(),
+            // A type can be given as a nested schema, such as `{"type": {"type": "int"}}`.
+            Some(nested) => return self.parse(nested, namespace),
+            None => return Err(invalid_schema("missing type")),
+        };
+        match type_name {
+            "record" | "error" => {
+                let name = self.define_name(object, namespace)?;
+                let record_namespace = name.rsplit_once('.').map(|(namespace, _)| namespace);
+                self.defining.insert(name.clone());
+                let fields = object
+                    .get("fields")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(|| invalid_schema(format!("record {name} has no fields")))?
+                    .iter()
+                    .map(|field| self.parse_field(field, record_namespace))
+                    .collect::<Result<Vec<_>>>();
+                self.defining.remove(&name);
+                let schema = Schema::Record(Record {
+                    name: name.clone(),
+                    fields: fields?,
+                });
+                self.names.insert(name, schema.clone());
+                Ok(schema)
+            }
+            "enum" => {
+                let name = self.define_name(object, namespace)?;
+                let symbols = object
+                    .get("symbols")
+                    .and_then(JsonValue::as_array)
+                    .ok_or_else(|| invalid_schema(format!("enum {name} has no symbols")))?
+                    .iter()
+                    .map(|symbol| {
+                        symbol.as_str().map(str::to_string).ok_or_else(|| {
+                            invalid_schema(format!("enum {name} has a symbol that is not a string"))
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let schema = Schema::Enum(Enum {
+                    name: name.clone(),
+                    symbols,
+                });
+                self.names.insert(name, schema.clone());
+                Ok(schema)
+            }
+            "fixed" => {
+                let name = self.define_name(object, namespace)?;
+                let size = object
+                    .get("size")
+                    .and_then(JsonValue::as_u64)
+                    .ok_or_else(|| invalid_schema(format!("fixed {name} has no size")))?;
+                let schema = Schema::Fixed(Fixed {
+                    name: name.clone(),
+                    size: size as usize,
+                });
+                self.names.insert(name, schema.clone());
+                Ok(schema)
+            }
+            "array" => {
+                let items = object
+                    .get("items")
+                    .ok_or_else(|| invalid_schema("array has no items"))?;
+                Ok(Schema::Array(Box::new(self.parse(items, namespace)?)))
+            }
+            "map" => {
+                let values = object
+                    .get("values")
+                    .ok_or_else(|| invalid_schema("map has no values"))?;
+                Ok(Schema::Map(Box::new(self.parse(values, namespace)?)))
+            }
+            name => self.parse_name(name, namespace),
+        }
+    }
+
+    fn parse_field(&mut self, json: &JsonValue, namespace: Option<&str>) -> Result<Field> {
+        let name = json
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| invalid_schema("a field has no name"))?;
+        let schema = json
+            .get("type")
+            .ok_or_else(|| invalid_schema(format!("field {name} has no type")))?;
+        Ok(Field {
+            name: name.to_string(),
+            schema: self.parse(schema, namespace)?,
+            default: json.get("default").cloned(),
+        })
+    }
+
+    /// Returns the full name of a named type that is being defined.
+    fn define_name(
+        &self,
+        object: &Map<String, JsonValue>,
+        namespace: Option<&str>,
+    ) -> Result<String> {
+        let name = object
+            .get("name")
+            .and_then(JsonValue::as_str)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| invalid_schema("a named type has no name"))?;
+        let name = full_name(
+            name,
+            object.get("namespace").and_then(JsonValue::as_str),
+            namespace,
+        );
+        if primitive(&name).is_some()
+            || self.names.contains_key(&name)
+            || self.defining.contains(&name)
+        {
+            return Err(invalid_schema(format!("{name} is defined more than once")));
+        }
+        Ok(name)
+    }
+}
+
+fn primitive(name: &str) -> Option<Schema> {
+    Some(match name {
+        "null" => Schema::Null,
+        "boolean" => Schema::Boolean,
+        "int" => Schema::Int,
+        "long" => Schema::Long,
+        "float" => Schema::Float,
+        "double" => Schema::Double,
+        "bytes" => Schema::Bytes,
+        "string" => Schema::String,
+        _ => return None,
+    })
+}
+
+fn full_name(name: &str, namespace: Option<&str>, enclosing_namespace: Option<&str>) -> String {
+    if name.contains('.') {
+        return name.to_string();
+    }
+    match namespace.or(enclosing_namespace) {
+        Some(namespace) if !namespace.is_empty() => format!("{namespace}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_primitives() {
+        assert_eq!(AvroSchema::parse(r#""long""#).unwrap().root, Schema::Long);
+        assert_eq!(
+            AvroSchema::parse(r#"{"type": "string", "logicalType": "uuid"}"#)
+                .unwrap()
+                .root,
+            Schema::String
+        );
+        assert_eq!(
+            AvroSchema::parse(r#"["null", "int"]"#).unwrap().root,
+            Schema::Union(vec![Schema::Null, Schema::Int])
+        );
+    }
+
+    #[test]
+    fn parse_named_types_with_namespaces() {
+        let schema = AvroSchema::parse(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "namespace": "com.example",
+                "fields": [
+                    {"name": "color", "type": {"type": "enum", "name": "Color", "symbols": ["RED", "BLUE"]}},
+                    {"name": "other", "type": "Color"},
+                    {"name": "hash", "type": {"type": "fixed", "name": "org.other.Hash", "size": 16}},
+                    {"name": "next", "type": ["null", "User"], "default": null}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schema.full_name(), Some("com.example.User"));
+        let Schema::Record(record) = &schema.root else {
+            panic!("expected a record");
+        };
+        assert!(matches!(
+            &record.fields[0].schema,
+            Schema::Enum(Enum { name, .. }) if name == "com.example.Color"
+        ));
+        assert_eq!(
+            record.fields[1].schema,
+            Schema::Ref("com.example.Color".to_string())
+        );
+        assert!(matches!(
+            &record.fields[2].schema,
+            Schema::Fixed(Fixed { name, size: 16 }) if name == "org.other.Hash"
+        ));
+        assert_eq!(
+            record.fields[3].schema,
+            Schema::Union(vec![
+                Schema::Null,
+                Schema::Ref("com.example.User".to_string())
+            ])
+        );
+        assert_eq!(record.fields[3].default, Some(JsonValue::Null));
+        assert!(matches!(
+            schema.resolve(&record.fields[1].schema).unwrap(),
+            Schema::Enum(_)
+        ));
+    }
+
+    #[test]
+    fn invalid_schemas() {
+        for definition in [
+            "not json",
+            r#""Unknown""#,
+            r#"{"type": "record", "name": "R"}"#,
+            r#"{"type": "array"}"#,
+            r#"[["null"], "int"]"#,
+            r#"{"type": "record", "name": "R", "fields": [{"name": "a", "type": {"type": "enum", "name": "R", "symbols": []}}]}"#,
+        ] {
+            let error = AvroSchema::parse(definition).unwrap_err();
+            assert_eq!(*error.kind(), ErrorKind::DataConversion, "{definition}");
+        }
+    }
+}
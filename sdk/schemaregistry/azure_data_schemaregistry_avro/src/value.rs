@@ -0,0 +1,110 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use std::collections::HashMap;
+
+/// A value of an Avro schema.
+///
+/// A value of a union is the value of one of its branches. When encoding, the first branch the
+/// value matches is written. A value of a logical type, such as `timestamp-millis`, is the value
+/// of its underlying type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AvroValue {
+    /// A value of the `null` type.
+    Null,
+    /// A value of the `boolean` type.
+    Boolean(bool),
+    /// A value of the `int` type.
+    Int(i32),
+    /// A value of the `long` type.
+    Long(i64),
+    /// A value of the `float` type.
+    Float(f32),
+    /// A value of the `double` type.
+    Double(f64),
+    /// A value of the `bytes` type.
+    Bytes(Vec<u8>),
+    /// A value of the `string` type.
+    String(String),
+    /// A value of a `fixed` type.
+    Fixed(Vec<u8>),
+    /// A symbol of an `enum` type.
+    Enum(String),
+    /// A value of an `array` type.
+    Array(Vec<AvroValue>),
+    /// A value of a `map` type.
+    Map(HashMap<String, AvroValue>),
+    /// A value of a `record` type, as its fields in the order of the schema.
+    ///
+    /// When encoding, the fields can be in any order, and a field the schema has a default for
+    /// can be left out.
+    Record(Vec<(String, AvroValue)>),
+}
+
+impl AvroValue {
+    /// Returns the value of a field of a record, or `None` if this is not a record or the record
+    /// has no such field.
+    pub fn field(&self, name: &str) -> Option<&AvroValue> {
+        match self {
+            AvroValue::Record(fields) => fields
+                .iter()
+                .find(|(field_name, _)| field_name == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for AvroValue {
+    fn from(value: bool) -> Self {
+        AvroValue::Boolean(value)
+    }
+}
+
+impl From<i32> for AvroValue {
+    fn from(value: i32) -> Self {
+        AvroValue::Int(value)
+    }
+}
+
+impl From<i64> for AvroValue {
+    fn from(value: i64) -> Self {
+        AvroValue::Long(value)
+    }
+}
+
+impl From<f32> for AvroValue {
+    fn from(value: f32) -> Self {
+        AvroValue::Float(value)
+    }
+}
+
+impl From<f64> for AvroValue {
+    fn from(value: f64) -> Self {
+        AvroValue::Double(value)
+    }
+}
+
+impl From<Vec<u8>> for AvroValue {
+    fn from(value: Vec<u8>) -> Self {
+        AvroValue::Bytes(value)
+    }
+}
+
+impl From<String> for AvroValue {
+    fn from(value: String) -> Self {
+        AvroValue::String(value)
+    }
+}
+
+impl From<&str> for AvroValue {
+    fn from(value: &str) -> Self {
+        AvroValue::String(value.to_string())
+    }
+}
+
+impl<T: Into<AvroValue>> From<Option<T>> for AvroValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(AvroValue::Null, Into::into)
+    }
+}
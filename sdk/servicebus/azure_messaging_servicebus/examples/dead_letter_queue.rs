@@ -67,6 +67,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "Message could not be processed due to invalid format".to_string(),
             ),
             properties_to_modify: None,
+            transaction: None,
         };
         receiver
             .dead_letter_message(&received_message, Some(dead_letter_options))
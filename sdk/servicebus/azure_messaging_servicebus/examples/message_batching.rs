@@ -234,7 +234,7 @@ async fn batching_with_properties(
         message.set_subject(format!("Batch Message {}", i));
 
         // Set custom properties
-        message.set_property("batch_id", &batch_id);
+        message.set_property("batch_id", batch_id.as_str());
         message.set_property("sequence", i.to_string());
         message.set_property("priority", if i % 2 == 0 { "high" } else { "normal" });
         message.set_property("category", "demo");
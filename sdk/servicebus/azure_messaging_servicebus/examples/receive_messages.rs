@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
                     // Print custom properties
                     for (key, value) in message.properties() {
-                        println!("Property {}: {}", key, value);
+                        println!("Property {}: {:?}", key, value);
                     }
 
                     // Complete the message to remove it from the queue
@@ -88,6 +88,7 @@ async fn demonstrate_queue_subqueues(
                 "Message dead lettered to demonstrate SubQueue enum".to_string(),
             ),
             properties_to_modify: None,
+            transaction: None,
         };
         receiver
             .dead_letter_message(&received_message, Some(dead_letter_options))
@@ -198,6 +199,7 @@ async fn demonstrate_subscription_subqueues(
                 "Subscription message dead lettered to demonstrate SubQueue enum".to_string(),
             ),
             properties_to_modify: None,
+            transaction: None,
         };
         receiver
             .dead_letter_message(&received_message, Some(dead_letter_options))
@@ -208,10 +208,10 @@ async fn receive_test_messages(
 
         // Get custom properties if they exist
         if let Some(credential_type) = message.property("credential_type") {
-            println!("  - Credential type: {}", credential_type);
+            println!("  - Credential type: {:?}", credential_type);
         }
         if let Some(timestamp) = message.property("timestamp") {
-            println!("  - Timestamp: {}", timestamp);
+            println!("  - Timestamp: {:?}", timestamp);
         }
     }
 
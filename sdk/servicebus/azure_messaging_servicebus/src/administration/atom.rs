@@ -0,0 +1,664 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! ATOM envelopes and XML representations used by the Service Bus management REST API.
+
+use super::models::{
+    CorrelationRuleFilter, QueueProperties, RuleAction, RuleFilter, RuleProperties,
+    SubscriptionProperties, TopicProperties,
+};
+use crate::{
+    models::{
+        MessagingSku, NamespaceProperties, NamespaceType, QueueRuntimeProperties,
+        SubscriptionRuntimeProperties, TopicRuntimeProperties,
+    },
+    ErrorKind, Result, ServiceBusError,
+};
+use azure_core::time::OffsetDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use time::format_description::well_known::Rfc3339;
+
+const ATOM_NAMESPACE: &str = "http://www.w3.org/2005/Atom";
+const SERVICEBUS_NAMESPACE: &str =
+    "http://schemas.microsoft.com/netservices/2010/10/servicebus/connect";
+const XML_SCHEMA_INSTANCE_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+const XML_SCHEMA_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema";
+
+/// The compatibility level of SQL filter and action expressions.
+const SQL_COMPATIBILITY_LEVEL: i32 = 20;
+
+/// An entry sent to create or update an entity.
+#[derive(Serialize)]
+#[serde(rename = "entry")]
+pub(crate) struct RequestEntry<'a> {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    content: RequestContent<'a>,
+}
+
+impl<'a> RequestEntry<'a> {
+    pub(crate) fn new(description: Description<'a>) -> Self {
+        Self {
+            xmlns: ATOM_NAMESPACE,
+            content: RequestContent {
+                content_type: "application/xml",
+                description,
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RequestContent<'a> {
+    #[serde(rename = "@type")]
+    content_type: &'static str,
+    #[serde(rename = "$value")]
+    description: Description<'a>,
+}
+
+/// The description of an entity, serialized as an element named after the variant.
+#[derive(Serialize)]
+pub(crate) enum Description<'a> {
+    QueueDescription(Described<&'a QueueProperties>),
+    TopicDescription(Described<&'a TopicProperties>),
+    SubscriptionDescription(Described<&'a SubscriptionProperties>),
+    RuleDescription(Described<&'a RuleDescription>),
+}
+
+/// Adds the namespace declarations required on a description element.
+#[derive(Serialize)]
+pub(crate) struct Described<T> {
+    #[serde(rename = "@xmlns")]
+    xmlns: &'static str,
+    #[serde(rename = "@xmlns:i")]
+    xmlns_i: &'static str,
+    #[serde(flatten)]
+    inner: T,
+}
+
+impl<T> From<T> for Described<T> {
+    fn from(inner: T) -> Self {
+        Self {
+            xmlns: SERVICEBUS_NAMESPACE,
+            xmlns_i: XML_SCHEMA_INSTANCE_NAMESPACE,
+            inner,
+        }
+    }
+}
+
+/// An entry returned by the service.
+#[derive(Deserialize)]
+pub(crate) struct ResponseEntry<T> {
+    pub(crate) title: Option<String>,
+    content: Option<ResponseContent<T>>,
+}
+
+#[derive(Deserialize)]
+struct ResponseContent<T> {
+    #[serde(rename = "$value")]
+    description: T,
+}
+
+impl<T> ResponseEntry<T> {
+    /// Returns the name and description of the entity.
+    ///
+    /// The service returns an empty feed instead of an entry when the entity does not exist.
+    pub(crate) fn into_description(self, entity: &str) -> Result<(Option<String>, T)> {
+        match self.content {
+            Some(content) => Ok((self.title, content.description)),
+            None => Err(ServiceBusError::new(
+                ErrorKind::EntityNotFound,
+                format!("{entity} was not found"),
+            )),
+        }
+    }
+}
+
+/// A feed of entries returned by the service.
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub(crate) struct Feed<T> {
+    #[serde(default)]
+    pub(crate) entry: Vec<ResponseEntry<T>>,
+}
+
+/// Runtime information included in queue, topic and subscription descriptions.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct RuntimeInfo {
+    size_in_bytes: Option<i64>,
+    message_count: Option<i64>,
+    subscription_count: Option<i32>,
+    count_details: Option<CountDetails>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    accessed_at: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+struct CountDetails {
+    active_message_count: Option<i64>,
+    dead_letter_message_count: Option<i64>,
+    scheduled_message_count: Option<i64>,
+    transfer_message_count: Option<i64>,
+    transfer_dead_letter_message_count: Option<i64>,
+}
+
+impl RuntimeInfo {
+    pub(crate) fn into_queue(self, queue_name: Option<String>) -> QueueRuntimeProperties {
+        let counts = self.count_details.unwrap_or_default();
+        QueueRuntimeProperties {
+            queue_name,
+            size_in_bytes: self.size_in_bytes,
+            total_message_count: self.message_count,
+            active_message_count: counts.active_message_count,
+            dead_letter_message_count: counts.dead_letter_message_count,
+            scheduled_message_count: counts.scheduled_message_count,
+            transfer_message_count: counts.transfer_message_count,
+            transfer_dead_letter_message_count: counts.transfer_dead_letter_message_count,
+            created_at: parse_time(self.created_at),
+            updated_at: parse_time(self.updated_at),
+            accessed_at: parse_time(self.accessed_at),
+        }
+    }
+
+    pub(crate) fn into_topic(self, topic_name: Option<String>) -> TopicRuntimeProperties {
+        let counts = self.count_details.unwrap_or_default();
+        TopicRuntimeProperties {
+            topic_name,
+            size_in_bytes: self.size_in_bytes,
+            subscription_count: self.subscription_count,
+            scheduled_message_count: counts.scheduled_message_count,
+            created_at: parse_time(self.created_at),
+            updated_at: parse_time(self.updated_at),
+            accessed_at: parse_time(self.accessed_at),
+        }
+    }
+
+    pub(crate) fn into_subscription(
+        self,
+        topic_name: &str,
+        subscription_name: Option<String>,
+    ) -> SubscriptionRuntimeProperties {
+        let counts = self.count_details.unwrap_or_default();
+        SubscriptionRuntimeProperties {
+            topic_name: Some(topic_name.to_string()),
+            subscription_name,
+            total_message_count: self.message_count,
+            active_message_count: counts.active_message_count,
+            dead_letter_message_count: counts.dead_letter_message_count,
+            transfer_message_count: counts.transfer_message_count,
+            transfer_dead_letter_message_count: counts.transfer_dead_letter_message_count,
+            created_at: parse_time(self.created_at),
+            updated_at: parse_time(self.updated_at),
+            accessed_at: parse_time(self.accessed_at),
+        }
+    }
+}
+
+/// The namespace information returned by the service.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct NamespaceInfo {
+    name: Option<String>,
+    namespace_type: Option<NamespaceType>,
+    created_time: Option<String>,
+    modified_time: Option<String>,
+    #[serde(rename = "MessagingSKU")]
+    messaging_sku: Option<MessagingSku>,
+    messaging_units: Option<i32>,
+}
+
+impl From<NamespaceInfo> for NamespaceProperties {
+    fn from(info: NamespaceInfo) -> Self {
+        NamespaceProperties {
+            namespace_name: info.name,
+            namespace_type: info.namespace_type,
+            created_at: parse_time(info.created_time),
+            modified_at: parse_time(info.modified_time),
+            messaging_sku: info.messaging_sku,
+            messaging_units: info.messaging_units,
+        }
+    }
+}
+
+/// The XML representation of a rule.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+pub(crate) struct RuleDescription {
+    filter: Option<FilterDescription>,
+    action: Option<ActionDescription>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+struct FilterDescription {
+    #[serde(rename(serialize = "@i:type", deserialize = "@type"))]
+    filter_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sql_expression: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compatibility_level: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    correlation_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_to_session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<PropertiesDescription>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct PropertiesDescription {
+    #[serde(rename = "KeyValueOfstringanyType", default)]
+    entries: Vec<KeyValueDescription>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct KeyValueDescription {
+    key: String,
+    value: ValueDescription,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ValueDescription {
+    #[serde(rename(serialize = "@i:type", deserialize = "@type"))]
+    value_type: String,
+    #[serde(rename = "@xmlns:d6p1", skip_deserializing)]
+    xmlns: &'static str,
+    #[serde(rename = "$text", default)]
+    value: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+struct ActionDescription {
+    #[serde(rename(serialize = "@i:type", deserialize = "@type"))]
+    action_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sql_expression: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compatibility_level: Option<i32>,
+}
+
+impl From<&RuleProperties> for RuleDescription {
+    fn from(rule: &RuleProperties) -> Self {
+        let filter = match rule.filter.as_ref().unwrap_or(&RuleFilter::True) {
+            RuleFilter::Sql(expression) => sql_filter("SqlFilter", expression),
+            RuleFilter::True => sql_filter("TrueFilter", "1=1"),
+            RuleFilter::False => sql_filter("FalseFilter", "1=0"),
+            RuleFilter::Correlation(filter) => FilterDescription {
+                filter_type: "CorrelationFilter".to_string(),
+                correlation_id: filter.correlation_id.clone(),
+                message_id: filter.message_id.clone(),
+                to: filter.to.clone(),
+                reply_to: filter.reply_to.clone(),
+                label: filter.subject.clone(),
+                session_id: filter.session_id.clone(),
+                reply_to_session_id: filter.reply_to_session_id.clone(),
+                content_type: filter.content_type.clone(),
+                properties: (!filter.application_properties.is_empty()).then(|| {
+                    PropertiesDescription {
+                        entries: filter
+                            .application_properties
+                            .iter()
+                            .map(|(key, value)| KeyValueDescription {
+                                key: key.clone(),
+                                value: ValueDescription {
+                                    value_type: "d6p1:string".to_string(),
+                                    xmlns: XML_SCHEMA_NAMESPACE,
+                                    value: value.clone(),
+                                },
+                            })
+                            .collect(),
+                    }
+                }),
+                ..Default::default()
+            },
+        };
+        let action = match &rule.action {
+            Some(RuleAction::Sql(expression)) => ActionDescription {
+                action_type: "SqlRuleAction".to_string(),
+                sql_expression: Some(expression.clone()),
+                compatibility_level: Some(SQL_COMPATIBILITY_LEVEL),
+            },
+            None => ActionDescription {
+                action_type: "EmptyRuleAction".to_string(),
+                ..Default::default()
+            },
+        };
+        RuleDescription {
+            filter: Some(filter),
+            action: Some(action),
+            name: rule.rule_name.clone(),
+        }
+    }
+}
+
+impl RuleDescription {
+    pub(crate) fn into_rule(self, rule_name: Option<String>) -> RuleProperties {
+        let filter = self.filter.map(|filter| match filter.filter_type.as_str() {
+            "TrueFilter" => RuleFilter::True,
+            "FalseFilter" => RuleFilter::False,
+            "CorrelationFilter" => RuleFilter::Correlation(Box::new(CorrelationRuleFilter {
+                correlation_id: filter.correlation_id,
+                message_id: filter.message_id,
+                to: filter.to,
+                reply_to: filter.reply_to,
+                subject: filter.label,
+                session_id: filter.session_id,
+                reply_to_session_id: filter.reply_to_session_id,
+                content_type: filter.content_type,
+                application_properties: filter
+                    .properties
+                    .map(|properties| {
+                        properties
+                            .entries
+                            .into_iter()
+                            .map(|entry| (entry.key, entry.value.value))
+                            .collect()
+                    })
+                    .unwrap_or_else(HashMap::new),
+            })),
+            _ => RuleFilter::Sql(filter.sql_expression.unwrap_or_default()),
+        });
+        let action = self.action.and_then(|action| {
+            (action.action_type == "SqlRuleAction")
+                .then(|| RuleAction::Sql(action.sql_expression.unwrap_or_default()))
+        });
+        RuleProperties {
+            rule_name: self.name.or(rule_name),
+            filter,
+            action,
+        }
+    }
+}
+
+fn sql_filter(filter_type: &str, expression: &str) -> FilterDescription {
+    FilterDescription {
+        filter_type: filter_type.to_string(),
+        sql_expression: Some(expression.to_string()),
+        compatibility_level: Some(SQL_COMPATIBILITY_LEVEL),
+        ..Default::default()
+    }
+}
+
+/// Parses a timestamp returned by the service, which may omit the time zone.
+fn parse_time(value: Option<String>) -> Option<OffsetDateTime> {
+    let value = value?;
+    OffsetDateTime::parse(&value, &Rfc3339)
+        .or_else(|_| OffsetDateTime::parse(&format!("{value}Z"), &Rfc3339))
+        .ok()
+}
+
+/// Serializes durations in the ISO 8601 format used by the service, such as `PT1M` or `P14D`.
+pub(crate) mod iso8601 {
+    use azure_core::time::Duration;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => serializer.serialize_str(&format(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                parse(&value)
+                    .ok_or_else(|| de::Error::custom(format!("invalid ISO 8601 duration: {value}")))
+            })
+            .transpose()
+    }
+
+    pub(crate) fn format(duration: Duration) -> String {
+        let mut result = String::from("P");
+        let days = duration.whole_days();
+        if days > 0 {
+            result.push_str(&format!("{days}D"));
+        }
+        let remainder = duration - Duration::days(days);
+        if remainder.is_zero() {
+            if days == 0 {
+                result.push_str("T0S");
+            }
+            return result;
+        }
+        result.push('T');
+        let hours = remainder.whole_hours();
+        if hours > 0 {
+            result.push_str(&format!("{hours}H"));
+        }
+        let minutes = remainder.whole_minutes() % 60;
+        if minutes > 0 {
+            result.push_str(&format!("{minutes}M"));
+        }
+        let seconds = remainder.whole_seconds() % 60;
+        let nanoseconds = remainder.subsec_nanoseconds();
+        if nanoseconds > 0 {
+            let fraction = format!("{nanoseconds:09}");
+            result.push_str(&format!("{seconds}.{}S", fraction.trim_end_matches('0')));
+        } else if seconds > 0 {
+            result.push_str(&format!("{seconds}S"));
+        }
+        result
+    }
+
+    pub(crate) fn parse(value: &str) -> Option<Duration> {
+        let value = value.strip_prefix('P')?;
+        let (date, time) = value.split_once('T').unwrap_or((value, ""));
+        let mut duration = Duration::ZERO;
+        for (part, is_time) in [(date, false), (time, true)] {
+            let mut number = String::new();
+            for c in part.chars() {
+                if c.is_ascii_digit() || c == '.' {
+                    number.push(c);
+                    continue;
+                }
+                let amount: f64 = number.parse().ok()?;
+                number.clear();
+                let seconds = match (c, is_time) {
+                    ('W', false) => amount * 604_800.0,
+                    ('D', false) => amount * 86_400.0,
+                    ('H', true) => amount * 3_600.0,
+                    ('M', true) => amount * 60.0,
+                    ('S', true) => amount,
+                    _ => return None,
+                };
+                duration += Duration::checked_seconds_f64(seconds)?;
+            }
+            if !number.is_empty() {
+                return None;
+            }
+        }
+        Some(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{time::Duration, xml};
+
+    #[test]
+    fn formats_and_parses_durations() {
+        for (duration, text) in [
+            (Duration::minutes(1), "PT1M"),
+            (Duration::days(14), "P14D"),
+            (Duration::seconds(30), "PT30S"),
+            (Duration::ZERO, "PT0S"),
+            (
+                Duration::days(1) + Duration::hours(2) + Duration::milliseconds(3500),
+                "P1DT2H3.5S",
+            ),
+        ] {
+            assert_eq!(iso8601::format(duration), text);
+            assert_eq!(iso8601::parse(text), Some(duration));
+        }
+        assert_eq!(
+            iso8601::parse("P10675199DT2H48M5.4775807S").map(|d| d.whole_days()),
+            Some(10675199)
+        );
+        assert_eq!(iso8601::parse("1M"), None);
+        assert_eq!(iso8601::parse("PT1X"), None);
+    }
+
+    #[test]
+    fn serializes_queue_entry() {
+        let properties = QueueProperties {
+            lock_duration: Some(Duration::seconds(45)),
+            max_delivery_count: Some(5),
+            requires_session: Some(true),
+            ..Default::default()
+        };
+        let body = xml::to_xml(&RequestEntry::new(Description::QueueDescription(
+            (&properties).into(),
+        )))
+        .unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains(
+            r#"<entry xmlns="http://www.w3.org/2005/Atom"><content type="application/xml"><QueueDescription xmlns="http://schemas.microsoft.com/netservices/2010/10/servicebus/connect" xmlns:i="http://www.w3.org/2001/XMLSchema-instance"><LockDuration>PT45S</LockDuration><RequiresSession>true</RequiresSession><MaxDeliveryCount>5</MaxDeliveryCount></QueueDescription></content></entry>"#
+        ));
+    }
+
+    #[test]
+    fn parses_queue_feed() {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="text">Queues</title>
+  <entry>
+    <title type="text">orders</title>
+    <content type="application/xml">
+      <QueueDescription xmlns="http://schemas.microsoft.com/netservices/2010/10/servicebus/connect" xmlns:i="http://www.w3.org/2001/XMLSchema-instance">
+        <LockDuration>PT1M</LockDuration>
+        <MaxSizeInMegabytes>1024</MaxSizeInMegabytes>
+        <SizeInBytes>2048</SizeInBytes>
+        <MessageCount>7</MessageCount>
+        <Status>Active</Status>
+        <CreatedAt>2024-05-01T10:00:00.1234567Z</CreatedAt>
+        <AccessedAt>0001-01-01T00:00:00</AccessedAt>
+        <CountDetails xmlns:d2p1="http://schemas.microsoft.com/netservices/2011/06/servicebus">
+          <d2p1:ActiveMessageCount>5</d2p1:ActiveMessageCount>
+          <d2p1:DeadLetterMessageCount>2</d2p1:DeadLetterMessageCount>
+        </CountDetails>
+      </QueueDescription>
+    </content>
+  </entry>
+</feed>"#;
+        let feed: Feed<QueueProperties> = xml::from_xml(body).unwrap();
+        let (name, properties) = feed
+            .entry
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_description("queue")
+            .unwrap();
+        assert_eq!(name.as_deref(), Some("orders"));
+        assert_eq!(properties.lock_duration, Some(Duration::minutes(1)));
+        assert_eq!(properties.max_size_in_megabytes, Some(1024));
+
+        let feed: Feed<RuntimeInfo> = xml::from_xml(body).unwrap();
+        let (name, info) = feed
+            .entry
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_description("queue")
+            .unwrap();
+        let runtime = info.into_queue(name);
+        assert_eq!(runtime.queue_name.as_deref(), Some("orders"));
+        assert_eq!(runtime.size_in_bytes, Some(2048));
+        assert_eq!(runtime.total_message_count, Some(7));
+        assert_eq!(runtime.active_message_count, Some(5));
+        assert_eq!(runtime.dead_letter_message_count, Some(2));
+        assert_eq!(
+            runtime.created_at.map(|t| t.unix_timestamp()),
+            Some(1714557600)
+        );
+        assert!(runtime.accessed_at.is_some());
+    }
+
+    #[test]
+    fn empty_feed_is_not_found() {
+        let entry: ResponseEntry<QueueProperties> =
+            xml::from_xml(r#"<feed xmlns="http://www.w3.org/2005/Atom"><title type="text">Publicly Listed Services</title></feed>"#)
+                .unwrap();
+        let error = entry.into_description("queue 'missing'").unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::EntityNotFound);
+    }
+
+    #[test]
+    fn rule_round_trip() {
+        let rule = RuleProperties {
+            rule_name: Some("high-priority".to_string()),
+            filter: Some(RuleFilter::Correlation(Box::new(CorrelationRuleFilter {
+                subject: Some("orders".to_string()),
+                application_properties: HashMap::from([(
+                    "priority".to_string(),
+                    "high".to_string(),
+                )]),
+                ..Default::default()
+            }))),
+            action: Some(RuleAction::Sql("SET sys.Label = 'routed'".to_string())),
+        };
+        let body = xml::to_xml(&RequestEntry::new(Description::RuleDescription(
+            Described::from(&RuleDescription::from(&rule)),
+        )))
+        .unwrap();
+        let text = std::str::from_utf8(&body).unwrap();
+        assert!(text.contains(r#"<Filter i:type="CorrelationFilter"><Label>orders</Label>"#));
+        assert!(text.contains(r#"<Value i:type="d6p1:string" xmlns:d6p1="http://www.w3.org/2001/XMLSchema">high</Value>"#));
+        assert!(text.contains(r#"<Action i:type="SqlRuleAction">"#));
+        assert!(text.contains("<Name>high-priority</Name>"));
+
+        // The request entry has the same shape as a response entry.
+        let entry: ResponseEntry<RuleDescription> = xml::from_xml(&body).unwrap();
+        let (_, description) = entry.into_description("rule").unwrap();
+        let parsed = description.into_rule(None);
+        assert_eq!(parsed.rule_name, rule.rule_name);
+        assert_eq!(parsed.filter, rule.filter);
+        assert_eq!(parsed.action, rule.action);
+    }
+
+    #[test]
+    fn sql_and_empty_rules() {
+        let rule = RuleProperties {
+            filter: Some(RuleFilter::Sql("color = 'red'".to_string())),
+            ..Default::default()
+        };
+        let description = RuleDescription::from(&rule);
+        let parsed = description.into_rule(Some("red".to_string()));
+        assert_eq!(parsed.rule_name.as_deref(), Some("red"));
+        assert_eq!(parsed.filter, rule.filter);
+        assert_eq!(parsed.action, None);
+
+        let parsed = RuleDescription::from(&RuleProperties::default()).into_rule(None);
+        assert_eq!(parsed.filter, Some(RuleFilter::True));
+    }
+}
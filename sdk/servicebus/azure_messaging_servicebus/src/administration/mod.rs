@@ -0,0 +1,1141 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Service Bus administration client.
+//!
+//! The [`ServiceBusAdministrationClient`] uses the Service Bus management REST API to create, update,
+//! delete and list queues, topics, subscriptions and rules, and to get their runtime properties,
+//! such as the number of active and dead-lettered messages.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use azure_messaging_servicebus::administration::{
+//!     QueueProperties, ServiceBusAdministrationClient,
+//! };
+//! use azure_core::time::Duration;
+//! use azure_identity::DeveloperToolsCredential;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let credential = DeveloperToolsCredential::new(None)?;
+//! let client = ServiceBusAdministrationClient::new("myservicebus.servicebus.windows.net", credential, None)?;
+//!
+//! let queue = client
+//!     .create_queue(
+//!         "orders",
+//!         Some(QueueProperties {
+//!             lock_duration: Some(Duration::seconds(30)),
+//!             max_delivery_count: Some(5),
+//!             ..Default::default()
+//!         }),
+//!         None,
+//!     )
+//!     .await?;
+//! println!("Created {:?}", queue.queue_name);
+//!
+//! let runtime = client.get_queue_runtime_properties("orders", None).await?;
+//! println!("{:?} active messages", runtime.active_message_count);
+//! # Ok(())
+//! # }
+//! ```
+
+mod atom;
+mod models;
+
+pub use models::{
+    CorrelationRuleFilter, QueueProperties, RuleAction, RuleFilter, RuleProperties,
+    SubscriptionProperties, TopicProperties,
+};
+
+use crate::{
+    models::{
+        NamespaceProperties, QueueRuntimeProperties, SubscriptionRuntimeProperties,
+        TopicRuntimeProperties,
+    },
+    ErrorKind, Result, ServiceBusError,
+};
+use atom::{
+    Described, Description, Feed, NamespaceInfo, RequestEntry, ResponseEntry, RuleDescription,
+    RuntimeInfo,
+};
+use azure_core::{
+    credentials::TokenCredential,
+    fmt::SafeDebug,
+    http::{
+        policies::{auth::BearerTokenAuthorizationPolicy, Policy},
+        ClientMethodOptions, ClientOptions, Context, Method, Pipeline, Request, Url,
+    },
+    xml,
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+const DEFAULT_API_VERSION: &str = "2021-05";
+const SERVICEBUS_AUTHORIZATION_SCOPE: &str = "https://servicebus.azure.net/.default";
+const ENTRY_CONTENT_TYPE: &str = "application/atom+xml;type=entry;charset=utf-8";
+
+/// The number of entities requested per page when listing all entities.
+const LIST_PAGE_SIZE: u32 = 100;
+
+/// Options for creating a [`ServiceBusAdministrationClient`].
+#[derive(Clone, SafeDebug)]
+pub struct ServiceBusAdministrationClientOptions {
+    /// The API version of the management REST API.
+    pub api_version: String,
+    /// Allows customization of the client.
+    pub client_options: ClientOptions,
+}
+
+impl Default for ServiceBusAdministrationClientOptions {
+    fn default() -> Self {
+        Self {
+            api_version: DEFAULT_API_VERSION.to_string(),
+            client_options: ClientOptions::default(),
+        }
+    }
+}
+
+/// Options for creating an entity.
+#[derive(Clone, Default, SafeDebug)]
+pub struct CreateEntityOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options for getting an entity or its runtime properties.
+#[derive(Clone, Default, SafeDebug)]
+pub struct GetEntityOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options for updating an entity.
+#[derive(Clone, Default, SafeDebug)]
+pub struct UpdateEntityOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options for deleting an entity.
+#[derive(Clone, Default, SafeDebug)]
+pub struct DeleteEntityOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options for listing entities.
+#[derive(Clone, Default, SafeDebug)]
+pub struct ListEntitiesOptions<'a> {
+    /// The number of entities to skip.
+    pub skip: Option<u32>,
+    /// The maximum number of entities to return. If `None`, all entities are returned.
+    pub top: Option<u32>,
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// Options for getting the properties of the namespace.
+#[derive(Clone, Default, SafeDebug)]
+pub struct GetNamespacePropertiesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+}
+
+/// A client for managing Service Bus queues, topics, subscriptions and rules.
+pub struct ServiceBusAdministrationClient {
+    endpoint: Url,
+    api_version: String,
+    pipeline: Pipeline,
+}
+
+impl ServiceBusAdministrationClient {
+    /// Creates a new `ServiceBusAdministrationClient` using Entra ID authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `fully_qualified_namespace` - The namespace, such as `myservicebus.servicebus.windows.net`
+    /// * `credential` - The credential used to authenticate requests
+    /// * `options` - Optional configuration for the client
+    pub fn new(
+        fully_qualified_namespace: &str,
+        credential: Arc<dyn TokenCredential>,
+        options: Option<ServiceBusAdministrationClientOptions>,
+    ) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        let endpoint =
+            Url::parse(&format!("https://{}/", fully_qualified_namespace)).map_err(|e| {
+                ServiceBusError::new(
+                    ErrorKind::InvalidRequest,
+                    format!("Invalid namespace: {}", e),
+                )
+            })?;
+        let auth_policy: Arc<dyn Policy> = Arc::new(BearerTokenAuthorizationPolicy::new(
+            credential,
+            [SERVICEBUS_AUTHORIZATION_SCOPE],
+        ));
+        Ok(Self {
+            endpoint,
+            api_version: options.api_version,
+            pipeline: Pipeline::new(
+                option_env!("CARGO_PKG_NAME"),
+                option_env!("CARGO_PKG_VERSION"),
+                options.client_options,
+                Vec::new(),
+                vec![auth_policy],
+                None,
+            ),
+        })
+    }
+
+    /// Gets the fully qualified namespace.
+    pub fn fully_qualified_namespace(&self) -> &str {
+        self.endpoint.host_str().unwrap_or_default()
+    }
+
+    /// Gets the properties of the namespace.
+    pub async fn get_namespace_properties(
+        &self,
+        options: Option<GetNamespacePropertiesOptions<'_>>,
+    ) -> Result<NamespaceProperties> {
+        let options = options.unwrap_or_default();
+        let (_, info): (_, NamespaceInfo) = self
+            .get_entry(
+                "$namespaceinfo",
+                "namespace",
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(info.into())
+    }
+
+    /// Creates a queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_name` - The name of the queue
+    /// * `properties` - The properties of the new queue. Properties that are not set take the service defaults.
+    /// * `options` - Optional configuration for the operation
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`ErrorKind::EntityAlreadyExists`] if the queue already exists.
+    pub async fn create_queue(
+        &self,
+        queue_name: &str,
+        properties: Option<QueueProperties>,
+        options: Option<CreateEntityOptions<'_>>,
+    ) -> Result<QueueProperties> {
+        let options = options.unwrap_or_default();
+        let properties = self.queue_with_forwarding(properties.unwrap_or_default());
+        let (name, properties) = self
+            .put_entry(
+                queue_name,
+                Description::QueueDescription(Described::from(&properties)),
+                false,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(QueueProperties {
+            queue_name: name.or_else(|| Some(queue_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Gets the properties of a queue.
+    pub async fn get_queue(
+        &self,
+        queue_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<QueueProperties> {
+        let options = options.unwrap_or_default();
+        let (name, properties): (_, QueueProperties) = self
+            .get_entry(
+                queue_name,
+                &format!("queue '{}'", queue_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(QueueProperties {
+            queue_name: name,
+            ..properties
+        })
+    }
+
+    /// Gets the runtime properties of a queue, such as its message counts.
+    pub async fn get_queue_runtime_properties(
+        &self,
+        queue_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<QueueRuntimeProperties> {
+        let options = options.unwrap_or_default();
+        let (name, info): (_, RuntimeInfo) = self
+            .get_entry(
+                queue_name,
+                &format!("queue '{}'", queue_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(info.into_queue(name))
+    }
+
+    /// Updates a queue, replacing all of its properties.
+    ///
+    /// Get the current properties with [`get_queue`](Self::get_queue) and modify them to avoid resetting other properties.
+    pub async fn update_queue(
+        &self,
+        queue_name: &str,
+        properties: QueueProperties,
+        options: Option<UpdateEntityOptions<'_>>,
+    ) -> Result<QueueProperties> {
+        let options = options.unwrap_or_default();
+        let properties = self.queue_with_forwarding(properties);
+        let (name, properties) = self
+            .put_entry(
+                queue_name,
+                Description::QueueDescription(Described::from(&properties)),
+                true,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(QueueProperties {
+            queue_name: name.or_else(|| Some(queue_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Deletes a queue.
+    pub async fn delete_queue(
+        &self,
+        queue_name: &str,
+        options: Option<DeleteEntityOptions<'_>>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        self.delete_entry(queue_name, &options.method_options.context)
+            .await
+    }
+
+    /// Lists the queues in the namespace.
+    pub async fn list_queues(
+        &self,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<QueueProperties>> {
+        let entries = self
+            .list_entries("$Resources/queues", options.unwrap_or_default())
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, properties)| QueueProperties {
+                queue_name: name,
+                ..properties
+            })
+            .collect())
+    }
+
+    /// Lists the runtime properties of the queues in the namespace.
+    pub async fn list_queues_runtime_properties(
+        &self,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<QueueRuntimeProperties>> {
+        let entries: Vec<(_, RuntimeInfo)> = self
+            .list_entries("$Resources/queues", options.unwrap_or_default())
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, info)| info.into_queue(name))
+            .collect())
+    }
+
+    /// Creates a topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `properties` - The properties of the new topic. Properties that are not set take the service defaults.
+    /// * `options` - Optional configuration for the operation
+    pub async fn create_topic(
+        &self,
+        topic_name: &str,
+        properties: Option<TopicProperties>,
+        options: Option<CreateEntityOptions<'_>>,
+    ) -> Result<TopicProperties> {
+        let options = options.unwrap_or_default();
+        let properties = properties.unwrap_or_default();
+        let (name, properties) = self
+            .put_entry(
+                topic_name,
+                Description::TopicDescription(Described::from(&properties)),
+                false,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(TopicProperties {
+            topic_name: name.or_else(|| Some(topic_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Gets the properties of a topic.
+    pub async fn get_topic(
+        &self,
+        topic_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<TopicProperties> {
+        let options = options.unwrap_or_default();
+        let (name, properties): (_, TopicProperties) = self
+            .get_entry(
+                topic_name,
+                &format!("topic '{}'", topic_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(TopicProperties {
+            topic_name: name,
+            ..properties
+        })
+    }
+
+    /// Gets the runtime properties of a topic, such as its number of subscriptions.
+    pub async fn get_topic_runtime_properties(
+        &self,
+        topic_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<TopicRuntimeProperties> {
+        let options = options.unwrap_or_default();
+        let (name, info): (_, RuntimeInfo) = self
+            .get_entry(
+                topic_name,
+                &format!("topic '{}'", topic_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(info.into_topic(name))
+    }
+
+    /// Updates a topic, replacing all of its properties.
+    ///
+    /// Get the current properties with [`get_topic`](Self::get_topic) and modify them to avoid resetting other properties.
+    pub async fn update_topic(
+        &self,
+        topic_name: &str,
+        properties: TopicProperties,
+        options: Option<UpdateEntityOptions<'_>>,
+    ) -> Result<TopicProperties> {
+        let options = options.unwrap_or_default();
+        let (name, properties) = self
+            .put_entry(
+                topic_name,
+                Description::TopicDescription(Described::from(&properties)),
+                true,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(TopicProperties {
+            topic_name: name.or_else(|| Some(topic_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Deletes a topic and its subscriptions.
+    pub async fn delete_topic(
+        &self,
+        topic_name: &str,
+        options: Option<DeleteEntityOptions<'_>>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        self.delete_entry(topic_name, &options.method_options.context)
+            .await
+    }
+
+    /// Lists the topics in the namespace.
+    pub async fn list_topics(
+        &self,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<TopicProperties>> {
+        let entries = self
+            .list_entries("$Resources/topics", options.unwrap_or_default())
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, properties)| TopicProperties {
+                topic_name: name,
+                ..properties
+            })
+            .collect())
+    }
+
+    /// Lists the runtime properties of the topics in the namespace.
+    pub async fn list_topics_runtime_properties(
+        &self,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<TopicRuntimeProperties>> {
+        let entries: Vec<(_, RuntimeInfo)> = self
+            .list_entries("$Resources/topics", options.unwrap_or_default())
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, info)| info.into_topic(name))
+            .collect())
+    }
+
+    /// Creates a subscription to a topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the subscription
+    /// * `properties` - The properties of the new subscription. Properties that are not set take the service defaults.
+    /// * `options` - Optional configuration for the operation
+    pub async fn create_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        properties: Option<SubscriptionProperties>,
+        options: Option<CreateEntityOptions<'_>>,
+    ) -> Result<SubscriptionProperties> {
+        let options = options.unwrap_or_default();
+        let properties = self.subscription_with_forwarding(properties.unwrap_or_default());
+        let (name, properties) = self
+            .put_entry(
+                &subscription_path(topic_name, subscription_name),
+                Description::SubscriptionDescription(Described::from(&properties)),
+                false,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(SubscriptionProperties {
+            topic_name: Some(topic_name.to_string()),
+            subscription_name: name.or_else(|| Some(subscription_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Gets the properties of a subscription.
+    pub async fn get_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<SubscriptionProperties> {
+        let options = options.unwrap_or_default();
+        let (name, properties): (_, SubscriptionProperties) = self
+            .get_entry(
+                &subscription_path(topic_name, subscription_name),
+                &format!("subscription '{}/{}'", topic_name, subscription_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(SubscriptionProperties {
+            topic_name: Some(topic_name.to_string()),
+            subscription_name: name,
+            ..properties
+        })
+    }
+
+    /// Gets the runtime properties of a subscription, such as its message counts.
+    pub async fn get_subscription_runtime_properties(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<SubscriptionRuntimeProperties> {
+        let options = options.unwrap_or_default();
+        let (name, info): (_, RuntimeInfo) = self
+            .get_entry(
+                &subscription_path(topic_name, subscription_name),
+                &format!("subscription '{}/{}'", topic_name, subscription_name),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(info.into_subscription(topic_name, name))
+    }
+
+    /// Updates a subscription, replacing all of its properties.
+    ///
+    /// Get the current properties with [`get_subscription`](Self::get_subscription) and modify them to avoid
+    /// resetting other properties.
+    pub async fn update_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        properties: SubscriptionProperties,
+        options: Option<UpdateEntityOptions<'_>>,
+    ) -> Result<SubscriptionProperties> {
+        let options = options.unwrap_or_default();
+        let properties = self.subscription_with_forwarding(properties);
+        let (name, properties) = self
+            .put_entry(
+                &subscription_path(topic_name, subscription_name),
+                Description::SubscriptionDescription(Described::from(&properties)),
+                true,
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(SubscriptionProperties {
+            topic_name: Some(topic_name.to_string()),
+            subscription_name: name.or_else(|| Some(subscription_name.to_string())),
+            ..properties
+        })
+    }
+
+    /// Deletes a subscription.
+    pub async fn delete_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        options: Option<DeleteEntityOptions<'_>>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        self.delete_entry(
+            &subscription_path(topic_name, subscription_name),
+            &options.method_options.context,
+        )
+        .await
+    }
+
+    /// Lists the subscriptions to a topic.
+    pub async fn list_subscriptions(
+        &self,
+        topic_name: &str,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<SubscriptionProperties>> {
+        let entries = self
+            .list_entries(
+                &format!("{}/Subscriptions", topic_name),
+                options.unwrap_or_default(),
+            )
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, properties)| SubscriptionProperties {
+                topic_name: Some(topic_name.to_string()),
+                subscription_name: name,
+                ..properties
+            })
+            .collect())
+    }
+
+    /// Lists the runtime properties of the subscriptions to a topic.
+    pub async fn list_subscriptions_runtime_properties(
+        &self,
+        topic_name: &str,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<SubscriptionRuntimeProperties>> {
+        let entries: Vec<(_, RuntimeInfo)> = self
+            .list_entries(
+                &format!("{}/Subscriptions", topic_name),
+                options.unwrap_or_default(),
+            )
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, info)| info.into_subscription(topic_name, name))
+            .collect())
+    }
+
+    /// Creates a rule on a subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the subscription
+    /// * `rule_name` - The name of the rule
+    /// * `rule` - The filter and action of the rule
+    /// * `options` - Optional configuration for the operation
+    pub async fn create_rule(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        rule_name: &str,
+        rule: RuleProperties,
+        options: Option<CreateEntityOptions<'_>>,
+    ) -> Result<RuleProperties> {
+        let options = options.unwrap_or_default();
+        self.put_rule(
+            topic_name,
+            subscription_name,
+            rule_name,
+            rule,
+            false,
+            &options.method_options.context,
+        )
+        .await
+    }
+
+    /// Gets a rule of a subscription.
+    pub async fn get_rule(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        rule_name: &str,
+        options: Option<GetEntityOptions<'_>>,
+    ) -> Result<RuleProperties> {
+        let options = options.unwrap_or_default();
+        let (name, description): (_, RuleDescription) = self
+            .get_entry(
+                &rule_path(topic_name, subscription_name, rule_name),
+                &format!(
+                    "rule '{}' of subscription '{}/{}'",
+                    rule_name, topic_name, subscription_name
+                ),
+                &options.method_options.context,
+            )
+            .await?;
+        Ok(description.into_rule(name))
+    }
+
+    /// Updates a rule of a subscription, replacing its filter and action.
+    pub async fn update_rule(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        rule_name: &str,
+        rule: RuleProperties,
+        options: Option<UpdateEntityOptions<'_>>,
+    ) -> Result<RuleProperties> {
+        let options = options.unwrap_or_default();
+        self.put_rule(
+            topic_name,
+            subscription_name,
+            rule_name,
+            rule,
+            true,
+            &options.method_options.context,
+        )
+        .await
+    }
+
+    /// Deletes a rule of a subscription.
+    pub async fn delete_rule(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        rule_name: &str,
+        options: Option<DeleteEntityOptions<'_>>,
+    ) -> Result<()> {
+        let options = options.unwrap_or_default();
+        self.delete_entry(
+            &rule_path(topic_name, subscription_name, rule_name),
+            &options.method_options.context,
+        )
+        .await
+    }
+
+    /// Lists the rules of a subscription.
+    pub async fn list_rules(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        options: Option<ListEntitiesOptions<'_>>,
+    ) -> Result<Vec<RuleProperties>> {
+        let entries: Vec<(_, RuleDescription)> = self
+            .list_entries(
+                &format!("{}/Rules", subscription_path(topic_name, subscription_name)),
+                options.unwrap_or_default(),
+            )
+            .await?;
+        Ok(entries
+            .into_iter()
+            .map(|(name, description)| description.into_rule(name))
+            .collect())
+    }
+
+    async fn put_rule(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        rule_name: &str,
+        rule: RuleProperties,
+        update: bool,
+        context: &Context<'_>,
+    ) -> Result<RuleProperties> {
+        let description = RuleDescription::from(&RuleProperties {
+            rule_name: Some(rule_name.to_string()),
+            ..rule
+        });
+        let (name, description): (_, RuleDescription) = self
+            .put_entry(
+                &rule_path(topic_name, subscription_name, rule_name),
+                Description::RuleDescription(Described::from(&description)),
+                update,
+                context,
+            )
+            .await?;
+        Ok(description.into_rule(name.or_else(|| Some(rule_name.to_string()))))
+    }
+
+    async fn get_entry<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        entity: &str,
+        context: &Context<'_>,
+    ) -> Result<(Option<String>, T)> {
+        let mut request = Request::new(self.url(path, &[])?, Method::Get);
+        let response = self.pipeline.send(context, &mut request, None).await?;
+        let entry: ResponseEntry<T> = response.into_body().xml()?;
+        entry.into_description(entity)
+    }
+
+    async fn put_entry<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        description: Description<'_>,
+        update: bool,
+        context: &Context<'_>,
+    ) -> Result<(Option<String>, T)> {
+        let mut request = Request::new(self.url(path, &[])?, Method::Put);
+        request.insert_header("content-type", ENTRY_CONTENT_TYPE);
+        if update {
+            request.insert_header("if-match", "*");
+        }
+        request.set_body(xml::to_xml(&RequestEntry::new(description))?);
+        let response = self.pipeline.send(context, &mut request, None).await?;
+        let entry: ResponseEntry<T> = response.into_body().xml()?;
+        entry.into_description(path)
+    }
+
+    async fn delete_entry(&self, path: &str, context: &Context<'_>) -> Result<()> {
+        let mut request = Request::new(self.url(path, &[])?, Method::Delete);
+        self.pipeline.send(context, &mut request, None).await?;
+        Ok(())
+    }
+
+    /// Lists entries, fetching pages until `options.top` entries are returned or there are no more entries.
+    async fn list_entries<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        options: ListEntitiesOptions<'_>,
+    ) -> Result<Vec<(Option<String>, T)>> {
+        let mut skip = options.skip.unwrap_or_default();
+        let mut remaining = options.top;
+        let mut entries = Vec::new();
+        loop {
+            let top = remaining.map_or(LIST_PAGE_SIZE, |r| r.min(LIST_PAGE_SIZE));
+            if top == 0 {
+                return Ok(entries);
+            }
+            let url = self.url(
+                path,
+                &[("$skip", skip.to_string()), ("$top", top.to_string())],
+            )?;
+            let mut request = Request::new(url, Method::Get);
+            let response = self
+                .pipeline
+                .send(&options.method_options.context, &mut request, None)
+                .await?;
+            let feed: Feed<T> = response.into_body().xml()?;
+            let count = feed.entry.len() as u32;
+            for entry in feed.entry {
+                entries.push(entry.into_description(path)?);
+            }
+            if count < top {
+                return Ok(entries);
+            }
+            skip += count;
+            remaining = remaining.map(|r| r - count);
+        }
+    }
+
+    fn url(&self, path: &str, query: &[(&str, String)]) -> Result<Url> {
+        let mut url = self.endpoint.join(path).map_err(|e| {
+            ServiceBusError::new(
+                ErrorKind::InvalidRequest,
+                format!("Invalid entity path '{}': {}", path, e),
+            )
+        })?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.append_pair("api-version", &self.api_version);
+            for (key, value) in query {
+                pairs.append_pair(key, value);
+            }
+        }
+        Ok(url)
+    }
+
+    /// Converts the names of forwarding destinations to the URLs the service requires.
+    fn forwarding_url(&self, destination: Option<String>) -> Option<String> {
+        destination.map(|destination| {
+            if destination.starts_with("http://") || destination.starts_with("https://") {
+                destination
+            } else {
+                format!("{}{}", self.endpoint, destination)
+            }
+        })
+    }
+
+    fn queue_with_forwarding(&self, properties: QueueProperties) -> QueueProperties {
+        QueueProperties {
+            forward_to: self.forwarding_url(properties.forward_to),
+            forward_dead_lettered_messages_to: self
+                .forwarding_url(properties.forward_dead_lettered_messages_to),
+            ..properties
+        }
+    }
+
+    fn subscription_with_forwarding(
+        &self,
+        properties: SubscriptionProperties,
+    ) -> SubscriptionProperties {
+        SubscriptionProperties {
+            forward_to: self.forwarding_url(properties.forward_to),
+            forward_dead_lettered_messages_to: self
+                .forwarding_url(properties.forward_dead_lettered_messages_to),
+            ..properties
+        }
+    }
+}
+
+fn subscription_path(topic_name: &str, subscription_name: &str) -> String {
+    format!("{}/Subscriptions/{}", topic_name, subscription_name)
+}
+
+fn rule_path(topic_name: &str, subscription_name: &str, rule_name: &str) -> String {
+    format!(
+        "{}/Rules/{}",
+        subscription_path(topic_name, subscription_name),
+        rule_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, StatusCode, Transport},
+        time::Duration,
+        Bytes,
+    };
+    use azure_core_test::{credentials::MockCredential, http::MockHttpClient};
+    use futures::FutureExt as _;
+    use std::sync::Mutex;
+
+    const QUEUE_ENTRY: &str = r#"<entry xmlns="http://www.w3.org/2005/Atom"><title type="text">orders</title><content type="application/xml"><QueueDescription xmlns="http://schemas.microsoft.com/netservices/2010/10/servicebus/connect" xmlns:i="http://www.w3.org/2001/XMLSchema-instance"><LockDuration>PT30S</LockDuration><MaxDeliveryCount>5</MaxDeliveryCount><ForwardTo>https://myservicebus.servicebus.windows.net/archive</ForwardTo></QueueDescription></content></entry>"#;
+
+    /// A request received by the mock service.
+    struct RecordedRequest {
+        method: Method,
+        url: Url,
+        if_match: Option<String>,
+        body: String,
+    }
+
+    /// Creates a client whose requests are recorded and answered by `respond`.
+    fn client(
+        requests: Arc<Mutex<Vec<RecordedRequest>>>,
+        respond: impl Fn(&Url) -> (StatusCode, String) + Send + Sync + 'static,
+    ) -> ServiceBusAdministrationClient {
+        let respond = Arc::new(respond);
+        let mock_client = Arc::new(MockHttpClient::new(move |req| {
+            let (status, body) = respond(req.url());
+            requests.lock().unwrap().push(RecordedRequest {
+                method: req.method(),
+                url: req.url().clone(),
+                if_match: req.headers().get_optional_string(&"if-match".into()),
+                body: String::from_utf8(Bytes::from(req.body()).to_vec()).unwrap(),
+            });
+            async move { Ok(AsyncRawResponse::from_bytes(status, Headers::new(), body)) }.boxed()
+        }));
+        ServiceBusAdministrationClient::new(
+            "myservicebus.servicebus.windows.net",
+            MockCredential::new().unwrap(),
+            Some(ServiceBusAdministrationClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn create_queue_sends_entry() -> Result<()> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = client(requests.clone(), |_| {
+            (StatusCode::Created, QUEUE_ENTRY.to_string())
+        });
+
+        let queue = client
+            .create_queue(
+                "orders",
+                Some(QueueProperties {
+                    lock_duration: Some(Duration::seconds(30)),
+                    max_delivery_count: Some(5),
+                    forward_to: Some("archive".to_string()),
+                    ..Default::default()
+                }),
+                None,
+            )
+            .await?;
+        assert_eq!(queue.queue_name.as_deref(), Some("orders"));
+        assert_eq!(queue.lock_duration, Some(Duration::seconds(30)));
+        assert_eq!(queue.max_delivery_count, Some(5));
+
+        let requests = requests.lock().unwrap();
+        let request = &requests[0];
+        assert_eq!(request.method, Method::Put);
+        assert_eq!(
+            request.url.as_str(),
+            "https://myservicebus.servicebus.windows.net/orders?api-version=2021-05"
+        );
+        assert_eq!(request.if_match, None);
+        assert!(request.body.contains("<LockDuration>PT30S</LockDuration>"));
+        assert!(request.body.contains(
+            "<ForwardTo>https://myservicebus.servicebus.windows.net/archive</ForwardTo>"
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_queue_sets_if_match() -> Result<()> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = client(requests.clone(), |_| {
+            (StatusCode::Ok, QUEUE_ENTRY.to_string())
+        });
+
+        client
+            .update_queue("orders", QueueProperties::default(), None)
+            .await?;
+        assert_eq!(requests.lock().unwrap()[0].if_match.as_deref(), Some("*"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_missing_queue_is_not_found() {
+        let client = client(Arc::new(Mutex::new(Vec::new())), |_| {
+            (
+                StatusCode::Ok,
+                r#"<feed xmlns="http://www.w3.org/2005/Atom"><title type="text">Publicly Listed Services</title></feed>"#.to_string(),
+            )
+        });
+
+        let error = client.get_queue("missing", None).await.unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::EntityNotFound);
+    }
+
+    #[tokio::test]
+    async fn maps_conflict_to_already_exists() {
+        let client = client(Arc::new(Mutex::new(Vec::new())), |_| {
+            (
+                StatusCode::Conflict,
+                "<Error><Code>409</Code></Error>".to_string(),
+            )
+        });
+
+        let error = client.create_topic("orders", None, None).await.unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::EntityAlreadyExists);
+    }
+
+    #[tokio::test]
+    async fn list_subscriptions_pages() -> Result<()> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = client(requests.clone(), |url| {
+            let query = |name: &str| -> u32 {
+                url.query_pairs()
+                    .find(|(k, _)| k == name)
+                    .map(|(_, v)| v.parse().unwrap())
+                    .unwrap()
+            };
+            // The topic has 150 subscriptions, more than fit in a single page.
+            let (skip, top) = (query("$skip"), query("$top"));
+            let entries: String = (skip..(skip + top).min(150))
+                .map(|i| format!(
+                    r#"<entry><title type="text">sub-{i}</title><content type="application/xml"><SubscriptionDescription><MaxDeliveryCount>{i}</MaxDeliveryCount><MessageCount>{i}</MessageCount></SubscriptionDescription></content></entry>"#
+                ))
+                .collect();
+            (
+                StatusCode::Ok,
+                format!(r#"<feed xmlns="http://www.w3.org/2005/Atom">{entries}</feed>"#),
+            )
+        });
+
+        let subscriptions = client
+            .list_subscriptions(
+                "orders",
+                Some(ListEntitiesOptions {
+                    top: Some(3),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        assert_eq!(subscriptions.len(), 3);
+        assert_eq!(subscriptions[1].subscription_name.as_deref(), Some("sub-1"));
+        assert_eq!(subscriptions[1].topic_name.as_deref(), Some("orders"));
+
+        let runtime = client
+            .list_subscriptions_runtime_properties("orders", None)
+            .await?;
+        assert_eq!(runtime.len(), 150);
+        assert_eq!(runtime[149].total_message_count, Some(149));
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0].url.as_str(),
+            "https://myservicebus.servicebus.windows.net/orders/Subscriptions?api-version=2021-05&%24skip=0&%24top=3"
+        );
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].url.as_str().ends_with("%24skip=100&%24top=100"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_rule_sends_rule_description() -> Result<()> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = client(requests.clone(), |_| {
+            (
+                StatusCode::Created,
+                r#"<entry xmlns="http://www.w3.org/2005/Atom"><title type="text">red</title><content type="application/xml"><RuleDescription xmlns="http://schemas.microsoft.com/netservices/2010/10/servicebus/connect" xmlns:i="http://www.w3.org/2001/XMLSchema-instance"><Filter i:type="SqlFilter"><SqlExpression>color = 'red'</SqlExpression><CompatibilityLevel>20</CompatibilityLevel></Filter><Action i:type="EmptyRuleAction"/><Name>red</Name></RuleDescription></content></entry>"#.to_string(),
+            )
+        });
+
+        let rule = client
+            .create_rule(
+                "orders",
+                "all",
+                "red",
+                RuleProperties {
+                    filter: Some(RuleFilter::Sql("color = 'red'".to_string())),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await?;
+        assert_eq!(rule.rule_name.as_deref(), Some("red"));
+        assert_eq!(
+            rule.filter,
+            Some(RuleFilter::Sql("color = 'red'".to_string()))
+        );
+        assert_eq!(rule.action, None);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[0].url.path(),
+            "/orders/Subscriptions/all/Rules/red"
+        );
+        assert!(requests[0].body.contains("<Name>red</Name>"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_namespace_properties() -> Result<()> {
+        let client = client(Arc::new(Mutex::new(Vec::new())), |url| {
+            assert_eq!(url.path(), "/$namespaceinfo");
+            (
+                StatusCode::Ok,
+                r#"<entry xmlns="http://www.w3.org/2005/Atom"><title type="text">myservicebus</title><content type="application/xml"><NamespaceInfo xmlns="http://schemas.microsoft.com/netservices/2010/10/servicebus/connect"><CreatedTime>2024-01-02T03:04:05.67Z</CreatedTime><MessagingSKU>Premium</MessagingSKU><MessagingUnits>2</MessagingUnits><Name>myservicebus</Name><NamespaceType>Messaging</NamespaceType></NamespaceInfo></content></entry>"#.to_string(),
+            )
+        });
+
+        let properties = client.get_namespace_properties(None).await?;
+        assert_eq!(properties.namespace_name.as_deref(), Some("myservicebus"));
+        assert_eq!(
+            properties.messaging_sku,
+            Some(crate::models::MessagingSku::Premium)
+        );
+        assert_eq!(properties.messaging_units, Some(2));
+        assert!(properties.created_at.is_some());
+        Ok(())
+    }
+}
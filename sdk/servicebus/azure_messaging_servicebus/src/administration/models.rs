@@ -0,0 +1,224 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Properties of Service Bus entities managed by the [`ServiceBusAdministrationClient`](super::ServiceBusAdministrationClient).
+
+use super::atom::iso8601;
+use crate::models::EntityStatus;
+use azure_core::{fmt::SafeDebug, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The properties of a queue.
+///
+/// Fields that are `None` when creating a queue take the service defaults.
+/// Elements are serialized in the order the service requires.
+#[derive(SafeDebug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct QueueProperties {
+    /// The name of the queue. Set on properties returned by the service, and ignored when creating or updating a queue.
+    #[serde(skip)]
+    pub queue_name: Option<String>,
+    /// How long a received message is locked for other receivers.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub lock_duration: Option<Duration>,
+    /// The maximum size of the queue in megabytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_in_megabytes: Option<i64>,
+    /// Whether the queue detects duplicate messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires_duplicate_detection: Option<bool>,
+    /// Whether the queue is session-enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires_session: Option<bool>,
+    /// How long messages live in the queue when they do not set a time-to-live.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub default_message_time_to_live: Option<Duration>,
+    /// Whether expired messages are moved to the dead letter queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_lettering_on_message_expiration: Option<bool>,
+    /// How long message IDs are remembered for duplicate detection.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_detection_history_time_window: Option<Duration>,
+    /// The number of deliveries after which a message is moved to the dead letter queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_delivery_count: Option<i32>,
+    /// Whether server-side batched operations are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_batched_operations: Option<bool>,
+    /// The status of the queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<EntityStatus>,
+    /// The name of the queue or topic that messages are forwarded to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<String>,
+    /// Application-defined metadata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<String>,
+    /// How long the queue may be idle before it is deleted.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub auto_delete_on_idle: Option<Duration>,
+    /// Whether the queue is partitioned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_partitioning: Option<bool>,
+    /// The name of the queue or topic that dead-lettered messages are forwarded to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_dead_lettered_messages_to: Option<String>,
+    /// Whether the queue is an express queue, which holds messages in memory before writing them to storage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_express: Option<bool>,
+    /// The maximum size of a message in kilobytes. Only supported by premium namespaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_message_size_in_kilobytes: Option<i64>,
+}
+
+/// The properties of a topic.
+///
+/// Fields that are `None` when creating a topic take the service defaults.
+#[derive(SafeDebug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TopicProperties {
+    /// The name of the topic. Set on properties returned by the service, and ignored when creating or updating a topic.
+    #[serde(skip)]
+    pub topic_name: Option<String>,
+    /// How long messages live in the topic when they do not set a time-to-live.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub default_message_time_to_live: Option<Duration>,
+    /// The maximum size of the topic in megabytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_size_in_megabytes: Option<i64>,
+    /// Whether the topic detects duplicate messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires_duplicate_detection: Option<bool>,
+    /// How long message IDs are remembered for duplicate detection.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_detection_history_time_window: Option<Duration>,
+    /// Whether server-side batched operations are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_batched_operations: Option<bool>,
+    /// The status of the topic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<EntityStatus>,
+    /// Application-defined metadata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<String>,
+    /// Whether the topic preserves the order of messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_ordering: Option<bool>,
+    /// How long the topic may be idle before it is deleted.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub auto_delete_on_idle: Option<Duration>,
+    /// Whether the topic is partitioned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_partitioning: Option<bool>,
+    /// Whether the topic is an express topic, which holds messages in memory before writing them to storage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_express: Option<bool>,
+    /// The maximum size of a message in kilobytes. Only supported by premium namespaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_message_size_in_kilobytes: Option<i64>,
+}
+
+/// The properties of a topic subscription.
+///
+/// Fields that are `None` when creating a subscription take the service defaults.
+#[derive(SafeDebug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SubscriptionProperties {
+    /// The name of the topic. Set on properties returned by the service, and ignored when creating or updating a subscription.
+    #[serde(skip)]
+    pub topic_name: Option<String>,
+    /// The name of the subscription. Set on properties returned by the service, and ignored when creating or updating a subscription.
+    #[serde(skip)]
+    pub subscription_name: Option<String>,
+    /// How long a received message is locked for other receivers.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub lock_duration: Option<Duration>,
+    /// Whether the subscription is session-enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub requires_session: Option<bool>,
+    /// How long messages live in the subscription when they do not set a time-to-live.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub default_message_time_to_live: Option<Duration>,
+    /// Whether expired messages are moved to the dead letter queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_lettering_on_message_expiration: Option<bool>,
+    /// Whether messages that fail filter evaluation are moved to the dead letter queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dead_lettering_on_filter_evaluation_exceptions: Option<bool>,
+    /// The number of deliveries after which a message is moved to the dead letter queue.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_delivery_count: Option<i32>,
+    /// Whether server-side batched operations are enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_batched_operations: Option<bool>,
+    /// The status of the subscription.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<EntityStatus>,
+    /// The name of the queue or topic that messages are forwarded to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_to: Option<String>,
+    /// Application-defined metadata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_metadata: Option<String>,
+    /// The name of the queue or topic that dead-lettered messages are forwarded to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_dead_lettered_messages_to: Option<String>,
+    /// How long the subscription may be idle before it is deleted.
+    #[serde(with = "iso8601", default, skip_serializing_if = "Option::is_none")]
+    pub auto_delete_on_idle: Option<Duration>,
+}
+
+/// The properties of a subscription rule.
+#[derive(SafeDebug, Clone, Default, PartialEq)]
+pub struct RuleProperties {
+    /// The name of the rule. Set on properties returned by the service, and ignored when creating or updating a rule.
+    pub rule_name: Option<String>,
+    /// The filter that selects the messages the rule applies to. Defaults to [`RuleFilter::True`].
+    pub filter: Option<RuleFilter>,
+    /// The action applied to messages that match the filter, if any.
+    pub action: Option<RuleAction>,
+}
+
+/// A filter that selects the messages a rule applies to.
+#[derive(SafeDebug, Clone, PartialEq)]
+pub enum RuleFilter {
+    /// Matches messages using a SQL-like expression over message properties, such as `color = 'red'`.
+    Sql(String),
+    /// Matches messages whose properties equal the set properties of the filter.
+    Correlation(Box<CorrelationRuleFilter>),
+    /// Matches all messages.
+    True,
+    /// Matches no messages.
+    False,
+}
+
+/// A filter that matches messages whose properties equal all of its set properties.
+#[derive(SafeDebug, Clone, Default, PartialEq)]
+pub struct CorrelationRuleFilter {
+    /// The correlation ID to match.
+    pub correlation_id: Option<String>,
+    /// The message ID to match.
+    pub message_id: Option<String>,
+    /// The `to` address to match.
+    pub to: Option<String>,
+    /// The reply-to address to match.
+    pub reply_to: Option<String>,
+    /// The subject (label) to match.
+    pub subject: Option<String>,
+    /// The session ID to match.
+    pub session_id: Option<String>,
+    /// The reply-to session ID to match.
+    pub reply_to_session_id: Option<String>,
+    /// The content type to match.
+    pub content_type: Option<String>,
+    /// The application properties to match.
+    pub application_properties: HashMap<String, String>,
+}
+
+/// An action applied to messages that match a rule's filter.
+#[derive(SafeDebug, Clone, PartialEq)]
+pub enum RuleAction {
+    /// Modifies message properties using a SQL-like expression, such as `SET sys.Label = 'routed'`.
+    Sql(String),
+}
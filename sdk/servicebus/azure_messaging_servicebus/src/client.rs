@@ -2,12 +2,12 @@
 // Licensed under the MIT license.
 
 use crate::{
-    common::authorizer::Authorizer, ErrorKind, ReceiveMode, Receiver, Result, Sender,
-    ServiceBusError,
+    common::recoverable::RecoverableConnection, AcceptSessionOptions, BeginTransactionOptions,
+    ReceiveMode, Receiver, Result, RetryOptions, Sender, ServiceBusTransaction, SessionReceiver,
 };
 use azure_core::{credentials::TokenCredential, fmt::SafeDebug, http::Url};
 use azure_core_amqp::{
-    AmqpConnection, AmqpConnectionApis, AmqpConnectionOptions, AmqpOrderedMap, AmqpSymbol,
+    AmqpConnectionOptions, AmqpOrderedMap, AmqpProxyOptions, AmqpSymbol, AmqpTransportType,
     AmqpValue,
 };
 use std::sync::Arc;
@@ -44,6 +44,29 @@ pub struct ServiceBusClientOptions {
     /// This optional identifier is passed to the Service Bus namespace during connection establishment
     /// and can be used for diagnostic purposes. It follows the same pattern as the Go SDK's ApplicationID.
     pub application_id: Option<String>,
+
+    /// Options for retrying operations that fail with transient errors.
+    ///
+    /// Senders and receivers reopen the connection and reattach their links when they are lost,
+    /// then retry the operation.
+    pub retry_options: RetryOptions,
+
+    /// An endpoint to open the connection to instead of the namespace, such as an AMQP proxy or a
+    /// local test broker.
+    ///
+    /// Entity paths are still authorized for the namespace.
+    pub custom_endpoint: Option<Url>,
+
+    /// The transport used to connect to the namespace. Defaults to AMQP over TCP.
+    ///
+    /// Use [`AmqpTransportType::AmqpWebSockets`] to connect over port 443 when the AMQP port is
+    /// blocked. WebSockets require the `websocket` feature.
+    pub transport_type: Option<AmqpTransportType>,
+
+    /// The HTTP proxy used to connect to the namespace.
+    ///
+    /// A proxy can only be used with [`AmqpTransportType::AmqpWebSockets`].
+    pub proxy: Option<AmqpProxyOptions>,
 }
 
 impl Default for ServiceBusClientOptions {
@@ -51,13 +74,24 @@ impl Default for ServiceBusClientOptions {
         Self {
             api_version: "2021-05".to_string(), // Default Service Bus API version
             application_id: None,
+            retry_options: RetryOptions::default(),
+            custom_endpoint: None,
+            transport_type: None,
+            proxy: None,
         }
     }
 }
 
 /// Options for creating a sender.
 #[derive(Clone, Default)]
-pub struct CreateSenderOptions; // Place holder for future options
+pub struct CreateSenderOptions {
+    /// The entity to send messages through.
+    ///
+    /// Messages are sent to this entity, which forwards them to the sender's queue or topic. Set
+    /// this to the entity messages are received from to send messages to another entity and
+    /// settle received messages in the same [`ServiceBusTransaction`].
+    pub via_entity_name: Option<String>,
+}
 
 /// Options for creating a receiver.
 #[derive(Clone)]
@@ -79,10 +113,9 @@ impl Default for CreateReceiverOptions {
 
 /// A client for interacting with Azure Service Bus.
 pub struct ServiceBusClient {
-    connection: Arc<AmqpConnection>,
+    connection: Arc<RecoverableConnection>,
     namespace: String,
     options: ServiceBusClientOptions,
-    authorizer: Option<Arc<Authorizer>>,
 }
 
 impl ServiceBusClient {
@@ -90,24 +123,31 @@ impl ServiceBusClient {
     fn build_connection_options(
         options: Option<ServiceBusClientOptions>,
     ) -> Option<AmqpConnectionOptions> {
-        if let Some(options) = options {
-            if let Some(application_id) = &options.application_id {
-                let mut properties = AmqpOrderedMap::new();
-                properties.insert(
-                    AmqpSymbol::from("user-agent"),
-                    AmqpValue::from(application_id.clone()),
-                );
-                Some(AmqpConnectionOptions {
-                    properties: Some(properties),
-                    ..Default::default()
-                })
-            } else {
-                None
-            }
-        } else {
-            None
+        let options = options?;
+        if options.application_id.is_none()
+            && options.custom_endpoint.is_none()
+            && options.transport_type.is_none()
+            && options.proxy.is_none()
+        {
+            return None;
         }
+        let properties = options.application_id.map(|application_id| {
+            let mut properties = AmqpOrderedMap::new();
+            properties.insert(
+                AmqpSymbol::from("user-agent"),
+                AmqpValue::from(application_id),
+            );
+            properties
+        });
+        Some(AmqpConnectionOptions {
+            properties,
+            custom_endpoint: options.custom_endpoint,
+            transport_type: options.transport_type,
+            proxy: options.proxy,
+            ..Default::default()
+        })
     }
+
     /// Returns a builder which can be used to create a new instance of [`ServiceBusClient`].
     ///
     /// # Examples
@@ -134,34 +174,22 @@ impl ServiceBusClient {
         credential: Arc<dyn TokenCredential>,
         options: Option<ServiceBusClientOptions>,
     ) -> Result<Self> {
-        let endpoint = format!("amqps://{}:5671", fully_qualified_namespace);
-        let endpoint_url = Url::parse(&endpoint).map_err(|e| {
-            ServiceBusError::new(
-                ErrorKind::InvalidRequest,
-                format!("Invalid endpoint URL: {}", e),
-            )
-        })?;
-        let namespace = fully_qualified_namespace.to_string();
-
-        let connection = Arc::new(AmqpConnection::new());
-
-        // Create authorizer with the credential for token-based authentication
-        let authorizer = Arc::new(Authorizer::new(Arc::downgrade(&connection), credential));
-
         let connection_options = Self::build_connection_options(options.clone());
-        connection
-            .open(
-                "servicebus-client".to_string(),
-                endpoint_url,
-                connection_options,
-            )
-            .await?;
+        let options = options.unwrap_or_default();
+
+        // The connection authorizes entity paths with the credential, and reauthorizes them if it is reopened.
+        let connection = RecoverableConnection::new(
+            fully_qualified_namespace,
+            Some(credential),
+            connection_options,
+            options.retry_options.clone(),
+        )?;
+        connection.open().await?;
 
         Ok(Self {
             connection,
-            namespace,
-            options: options.unwrap_or_default(),
-            authorizer: Some(authorizer),
+            namespace: fully_qualified_namespace.to_string(),
+            options,
         })
     }
 
@@ -169,19 +197,36 @@ impl ServiceBusClient {
     pub async fn create_sender(
         &self,
         queue_or_topic_name: &str,
-        _options: Option<CreateSenderOptions>,
+        options: Option<CreateSenderOptions>,
     ) -> Result<Sender> {
+        let via_entity_name = options.and_then(|options| options.via_entity_name);
+
         // Authorize the path if we have a credential-based client
         self.authorize_path(queue_or_topic_name).await?;
+        if let Some(via_entity_name) = &via_entity_name {
+            self.authorize_path(via_entity_name).await?;
+        }
 
         Sender::new(
             self.connection.clone(),
             queue_or_topic_name.to_string(),
+            via_entity_name,
             self.options.clone(),
         )
         .await
     }
 
+    /// Begins a transaction.
+    ///
+    /// The transaction can be used with the senders and receivers created by this client. See
+    /// [`ServiceBusTransaction`] for an example.
+    pub async fn begin_transaction(
+        &self,
+        _options: Option<BeginTransactionOptions>,
+    ) -> Result<ServiceBusTransaction> {
+        ServiceBusTransaction::begin(&self.connection).await
+    }
+
     /// Creates a receiver for the specified queue with options.
     ///
     /// # Examples
@@ -303,6 +348,122 @@ impl ServiceBusClient {
         .await
     }
 
+    /// Locks a specific session of a session-enabled queue and returns a receiver for its messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_name` - The name of the session-enabled queue
+    /// * `session_id` - The ID of the session to lock
+    /// * `options` - Optional configuration for the session receiver
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`ErrorKind::SessionCannotBeLocked`] if the session is locked by another receiver.
+    pub async fn accept_session(
+        &self,
+        queue_name: &str,
+        session_id: &str,
+        options: Option<AcceptSessionOptions>,
+    ) -> Result<SessionReceiver> {
+        let receiver = self
+            .create_session_receiver(queue_name.to_string(), options.unwrap_or_default())
+            .await?;
+        SessionReceiver::accept(receiver, session_id.to_string()).await
+    }
+
+    /// Locks the next available session of a session-enabled queue and returns a receiver for its messages.
+    ///
+    /// Waits up to [`AcceptSessionOptions::max_wait_time`] for a session with messages to become available.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_name` - The name of the session-enabled queue
+    /// * `options` - Optional configuration for the session receiver
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`ErrorKind::RequestTimeout`] if no session becomes available in time.
+    pub async fn accept_next_session(
+        &self,
+        queue_name: &str,
+        options: Option<AcceptSessionOptions>,
+    ) -> Result<SessionReceiver> {
+        let options = options.unwrap_or_default();
+        let max_wait_time = options.max_wait_time;
+        let receiver = self
+            .create_session_receiver(queue_name.to_string(), options)
+            .await?;
+        SessionReceiver::accept_next(receiver, max_wait_time).await
+    }
+
+    /// Locks a specific session of a session-enabled subscription and returns a receiver for its messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the session-enabled subscription
+    /// * `session_id` - The ID of the session to lock
+    /// * `options` - Optional configuration for the session receiver
+    pub async fn accept_session_for_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        session_id: &str,
+        options: Option<AcceptSessionOptions>,
+    ) -> Result<SessionReceiver> {
+        let entity_path = format!("{}/subscriptions/{}", topic_name, subscription_name);
+        let receiver = self
+            .create_session_receiver(entity_path, options.unwrap_or_default())
+            .await?;
+        SessionReceiver::accept(receiver, session_id.to_string()).await
+    }
+
+    /// Locks the next available session of a session-enabled subscription and returns a receiver for its messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the session-enabled subscription
+    /// * `options` - Optional configuration for the session receiver
+    pub async fn accept_next_session_for_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        options: Option<AcceptSessionOptions>,
+    ) -> Result<SessionReceiver> {
+        let options = options.unwrap_or_default();
+        let max_wait_time = options.max_wait_time;
+        let entity_path = format!("{}/subscriptions/{}", topic_name, subscription_name);
+        let receiver = self.create_session_receiver(entity_path, options).await?;
+        SessionReceiver::accept_next(receiver, max_wait_time).await
+    }
+
+    pub(crate) async fn create_session_receiver(
+        &self,
+        entity_path: String,
+        options: AcceptSessionOptions,
+    ) -> Result<Receiver> {
+        self.authorize_path(&entity_path).await?;
+
+        Receiver::new(
+            self.connection.clone(),
+            entity_path,
+            None,
+            options.receive_mode,
+            self.options.clone(),
+        )
+        .await
+    }
+
+    /// Creates a client that shares this client's connection.
+    pub(crate) fn clone_internal(&self) -> Self {
+        Self {
+            connection: self.connection.clone(),
+            namespace: self.namespace.clone(),
+            options: self.options.clone(),
+        }
+    }
+
     /// Gets the fully qualified namespace.
     pub fn fully_qualified_namespace(&self) -> &str {
         &self.namespace
@@ -311,19 +472,7 @@ impl ServiceBusClient {
     /// Authorizes access to a Service Bus entity path using the configured credential.
     /// This method is used internally by senders and receivers when authentication is required.
     pub(crate) async fn authorize_path(&self, entity_path: &str) -> Result<()> {
-        if let Some(ref authorizer) = self.authorizer {
-            let entity_url = azure_core::http::Url::parse(&format!(
-                "amqps://{}:5671/{}",
-                self.namespace, entity_path
-            ))
-            .map_err(azure_core::Error::from)?;
-
-            authorizer
-                .authorize_path(&self.connection, &entity_url)
-                .await?;
-        }
-        // If no authorizer is configured (e.g., connection string auth), no additional authorization is needed
-        Ok(())
+        self.connection.authorize_path(entity_path).await
     }
 
     /// Closes the client and all associated senders and receivers.
@@ -356,6 +505,14 @@ impl ServiceBusClient {
 pub struct ServiceBusClientBuilder {
     /// Application ID for diagnostic purposes.
     application_id: Option<String>,
+    /// Options for retrying operations.
+    retry_options: Option<RetryOptions>,
+    /// Endpoint to connect to instead of the namespace.
+    custom_endpoint: Option<String>,
+    /// Transport used to connect to the namespace.
+    transport_type: Option<AmqpTransportType>,
+    /// HTTP proxy used to connect to the namespace.
+    proxy: Option<AmqpProxyOptions>,
 }
 
 impl ServiceBusClientBuilder {
@@ -393,6 +550,53 @@ impl ServiceBusClientBuilder {
         self
     }
 
+    /// Sets the options used to retry operations that fail with transient errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry_options` - The options used to configure retry operations.
+    pub fn with_retry_options(mut self, retry_options: RetryOptions) -> Self {
+        self.retry_options = Some(retry_options);
+        self
+    }
+
+    /// Sets a custom endpoint to open the connection to.
+    ///
+    /// The connection is opened to this endpoint instead of the namespace, which is still used
+    /// to authorize entity paths. This allows the client to connect through an AMQP proxy, or to
+    /// a local broker such as the one in `azure_core_amqp_test`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - The URL of the endpoint, such as `amqp://127.0.0.1:5672`.
+    pub fn with_custom_endpoint(mut self, endpoint: String) -> Self {
+        self.custom_endpoint = Some(endpoint);
+        self
+    }
+
+    /// Sets the transport used to connect to the namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `transport_type` - The transport, such as [`AmqpTransportType::AmqpWebSockets`], which
+    ///   requires the `websocket` feature.
+    pub fn with_transport_type(mut self, transport_type: AmqpTransportType) -> Self {
+        self.transport_type = Some(transport_type);
+        self
+    }
+
+    /// Sets the HTTP proxy used to connect to the namespace.
+    ///
+    /// A proxy can only be used with [`AmqpTransportType::AmqpWebSockets`].
+    ///
+    /// # Arguments
+    ///
+    /// * `proxy` - The proxy URL and optional Basic authentication credentials.
+    pub fn with_proxy(mut self, proxy: AmqpProxyOptions) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
     /// Opens a connection to the Service Bus namespace.
     ///
     /// # Arguments
@@ -428,8 +632,16 @@ impl ServiceBusClientBuilder {
         fully_qualified_namespace: &str,
         credential: Arc<dyn TokenCredential>,
     ) -> Result<ServiceBusClient> {
+        let custom_endpoint = match self.custom_endpoint {
+            Some(endpoint) => Some(Url::parse(&endpoint).map_err(azure_core::Error::from)?),
+            None => None,
+        };
         let options = ServiceBusClientOptions {
             application_id: self.application_id,
+            retry_options: self.retry_options.unwrap_or_default(),
+            custom_endpoint,
+            transport_type: self.transport_type,
+            proxy: self.proxy,
             ..Default::default()
         };
 
@@ -548,6 +760,39 @@ mod tests {
         assert!(connection_options.is_none());
     }
 
+    #[test]
+    fn test_build_connection_options_with_custom_endpoint() {
+        let endpoint = Url::parse("amqp://127.0.0.1:5672").unwrap();
+        let options = ServiceBusClientOptions {
+            custom_endpoint: Some(endpoint.clone()),
+            ..Default::default()
+        };
+
+        let connection_options = ServiceBusClient::build_connection_options(Some(options)).unwrap();
+        assert_eq!(connection_options.custom_endpoint, Some(endpoint));
+        assert!(connection_options.properties.is_none());
+    }
+
+    #[test]
+    fn test_build_connection_options_with_websockets() {
+        let proxy = AmqpProxyOptions::new(Url::parse("http://proxy.contoso.com:8080").unwrap());
+        let options = ServiceBusClientOptions {
+            transport_type: Some(AmqpTransportType::AmqpWebSockets),
+            proxy: Some(proxy.clone()),
+            ..Default::default()
+        };
+
+        let connection_options = ServiceBusClient::build_connection_options(Some(options)).unwrap();
+        assert_eq!(
+            connection_options.transport_type,
+            Some(AmqpTransportType::AmqpWebSockets)
+        );
+        assert_eq!(
+            connection_options.proxy.map(|proxy| proxy.url),
+            Some(proxy.url)
+        );
+    }
+
     #[test]
     fn test_build_connection_options_with_none() {
         let connection_options = ServiceBusClient::build_connection_options(None);
@@ -1,6 +1,7 @@
 // Copyright (c) Microsoft Corporation. All Rights reserved
 // Licensed under the MIT license.
 
+use super::recoverable::RecoverableConnection;
 use async_lock::Mutex as AsyncMutex;
 use azure_core::{
     async_runtime::{get_async_runtime, SpawnedTask},
@@ -50,7 +51,7 @@ pub(crate) struct Authorizer {
     /// Bias to apply to token refresh time. This determines how much time we will refresh the token before it expires.
     token_refresh_bias: SyncMutex<TokenRefreshTimes>,
     credential: Arc<dyn TokenCredential>,
-    connection: Weak<AmqpConnection>,
+    connection: Weak<RecoverableConnection>,
     /// This is used to disable authorization for testing purposes.
     #[cfg(test)]
     disable_authorization: SyncMutex<bool>,
@@ -60,7 +61,10 @@ unsafe impl Send for Authorizer {}
 unsafe impl Sync for Authorizer {}
 
 impl Authorizer {
-    pub fn new(connection: Weak<AmqpConnection>, credential: Arc<dyn TokenCredential>) -> Self {
+    pub fn new(
+        connection: Weak<RecoverableConnection>,
+        credential: Arc<dyn TokenCredential>,
+    ) -> Self {
         Self {
             authorization_refresher: OnceLock::new(),
             authorization_scopes: AsyncMutex::new(HashMap::new()),
@@ -133,6 +137,28 @@ impl Authorizer {
             .clone())
     }
 
+    /// Authorizes every previously authorized path on a reopened connection.
+    ///
+    /// Tokens are reused while they remain valid for longer than the refresh bias.
+    pub(crate) async fn reauthorize(
+        self: &Arc<Self>,
+        connection: &Arc<AmqpConnection>,
+    ) -> Result<()> {
+        let mut scopes = self.authorization_scopes.lock().await;
+        let refresh_after = OffsetDateTime::now_utc() + TOKEN_REFRESH_BIAS;
+        for (path, token) in scopes.iter_mut() {
+            if token.expires_on < refresh_after {
+                *token = self
+                    .credential
+                    .get_token(&[SERVICEBUS_AUTHORIZATION_SCOPE], None)
+                    .await?;
+            }
+            debug!("Reauthorizing path: {path}");
+            self.perform_authorization(connection, path, token).await?;
+        }
+        Ok(())
+    }
+
     /// Actually perform an authorization against the Service Bus service.
     ///
     /// This method establishes a connection to the Service Bus service and
@@ -336,13 +362,16 @@ impl Authorizer {
                     .get_token(&[SERVICEBUS_AUTHORIZATION_SCOPE], None)
                     .await?;
 
-                // Create an ephemeral connection to host the authentication.
+                // Authorize on the current connection, which may have been reopened since the path was first authorized.
                 let connection = self.connection.upgrade().ok_or_else(|| {
                     azure_core::Error::with_message(
                         AzureErrorKind::Other,
                         "Connection has been dropped",
                     )
                 })?;
+                let (connection, _) = connection.current().await.map_err(|e| {
+                    azure_core::Error::with_message(AzureErrorKind::Other, e.to_string())
+                })?;
                 self.perform_authorization(&connection, &url, &new_token)
                     .await?;
 
@@ -456,10 +485,7 @@ mod tests {
         let mock_credential = MockTokenCredential::new(15);
 
         let connection = Arc::new(azure_core_amqp::AmqpConnection::new());
-        let authorizer = Arc::new(Authorizer::new(
-            Arc::downgrade(&connection),
-            mock_credential.clone(),
-        ));
+        let authorizer = Arc::new(Authorizer::new(Weak::new(), mock_credential.clone()));
 
         // Disable actual authorization for testing
         authorizer.disable_authorization().unwrap();
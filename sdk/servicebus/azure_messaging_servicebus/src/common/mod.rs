@@ -7,3 +7,11 @@
 ///
 /// Handles Entra ID authentication tokens and automatic token refresh for Service Bus resources.
 pub mod authorizer;
+
+/// Connection recovery for Service Bus operations.
+///
+/// Reopens the AMQP connection after it is lost, and reauthorizes the entity paths used on it.
+pub mod recoverable;
+
+/// Retry functionality for Service Bus operations.
+pub mod retry;
@@ -0,0 +1,230 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use super::{
+    authorizer::Authorizer,
+    retry::{recover_with_backoff, RetryOptions},
+};
+use crate::{ErrorKind, Result, ServiceBusError};
+use async_lock::Mutex;
+use azure_core::{credentials::TokenCredential, http::Url};
+use azure_core_amqp::{AmqpConnection, AmqpConnectionApis, AmqpConnectionOptions};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+const CONNECTION_NAME: &str = "servicebus-client";
+
+/// An AMQP connection to a Service Bus namespace that can be reopened after it is lost.
+///
+/// Each time the connection is reopened its generation is incremented. Senders and receivers
+/// remember the generation their sessions and links were created on, and recreate them when the
+/// connection has been reopened since.
+pub(crate) struct RecoverableConnection {
+    namespace: String,
+    endpoint: Url,
+    connection_options: Option<AmqpConnectionOptions>,
+    retry_options: RetryOptions,
+    authorizer: Option<Arc<Authorizer>>,
+    state: Mutex<ConnectionState>,
+}
+
+struct ConnectionState {
+    connection: Arc<AmqpConnection>,
+    generation: u64,
+    closed: bool,
+}
+
+impl RecoverableConnection {
+    /// Creates a connection to a namespace. The connection is not opened until [`open`](Self::open) is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `fully_qualified_namespace` - The namespace, such as `myservicebus.servicebus.windows.net`.
+    /// * `credential` - The credential used to authorize entity paths, if any.
+    /// * `connection_options` - Options used each time the connection is opened.
+    /// * `retry_options` - Options for retrying operations on the connection.
+    pub(crate) fn new(
+        fully_qualified_namespace: &str,
+        credential: Option<Arc<dyn TokenCredential>>,
+        connection_options: Option<AmqpConnectionOptions>,
+        retry_options: RetryOptions,
+    ) -> Result<Arc<Self>> {
+        let endpoint =
+            Url::parse(&format!("amqps://{}:5671", fully_qualified_namespace)).map_err(|e| {
+                ServiceBusError::new(
+                    ErrorKind::InvalidRequest,
+                    format!("Invalid endpoint URL: {}", e),
+                )
+            })?;
+        Ok(Arc::new_cyclic(|connection| Self {
+            namespace: fully_qualified_namespace.to_string(),
+            endpoint,
+            connection_options,
+            retry_options,
+            authorizer: credential
+                .map(|credential| Arc::new(Authorizer::new(connection.clone(), credential))),
+            state: Mutex::new(ConnectionState {
+                connection: Arc::new(AmqpConnection::new()),
+                generation: 0,
+                closed: false,
+            }),
+        }))
+    }
+
+    /// Opens the connection, retrying transient failures.
+    pub(crate) async fn open(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.connection = recover_with_backoff(
+            &self.retry_options,
+            || async {
+                let connection = Arc::new(AmqpConnection::new());
+                self.open_connection(&connection).await?;
+                Ok(connection)
+            },
+            |_| async { Ok(()) },
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the options for retrying operations on the connection.
+    pub(crate) fn retry_options(&self) -> &RetryOptions {
+        &self.retry_options
+    }
+
+    /// Gets the current AMQP connection and its generation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`ErrorKind::ServiceBusClosed`] if the connection has been closed.
+    pub(crate) async fn current(&self) -> Result<(Arc<AmqpConnection>, u64)> {
+        let state = self.state.lock().await;
+        if state.closed {
+            return Err(Self::closed_error());
+        }
+        Ok((state.connection.clone(), state.generation))
+    }
+
+    /// Gets the generation of the current AMQP connection.
+    pub(crate) async fn generation(&self) -> u64 {
+        self.state.lock().await.generation
+    }
+
+    /// Reopens the connection after a failure on the given generation.
+    ///
+    /// If the connection has already been reopened since that generation, it is not reopened
+    /// again. Entity paths authorized on the failed connection are authorized on the new one.
+    pub(crate) async fn recover(&self, failed_generation: u64) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.closed {
+            return Err(Self::closed_error());
+        }
+        if state.generation != failed_generation {
+            debug!(
+                "Connection already recovered from generation {}",
+                failed_generation
+            );
+            return Ok(());
+        }
+
+        info!(
+            "Reopening connection to {} after generation {} failed",
+            self.namespace, failed_generation
+        );
+        if let Err(e) = state.connection.close().await {
+            warn!("Failed to close the failed connection: {}", e);
+        }
+        let connection = Arc::new(AmqpConnection::new());
+        self.open_connection(&connection).await?;
+        if let Some(authorizer) = &self.authorizer {
+            authorizer.reauthorize(&connection).await?;
+        }
+        state.connection = connection;
+        state.generation += 1;
+        Ok(())
+    }
+
+    /// Authorizes access to an entity path using the configured credential.
+    pub(crate) async fn authorize_path(&self, entity_path: &str) -> Result<()> {
+        if let Some(authorizer) = &self.authorizer {
+            let entity_url =
+                Url::parse(&format!("amqps://{}:5671/{}", self.namespace, entity_path))
+                    .map_err(azure_core::Error::from)?;
+            let (connection, _) = self.current().await?;
+            authorizer.authorize_path(&connection, &entity_url).await?;
+        }
+        Ok(())
+    }
+
+    /// Closes the connection. It is not reopened after it is closed.
+    pub(crate) async fn close(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.closed = true;
+        state.connection.close().await?;
+        Ok(())
+    }
+
+    async fn open_connection(&self, connection: &AmqpConnection) -> Result<()> {
+        connection
+            .open(
+                CONNECTION_NAME.to_string(),
+                self.endpoint.clone(),
+                self.connection_options.clone(),
+            )
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to open connection"))
+    }
+
+    fn closed_error() -> ServiceBusError {
+        ServiceBusError::new(
+            ErrorKind::ServiceBusClosed,
+            "The connection has been closed",
+        )
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Creates a connection that is never opened, so operations on it fail without retrying.
+    pub(crate) fn create_test_connection() -> Arc<RecoverableConnection> {
+        RecoverableConnection::new(
+            "test.servicebus.windows.net",
+            None,
+            None,
+            RetryOptions::default(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn recover_ignores_stale_generations() -> Result<()> {
+        let connection = create_test_connection();
+        assert_eq!(connection.generation().await, 0);
+
+        // A failure on a generation that has already been replaced doesn't reopen the connection.
+        connection.recover(1).await?;
+        assert_eq!(connection.generation().await, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn closed_connection_is_not_recovered() {
+        let connection = create_test_connection();
+        // Closing an unopened connection fails, but still marks it closed.
+        let _ = connection.close().await;
+
+        let error = connection.current().await.err().unwrap();
+        assert_eq!(*error.kind(), ErrorKind::ServiceBusClosed);
+        let error = connection.recover(0).await.unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::ServiceBusClosed);
+    }
+
+    #[test]
+    fn rejects_invalid_namespace() {
+        assert!(
+            RecoverableConnection::new("in valid", None, None, RetryOptions::default()).is_err()
+        );
+    }
+}
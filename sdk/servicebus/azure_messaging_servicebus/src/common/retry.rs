@@ -0,0 +1,376 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+// cspell: ignore retryable backoff
+
+use crate::{ErrorKind, Result, ServiceBusError};
+use azure_core::{fmt::SafeDebug, http::StatusCode, sleep::sleep, time::Duration};
+use azure_core_amqp::{error::AmqpErrorKind, AmqpError, AmqpErrorCondition};
+use rand::random;
+use std::future::Future;
+use tracing::{debug, info, warn};
+
+/// Action to be taken after a Service Bus operation fails.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum ErrorRecoveryAction {
+    /// The error is transient. Retry the operation.
+    RetryAction,
+    /// The connection is broken. Reopen the connection, then reattach sessions and links.
+    ReconnectConnection,
+    /// The link or its session is broken. Reattach them.
+    ReconnectLink,
+    /// The error is not retryable. Return it.
+    ReturnError,
+}
+
+/// Options for configuring exponential backoff retry behavior.
+///
+/// Senders and receivers retry operations that fail with transient errors. When the connection
+/// or a link is lost, they reopen it before retrying.
+#[derive(SafeDebug, Clone)]
+pub struct RetryOptions {
+    /// The initial backoff delay (Default is 200ms).
+    pub initial_delay: Duration,
+
+    /// The maximum backoff delay (Default is 30s).
+    pub max_delay: Duration,
+
+    /// The maximum total elapsed time for retries (Default is 60s).
+    pub max_total_elapsed: Duration,
+
+    /// The maximum number of retries (Default is 8). Set to 0 to disable retries.
+    pub max_retries: u32,
+}
+
+impl Default for RetryOptions {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::milliseconds(200),
+            max_delay: Duration::seconds(30),
+            max_total_elapsed: Duration::seconds(60),
+            max_retries: 8,
+        }
+    }
+}
+
+impl RetryOptions {
+    /// Calculates the backoff before the given retry, with up to 255ms of jitter.
+    fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .initial_delay
+            .checked_mul(2_i32.saturating_pow(retry))
+            .unwrap_or(self.max_delay)
+            + Duration::milliseconds(i64::from(random::<u8>()));
+        delay.min(self.max_delay)
+    }
+}
+
+/// Runs an operation, retrying it with exponential backoff while it fails with recoverable errors.
+///
+/// Before retrying an operation that failed because its connection or link was lost, `recover`
+/// is called to reopen them. If recovery fails, its error is returned.
+///
+/// # Arguments
+///
+/// * `options` - Configuration options for the retry policy.
+/// * `operation` - The operation to run.
+/// * `recover` - Function that reopens the connection or link, according to the recovery action.
+pub(crate) async fn recover_with_backoff<T, F, Fut, R, RFut>(
+    options: &RetryOptions,
+    mut operation: F,
+    mut recover: R,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+    R: FnMut(ErrorRecoveryAction) -> RFut,
+    RFut: Future<Output = Result<()>>,
+{
+    let start_time = std::time::Instant::now();
+    let mut retry = 0u32;
+    loop {
+        let error = match operation().await {
+            Ok(result) => {
+                if retry > 0 {
+                    info!("Operation succeeded after {} retries", retry);
+                }
+                return Ok(result);
+            }
+            Err(error) => error,
+        };
+
+        let action = recovery_action(&error);
+        if action == ErrorRecoveryAction::ReturnError {
+            debug!(err = %error, "Error is not retryable, returning.");
+            return Err(error);
+        }
+        if retry >= options.max_retries || start_time.elapsed() >= options.max_total_elapsed {
+            warn!(
+                err = %error,
+                max_retries = options.max_retries,
+                elapsed = ?start_time.elapsed(),
+                "Maximum retries reached or time elapsed, returning error."
+            );
+            return Err(error);
+        }
+
+        if action != ErrorRecoveryAction::RetryAction {
+            warn!(err = %error, action = ?action, "Operation failed, recovering.");
+            recover(action).await?;
+        }
+
+        let backoff = options.backoff(retry);
+        debug!(
+            err = %error,
+            backoff = ?backoff,
+            retry = retry + 1,
+            "Operation failed, retrying after backoff."
+        );
+        sleep(backoff).await;
+        retry += 1;
+    }
+}
+
+/// Classifies a Service Bus error into the action needed to recover from it.
+///
+/// Errors that the service reports about messages, sessions or entities are returned to the
+/// caller. Otherwise the AMQP error that caused the failure, if any, decides the action.
+pub(crate) fn recovery_action(error: &ServiceBusError) -> ErrorRecoveryAction {
+    match error.kind() {
+        ErrorKind::Amqp | ErrorKind::RequestTimeout | ErrorKind::Unknown => {}
+        _ => return ErrorRecoveryAction::ReturnError,
+    }
+
+    let mut cause = std::error::Error::source(error);
+    while let Some(error) = cause {
+        if let Some(amqp_error) = error.downcast_ref::<AmqpError>() {
+            if !matches!(amqp_error.kind(), AmqpErrorKind::AzureCore(_)) {
+                return amqp_recovery_action(amqp_error);
+            }
+        }
+        cause = error.source();
+    }
+    ErrorRecoveryAction::ReturnError
+}
+
+/// Classifies an AMQP error into the action needed to recover from it.
+fn amqp_recovery_action(error: &AmqpError) -> ErrorRecoveryAction {
+    match error.kind() {
+        AmqpErrorKind::ManagementStatusCode(status, _) => match *status {
+            StatusCode::RequestTimeout
+            | StatusCode::TooManyRequests
+            | StatusCode::InternalServerError
+            | StatusCode::BadGateway
+            | StatusCode::ServiceUnavailable
+            | StatusCode::GatewayTimeout => ErrorRecoveryAction::RetryAction,
+            _ => ErrorRecoveryAction::ReturnError,
+        },
+        AmqpErrorKind::ConnectionClosedByRemote(_)
+        | AmqpErrorKind::ConnectionDetachedByRemote(_)
+        | AmqpErrorKind::ConnectionDropped(_)
+        | AmqpErrorKind::FramingError(_)
+        | AmqpErrorKind::IdleTimeoutElapsed(_) => ErrorRecoveryAction::ReconnectConnection,
+        AmqpErrorKind::SessionClosedByRemote(_)
+        | AmqpErrorKind::SessionDetachedByRemote(_)
+        | AmqpErrorKind::LinkClosedByRemote(_)
+        | AmqpErrorKind::LinkDetachedByRemote(_)
+        | AmqpErrorKind::LinkStateError(_)
+        | AmqpErrorKind::DetachError(_)
+        | AmqpErrorKind::TransferLimitExceeded(_) => ErrorRecoveryAction::ReconnectLink,
+        AmqpErrorKind::AmqpDescribedError(described) => match described.condition {
+            AmqpErrorCondition::ServerBusyError
+            | AmqpErrorCondition::TimeoutError
+            | AmqpErrorCondition::InternalError
+            | AmqpErrorCondition::EntityUpdated
+            | AmqpErrorCondition::OperationCancelled => ErrorRecoveryAction::RetryAction,
+            AmqpErrorCondition::ConnectionForced | AmqpErrorCondition::ConnectionFramingError => {
+                ErrorRecoveryAction::ReconnectConnection
+            }
+            AmqpErrorCondition::LinkDetachForced | AmqpErrorCondition::LinkStolen => {
+                ErrorRecoveryAction::ReconnectLink
+            }
+            _ => ErrorRecoveryAction::ReturnError,
+        },
+        _ => ErrorRecoveryAction::ReturnError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core_amqp::AmqpDescribedError;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    };
+
+    fn fast_retries(max_retries: u32) -> RetryOptions {
+        RetryOptions {
+            initial_delay: Duration::milliseconds(1),
+            max_delay: Duration::milliseconds(5),
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    fn amqp_error(kind: AmqpErrorKind) -> ServiceBusError {
+        AmqpError::from(kind).into()
+    }
+
+    fn described(condition: AmqpErrorCondition) -> ServiceBusError {
+        amqp_error(AmqpErrorKind::AmqpDescribedError(AmqpDescribedError::new(
+            condition,
+            None,
+            Default::default(),
+        )))
+    }
+
+    #[test]
+    fn classifies_errors() {
+        assert_eq!(
+            recovery_action(&amqp_error(AmqpErrorKind::ConnectionDropped(
+                std::io::Error::other("reset").into()
+            ))),
+            ErrorRecoveryAction::ReconnectConnection
+        );
+        assert_eq!(
+            recovery_action(&amqp_error(AmqpErrorKind::LinkDetachedByRemote(
+                std::io::Error::other("detached").into()
+            ))),
+            ErrorRecoveryAction::ReconnectLink
+        );
+        assert_eq!(
+            recovery_action(&described(AmqpErrorCondition::ServerBusyError)),
+            ErrorRecoveryAction::RetryAction
+        );
+        assert_eq!(
+            recovery_action(&described(AmqpErrorCondition::UnauthorizedAccess)),
+            ErrorRecoveryAction::ReturnError
+        );
+        assert_eq!(
+            recovery_action(&amqp_error(AmqpErrorKind::ManagementStatusCode(
+                StatusCode::ServiceUnavailable,
+                None
+            ))),
+            ErrorRecoveryAction::RetryAction
+        );
+        assert_eq!(
+            recovery_action(&described(AmqpErrorCondition::MessageLockLost)),
+            ErrorRecoveryAction::ReturnError
+        );
+        assert_eq!(
+            recovery_action(&ServiceBusError::new(ErrorKind::Amqp, "no source")),
+            ErrorRecoveryAction::ReturnError
+        );
+    }
+
+    #[test]
+    fn classifies_wrapped_errors() {
+        let inner = AmqpError::from(AmqpErrorKind::ConnectionDropped(
+            std::io::Error::other("reset").into(),
+        ));
+        let wrapped = AmqpError::from(azure_core::Error::with_error(
+            azure_core::error::ErrorKind::Other,
+            inner,
+            "Could not attach",
+        ));
+        assert_eq!(
+            recovery_action(&ServiceBusError::from(wrapped)),
+            ErrorRecoveryAction::ReconnectConnection
+        );
+    }
+
+    #[tokio::test]
+    async fn recovers_before_retrying() {
+        let attempts = AtomicU32::new(0);
+        let recoveries = Mutex::new(Vec::new());
+        let result = recover_with_backoff(
+            &fast_retries(3),
+            || async {
+                match attempts.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err(amqp_error(AmqpErrorKind::ConnectionDropped(
+                        std::io::Error::other("reset").into(),
+                    ))),
+                    1 => Err(described(AmqpErrorCondition::ServerBusyError)),
+                    _ => Ok("sent"),
+                }
+            },
+            |action| {
+                recoveries.lock().unwrap().push(action);
+                async { Ok(()) }
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "sent");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            *recoveries.lock().unwrap(),
+            vec![ErrorRecoveryAction::ReconnectConnection]
+        );
+    }
+
+    #[tokio::test]
+    async fn returns_errors_that_are_not_retryable() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = recover_with_backoff(
+            &fast_retries(3),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(described(AmqpErrorCondition::MessageLockLost))
+            },
+            |_| async { Ok(()) },
+        )
+        .await;
+
+        assert_eq!(*result.unwrap_err().kind(), ErrorKind::MessageLockLost);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stops_after_max_retries() {
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = recover_with_backoff(
+            &fast_retries(2),
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(described(AmqpErrorCondition::ServerBusyError))
+            },
+            |_| async { Ok(()) },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn returns_recovery_errors() {
+        let result: Result<()> = recover_with_backoff(
+            &fast_retries(2),
+            || async {
+                Err(amqp_error(AmqpErrorKind::LinkDetachedByRemote(
+                    std::io::Error::other("detached").into(),
+                )))
+            },
+            |_| async {
+                Err(ServiceBusError::new(
+                    ErrorKind::SessionLockLost,
+                    "session lost",
+                ))
+            },
+        )
+        .await;
+
+        assert_eq!(*result.unwrap_err().kind(), ErrorKind::SessionLockLost);
+    }
+
+    #[test]
+    fn backoff_is_bounded() {
+        let options = RetryOptions::default();
+        assert!(options.backoff(0) >= Duration::milliseconds(200));
+        assert!(options.backoff(0) < Duration::milliseconds(456));
+        assert_eq!(options.backoff(20), Duration::seconds(30));
+        assert_eq!(options.backoff(u32::MAX), Duration::seconds(30));
+    }
+}
@@ -1,7 +1,8 @@
 // Copyright (c) Microsoft Corporation. All Rights reserved
 // Licensed under the MIT license.
 
-use azure_core::{error::ErrorKind as CoreErrorKind, fmt::SafeDebug};
+use azure_core::{error::ErrorKind as CoreErrorKind, fmt::SafeDebug, http::StatusCode};
+use azure_core_amqp::{error::AmqpErrorKind, AmqpError};
 use std::fmt;
 
 /// The kind of Service Bus error.
@@ -13,6 +14,8 @@ pub enum ErrorKind {
     Cancelled,
     /// The entity (queue, topic, or subscription) was not found.
     EntityNotFound,
+    /// The entity (queue, topic, subscription, or rule) already exists.
+    EntityAlreadyExists,
     /// The request was invalid or malformed.
     InvalidRequest,
     /// A message lock was lost.
@@ -27,6 +30,9 @@ pub enum ErrorKind {
     RequestTimeout,
     /// The sender or receiver has been closed.
     ServiceBusClosed,
+    /// The requested session could not be locked, because it is locked by another receiver
+    /// or no session became available.
+    SessionCannotBeLocked,
     /// A session lock was lost.
     SessionLockLost,
     /// An unknown error occurred.
@@ -39,6 +45,7 @@ impl fmt::Display for ErrorKind {
             ErrorKind::Amqp => write!(f, "AMQP error"),
             ErrorKind::Cancelled => write!(f, "Operation was cancelled"),
             ErrorKind::EntityNotFound => write!(f, "Entity not found"),
+            ErrorKind::EntityAlreadyExists => write!(f, "Entity already exists"),
             ErrorKind::InvalidRequest => write!(f, "Invalid request"),
             ErrorKind::MessageLockLost => write!(f, "Message lock lost"),
             ErrorKind::MessageNotFound => write!(f, "Message not found"),
@@ -46,6 +53,7 @@ impl fmt::Display for ErrorKind {
             ErrorKind::QuotaExceeded => write!(f, "Quota exceeded"),
             ErrorKind::RequestTimeout => write!(f, "Request timeout"),
             ErrorKind::ServiceBusClosed => write!(f, "Service Bus client closed"),
+            ErrorKind::SessionCannotBeLocked => write!(f, "Session cannot be locked"),
             ErrorKind::SessionLockLost => write!(f, "Session lock lost"),
             ErrorKind::Unknown => write!(f, "Unknown error"),
         }
@@ -57,7 +65,7 @@ impl fmt::Display for ErrorKind {
 pub struct ServiceBusError {
     kind: ErrorKind,
     message: String,
-    source: Option<Box<dyn std::error::Error + 'static>>,
+    source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
 }
 
 impl ServiceBusError {
@@ -74,7 +82,7 @@ impl ServiceBusError {
     pub fn with_source(
         kind: ErrorKind,
         message: impl Into<String>,
-        source: Box<dyn std::error::Error + 'static>,
+        source: Box<dyn std::error::Error + Send + Sync + 'static>,
     ) -> Self {
         Self {
             kind,
@@ -102,7 +110,7 @@ impl fmt::Display for ServiceBusError {
 
 impl std::error::Error for ServiceBusError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        self.source.as_deref()
+        self.source.as_deref().map(|e| e as _)
     }
 }
 
@@ -110,17 +118,103 @@ impl From<azure_core::error::Error> for ServiceBusError {
     fn from(error: azure_core::error::Error) -> Self {
         let kind = match error.kind() {
             CoreErrorKind::Io => ErrorKind::Amqp,
-            CoreErrorKind::HttpResponse { .. } => ErrorKind::InvalidRequest,
+            CoreErrorKind::HttpResponse { status, .. } => match *status {
+                StatusCode::NotFound => ErrorKind::EntityNotFound,
+                StatusCode::Conflict => ErrorKind::EntityAlreadyExists,
+                StatusCode::RequestTimeout => ErrorKind::RequestTimeout,
+                _ => ErrorKind::InvalidRequest,
+            },
             CoreErrorKind::Other => ErrorKind::Unknown,
             _ => ErrorKind::Unknown,
         };
 
-        ServiceBusError::new(kind, error.to_string())
+        ServiceBusError::with_source(kind, error.to_string(), Box::new(error))
     }
 }
 
-impl From<azure_core_amqp::AmqpError> for ServiceBusError {
-    fn from(error: azure_core_amqp::AmqpError) -> Self {
-        ServiceBusError::with_source(ErrorKind::Amqp, format!("{}", error), Box::new(error))
+impl ServiceBusError {
+    /// Creates an error from an AMQP error, preserving the Service Bus error condition reported by the service.
+    pub(crate) fn from_amqp(error: AmqpError, context: impl fmt::Display) -> Self {
+        let kind = amqp_error_kind(&error);
+        ServiceBusError::with_source(kind, format!("{}: {:?}", context, error), Box::new(error))
+    }
+}
+
+impl From<AmqpError> for ServiceBusError {
+    fn from(error: AmqpError) -> Self {
+        let kind = amqp_error_kind(&error);
+        ServiceBusError::with_source(kind, format!("{}", error), Box::new(error))
+    }
+}
+
+/// Maps the error condition of an AMQP error to a Service Bus error kind.
+fn amqp_error_kind(error: &AmqpError) -> ErrorKind {
+    match error.kind() {
+        AmqpErrorKind::AmqpDescribedError(described) => match <&str>::from(&described.condition) {
+            "com.microsoft:session-cannot-be-locked" => ErrorKind::SessionCannotBeLocked,
+            "com.microsoft:session-lock-lost" => ErrorKind::SessionLockLost,
+            "com.microsoft:message-lock-lost" => ErrorKind::MessageLockLost,
+            "com.microsoft:timeout" => ErrorKind::RequestTimeout,
+            "amqp:not-found" => ErrorKind::EntityNotFound,
+            _ => ErrorKind::Amqp,
+        },
+        // The management link reports errors as HTTP-like status codes.
+        AmqpErrorKind::ManagementStatusCode(status, description) => {
+            let description = description.as_deref().unwrap_or_default();
+            match *status {
+                StatusCode::Gone if description.contains("session") => ErrorKind::SessionLockLost,
+                StatusCode::Gone => ErrorKind::MessageLockLost,
+                StatusCode::RequestTimeout => ErrorKind::RequestTimeout,
+                StatusCode::NotFound => ErrorKind::EntityNotFound,
+                _ => ErrorKind::Amqp,
+            }
+        }
+        _ => ErrorKind::Amqp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core_amqp::{error::AmqpDescribedError, AmqpErrorCondition, AmqpOrderedMap};
+
+    fn described(condition: &str) -> AmqpError {
+        AmqpErrorKind::AmqpDescribedError(AmqpDescribedError::new(
+            AmqpErrorCondition::UnknownValue(condition.to_string()),
+            None,
+            AmqpOrderedMap::new(),
+        ))
+        .into()
+    }
+
+    #[test]
+    fn maps_session_error_conditions() {
+        let error = ServiceBusError::from(described("com.microsoft:session-cannot-be-locked"));
+        assert_eq!(error.kind(), &ErrorKind::SessionCannotBeLocked);
+
+        let error = ServiceBusError::from_amqp(
+            described("com.microsoft:session-lock-lost"),
+            "Failed to receive",
+        );
+        assert_eq!(error.kind(), &ErrorKind::SessionLockLost);
+        assert!(error.message().starts_with("Failed to receive"));
+
+        let error = ServiceBusError::from(described("amqp:internal-error"));
+        assert_eq!(error.kind(), &ErrorKind::Amqp);
+    }
+
+    #[test]
+    fn maps_management_status_codes() {
+        let error = ServiceBusError::from(AmqpError::from(AmqpErrorKind::ManagementStatusCode(
+            StatusCode::Gone,
+            Some("The session lock has expired on the session".to_string()),
+        )));
+        assert_eq!(error.kind(), &ErrorKind::SessionLockLost);
+
+        let error = ServiceBusError::from(AmqpError::from(AmqpErrorKind::ManagementStatusCode(
+            StatusCode::Gone,
+            Some("The lock supplied is invalid".to_string()),
+        )));
+        assert_eq!(error.kind(), &ErrorKind::MessageLockLost);
     }
 }
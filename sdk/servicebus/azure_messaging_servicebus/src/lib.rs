@@ -6,6 +6,8 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+/// Service Bus administration client for managing entities.
+pub mod administration;
 /// Service Bus client
 pub mod client;
 mod error;
@@ -16,18 +18,34 @@ pub mod receiver;
 /// Service Bus message sending functionality and options.
 pub mod sender;
 
+/// Service Bus session receiving functionality and options.
+pub mod session_receiver;
+
+/// Service Bus transactions.
+pub mod transaction;
+
+/// Service Bus message processors.
+pub mod processor;
+
 /// Models and types used throughout the Service Bus client.
 pub mod models;
 
 /// Common types and utilities.
 mod common;
 
+pub use administration::{ServiceBusAdministrationClient, ServiceBusAdministrationClientOptions};
+pub use azure_core_amqp::{AmqpProxyOptions, AmqpTransportType};
 pub use client::{
     CreateReceiverOptions, CreateSenderOptions, ServiceBusClient, ServiceBusClientBuilder,
     ServiceBusClientOptions, SubQueue,
 };
+pub use common::retry::RetryOptions;
 pub use error::{ErrorKind, ServiceBusError};
 pub use message::{Message, MessageBatch, ReceivedMessage};
+pub use processor::{
+    MessageHandler, ServiceBusProcessor, ServiceBusProcessorOptions, ServiceBusSessionProcessor,
+    ServiceBusSessionProcessorOptions,
+};
 pub use receiver::{
     AbandonMessageOptions, CompleteMessageOptions, DeadLetterMessageOptions, DeferMessageOptions,
     PeekMessagesOptions, ReceiveDeferredMessagesOptions, ReceiveMessageOptions, ReceiveMode,
@@ -38,6 +56,11 @@ pub use sender::{
     ScheduleMessagesOptions, SendMessageBatchOptions, SendMessageOptions, SendMessagesOptions,
     Sender,
 };
+pub use session_receiver::{
+    AcceptSessionOptions, GetSessionStateOptions, RenewSessionLockOptions, SessionReceiver,
+    SetSessionStateOptions,
+};
+pub use transaction::{BeginTransactionOptions, ServiceBusTransaction};
 
 /// Result type used throughout the Service Bus client.
 pub type Result<T> = std::result::Result<T, ServiceBusError>;
@@ -5,7 +5,7 @@ use crate::{ErrorKind, Result, ServiceBusError};
 use azure_core::{fmt::SafeDebug, time::Duration, Uuid};
 use azure_core_amqp::{
     message::{AmqpApplicationProperties, AmqpMessageBody, AmqpMessageId, AmqpMessageProperties},
-    AmqpMessage, AmqpSimpleValue, AmqpSymbol,
+    AmqpMessage, AmqpSimpleValue, AmqpSymbol, AmqpValue,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -14,8 +14,10 @@ use time::OffsetDateTime;
 /// A message to be sent to Service Bus.
 #[derive(SafeDebug, Clone)]
 pub struct Message {
-    body: Vec<u8>,
-    properties: HashMap<String, String>,
+    body: AmqpMessageBody,
+    /// The data sections of a body that has more than one, concatenated.
+    joined_body: Option<Vec<u8>>,
+    properties: HashMap<String, AmqpSimpleValue>,
     session_id: Option<String>,
     message_id: Option<String>,
     correlation_id: Option<String>,
@@ -31,7 +33,8 @@ impl Message {
     /// Creates a new message with the specified body.
     pub fn new<T: Into<Vec<u8>>>(body: T) -> Self {
         Self {
-            body: body.into(),
+            body: AmqpMessageBody::Binary(vec![body.into()]),
+            joined_body: None,
             properties: HashMap::new(),
             session_id: None,
             message_id: None,
@@ -45,29 +48,44 @@ impl Message {
         }
     }
 
-    /// Gets the message body as bytes.
+    /// Gets the message body as bytes, concatenating its data sections.
+    ///
+    /// Returns an empty slice if the body is not binary data. Use
+    /// [`amqp_body`](Message::amqp_body) to get value and sequence bodies.
     pub fn body(&self) -> &[u8] {
-        &self.body
+        data_body(&self.body, &self.joined_body).unwrap_or_default()
     }
 
     /// Gets the message body as a string, if it's valid UTF-8.
     pub fn body_as_string(&self) -> Result<String> {
-        String::from_utf8(self.body.clone())
-            .map_err(|_| ServiceBusError::new(ErrorKind::InvalidRequest, "Body is not valid UTF-8"))
+        body_as_string(&self.body, &self.joined_body)
+    }
+
+    /// Gets the AMQP body of the message.
+    pub fn amqp_body(&self) -> &AmqpMessageBody {
+        &self.body
+    }
+
+    /// Sets the AMQP body of the message.
+    ///
+    /// Use this to send an AMQP value or sequence body instead of binary data.
+    pub fn set_amqp_body(&mut self, body: impl Into<AmqpMessageBody>) {
+        self.body = body.into();
+        self.joined_body = join_data_sections(&self.body);
     }
 
     /// Sets a custom property on the message.
-    pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<String>) {
+    pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<AmqpSimpleValue>) {
         self.properties.insert(key.into(), value.into());
     }
 
     /// Gets a custom property from the message.
-    pub fn property(&self, key: &str) -> Option<&String> {
+    pub fn property(&self, key: &str) -> Option<&AmqpSimpleValue> {
         self.properties.get(key)
     }
 
     /// Gets all custom properties.
-    pub fn properties(&self) -> &HashMap<String, String> {
+    pub fn properties(&self) -> &HashMap<String, AmqpSimpleValue> {
         &self.properties
     }
 
@@ -177,8 +195,10 @@ impl From<String> for Message {
 /// A message received from Service Bus.
 #[derive(SafeDebug, Clone)]
 pub struct ReceivedMessage {
-    body: Vec<u8>,
-    properties: HashMap<String, String>,
+    message: AmqpMessage,
+    /// The data sections of a body that has more than one, concatenated.
+    joined_body: Option<Vec<u8>>,
+    properties: HashMap<String, AmqpSimpleValue>,
     system_properties: SystemProperties,
     lock_token: Option<Uuid>,
 }
@@ -214,42 +234,67 @@ pub struct SystemProperties {
     pub dead_letter_reason: Option<String>,
     /// The dead letter error description.
     pub dead_letter_error_description: Option<String>,
+    /// The time the message lock expires, for messages received in peek-lock mode.
+    pub locked_until: Option<OffsetDateTime>,
 }
 
 impl ReceivedMessage {
     /// Creates a new received message.
     pub(crate) fn new(
-        body: Vec<u8>,
-        properties: HashMap<String, String>,
+        message: AmqpMessage,
         system_properties: SystemProperties,
         lock_token: Option<Uuid>,
     ) -> Self {
+        let properties = message
+            .application_properties
+            .as_ref()
+            .map(|properties| {
+                properties
+                    .0
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
         Self {
-            body,
+            joined_body: join_data_sections(&message.body),
+            message,
             properties,
             system_properties,
             lock_token,
         }
     }
 
-    /// Gets the message body as bytes.
+    /// Gets the message body as bytes, concatenating its data sections.
+    ///
+    /// Returns an empty slice if the body is not binary data. Use
+    /// [`amqp_body`](ReceivedMessage::amqp_body) to get value and sequence bodies.
     pub fn body(&self) -> &[u8] {
-        &self.body
+        data_body(&self.message.body, &self.joined_body).unwrap_or_default()
     }
 
     /// Gets the message body as a string, if it's valid UTF-8.
     pub fn body_as_string(&self) -> Result<String> {
-        String::from_utf8(self.body.clone())
-            .map_err(|_| ServiceBusError::new(ErrorKind::InvalidRequest, "Body is not valid UTF-8"))
+        body_as_string(&self.message.body, &self.joined_body)
+    }
+
+    /// Gets the AMQP body of the message.
+    pub fn amqp_body(&self) -> &AmqpMessageBody {
+        &self.message.body
+    }
+
+    /// Gets the AMQP message as it was received, including sections that are not otherwise exposed.
+    pub fn raw_amqp_message(&self) -> &AmqpMessage {
+        &self.message
     }
 
     /// Gets a custom property from the message.
-    pub fn property(&self, key: &str) -> Option<&String> {
+    pub fn property(&self, key: &str) -> Option<&AmqpSimpleValue> {
         self.properties.get(key)
     }
 
     /// Gets all custom properties.
-    pub fn properties(&self) -> &HashMap<String, String> {
+    pub fn properties(&self) -> &HashMap<String, AmqpSimpleValue> {
         &self.properties
     }
 
@@ -292,15 +337,18 @@ impl ReceivedMessage {
     pub fn delivery_count(&self) -> Option<u32> {
         self.system_properties.delivery_count
     }
+
+    /// Gets the time the message lock expires, for messages received in peek-lock mode.
+    pub fn locked_until(&self) -> Option<OffsetDateTime> {
+        self.system_properties.locked_until
+    }
 }
 
 impl From<Message> for AmqpMessage {
     fn from(message: Message) -> Self {
         let mut amqp_message_builder = AmqpMessage::builder();
 
-        // Set the body as binary data
-        amqp_message_builder =
-            amqp_message_builder.with_body(AmqpMessageBody::Binary(vec![message.body]));
+        amqp_message_builder = amqp_message_builder.with_body(message.body);
 
         // Set message properties
         let mut properties = AmqpMessageProperties::default();
@@ -325,13 +373,21 @@ impl From<Message> for AmqpMessage {
             properties.subject = Some(subject);
         }
 
+        if let Some(session_id) = message.session_id {
+            properties.group_id = Some(session_id);
+        }
+
+        if let Some(reply_to_session_id) = message.reply_to_session_id {
+            properties.reply_to_group_id = Some(reply_to_session_id);
+        }
+
         amqp_message_builder = amqp_message_builder.with_properties(properties);
 
         // Add application properties
         if !message.properties.is_empty() {
             let mut app_props = AmqpApplicationProperties::new();
             for (key, value) in message.properties {
-                app_props.insert(key, AmqpSimpleValue::String(value));
+                app_props.insert(key, value);
             }
             amqp_message_builder = amqp_message_builder.with_application_properties(app_props);
         }
@@ -476,7 +532,16 @@ impl MessageBatch {
         let mut size = 0;
 
         // Message body size
-        size += message.body.len();
+        size += match &message.body {
+            AmqpMessageBody::Binary(data) => data.iter().map(Vec::len).sum(),
+            AmqpMessageBody::Sequence(lists) => lists
+                .iter()
+                .flat_map(|list| list.iter())
+                .map(estimate_value_size)
+                .sum(),
+            AmqpMessageBody::Value(value) => estimate_value_size(value),
+            AmqpMessageBody::Empty => 0,
+        };
 
         // Message properties overhead (estimated)
         if let Some(id) = &message.message_id {
@@ -497,7 +562,7 @@ impl MessageBatch {
 
         // Custom properties
         for (key, value) in &message.properties {
-            size += key.len() + value.len() + 32; // Key-value pair overhead
+            size += key.len() + estimate_simple_value_size(value) + 32; // Key-value pair overhead
         }
 
         // AMQP frame overhead (estimated)
@@ -507,9 +572,68 @@ impl MessageBatch {
     }
 }
 
+/// Concatenates the data sections of a body that has more than one, so the body can be
+/// borrowed as a single slice.
+fn join_data_sections(body: &AmqpMessageBody) -> Option<Vec<u8>> {
+    match body {
+        AmqpMessageBody::Binary(data) if data.len() > 1 => Some(data.concat()),
+        _ => None,
+    }
+}
+
+/// Gets the bytes of a binary body, given its data sections joined by [`join_data_sections`].
+fn data_body<'a>(body: &'a AmqpMessageBody, joined_body: &'a Option<Vec<u8>>) -> Option<&'a [u8]> {
+    match body {
+        AmqpMessageBody::Binary(data) => Some(
+            joined_body
+                .as_deref()
+                .or_else(|| data.first().map(Vec::as_slice))
+                .unwrap_or_default(),
+        ),
+        _ => None,
+    }
+}
+
+fn body_as_string(body: &AmqpMessageBody, joined_body: &Option<Vec<u8>>) -> Result<String> {
+    let data = data_body(body, joined_body).ok_or_else(|| {
+        ServiceBusError::new(ErrorKind::InvalidRequest, "Body is not binary data")
+    })?;
+    String::from_utf8(data.to_vec())
+        .map_err(|_| ServiceBusError::new(ErrorKind::InvalidRequest, "Body is not valid UTF-8"))
+}
+
+/// Estimates the encoded size of an AMQP value.
+fn estimate_value_size(value: &AmqpValue) -> usize {
+    match value {
+        AmqpValue::Binary(bytes) => bytes.len() + 5,
+        AmqpValue::String(string) => string.len() + 5,
+        AmqpValue::Symbol(symbol) => symbol.0.len() + 5,
+        AmqpValue::List(list) => list.iter().map(estimate_value_size).sum::<usize>() + 9,
+        AmqpValue::Array(values) => values.iter().map(estimate_value_size).sum::<usize>() + 9,
+        AmqpValue::Map(map) => {
+            map.iter()
+                .map(|(key, value)| estimate_value_size(key) + estimate_value_size(value))
+                .sum::<usize>()
+                + 9
+        }
+        _ => 17,
+    }
+}
+
+/// Estimates the encoded size of an AMQP simple value.
+fn estimate_simple_value_size(value: &AmqpSimpleValue) -> usize {
+    match value {
+        AmqpSimpleValue::Binary(bytes) => bytes.len() + 5,
+        AmqpSimpleValue::String(string) => string.len() + 5,
+        AmqpSimpleValue::Symbol(symbol) => symbol.0.len() + 5,
+        _ => 17,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use azure_core_amqp::AmqpList;
 
     #[test]
     fn from_static_str() {
@@ -536,6 +660,84 @@ mod tests {
         let message: Message = text.into();
         assert_eq!(message.body_as_string().unwrap(), "Dynamic message 42");
     }
+
+    #[test]
+    fn session_id_maps_to_group_id() {
+        let mut message = Message::from("Session message");
+        message.set_session_id("session-1");
+        message.set_reply_to_session_id("session-2");
+
+        let amqp_message = AmqpMessage::from(message);
+        let properties = amqp_message.properties.unwrap();
+        assert_eq!(properties.group_id.as_deref(), Some("session-1"));
+        assert_eq!(properties.reply_to_group_id.as_deref(), Some("session-2"));
+    }
+
+    #[test]
+    fn typed_properties_map_to_application_properties() {
+        let mut message = Message::from("Typed properties");
+        message.set_property("count", 42i64);
+        message.set_property("id", Uuid::nil());
+        message.set_property("payload", vec![1u8, 2, 3]);
+
+        let amqp_message = AmqpMessage::from(message);
+        let properties = amqp_message.application_properties.unwrap();
+        assert_eq!(properties.0.get("count"), Some(&AmqpSimpleValue::Long(42)));
+        assert_eq!(
+            properties.0.get("id"),
+            Some(&AmqpSimpleValue::Uuid(Uuid::nil()))
+        );
+        assert_eq!(
+            properties.0.get("payload"),
+            Some(&AmqpSimpleValue::Binary(vec![1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn value_body_is_sent_as_value() {
+        let mut message = Message::from("replaced");
+        message.set_amqp_body(AmqpValue::Int(7));
+
+        assert!(message.body().is_empty());
+        assert!(message.body_as_string().is_err());
+
+        let amqp_message = AmqpMessage::from(message);
+        assert_eq!(amqp_message.body, AmqpMessageBody::Value(AmqpValue::Int(7)));
+    }
+
+    #[test]
+    fn received_message_keeps_amqp_message() {
+        let mut application_properties = AmqpApplicationProperties::new();
+        application_properties.insert("count".to_string(), 42i32);
+        let amqp_message = AmqpMessage::builder()
+            .with_body(AmqpMessageBody::Sequence(vec![AmqpList(vec![
+                AmqpValue::String("a".to_string()),
+            ])]))
+            .with_application_properties(application_properties)
+            .build();
+
+        let message = ReceivedMessage::new(amqp_message.clone(), SystemProperties::default(), None);
+        assert_eq!(message.property("count"), Some(&AmqpSimpleValue::Int(42)));
+        assert!(matches!(message.amqp_body(), AmqpMessageBody::Sequence(_)));
+        assert!(message.body().is_empty());
+        assert_eq!(message.raw_amqp_message(), &amqp_message);
+    }
+
+    #[test]
+    fn data_sections_are_concatenated() {
+        let body = AmqpMessageBody::Binary(vec![b"Hello, ".to_vec(), b"world!".to_vec()]);
+
+        let mut message = Message::from("replaced");
+        message.set_amqp_body(body.clone());
+        assert_eq!(message.body(), b"Hello, world!");
+        assert_eq!(message.body_as_string().unwrap(), "Hello, world!");
+
+        let amqp_message = AmqpMessage::builder().with_body(body.clone()).build();
+        let message = ReceivedMessage::new(amqp_message, SystemProperties::default(), None);
+        assert_eq!(message.body(), b"Hello, world!");
+        assert_eq!(message.body_as_string().unwrap(), "Hello, world!");
+        assert_eq!(message.amqp_body(), &body);
+    }
 }
 
 #[cfg(test)]
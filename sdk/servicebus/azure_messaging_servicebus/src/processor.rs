@@ -0,0 +1,972 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Service Bus message processors.
+//!
+//! A [`ServiceBusProcessor`] receives messages from a queue or subscription and dispatches them
+//! to a [`MessageHandler`], running up to a configurable number of handlers concurrently.
+//! A [`ServiceBusSessionProcessor`] does the same for session-enabled entities, processing
+//! several sessions at a time.
+//!
+//! While a handler is running, the processor renews the lock on its message. When the handler
+//! returns, the message is completed if it succeeded and abandoned if it failed. Errors are
+//! reported to [`MessageHandler::process_error`] and do not stop the processor.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use azure_messaging_servicebus::{
+//!     processor::{MessageHandler, ProcessErrorContext, ProcessMessageContext},
+//!     Result, ServiceBusClient, ServiceBusProcessorOptions,
+//! };
+//! use azure_identity::DeveloperToolsCredential;
+//! use std::sync::Arc;
+//!
+//! struct Handler;
+//!
+//! #[async_trait::async_trait]
+//! impl MessageHandler for Handler {
+//!     async fn process_message(&self, context: &ProcessMessageContext<'_>) -> Result<()> {
+//!         println!("Received: {}", context.message().body_as_string()?);
+//!         Ok(())
+//!     }
+//!
+//!     async fn process_error(&self, context: ProcessErrorContext) {
+//!         eprintln!("Error from {:?}: {}", context.error_source, context.error);
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+//! let credential = DeveloperToolsCredential::new(None)?;
+//! let client = ServiceBusClient::builder().open("myservicebus.servicebus.windows.net", credential.clone()).await?;
+//! let processor = client
+//!     .create_processor(
+//!         "my-queue",
+//!         Arc::new(Handler),
+//!         Some(ServiceBusProcessorOptions {
+//!             max_concurrent_calls: 4,
+//!             ..Default::default()
+//!         }),
+//!     )
+//!     .await?;
+//!
+//! // Process messages until `processor.stop()` is called from another task.
+//! processor.run().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    client::CreateReceiverOptions, AcceptSessionOptions, ErrorKind, ReceiveMessageOptions,
+    ReceiveMode, ReceivedMessage, Receiver, Result, ServiceBusClient, ServiceBusError,
+    SessionReceiver, SubQueue,
+};
+use async_trait::async_trait;
+use azure_core::{
+    fmt::SafeDebug,
+    sleep::sleep,
+    time::{Duration, OffsetDateTime},
+};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, Either, Fuse, FusedFuture, Shared},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt,
+};
+use std::{
+    collections::HashSet,
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, warn};
+
+const DEFAULT_MAX_AUTO_LOCK_RENEWAL_DURATION: Duration = Duration::minutes(5);
+const DEFAULT_MAX_CONCURRENT_SESSIONS: usize = 8;
+const DEFAULT_SESSION_IDLE_TIMEOUT: Duration = Duration::minutes(1);
+
+/// How long to wait before renewing a lock whose expiration time is unknown.
+const DEFAULT_LOCK_RENEWAL_INTERVAL: Duration = Duration::seconds(10);
+
+/// The minimum time between lock renewals.
+const MIN_LOCK_RENEWAL_INTERVAL: Duration = Duration::seconds(1);
+
+/// How long to wait before receiving again after a receive fails.
+const ERROR_RETRY_DELAY: Duration = Duration::seconds(1);
+
+/// Handles messages received by a [`ServiceBusProcessor`] or [`ServiceBusSessionProcessor`].
+#[async_trait]
+pub trait MessageHandler: Send + Sync {
+    /// Processes a received message.
+    ///
+    /// If [`auto_complete_messages`](ServiceBusProcessorOptions::auto_complete_messages) is enabled,
+    /// the message is completed if this returns `Ok`, and abandoned so that it can be redelivered if it returns an error.
+    /// Otherwise, the handler is responsible for settling the message using [`ProcessMessageContext::receiver`].
+    async fn process_message(&self, context: &ProcessMessageContext<'_>) -> Result<()>;
+
+    /// Called when an error occurs while processing messages, including errors returned by
+    /// [`process_message`](MessageHandler::process_message).
+    ///
+    /// The default implementation logs the error.
+    async fn process_error(&self, context: ProcessErrorContext) {
+        warn!(
+            "Error processing messages from {} ({:?}): {}",
+            context.entity_path, context.error_source, context.error
+        );
+    }
+}
+
+/// The message being processed and the receiver it was received from.
+pub struct ProcessMessageContext<'a> {
+    message: &'a ReceivedMessage,
+    receiver: &'a Receiver,
+    session: Option<&'a SessionReceiver>,
+}
+
+impl ProcessMessageContext<'_> {
+    /// Gets the message being processed.
+    pub fn message(&self) -> &ReceivedMessage {
+        self.message
+    }
+
+    /// Gets the receiver the message was received from, which can be used to settle the message.
+    pub fn receiver(&self) -> &Receiver {
+        self.receiver
+    }
+
+    /// Gets the session receiver the message was received from, if it was received by a [`ServiceBusSessionProcessor`].
+    ///
+    /// The session receiver can be used to get and set session state.
+    pub fn session_receiver(&self) -> Option<&SessionReceiver> {
+        self.session
+    }
+}
+
+/// The operation that caused an error reported to [`MessageHandler::process_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessErrorSource {
+    /// Receiving messages failed.
+    Receive,
+    /// Accepting a session failed.
+    AcceptSession,
+    /// The message handler returned an error.
+    ProcessMessageCallback,
+    /// Completing a message failed.
+    Complete,
+    /// Abandoning a message failed.
+    Abandon,
+    /// Renewing a message lock failed.
+    RenewLock,
+    /// Renewing a session lock failed.
+    RenewSessionLock,
+    /// Closing a session receiver failed.
+    CloseSession,
+}
+
+/// An error reported to [`MessageHandler::process_error`].
+#[derive(SafeDebug)]
+pub struct ProcessErrorContext {
+    /// The error.
+    pub error: ServiceBusError,
+
+    /// The operation that failed.
+    pub error_source: ProcessErrorSource,
+
+    /// The path of the entity being processed.
+    pub entity_path: String,
+
+    /// The ID of the session being processed, if any.
+    pub session_id: Option<String>,
+}
+
+/// Options for a [`ServiceBusProcessor`].
+#[derive(Clone, SafeDebug)]
+pub struct ServiceBusProcessorOptions {
+    /// The receive mode for the processor's receiver.
+    pub receive_mode: ReceiveMode,
+
+    /// The sub-queue to process (e.g., dead letter queue).
+    pub sub_queue: Option<SubQueue>,
+
+    /// The maximum number of messages handled concurrently. Defaults to 1.
+    pub max_concurrent_calls: usize,
+
+    /// Whether messages are completed or abandoned automatically based on the result of the handler. Defaults to `true`.
+    ///
+    /// Ignored in [`ReceiveMode::ReceiveAndDelete`] mode.
+    pub auto_complete_messages: bool,
+
+    /// The maximum time for which the lock on a message is renewed while its handler is running. Defaults to 5 minutes.
+    ///
+    /// A zero duration disables automatic lock renewal.
+    pub max_auto_lock_renewal_duration: Duration,
+}
+
+impl Default for ServiceBusProcessorOptions {
+    fn default() -> Self {
+        Self {
+            receive_mode: ReceiveMode::PeekLock,
+            sub_queue: None,
+            max_concurrent_calls: 1,
+            auto_complete_messages: true,
+            max_auto_lock_renewal_duration: DEFAULT_MAX_AUTO_LOCK_RENEWAL_DURATION,
+        }
+    }
+}
+
+/// Options for a [`ServiceBusSessionProcessor`].
+#[derive(Clone, SafeDebug)]
+pub struct ServiceBusSessionProcessorOptions {
+    /// The receive mode for the session receivers.
+    pub receive_mode: ReceiveMode,
+
+    /// The maximum number of sessions processed concurrently. Defaults to 8.
+    pub max_concurrent_sessions: usize,
+
+    /// The maximum number of messages handled concurrently within a session. Defaults to 1,
+    /// which preserves the order of messages within each session.
+    pub max_concurrent_calls_per_session: usize,
+
+    /// Whether messages are completed or abandoned automatically based on the result of the handler. Defaults to `true`.
+    ///
+    /// Ignored in [`ReceiveMode::ReceiveAndDelete`] mode.
+    pub auto_complete_messages: bool,
+
+    /// The maximum time for which the lock on a session is renewed while it is being processed. Defaults to 5 minutes.
+    ///
+    /// A zero duration disables automatic lock renewal.
+    pub max_auto_lock_renewal_duration: Duration,
+
+    /// The IDs of the sessions to process. If empty, the next available sessions are processed.
+    pub session_ids: Vec<String>,
+
+    /// How long to wait for the next message in a session before closing it and accepting another session.
+    /// Defaults to 1 minute.
+    pub session_idle_timeout: Duration,
+}
+
+impl Default for ServiceBusSessionProcessorOptions {
+    fn default() -> Self {
+        Self {
+            receive_mode: ReceiveMode::PeekLock,
+            max_concurrent_sessions: DEFAULT_MAX_CONCURRENT_SESSIONS,
+            max_concurrent_calls_per_session: 1,
+            auto_complete_messages: true,
+            max_auto_lock_renewal_duration: DEFAULT_MAX_AUTO_LOCK_RENEWAL_DURATION,
+            session_ids: Vec::new(),
+            session_idle_timeout: DEFAULT_SESSION_IDLE_TIMEOUT,
+        }
+    }
+}
+
+/// Receives messages from a queue or subscription and dispatches them to a [`MessageHandler`].
+///
+/// Create a `ServiceBusProcessor` with [`ServiceBusClient::create_processor`] or
+/// [`ServiceBusClient::create_processor_for_subscription`].
+///
+/// Messages are processed at least once, so handlers should be idempotent.
+pub struct ServiceBusProcessor {
+    receiver: Receiver,
+    dispatcher: Dispatcher,
+    max_concurrent_calls: usize,
+    stop: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl ServiceBusProcessor {
+    pub(crate) fn new(
+        receiver: Receiver,
+        handler: Arc<dyn MessageHandler>,
+        options: ServiceBusProcessorOptions,
+    ) -> Result<Self> {
+        let max_concurrent_calls = validate_concurrency(
+            options.max_concurrent_calls,
+            "max_concurrent_calls must be greater than zero",
+        )?;
+        let entity_path = match receiver.subscription_name() {
+            Some(subscription_name) => format!(
+                "{}/subscriptions/{}",
+                receiver.entity_name(),
+                subscription_name
+            ),
+            None => receiver.entity_name().to_string(),
+        };
+        Ok(Self {
+            dispatcher: Dispatcher {
+                handler,
+                entity_path,
+                auto_complete_messages: options.auto_complete_messages,
+                max_auto_lock_renewal_duration: options.max_auto_lock_renewal_duration,
+            },
+            receiver,
+            max_concurrent_calls,
+            stop: Mutex::new(None),
+        })
+    }
+
+    /// Gets the path of the entity being processed.
+    pub fn entity_path(&self) -> &str {
+        &self.dispatcher.entity_path
+    }
+
+    /// Gets the receiver used by the processor.
+    pub fn receiver(&self) -> &Receiver {
+        &self.receiver
+    }
+
+    /// Processes messages until [`stop`](Self::stop) is called.
+    ///
+    /// After stopping, no more messages are received, and this returns once running handlers have completed.
+    pub async fn run(&self) {
+        let stop = self.start();
+        self.dispatcher
+            .pump(&self.receiver, None, self.max_concurrent_calls, None, stop)
+            .await;
+    }
+
+    /// Stops a running processor.
+    ///
+    /// [`run`](Self::run) returns once running handlers have completed.
+    pub fn stop(&self) {
+        if let Some(stop) = self.stop.lock().unwrap().take() {
+            let _ = stop.send(());
+        }
+    }
+
+    fn start(&self) -> StopSignal {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        *self.stop.lock().unwrap() = Some(stop_tx);
+        stop_rx.shared()
+    }
+}
+
+/// Receives messages from the sessions of a session-enabled queue or subscription and dispatches them to a [`MessageHandler`].
+///
+/// Create a `ServiceBusSessionProcessor` with [`ServiceBusClient::create_session_processor`] or
+/// [`ServiceBusClient::create_session_processor_for_subscription`].
+///
+/// Each session is processed until no message is received within
+/// [`session_idle_timeout`](ServiceBusSessionProcessorOptions::session_idle_timeout), after which the session is
+/// closed and another is accepted. The session lock is renewed while the session is being processed.
+pub struct ServiceBusSessionProcessor {
+    client: ServiceBusClient,
+    dispatcher: Dispatcher,
+    options: ServiceBusSessionProcessorOptions,
+    stop: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+impl ServiceBusSessionProcessor {
+    pub(crate) fn new(
+        client: ServiceBusClient,
+        entity_path: String,
+        handler: Arc<dyn MessageHandler>,
+        options: ServiceBusSessionProcessorOptions,
+    ) -> Result<Self> {
+        validate_concurrency(
+            options.max_concurrent_sessions,
+            "max_concurrent_sessions must be greater than zero",
+        )?;
+        validate_concurrency(
+            options.max_concurrent_calls_per_session,
+            "max_concurrent_calls_per_session must be greater than zero",
+        )?;
+        Ok(Self {
+            client,
+            dispatcher: Dispatcher {
+                handler,
+                entity_path,
+                auto_complete_messages: options.auto_complete_messages,
+                max_auto_lock_renewal_duration: options.max_auto_lock_renewal_duration,
+            },
+            options,
+            stop: Mutex::new(None),
+        })
+    }
+
+    /// Gets the path of the entity being processed.
+    pub fn entity_path(&self) -> &str {
+        &self.dispatcher.entity_path
+    }
+
+    /// Processes sessions until [`stop`](Self::stop) is called.
+    ///
+    /// After stopping, no more sessions are accepted and no more messages are received,
+    /// and this returns once running handlers have completed and their sessions are closed.
+    pub async fn run(&self) {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        *self.stop.lock().unwrap() = Some(stop_tx);
+        let stop = stop_rx.shared();
+        let mut stopped = stop.clone().fuse();
+
+        let mut sessions = FuturesUnordered::new();
+        let mut active = HashSet::new();
+        let mut next_session = 0;
+        loop {
+            while !stopped.is_terminated() && sessions.len() < self.options.max_concurrent_sessions
+            {
+                let session_id = if self.options.session_ids.is_empty() {
+                    None
+                } else {
+                    let ids = &self.options.session_ids;
+                    let available = (0..ids.len())
+                        .map(|i| &ids[(next_session + i) % ids.len()])
+                        .position(|id| !active.contains(id));
+                    let Some(offset) = available else {
+                        break;
+                    };
+                    let index = (next_session + offset) % ids.len();
+                    next_session = index + 1;
+                    active.insert(ids[index].clone());
+                    Some(ids[index].as_str())
+                };
+                sessions.push(self.process_session(session_id, stop.clone()));
+            }
+            if sessions.is_empty() {
+                return;
+            }
+
+            futures::select! {
+                session_id = sessions.select_next_some() => {
+                    if let Some(session_id) = session_id {
+                        active.remove(session_id);
+                    }
+                }
+                _ = stopped => {}
+            }
+        }
+    }
+
+    /// Stops a running processor.
+    ///
+    /// [`run`](Self::run) returns once running handlers have completed and their sessions are closed.
+    pub fn stop(&self) {
+        if let Some(stop) = self.stop.lock().unwrap().take() {
+            let _ = stop.send(());
+        }
+    }
+
+    /// Accepts a session and processes its messages until it is idle or the processor is stopped.
+    ///
+    /// Returns the configured session ID, if any, so that it can be accepted again.
+    async fn process_session<'a>(
+        &self,
+        session_id: Option<&'a str>,
+        stop: StopSignal,
+    ) -> Option<&'a str> {
+        let session = futures::select! {
+            session = self.accept_session(session_id).fuse() => session,
+            _ = stop.clone().fuse() => None,
+        };
+        let Some(session) = session else {
+            return session_id;
+        };
+        debug!("Processing session {}", session.session_id());
+
+        let pump = pin!(self.dispatcher.pump(
+            &session,
+            Some(&session),
+            self.options.max_concurrent_calls_per_session,
+            Some(self.options.session_idle_timeout),
+            stop,
+        ));
+        let renew = pin!(self.renew_session_lock(&session));
+        match future::select(pump, renew).await {
+            Either::Left(((), _)) => {}
+            Either::Right((never, _)) => match never {},
+        }
+
+        if let Err(error) = session.close().await {
+            self.dispatcher
+                .report(
+                    error,
+                    ProcessErrorSource::CloseSession,
+                    Some(session.session_id()),
+                )
+                .await;
+        }
+        session_id
+    }
+
+    async fn accept_session(&self, session_id: Option<&str>) -> Option<SessionReceiver> {
+        let options = AcceptSessionOptions {
+            receive_mode: self.options.receive_mode.clone(),
+            max_wait_time: None,
+        };
+        let result = match self
+            .client
+            .create_session_receiver(self.dispatcher.entity_path.clone(), options)
+            .await
+        {
+            Ok(receiver) => match session_id {
+                Some(session_id) => SessionReceiver::accept(receiver, session_id.to_string()).await,
+                None => SessionReceiver::accept_next(receiver, None).await,
+            },
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(session) => Some(session),
+            // No session became available in time.
+            Err(error) if *error.kind() == ErrorKind::RequestTimeout => None,
+            Err(error) => {
+                self.dispatcher
+                    .report(error, ProcessErrorSource::AcceptSession, session_id)
+                    .await;
+                sleep(ERROR_RETRY_DELAY).await;
+                None
+            }
+        }
+    }
+
+    async fn renew_session_lock(&self, session: &SessionReceiver) -> Infallible {
+        if session.receive_mode() != ReceiveMode::PeekLock {
+            return future::pending().await;
+        }
+        renew_lock(
+            self.options.max_auto_lock_renewal_duration,
+            session.session_locked_until(),
+            || async {
+                match session.renew_session_lock(None).await {
+                    Ok(locked_until) => Some(locked_until),
+                    Err(error) => {
+                        self.dispatcher
+                            .report(
+                                error,
+                                ProcessErrorSource::RenewSessionLock,
+                                Some(session.session_id()),
+                            )
+                            .await;
+                        None
+                    }
+                }
+            },
+        )
+        .await
+    }
+}
+
+impl ServiceBusClient {
+    /// Creates a processor for the specified queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_name` - The name of the queue
+    /// * `handler` - The handler called for each message
+    /// * `options` - Optional configuration for the processor
+    pub async fn create_processor(
+        &self,
+        queue_name: &str,
+        handler: Arc<dyn MessageHandler>,
+        options: Option<ServiceBusProcessorOptions>,
+    ) -> Result<ServiceBusProcessor> {
+        let options = options.unwrap_or_default();
+        let receiver = self
+            .create_receiver(queue_name, Some(receiver_options(&options)))
+            .await?;
+        ServiceBusProcessor::new(receiver, handler, options)
+    }
+
+    /// Creates a processor for the specified topic subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the subscription
+    /// * `handler` - The handler called for each message
+    /// * `options` - Optional configuration for the processor
+    pub async fn create_processor_for_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        handler: Arc<dyn MessageHandler>,
+        options: Option<ServiceBusProcessorOptions>,
+    ) -> Result<ServiceBusProcessor> {
+        let options = options.unwrap_or_default();
+        let receiver = self
+            .create_receiver_for_subscription(
+                topic_name,
+                subscription_name,
+                Some(receiver_options(&options)),
+            )
+            .await?;
+        ServiceBusProcessor::new(receiver, handler, options)
+    }
+
+    /// Creates a session processor for the specified session-enabled queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_name` - The name of the session-enabled queue
+    /// * `handler` - The handler called for each message
+    /// * `options` - Optional configuration for the processor
+    pub fn create_session_processor(
+        &self,
+        queue_name: &str,
+        handler: Arc<dyn MessageHandler>,
+        options: Option<ServiceBusSessionProcessorOptions>,
+    ) -> Result<ServiceBusSessionProcessor> {
+        ServiceBusSessionProcessor::new(
+            self.clone_internal(),
+            queue_name.to_string(),
+            handler,
+            options.unwrap_or_default(),
+        )
+    }
+
+    /// Creates a session processor for the specified session-enabled topic subscription.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - The name of the topic
+    /// * `subscription_name` - The name of the session-enabled subscription
+    /// * `handler` - The handler called for each message
+    /// * `options` - Optional configuration for the processor
+    pub fn create_session_processor_for_subscription(
+        &self,
+        topic_name: &str,
+        subscription_name: &str,
+        handler: Arc<dyn MessageHandler>,
+        options: Option<ServiceBusSessionProcessorOptions>,
+    ) -> Result<ServiceBusSessionProcessor> {
+        ServiceBusSessionProcessor::new(
+            self.clone_internal(),
+            format!("{}/subscriptions/{}", topic_name, subscription_name),
+            handler,
+            options.unwrap_or_default(),
+        )
+    }
+}
+
+/// Resolves when the processor is stopped.
+type StopSignal = Shared<oneshot::Receiver<()>>;
+
+/// The outcome of receiving a single message.
+enum Received {
+    Message(Box<ReceivedMessage>),
+    Idle,
+    Failed,
+}
+
+/// Dispatches messages to the handler and settles them.
+struct Dispatcher {
+    handler: Arc<dyn MessageHandler>,
+    entity_path: String,
+    auto_complete_messages: bool,
+    max_auto_lock_renewal_duration: Duration,
+}
+
+impl Dispatcher {
+    /// Receives messages and runs up to `max_concurrent_calls` handlers at a time.
+    ///
+    /// Stops receiving when `stop` resolves, when no message is received within `idle_timeout`, or, for sessions,
+    /// when receiving fails. Returns once running handlers have completed.
+    async fn pump(
+        &self,
+        receiver: &Receiver,
+        session: Option<&SessionReceiver>,
+        max_concurrent_calls: usize,
+        idle_timeout: Option<Duration>,
+        stop: StopSignal,
+    ) {
+        let mut stop = stop.fuse();
+        let mut in_flight = FuturesUnordered::new();
+        let mut receive: Fuse<BoxFuture<'_, Received>> = Fuse::terminated();
+        let mut receiving = true;
+        loop {
+            if receiving && receive.is_terminated() && in_flight.len() < max_concurrent_calls {
+                receive = self.receive(receiver, session, idle_timeout).boxed().fuse();
+            }
+            if !receiving && in_flight.is_empty() {
+                return;
+            }
+
+            futures::select! {
+                received = receive => match received {
+                    Received::Message(message) => {
+                        in_flight.push(self.dispatch(receiver, session, *message));
+                    }
+                    Received::Idle => receiving = false,
+                    Received::Failed => receiving = session.is_none(),
+                },
+                _ = in_flight.select_next_some() => {}
+                _ = stop => {
+                    receiving = false;
+                    receive = Fuse::terminated();
+                }
+            }
+        }
+    }
+
+    async fn receive(
+        &self,
+        receiver: &Receiver,
+        session: Option<&SessionReceiver>,
+        idle_timeout: Option<Duration>,
+    ) -> Received {
+        let options = ReceiveMessageOptions {
+            max_message_count: 1,
+            max_wait_time: idle_timeout,
+        };
+        match receiver.receive_message(Some(options)).await {
+            Ok(Some(message)) => Received::Message(Box::new(message)),
+            Ok(None) => Received::Idle,
+            Err(error) => {
+                self.report(
+                    error,
+                    ProcessErrorSource::Receive,
+                    session.map(SessionReceiver::session_id),
+                )
+                .await;
+                if session.is_none() {
+                    sleep(ERROR_RETRY_DELAY).await;
+                }
+                Received::Failed
+            }
+        }
+    }
+
+    async fn dispatch(
+        &self,
+        receiver: &Receiver,
+        session: Option<&SessionReceiver>,
+        message: ReceivedMessage,
+    ) {
+        let session_id = session.map(SessionReceiver::session_id);
+        let context = ProcessMessageContext {
+            message: &message,
+            receiver,
+            session,
+        };
+        let peek_lock = receiver.receive_mode() == ReceiveMode::PeekLock;
+
+        // Messages in a session are locked by the session lock.
+        let result = if peek_lock && session.is_none() {
+            let process = pin!(self.handler.process_message(&context));
+            let renew = pin!(self.renew_message_lock(receiver, &message));
+            match future::select(process, renew).await {
+                Either::Left((result, _)) => result,
+                Either::Right((never, _)) => match never {},
+            }
+        } else {
+            self.handler.process_message(&context).await
+        };
+
+        let settle = peek_lock && self.auto_complete_messages;
+        match result {
+            Ok(()) if settle => {
+                if let Err(error) = receiver.complete_message(&message, None).await {
+                    self.report(error, ProcessErrorSource::Complete, session_id)
+                        .await;
+                }
+            }
+            Ok(()) => {}
+            Err(error) => {
+                self.report(
+                    error,
+                    ProcessErrorSource::ProcessMessageCallback,
+                    session_id,
+                )
+                .await;
+                if settle {
+                    if let Err(error) = receiver.abandon_message(&message, None).await {
+                        self.report(error, ProcessErrorSource::Abandon, session_id)
+                            .await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn renew_message_lock(
+        &self,
+        receiver: &Receiver,
+        message: &ReceivedMessage,
+    ) -> Infallible {
+        renew_lock(
+            self.max_auto_lock_renewal_duration,
+            message.locked_until(),
+            || async {
+                match receiver.renew_message_lock(message, None).await {
+                    Ok(locked_until) => Some(locked_until),
+                    Err(error) => {
+                        self.report(error, ProcessErrorSource::RenewLock, None)
+                            .await;
+                        None
+                    }
+                }
+            },
+        )
+        .await
+    }
+
+    async fn report(
+        &self,
+        error: ServiceBusError,
+        error_source: ProcessErrorSource,
+        session_id: Option<&str>,
+    ) {
+        self.handler
+            .process_error(ProcessErrorContext {
+                error,
+                error_source,
+                entity_path: self.entity_path.clone(),
+                session_id: session_id.map(str::to_string),
+            })
+            .await;
+    }
+}
+
+/// Renews a lock every half of its remaining duration until `max_duration` has elapsed, and then waits forever.
+///
+/// `renew` returns the new expiration time, or `None` if renewal failed.
+async fn renew_lock<F, Fut>(
+    max_duration: Duration,
+    mut locked_until: Option<OffsetDateTime>,
+    mut renew: F,
+) -> Infallible
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<OffsetDateTime>>,
+{
+    let deadline = OffsetDateTime::now_utc() + max_duration;
+    loop {
+        let now = OffsetDateTime::now_utc();
+        let delay = locked_until.map_or(DEFAULT_LOCK_RENEWAL_INTERVAL, |locked_until| {
+            ((locked_until - now) / 2_i32).max(MIN_LOCK_RENEWAL_INTERVAL)
+        });
+        if now + delay >= deadline {
+            return future::pending().await;
+        }
+        sleep(delay).await;
+        locked_until = renew().await;
+    }
+}
+
+fn validate_concurrency(value: usize, message: &str) -> Result<usize> {
+    if value == 0 {
+        return Err(ServiceBusError::new(ErrorKind::InvalidRequest, message));
+    }
+    Ok(value)
+}
+
+fn receiver_options(options: &ServiceBusProcessorOptions) -> CreateReceiverOptions {
+    CreateReceiverOptions {
+        receive_mode: options.receive_mode.clone(),
+        sub_queue: options.sub_queue.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::ServiceBusClientOptions, common::recoverable::tests::create_test_connection,
+    };
+    use futures::channel::mpsc;
+
+    /// Records the sources of reported errors and fails every message.
+    struct RecordingHandler {
+        errors: mpsc::UnboundedSender<ProcessErrorSource>,
+    }
+
+    #[async_trait]
+    impl MessageHandler for RecordingHandler {
+        async fn process_message(&self, _context: &ProcessMessageContext<'_>) -> Result<()> {
+            Err(ServiceBusError::new(ErrorKind::Unknown, "not expected"))
+        }
+
+        async fn process_error(&self, context: ProcessErrorContext) {
+            let _ = self.errors.unbounded_send(context.error_source);
+        }
+    }
+
+    async fn create_test_receiver(subscription_name: Option<String>) -> Receiver {
+        Receiver::new(
+            create_test_connection(),
+            "test-entity".to_string(),
+            subscription_name,
+            ReceiveMode::PeekLock,
+            ServiceBusClientOptions::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn default_options() {
+        let options = ServiceBusProcessorOptions::default();
+        assert_eq!(options.receive_mode, ReceiveMode::PeekLock);
+        assert_eq!(options.max_concurrent_calls, 1);
+        assert!(options.auto_complete_messages);
+        assert_eq!(options.max_auto_lock_renewal_duration, Duration::minutes(5));
+
+        let options = ServiceBusSessionProcessorOptions::default();
+        assert_eq!(options.max_concurrent_sessions, 8);
+        assert_eq!(options.max_concurrent_calls_per_session, 1);
+        assert!(options.session_ids.is_empty());
+        assert_eq!(options.session_idle_timeout, Duration::minutes(1));
+    }
+
+    #[tokio::test]
+    async fn rejects_zero_concurrency() {
+        let (errors, _) = mpsc::unbounded();
+        let result = ServiceBusProcessor::new(
+            create_test_receiver(None).await,
+            Arc::new(RecordingHandler { errors }),
+            ServiceBusProcessorOptions {
+                max_concurrent_calls: 0,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            result.map(|_| ()).unwrap_err().kind(),
+            ErrorKind::InvalidRequest
+        ));
+    }
+
+    #[tokio::test]
+    async fn entity_path_includes_subscription() {
+        let (errors, _) = mpsc::unbounded();
+        let processor = ServiceBusProcessor::new(
+            create_test_receiver(Some("test-subscription".to_string())).await,
+            Arc::new(RecordingHandler { errors }),
+            ServiceBusProcessorOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            processor.entity_path(),
+            "test-entity/subscriptions/test-subscription"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_receive_errors_until_stopped() {
+        let (errors, mut reported) = mpsc::unbounded();
+        let processor = ServiceBusProcessor::new(
+            create_test_receiver(None).await,
+            Arc::new(RecordingHandler { errors }),
+            ServiceBusProcessorOptions {
+                max_concurrent_calls: 4,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // The connection is not open, so receiving fails.
+        futures::join!(processor.run(), async {
+            assert_eq!(reported.next().await, Some(ProcessErrorSource::Receive));
+            processor.stop();
+        });
+    }
+
+    #[tokio::test]
+    async fn renew_lock_stops_after_max_duration() {
+        let mut renewals = 0;
+        let renew = renew_lock(Duration::ZERO, None, || {
+            renewals += 1;
+            future::ready(None)
+        });
+        assert!(renew.now_or_never().is_none());
+        assert_eq!(renewals, 0);
+    }
+}
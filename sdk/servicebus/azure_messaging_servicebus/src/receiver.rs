@@ -87,6 +87,7 @@
 //!                 reason: Some("ProcessingFailed".to_string()),
 //!                 error_description: Some("Unrecoverable processing error".to_string()),
 //!                 properties_to_modify: None,
+//!                 transaction: None,
 //!             };
 //!             receiver.dead_letter_message(&message, Some(dead_letter_options)).await?;
 //!         }
@@ -99,18 +100,27 @@
 //! ```
 
 use crate::{
-    client::ServiceBusClientOptions, message::SystemProperties, ErrorKind, ReceivedMessage, Result,
-    ServiceBusError,
+    client::ServiceBusClientOptions,
+    common::{
+        recoverable::RecoverableConnection,
+        retry::{recover_with_backoff, ErrorRecoveryAction},
+    },
+    message::SystemProperties,
+    transaction::{ServiceBusTransaction, Settlement},
+    ErrorKind, ReceivedMessage, Result, ServiceBusError,
 };
-use async_lock::{Mutex, OnceCell};
+use async_lock::Mutex;
 use azure_core::{fmt::SafeDebug, time::Duration, time::OffsetDateTime, Uuid};
 use azure_core_amqp::{
-    message::{AmqpMessageBody, AmqpMessageId},
-    AmqpConnection, AmqpDelivery, AmqpDeliveryApis, AmqpManagementApis, AmqpReceiver,
-    AmqpReceiverApis, AmqpSession, AmqpSessionApis, AmqpSimpleValue, AmqpSource, AmqpValue,
+    message::AmqpMessageId, AmqpDelivery, AmqpDeliveryApis, AmqpManagementApis, AmqpMessage,
+    AmqpOrderedMap, AmqpReceiver, AmqpReceiverApis, AmqpSession, AmqpSessionApis, AmqpSimpleValue,
+    AmqpSource, AmqpSymbol, AmqpTimestamp, AmqpValue, Deserializable,
 };
 use futures::{select, FutureExt};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 use tracing::{debug, trace, warn};
 
 /// Represents the lock style to use for a receiver - either `PeekLock` or `ReceiveAndDelete`.
@@ -257,12 +267,13 @@ pub struct PeekMessagesOptions {
 /// This struct provides configuration options for completing messages, which
 /// removes them from the Service Bus entity and marks them as successfully processed.
 /// Message completion is only available in [`ReceiveMode::PeekLock`] mode.
-///
-/// Currently, this struct is defined for future extensibility but contains no
-/// configuration options. Additional options may be added in future versions.
-///
 #[derive(SafeDebug, Clone, Default)]
-pub struct CompleteMessageOptions;
+pub struct CompleteMessageOptions {
+    /// The transaction to complete the message in.
+    ///
+    /// The message is only removed from the entity when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
+}
 
 /// Options for configuring message abandon operations.
 ///
@@ -290,6 +301,7 @@ pub struct CompleteMessageOptions;
 ///
 /// let options_with_properties = AbandonMessageOptions {
 ///     properties_to_modify: Some(properties),
+///     transaction: None,
 /// };
 /// ```
 #[derive(SafeDebug, Clone, Default)]
@@ -303,6 +315,11 @@ pub struct AbandonMessageOptions {
     /// - `Some(properties)` - Modify the specified properties
     /// - `None` - Abandon without modifying any properties
     pub properties_to_modify: Option<std::collections::HashMap<String, String>>,
+
+    /// The transaction to abandon the message in.
+    ///
+    /// The message lock is only released when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
 }
 
 /// Options for configuring message dead letter operations.
@@ -326,6 +343,7 @@ pub struct AbandonMessageOptions {
 ///     reason: Some("ValidationFailed".to_string()),
 ///     error_description: Some("Message schema validation failed".to_string()),
 ///     properties_to_modify: None,
+///     transaction: None,
 /// };
 ///
 /// // Dead letter with custom properties for diagnostics
@@ -337,6 +355,7 @@ pub struct AbandonMessageOptions {
 ///     reason: Some("ProcessingError".to_string()),
 ///     error_description: Some("Failed to process after 3 retries".to_string()),
 ///     properties_to_modify: Some(properties),
+///     transaction: None,
 /// };
 /// ```
 #[derive(SafeDebug, Clone, Default)]
@@ -362,6 +381,11 @@ pub struct DeadLetterMessageOptions {
     /// processing attempts, or storing other metadata that might be helpful for
     /// troubleshooting or reprocessing.
     pub properties_to_modify: Option<std::collections::HashMap<String, String>>,
+
+    /// The transaction to dead letter the message in.
+    ///
+    /// The message is only moved to the dead letter queue when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
 }
 
 /// Options for configuring message defer operations.
@@ -515,6 +539,7 @@ pub struct RenewMessageLockOptions;
 ///                 reason: Some("ValidationFailed".to_string()),
 ///                 error_description: Some("Invalid message format".to_string()),
 ///                 properties_to_modify: None,
+///                 transaction: None,
 ///             };
 ///             receiver.dead_letter_message(&message, Some(dead_letter_options)).await?;
 ///         }
@@ -531,16 +556,26 @@ pub struct RenewMessageLockOptions;
 /// # async fn validate_and_process(message: &azure_messaging_servicebus::ReceivedMessage) -> Result<(), ValidationError> { Ok(()) }
 /// ```
 pub struct Receiver {
-    connection: Arc<AmqpConnection>,
+    connection: Arc<RecoverableConnection>,
     entity_name: String,
     subscription_name: Option<String>,
     receive_mode: ReceiveMode,
     _options: ServiceBusClientOptions,
-    // Cached session and receiver to avoid creating new ones on each call
-    session: OnceCell<Arc<AmqpSession>>,
-    amqp_receiver: OnceCell<Arc<AmqpReceiver>>,
+    // Cached session and receiver, recreated when the connection is reopened
+    links: Mutex<Option<ReceiverLinks>>,
     // Track deliveries by lock token for settlement operations
     delivery_map: Arc<Mutex<HashMap<Uuid, AmqpDelivery>>>,
+    // Session filter for session receivers: the session ID, or null for the next available session
+    session_filter: Option<AmqpValue>,
+    // Deliveries received before they were requested, returned by the next receive
+    buffered_deliveries: Mutex<VecDeque<AmqpDelivery>>,
+}
+
+/// The session and receiver link of a [`Receiver`], and the connection generation they were created on.
+struct ReceiverLinks {
+    generation: u64,
+    session: Arc<AmqpSession>,
+    receiver: Option<Arc<AmqpReceiver>>,
 }
 
 impl Receiver {
@@ -562,7 +597,7 @@ impl Receiver {
     ///
     /// Returns a `Result<Receiver>` that will be `Ok(receiver)` on success or an error on failure.
     pub(crate) async fn new(
-        connection: Arc<AmqpConnection>,
+        connection: Arc<RecoverableConnection>,
         entity_name: String,
         subscription_name: Option<String>,
         receive_mode: ReceiveMode,
@@ -587,12 +622,21 @@ impl Receiver {
             subscription_name,
             receive_mode,
             _options: options,
-            session: OnceCell::new(),
-            amqp_receiver: OnceCell::new(),
+            links: Mutex::new(None),
             delivery_map: Arc::new(Mutex::new(HashMap::new())),
+            session_filter: None,
+            buffered_deliveries: Mutex::new(VecDeque::new()),
         })
     }
 
+    /// Configures the receiver to attach to a session.
+    ///
+    /// If `session_id` is `None`, the service locks the next available session.
+    pub(crate) fn with_session_filter(mut self, session_id: Option<String>) -> Self {
+        self.session_filter = Some(session_id.map_or(AmqpValue::Null, AmqpValue::String));
+        self
+    }
+
     /// Receives a single message from the Service Bus entity.
     ///
     /// This is a convenience method that calls [`receive_messages`](Receiver::receive_messages)
@@ -702,19 +746,24 @@ impl Receiver {
             options.as_ref().and_then(|o| o.max_wait_time)
         );
 
-        // Ensure session and receiver are available
-        let amqp_receiver = self.ensure_receiver().await?;
-
         let mut messages = Vec::new();
 
         for i in 0..max_message_count {
             debug!("receive_messages: iteration {}", i);
 
+            let buffered = self.buffered_deliveries.lock().await.pop_front();
+            if let Some(delivery) = buffered {
+                if let Some(message) = self.convert_delivery_to_message(delivery).await? {
+                    messages.push(message);
+                }
+                continue;
+            }
+
             let delivery_result =
                 if let Some(timeout_duration) = options.as_ref().and_then(|o| o.max_wait_time) {
                     debug!("receive_messages: using timeout {:?}", timeout_duration);
                     select! {
-                        delivery = amqp_receiver.receive_delivery().fuse() => {
+                        delivery = self.receive_delivery().fuse() => {
                             debug!("receive_messages: received delivery on iteration {}", i);
                             delivery
                         },
@@ -729,7 +778,7 @@ impl Receiver {
                         "receive_messages: no timeout, blocking receive on iteration {}",
                         i
                     );
-                    amqp_receiver.receive_delivery().await
+                    self.receive_delivery().await
                 };
 
             match delivery_result {
@@ -742,10 +791,7 @@ impl Receiver {
                 Err(err) => {
                     debug!("receive_messages: error on iteration {}: {:?}", i, err);
                     if messages.is_empty() {
-                        return Err(ServiceBusError::new(
-                            ErrorKind::Amqp,
-                            format!("Error receiving message: {:?}", err),
-                        ));
+                        return Err(err);
                     } else {
                         break;
                     }
@@ -757,59 +803,239 @@ impl Receiver {
         Ok(messages)
     }
 
-    /// Ensures that a session and receiver are available, creating them if necessary.
-    async fn ensure_receiver(&self) -> Result<Arc<AmqpReceiver>> {
-        let receiver = self
-            .amqp_receiver
-            .get_or_try_init(|| async {
-                // First ensure we have a session
-                let session = self.ensure_session().await?;
-
-                // Create AMQP receiver
-                let entity_path = self.get_entity_path();
-                let amqp_source = AmqpSource::builder().with_address(entity_path).build();
-                let amqp_receiver = AmqpReceiver::new();
+    /// Receives the next delivery, reattaching the receiver link after transient failures.
+    async fn receive_delivery(&self) -> Result<AmqpDelivery> {
+        recover_with_backoff(
+            self.connection.retry_options(),
+            || async {
+                let amqp_receiver = self.ensure_receiver().await?;
                 amqp_receiver
-                    .attach(&session, amqp_source, None)
+                    .receive_delivery()
                     .await
-                    .map_err(|e| {
-                        ServiceBusError::new(
-                            ErrorKind::Amqp,
-                            format!("Failed to create receiver: {:?}", e),
-                        )
-                    })?;
-
-                Ok::<Arc<AmqpReceiver>, ServiceBusError>(Arc::new(amqp_receiver))
+                    .map_err(|e| ServiceBusError::from_amqp(e, "Failed to receive message"))
+            },
+            |action| self.recover(action),
+        )
+        .await
+    }
+
+    /// Ensures that a session and receiver are available, creating them if necessary.
+    async fn ensure_receiver(&self) -> Result<Arc<AmqpReceiver>> {
+        let mut links = self.links.lock().await;
+        let links = self.ensure_links(&mut links).await?;
+        if let Some(receiver) = &links.receiver {
+            return Ok(receiver.clone());
+        }
+
+        // Create AMQP receiver
+        let entity_path = self.get_entity_path();
+        let mut amqp_source = AmqpSource::builder().with_address(entity_path);
+        if let Some(session_filter) = &self.session_filter {
+            amqp_source =
+                amqp_source.add_to_filter(AmqpSymbol::from(SESSION_FILTER), session_filter.clone());
+        }
+        let amqp_receiver = AmqpReceiver::new();
+        amqp_receiver
+            .attach(&links.session, amqp_source.build(), None)
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to create receiver"))?;
+
+        let amqp_receiver = Arc::new(amqp_receiver);
+        links.receiver = Some(amqp_receiver.clone());
+        Ok(amqp_receiver)
+    }
+
+    /// Gets the receiver link that the deliveries being tracked were received on.
+    ///
+    /// Deliveries can only be settled on the link they were received on, so the link is never
+    /// recreated here.
+    ///
+    /// Returns the link with the generation of the connection it is attached on.
+    async fn settlement_receiver(&self) -> Result<(Arc<AmqpReceiver>, u64)> {
+        let generation = self.connection.generation().await;
+        self.links
+            .lock()
+            .await
+            .as_ref()
+            .filter(|links| links.generation == generation)
+            .and_then(|links| links.receiver.clone())
+            .map(|receiver| (receiver, generation))
+            .ok_or_else(|| {
+                ServiceBusError::new(
+                    ErrorKind::MessageLockLost,
+                    "The link the message was received on has been lost",
+                )
             })
-            .await?;
+    }
+
+    /// Settles a delivery on the link it was received on, in the transaction if one is given.
+    async fn settle_delivery(
+        &self,
+        delivery: &AmqpDelivery,
+        settlement: Settlement,
+        transaction: Option<&ServiceBusTransaction>,
+    ) -> Result<()> {
+        let (amqp_receiver, generation) = self.settlement_receiver().await?;
+        if let Some(transaction) = transaction {
+            return transaction
+                .settle(&amqp_receiver, generation, delivery, settlement)
+                .await;
+        }
+
+        let (result, operation) = match settlement {
+            Settlement::Accept => (amqp_receiver.accept_delivery(delivery).await, "accept"),
+            Settlement::Reject => (amqp_receiver.reject_delivery(delivery).await, "reject"),
+            Settlement::Release => (amqp_receiver.release_delivery(delivery).await, "release"),
+        };
+        result.map_err(|e| {
+            ServiceBusError::new(
+                ErrorKind::Amqp,
+                format!("Failed to {} delivery: {:?}", operation, e),
+            )
+        })
+    }
+
+    /// Attaches the receiver link if it is not already attached.
+    pub(crate) async fn attach(&self) -> Result<()> {
+        self.ensure_receiver().await.map(|_| ())
+    }
+
+    /// Waits for the first message on a session receiver link and returns its session ID.
+    ///
+    /// The message is buffered and returned by the next receive.
+    pub(crate) async fn receive_session_id(
+        &self,
+        max_wait_time: Option<Duration>,
+    ) -> Result<String> {
+        let amqp_receiver = self.ensure_receiver().await?;
+        let delivery = match max_wait_time {
+            Some(max_wait_time) => select! {
+                delivery = amqp_receiver.receive_delivery().fuse() => delivery,
+                _ = azure_core::sleep::sleep(max_wait_time).fuse() => {
+                    return Err(ServiceBusError::new(
+                        ErrorKind::RequestTimeout,
+                        "No session became available within the wait time",
+                    ));
+                }
+            },
+            None => amqp_receiver.receive_delivery().await,
+        }
+        .map_err(|e| ServiceBusError::from_amqp(e, "Failed to receive from session"))?;
+
+        let session_id = delivery
+            .message()
+            .properties
+            .as_ref()
+            .and_then(|p| p.group_id.clone())
+            .ok_or_else(|| {
+                ServiceBusError::new(
+                    ErrorKind::InvalidRequest,
+                    "Message received from a session does not have a session ID",
+                )
+            })?;
+        self.buffered_deliveries.lock().await.push_back(delivery);
+        Ok(session_id)
+    }
 
-        Ok(receiver.clone())
+    /// Sends a request over the management link and returns the response properties.
+    pub(crate) async fn call_management(
+        &self,
+        operation: &str,
+        application_properties: AmqpOrderedMap<String, AmqpSimpleValue>,
+    ) -> Result<AmqpOrderedMap<String, AmqpValue>> {
+        recover_with_backoff(
+            self.connection.retry_options(),
+            || async {
+                let management_client = self.ensure_management_client().await?;
+                management_client
+                    .call(operation.to_string(), application_properties.clone())
+                    .await
+                    .map_err(|e| ServiceBusError::from_amqp(e, format!("Failed to {}", operation)))
+            },
+            |action| self.recover(action),
+        )
+        .await
     }
 
     /// Ensures that a session is available, creating it if necessary.
     async fn ensure_session(&self) -> Result<Arc<AmqpSession>> {
-        let session = self
-            .session
-            .get_or_try_init(|| async {
-                let session = AmqpSession::new();
-                session
-                    .begin(
-                        self.connection.as_ref(),
-                        Some(azure_core_amqp::AmqpSessionOptions::with_unbounded_windows()),
-                    )
-                    .await
-                    .map_err(|e| {
-                        ServiceBusError::new(
-                            ErrorKind::Amqp,
-                            format!("Failed to create session: {:?}", e),
-                        )
-                    })?;
-
-                Ok::<Arc<AmqpSession>, ServiceBusError>(Arc::new(session))
-            })
-            .await?;
+        let mut links = self.links.lock().await;
+        Ok(self.ensure_links(&mut links).await?.session.clone())
+    }
+
+    /// Ensures that a session is open on the current connection.
+    ///
+    /// Links created on a connection that has since been reopened are discarded.
+    async fn ensure_links<'a>(
+        &self,
+        links: &'a mut Option<ReceiverLinks>,
+    ) -> Result<&'a mut ReceiverLinks> {
+        let (connection, generation) = self.connection.current().await?;
+        if let Some(existing) = links.take() {
+            if existing.generation == generation {
+                return Ok(links.insert(existing));
+            }
+            self.discard_links(existing).await?;
+        }
 
-        Ok(session.clone())
+        debug!(
+            "Beginning session for entity {} on connection generation {}",
+            self.get_entity_path(),
+            generation
+        );
+        let session = AmqpSession::new();
+        session
+            .begin(
+                connection.as_ref(),
+                Some(azure_core_amqp::AmqpSessionOptions::with_unbounded_windows()),
+            )
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to create session"))?;
+
+        Ok(links.insert(ReceiverLinks {
+            generation,
+            session: Arc::new(session),
+            receiver: None,
+        }))
+    }
+
+    /// Discards the links, and reopens the connection if it was lost.
+    async fn recover(&self, action: ErrorRecoveryAction) -> Result<()> {
+        let links = self.links.lock().await.take();
+        if action == ErrorRecoveryAction::ReconnectConnection {
+            let generation = match &links {
+                Some(links) => links.generation,
+                None => self.connection.generation().await,
+            };
+            self.connection.recover(generation).await?;
+        }
+        match links {
+            Some(links) => self.discard_links(links).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Forgets the deliveries received on discarded links, which can no longer be settled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`ErrorKind::SessionLockLost`] if the receiver was attached to a
+    /// session, since the session lock is released when its link is lost.
+    async fn discard_links(&self, links: ReceiverLinks) -> Result<()> {
+        debug!(
+            "Discarding links for entity {} from connection generation {}",
+            self.get_entity_path(),
+            links.generation
+        );
+        self.delivery_map.lock().await.clear();
+        self.buffered_deliveries.lock().await.clear();
+        if self.session_filter.is_some() && links.receiver.is_some() {
+            return Err(ServiceBusError::new(
+                ErrorKind::SessionLockLost,
+                "The session link was lost, releasing the session lock",
+            ));
+        }
+        Ok(())
     }
 
     /// Ensures that a management client is available, creating it if necessary.
@@ -912,7 +1138,7 @@ impl Receiver {
     pub async fn complete_message(
         &self,
         message: &ReceivedMessage,
-        _options: Option<CompleteMessageOptions>,
+        options: Option<CompleteMessageOptions>,
     ) -> Result<()> {
         if self.receive_mode != ReceiveMode::PeekLock {
             return Err(ServiceBusError::new(
@@ -942,16 +1168,9 @@ impl Receiver {
         };
 
         // Accept the delivery using AMQP
-        let amqp_receiver = self.ensure_receiver().await?;
-        amqp_receiver
-            .accept_delivery(&delivery)
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(
-                    ErrorKind::Amqp,
-                    format!("Failed to accept delivery: {:?}", e),
-                )
-            })?;
+        let transaction = options.and_then(|options| options.transaction);
+        self.settle_delivery(&delivery, Settlement::Accept, transaction.as_ref())
+            .await?;
 
         trace!(
             "Message completed successfully with lock token: {}",
@@ -1012,6 +1231,7 @@ impl Receiver {
     ///
     ///             let abandon_options = AbandonMessageOptions {
     ///                 properties_to_modify: Some(properties),
+    ///                 transaction: None,
     ///             };
     ///
     ///             receiver.abandon_message(&message, Some(abandon_options)).await?;
@@ -1031,7 +1251,7 @@ impl Receiver {
     pub async fn abandon_message(
         &self,
         message: &ReceivedMessage,
-        _options: Option<AbandonMessageOptions>,
+        options: Option<AbandonMessageOptions>,
     ) -> Result<()> {
         if self.receive_mode != ReceiveMode::PeekLock {
             return Err(ServiceBusError::new(
@@ -1061,16 +1281,9 @@ impl Receiver {
         };
 
         // Release the delivery using AMQP
-        let amqp_receiver = self.ensure_receiver().await?;
-        amqp_receiver
-            .release_delivery(&delivery)
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(
-                    ErrorKind::Amqp,
-                    format!("Failed to release delivery: {:?}", e),
-                )
-            })?;
+        let transaction = options.and_then(|options| options.transaction);
+        self.settle_delivery(&delivery, Settlement::Release, transaction.as_ref())
+            .await?;
 
         trace!(
             "Message abandoned successfully with lock token: {}",
@@ -1130,6 +1343,7 @@ impl Receiver {
     ///                 reason: Some("ValidationFailed".to_string()),
     ///                 error_description: Some(format!("Message validation failed: {}", validation_error)),
     ///                 properties_to_modify: Some(properties),
+    ///                 transaction: None,
     ///             };
     ///
     ///             receiver.dead_letter_message(&message, Some(dead_letter_options)).await?;
@@ -1179,16 +1393,9 @@ impl Receiver {
         };
 
         // Reject the delivery using AMQP
-        let amqp_receiver = self.ensure_receiver().await?;
-        amqp_receiver
-            .reject_delivery(&delivery)
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(
-                    ErrorKind::Amqp,
-                    format!("Failed to reject delivery: {:?}", e),
-                )
-            })?;
+        let transaction = options.and_then(|options| options.transaction);
+        self.settle_delivery(&delivery, Settlement::Reject, transaction.as_ref())
+            .await?;
 
         trace!(
             "Message dead lettered successfully with lock token: {}",
@@ -1281,9 +1488,6 @@ impl Receiver {
 
         debug!("Deferring message with lock token: {}", lock_token);
 
-        // Create management client and defer the message
-        let management_client = self.ensure_management_client().await?;
-
         let mut application_properties: azure_core_amqp::AmqpOrderedMap<
             String,
             azure_core_amqp::AmqpSimpleValue,
@@ -1302,15 +1506,9 @@ impl Receiver {
             }
         }
 
-        let _response = management_client
-            .call(
-                "com.microsoft:defer-message".to_string(),
-                application_properties,
-            )
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(ErrorKind::Amqp, format!("Failed to defer message: {:?}", e))
-            })?;
+        let _response = self
+            .call_management("com.microsoft:defer-message", application_properties)
+            .await?;
 
         // Remove the delivery from our tracking map since it's now deferred
         let _delivery = {
@@ -1497,9 +1695,6 @@ impl Receiver {
             sequence_numbers
         );
 
-        // Create management client and receive the deferred messages
-        let management_client = self.ensure_management_client().await?;
-
         let mut application_properties: azure_core_amqp::AmqpOrderedMap<
             String,
             azure_core_amqp::AmqpSimpleValue,
@@ -1523,18 +1718,12 @@ impl Receiver {
         };
         application_properties.insert("receiver-settle-mode".to_string(), settle_mode.into());
 
-        let response = management_client
-            .call(
-                "com.microsoft:receive-by-sequence-number".to_string(),
+        let response = self
+            .call_management(
+                "com.microsoft:receive-by-sequence-number",
                 application_properties,
             )
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(
-                    ErrorKind::Amqp,
-                    format!("Failed to receive deferred messages: {:?}", e),
-                )
-            })?;
+            .await?;
 
         // Process the response to reconstruct ReceivedMessage instances
         let messages = self.parse_deferred_messages_response(response).await?;
@@ -1554,8 +1743,6 @@ impl Receiver {
         &self,
         response: azure_core_amqp::AmqpOrderedMap<String, azure_core_amqp::AmqpValue>,
     ) -> Result<Vec<ReceivedMessage>> {
-        use std::collections::HashMap;
-
         // Extract the "messages" field from the response
         let messages_value = response.get("messages").ok_or_else(|| {
             ServiceBusError::new(
@@ -1610,20 +1797,10 @@ impl Receiver {
                 }
             };
 
-            // Deserialize the AMQP message from binary data
-            // Note: This is a simplified reconstruction. In a full implementation,
-            // we would need to properly deserialize the AMQP message and extract
-            // all the Service Bus specific properties, system properties, etc.
-
-            // For now, create a basic ReceivedMessage structure
-            // This would need to be enhanced to properly reconstruct from AMQP binary data
-            let system_properties = SystemProperties::default();
-
-            // Create a placeholder ReceivedMessage
-            // TODO: Implement proper AMQP binary deserialization
+            let message = decode_message(message_bytes)?;
+            let system_properties = system_properties(&message);
             let received_message = ReceivedMessage::new(
-                message_bytes.clone(),
-                HashMap::new(),
+                message,
                 system_properties,
                 None, // lock_token - deferred messages typically don't have lock tokens initially
             );
@@ -1706,9 +1883,6 @@ impl Receiver {
 
         debug!("Renewing message lock with lock token: {}", lock_token);
 
-        // Create management client and renew the message lock
-        let management_client = self.ensure_management_client().await?;
-
         let mut application_properties: azure_core_amqp::AmqpOrderedMap<
             String,
             azure_core_amqp::AmqpSimpleValue,
@@ -1717,48 +1891,11 @@ impl Receiver {
         // Add the lock token as a string representation
         application_properties.insert("lock-token".to_string(), lock_token.to_string().into());
 
-        let response = management_client
-            .call(
-                "com.microsoft:renew-lock".to_string(),
-                application_properties,
-            )
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(
-                    ErrorKind::Amqp,
-                    format!("Failed to renew message lock: {:?}", e),
-                )
-            })?;
+        let response = self
+            .call_management("com.microsoft:renew-lock", application_properties)
+            .await?;
 
-        // Extract the new expiration time from the response
-        let locked_until = response
-            .get("expiration")
-            .or_else(|| response.get("locked-until-utc"))
-            .ok_or_else(|| {
-                ServiceBusError::new(
-                    ErrorKind::InvalidRequest,
-                    "Management response did not contain expiration time",
-                )
-            })?;
-
-        // Convert the response value to an OffsetDateTime
-        let locked_until = match locked_until {
-            azure_core_amqp::AmqpValue::TimeStamp(timestamp) => {
-                let timestamp: azure_core_amqp::AmqpTimestamp = timestamp.clone();
-                if let Some(system_time) = timestamp.0 {
-                    OffsetDateTime::from(system_time)
-                } else {
-                    OffsetDateTime::now_utc() + Duration::seconds(60)
-                }
-            }
-            _ => {
-                // Fallback to a default if we can't parse the timestamp
-                warn!(
-                    "Could not parse expiration timestamp from management response, using default"
-                );
-                OffsetDateTime::now_utc() + Duration::seconds(60)
-            }
-        };
+        let locked_until = parse_expiration(&response)?;
 
         trace!(
             "Message lock renewed successfully with lock token: {}, new expiration: {}",
@@ -1912,9 +2049,6 @@ impl Receiver {
             max_count, options
         );
 
-        // Create management client for peek operation
-        let management_client = self.ensure_management_client().await?;
-
         let mut application_properties: azure_core_amqp::AmqpOrderedMap<
             String,
             azure_core_amqp::AmqpSimpleValue,
@@ -1931,15 +2065,9 @@ impl Receiver {
             );
         }
 
-        let response = management_client
-            .call(
-                "com.microsoft:peek-message".to_string(),
-                application_properties,
-            )
-            .await
-            .map_err(|e| {
-                ServiceBusError::new(ErrorKind::Amqp, format!("Failed to peek messages: {:?}", e))
-            })?;
+        let response = self
+            .call_management("com.microsoft:peek-message", application_properties)
+            .await?;
 
         // Process the response to reconstruct ReceivedMessage instances
         let messages = self.parse_peeked_messages_response(response).await?;
@@ -1962,8 +2090,6 @@ impl Receiver {
         &self,
         response: azure_core_amqp::AmqpOrderedMap<String, azure_core_amqp::AmqpValue>,
     ) -> Result<Vec<ReceivedMessage>> {
-        use std::collections::HashMap;
-
         // Extract the "messages" field from the response
         let messages_value = response.get("messages").ok_or_else(|| {
             ServiceBusError::new(
@@ -2018,20 +2144,10 @@ impl Receiver {
                 }
             };
 
-            // Deserialize the AMQP message from binary data
-            // Note: This is a simplified reconstruction. In a full implementation,
-            // we would need to properly deserialize the AMQP message and extract
-            // all the Service Bus specific properties, system properties, etc.
-
-            // For now, create a basic ReceivedMessage structure
-            // This would need to be enhanced to properly reconstruct from AMQP binary data
-            let system_properties = SystemProperties::default();
-
-            // Create a ReceivedMessage for peeked message
-            // Peeked messages do not have lock tokens
+            let message = decode_message(message_bytes)?;
+            let system_properties = system_properties(&message);
             let received_message = ReceivedMessage::new(
-                message_bytes.clone(),
-                HashMap::new(),
+                message,
                 system_properties,
                 None, // lock_token - peeked messages never have lock tokens
             );
@@ -2082,9 +2198,13 @@ impl Receiver {
         let entity_path = self.get_entity_path();
         debug!("Closing Receiver for entity: {}", entity_path);
 
+        let Some(links) = self.links.lock().await.take() else {
+            trace!("Receiver closed successfully for entity: {}", entity_path);
+            return Ok(());
+        };
+
         // Detach the AMQP receiver if it exists
-        if let Some(receiver) = self.amqp_receiver.get() {
-            let receiver = receiver.clone();
+        if let Some(receiver) = links.receiver {
             // Try to get exclusive access to the receiver for detachment
             match Arc::try_unwrap(receiver) {
                 Ok(receiver) => {
@@ -2111,22 +2231,20 @@ impl Receiver {
             }
         }
 
-        // End the AMQP session if it exists
-        if let Some(session) = self.session.get() {
-            match session.end().await {
-                Ok(_) => {
-                    trace!(
-                        "AMQP session ended successfully for entity: {}",
-                        entity_path
-                    );
-                }
-                Err(e) => {
-                    // Log but don't fail - connection might already be closed
-                    warn!(
-                        "Failed to end AMQP session for entity '{}': {}",
-                        entity_path, e
-                    );
-                }
+        // End the AMQP session
+        match links.session.end().await {
+            Ok(_) => {
+                trace!(
+                    "AMQP session ended successfully for entity: {}",
+                    entity_path
+                );
+            }
+            Err(e) => {
+                // Log but don't fail - connection might already be closed
+                warn!(
+                    "Failed to end AMQP session for entity '{}': {}",
+                    entity_path, e
+                );
             }
         }
 
@@ -2147,76 +2265,9 @@ impl Receiver {
         &self,
         delivery: AmqpDelivery,
     ) -> Result<Option<ReceivedMessage>> {
-        let message = delivery.message();
-
-        // Extract body
-        let body = match &message.body {
-            AmqpMessageBody::Binary(binary_data) => {
-                // Combine all binary chunks
-                binary_data
-                    .iter()
-                    .flat_map(|chunk| chunk.iter())
-                    .cloned()
-                    .collect()
-            }
-            AmqpMessageBody::Value(value) => {
-                // Convert value to string and then to bytes
-                format!("{:?}", value).into_bytes()
-            }
-            AmqpMessageBody::Sequence(sequence) => {
-                // Convert sequence to string and then to bytes
-                format!("{:?}", sequence).into_bytes()
-            }
-            AmqpMessageBody::Empty => Vec::new(),
-        };
+        let message = delivery.message().clone();
 
-        // Extract application properties
-        let mut properties = HashMap::new();
-        if let Some(app_props) = message.application_properties.as_ref() {
-            for (key, value) in app_props.0.iter() {
-                let value_str = match value {
-                    AmqpSimpleValue::String(s) => s.clone(),
-                    AmqpSimpleValue::Int(i) => i.to_string(),
-                    AmqpSimpleValue::UInt(u) => u.to_string(),
-                    AmqpSimpleValue::Long(l) => l.to_string(),
-                    AmqpSimpleValue::ULong(ul) => ul.to_string(),
-                    AmqpSimpleValue::Boolean(b) => b.to_string(),
-                    _ => format!("{:?}", value),
-                };
-                properties.insert(key.clone(), value_str);
-            }
-        }
-
-        // Extract system properties from message properties
-        let mut system_properties = SystemProperties::default();
-
-        if let Some(msg_props) = message.properties.as_ref() {
-            if let Some(message_id) = &msg_props.message_id {
-                system_properties.message_id = match message_id {
-                    AmqpMessageId::String(s) => Some(s.clone()),
-                    AmqpMessageId::Uuid(u) => Some(u.to_string()),
-                    AmqpMessageId::Binary(b) => Some(format!("{:?}", b)),
-                    AmqpMessageId::Ulong(u) => Some(u.to_string()),
-                };
-            }
-            if let Some(correlation_id) = &msg_props.correlation_id {
-                system_properties.correlation_id = match correlation_id {
-                    AmqpMessageId::String(s) => Some(s.clone()),
-                    AmqpMessageId::Uuid(u) => Some(u.to_string()),
-                    AmqpMessageId::Binary(b) => Some(format!("{:?}", b)),
-                    AmqpMessageId::Ulong(u) => Some(u.to_string()),
-                };
-            }
-            if let Some(content_type) = &msg_props.content_type {
-                system_properties.content_type = Some(content_type.into());
-            }
-            if let Some(reply_to) = &msg_props.reply_to {
-                system_properties.reply_to = Some(reply_to.clone());
-            }
-            if let Some(subject) = &msg_props.subject {
-                system_properties.subject = Some(subject.clone());
-            }
-        }
+        let system_properties = system_properties(&message);
 
         // Generate a lock token for PeekLock mode and store the delivery
         let lock_token = if self.receive_mode == ReceiveMode::PeekLock {
@@ -2229,13 +2280,116 @@ impl Receiver {
             None
         };
 
-        let received_message =
-            ReceivedMessage::new(body, properties, system_properties, lock_token);
+        let received_message = ReceivedMessage::new(message, system_properties, lock_token);
 
         Ok(Some(received_message))
     }
 }
 
+/// Extracts the system properties of a message from its properties and annotations.
+fn system_properties(message: &AmqpMessage) -> SystemProperties {
+    let mut system_properties = SystemProperties::default();
+
+    if let Some(msg_props) = message.properties.as_ref() {
+        if let Some(message_id) = &msg_props.message_id {
+            system_properties.message_id = match message_id {
+                AmqpMessageId::String(s) => Some(s.clone()),
+                AmqpMessageId::Uuid(u) => Some(u.to_string()),
+                AmqpMessageId::Binary(b) => Some(format!("{:?}", b)),
+                AmqpMessageId::Ulong(u) => Some(u.to_string()),
+            };
+        }
+        if let Some(correlation_id) = &msg_props.correlation_id {
+            system_properties.correlation_id = match correlation_id {
+                AmqpMessageId::String(s) => Some(s.clone()),
+                AmqpMessageId::Uuid(u) => Some(u.to_string()),
+                AmqpMessageId::Binary(b) => Some(format!("{:?}", b)),
+                AmqpMessageId::Ulong(u) => Some(u.to_string()),
+            };
+        }
+        if let Some(content_type) = &msg_props.content_type {
+            system_properties.content_type = Some(content_type.into());
+        }
+        if let Some(reply_to) = &msg_props.reply_to {
+            system_properties.reply_to = Some(reply_to.clone());
+        }
+        if let Some(subject) = &msg_props.subject {
+            system_properties.subject = Some(subject.clone());
+        }
+        if let Some(group_id) = &msg_props.group_id {
+            system_properties.session_id = Some(group_id.clone());
+        }
+        if let Some(reply_to_group_id) = &msg_props.reply_to_group_id {
+            system_properties.reply_to_session_id = Some(reply_to_group_id.clone());
+        }
+    }
+
+    if let Some(annotations) = message.message_annotations.as_ref() {
+        system_properties.locked_until = annotations
+            .0
+            .iter()
+            .find(|(key, _)| **key == LOCKED_UNTIL_ANNOTATION)
+            .and_then(|(_, value)| match value {
+                AmqpValue::TimeStamp(AmqpTimestamp(Some(time))) => {
+                    Some(OffsetDateTime::from(*time))
+                }
+                _ => None,
+            });
+    }
+
+    system_properties
+}
+
+/// Decodes a message that a management operation returned in its encoded form.
+fn decode_message(message_bytes: &[u8]) -> Result<AmqpMessage> {
+    AmqpMessage::decode(message_bytes).map_err(|e| {
+        ServiceBusError::with_source(
+            ErrorKind::InvalidRequest,
+            "Failed to decode message from management response",
+            Box::new(e),
+        )
+    })
+}
+
+/// The source filter that selects a session.
+const SESSION_FILTER: &str = "com.microsoft:session-filter";
+
+/// The message annotation containing the time the message lock expires.
+const LOCKED_UNTIL_ANNOTATION: &str = "x-opt-locked-until";
+
+/// Extracts the lock expiration time from a management response.
+pub(crate) fn parse_expiration(
+    response: &AmqpOrderedMap<String, AmqpValue>,
+) -> Result<OffsetDateTime> {
+    let locked_until = response
+        .get("expiration")
+        .or_else(|| response.get("locked-until-utc"))
+        .ok_or_else(|| {
+            ServiceBusError::new(
+                ErrorKind::InvalidRequest,
+                "Management response did not contain expiration time",
+            )
+        })?;
+
+    // Convert the response value to an OffsetDateTime
+    let locked_until = match locked_until {
+        AmqpValue::TimeStamp(timestamp) => {
+            let timestamp: azure_core_amqp::AmqpTimestamp = timestamp.clone();
+            if let Some(system_time) = timestamp.0 {
+                OffsetDateTime::from(system_time)
+            } else {
+                OffsetDateTime::now_utc() + Duration::seconds(60)
+            }
+        }
+        _ => {
+            // Fallback to a default if we can't parse the timestamp
+            warn!("Could not parse expiration timestamp from management response, using default");
+            OffsetDateTime::now_utc() + Duration::seconds(60)
+        }
+    };
+    Ok(locked_until)
+}
+
 impl Drop for Receiver {
     fn drop(&mut self) {
         let entity_path = self.get_entity_path();
@@ -2246,13 +2400,10 @@ impl Drop for Receiver {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use azure_core_amqp::AmqpConnection;
-    use std::sync::Arc;
-
-    /// Creates a mock connection for testing
-    fn create_test_connection() -> Arc<AmqpConnection> {
-        Arc::new(AmqpConnection::new())
-    }
+    use crate::common::recoverable::tests::create_test_connection;
+    use azure_core_amqp::message::{
+        AmqpApplicationProperties, AmqpMessageBody, AmqpMessageProperties,
+    };
 
     /// Creates test ServiceBusClientOptions
     fn create_test_options() -> ServiceBusClientOptions {
@@ -2342,9 +2493,10 @@ mod tests {
             subscription_name: None,
             receive_mode: ReceiveMode::PeekLock,
             _options: options,
-            session: OnceCell::new(),
-            amqp_receiver: OnceCell::new(),
+            links: Mutex::new(None),
             delivery_map: Arc::new(Mutex::new(HashMap::new())),
+            session_filter: None,
+            buffered_deliveries: Mutex::new(VecDeque::new()),
         };
 
         assert_eq!(receiver.get_entity_path(), entity_name);
@@ -2363,9 +2515,10 @@ mod tests {
             subscription_name: Some(subscription_name.clone()),
             receive_mode: ReceiveMode::PeekLock,
             _options: options,
-            session: OnceCell::new(),
-            amqp_receiver: OnceCell::new(),
+            links: Mutex::new(None),
             delivery_map: Arc::new(Mutex::new(HashMap::new())),
+            session_filter: None,
+            buffered_deliveries: Mutex::new(VecDeque::new()),
         };
 
         let expected_path = format!("{}/subscriptions/{}", entity_name, subscription_name);
@@ -2392,6 +2545,30 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_with_session_filter() -> Result<()> {
+        let receiver = Receiver::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            ReceiveMode::PeekLock,
+            create_test_options(),
+        )
+        .await?;
+        assert!(receiver.session_filter.is_none());
+
+        let receiver = receiver.with_session_filter(Some("session-1".to_string()));
+        assert_eq!(
+            receiver.session_filter,
+            Some(AmqpValue::String("session-1".to_string()))
+        );
+
+        // No session ID requests the next available session.
+        let receiver = receiver.with_session_filter(None);
+        assert_eq!(receiver.session_filter, Some(AmqpValue::Null));
+        Ok(())
+    }
+
     #[test]
     fn test_receiver_drop() {
         let connection = create_test_connection();
@@ -2404,9 +2581,10 @@ mod tests {
             subscription_name: None,
             receive_mode: ReceiveMode::PeekLock,
             _options: options,
-            session: OnceCell::new(),
-            amqp_receiver: OnceCell::new(),
+            links: Mutex::new(None),
             delivery_map: Arc::new(Mutex::new(HashMap::new())),
+            session_filter: None,
+            buffered_deliveries: Mutex::new(VecDeque::new()),
         };
 
         // Should not panic when dropped
@@ -2430,8 +2608,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2447,6 +2624,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None,
         );
@@ -2473,8 +2651,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2490,6 +2667,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None,
         );
@@ -2516,8 +2694,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2533,6 +2710,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None,
         );
@@ -2568,8 +2746,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2585,6 +2762,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None, // No lock token
         );
@@ -2611,8 +2789,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2628,6 +2805,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None,
         );
@@ -2654,8 +2832,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2671,6 +2848,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None, // No lock token
         );
@@ -2772,8 +2950,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -2789,6 +2966,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -2824,8 +3002,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -2841,6 +3018,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -2878,8 +3056,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -2895,6 +3072,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -2929,8 +3107,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2946,6 +3123,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None,
         );
@@ -2972,8 +3150,7 @@ mod tests {
         .unwrap();
 
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: None,
                 correlation_id: None,
@@ -2989,6 +3166,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             None, // No lock token
         );
@@ -3016,8 +3194,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -3033,6 +3210,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -3069,8 +3247,7 @@ mod tests {
         // Test with a valid UUID lock token format
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -3086,6 +3263,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -3118,8 +3296,7 @@ mod tests {
         let sequence_number = 12345i64;
 
         let message = ReceivedMessage::new(
-            b"test deferred message".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test deferred message".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -3135,6 +3312,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -3176,8 +3354,7 @@ mod tests {
         let lock_token_2 = Uuid::new_v4();
 
         let message_1 = ReceivedMessage::new(
-            b"test message 1".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test message 1".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id-1".to_string()),
                 correlation_id: None,
@@ -3193,13 +3370,13 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token_1),
         );
 
         let message_2 = ReceivedMessage::new(
-            b"test message 2".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test message 2".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id-2".to_string()),
                 correlation_id: None,
@@ -3215,6 +3392,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token_2),
         );
@@ -3249,8 +3427,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -3266,6 +3443,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -3301,8 +3479,7 @@ mod tests {
 
         let lock_token = Uuid::new_v4();
         let message = ReceivedMessage::new(
-            b"test".to_vec(),
-            HashMap::new(),
+            AmqpMessage::from(b"test".to_vec()),
             SystemProperties {
                 message_id: Some("test-msg-id".to_string()),
                 correlation_id: None,
@@ -3318,6 +3495,7 @@ mod tests {
                 dead_letter_source: None,
                 dead_letter_reason: None,
                 dead_letter_error_description: None,
+                locked_until: None,
             },
             Some(lock_token),
         );
@@ -3737,4 +3915,105 @@ mod tests {
         };
         assert_eq!(options.from_sequence_number, Some(12345));
     }
+
+    async fn create_attached_receiver(session_id: Option<Option<String>>) -> Receiver {
+        let mut receiver = Receiver::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            ReceiveMode::PeekLock,
+            create_test_options(),
+        )
+        .await
+        .unwrap();
+        if let Some(session_id) = session_id {
+            receiver = receiver.with_session_filter(session_id);
+        }
+        *receiver.links.lock().await = Some(ReceiverLinks {
+            generation: 0,
+            session: Arc::new(AmqpSession::new()),
+            receiver: Some(Arc::new(AmqpReceiver::new())),
+        });
+        receiver
+    }
+
+    #[tokio::test]
+    async fn test_receive_fails_without_connection() -> Result<()> {
+        let receiver = Receiver::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            ReceiveMode::PeekLock,
+            create_test_options(),
+        )
+        .await?;
+
+        assert!(receiver.receive_messages(1, None).await.is_err());
+        assert!(receiver.links.lock().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recover_discards_links() -> Result<()> {
+        let receiver = create_attached_receiver(None).await;
+        assert!(receiver.settlement_receiver().await.is_ok());
+
+        receiver.recover(ErrorRecoveryAction::ReconnectLink).await?;
+        assert!(receiver.links.lock().await.is_none());
+
+        // Messages received on the discarded link can no longer be settled.
+        let error = receiver.settlement_receiver().await.err().unwrap();
+        assert_eq!(*error.kind(), ErrorKind::MessageLockLost);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recover_session_receiver_loses_session_lock() {
+        let receiver = create_attached_receiver(Some(Some("session-1".to_string()))).await;
+
+        let error = receiver
+            .recover(ErrorRecoveryAction::ReconnectLink)
+            .await
+            .unwrap_err();
+        assert_eq!(*error.kind(), ErrorKind::SessionLockLost);
+        assert!(receiver.links.lock().await.is_none());
+    }
+
+    #[test]
+    fn test_decode_management_message() -> Result<()> {
+        let mut application_properties = AmqpApplicationProperties::new();
+        application_properties.insert("count".to_string(), 42i32);
+        let amqp_message = AmqpMessage::builder()
+            .with_body(AmqpMessageBody::Binary(vec![b"peeked".to_vec()]))
+            .with_properties(AmqpMessageProperties {
+                message_id: Some(AmqpMessageId::String("message-1".to_string())),
+                subject: Some("subject".to_string()),
+                group_id: Some("session-1".to_string()),
+                ..Default::default()
+            })
+            .with_application_properties(application_properties)
+            .build();
+        let message_bytes = AmqpMessage::serialize(&amqp_message).unwrap();
+
+        let message = decode_message(&message_bytes)?;
+        let system_properties = system_properties(&message);
+        let message = ReceivedMessage::new(message, system_properties, None);
+        assert_eq!(message.body(), b"peeked");
+        assert_eq!(message.property("count"), Some(&AmqpSimpleValue::Int(42)));
+        assert_eq!(
+            message.system_properties().message_id.as_deref(),
+            Some("message-1")
+        );
+        assert_eq!(
+            message.system_properties().subject.as_deref(),
+            Some("subject")
+        );
+        assert_eq!(
+            message.system_properties().session_id.as_deref(),
+            Some("session-1")
+        );
+
+        assert!(decode_message(b"not a message").is_err());
+        Ok(())
+    }
 }
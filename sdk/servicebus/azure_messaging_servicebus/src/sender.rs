@@ -1,34 +1,50 @@
 // Copyright (c) Microsoft Corporation. All Rights reserved
 // Licensed under the MIT license.
 
-use crate::{client::ServiceBusClientOptions, ErrorKind, Message, Result, ServiceBusError};
-use azure_core::fmt::SafeDebug;
+use crate::{
+    client::ServiceBusClientOptions,
+    common::{
+        recoverable::RecoverableConnection,
+        retry::{recover_with_backoff, ErrorRecoveryAction},
+    },
+    transaction::ServiceBusTransaction,
+    ErrorKind, Message, Result, ServiceBusError,
+};
+use async_lock::Mutex;
+use azure_core::{fmt::SafeDebug, Uuid};
 use azure_core_amqp::{
-    AmqpConnection, AmqpMessage, AmqpSender, AmqpSenderApis, AmqpSession, AmqpSessionApis,
-    AmqpTarget,
+    error::AmqpErrorKind, AmqpError, AmqpMessage, AmqpOrderedMap, AmqpSendOutcome, AmqpSender,
+    AmqpSenderApis, AmqpSenderOptions, AmqpSession, AmqpSessionApis, AmqpSymbol, AmqpTarget,
 };
 use std::sync::Arc;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
+
+/// The link property that tells the service to forward messages sent via another entity.
+const TRANSFER_DESTINATION_ADDRESS: &str = "com.microsoft:transfer-destination-address";
 
 /// Options for sending a single message.
 ///
 /// This struct contains optional parameters that can be specified when sending
 /// a message using [`Sender::send_message`].
-///
-/// Currently no options are available, but this provides
-/// extensibility for future parameters
 #[derive(SafeDebug, Clone, Default)]
-pub struct SendMessageOptions;
+pub struct SendMessageOptions {
+    /// The transaction to send the message in.
+    ///
+    /// The message is only enqueued when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
+}
 
 /// Options for sending multiple messages.
 ///
 /// This struct contains optional parameters that can be specified when sending
 /// multiple messages using [`Sender::send_messages`].
-///
-/// Currently no options are available, but this provides
-/// extensibility for future parameters
 #[derive(SafeDebug, Clone, Default)]
-pub struct SendMessagesOptions;
+pub struct SendMessagesOptions {
+    /// The transaction to send the messages in.
+    ///
+    /// The messages are only enqueued when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
+}
 
 /// Options for creating a message batch.
 ///
@@ -57,11 +73,13 @@ pub struct CreateMessageBatchOptions {
 ///
 /// This struct contains optional parameters that can be specified when sending
 /// a message batch using [`Sender::send_message_batch`].
-///
-/// Currently no options are available, but this provides
-/// extensibility for future parameters
 #[derive(SafeDebug, Clone, Default)]
-pub struct SendMessageBatchOptions;
+pub struct SendMessageBatchOptions {
+    /// The transaction to send the messages in.
+    ///
+    /// The messages are only enqueued when the transaction is committed.
+    pub transaction: Option<ServiceBusTransaction>,
+}
 
 /// Options for scheduling a message.
 ///
@@ -95,16 +113,28 @@ pub struct CancelScheduledMessagesOptions;
 
 /// A sender for sending messages to a Service Bus queue or topic.
 pub struct Sender {
-    connection: Arc<AmqpConnection>,
+    connection: Arc<RecoverableConnection>,
     entity_name: String,
-    //options: Option<ServiceBusClientOptions>,
+    // The entity messages are sent through on their way to `entity_name`, if any
+    via_entity_name: Option<String>,
+    // The link messages are sent on, created on first use and recreated after it is lost
+    link: Mutex<Option<SenderLink>>,
+}
+
+/// The session and link a sender sends messages on.
+struct SenderLink {
+    /// The generation of the connection the link was created on.
+    generation: u64,
+    session: AmqpSession,
+    sender: Arc<AmqpSender>,
 }
 
 impl Sender {
     /// Creates a new sender.
     pub(crate) async fn new(
-        connection: Arc<AmqpConnection>,
+        connection: Arc<RecoverableConnection>,
         entity_name: String,
+        via_entity_name: Option<String>,
         _options: ServiceBusClientOptions,
     ) -> Result<Self> {
         debug!("Creating Sender for entity: {}", entity_name);
@@ -114,7 +144,8 @@ impl Sender {
         Ok(Self {
             connection,
             entity_name,
-            //options: Some(options),
+            via_entity_name,
+            link: Mutex::new(None),
         })
     }
 
@@ -144,37 +175,14 @@ impl Sender {
     pub async fn send_message(
         &self,
         message: Message,
-        _options: Option<SendMessageOptions>,
+        options: Option<SendMessageOptions>,
     ) -> Result<()> {
         debug!("Sending message to entity: {}", self.entity_name);
 
-        // Create AMQP session
-        let session = AmqpSession::new();
-        session.begin(&self.connection, None).await?;
-
-        // Create AMQP sender
-        let amqp_sender = AmqpSender::new();
-
-        // Create target with the entity name
-        let target = AmqpTarget::from(self.entity_name.clone());
-
-        // Attach the sender to the session
-        amqp_sender
-            .attach(&session, "sender-link".to_string(), target, None)
+        let transaction = options.and_then(|options| options.transaction);
+        self.send_amqp_messages(vec![message.into()], transaction.as_ref())
             .await?;
 
-        // Convert Message to AmqpMessage
-        let amqp_message: AmqpMessage = message.into();
-
-        // Send the message
-        let _outcome = amqp_sender.send(amqp_message, None).await?;
-
-        // Detach the sender
-        amqp_sender.detach().await?;
-
-        // End the session
-        session.end().await?;
-
         trace!("Message sent successfully to entity: {}", self.entity_name);
         Ok(())
     }
@@ -208,7 +216,7 @@ impl Sender {
     pub async fn send_messages(
         &self,
         messages: Vec<Message>,
-        _options: Option<SendMessagesOptions>,
+        options: Option<SendMessagesOptions>,
     ) -> Result<()> {
         debug!(
             "Sending {} messages to entity: {}",
@@ -216,32 +224,12 @@ impl Sender {
             self.entity_name
         );
 
-        // Create AMQP session
-        let session = AmqpSession::new();
-        session.begin(&self.connection, None).await?;
-
-        // Create AMQP sender
-        let amqp_sender = AmqpSender::new();
-
-        // Create target with the entity name
-        let target = AmqpTarget::from(self.entity_name.clone());
-
-        // Attach the sender to the session
-        amqp_sender
-            .attach(&session, "sender-link".to_string(), target, None)
-            .await?;
-
-        // Send messages one by one
-        for message in messages {
-            let amqp_message: AmqpMessage = message.into();
-            let _outcome = amqp_sender.send(amqp_message, None).await?;
-        }
-
-        // Detach the sender
-        amqp_sender.detach().await?;
-
-        // End the session
-        session.end().await?;
+        let transaction = options.and_then(|options| options.transaction);
+        self.send_amqp_messages(
+            messages.into_iter().map(Into::into).collect(),
+            transaction.as_ref(),
+        )
+        .await?;
 
         trace!("Messages sent successfully to entity: {}", self.entity_name);
         Ok(())
@@ -341,7 +329,7 @@ impl Sender {
     pub async fn send_message_batch(
         &self,
         batch: crate::MessageBatch,
-        _options: Option<SendMessageBatchOptions>,
+        options: Option<SendMessageBatchOptions>,
     ) -> crate::Result<()> {
         let messages = batch.into_messages();
 
@@ -356,34 +344,14 @@ impl Sender {
             self.entity_name
         );
 
-        // Create AMQP session
-        let session = AmqpSession::new();
-        session.begin(&self.connection, None).await?;
-
-        // Create AMQP sender
-        let amqp_sender = AmqpSender::new();
-
-        // Create target with the entity name
-        let target = AmqpTarget::from(self.entity_name.clone());
-
-        // Attach the sender to the session
-        amqp_sender
-            .attach(&session, "sender-link".to_string(), target, None)
-            .await?;
-
-        // Send all messages in the batch
         // Note: In a full implementation, we would use AMQP transfer batching,
-        // but for now we send them sequentially in a single session
-        for message in messages {
-            let amqp_message: AmqpMessage = message.into();
-            let _outcome = amqp_sender.send(amqp_message, None).await?;
-        }
-
-        // Detach the sender
-        amqp_sender.detach().await?;
-
-        // End the session
-        session.end().await?;
+        // but for now we send them sequentially on the same link
+        let transaction = options.and_then(|options| options.transaction);
+        self.send_amqp_messages(
+            messages.into_iter().map(Into::into).collect(),
+            transaction.as_ref(),
+        )
+        .await?;
 
         trace!(
             "Message batch sent successfully to entity: {}",
@@ -437,32 +405,8 @@ impl Sender {
         // Set the scheduled enqueue time on the message
         message.set_scheduled_enqueue_time(scheduled_enqueue_time);
 
-        // Create AMQP session
-        let session = AmqpSession::new();
-        session.begin(&self.connection, None).await?;
-
-        // Create AMQP sender
-        let amqp_sender = AmqpSender::new();
-
-        // Create target with the entity name
-        let target = AmqpTarget::from(self.entity_name.clone());
-
-        // Attach the sender to the session
-        amqp_sender
-            .attach(&session, "sender-link".to_string(), target, None)
-            .await?;
-
-        // Convert Message to AmqpMessage
-        let amqp_message: AmqpMessage = message.into();
-
-        // Send the message (the scheduling is handled by the message properties)
-        let _outcome = amqp_sender.send(amqp_message, None).await?;
-
-        // Detach the sender
-        amqp_sender.detach().await?;
-
-        // End the session
-        session.end().await?;
+        // The scheduling is handled by the message properties
+        self.send_amqp_messages(vec![message.into()], None).await?;
 
         // TODO: In a real implementation, we would need to use AMQP management operations
         // to get the actual sequence number from the broker. For now, return a placeholder.
@@ -545,9 +489,23 @@ impl Sender {
     pub async fn close(&self) -> Result<()> {
         debug!("Closing Sender for entity: {}", self.entity_name);
 
-        // Sender creates sessions and senders on-demand for each send operation,
-        // so there are no persistent AMQP resources to clean up here.
-        // The connection will handle closing any outstanding sessions.
+        if let Some(link) = self.link.lock().await.take() {
+            // Detach the link if no send is using it; the session end releases it otherwise.
+            if let Ok(sender) = Arc::try_unwrap(link.sender) {
+                if let Err(e) = sender.detach().await {
+                    warn!(
+                        "Failed to detach AMQP sender for entity '{}': {}",
+                        self.entity_name, e
+                    );
+                }
+            }
+            if let Err(e) = link.session.end().await {
+                warn!(
+                    "Failed to end AMQP session for entity '{}': {}",
+                    self.entity_name, e
+                );
+            }
+        }
 
         trace!(
             "Sender closed successfully for entity: {}",
@@ -555,6 +513,124 @@ impl Sender {
         );
         Ok(())
     }
+
+    /// Sends messages in order, retrying each one after transient failures.
+    ///
+    /// If the connection or link is lost, it is reopened before the message is sent again.
+    /// Messages sent in a transaction are not retried, because the transaction is rolled back
+    /// when the connection is lost.
+    async fn send_amqp_messages(
+        &self,
+        messages: Vec<AmqpMessage>,
+        transaction: Option<&ServiceBusTransaction>,
+    ) -> Result<()> {
+        if let Some(transaction) = transaction {
+            let (sender, generation) = self.ensure_sender().await?;
+            for message in &messages {
+                Self::check_outcome(transaction.send(&sender, generation, message).await?)?;
+            }
+            return Ok(());
+        }
+
+        for message in &messages {
+            recover_with_backoff(
+                self.connection.retry_options(),
+                || async {
+                    let (sender, _) = self.ensure_sender().await?;
+                    Self::check_outcome(sender.send_ref(message, None).await?)
+                },
+                |action| self.recover(action),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Converts a rejected send outcome into an error.
+    fn check_outcome(outcome: AmqpSendOutcome) -> Result<()> {
+        match outcome {
+            AmqpSendOutcome::Rejected(Some(error)) => {
+                Err(AmqpError::from(AmqpErrorKind::AmqpDescribedError(error)).into())
+            }
+            AmqpSendOutcome::Rejected(None) => {
+                Err(AmqpError::from(AmqpErrorKind::SendRejected).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Ensures that a link is attached on the current connection, creating it if necessary.
+    ///
+    /// Returns the link with the generation of the connection it is attached on.
+    async fn ensure_sender(&self) -> Result<(Arc<AmqpSender>, u64)> {
+        let mut link = self.link.lock().await;
+        let (connection, generation) = self.connection.current().await?;
+        if let Some(link) = link.as_ref().filter(|link| link.generation == generation) {
+            return Ok((link.sender.clone(), generation));
+        }
+
+        debug!(
+            "Attaching sender for entity {} on connection generation {}",
+            self.entity_name, generation
+        );
+        let session = AmqpSession::new();
+        session.begin(&connection, None).await?;
+        let sender = AmqpSender::new();
+        let (target, options) = self.link_target();
+        sender
+            .attach(
+                &session,
+                format!("sender-{}", Uuid::new_v4()),
+                target,
+                options,
+            )
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to attach sender"))?;
+        let sender = Arc::new(sender);
+        *link = Some(SenderLink {
+            generation,
+            session,
+            sender: sender.clone(),
+        });
+        Ok((sender, generation))
+    }
+
+    /// Gets the target and options to attach the link with.
+    ///
+    /// A send-via link targets the via entity, and the service forwards the messages to the
+    /// destination entity.
+    fn link_target(&self) -> (AmqpTarget, Option<AmqpSenderOptions>) {
+        match &self.via_entity_name {
+            Some(via_entity_name) => {
+                let mut properties = AmqpOrderedMap::new();
+                properties.insert(
+                    AmqpSymbol::from(TRANSFER_DESTINATION_ADDRESS),
+                    self.entity_name.clone().into(),
+                );
+                (
+                    AmqpTarget::from(via_entity_name.clone()),
+                    Some(AmqpSenderOptions {
+                        properties: Some(properties),
+                        ..Default::default()
+                    }),
+                )
+            }
+            None => (AmqpTarget::from(self.entity_name.clone()), None),
+        }
+    }
+
+    /// Discards the link, and reopens the connection if it was lost.
+    async fn recover(&self, action: ErrorRecoveryAction) -> Result<()> {
+        let link = self.link.lock().await.take();
+        if action == ErrorRecoveryAction::ReconnectConnection {
+            let generation = match link {
+                Some(link) => link.generation,
+                None => self.connection.generation().await,
+            };
+            self.connection.recover(generation).await?;
+        }
+        Ok(())
+    }
 }
 
 impl Drop for Sender {
@@ -566,14 +642,8 @@ impl Drop for Sender {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Message;
-    use azure_core_amqp::AmqpConnection;
-    use std::sync::Arc;
-
-    /// Creates a mock connection for testing
-    fn create_test_connection() -> Arc<AmqpConnection> {
-        Arc::new(AmqpConnection::new())
-    }
+    use crate::{common::recoverable::tests::create_test_connection, Message};
+    use azure_core_amqp::{AmqpSimpleValue, AmqpValue};
 
     /// Creates test ServiceBusClientOptions
     fn create_test_options() -> ServiceBusClientOptions {
@@ -586,7 +656,7 @@ mod tests {
         let entity_name = "test-queue".to_string();
         let options = create_test_options();
 
-        let sender = Sender::new(connection, entity_name.clone(), options).await?;
+        let sender = Sender::new(connection, entity_name.clone(), None, options).await?;
 
         assert_eq!(sender.entity_name(), &entity_name);
         Ok(())
@@ -600,11 +670,46 @@ mod tests {
         let sender = Sender {
             connection,
             entity_name: entity_name.clone(),
+            via_entity_name: None,
+            link: Mutex::new(None),
         };
 
         assert_eq!(sender.entity_name(), &entity_name);
     }
 
+    #[test]
+    fn test_link_target() {
+        let sender = Sender {
+            connection: create_test_connection(),
+            entity_name: "shipments".to_string(),
+            via_entity_name: None,
+            link: Mutex::new(None),
+        };
+        let (target, options) = sender.link_target();
+        assert_eq!(target.address.as_deref(), Some("shipments"));
+        assert!(options.is_none());
+    }
+
+    #[test]
+    fn test_link_target_via_entity() {
+        let sender = Sender {
+            connection: create_test_connection(),
+            entity_name: "shipments".to_string(),
+            via_entity_name: Some("orders".to_string()),
+            link: Mutex::new(None),
+        };
+        let (target, options) = sender.link_target();
+        // the link is attached to the via entity, which forwards to the destination
+        assert_eq!(target.address.as_deref(), Some("orders"));
+        let properties = options
+            .and_then(|options| options.properties)
+            .expect("send-via link properties");
+        assert_eq!(
+            properties.get(&AmqpSymbol::from(TRANSFER_DESTINATION_ADDRESS)),
+            Some(&AmqpValue::from("shipments".to_string()))
+        );
+    }
+
     #[tokio::test]
     async fn test_sender_close() -> Result<()> {
         let connection = create_test_connection();
@@ -612,13 +717,51 @@ mod tests {
 
         let options = create_test_options();
 
-        let sender = Sender::new(connection, entity_name, options).await?;
+        let sender = Sender::new(connection, entity_name, None, options).await?;
 
         // Should not fail
         sender.close().await?;
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_send_fails_without_connection() -> Result<()> {
+        let sender = Sender::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            create_test_options(),
+        )
+        .await?;
+
+        assert!(sender
+            .send_message(Message::from("test"), None)
+            .await
+            .is_err());
+        assert!(sender.link.lock().await.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_recover_discards_link() -> Result<()> {
+        let sender = Sender::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            create_test_options(),
+        )
+        .await?;
+        *sender.link.lock().await = Some(SenderLink {
+            generation: 0,
+            session: AmqpSession::new(),
+            sender: Arc::new(AmqpSender::new()),
+        });
+
+        sender.recover(ErrorRecoveryAction::ReconnectLink).await?;
+        assert!(sender.link.lock().await.is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_sender_drop() {
         let connection = create_test_connection();
@@ -627,6 +770,8 @@ mod tests {
         let sender = Sender {
             connection,
             entity_name,
+            via_entity_name: None,
+            link: Mutex::new(None),
         };
 
         // Should not panic when dropped
@@ -662,13 +807,13 @@ mod tests {
         let mut message = Message::new("test".as_bytes());
 
         message.set_property("custom_key", "custom_value");
-        message.set_property("number", "42");
+        message.set_property("number", 42);
 
         assert_eq!(
             message.property("custom_key"),
-            Some(&"custom_value".to_string())
+            Some(&AmqpSimpleValue::String("custom_value".to_string()))
         );
-        assert_eq!(message.property("number"), Some(&"42".to_string()));
+        assert_eq!(message.property("number"), Some(&AmqpSimpleValue::Int(42)));
         assert_eq!(message.property("non_existent"), None);
 
         assert_eq!(message.properties().len(), 2);
@@ -682,6 +827,8 @@ mod tests {
         let sender = Sender {
             connection,
             entity_name,
+            via_entity_name: None,
+            link: Mutex::new(None),
         };
 
         let batch_options = CreateMessageBatchOptions {
@@ -0,0 +1,283 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Service Bus session receiver functionality.
+//!
+//! Session-enabled queues and subscriptions deliver the messages of each session, identified by
+//! [`Message::session_id`](crate::Message::session_id), in order to a single receiver at a time.
+//! A [`SessionReceiver`] holds the lock on one session, and can read and write state stored with the session.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use azure_messaging_servicebus::ServiceBusClient;
+//! use azure_identity::DeveloperToolsCredential;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let credential = DeveloperToolsCredential::new(None)?;
+//! let client = ServiceBusClient::builder().open("myservicebus.servicebus.windows.net", credential.clone()).await?;
+//!
+//! // Lock the next session that has messages available.
+//! let receiver = client.accept_next_session("my-session-queue", None).await?;
+//! println!("Locked session {}", receiver.session_id());
+//!
+//! while let Some(message) = receiver.receive_message(None).await? {
+//!     receiver.complete_message(&message, None).await?;
+//! }
+//! receiver.set_session_state(Some(b"checkpoint".to_vec()), None).await?;
+//! receiver.close().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    receiver::parse_expiration, ErrorKind, ReceiveMode, Receiver, Result, ServiceBusError,
+};
+use azure_core::{fmt::SafeDebug, time::Duration, time::OffsetDateTime};
+use azure_core_amqp::{AmqpOrderedMap, AmqpSimpleValue, AmqpValue};
+use std::{ops::Deref, sync::Mutex};
+use tracing::debug;
+
+/// Options for accepting a session.
+#[derive(Clone, SafeDebug)]
+pub struct AcceptSessionOptions {
+    /// The receive mode for the session receiver.
+    pub receive_mode: ReceiveMode,
+
+    /// How long [`accept_next_session`](crate::ServiceBusClient::accept_next_session) waits for a session
+    /// to become available. If `None`, it waits indefinitely.
+    ///
+    /// Ignored when accepting a specific session.
+    pub max_wait_time: Option<Duration>,
+}
+
+impl Default for AcceptSessionOptions {
+    fn default() -> Self {
+        Self {
+            receive_mode: ReceiveMode::PeekLock,
+            max_wait_time: None,
+        }
+    }
+}
+
+/// Options for getting session state.
+///
+/// Currently, this struct is defined for future extensibility but contains no
+/// configuration options.
+#[derive(SafeDebug, Clone, Default)]
+pub struct GetSessionStateOptions;
+
+/// Options for setting session state.
+///
+/// Currently, this struct is defined for future extensibility but contains no
+/// configuration options.
+#[derive(SafeDebug, Clone, Default)]
+pub struct SetSessionStateOptions;
+
+/// Options for renewing a session lock.
+///
+/// Currently, this struct is defined for future extensibility but contains no
+/// configuration options.
+#[derive(SafeDebug, Clone, Default)]
+pub struct RenewSessionLockOptions;
+
+/// A receiver that holds the lock on a single session of a session-enabled queue or subscription.
+///
+/// Create a `SessionReceiver` with [`ServiceBusClient::accept_session`](crate::ServiceBusClient::accept_session)
+/// or [`ServiceBusClient::accept_next_session`](crate::ServiceBusClient::accept_next_session).
+///
+/// All [`Receiver`] operations are available on a `SessionReceiver` and apply only to messages in its session.
+/// The session lock must be renewed with [`renew_session_lock`](SessionReceiver::renew_session_lock) before it
+/// expires; once it is lost, operations fail with [`ErrorKind::SessionLockLost`].
+pub struct SessionReceiver {
+    receiver: Receiver,
+    session_id: String,
+    locked_until: Mutex<Option<OffsetDateTime>>,
+}
+
+impl SessionReceiver {
+    /// Locks a specific session.
+    pub(crate) async fn accept(receiver: Receiver, session_id: String) -> Result<Self> {
+        let receiver = receiver.with_session_filter(Some(session_id.clone()));
+        receiver.attach().await?;
+        debug!("Accepted session {}", session_id);
+        Ok(Self::new(receiver, session_id))
+    }
+
+    /// Locks the next available session.
+    pub(crate) async fn accept_next(
+        receiver: Receiver,
+        max_wait_time: Option<Duration>,
+    ) -> Result<Self> {
+        let receiver = receiver.with_session_filter(None);
+        let session_id = receiver.receive_session_id(max_wait_time).await?;
+        debug!("Accepted next available session {}", session_id);
+        Ok(Self::new(receiver, session_id))
+    }
+
+    fn new(receiver: Receiver, session_id: String) -> Self {
+        Self {
+            receiver,
+            session_id,
+            locked_until: Mutex::new(None),
+        }
+    }
+
+    /// Gets the ID of the locked session.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Gets the time the session lock expires, if it has been renewed by this receiver.
+    pub fn session_locked_until(&self) -> Option<OffsetDateTime> {
+        *self.locked_until.lock().unwrap()
+    }
+
+    /// Gets the state stored with the session, or `None` if no state is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for the operation
+    pub async fn get_session_state(
+        &self,
+        _options: Option<GetSessionStateOptions>,
+    ) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .receiver
+            .call_management("com.microsoft:get-session-state", self.session_properties())
+            .await?;
+        match response.get("session-state") {
+            Some(AmqpValue::Binary(state)) => Ok(Some(state.clone())),
+            Some(AmqpValue::Null) | None => Ok(None),
+            Some(other) => Err(ServiceBusError::new(
+                ErrorKind::InvalidRequest,
+                format!(
+                    "Unexpected session state in management response: {:?}",
+                    other
+                ),
+            )),
+        }
+    }
+
+    /// Sets the state stored with the session. Passing `None` clears the state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The new session state
+    /// * `options` - Configuration options for the operation
+    pub async fn set_session_state(
+        &self,
+        state: Option<Vec<u8>>,
+        _options: Option<SetSessionStateOptions>,
+    ) -> Result<()> {
+        let mut application_properties = self.session_properties();
+        if let Some(state) = state {
+            application_properties
+                .insert("session-state".to_string(), AmqpSimpleValue::Binary(state));
+        }
+        self.receiver
+            .call_management("com.microsoft:set-session-state", application_properties)
+            .await?;
+        Ok(())
+    }
+
+    /// Renews the session lock, returning the new expiration time.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Configuration options for the operation
+    pub async fn renew_session_lock(
+        &self,
+        _options: Option<RenewSessionLockOptions>,
+    ) -> Result<OffsetDateTime> {
+        let response = self
+            .receiver
+            .call_management(
+                "com.microsoft:renew-session-lock",
+                self.session_properties(),
+            )
+            .await?;
+        let locked_until = parse_expiration(&response)?;
+        *self.locked_until.lock().unwrap() = Some(locked_until);
+        debug!(
+            "Renewed lock on session {} until {}",
+            self.session_id, locked_until
+        );
+        Ok(locked_until)
+    }
+
+    fn session_properties(&self) -> AmqpOrderedMap<String, AmqpSimpleValue> {
+        let mut application_properties = AmqpOrderedMap::new();
+        application_properties.insert("session-id".to_string(), self.session_id.clone().into());
+        application_properties
+    }
+}
+
+impl Deref for SessionReceiver {
+    type Target = Receiver;
+
+    fn deref(&self) -> &Receiver {
+        &self.receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::ServiceBusClientOptions, common::recoverable::tests::create_test_connection,
+    };
+
+    async fn create_test_receiver() -> Receiver {
+        Receiver::new(
+            create_test_connection(),
+            "test-queue".to_string(),
+            None,
+            ReceiveMode::PeekLock,
+            ServiceBusClientOptions::default(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn session_receiver_exposes_session_and_receiver() {
+        let receiver = SessionReceiver::new(create_test_receiver().await, "session-1".to_string());
+        assert_eq!(receiver.session_id(), "session-1");
+        assert_eq!(receiver.session_locked_until(), None);
+        assert_eq!(receiver.entity_name(), "test-queue");
+        assert_eq!(receiver.receive_mode(), ReceiveMode::PeekLock);
+
+        let properties = receiver.session_properties();
+        assert_eq!(
+            properties.get("session-id"),
+            Some(&AmqpSimpleValue::String("session-1".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn accept_session_fails_without_connection() {
+        let result =
+            SessionReceiver::accept(create_test_receiver().await, "session-1".into()).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn session_state_fails_without_connection() {
+        let receiver = SessionReceiver::new(create_test_receiver().await, "session-1".to_string());
+        assert!(receiver.get_session_state(None).await.is_err());
+        assert!(receiver
+            .set_session_state(Some(b"state".to_vec()), None)
+            .await
+            .is_err());
+        assert!(receiver.renew_session_lock(None).await.is_err());
+    }
+
+    #[test]
+    fn accept_session_options_default() {
+        let options = AcceptSessionOptions::default();
+        assert_eq!(options.receive_mode, ReceiveMode::PeekLock);
+        assert!(options.max_wait_time.is_none());
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+use crate::{common::recoverable::RecoverableConnection, ErrorKind, Result, ServiceBusError};
+use async_lock::Mutex;
+use azure_core::{fmt::SafeDebug, Uuid};
+use azure_core_amqp::{
+    AmqpDelivery, AmqpMessage, AmqpReceiver, AmqpSendOutcome, AmqpSender, AmqpSession,
+    AmqpSessionApis, AmqpTransaction, AmqpTransactionApis,
+};
+use std::{fmt, sync::Arc};
+use tracing::{debug, warn};
+
+/// Options for beginning a transaction.
+///
+/// This struct contains optional parameters that can be specified when beginning
+/// a transaction using [`crate::ServiceBusClient::begin_transaction`].
+///
+/// Currently no options are available, but this provides
+/// extensibility for future parameters
+#[derive(SafeDebug, Clone, Default)]
+pub struct BeginTransactionOptions;
+
+/// A Service Bus transaction.
+///
+/// Messages sent and messages settled with the transaction in their options take effect together
+/// when the transaction is committed, and are undone when it is rolled back. A transaction that is
+/// dropped without being committed is rolled back by the service.
+///
+/// A transaction only covers the connection it was begun on, so it can only be used with senders
+/// and receivers created by the same [`crate::ServiceBusClient`]. Operations in a transaction are
+/// not retried. If the connection is lost, the transaction is rolled back and the operation
+/// fails.
+///
+/// All the entities in a transaction must be reachable through the first entity the transaction
+/// operates on. To complete a message and send messages to another entity in the same
+/// transaction, create the sender with [`crate::CreateSenderOptions::via_entity_name`] set to the
+/// entity the message was received from.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use azure_messaging_servicebus::{
+///     CompleteMessageOptions, CreateSenderOptions, Message, SendMessageOptions, ServiceBusClient,
+/// };
+/// use azure_identity::DeveloperToolsCredential;
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let credential = DeveloperToolsCredential::new(None)?;
+/// let client = ServiceBusClient::builder().open("myservicebus.servicebus.windows.net", credential.clone()).await?;
+/// let receiver = client.create_receiver("orders", None).await?;
+/// let sender = client
+///     .create_sender(
+///         "shipments",
+///         Some(CreateSenderOptions {
+///             via_entity_name: Some("orders".to_string()),
+///         }),
+///     )
+///     .await?;
+///
+/// if let Some(order) = receiver.receive_message(None).await? {
+///     let transaction = client.begin_transaction(None).await?;
+///     sender
+///         .send_message(
+///             Message::from("Ship it"),
+///             Some(SendMessageOptions {
+///                 transaction: Some(transaction.clone()),
+///             }),
+///         )
+///         .await?;
+///     receiver
+///         .complete_message(
+///             &order,
+///             Some(CompleteMessageOptions {
+///                 transaction: Some(transaction.clone()),
+///             }),
+///         )
+///         .await?;
+///     transaction.commit().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ServiceBusTransaction {
+    inner: Arc<TransactionScope>,
+}
+
+/// The controller session and transaction shared by the clones of a [`ServiceBusTransaction`].
+struct TransactionScope {
+    /// The generation of the connection the transaction was declared on.
+    generation: u64,
+    session: AmqpSession,
+    // Taken when the transaction is committed or rolled back
+    transaction: Mutex<Option<AmqpTransaction>>,
+}
+
+impl ServiceBusTransaction {
+    /// Declares a transaction on a new session of the current connection.
+    pub(crate) async fn begin(connection: &RecoverableConnection) -> Result<Self> {
+        let (connection, generation) = connection.current().await?;
+
+        let session = AmqpSession::new();
+        session.begin(&connection, None).await?;
+        let transaction = AmqpTransaction::new();
+        transaction
+            .declare(&session, format!("coordinator-{}", Uuid::new_v4()))
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to declare transaction"))?;
+        debug!(
+            "Declared transaction on connection generation {}",
+            generation
+        );
+
+        Ok(Self {
+            inner: Arc::new(TransactionScope {
+                generation,
+                session,
+                transaction: Mutex::new(Some(transaction)),
+            }),
+        })
+    }
+
+    /// Commits the transaction.
+    ///
+    /// The messages sent and settled in the transaction take effect. If the service can't commit
+    /// the transaction, it is rolled back and an error is returned.
+    pub async fn commit(self) -> Result<()> {
+        let transaction = self.take().await?;
+        let result = transaction
+            .commit()
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to commit transaction"));
+        self.end_session().await;
+        result
+    }
+
+    /// Rolls back the transaction.
+    ///
+    /// The messages sent in the transaction are discarded, and the messages settled in the
+    /// transaction stay locked until their lock expires.
+    pub async fn rollback(self) -> Result<()> {
+        let transaction = self.take().await?;
+        let result = transaction
+            .rollback()
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to roll back transaction"));
+        self.end_session().await;
+        result
+    }
+
+    /// Sends a message on a link attached to the given connection generation.
+    pub(crate) async fn send(
+        &self,
+        sender: &AmqpSender,
+        generation: u64,
+        message: &AmqpMessage,
+    ) -> Result<AmqpSendOutcome> {
+        self.check_generation(generation)?;
+        let transaction = self.inner.transaction.lock().await;
+        transaction
+            .as_ref()
+            .ok_or_else(Self::completed_error)?
+            .send_ref(sender, message, None)
+            .await
+            .map_err(|e| ServiceBusError::from_amqp(e, "Failed to send message in transaction"))
+    }
+
+    /// Settles a delivery received on a link attached to the given connection generation.
+    pub(crate) async fn settle(
+        &self,
+        receiver: &AmqpReceiver,
+        generation: u64,
+        delivery: &AmqpDelivery,
+        settlement: Settlement,
+    ) -> Result<()> {
+        self.check_generation(generation)?;
+        let transaction = self.inner.transaction.lock().await;
+        let transaction = transaction.as_ref().ok_or_else(Self::completed_error)?;
+        match settlement {
+            Settlement::Accept => transaction.accept_delivery(receiver, delivery).await,
+            Settlement::Reject => transaction.reject_delivery(receiver, delivery).await,
+            Settlement::Release => transaction.release_delivery(receiver, delivery).await,
+        }
+        .map_err(|e| ServiceBusError::from_amqp(e, "Failed to settle message in transaction"))
+    }
+
+    /// Checks that a link is on the connection the transaction was declared on.
+    fn check_generation(&self, generation: u64) -> Result<()> {
+        if generation != self.inner.generation {
+            return Err(ServiceBusError::new(
+                ErrorKind::Amqp,
+                "The connection the transaction was declared on has been lost",
+            ));
+        }
+        Ok(())
+    }
+
+    async fn take(&self) -> Result<AmqpTransaction> {
+        self.inner
+            .transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(Self::completed_error)
+    }
+
+    async fn end_session(&self) {
+        if let Err(e) = self.inner.session.end().await {
+            warn!("Failed to end transaction session: {}", e);
+        }
+    }
+
+    fn completed_error() -> ServiceBusError {
+        ServiceBusError::new(
+            ErrorKind::InvalidRequest,
+            "The transaction has already been committed or rolled back",
+        )
+    }
+}
+
+impl fmt::Debug for ServiceBusTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceBusTransaction")
+            .field("generation", &self.inner.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How a delivery is settled in a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Settlement {
+    /// Complete the message.
+    Accept,
+    /// Dead-letter the message.
+    Reject,
+    /// Abandon the message.
+    Release,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a transaction that was never declared, as if it had been begun on the given
+    /// connection generation.
+    fn create_test_transaction(generation: u64) -> ServiceBusTransaction {
+        ServiceBusTransaction {
+            inner: Arc::new(TransactionScope {
+                generation,
+                session: AmqpSession::new(),
+                transaction: Mutex::new(Some(AmqpTransaction::new())),
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn begin_fails_without_connection() {
+        let connection = crate::common::recoverable::tests::create_test_connection();
+        assert!(ServiceBusTransaction::begin(&connection).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_links_on_another_connection() {
+        let transaction = create_test_transaction(0);
+        let error = transaction
+            .send(&AmqpSender::new(), 1, &AmqpMessage::default())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), &ErrorKind::Amqp);
+    }
+
+    #[tokio::test]
+    async fn completed_transaction_cannot_be_used() {
+        let transaction = create_test_transaction(0);
+        let other = transaction.clone();
+        transaction.take().await.unwrap();
+
+        let error = other.clone().commit().await.unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidRequest);
+        let error = other
+            .send(&AmqpSender::new(), 0, &AmqpMessage::default())
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), &ErrorKind::InvalidRequest);
+    }
+}
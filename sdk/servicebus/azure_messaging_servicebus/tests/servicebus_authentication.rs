@@ -83,23 +83,23 @@ async fn test_token_credential_message_properties(ctx: TestContext) -> Result<()
     // Validate custom properties
     assert_eq!(
         received_message.property("credential_type"),
-        Some("DeveloperToolsCredential".to_string()).as_ref()
+        Some(&"DeveloperToolsCredential".into())
     );
     assert_eq!(
         received_message.property("test_name"),
-        Some("test_token_credential_message_properties".to_string()).as_ref()
+        Some(&"test_token_credential_message_properties".into())
     );
     assert_eq!(
         received_message.property("environment"),
-        Some("live_test".to_string()).as_ref()
+        Some(&"live_test".into())
     );
     assert_eq!(
         received_message.property("number_value"),
-        Some("123".to_string()).as_ref()
+        Some(&"123".into())
     );
     assert_eq!(
         received_message.property("boolean_value"),
-        Some("true".to_string()).as_ref()
+        Some(&"true".into())
     );
 
     println!("All message properties validated successfully");
@@ -70,7 +70,7 @@ async fn test_token_credential_batch_operations(ctx: TestContext) -> Result<(),
     // Verify and complete all messages
     for message in received_messages.iter() {
         if let Some(cred_type) = message.property("credential_type") {
-            assert_eq!(cred_type, "DeveloperToolsCredential");
+            assert_eq!(*cred_type, "DeveloperToolsCredential".to_string());
         }
 
         receiver.complete_message(message, None).await?;
@@ -122,7 +122,7 @@ async fn test_message_batch_send_receive(ctx: TestContext) -> Result<(), Box<dyn
         let mut message = Message::from(message_body.clone());
         message.set_message_id(&message_id);
         message.set_correlation_id(&batch_id);
-        message.set_property("batch_id", &batch_id);
+        message.set_property("batch_id", batch_id.as_str());
         message.set_property("sequence", i.to_string());
         message.set_property("test_type", "batch_send_receive");
 
@@ -182,7 +182,7 @@ async fn test_message_batch_send_receive(ctx: TestContext) -> Result<(), Box<dyn
         let msg = found.unwrap();
         assert_eq!(msg.body_as_string().unwrap(), expected_body);
         assert_eq!(msg.correlation_id(), Some(&batch_id));
-        assert_eq!(msg.property("batch_id"), Some(&batch_id));
+        assert_eq!(msg.property("batch_id"), Some(&batch_id.as_str().into()));
 
         // Complete the message
         receiver.complete_message(msg, None).await?;
@@ -373,7 +373,7 @@ async fn test_batch_with_different_options(ctx: TestContext) -> Result<(), Box<d
     let messages = receiver.receive_messages(5, None).await?;
 
     for msg in messages {
-        if msg.property("test_type") == Some(&"batch_options".to_string()) {
+        if msg.property("test_type") == Some(&"batch_options".into()) {
             receiver.complete_message(&msg, None).await?;
         }
     }
@@ -719,7 +719,7 @@ async fn test_basic_send_receive_round_trip(ctx: TestContext) -> Result<(), Box<
     );
     assert_eq!(
         received_message.property("test_property"),
-        Some("test_value".to_string()).as_ref(),
+        Some(&"test_value".into()),
         "Custom property should match"
     );
 
@@ -84,16 +84,13 @@ async fn test_message_properties_preservation(ctx: TestContext) -> Result<(), Bo
     // Verify custom properties
     assert_eq!(
         received_message.property("custom_prop_1"),
-        Some("value1".to_string()).as_ref()
+        Some(&"value1".into())
     );
     assert_eq!(
         received_message.property("custom_prop_2"),
-        Some("value2".to_string()).as_ref()
-    );
-    assert_eq!(
-        received_message.property("number_prop"),
-        Some("42".to_string()).as_ref()
+        Some(&"value2".into())
     );
+    assert_eq!(received_message.property("number_prop"), Some(&"42".into()));
 
     // Complete message
     receiver.complete_message(received_message, None).await?;
@@ -237,7 +237,7 @@ async fn test_basic_send_receive_round_trip(ctx: TestContext) -> Result<(), Box<
     );
     assert_eq!(
         received_message.property("test_property"),
-        Some("test_value".to_string()).as_ref(),
+        Some(&"test_value".into()),
         "Custom property should match"
     );
 
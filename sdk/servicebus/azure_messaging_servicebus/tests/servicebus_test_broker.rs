@@ -0,0 +1,133 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Tests that run the Service Bus client against the local AMQP test broker.
+
+use azure_core::{
+    credentials::{AccessToken, TokenCredential, TokenRequestOptions},
+    time::{Duration, OffsetDateTime},
+};
+use azure_core_amqp_test::TestBroker;
+use azure_messaging_servicebus::{
+    CreateReceiverOptions, Message, ReceiveMessageOptions, ServiceBusClient, SubQueue,
+};
+use std::{error::Error, sync::Arc};
+
+const NAMESPACE: &str = "test.servicebus.windows.net";
+
+/// Issues tokens that outlive the tests, so the client doesn't refresh them while a test runs.
+#[derive(Debug)]
+struct TestCredential;
+
+#[async_trait::async_trait]
+impl TokenCredential for TestCredential {
+    async fn get_token(
+        &self,
+        scopes: &[&str],
+        _options: Option<TokenRequestOptions<'_>>,
+    ) -> azure_core::Result<AccessToken> {
+        Ok(AccessToken::new(
+            format!("TEST TOKEN {}", scopes.join(" ")),
+            OffsetDateTime::now_utc() + Duration::hours(1),
+        ))
+    }
+}
+
+async fn open_client(broker: &TestBroker) -> Result<ServiceBusClient, Box<dyn Error>> {
+    Ok(ServiceBusClient::builder()
+        .with_custom_endpoint(broker.endpoint().to_string())
+        .open(NAMESPACE, Arc::new(TestCredential))
+        .await?)
+}
+
+/// Waits for the broker to process settlements, which clients send without waiting for a reply.
+async fn wait_for_count(count: impl Fn() -> Option<usize>, expected: usize) {
+    for _ in 0..50 {
+        if count() == Some(expected) {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(count(), Some(expected));
+}
+
+fn receive_options() -> Option<ReceiveMessageOptions> {
+    Some(ReceiveMessageOptions {
+        max_message_count: 10,
+        max_wait_time: Some(Duration::seconds(5)),
+    })
+}
+
+#[tokio::test]
+async fn send_and_complete() -> Result<(), Box<dyn Error>> {
+    let broker = TestBroker::builder().with_queue("queue").start().await?;
+    let client = open_client(&broker).await?;
+
+    let sender = client.create_sender("queue", None).await?;
+    sender
+        .send_messages(vec![Message::new("first"), Message::new("second")], None)
+        .await?;
+    assert_eq!(broker.active_message_count("queue"), Some(2));
+
+    let receiver = client.create_receiver("queue", None).await?;
+    let messages = receiver.receive_messages(2, receive_options()).await?;
+    let bodies: Vec<String> = messages
+        .iter()
+        .map(|message| message.body_as_string())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(bodies, ["first", "second"]);
+
+    for message in &messages {
+        receiver.complete_message(message, None).await?;
+    }
+    wait_for_count(|| broker.active_message_count("queue"), 0).await;
+    assert!(broker
+        .authorized_audiences()
+        .iter()
+        .any(|audience| audience.ends_with("/queue")));
+
+    receiver.close().await?;
+    sender.close().await?;
+    client.close().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn dead_letter_and_receive_from_sub_queue() -> Result<(), Box<dyn Error>> {
+    let broker = TestBroker::builder().with_queue("queue").start().await?;
+    let client = open_client(&broker).await?;
+
+    let sender = client.create_sender("queue", None).await?;
+    sender.send_message(Message::new("poison"), None).await?;
+
+    let receiver = client.create_receiver("queue", None).await?;
+    let message = receiver
+        .receive_message(receive_options())
+        .await?
+        .expect("a message should be received");
+    receiver.dead_letter_message(&message, None).await?;
+    wait_for_count(|| broker.dead_letter_message_count("queue"), 1).await;
+    assert_eq!(broker.active_message_count("queue"), Some(0));
+
+    let dead_letter_receiver = client
+        .create_receiver(
+            "queue",
+            Some(CreateReceiverOptions {
+                sub_queue: Some(SubQueue::DeadLetter),
+                ..Default::default()
+            }),
+        )
+        .await?;
+    let message = dead_letter_receiver
+        .receive_message(receive_options())
+        .await?
+        .expect("the dead-lettered message should be received");
+    assert_eq!(message.body_as_string()?, "poison");
+    dead_letter_receiver
+        .complete_message(&message, None)
+        .await?;
+    wait_for_count(|| broker.dead_letter_message_count("queue"), 0).await;
+
+    client.close().await?;
+    Ok(())
+}
@@ -237,7 +237,7 @@ async fn test_basic_send_receive_round_trip(ctx: TestContext) -> Result<(), Box<
     );
     assert_eq!(
         received_message.property("test_property"),
-        Some("test_value".to_string()).as_ref(),
+        Some(&"test_value".into()),
         "Custom property should match"
     );
 
@@ -319,7 +319,7 @@ async fn test_topic_subscription_messaging(ctx: TestContext) -> Result<(), Box<d
         "Topic subscription test message"
     );
     if let Some(test_name) = received_message.property("test_name") {
-        assert_eq!(test_name, "test_topic_subscription_messaging");
+        assert_eq!(*test_name, "test_topic_subscription_messaging".to_string());
     }
 
     // Complete the message
@@ -407,7 +407,7 @@ async fn test_topic_multiple_messages(ctx: TestContext) -> Result<(), Box<dyn Er
         );
 
         if let Some(test_name) = received_message.property("test_name") {
-            assert_eq!(test_name, "test_topic_multiple_messages");
+            assert_eq!(*test_name, "test_topic_multiple_messages".to_string());
         }
 
         receiver.complete_message(received_message, None).await?;
@@ -506,20 +506,14 @@ async fn test_topic_subscription_with_properties(ctx: TestContext) -> Result<(),
     // Verify custom properties
     assert_eq!(
         received_message.property("test_name"),
-        Some("test_topic_subscription_with_properties".to_string()).as_ref()
+        Some(&"test_topic_subscription_with_properties".into())
     );
     assert_eq!(
         received_message.property("category"),
-        Some("important".to_string()).as_ref()
-    );
-    assert_eq!(
-        received_message.property("region"),
-        Some("global".to_string()).as_ref()
-    );
-    assert_eq!(
-        received_message.property("priority"),
-        Some("high".to_string()).as_ref()
+        Some(&"important".into())
     );
+    assert_eq!(received_message.property("region"), Some(&"global".into()));
+    assert_eq!(received_message.property("priority"), Some(&"high".into()));
 
     // Complete the message
     receiver.complete_message(received_message, None).await?;
@@ -0,0 +1,218 @@
+// Copyright (c) Microsoft Corporation. All Rights reserved
+// Licensed under the MIT license.
+
+//! Tests for Service Bus transactions.
+
+mod common;
+use azure_core::{time::Duration, Uuid};
+use azure_core_test::{recorded, TestContext};
+use azure_messaging_servicebus::{
+    CompleteMessageOptions, CreateSenderOptions, Message, ReceiveMessageOptions,
+    SendMessageOptions, ServiceBusClient,
+};
+use common::{get_queue_name, get_servicebus_namespace, get_subscription_name, get_topic_name};
+use std::error::Error;
+
+#[recorded::test(live)]
+async fn test_transaction_commit(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    let recording = ctx.recording();
+
+    let namespace = get_servicebus_namespace()?;
+    let queue_name = get_queue_name()?;
+
+    let client = ServiceBusClient::builder()
+        .open(&namespace, recording.credential())
+        .await?;
+    let sender = client.create_sender(&queue_name, None).await?;
+    let receiver = client.create_receiver(&queue_name, None).await?;
+
+    let request_id = Uuid::new_v4().to_string();
+    let mut request = Message::from("Transaction request");
+    request.set_message_id(&request_id);
+    sender.send_message(request, None).await?;
+
+    let received = receiver
+        .receive_message(None)
+        .await?
+        .ok_or("Should receive the request")?;
+    assert_eq!(received.message_id(), Some(&request_id));
+
+    // Complete the request and send the reply in the same transaction
+    let reply_id = Uuid::new_v4().to_string();
+    let mut reply = Message::from("Transaction reply");
+    reply.set_message_id(&reply_id);
+    let transaction = client.begin_transaction(None).await?;
+    sender
+        .send_message(
+            reply,
+            Some(SendMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    receiver
+        .complete_message(
+            &received,
+            Some(CompleteMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    transaction.commit().await?;
+
+    let received = receiver
+        .receive_message(None)
+        .await?
+        .ok_or("Should receive the reply")?;
+    assert_eq!(received.message_id(), Some(&reply_id));
+    receiver.complete_message(&received, None).await?;
+
+    receiver.close().await?;
+    sender.close().await?;
+    client.close().await?;
+    Ok(())
+}
+
+#[recorded::test(live)]
+async fn test_transaction_rollback(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    let recording = ctx.recording();
+
+    let namespace = get_servicebus_namespace()?;
+    let queue_name = get_queue_name()?;
+
+    let client = ServiceBusClient::builder()
+        .open(&namespace, recording.credential())
+        .await?;
+    let sender = client.create_sender(&queue_name, None).await?;
+    let receiver = client.create_receiver(&queue_name, None).await?;
+
+    let transaction = client.begin_transaction(None).await?;
+    sender
+        .send_message(
+            Message::from("Rolled back"),
+            Some(SendMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    transaction.rollback().await?;
+
+    // The message sent in the transaction is discarded
+    let messages = receiver
+        .receive_messages(
+            1,
+            Some(ReceiveMessageOptions {
+                max_message_count: 1,
+                max_wait_time: Some(Duration::seconds(5)),
+            }),
+        )
+        .await?;
+    assert!(messages.is_empty(), "Rolled back message was enqueued");
+
+    receiver.close().await?;
+    sender.close().await?;
+    client.close().await?;
+    Ok(())
+}
+
+#[recorded::test(live)]
+async fn test_transaction_send_via(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    let recording = ctx.recording();
+
+    let namespace = get_servicebus_namespace()?;
+    let queue_name = get_queue_name()?;
+    let topic_name = get_topic_name()?;
+    let subscription_name = get_subscription_name()?;
+
+    let client = ServiceBusClient::builder()
+        .open(&namespace, recording.credential())
+        .await?;
+    let sender = client.create_sender(&queue_name, None).await?;
+    let receiver = client.create_receiver(&queue_name, None).await?;
+    // Send to the topic through the queue, so the transaction only spans one entity
+    let via_sender = client
+        .create_sender(
+            &topic_name,
+            Some(CreateSenderOptions {
+                via_entity_name: Some(queue_name.clone()),
+            }),
+        )
+        .await?;
+    let subscription_receiver = client
+        .create_receiver_for_subscription(&topic_name, &subscription_name, None)
+        .await?;
+    let receive_options = || {
+        Some(ReceiveMessageOptions {
+            max_message_count: 1,
+            max_wait_time: Some(Duration::seconds(5)),
+        })
+    };
+
+    let request_id = Uuid::new_v4().to_string();
+    let mut request = Message::from("Transaction request");
+    request.set_message_id(&request_id);
+    sender.send_message(request, None).await?;
+
+    let received = receiver
+        .receive_message(None)
+        .await?
+        .ok_or("Should receive the request")?;
+    assert_eq!(received.message_id(), Some(&request_id));
+
+    // A rolled back transaction forwards nothing to the topic
+    let transaction = client.begin_transaction(None).await?;
+    via_sender
+        .send_message(
+            Message::from("Rolled back"),
+            Some(SendMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    transaction.rollback().await?;
+    let messages = subscription_receiver
+        .receive_messages(1, receive_options())
+        .await?;
+    assert!(messages.is_empty(), "Rolled back message was forwarded");
+
+    // Complete the request and forward the reply to the topic in the same transaction
+    let reply_id = Uuid::new_v4().to_string();
+    let mut reply = Message::from("Transaction reply");
+    reply.set_message_id(&reply_id);
+    let transaction = client.begin_transaction(None).await?;
+    via_sender
+        .send_message(
+            reply,
+            Some(SendMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    receiver
+        .complete_message(
+            &received,
+            Some(CompleteMessageOptions {
+                transaction: Some(transaction.clone()),
+            }),
+        )
+        .await?;
+    transaction.commit().await?;
+
+    let received = subscription_receiver
+        .receive_message(None)
+        .await?
+        .ok_or("Should receive the reply from the subscription")?;
+    assert_eq!(received.message_id(), Some(&reply_id));
+    subscription_receiver
+        .complete_message(&received, None)
+        .await?;
+    let messages = receiver.receive_messages(1, receive_options()).await?;
+    assert!(messages.is_empty(), "Completed request was not removed");
+
+    subscription_receiver.close().await?;
+    via_sender.close().await?;
+    receiver.close().await?;
+    sender.close().await?;
+    client.close().await?;
+    Ok(())
+}
@@ -8,21 +8,25 @@ use crate::{
         clients::BlobClient as GeneratedBlobClient, models::BlobClientDownloadInternalOptions,
     },
     models::{
-        BlobClientDownloadIntoResult, BlobClientDownloadOptions, BlobClientDownloadResult,
+        query_request_body, BlobClientDownloadIntoResult, BlobClientDownloadOptions,
+        BlobClientDownloadResult, BlobClientQueryOptions, BlobClientQueryResult,
         BlobClientUploadOptions, BlobClientUploadResult, BlobDownloadProperties, HttpRange,
         StorageErrorCode,
     },
     partitioned_transfer::{self, PartitionedDownloadBehavior},
+    streams::query_stream::query_stream,
     AppendBlobClient, BlockBlobClient, PageBlobClient,
 };
 use async_trait::async_trait;
 use azure_core::{
     credentials::TokenCredential,
-    error::ErrorKind,
+    error::{CheckSuccessOptions, ErrorKind},
     http::{
         policies::{auth::BearerTokenAuthorizationPolicy, Policy},
-        AsyncRawResponse, Etag, NoFormat, Pipeline, RequestContent, StatusCode, Url, UrlExt,
+        AsyncRawResponse, Etag, Method, NoFormat, Pipeline, PipelineStreamOptions, Request,
+        RequestContent, StatusCode, Url, UrlExt,
     },
+    time::to_rfc7231,
     tracing, Bytes, Result,
 };
 use std::{ops::Range, sync::Arc};
@@ -278,6 +282,91 @@ impl BlobClient {
         self.block_blob_client().upload(content, options).await
     }
 
+    /// Queries the content of the blob using a SQL expression and returns only the matching data.
+    ///
+    /// The service reads the blob in [`BlobClientQueryOptions::input_format`] and serializes the
+    /// results in [`BlobClientQueryOptions::output_format`]. Progress and non-fatal errors reported
+    /// by the service while the results are streamed are passed to
+    /// [`BlobClientQueryOptions::progress_handler`] and [`BlobClientQueryOptions::error_handler`].
+    ///
+    /// # Arguments
+    ///
+    /// * `expression` - The query expression, for example `SELECT * from BlobStorage`.
+    /// * `options` - Optional configuration for the request.
+    #[tracing::function("Storage.Blob.Blob.query")]
+    pub async fn query(
+        &self,
+        expression: &str,
+        options: Option<BlobClientQueryOptions<'_>>,
+    ) -> Result<BlobClientQueryResult> {
+        let options = options.unwrap_or_default();
+        let body = query_request_body(
+            expression,
+            options.input_format.as_ref(),
+            options.output_format.as_ref(),
+        )?;
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("comp", "query");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Post);
+        request.insert_header("content-type", "application/xml");
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_modified_since) = options.if_modified_since {
+            request.insert_header("if-modified-since", to_rfc7231(&if_modified_since));
+        }
+        if let Some(if_none_match) = options.if_none_match.as_ref() {
+            request.insert_header("if-none-match", if_none_match.to_string());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        if let Some(encryption_algorithm) = options.encryption_algorithm.as_ref() {
+            request.insert_header(
+                "x-ms-encryption-algorithm",
+                encryption_algorithm.to_string(),
+            );
+        }
+        if let Some(encryption_key) = options.encryption_key {
+            request.insert_header("x-ms-encryption-key", encryption_key);
+        }
+        if let Some(encryption_key_sha256) = options.encryption_key_sha256 {
+            request.insert_header("x-ms-encryption-key-sha256", encryption_key_sha256);
+        }
+        if let Some(if_tags) = options.if_tags {
+            request.insert_header("x-ms-if-tags", if_tags);
+        }
+        if let Some(lease_id) = options.lease_id {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", self.version.clone());
+        request.set_body(body);
+        let response = self
+            .pipeline
+            .stream(
+                &ctx,
+                &mut request,
+                Some(PipelineStreamOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200, 206],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let (status, headers, body) = response.deconstruct();
+        let stream = query_stream(body, options.progress_handler, options.error_handler);
+        let body = AsyncRawResponse::new(status, headers.clone(), Box::pin(stream)).into_body();
+        Ok(BlobClientQueryResult { body, headers })
+    }
+
     /// Checks if the blob exists.
     ///
     /// Returns `true` if the blob exists, `false` if the blob does not exist, and propagates all other errors.
@@ -334,3 +423,75 @@ impl PartitionedDownloadBehavior for BlobClientDownloadBehavior<'_> {
             .map(AsyncRawResponse::from)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{BlobQueryProgressHandler, DelimitedTextConfiguration, QueryFormat},
+        streams::query_stream::tests::{data_record, end_record, progress_record, query_response},
+    };
+    use azure_core::http::{headers::Headers, ClientOptions, Transport};
+    use azure_core_test::http::MockHttpClient;
+    use futures::FutureExt as _;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test]
+    async fn query_sends_request_and_decodes_response() -> Result<()> {
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            assert_eq!(req.method(), Method::Post);
+            assert_eq!(req.url().query(), Some("comp=query"));
+            let body: Bytes = req.body().into();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body.contains("<Expression>SELECT _1 from BlobStorage</Expression>"));
+            assert!(body.contains("<Type>delimited</Type>"));
+            async move {
+                Ok(AsyncRawResponse::from_bytes(
+                    StatusCode::Ok,
+                    Headers::new(),
+                    query_response(&[
+                        data_record(b"1\n"),
+                        progress_record(8, 8),
+                        data_record(b"2\n"),
+                        end_record(8),
+                    ]),
+                ))
+            }
+            .boxed()
+        }));
+        let client = BlobClient::new(
+            Url::parse("https://example.blob.core.windows.net/container/blob.csv").unwrap(),
+            None,
+            Some(BlobClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )?;
+
+        let scanned = Arc::new(AtomicU64::new(0));
+        let handler = {
+            let scanned = scanned.clone();
+            BlobQueryProgressHandler::new(move |n| scanned.store(n, Ordering::SeqCst))
+        };
+        let result = client
+            .query(
+                "SELECT _1 from BlobStorage",
+                Some(BlobClientQueryOptions {
+                    input_format: Some(QueryFormat::Delimited(
+                        DelimitedTextConfiguration::default(),
+                    )),
+                    progress_handler: Some(handler),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let data = result.body.collect().await?;
+        assert_eq!(&data[..], b"1\n2\n");
+        assert_eq!(scanned.load(Ordering::SeqCst), 8);
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{
+    models::{
+        BlobClientDownloadOptions, BlobClientDownloadResult, BlobClientGetPropertiesOptions,
+        BlobClientGetPropertiesResultHeaders, BlobClientUploadOptions, BlobClientUploadResult,
+        HttpRange,
+    },
+    streams::{decrypt_stream::decrypt_stream, encrypt_stream::EncryptStream},
+    BlobClient,
+};
+use azure_core::{
+    http::{AsyncRawResponse, Body, NoFormat, RequestContent, Url},
+    stream::BytesStream,
+    Bytes, Result,
+};
+use azure_storage_common::encryption::{
+    ClientSideEncryptionOptions, ContentCipher, EncryptionData, ENCRYPTION_DATA_KEY,
+};
+use std::{collections::HashMap, fmt};
+
+/// A client that encrypts blob content before uploading it and decrypts it when downloading.
+///
+/// Content is encrypted with client-side (envelope) encryption using the version 2 format shared
+/// by the other Azure Storage SDKs: AES-GCM over 4 MiB regions with a content encryption key
+/// wrapped by the [`KeyEncryptionKey`](crate::encryption::KeyEncryptionKey) in [`ClientSideEncryptionOptions`].
+/// The wrapped key is stored in the blob's `encryptiondata` metadata.
+///
+/// Blobs without encryption metadata are downloaded as-is.
+pub struct EncryptedBlobClient {
+    client: BlobClient,
+    options: ClientSideEncryptionOptions,
+}
+
+impl fmt::Debug for EncryptedBlobClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedBlobClient")
+            .field("url", self.url())
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptedBlobClient {
+    /// Creates a new `EncryptedBlobClient`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`BlobClient`] used to send requests.
+    /// * `options` - The keys used to encrypt and decrypt content.
+    pub fn new(client: BlobClient, options: ClientSideEncryptionOptions) -> Self {
+        Self { client, options }
+    }
+
+    /// Gets the [`BlobClient`] used to send requests.
+    ///
+    /// Operations on this client do not encrypt or decrypt content.
+    pub fn blob_client(&self) -> &BlobClient {
+        &self.client
+    }
+
+    /// Gets the URL of the resource this client is configured for.
+    pub fn url(&self) -> &Url {
+        self.client.url()
+    }
+
+    /// Encrypts content and uploads it to a block blob, overwriting any existing blob by default.
+    ///
+    /// Content is encrypted region by region as it's uploaded, so a stream isn't read into memory.
+    /// The encryption metadata is added to any [`metadata`](BlobClientUploadOptions::metadata) in `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content to encrypt and upload.
+    /// * `options` - Optional parameters for the request.
+    pub async fn upload(
+        &self,
+        content: RequestContent<Bytes, NoFormat>,
+        options: Option<BlobClientUploadOptions<'_>>,
+    ) -> Result<BlobClientUploadResult> {
+        let mut options = options.unwrap_or_default();
+        let plaintext = match Body::from(content) {
+            Body::Bytes(bytes) => Box::new(BytesStream::new(bytes)),
+            Body::SeekableStream(stream) => stream,
+        };
+
+        let (cipher, encryption_data) = ContentCipher::generate(&self.options).await?;
+        let ciphertext = Body::SeekableStream(Box::new(EncryptStream::new(plaintext, cipher)));
+        options
+            .metadata
+            .get_or_insert_with(HashMap::new)
+            .insert(ENCRYPTION_DATA_KEY.to_string(), encryption_data.to_json()?);
+
+        self.client.upload(ciphertext.into(), Some(options)).await
+    }
+
+    /// Downloads a blob and decrypts its contents.
+    ///
+    /// When [`range`](BlobClientDownloadOptions::range) is set, the blob's properties are read first
+    /// and only the encrypted regions overlapping the range are downloaded.
+    ///
+    /// [`BlobClientDownloadResult::properties`] reports the decrypted content length, while
+    /// [`BlobClientDownloadResult::headers`] are the headers of the encrypted response.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional configuration for the request.
+    pub async fn download(
+        &self,
+        options: Option<BlobClientDownloadOptions<'_>>,
+    ) -> Result<BlobClientDownloadResult> {
+        let mut options = options.unwrap_or_default();
+        let Some(range) = options.range.take() else {
+            let result = self.client.download(Some(options)).await?;
+            return self.decrypt(result, 0, None).await;
+        };
+
+        let properties = self
+            .client
+            .get_properties(Some(BlobClientGetPropertiesOptions {
+                encryption_algorithm: options.encryption_algorithm,
+                encryption_key: options.encryption_key.clone(),
+                encryption_key_sha256: options.encryption_key_sha256.clone(),
+                if_match: options.if_match.clone(),
+                if_modified_since: options.if_modified_since,
+                if_none_match: options.if_none_match.clone(),
+                if_tags: options.if_tags.clone(),
+                if_unmodified_since: options.if_unmodified_since,
+                lease_id: options.lease_id.clone(),
+                method_options: options.method_options.clone(),
+                snapshot: options.snapshot.clone(),
+                timeout: options.timeout,
+                version_id: options.version_id.clone(),
+            }))
+            .await?;
+        let Some(encryption_data) = properties.metadata()?.remove(ENCRYPTION_DATA_KEY) else {
+            options.range = Some(range);
+            return self.client.download(Some(options)).await;
+        };
+        let encryption_data = EncryptionData::from_json(&encryption_data)?;
+
+        // Download the whole regions that contain the requested range.
+        let region_len = encryption_data.region_data_length() as u64;
+        let encrypted_region_len = encryption_data.encrypted_region_length() as u64;
+        let first_region = range.offset() / region_len;
+        let encrypted_offset = first_region * encrypted_region_len;
+        options.range = Some(match range.length() {
+            Some(length) => {
+                let last_region =
+                    range.offset().saturating_add(length).saturating_sub(1) / region_len;
+                HttpRange::new(
+                    encrypted_offset,
+                    (last_region + 1 - first_region) * encrypted_region_len,
+                )
+            }
+            None => HttpRange::from_offset(encrypted_offset),
+        });
+        // Lock to the version of the blob whose encryption data was read.
+        if options.if_match.is_none() {
+            options.if_match = properties.etag()?;
+        }
+
+        let result = self.client.download(Some(options)).await?;
+        let skip = range.offset() - first_region * region_len;
+        self.decrypt_with(result, &encryption_data, skip, range.length())
+            .await
+    }
+
+    async fn decrypt(
+        &self,
+        mut result: BlobClientDownloadResult,
+        skip: u64,
+        take: Option<u64>,
+    ) -> Result<BlobClientDownloadResult> {
+        let Some(encryption_data) = result.properties.metadata.remove(ENCRYPTION_DATA_KEY) else {
+            return Ok(result);
+        };
+        let encryption_data = EncryptionData::from_json(&encryption_data)?;
+        self.decrypt_with(result, &encryption_data, skip, take)
+            .await
+    }
+
+    async fn decrypt_with(
+        &self,
+        mut result: BlobClientDownloadResult,
+        encryption_data: &EncryptionData,
+        skip: u64,
+        take: Option<u64>,
+    ) -> Result<BlobClientDownloadResult> {
+        let cipher = ContentCipher::from_encryption_data(&self.options, encryption_data).await?;
+        result.properties.metadata.remove(ENCRYPTION_DATA_KEY);
+        if let Some(len) = result.properties.content_length {
+            let len = encryption_data.plaintext_length(len)?.saturating_sub(skip);
+            result.properties.content_length = Some(take.map_or(len, |take| take.min(len)));
+        }
+        // The MD5 hashes describe the encrypted content.
+        result.properties.content_md5 = None;
+        result.properties.blob_content_md5 = None;
+
+        let body = decrypt_stream(result.body, cipher, skip, take);
+        result.body = AsyncRawResponse::new(
+            azure_core::http::StatusCode::Ok,
+            result.headers.clone(),
+            Box::pin(body),
+        )
+        .into_body();
+        Ok(result)
+    }
+}
+
+impl BlobClient {
+    /// Creates an [`EncryptedBlobClient`] for this blob that encrypts content before uploading it and decrypts it when downloading.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The keys used to encrypt and decrypt content.
+    pub fn encrypted_client(&self, options: ClientSideEncryptionOptions) -> EncryptedBlobClient {
+        EncryptedBlobClient::new(
+            BlobClient {
+                endpoint: self.endpoint.clone(),
+                pipeline: self.pipeline.clone(),
+                version: self.version.clone(),
+                tracer: self.tracer.clone(),
+            },
+            options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{encryption::LocalKeyEncryptionKey, BlobClientOptions};
+    use azure_core::http::{
+        headers::{HeaderName, Headers},
+        ClientOptions, Method, StatusCode, Transport,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use azure_storage_common::encryption::{NONCE_LENGTH, REGION_DATA_LENGTH, TAG_LENGTH};
+    use futures::{AsyncReadExt as _, FutureExt as _};
+    use std::{
+        num::NonZero,
+        sync::{Arc, Mutex},
+    };
+
+    const ENCRYPTED_REGION_LENGTH: usize = NONCE_LENGTH + REGION_DATA_LENGTH + TAG_LENGTH;
+
+    fn encryption_options() -> ClientSideEncryptionOptions {
+        let kek = LocalKeyEncryptionKey::new("local-key", vec![7; 32]).unwrap();
+        ClientSideEncryptionOptions::new(Arc::new(kek))
+    }
+
+    /// A blob stored by the mock service.
+    #[derive(Default)]
+    struct StoredBlob {
+        content: Vec<u8>,
+        metadata: Vec<(String, String)>,
+        ranges: Vec<String>,
+    }
+
+    fn client(blob: Arc<Mutex<StoredBlob>>) -> EncryptedBlobClient {
+        let mock_client = Arc::new(MockHttpClient::new(move |req| {
+            let blob = blob.clone();
+            let method = req.method();
+            let range = req
+                .headers()
+                .get_optional_str(&HeaderName::from_static("range"))
+                .map(str::to_string);
+            let mut metadata = Vec::new();
+            for (name, value) in req.headers().iter() {
+                if let Some(key) = name.as_str().strip_prefix("x-ms-meta-") {
+                    metadata.push((key.to_string(), value.as_str().to_string()));
+                }
+            }
+            let body = req.body().clone();
+            async move {
+                let body = match body {
+                    Body::Bytes(bytes) => bytes.to_vec(),
+                    Body::SeekableStream(mut stream) => {
+                        let mut buffer = Vec::new();
+                        stream.read_to_end(&mut buffer).await?;
+                        buffer
+                    }
+                };
+                let mut blob = blob.lock().unwrap();
+                let mut headers = Headers::new();
+                headers.insert("etag", "\"0x1\"");
+                match method {
+                    Method::Put => {
+                        blob.content = body;
+                        blob.metadata = metadata;
+                        return Ok(AsyncRawResponse::from_bytes(
+                            StatusCode::Created,
+                            headers,
+                            Bytes::new(),
+                        ));
+                    }
+                    Method::Head | Method::Get => {}
+                    _ => panic!("unexpected method {method:?}"),
+                }
+                for (key, value) in &blob.metadata {
+                    headers.insert(format!("x-ms-meta-{key}"), value.clone());
+                }
+                let len = blob.content.len();
+                if method == Method::Head {
+                    headers.insert("content-length", len.to_string());
+                    return Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        headers,
+                        Bytes::new(),
+                    ));
+                }
+                let (start, end) = match &range {
+                    Some(range) => {
+                        blob.ranges.push(range.clone());
+                        let (start, end) = range
+                            .strip_prefix("bytes=")
+                            .unwrap()
+                            .split_once('-')
+                            .unwrap();
+                        let start: usize = start.parse().unwrap();
+                        let end = end.parse::<usize>().map_or(len, |end| (end + 1).min(len));
+                        (start, end)
+                    }
+                    None => (0, len),
+                };
+                let content = Bytes::copy_from_slice(&blob.content[start..end]);
+                headers.insert("content-length", content.len().to_string());
+                let status = if range.is_some() {
+                    headers.insert(
+                        "content-range",
+                        format!("bytes {}-{}/{}", start, end - 1, len),
+                    );
+                    StatusCode::PartialContent
+                } else {
+                    StatusCode::Ok
+                };
+                Ok(AsyncRawResponse::from_bytes(status, headers, content))
+            }
+            .boxed()
+        }));
+        let client = BlobClient::new(
+            Url::parse("https://example.blob.core.windows.net/container/blob").unwrap(),
+            None,
+            Some(BlobClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        client.encrypted_client(encryption_options())
+    }
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[tokio::test]
+    async fn upload_encrypts_and_download_decrypts() -> Result<()> {
+        let blob = Arc::new(Mutex::new(StoredBlob::default()));
+        let client = client(blob.clone());
+        let content = plaintext(1000);
+
+        client
+            .upload(RequestContent::from(content.clone()), None)
+            .await?;
+        {
+            let blob = blob.lock().unwrap();
+            assert_eq!(blob.content.len(), 1000 + NONCE_LENGTH + TAG_LENGTH);
+            assert_ne!(
+                &blob.content[NONCE_LENGTH..NONCE_LENGTH + 1000],
+                &content[..]
+            );
+            let (key, json) = &blob.metadata[0];
+            assert_eq!(key, ENCRYPTION_DATA_KEY);
+            assert!(json.contains(r#""Protocol":"2.0""#));
+        }
+
+        let result = client.download(None).await?;
+        assert_eq!(result.properties.content_length, Some(1000));
+        assert!(!result.properties.metadata.contains_key(ENCRYPTION_DATA_KEY));
+        assert_eq!(result.body.collect().await?.to_vec(), content);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn range_download_fetches_overlapping_regions() -> Result<()> {
+        let blob = Arc::new(Mutex::new(StoredBlob::default()));
+        let client = client(blob.clone());
+        let content = plaintext(REGION_DATA_LENGTH + 100);
+        // Upload in a single request so the mock service doesn't need to handle blocks.
+        client
+            .upload(
+                RequestContent::from(content.clone()),
+                Some(BlobClientUploadOptions {
+                    partition_size: NonZero::new(2 * ENCRYPTED_REGION_LENGTH as u64),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let start = REGION_DATA_LENGTH as u64 + 10;
+        let result = client
+            .download(Some(BlobClientDownloadOptions {
+                range: Some((start..start + 50).into()),
+                ..Default::default()
+            }))
+            .await?;
+        assert_eq!(result.properties.content_length, Some(50));
+        assert_eq!(
+            result.body.collect().await?.to_vec(),
+            &content[start as usize..start as usize + 50]
+        );
+        // The first region is skipped.
+        let ranges = blob.lock().unwrap().ranges.clone();
+        assert!(ranges[0].starts_with(&format!("bytes={}-", ENCRYPTED_REGION_LENGTH)));
+
+        let result = client
+            .download(Some(BlobClientDownloadOptions {
+                range: Some((REGION_DATA_LENGTH as u64 - 5..).into()),
+                ..Default::default()
+            }))
+            .await?;
+        assert_eq!(
+            result.body.collect().await?.to_vec(),
+            &content[REGION_DATA_LENGTH - 5..]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn download_passes_through_unencrypted_blobs() -> Result<()> {
+        let blob = Arc::new(Mutex::new(StoredBlob {
+            content: b"plain text".to_vec(),
+            ..Default::default()
+        }));
+        let client = client(blob);
+
+        let result = client.download(None).await?;
+        assert_eq!(&result.body.collect().await?[..], b"plain text");
+
+        let result = client
+            .download(Some(BlobClientDownloadOptions {
+                range: Some((6u64..10).into()),
+                ..Default::default()
+            }))
+            .await?;
+        assert_eq!(&result.body.collect().await?[..], b"text");
+        Ok(())
+    }
+}
@@ -12,6 +12,8 @@ mod blob_client;
 mod blob_container_client;
 mod blob_service_client;
 mod block_blob_client;
+#[cfg(feature = "encryption")]
+mod encrypted_blob_client;
 mod page_blob_client;
 
 pub use append_blob_client::{AppendBlobClient, AppendBlobClientOptions};
@@ -19,6 +21,9 @@ pub use blob_client::{BlobClient, BlobClientOptions};
 pub use blob_container_client::{BlobContainerClient, BlobContainerClientOptions};
 pub use blob_service_client::{BlobServiceClient, BlobServiceClientOptions};
 pub use block_blob_client::{BlockBlobClient, BlockBlobClientOptions};
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub use encrypted_blob_client::EncryptedBlobClient;
 pub use page_blob_client::{PageBlobClient, PageBlobClientOptions};
 
 #[allow(clippy::needless_update)]
@@ -9,6 +9,9 @@
 
 pub(crate) mod buffers;
 pub mod clients;
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub use azure_storage_common::encryption;
 #[allow(unused_imports)]
 mod generated;
 mod parsers;
@@ -10,8 +10,8 @@ use azure_core::{
 use time::OffsetDateTime;
 
 use crate::models::{
-    AccessTier, BlobClientDownloadInternalOptions, EncryptionAlgorithmType, HttpRange,
-    ImmutabilityPolicyMode,
+    AccessTier, BlobClientDownloadInternalOptions, BlobQueryErrorHandler, BlobQueryProgressHandler,
+    EncryptionAlgorithmType, HttpRange, ImmutabilityPolicyMode, QueryFormat,
 };
 
 /// Options to be passed to `BlobClient::download()`
@@ -198,3 +198,53 @@ pub struct BlockBlobClientUploadOptions<'a> {
     /// The tier to be set on the blob.
     pub tier: Option<AccessTier>,
 }
+
+/// Options to be passed to `BlobClient::query()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct BlobClientQueryOptions<'a> {
+    /// The algorithm used to produce the encryption key hash. Must be provided if the encryption key is provided.
+    pub encryption_algorithm: Option<EncryptionAlgorithmType>,
+
+    /// Specifies the encryption key to use to encrypt the data provided in the request.
+    pub encryption_key: Option<String>,
+
+    /// The SHA-256 hash of the provided encryption key. Must be provided if the encryption key is provided.
+    pub encryption_key_sha256: Option<String>,
+
+    /// Optional. Called with non-fatal errors reported by the service, such as malformed records.
+    /// If not set, the first error reported by the service fails the returned stream.
+    pub error_handler: Option<BlobQueryErrorHandler>,
+
+    /// Specify this value to operate only on a blob with a matching Etag value.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a blob if it has been modified since the specified date-time.
+    pub if_modified_since: Option<OffsetDateTime>,
+
+    /// Specify this value to operate only on a blob with a non-matching Etag value.
+    pub if_none_match: Option<Etag>,
+
+    /// Specifies a SQL-like where clause on blob tags to operate only on a blob with matching tags.
+    pub if_tags: Option<String>,
+
+    /// Specify this value to operate only on a blob if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// The serialization format of the blob content. Defaults to CSV without headers when not set.
+    pub input_format: Option<QueryFormat>,
+
+    /// If specified, the operation only succeeds if the resource's lease is active and matches this ID.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The serialization format of the query results. Defaults to the input format when not set.
+    pub output_format: Option<QueryFormat>,
+
+    /// Optional. Called with the number of bytes of the blob scanned as the service reports progress.
+    pub progress_handler: Option<BlobQueryProgressHandler>,
+
+    /// The timeout parameter is expressed in seconds. For more information, see [Setting Timeouts for Blob Service Operations.](https://docs.microsoft.com/en-us/rest/api/storageservices/fileservices/setting-timeouts-for-blob-service-operations)
+    pub timeout: Option<i32>,
+}
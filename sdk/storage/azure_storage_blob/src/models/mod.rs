@@ -9,6 +9,7 @@ pub(crate) mod error;
 pub(crate) mod extensions;
 pub(crate) mod http_ranges;
 mod method_options;
+mod query;
 
 pub use http_ranges::HttpRange;
 pub(crate) mod response_ext;
@@ -19,8 +20,14 @@ pub use download_result::{
     BlobClientDownloadIntoResult, BlobClientDownloadResult, BlobDownloadProperties,
 };
 pub use method_options::BlobClientDownloadOptions;
+pub use method_options::BlobClientQueryOptions;
 pub use method_options::BlockBlobClientUploadOptions;
 pub use method_options::BlockBlobClientUploadOptions as BlobClientUploadOptions;
+pub(crate) use query::query_request_body;
+pub use query::{
+    ArrowConfiguration, ArrowField, BlobClientQueryResult, BlobQueryError, BlobQueryErrorHandler,
+    BlobQueryProgressHandler, DelimitedTextConfiguration, JsonTextConfiguration, QueryFormat,
+};
 pub use upload_result::BlockBlobClientUploadResult;
 pub use upload_result::BlockBlobClientUploadResult as BlobClientUploadResult;
 
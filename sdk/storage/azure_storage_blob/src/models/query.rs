@@ -0,0 +1,369 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use azure_core::{
+    error::{Error, ErrorKind},
+    fmt::SafeDebug,
+    http::{headers::Headers, response::AsyncResponseBody},
+    xml, Bytes, Result,
+};
+use serde::Serialize;
+use std::{fmt, sync::Arc};
+
+/// Result of a `BlobClient::query()` operation.
+#[derive(SafeDebug)]
+pub struct BlobClientQueryResult {
+    /// The query results, serialized using the requested output format.
+    pub body: AsyncResponseBody,
+
+    /// All headers from the response.
+    ///
+    /// Use this to access headers that are not surfaced as named fields, such as
+    /// `x-ms-request-id`, `x-ms-client-request-id`, and more.
+    pub headers: Headers,
+}
+
+/// The serialization format of the data queried by, or returned from, `BlobClient::query()`.
+#[derive(Clone, SafeDebug)]
+#[non_exhaustive]
+pub enum QueryFormat {
+    /// Delimited text, such as CSV.
+    Delimited(DelimitedTextConfiguration),
+
+    /// Newline-delimited JSON.
+    Json(JsonTextConfiguration),
+
+    /// Apache Arrow. Only supported as an output format.
+    Arrow(ArrowConfiguration),
+
+    /// Apache Parquet. Only supported as an input format.
+    Parquet,
+}
+
+/// Settings for delimited text such as CSV.
+#[derive(Clone, Default, SafeDebug, Serialize)]
+pub struct DelimitedTextConfiguration {
+    /// The string used to separate columns.
+    #[serde(rename = "ColumnSeparator", skip_serializing_if = "Option::is_none")]
+    pub column_separator: Option<String>,
+
+    /// The string used to quote a specific field.
+    #[serde(rename = "FieldQuote", skip_serializing_if = "Option::is_none")]
+    pub field_quote: Option<String>,
+
+    /// The string used to separate records.
+    #[serde(rename = "RecordSeparator", skip_serializing_if = "Option::is_none")]
+    pub record_separator: Option<String>,
+
+    /// The string used as an escape character.
+    #[serde(rename = "EscapeChar", skip_serializing_if = "Option::is_none")]
+    pub escape_char: Option<String>,
+
+    /// Whether the data has a header row.
+    #[serde(rename = "HasHeaders", skip_serializing_if = "Option::is_none")]
+    pub headers_present: Option<bool>,
+}
+
+/// Settings for newline-delimited JSON.
+#[derive(Clone, Default, SafeDebug, Serialize)]
+pub struct JsonTextConfiguration {
+    /// The string used to separate records.
+    #[serde(rename = "RecordSeparator", skip_serializing_if = "Option::is_none")]
+    pub record_separator: Option<String>,
+}
+
+/// Settings for Apache Arrow output.
+#[derive(Clone, Default, SafeDebug, Serialize)]
+pub struct ArrowConfiguration {
+    /// The fields of the Arrow schema.
+    #[serde(rename = "Schema", with = "arrow_schema")]
+    pub schema: Vec<ArrowField>,
+}
+
+/// A field in an Apache Arrow schema.
+#[derive(Clone, Default, SafeDebug, Serialize)]
+pub struct ArrowField {
+    /// The Arrow type of the field, for example `int64`, `double`, `string`, or `decimal`.
+    #[serde(rename = "Type")]
+    pub field_type: String,
+
+    /// The name of the field.
+    #[serde(rename = "Name", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The precision of a `decimal` field.
+    #[serde(rename = "Precision", skip_serializing_if = "Option::is_none")]
+    pub precision: Option<i32>,
+
+    /// The scale of a `decimal` field.
+    #[serde(rename = "Scale", skip_serializing_if = "Option::is_none")]
+    pub scale: Option<i32>,
+}
+
+mod arrow_schema {
+    use super::ArrowField;
+    use serde::{Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct Schema<'a> {
+        #[serde(rename = "Field")]
+        fields: &'a [ArrowField],
+    }
+
+    pub fn serialize<S: Serializer>(
+        fields: &[ArrowField],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Schema { fields }.serialize(serializer)
+    }
+}
+
+/// An error or warning reported by the service while processing a query.
+#[derive(Clone, SafeDebug)]
+#[safe(true)]
+pub struct BlobQueryError {
+    /// Whether the error stopped the query.
+    pub is_fatal: bool,
+
+    /// The name of the error.
+    pub name: String,
+
+    /// A description of the error.
+    pub description: String,
+
+    /// The position in the blob at which the error occurred.
+    pub position: i64,
+}
+
+impl fmt::Display for BlobQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} (position {})",
+            self.name, self.description, self.position
+        )
+    }
+}
+
+/// A callback invoked with the number of bytes of the blob scanned so far by a query.
+#[derive(Clone)]
+pub struct BlobQueryProgressHandler(Arc<dyn Fn(u64) + Send + Sync>);
+
+impl BlobQueryProgressHandler {
+    /// Creates a progress handler from a callback.
+    pub fn new(handler: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn call(&self, bytes_scanned: u64) {
+        (self.0)(bytes_scanned)
+    }
+}
+
+impl fmt::Debug for BlobQueryProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlobQueryProgressHandler")
+    }
+}
+
+/// A callback invoked with non-fatal errors reported by the service while processing a query.
+#[derive(Clone)]
+pub struct BlobQueryErrorHandler(Arc<dyn Fn(&BlobQueryError) + Send + Sync>);
+
+impl BlobQueryErrorHandler {
+    /// Creates an error handler from a callback.
+    pub fn new(handler: impl Fn(&BlobQueryError) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(crate) fn call(&self, error: &BlobQueryError) {
+        (self.0)(error)
+    }
+}
+
+impl fmt::Debug for BlobQueryErrorHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BlobQueryErrorHandler")
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "QueryRequest")]
+struct QueryRequest<'a> {
+    #[serde(rename = "QueryType")]
+    query_type: &'static str,
+
+    #[serde(rename = "Expression")]
+    expression: &'a str,
+
+    #[serde(rename = "InputSerialization", skip_serializing_if = "Option::is_none")]
+    input: Option<QuerySerialization<'a>>,
+
+    #[serde(
+        rename = "OutputSerialization",
+        skip_serializing_if = "Option::is_none"
+    )]
+    output: Option<QuerySerialization<'a>>,
+}
+
+#[derive(Serialize)]
+struct QuerySerialization<'a> {
+    #[serde(rename = "Format")]
+    format: QueryFormatXml<'a>,
+}
+
+#[derive(Default, Serialize)]
+struct QueryFormatXml<'a> {
+    #[serde(rename = "Type")]
+    format_type: &'static str,
+
+    #[serde(
+        rename = "DelimitedTextConfiguration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    delimited: Option<&'a DelimitedTextConfiguration>,
+
+    #[serde(
+        rename = "JsonTextConfiguration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    json: Option<&'a JsonTextConfiguration>,
+
+    #[serde(rename = "ArrowConfiguration", skip_serializing_if = "Option::is_none")]
+    arrow: Option<&'a ArrowConfiguration>,
+
+    #[serde(
+        rename = "ParquetTextConfiguration",
+        skip_serializing_if = "Option::is_none"
+    )]
+    parquet: Option<ParquetTextConfiguration>,
+}
+
+#[derive(Serialize)]
+struct ParquetTextConfiguration {}
+
+impl<'a> From<&'a QueryFormat> for QuerySerialization<'a> {
+    fn from(format: &'a QueryFormat) -> Self {
+        let format = match format {
+            QueryFormat::Delimited(config) => QueryFormatXml {
+                format_type: "delimited",
+                delimited: Some(config),
+                ..Default::default()
+            },
+            QueryFormat::Json(config) => QueryFormatXml {
+                format_type: "json",
+                json: Some(config),
+                ..Default::default()
+            },
+            QueryFormat::Arrow(config) => QueryFormatXml {
+                format_type: "arrow",
+                arrow: Some(config),
+                ..Default::default()
+            },
+            QueryFormat::Parquet => QueryFormatXml {
+                format_type: "parquet",
+                parquet: Some(ParquetTextConfiguration {}),
+                ..Default::default()
+            },
+        };
+        Self { format }
+    }
+}
+
+/// Serializes the body of a Quick Query request.
+pub(crate) fn query_request_body(
+    expression: &str,
+    input_format: Option<&QueryFormat>,
+    output_format: Option<&QueryFormat>,
+) -> Result<Bytes> {
+    if matches!(input_format, Some(QueryFormat::Arrow(_))) {
+        return Err(Error::with_message(
+            ErrorKind::Other,
+            "Arrow is not supported as a query input format",
+        ));
+    }
+    if matches!(output_format, Some(QueryFormat::Parquet)) {
+        return Err(Error::with_message(
+            ErrorKind::Other,
+            "Parquet is not supported as a query output format",
+        ));
+    }
+    xml::to_xml(&QueryRequest {
+        query_type: "SQL",
+        expression,
+        input: input_format.map(Into::into),
+        output: output_format.map(Into::into),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_delimited_to_json() {
+        let body = query_request_body(
+            "SELECT * from BlobStorage",
+            Some(&QueryFormat::Delimited(DelimitedTextConfiguration {
+                column_separator: Some(",".into()),
+                headers_present: Some(true),
+                ..Default::default()
+            })),
+            Some(&QueryFormat::Json(JsonTextConfiguration {
+                record_separator: Some(";".into()),
+            })),
+        )
+        .unwrap();
+        assert_eq!(
+            std::str::from_utf8(&body).unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="utf-8"?>"#,
+                "<QueryRequest><QueryType>SQL</QueryType><Expression>SELECT * from BlobStorage</Expression>",
+                "<InputSerialization><Format><Type>delimited</Type><DelimitedTextConfiguration>",
+                "<ColumnSeparator>,</ColumnSeparator><HasHeaders>true</HasHeaders>",
+                "</DelimitedTextConfiguration></Format></InputSerialization>",
+                "<OutputSerialization><Format><Type>json</Type><JsonTextConfiguration>",
+                "<RecordSeparator>;</RecordSeparator></JsonTextConfiguration></Format></OutputSerialization>",
+                "</QueryRequest>"
+            )
+        );
+    }
+
+    #[test]
+    fn serializes_parquet_to_arrow() {
+        let body = query_request_body(
+            "SELECT _2 from BlobStorage",
+            Some(&QueryFormat::Parquet),
+            Some(&QueryFormat::Arrow(ArrowConfiguration {
+                schema: vec![ArrowField {
+                    field_type: "decimal".into(),
+                    name: Some("price".into()),
+                    precision: Some(4),
+                    scale: Some(2),
+                }],
+            })),
+        )
+        .unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.contains(
+            "<InputSerialization><Format><Type>parquet</Type><ParquetTextConfiguration/></Format></InputSerialization>"
+        ));
+        assert!(body.contains(concat!(
+            "<OutputSerialization><Format><Type>arrow</Type><ArrowConfiguration><Schema><Field>",
+            "<Type>decimal</Type><Name>price</Name><Precision>4</Precision><Scale>2</Scale>",
+            "</Field></Schema></ArrowConfiguration></Format></OutputSerialization>"
+        )));
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        let arrow = QueryFormat::Arrow(ArrowConfiguration::default());
+        assert!(query_request_body("SELECT * from BlobStorage", Some(&arrow), None).is_err());
+        assert!(query_request_body(
+            "SELECT * from BlobStorage",
+            None,
+            Some(&QueryFormat::Parquet)
+        )
+        .is_err());
+    }
+}
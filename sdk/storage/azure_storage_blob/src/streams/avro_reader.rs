@@ -0,0 +1,602 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! A minimal, incremental reader for [Avro object container files](https://avro.apache.org/docs/1.11.1/specification/#object-container-files).
+//!
+//! The Blob service frames Quick Query responses as an Avro object container file. Bytes are
+//! pushed into the [`AvroReader`] as they arrive from the network and records are returned as
+//! soon as a complete block has been received. Only the `null` codec is supported, which is the
+//! only codec the service uses.
+
+use azure_core::{
+    error::{Error, ErrorKind},
+    Result,
+};
+use bytes::{Buf, BytesMut};
+use serde_json::Value as Json;
+use std::collections::{HashMap, VecDeque};
+
+const MAGIC: &[u8; 4] = b"Obj\x01";
+const SYNC_LEN: usize = 16;
+
+/// A decoded Avro datum.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Boolean(bool),
+    Long(i64),
+    Double(f64),
+    Bytes(Vec<u8>),
+    String(String),
+    Enum(String),
+    Array(Vec<Value>),
+    Map(HashMap<String, Value>),
+    Record {
+        /// The full name of the record schema, including its namespace.
+        name: String,
+        fields: Vec<(String, Value)>,
+    },
+}
+
+impl Value {
+    /// Gets the value of a record field by name.
+    pub(crate) fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Record { fields, .. } => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Schema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Fixed(usize),
+    Enum(Vec<String>),
+    Array(Box<Schema>),
+    Map(Box<Schema>),
+    Union(Vec<Schema>),
+    Record {
+        name: String,
+        fields: Vec<(String, Schema)>,
+    },
+}
+
+impl Schema {
+    fn parse(
+        json: &Json,
+        namespace: Option<&str>,
+        named: &mut HashMap<String, Schema>,
+    ) -> Result<Self> {
+        match json {
+            Json::String(name) => Self::parse_name(name, namespace, named),
+            Json::Array(variants) => Ok(Schema::Union(
+                variants
+                    .iter()
+                    .map(|v| Self::parse(v, namespace, named))
+                    .collect::<Result<_>>()?,
+            )),
+            Json::Object(obj) => {
+                let ty = obj
+                    .get("type")
+                    .ok_or_else(|| invalid("schema is missing a type"))?;
+                let Json::String(ty) = ty else {
+                    return Self::parse(ty, namespace, named);
+                };
+                let full_name = || -> Result<String> {
+                    let name = obj
+                        .get("name")
+                        .and_then(Json::as_str)
+                        .ok_or_else(|| invalid("named schema is missing a name"))?;
+                    let namespace = obj.get("namespace").and_then(Json::as_str).or(namespace);
+                    Ok(match namespace {
+                        Some(ns) if !name.contains('.') && !ns.is_empty() => format!("{ns}.{name}"),
+                        _ => name.to_string(),
+                    })
+                };
+                let schema = match ty.as_str() {
+                    "record" | "error" => {
+                        let name = full_name()?;
+                        let inner_namespace = name.rsplit_once('.').map(|(ns, _)| ns.to_string());
+                        let fields = obj
+                            .get("fields")
+                            .and_then(Json::as_array)
+                            .ok_or_else(|| invalid("record schema is missing fields"))?
+                            .iter()
+                            .map(|field| {
+                                let field_name = field
+                                    .get("name")
+                                    .and_then(Json::as_str)
+                                    .ok_or_else(|| invalid("record field is missing a name"))?;
+                                let field_type = field
+                                    .get("type")
+                                    .ok_or_else(|| invalid("record field is missing a type"))?;
+                                Ok((
+                                    field_name.to_string(),
+                                    Self::parse(field_type, inner_namespace.as_deref(), named)?,
+                                ))
+                            })
+                            .collect::<Result<_>>()?;
+                        Schema::Record { name, fields }
+                    }
+                    "enum" => {
+                        full_name()?;
+                        let symbols = obj
+                            .get("symbols")
+                            .and_then(Json::as_array)
+                            .ok_or_else(|| invalid("enum schema is missing symbols"))?
+                            .iter()
+                            .map(|s| s.as_str().map(str::to_string))
+                            .collect::<Option<_>>()
+                            .ok_or_else(|| invalid("enum symbols must be strings"))?;
+                        Schema::Enum(symbols)
+                    }
+                    "fixed" => {
+                        full_name()?;
+                        let size = obj
+                            .get("size")
+                            .and_then(Json::as_u64)
+                            .ok_or_else(|| invalid("fixed schema is missing a size"))?;
+                        Schema::Fixed(size as usize)
+                    }
+                    "array" => Schema::Array(Box::new(Self::parse(
+                        obj.get("items")
+                            .ok_or_else(|| invalid("array schema is missing items"))?,
+                        namespace,
+                        named,
+                    )?)),
+                    "map" => Schema::Map(Box::new(Self::parse(
+                        obj.get("values")
+                            .ok_or_else(|| invalid("map schema is missing values"))?,
+                        namespace,
+                        named,
+                    )?)),
+                    other => return Self::parse_name(other, namespace, named),
+                };
+                if matches!(ty.as_str(), "record" | "error" | "enum" | "fixed") {
+                    named.insert(full_name()?, schema.clone());
+                }
+                Ok(schema)
+            }
+            _ => Err(invalid("unexpected schema JSON")),
+        }
+    }
+
+    fn parse_name(
+        name: &str,
+        namespace: Option<&str>,
+        named: &HashMap<String, Schema>,
+    ) -> Result<Self> {
+        Ok(match name {
+            "null" => Schema::Null,
+            "boolean" => Schema::Boolean,
+            "int" => Schema::Int,
+            "long" => Schema::Long,
+            "float" => Schema::Float,
+            "double" => Schema::Double,
+            "bytes" => Schema::Bytes,
+            "string" => Schema::String,
+            _ => namespace
+                .and_then(|ns| named.get(&format!("{ns}.{name}")))
+                .or_else(|| named.get(name))
+                .cloned()
+                .ok_or_else(|| invalid(format!("unknown schema type '{name}'")))?,
+        })
+    }
+}
+
+/// Indicates whether decoding failed because more bytes are needed or because the data is invalid.
+enum DecodeError {
+    Incomplete,
+    Invalid(Error),
+}
+
+impl From<Error> for DecodeError {
+    fn from(err: Error) -> Self {
+        DecodeError::Invalid(err)
+    }
+}
+
+type DecodeResult<T> = std::result::Result<T, DecodeError>;
+
+fn invalid(message: impl Into<String>) -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        format!("invalid Avro data: {}", message.into()),
+    )
+}
+
+/// Reads primitive values from a byte slice, reporting [`DecodeError::Incomplete`] when the slice ends early.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError::Incomplete)?;
+        let bytes = self
+            .data
+            .get(self.pos..end)
+            .ok_or(DecodeError::Incomplete)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_long(&mut self) -> DecodeResult<i64> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                // Zigzag decode.
+                return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+            }
+        }
+        Err(invalid("variable-length integer is too long").into())
+    }
+
+    fn read_len(&mut self) -> DecodeResult<usize> {
+        usize::try_from(self.read_long()?).map_err(|_| invalid("negative length").into())
+    }
+
+    fn read_bytes(&mut self) -> DecodeResult<&'a [u8]> {
+        let len = self.read_len()?;
+        self.take(len)
+    }
+
+    fn read_string(&mut self) -> DecodeResult<String> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("string is not valid UTF-8").into())
+    }
+
+    /// Reads the item count of the next array or map block, skipping the byte size of negative counts.
+    fn read_block_count(&mut self) -> DecodeResult<usize> {
+        let count = self.read_long()?;
+        if count < 0 {
+            self.read_long()?;
+        }
+        Ok(count.unsigned_abs() as usize)
+    }
+
+    fn read_value(&mut self, schema: &Schema) -> DecodeResult<Value> {
+        Ok(match schema {
+            Schema::Null => Value::Null,
+            Schema::Boolean => Value::Boolean(self.take(1)?[0] != 0),
+            Schema::Int | Schema::Long => Value::Long(self.read_long()?),
+            Schema::Float => {
+                let bytes: [u8; 4] = self.take(4)?.try_into().expect("took 4 bytes");
+                Value::Double(f32::from_le_bytes(bytes).into())
+            }
+            Schema::Double => {
+                let bytes: [u8; 8] = self.take(8)?.try_into().expect("took 8 bytes");
+                Value::Double(f64::from_le_bytes(bytes))
+            }
+            Schema::Bytes => Value::Bytes(self.read_bytes()?.to_vec()),
+            Schema::String => Value::String(self.read_string()?),
+            Schema::Fixed(size) => Value::Bytes(self.take(*size)?.to_vec()),
+            Schema::Enum(symbols) => {
+                let index = self.read_len()?;
+                Value::Enum(
+                    symbols
+                        .get(index)
+                        .cloned()
+                        .ok_or_else(|| invalid("enum index out of range"))?,
+                )
+            }
+            Schema::Array(items) => {
+                let mut values = Vec::new();
+                loop {
+                    let count = self.read_block_count()?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        values.push(self.read_value(items)?);
+                    }
+                }
+                Value::Array(values)
+            }
+            Schema::Map(values_schema) => {
+                let mut values = HashMap::new();
+                loop {
+                    let count = self.read_block_count()?;
+                    if count == 0 {
+                        break;
+                    }
+                    for _ in 0..count {
+                        let key = self.read_string()?;
+                        values.insert(key, self.read_value(values_schema)?);
+                    }
+                }
+                Value::Map(values)
+            }
+            Schema::Union(variants) => {
+                let index = self.read_len()?;
+                let variant = variants
+                    .get(index)
+                    .ok_or_else(|| invalid("union index out of range"))?;
+                self.read_value(variant)?
+            }
+            Schema::Record { name, fields } => Value::Record {
+                name: name.clone(),
+                fields: fields
+                    .iter()
+                    .map(|(field_name, field_schema)| {
+                        Ok((field_name.clone(), self.read_value(field_schema)?))
+                    })
+                    .collect::<DecodeResult<_>>()?,
+            },
+        })
+    }
+}
+
+struct Header {
+    schema: Schema,
+    sync: [u8; SYNC_LEN],
+}
+
+impl Header {
+    fn decode(cursor: &mut Cursor) -> DecodeResult<Self> {
+        if cursor.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("missing object container file magic").into());
+        }
+
+        let mut metadata = HashMap::new();
+        loop {
+            let count = cursor.read_block_count()?;
+            if count == 0 {
+                break;
+            }
+            for _ in 0..count {
+                let key = cursor.read_string()?;
+                metadata.insert(key, cursor.read_bytes()?.to_vec());
+            }
+        }
+        let sync = cursor.take(SYNC_LEN)?.try_into().expect("took sync bytes");
+
+        match metadata.get("avro.codec").map(Vec::as_slice) {
+            None | Some(b"null") => {}
+            Some(codec) => {
+                return Err(invalid(format!(
+                    "unsupported codec '{}'",
+                    String::from_utf8_lossy(codec)
+                ))
+                .into())
+            }
+        }
+        let schema = metadata
+            .get("avro.schema")
+            .ok_or_else(|| invalid("missing avro.schema metadata"))?;
+        let schema: Json = serde_json::from_slice(schema)
+            .map_err(|e| invalid(format!("avro.schema is not valid JSON: {e}")))?;
+        let schema = Schema::parse(&schema, None, &mut HashMap::new())?;
+
+        Ok(Self { schema, sync })
+    }
+}
+
+/// Incrementally decodes an Avro object container file.
+#[derive(Default)]
+pub(crate) struct AvroReader {
+    buffer: BytesMut,
+    header: Option<Header>,
+    pending: VecDeque<Value>,
+}
+
+impl AvroReader {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends bytes received from the underlying stream.
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Returns `true` if every pushed byte has been decoded and returned.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buffer.is_empty() && self.pending.is_empty()
+    }
+
+    /// Returns the next decoded value, or `None` if more bytes must be pushed first.
+    pub(crate) fn next_value(&mut self) -> Result<Option<Value>> {
+        loop {
+            if let Some(value) = self.pending.pop_front() {
+                return Ok(Some(value));
+            }
+
+            let mut cursor = Cursor::new(&self.buffer);
+            let result = match &self.header {
+                None => Header::decode(&mut cursor).map(|header| {
+                    self.header = Some(header);
+                }),
+                Some(header) => Self::decode_block(&mut cursor, header).map(|values| {
+                    self.pending.extend(values);
+                }),
+            };
+            match result {
+                Ok(()) => {
+                    let consumed = cursor.pos;
+                    self.buffer.advance(consumed);
+                }
+                Err(DecodeError::Incomplete) => return Ok(None),
+                Err(DecodeError::Invalid(err)) => return Err(err),
+            }
+        }
+    }
+
+    fn decode_block(cursor: &mut Cursor, header: &Header) -> DecodeResult<Vec<Value>> {
+        let count = cursor.read_len()?;
+        let data = cursor.read_bytes()?;
+        let sync = cursor.take(SYNC_LEN)?;
+        if sync != header.sync {
+            return Err(invalid("block sync marker does not match the header").into());
+        }
+
+        // The whole block is available, so running out of data here means the block is corrupt.
+        let mut block = Cursor::new(data);
+        let values = (0..count)
+            .map(|_| match block.read_value(&header.schema) {
+                Err(DecodeError::Incomplete) => {
+                    Err(invalid("block ended before all objects were read").into())
+                }
+                other => other,
+            })
+            .collect::<DecodeResult<Vec<_>>>()?;
+        if block.pos != data.len() {
+            return Err(invalid("block contains trailing bytes").into());
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Encodes Avro values for building test payloads.
+    pub(crate) fn long(value: i64) -> Vec<u8> {
+        let mut n = ((value << 1) ^ (value >> 63)) as u64;
+        let mut out = Vec::new();
+        loop {
+            if n & !0x7f == 0 {
+                out.push(n as u8);
+                return out;
+            }
+            out.push((n as u8 & 0x7f) | 0x80);
+            n >>= 7;
+        }
+    }
+
+    pub(crate) fn bytes(value: &[u8]) -> Vec<u8> {
+        let mut out = long(value.len() as i64);
+        out.extend_from_slice(value);
+        out
+    }
+
+    /// Builds an object container file with one block per entry in `blocks`.
+    pub(crate) fn container(schema: &str, blocks: &[(i64, Vec<u8>)]) -> Vec<u8> {
+        let sync = [7u8; SYNC_LEN];
+        let mut out = MAGIC.to_vec();
+        out.extend(long(2));
+        out.extend(bytes(b"avro.schema"));
+        out.extend(bytes(schema.as_bytes()));
+        out.extend(bytes(b"avro.codec"));
+        out.extend(bytes(b"null"));
+        out.extend(long(0));
+        out.extend_from_slice(&sync);
+        for (count, data) in blocks {
+            out.extend(long(*count));
+            out.extend(bytes(data));
+            out.extend_from_slice(&sync);
+        }
+        out
+    }
+
+    const RECORD_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "point",
+        "namespace": "test",
+        "fields": [
+            {"name": "x", "type": "long"},
+            {"name": "label", "type": ["null", "string"]},
+            {"name": "weight", "type": "double"},
+            {"name": "ok", "type": "boolean"}
+        ]
+    }"#;
+
+    fn point(x: i64, label: Option<&str>) -> Vec<u8> {
+        let mut out = long(x);
+        match label {
+            Some(label) => {
+                out.extend(long(1));
+                out.extend(bytes(label.as_bytes()));
+            }
+            None => out.extend(long(0)),
+        }
+        out.extend(1.5f64.to_le_bytes());
+        out.push(1);
+        out
+    }
+
+    #[test]
+    fn decodes_records_across_blocks() {
+        let mut block = point(-3, Some("a"));
+        block.extend(point(300, None));
+        let data = container(RECORD_SCHEMA, &[(2, block), (1, point(0, Some("z")))]);
+
+        let mut reader = AvroReader::new();
+        reader.push(&data);
+        let mut values = Vec::new();
+        while let Some(value) = reader.next_value().unwrap() {
+            values.push(value);
+        }
+        assert!(reader.is_empty());
+        assert_eq!(values.len(), 3);
+        let Value::Record { name, .. } = &values[0] else {
+            panic!("expected a record");
+        };
+        assert_eq!(name, "test.point");
+        assert_eq!(values[0].field("x"), Some(&Value::Long(-3)));
+        assert_eq!(values[0].field("label"), Some(&Value::String("a".into())));
+        assert_eq!(values[1].field("x"), Some(&Value::Long(300)));
+        assert_eq!(values[1].field("label"), Some(&Value::Null));
+        assert_eq!(values[1].field("weight"), Some(&Value::Double(1.5)));
+        assert_eq!(values[2].field("ok"), Some(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn decodes_incrementally() {
+        let data = container(RECORD_SCHEMA, &[(1, point(42, Some("b")))]);
+
+        let mut reader = AvroReader::new();
+        let mut values = Vec::new();
+        for byte in &data {
+            reader.push(std::slice::from_ref(byte));
+            while let Some(value) = reader.next_value().unwrap() {
+                values.push(value);
+            }
+        }
+        assert!(reader.is_empty());
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].field("x"), Some(&Value::Long(42)));
+    }
+
+    #[test]
+    fn rejects_mismatched_sync_marker() {
+        let mut data = container(RECORD_SCHEMA, &[(1, point(1, None))]);
+        let len = data.len();
+        data[len - 1] ^= 0xff;
+
+        let mut reader = AvroReader::new();
+        reader.push(&data);
+        assert!(reader.next_value().is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_codec() {
+        let mut data = MAGIC.to_vec();
+        data.extend(long(1));
+        data.extend(bytes(b"avro.codec"));
+        data.extend(bytes(b"deflate"));
+        data.extend(long(0));
+        data.extend_from_slice(&[0; SYNC_LEN]);
+
+        let mut reader = AvroReader::new();
+        reader.push(&data);
+        let err = reader.next_value().unwrap_err();
+        assert!(err.to_string().contains("deflate"));
+    }
+}
@@ -0,0 +1,71 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use async_stream::try_stream;
+use azure_core::Result;
+use azure_storage_common::encryption::ContentCipher;
+use bytes::{Bytes, BytesMut};
+use futures::{Stream, TryStreamExt};
+
+/// Decrypts a stream of whole client-side encrypted regions.
+///
+/// The first `skip` bytes of plaintext are dropped and at most `take` bytes are returned, so that
+/// a range of plaintext can be returned from the region-aligned range that was downloaded.
+pub(crate) fn decrypt_stream<S>(
+    body: S,
+    cipher: ContentCipher,
+    skip: u64,
+    take: Option<u64>,
+) -> impl Stream<Item = Result<Bytes>> + Send
+where
+    S: Stream<Item = Result<Bytes>> + Send + Unpin,
+{
+    try_stream! {
+        let mut body = body;
+        let region_len = cipher.encrypted_region_length();
+        let mut buffer = BytesMut::new();
+        let mut trim = Trim { skip, remaining: take };
+        let mut done = false;
+        while let Some(chunk) = body.try_next().await? {
+            buffer.extend_from_slice(&chunk);
+            while buffer.len() >= region_len {
+                let region = buffer.split_to(region_len);
+                if let Some(plaintext) = trim.apply(cipher.decrypt_region(&region)?) {
+                    yield plaintext;
+                }
+                if trim.remaining == Some(0) {
+                    done = true;
+                    break;
+                }
+            }
+            if done {
+                break;
+            }
+        }
+        if !done && !buffer.is_empty() {
+            if let Some(plaintext) = trim.apply(cipher.decrypt_region(&buffer)?) {
+                yield plaintext;
+            }
+        }
+    }
+}
+
+struct Trim {
+    skip: u64,
+    remaining: Option<u64>,
+}
+
+impl Trim {
+    fn apply(&mut self, plaintext: Vec<u8>) -> Option<Bytes> {
+        let mut plaintext = Bytes::from(plaintext);
+        let skip = self.skip.min(plaintext.len() as u64);
+        self.skip -= skip;
+        let _ = plaintext.split_to(skip as usize);
+        if let Some(remaining) = self.remaining.as_mut() {
+            let len = (*remaining).min(plaintext.len() as u64);
+            plaintext.truncate(len as usize);
+            *remaining -= len;
+        }
+        (!plaintext.is_empty()).then_some(plaintext)
+    }
+}
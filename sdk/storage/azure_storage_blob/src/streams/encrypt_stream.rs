@@ -0,0 +1,163 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use std::{
+    cmp::min,
+    fmt, io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use azure_core::stream::SeekableStream;
+use azure_storage_common::encryption::ContentCipher;
+use bytes::Bytes;
+use futures::AsyncRead;
+
+/// Encrypts a stream of plaintext region by region, so that at most one region of plaintext and
+/// one region of ciphertext are held in memory at a time.
+///
+/// Resetting the stream resets the plaintext stream, and re-encrypts it with new nonces.
+#[derive(Clone)]
+pub(crate) struct EncryptStream {
+    inner: Box<dyn SeekableStream>,
+    cipher: Arc<ContentCipher>,
+    /// The plaintext of the region being read, of which the first `filled` bytes are valid.
+    plaintext: Vec<u8>,
+    filled: usize,
+    /// The ciphertext of the last encrypted region that hasn't been read yet.
+    ciphertext: Bytes,
+    regions: u64,
+    eof: bool,
+}
+
+impl EncryptStream {
+    pub(crate) fn new(inner: Box<dyn SeekableStream>, cipher: ContentCipher) -> Self {
+        Self {
+            inner,
+            cipher: Arc::new(cipher),
+            plaintext: Vec::new(),
+            filled: 0,
+            ciphertext: Bytes::new(),
+            regions: 0,
+            eof: false,
+        }
+    }
+}
+
+impl fmt::Debug for EncryptStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptStream")
+            .field("inner", &self.inner)
+            .field("regions", &self.regions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsyncRead for EncryptStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            if !this.ciphertext.is_empty() {
+                let count = min(buf.len(), this.ciphertext.len());
+                buf[..count].copy_from_slice(&this.ciphertext.split_to(count));
+                return Poll::Ready(Ok(count));
+            }
+            if this.eof && this.filled == 0 && this.regions > 0 {
+                return Poll::Ready(Ok(0));
+            }
+
+            let region_len = this.cipher.region_data_length();
+            if this.plaintext.len() != region_len {
+                this.plaintext = vec![0; region_len];
+            }
+            while !this.eof && this.filled < region_len {
+                match Pin::new(&mut this.inner).poll_read(cx, &mut this.plaintext[this.filled..]) {
+                    Poll::Ready(Ok(0)) => this.eof = true,
+                    Poll::Ready(Ok(count)) => this.filled += count,
+                    Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+            // Empty content is encrypted as a single empty region.
+            if this.filled == 0 && this.regions > 0 {
+                continue;
+            }
+
+            let region = this
+                .cipher
+                .encrypt_region(&this.plaintext[..this.filled])
+                .map_err(io::Error::other)?;
+            this.ciphertext = region.into();
+            this.filled = 0;
+            this.regions += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl SeekableStream for EncryptStream {
+    async fn reset(&mut self) -> azure_core::Result<()> {
+        self.inner.reset().await?;
+        self.filled = 0;
+        self.ciphertext = Bytes::new();
+        self.regions = 0;
+        self.eof = false;
+        Ok(())
+    }
+
+    fn len(&self) -> Option<u64> {
+        self.inner
+            .len()
+            .map(|len| self.cipher.encrypted_length(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::{ClientSideEncryptionOptions, LocalKeyEncryptionKey};
+    use azure_core::stream::BytesStream;
+    use azure_storage_common::encryption::{EncryptionData, NONCE_LENGTH, TAG_LENGTH};
+    use futures::AsyncReadExt;
+
+    async fn cipher() -> (ContentCipher, ClientSideEncryptionOptions, EncryptionData) {
+        let kek = LocalKeyEncryptionKey::new("local-key", vec![7; 32]).unwrap();
+        let options = ClientSideEncryptionOptions::new(Arc::new(kek));
+        let (cipher, data) = ContentCipher::generate(&options).await.unwrap();
+        (cipher, options, data)
+    }
+
+    #[tokio::test]
+    async fn encrypts_stream() -> azure_core::Result<()> {
+        for len in [0usize, 10, 4 * 1024 * 1024, 4 * 1024 * 1024 + 10] {
+            let (cipher, options, data) = cipher().await;
+            let plaintext: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut stream =
+                EncryptStream::new(Box::new(BytesStream::new(plaintext.clone())), cipher);
+
+            let mut ciphertext = Vec::new();
+            stream.read_to_end(&mut ciphertext).await?;
+            assert_eq!(Some(ciphertext.len() as u64), stream.len());
+            assert_eq!(
+                ciphertext.len(),
+                len + len.div_ceil(4 * 1024 * 1024).max(1) * (NONCE_LENGTH + TAG_LENGTH)
+            );
+
+            let cipher = ContentCipher::from_encryption_data(&options, &data).await?;
+            assert_eq!(cipher.decrypt(&ciphertext)?, plaintext);
+
+            // Resetting the stream encrypts the content again.
+            stream.reset().await?;
+            let mut retried = Vec::new();
+            stream.read_to_end(&mut retried).await?;
+            assert_eq!(cipher.decrypt(&retried)?, plaintext);
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
 // Copyright (c) Microsoft Corporation. All rights reserved.
 // Licensed under the MIT License.
 
+pub(crate) mod avro_reader;
+#[cfg(feature = "encryption")]
+pub(crate) mod decrypt_stream;
+#[cfg(feature = "encryption")]
+pub(crate) mod encrypt_stream;
 pub(crate) mod multi_bytes_stream;
 pub(crate) mod partitioned_stream;
+pub(crate) mod query_stream;
@@ -0,0 +1,268 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use async_stream::try_stream;
+use azure_core::{
+    error::{Error, ErrorKind},
+    Result,
+};
+use bytes::Bytes;
+use futures::{Stream, TryStreamExt};
+
+use super::avro_reader::{AvroReader, Value};
+use crate::models::{BlobQueryError, BlobQueryErrorHandler, BlobQueryProgressHandler};
+
+/// A record in a Quick Query response.
+enum QueryRecord {
+    Data(Bytes),
+    Progress { bytes_scanned: u64 },
+    Error(BlobQueryError),
+    End { total_bytes: u64 },
+}
+
+impl QueryRecord {
+    fn from_value(value: Value) -> Result<Self> {
+        let Value::Record { name, .. } = &value else {
+            return Err(malformed("expected a record"));
+        };
+        let long = |field: &str| match value.field(field) {
+            Some(Value::Long(n)) => Ok(*n),
+            _ => Err(malformed(format!("missing '{field}'"))),
+        };
+        let string = |field: &str| match value.field(field) {
+            Some(Value::String(s)) => Ok(s.clone()),
+            _ => Err(malformed(format!("missing '{field}'"))),
+        };
+
+        // Record names are namespaced, for example `com.microsoft.azure.storage.queryBlobContents.resultData`.
+        let kind = name.rsplit('.').next().unwrap_or_default();
+        Ok(match kind {
+            "resultData" => match value.field("data") {
+                Some(Value::Bytes(data)) => QueryRecord::Data(data.clone().into()),
+                _ => return Err(malformed("missing 'data'")),
+            },
+            "progress" => QueryRecord::Progress {
+                bytes_scanned: long("bytesScanned")?.try_into().unwrap_or_default(),
+            },
+            "error" => QueryRecord::Error(BlobQueryError {
+                is_fatal: matches!(value.field("fatal"), Some(Value::Boolean(true))),
+                name: string("name")?,
+                description: string("description")?,
+                position: long("position")?,
+            }),
+            "end" => QueryRecord::End {
+                total_bytes: long("totalBytes")?.try_into().unwrap_or_default(),
+            },
+            _ => return Err(malformed(format!("unexpected record '{name}'"))),
+        })
+    }
+}
+
+fn malformed(message: impl Into<String>) -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        format!("malformed query response: {}", message.into()),
+    )
+}
+
+fn query_error(error: BlobQueryError) -> Error {
+    Error::with_message(ErrorKind::Other, format!("query failed: {error}"))
+}
+
+/// Decodes the Avro-framed body of a Quick Query response into the raw query results.
+///
+/// Progress records are reported to `progress_handler`. Non-fatal errors are reported to
+/// `error_handler` if set; fatal errors, and non-fatal errors without a handler, end the stream with an error.
+pub(crate) fn query_stream<S>(
+    body: S,
+    progress_handler: Option<BlobQueryProgressHandler>,
+    error_handler: Option<BlobQueryErrorHandler>,
+) -> impl Stream<Item = Result<Bytes>> + Send
+where
+    S: Stream<Item = Result<Bytes>> + Send + Unpin,
+{
+    try_stream! {
+        let mut body = body;
+        let mut reader = AvroReader::new();
+        let mut ended = false;
+        while let Some(chunk) = body.try_next().await? {
+            reader.push(&chunk);
+            while let Some(value) = reader.next_value()? {
+                if ended {
+                    Err(malformed("records after the end record"))?;
+                }
+                match QueryRecord::from_value(value)? {
+                    QueryRecord::Data(data) => {
+                        if !data.is_empty() {
+                            yield data;
+                        }
+                    }
+                    QueryRecord::Progress { bytes_scanned } => {
+                        if let Some(handler) = &progress_handler {
+                            handler.call(bytes_scanned);
+                        }
+                    }
+                    QueryRecord::Error(error) => match &error_handler {
+                        Some(handler) if !error.is_fatal => handler.call(&error),
+                        _ => Err(query_error(error))?,
+                    },
+                    QueryRecord::End { total_bytes } => {
+                        if let Some(handler) = &progress_handler {
+                            handler.call(total_bytes);
+                        }
+                        ended = true;
+                    }
+                }
+            }
+        }
+        if !ended || !reader.is_empty() {
+            Err(malformed("the response ended before the end record"))?;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::streams::avro_reader::tests::{bytes, container, long};
+    use futures::{stream, StreamExt};
+    use std::sync::{Arc, Mutex};
+
+    pub(crate) const QUERY_SCHEMA: &str = r#"[
+        {"type": "record", "name": "com.microsoft.azure.storage.queryBlobContents.resultData",
+         "fields": [{"name": "data", "type": "bytes"}]},
+        {"type": "record", "name": "com.microsoft.azure.storage.queryBlobContents.error",
+         "fields": [{"name": "fatal", "type": "boolean"}, {"name": "name", "type": "string"},
+                    {"name": "description", "type": "string"}, {"name": "position", "type": "long"}]},
+        {"type": "record", "name": "com.microsoft.azure.storage.queryBlobContents.progress",
+         "fields": [{"name": "bytesScanned", "type": "long"}, {"name": "totalBytes", "type": "long"}]},
+        {"type": "record", "name": "com.microsoft.azure.storage.queryBlobContents.end",
+         "fields": [{"name": "totalBytes", "type": "long"}]}
+    ]"#;
+
+    pub(crate) fn data_record(data: &[u8]) -> Vec<u8> {
+        let mut out = long(0);
+        out.extend(bytes(data));
+        out
+    }
+
+    pub(crate) fn error_record(fatal: bool, name: &str, position: i64) -> Vec<u8> {
+        let mut out = long(1);
+        out.push(fatal as u8);
+        out.extend(bytes(name.as_bytes()));
+        out.extend(bytes(b"description"));
+        out.extend(long(position));
+        out
+    }
+
+    pub(crate) fn progress_record(scanned: i64, total: i64) -> Vec<u8> {
+        let mut out = long(2);
+        out.extend(long(scanned));
+        out.extend(long(total));
+        out
+    }
+
+    pub(crate) fn end_record(total: i64) -> Vec<u8> {
+        let mut out = long(3);
+        out.extend(long(total));
+        out
+    }
+
+    pub(crate) fn query_response(records: &[Vec<u8>]) -> Vec<u8> {
+        let blocks: Vec<_> = records.iter().map(|r| (1, r.clone())).collect();
+        container(QUERY_SCHEMA, &blocks)
+    }
+
+    fn chunked(data: Vec<u8>, size: usize) -> impl Stream<Item = Result<Bytes>> + Send + Unpin {
+        let chunks: Vec<_> = data
+            .chunks(size)
+            .map(|c| Ok(Bytes::copy_from_slice(c)))
+            .collect();
+        stream::iter(chunks)
+    }
+
+    async fn collect(stream: impl Stream<Item = Result<Bytes>>) -> Result<Vec<u8>> {
+        let mut stream = Box::pin(stream);
+        let mut out = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            out.extend_from_slice(&chunk?);
+        }
+        Ok(out)
+    }
+
+    #[tokio::test]
+    async fn yields_data_and_reports_progress() {
+        let body = query_response(&[
+            data_record(b"a,b\n"),
+            progress_record(10, 20),
+            data_record(b"c,d\n"),
+            progress_record(20, 20),
+            end_record(20),
+        ]);
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let progress = progress.clone();
+            BlobQueryProgressHandler::new(move |n| progress.lock().unwrap().push(n))
+        };
+
+        let data = collect(query_stream(chunked(body, 5), Some(handler), None))
+            .await
+            .unwrap();
+        assert_eq!(data, b"a,b\nc,d\n");
+        assert_eq!(*progress.lock().unwrap(), vec![10, 20, 20]);
+    }
+
+    #[tokio::test]
+    async fn non_fatal_errors_go_to_handler() {
+        let body = query_response(&[
+            error_record(false, "InvalidColumnOrdinal", 3),
+            data_record(b"x"),
+            end_record(1),
+        ]);
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let errors = errors.clone();
+            BlobQueryErrorHandler::new(move |e| errors.lock().unwrap().push(e.clone()))
+        };
+
+        let data = collect(query_stream(chunked(body, 64), None, Some(handler)))
+            .await
+            .unwrap();
+        assert_eq!(data, b"x");
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].name, "InvalidColumnOrdinal");
+        assert_eq!(errors[0].position, 3);
+    }
+
+    #[tokio::test]
+    async fn fatal_errors_fail_the_stream() {
+        let body = query_response(&[error_record(true, "ParseError", 0), end_record(0)]);
+        let handler = BlobQueryErrorHandler::new(|_| panic!("fatal errors are not handled"));
+
+        let err = collect(query_stream(chunked(body, 64), None, Some(handler)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ParseError"));
+    }
+
+    #[tokio::test]
+    async fn errors_without_handler_fail_the_stream() {
+        let body = query_response(&[
+            error_record(false, "InvalidColumnOrdinal", 3),
+            end_record(0),
+        ]);
+        assert!(collect(query_stream(chunked(body, 64), None, None))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn truncated_response_fails() {
+        let mut body = query_response(&[data_record(b"x"), end_record(1)]);
+        body.truncate(body.len() - 4);
+        assert!(collect(query_stream(chunked(body, 64), None, None))
+            .await
+            .is_err());
+    }
+}
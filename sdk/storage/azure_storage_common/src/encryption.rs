@@ -0,0 +1,736 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Client-side (envelope) encryption shared by the Azure Storage client libraries.
+//!
+//! Content is encrypted with a random, single-use AES-256 content encryption key (CEK) using
+//! AES-GCM. The content is split into regions of 4 MiB that are encrypted independently, so that
+//! ranges of encrypted blobs can be downloaded and decrypted without reading the whole blob.
+//! Each encrypted region is stored as `nonce || ciphertext || tag`.
+//!
+//! The CEK is wrapped by a [`KeyEncryptionKey`] you provide, such as a Key Vault key or a
+//! [`LocalKeyEncryptionKey`], and stored with the content as [`EncryptionData`]. This is the
+//! same "2.0" format used by the other Azure Storage SDKs, so data can be exchanged with them.
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use async_trait::async_trait;
+use azure_core::{
+    base64,
+    error::{Error, ErrorKind},
+    fmt::SafeDebug,
+    Result,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// The name of the metadata entry, or message property, containing the [`EncryptionData`].
+pub const ENCRYPTION_DATA_KEY: &str = "encryptiondata";
+
+/// The encryption protocol version written by this library.
+pub const ENCRYPTION_PROTOCOL_V2: &str = "2.0";
+
+/// The number of plaintext bytes in each encrypted region.
+pub const REGION_DATA_LENGTH: usize = 4 * 1024 * 1024;
+
+/// The length of the nonce stored before each encrypted region.
+pub const NONCE_LENGTH: usize = 12;
+
+/// The length of the authentication tag stored after each encrypted region.
+pub const TAG_LENGTH: usize = 16;
+
+const CONTENT_ENCRYPTION_ALGORITHM: &str = "AES_GCM_256";
+const CONTENT_KEY_LENGTH: usize = 32;
+const DEFAULT_KEY_WRAP_ALGORITHM: &str = "A256KW";
+
+/// A key used to wrap and unwrap content encryption keys.
+///
+/// Implement this trait to wrap keys with a key management service such as Azure Key Vault.
+/// Use [`LocalKeyEncryptionKey`] to wrap keys with a key held in memory.
+#[async_trait]
+pub trait KeyEncryptionKey: Send + Sync + fmt::Debug {
+    /// Gets the identifier of the key.
+    ///
+    /// The identifier is stored with the encrypted content and passed to a
+    /// [`KeyEncryptionKeyResolver`] to find the key again when decrypting.
+    fn key_id(&self) -> &str;
+
+    /// Gets the algorithm used to wrap keys when [`ClientSideEncryptionOptions::key_wrap_algorithm`] is not set.
+    ///
+    /// Defaults to `A256KW`. Override this for keys that don't support it, for example RSA keys.
+    fn default_key_wrap_algorithm(&self) -> &str {
+        DEFAULT_KEY_WRAP_ALGORITHM
+    }
+
+    /// Wraps (encrypts) a content encryption key.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The key wrap algorithm, for example `A256KW` or `RSA-OAEP`.
+    /// * `key` - The key to wrap.
+    async fn wrap_key(&self, algorithm: &str, key: &[u8]) -> Result<Vec<u8>>;
+
+    /// Unwraps (decrypts) a content encryption key.
+    ///
+    /// # Arguments
+    ///
+    /// * `algorithm` - The key wrap algorithm used to wrap the key.
+    /// * `wrapped_key` - The wrapped key.
+    async fn unwrap_key(&self, algorithm: &str, wrapped_key: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Resolves a [`KeyEncryptionKey`] from the key identifier stored with encrypted content.
+#[async_trait]
+pub trait KeyEncryptionKeyResolver: Send + Sync + fmt::Debug {
+    /// Gets the key with the given identifier.
+    async fn resolve(&self, key_id: &str) -> Result<Arc<dyn KeyEncryptionKey>>;
+}
+
+/// A [`KeyEncryptionKey`] that wraps keys with an AES key held in memory using AES key wrap ([RFC 3394](https://www.rfc-editor.org/rfc/rfc3394)).
+#[derive(Clone)]
+pub struct LocalKeyEncryptionKey {
+    key_id: String,
+    key: Vec<u8>,
+}
+
+impl LocalKeyEncryptionKey {
+    /// Creates a new `LocalKeyEncryptionKey`.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_id` - The identifier of the key, stored with the encrypted content.
+    /// * `key` - A 128-, 192-, or 256-bit AES key.
+    pub fn new(key_id: impl Into<String>, key: Vec<u8>) -> Result<Self> {
+        if !matches!(key.len(), 16 | 24 | 32) {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                "key encryption key must be 128, 192, or 256 bits",
+            ));
+        }
+        Ok(Self {
+            key_id: key_id.into(),
+            key,
+        })
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self.key.len() {
+            16 => "A128KW",
+            24 => "A192KW",
+            _ => "A256KW",
+        }
+    }
+
+    fn check_algorithm(&self, algorithm: &str) -> Result<()> {
+        if algorithm != self.algorithm() {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                format!(
+                    "key wrap algorithm '{algorithm}' is not supported by a {}-bit key; use '{}'",
+                    self.key.len() * 8,
+                    self.algorithm()
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LocalKeyEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalKeyEncryptionKey")
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl KeyEncryptionKey for LocalKeyEncryptionKey {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn default_key_wrap_algorithm(&self) -> &str {
+        self.algorithm()
+    }
+
+    async fn wrap_key(&self, algorithm: &str, key: &[u8]) -> Result<Vec<u8>> {
+        self.check_algorithm(algorithm)?;
+        let wrapped = match self.key.len() {
+            16 => aes_kw::KekAes128::new(self.key.as_slice().into()).wrap_vec(key),
+            24 => aes_kw::KekAes192::new(self.key.as_slice().into()).wrap_vec(key),
+            _ => aes_kw::KekAes256::new(self.key.as_slice().into()).wrap_vec(key),
+        };
+        wrapped
+            .map_err(|e| Error::with_message(ErrorKind::Other, format!("failed to wrap key: {e}")))
+    }
+
+    async fn unwrap_key(&self, algorithm: &str, wrapped_key: &[u8]) -> Result<Vec<u8>> {
+        self.check_algorithm(algorithm)?;
+        let unwrapped = match self.key.len() {
+            16 => aes_kw::KekAes128::new(self.key.as_slice().into()).unwrap_vec(wrapped_key),
+            24 => aes_kw::KekAes192::new(self.key.as_slice().into()).unwrap_vec(wrapped_key),
+            _ => aes_kw::KekAes256::new(self.key.as_slice().into()).unwrap_vec(wrapped_key),
+        };
+        unwrapped.map_err(|e| {
+            Error::with_message(ErrorKind::Other, format!("failed to unwrap key: {e}"))
+        })
+    }
+}
+
+#[async_trait]
+impl KeyEncryptionKeyResolver for LocalKeyEncryptionKey {
+    async fn resolve(&self, key_id: &str) -> Result<Arc<dyn KeyEncryptionKey>> {
+        if key_id != self.key_id {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                format!("key encryption key '{key_id}' not found"),
+            ));
+        }
+        Ok(Arc::new(self.clone()))
+    }
+}
+
+/// Options for client-side encryption.
+#[derive(Clone, Default, SafeDebug)]
+pub struct ClientSideEncryptionOptions {
+    /// The key used to wrap content encryption keys when encrypting.
+    ///
+    /// Also used to unwrap content encryption keys when decrypting if no [`key_resolver`](Self::key_resolver)
+    /// is set and its [`key_id`](KeyEncryptionKey::key_id) matches the key that encrypted the content.
+    pub key_encryption_key: Option<Arc<dyn KeyEncryptionKey>>,
+
+    /// Resolves the key used to unwrap content encryption keys when decrypting.
+    pub key_resolver: Option<Arc<dyn KeyEncryptionKeyResolver>>,
+
+    /// The algorithm used to wrap content encryption keys.
+    ///
+    /// Defaults to the [`default_key_wrap_algorithm`](KeyEncryptionKey::default_key_wrap_algorithm) of the key encryption key.
+    pub key_wrap_algorithm: Option<String>,
+}
+
+impl ClientSideEncryptionOptions {
+    /// Creates options that encrypt and decrypt using the given key.
+    pub fn new(key_encryption_key: Arc<dyn KeyEncryptionKey>) -> Self {
+        Self {
+            key_encryption_key: Some(key_encryption_key),
+            ..Default::default()
+        }
+    }
+
+    async fn resolve(&self, key_id: &str) -> Result<Arc<dyn KeyEncryptionKey>> {
+        if let Some(resolver) = &self.key_resolver {
+            return resolver.resolve(key_id).await;
+        }
+        match &self.key_encryption_key {
+            Some(kek) if kek.key_id() == key_id => Ok(kek.clone()),
+            _ => Err(Error::with_message(
+                ErrorKind::Other,
+                format!("no key encryption key or key resolver is configured for key '{key_id}'"),
+            )),
+        }
+    }
+}
+
+/// The metadata stored with client-side encrypted content.
+#[derive(Clone, Deserialize, SafeDebug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EncryptionData {
+    /// The wrapped content encryption key.
+    pub wrapped_content_key: WrappedContentKey,
+
+    /// The encryption protocol and algorithm.
+    pub encryption_agent: EncryptionAgent,
+
+    /// The layout of encrypted regions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_region_info: Option<EncryptedRegionInfo>,
+
+    /// Information about the library that wrapped the key.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub key_wrapping_metadata: HashMap<String, String>,
+}
+
+/// A wrapped content encryption key.
+#[derive(Clone, Deserialize, SafeDebug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WrappedContentKey {
+    /// The identifier of the key encryption key.
+    pub key_id: String,
+
+    /// The wrapped content encryption key.
+    #[serde(with = "base64")]
+    pub encrypted_key: Vec<u8>,
+
+    /// The key wrap algorithm.
+    pub algorithm: String,
+}
+
+/// The encryption protocol and algorithm used to encrypt content.
+#[derive(Clone, Deserialize, SafeDebug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EncryptionAgent {
+    /// The encryption protocol version, for example `2.0`.
+    pub protocol: String,
+
+    /// The content encryption algorithm, for example `AES_GCM_256`.
+    pub encryption_algorithm: String,
+}
+
+/// The layout of encrypted regions.
+#[derive(Clone, Deserialize, SafeDebug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EncryptedRegionInfo {
+    /// The number of plaintext bytes in each region.
+    pub data_length: usize,
+
+    /// The length of the nonce stored before each region.
+    pub nonce_length: usize,
+}
+
+impl EncryptionData {
+    /// Parses encryption data from its JSON representation.
+    ///
+    /// Returns an error if the encrypted regions don't have the layout of protocol version 2.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let data: Self = serde_json::from_str(json).map_err(|e| {
+            Error::with_error(ErrorKind::DataConversion, e, "invalid encryption data")
+        })?;
+        data.check_region_info()?;
+        Ok(data)
+    }
+
+    /// Serializes encryption data to its JSON representation.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| {
+            Error::with_error(
+                ErrorKind::DataConversion,
+                e,
+                "failed to serialize encryption data",
+            )
+        })
+    }
+
+    /// The number of plaintext bytes in each encrypted region.
+    pub fn region_data_length(&self) -> usize {
+        self.encrypted_region_info
+            .as_ref()
+            .map(|info| info.data_length)
+            .unwrap_or(REGION_DATA_LENGTH)
+    }
+
+    /// The number of bytes in each encrypted region, including its nonce and tag.
+    pub fn encrypted_region_length(&self) -> usize {
+        self.region_data_length()
+            .saturating_add(NONCE_LENGTH + TAG_LENGTH)
+    }
+
+    /// Checks that the encrypted regions have the layout of protocol version 2.
+    ///
+    /// The layout is read from metadata that anyone who can write the content can change, so
+    /// other region sizes are rejected rather than trusted for length and range calculations.
+    fn check_region_info(&self) -> Result<()> {
+        match &self.encrypted_region_info {
+            Some(info)
+                if info.nonce_length != NONCE_LENGTH || info.data_length != REGION_DATA_LENGTH =>
+            {
+                Err(Error::with_message(
+                    ErrorKind::DataConversion,
+                    format!(
+                        "unsupported encrypted region layout: {} byte regions with {} byte nonces",
+                        info.data_length, info.nonce_length
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Gets the plaintext length of content with the given encrypted length.
+    ///
+    /// Returns an error if `encrypted_length` isn't a valid length of encrypted content, for example
+    /// because the content was truncated.
+    pub fn plaintext_length(&self, encrypted_length: u64) -> Result<u64> {
+        let region = self.encrypted_region_length() as u64;
+        let overhead = (NONCE_LENGTH + TAG_LENGTH) as u64;
+        let last_region = match encrypted_length % region {
+            0 if encrypted_length > 0 => region,
+            remainder => remainder,
+        };
+        last_region
+            .checked_sub(overhead)
+            .and_then(|last| {
+                let regions = encrypted_length.div_ceil(region).max(1);
+                (regions - 1)
+                    .checked_mul(region - overhead)?
+                    .checked_add(last)
+            })
+            .ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::DataConversion,
+                    format!("{encrypted_length} bytes is not a valid length of encrypted content"),
+                )
+            })
+    }
+}
+
+/// The protocol version padded to 8 bytes, which is prepended to the content encryption key before it is wrapped.
+fn wrapped_key_prefix(protocol: &str) -> [u8; 8] {
+    let mut prefix = [0u8; 8];
+    let len = protocol.len().min(prefix.len());
+    prefix[..len].copy_from_slice(&protocol.as_bytes()[..len]);
+    prefix
+}
+
+fn decryption_failed() -> Error {
+    Error::with_message(
+        ErrorKind::DataConversion,
+        "failed to decrypt content; it may have been modified or encrypted with a different key",
+    )
+}
+
+/// Encrypts and decrypts content with a content encryption key.
+///
+/// Internal use only -- used by the Azure Storage client libraries.
+#[doc(hidden)]
+pub struct ContentCipher {
+    cipher: Aes256Gcm,
+    region_data_length: usize,
+}
+
+impl fmt::Debug for ContentCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentCipher").finish_non_exhaustive()
+    }
+}
+
+impl ContentCipher {
+    /// Creates a cipher with a new random content encryption key, wrapped with the configured key encryption key.
+    pub async fn generate(options: &ClientSideEncryptionOptions) -> Result<(Self, EncryptionData)> {
+        let kek = options.key_encryption_key.as_ref().ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::Other,
+                "a key encryption key is required to encrypt content",
+            )
+        })?;
+        let algorithm = options
+            .key_wrap_algorithm
+            .as_deref()
+            .unwrap_or_else(|| kek.default_key_wrap_algorithm());
+
+        let cek: [u8; CONTENT_KEY_LENGTH] = rand::random();
+        let mut to_wrap = wrapped_key_prefix(ENCRYPTION_PROTOCOL_V2).to_vec();
+        to_wrap.extend_from_slice(&cek);
+        let encrypted_key = kek.wrap_key(algorithm, &to_wrap).await?;
+
+        let data = EncryptionData {
+            wrapped_content_key: WrappedContentKey {
+                key_id: kek.key_id().to_string(),
+                encrypted_key,
+                algorithm: algorithm.to_string(),
+            },
+            encryption_agent: EncryptionAgent {
+                protocol: ENCRYPTION_PROTOCOL_V2.to_string(),
+                encryption_algorithm: CONTENT_ENCRYPTION_ALGORITHM.to_string(),
+            },
+            encrypted_region_info: Some(EncryptedRegionInfo {
+                data_length: REGION_DATA_LENGTH,
+                nonce_length: NONCE_LENGTH,
+            }),
+            key_wrapping_metadata: HashMap::from([(
+                "EncryptionLibrary".to_string(),
+                concat!("Rust ", env!("CARGO_PKG_VERSION")).to_string(),
+            )]),
+        };
+        let cipher = Self::new(&cek, REGION_DATA_LENGTH)?;
+        Ok((cipher, data))
+    }
+
+    /// Creates a cipher by unwrapping the content encryption key in `data`.
+    pub async fn from_encryption_data(
+        options: &ClientSideEncryptionOptions,
+        data: &EncryptionData,
+    ) -> Result<Self> {
+        let protocol = data.encryption_agent.protocol.as_str();
+        if !protocol.starts_with("2.") {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                format!("client-side encryption protocol '{protocol}' is not supported"),
+            ));
+        }
+        if data.encryption_agent.encryption_algorithm != CONTENT_ENCRYPTION_ALGORITHM {
+            return Err(Error::with_message(
+                ErrorKind::Other,
+                format!(
+                    "content encryption algorithm '{}' is not supported",
+                    data.encryption_agent.encryption_algorithm
+                ),
+            ));
+        }
+        data.check_region_info()?;
+
+        let wrapped = &data.wrapped_content_key;
+        let kek = options.resolve(&wrapped.key_id).await?;
+        let unwrapped = kek
+            .unwrap_key(&wrapped.algorithm, &wrapped.encrypted_key)
+            .await?;
+        let prefix = wrapped_key_prefix(protocol);
+        let cek = unwrapped
+            .strip_prefix(prefix.as_slice())
+            .filter(|cek| cek.len() == CONTENT_KEY_LENGTH)
+            .ok_or_else(|| {
+                Error::with_message(
+                    ErrorKind::DataConversion,
+                    "unwrapped content encryption key is malformed",
+                )
+            })?;
+        Self::new(cek, data.region_data_length())
+    }
+
+    fn new(cek: &[u8], region_data_length: usize) -> Result<Self> {
+        let cipher = Aes256Gcm::new_from_slice(cek)
+            .map_err(|_| Error::with_message(ErrorKind::Other, "invalid content encryption key"))?;
+        Ok(Self {
+            cipher,
+            region_data_length,
+        })
+    }
+
+    /// The number of bytes in each encrypted region, including its nonce and tag.
+    pub fn encrypted_region_length(&self) -> usize {
+        NONCE_LENGTH + self.region_data_length + TAG_LENGTH
+    }
+
+    /// The number of plaintext bytes in each encrypted region.
+    pub fn region_data_length(&self) -> usize {
+        self.region_data_length
+    }
+
+    /// Gets the encrypted length of content with the given plaintext length.
+    pub fn encrypted_length(&self, plaintext_length: u64) -> u64 {
+        let regions = plaintext_length
+            .div_ceil(self.region_data_length as u64)
+            .max(1);
+        plaintext_length + regions * (NONCE_LENGTH + TAG_LENGTH) as u64
+    }
+
+    /// Encrypts content, region by region.
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(self.encrypted_length(plaintext.len() as u64) as usize);
+        let mut chunks: Vec<&[u8]> = plaintext.chunks(self.region_data_length).collect();
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        for chunk in chunks {
+            out.extend(self.encrypt_region(chunk)?);
+        }
+        Ok(out)
+    }
+
+    /// Encrypts a single region of at most [`region_data_length`](Self::region_data_length) bytes.
+    pub fn encrypt_region(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        debug_assert!(plaintext.len() <= self.region_data_length);
+        let nonce: [u8; NONCE_LENGTH] = rand::random();
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| Error::with_message(ErrorKind::Other, "failed to encrypt content"))?;
+        let mut region = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        region.extend_from_slice(&nonce);
+        region.extend_from_slice(&ciphertext);
+        Ok(region)
+    }
+
+    /// Decrypts a single encrypted region.
+    pub fn decrypt_region(&self, region: &[u8]) -> Result<Vec<u8>> {
+        if region.len() < NONCE_LENGTH + TAG_LENGTH || region.len() > self.encrypted_region_length()
+        {
+            return Err(decryption_failed());
+        }
+        let (nonce, ciphertext) = region.split_at(NONCE_LENGTH);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| decryption_failed())
+    }
+
+    /// Decrypts content consisting of one or more whole encrypted regions.
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(ciphertext.len());
+        for region in ciphertext.chunks(self.encrypted_region_length()) {
+            out.extend(self.decrypt_region(region)?);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> ClientSideEncryptionOptions {
+        let kek = LocalKeyEncryptionKey::new("local-key", vec![3; 32]).unwrap();
+        ClientSideEncryptionOptions::new(Arc::new(kek))
+    }
+
+    #[tokio::test]
+    async fn round_trips_multiple_regions() {
+        let options = options();
+        let (cipher, data) = ContentCipher::generate(&options).await.unwrap();
+        let plaintext: Vec<u8> = (0..REGION_DATA_LENGTH + 10).map(|i| i as u8).collect();
+
+        let ciphertext = cipher.encrypt(&plaintext).unwrap();
+        assert_eq!(
+            ciphertext.len(),
+            plaintext.len() + 2 * (NONCE_LENGTH + TAG_LENGTH)
+        );
+        assert_eq!(
+            data.plaintext_length(ciphertext.len() as u64).unwrap(),
+            plaintext.len() as u64
+        );
+        assert_eq!(
+            cipher.encrypted_length(plaintext.len() as u64),
+            ciphertext.len() as u64
+        );
+
+        let data = EncryptionData::from_json(&data.to_json().unwrap()).unwrap();
+        let cipher = ContentCipher::from_encryption_data(&options, &data)
+            .await
+            .unwrap();
+        assert_eq!(cipher.decrypt(&ciphertext).unwrap(), plaintext);
+    }
+
+    #[tokio::test]
+    async fn encrypts_empty_content() {
+        let (cipher, _) = ContentCipher::generate(&options()).await.unwrap();
+        let ciphertext = cipher.encrypt(&[]).unwrap();
+        assert_eq!(ciphertext.len(), NONCE_LENGTH + TAG_LENGTH);
+        assert!(cipher.decrypt(&ciphertext).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn detects_tampering() {
+        let (cipher, _) = ContentCipher::generate(&options()).await.unwrap();
+        let mut ciphertext = cipher.encrypt(b"hello").unwrap();
+        ciphertext[NONCE_LENGTH] ^= 1;
+        assert!(cipher.decrypt(&ciphertext).is_err());
+    }
+
+    #[tokio::test]
+    async fn serializes_v2_encryption_data() {
+        let (_, data) = ContentCipher::generate(&options()).await.unwrap();
+        let json: serde_json::Value = serde_json::from_str(&data.to_json().unwrap()).unwrap();
+        assert_eq!(json["WrappedContentKey"]["KeyId"], "local-key");
+        assert_eq!(json["WrappedContentKey"]["Algorithm"], "A256KW");
+        assert_eq!(json["EncryptionAgent"]["Protocol"], "2.0");
+        assert_eq!(
+            json["EncryptionAgent"]["EncryptionAlgorithm"],
+            "AES_GCM_256"
+        );
+        assert_eq!(
+            json["EncryptedRegionInfo"]["DataLength"],
+            REGION_DATA_LENGTH
+        );
+        assert_eq!(json["EncryptedRegionInfo"]["NonceLength"], NONCE_LENGTH);
+    }
+
+    #[tokio::test]
+    async fn rejects_other_region_layouts() {
+        let (_, data) = ContentCipher::generate(&options()).await.unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&data.to_json().unwrap()).unwrap();
+        assert!(EncryptionData::from_json(&json.to_string()).is_ok());
+
+        for data_length in [0, 1024, usize::MAX] {
+            json["EncryptedRegionInfo"]["DataLength"] = data_length.into();
+            assert!(EncryptionData::from_json(&json.to_string()).is_err());
+        }
+
+        let mut data = data;
+        data.encrypted_region_info.as_mut().unwrap().data_length = usize::MAX;
+        assert!(ContentCipher::from_encryption_data(&options(), &data)
+            .await
+            .is_err());
+        assert_eq!(data.encrypted_region_length(), usize::MAX);
+    }
+
+    #[tokio::test]
+    async fn wrapped_key_includes_protocol_prefix() {
+        let kek = LocalKeyEncryptionKey::new("local-key", vec![3; 32]).unwrap();
+        let (_, data) = ContentCipher::generate(&options()).await.unwrap();
+        let unwrapped = kek
+            .unwrap_key("A256KW", &data.wrapped_content_key.encrypted_key)
+            .await
+            .unwrap();
+        assert_eq!(&unwrapped[..8], b"2.0\0\0\0\0\0");
+        assert_eq!(unwrapped.len(), 8 + CONTENT_KEY_LENGTH);
+    }
+
+    #[tokio::test]
+    async fn decrypting_requires_matching_key() {
+        let (_, data) = ContentCipher::generate(&options()).await.unwrap();
+        let other = LocalKeyEncryptionKey::new("other-key", vec![3; 32]).unwrap();
+        let err = ContentCipher::from_encryption_data(
+            &ClientSideEncryptionOptions::new(Arc::new(other.clone())),
+            &data,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("local-key"));
+
+        let resolver = LocalKeyEncryptionKey::new("local-key", vec![3; 32]).unwrap();
+        let options = ClientSideEncryptionOptions {
+            key_resolver: Some(Arc::new(resolver)),
+            ..Default::default()
+        };
+        assert!(ContentCipher::from_encryption_data(&options, &data)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn plaintext_length_rejects_truncated_content() {
+        let (cipher, data) = ContentCipher::generate(&options()).await.unwrap();
+        let region = (NONCE_LENGTH + REGION_DATA_LENGTH + TAG_LENGTH) as u64;
+        assert_eq!(
+            data.plaintext_length(0).unwrap_err().kind(),
+            &ErrorKind::DataConversion
+        );
+        assert!(data.plaintext_length(10).is_err());
+        assert!(data.plaintext_length(region + 10).is_err());
+        assert_eq!(
+            data.plaintext_length(cipher.encrypted_length(0)).unwrap(),
+            0
+        );
+        assert_eq!(
+            data.plaintext_length(region).unwrap(),
+            REGION_DATA_LENGTH as u64
+        );
+        assert_eq!(
+            data.plaintext_length(region + (NONCE_LENGTH + TAG_LENGTH) as u64)
+                .unwrap(),
+            REGION_DATA_LENGTH as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn wraps_with_local_key_algorithm() {
+        for (len, algorithm) in [(16, "A128KW"), (24, "A192KW"), (32, "A256KW")] {
+            let kek = LocalKeyEncryptionKey::new("local-key", vec![3; len]).unwrap();
+            let options = ClientSideEncryptionOptions::new(Arc::new(kek));
+            let (cipher, data) = ContentCipher::generate(&options).await.unwrap();
+            assert_eq!(data.wrapped_content_key.algorithm, algorithm);
+
+            let ciphertext = cipher.encrypt(b"hello").unwrap();
+            let cipher = ContentCipher::from_encryption_data(&options, &data)
+                .await
+                .unwrap();
+            assert_eq!(cipher.decrypt(&ciphertext).unwrap(), b"hello");
+        }
+    }
+
+    #[test]
+    fn local_key_rejects_invalid_length() {
+        assert!(LocalKeyEncryptionKey::new("k", vec![0; 20]).is_err());
+    }
+}
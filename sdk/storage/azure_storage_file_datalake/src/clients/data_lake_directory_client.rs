@@ -0,0 +1,438 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, path_operations::PathOperations};
+use crate::{
+    models::{
+        AccessControlChangeResult, DataLakeDirectoryClientSetAccessControlRecursiveOptions,
+        DataLakePathCreateOptions, DataLakePathDeleteOptions, DataLakePathGetAccessControlOptions,
+        DataLakePathGetPropertiesOptions, DataLakePathRenameOptions,
+        DataLakePathSetAccessControlOptions, PathAccessControl, PathAccessControlItem,
+        PathProperties, PathResourceType, PathSetAccessControlRecursiveMode,
+    },
+    DataLakeFileClient,
+};
+use azure_core::{
+    http::{pager::PageIterator, NoFormat, Pipeline, Response, StatusCode, Url},
+    tracing, Result,
+};
+
+/// A client for a directory in a Data Lake Storage Gen2 file system.
+///
+/// Obtain a `DataLakeDirectoryClient` from [`DataLakeFileSystemClient::directory_client()`](crate::DataLakeFileSystemClient::directory_client).
+/// Directory operations such as rename and delete are atomic in accounts with a hierarchical namespace.
+#[tracing::client]
+pub struct DataLakeDirectoryClient {
+    pub(crate) endpoint: Url,
+    pub(crate) file_system_endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+impl DataLakeDirectoryClient {
+    fn operations(&self) -> PathOperations<'_> {
+        PathOperations {
+            endpoint: &self.endpoint,
+            pipeline: &self.pipeline,
+            version: &self.version,
+        }
+    }
+
+    /// Gets the URL of the directory.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Returns a new instance of [`DataLakeDirectoryClient`] for a subdirectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `subdirectory_path` - The `/`-separated path of the subdirectory relative to this directory.
+    pub fn subdirectory_client(&self, subdirectory_path: &str) -> DataLakeDirectoryClient {
+        DataLakeDirectoryClient {
+            endpoint: append_path(&self.endpoint, subdirectory_path),
+            file_system_endpoint: self.file_system_endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Returns a new instance of [`DataLakeFileClient`] for a file in this directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The `/`-separated path of the file relative to this directory.
+    pub fn file_client(&self, file_path: &str) -> DataLakeFileClient {
+        DataLakeFileClient {
+            endpoint: append_path(&self.endpoint, file_path),
+            file_system_endpoint: self.file_system_endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Creates the directory. Missing parent directories are created implicitly.
+    ///
+    /// By default an existing directory is replaced; use [`DataLakePathCreateOptions::if_not_exists()`] to fail instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.create")]
+    pub async fn create(
+        &self,
+        options: Option<DataLakePathCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .create(PathResourceType::Directory, options)
+            .await
+    }
+
+    /// Deletes the directory and everything beneath it.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<DataLakePathDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations().delete(Some(true), options).await
+    }
+
+    /// Atomically renames the directory, moving everything beneath it.
+    ///
+    /// Returns a client for the renamed directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_path` - The `/`-separated destination path relative to the file system root.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.rename")]
+    pub async fn rename(
+        &self,
+        new_path: &str,
+        options: Option<DataLakePathRenameOptions<'_>>,
+    ) -> Result<DataLakeDirectoryClient> {
+        let destination = append_path(&self.file_system_endpoint, new_path);
+        self.operations().rename(&destination, options).await?;
+        Ok(DataLakeDirectoryClient {
+            endpoint: destination,
+            file_system_endpoint: self.file_system_endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        })
+    }
+
+    /// Gets the system properties, user-defined properties and access control of the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<DataLakePathGetPropertiesOptions<'_>>,
+    ) -> Result<PathProperties> {
+        self.operations().get_properties(options).await
+    }
+
+    /// Checks if the directory exists.
+    ///
+    /// Returns `true` if the directory exists, `false` if the directory does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the owner, group, permissions and access control list of the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.getAccessControl")]
+    pub async fn get_access_control(
+        &self,
+        options: Option<DataLakePathGetAccessControlOptions<'_>>,
+    ) -> Result<PathAccessControl> {
+        self.operations().get_access_control(options).await
+    }
+
+    /// Replaces the access control list of the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_control_list` - The complete access control list, including the owning user, owning group and other entries.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.setAccessControlList")]
+    pub async fn set_access_control_list(
+        &self,
+        access_control_list: &[PathAccessControlItem],
+        options: Option<DataLakePathSetAccessControlOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .set_access_control(
+                Some(PathAccessControlItem::format_list(access_control_list)),
+                None,
+                options,
+            )
+            .await
+    }
+
+    /// Sets the POSIX permissions of the directory, and optionally its owner and group.
+    ///
+    /// # Arguments
+    ///
+    /// * `permissions` - The permissions in symbolic (`rwxr-x---`) or octal (`0750`) notation.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.setPermissions")]
+    pub async fn set_permissions(
+        &self,
+        permissions: &str,
+        options: Option<DataLakePathSetAccessControlOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .set_access_control(None, Some(permissions.to_string()), options)
+            .await
+    }
+
+    /// Replaces the access control list of the directory and every path beneath it.
+    ///
+    /// The service processes paths in batches; each page yields the [`AccessControlChangeResult`] for one batch.
+    /// Use [`AccessControlChangeCounters`](crate::models::AccessControlChangeCounters) to aggregate the results.
+    ///
+    /// Unless [`DataLakeDirectoryClientSetAccessControlRecursiveOptions::continue_on_failure`] is set, paging stops at the
+    /// first batch containing failures. The `x-ms-continuation` header of that page's response can be passed as
+    /// `method_options.continuation` to resume after fixing the failures. An interrupted operation can be resumed
+    /// the same way using [`PageIterator::continuation()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `access_control_list` - The complete access control list to set on each path.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.setAccessControlRecursive")]
+    pub fn set_access_control_recursive(
+        &self,
+        access_control_list: &[PathAccessControlItem],
+        options: Option<DataLakeDirectoryClientSetAccessControlRecursiveOptions<'_>>,
+    ) -> Result<PageIterator<Response<AccessControlChangeResult>>> {
+        self.operations().set_access_control_recursive(
+            PathSetAccessControlRecursiveMode::Set,
+            PathAccessControlItem::format_list(access_control_list),
+            options,
+        )
+    }
+
+    /// Adds or updates entries in the access control list of the directory and every path beneath it.
+    ///
+    /// Paging behaves as described in [`DataLakeDirectoryClient::set_access_control_recursive()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `access_control_list` - The entries to add or update on each path.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.updateAccessControlRecursive")]
+    pub fn update_access_control_recursive(
+        &self,
+        access_control_list: &[PathAccessControlItem],
+        options: Option<DataLakeDirectoryClientSetAccessControlRecursiveOptions<'_>>,
+    ) -> Result<PageIterator<Response<AccessControlChangeResult>>> {
+        self.operations().set_access_control_recursive(
+            PathSetAccessControlRecursiveMode::Modify,
+            PathAccessControlItem::format_list(access_control_list),
+            options,
+        )
+    }
+
+    /// Removes entries from the access control list of the directory and every path beneath it.
+    ///
+    /// Permissions of the given entries are ignored. Paging behaves as described in
+    /// [`DataLakeDirectoryClient::set_access_control_recursive()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `access_control_list` - The entries to remove from each path.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.Directory.removeAccessControlRecursive")]
+    pub fn remove_access_control_recursive(
+        &self,
+        access_control_list: &[PathAccessControlItem],
+        options: Option<DataLakeDirectoryClientSetAccessControlRecursiveOptions<'_>>,
+    ) -> Result<PageIterator<Response<AccessControlChangeResult>>> {
+        self.operations().set_access_control_recursive(
+            PathSetAccessControlRecursiveMode::Remove,
+            PathAccessControlItem::format_removal_list(access_control_list),
+            options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            AccessControlChangeCounters, AccessControlType,
+            DataLakeDirectoryClientSetAccessControlRecursiveOptions, PathAccessControlItem,
+            RolePermissions,
+        },
+        DataLakeFileSystemClient, DataLakeFileSystemClientOptions,
+    };
+    use azure_core::{
+        http::{
+            headers::{HeaderName, Headers},
+            AsyncRawResponse, ClientOptions, HttpClient, Method, StatusCode, Transport, Url,
+        },
+        Bytes, Result,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use futures::{FutureExt as _, TryStreamExt as _};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    const CONTINUATION: HeaderName = HeaderName::from_static("x-ms-continuation");
+
+    fn file_system_client(mock_client: Arc<dyn HttpClient>) -> DataLakeFileSystemClient {
+        DataLakeFileSystemClient::new(
+            Url::parse("https://account.dfs.core.windows.net/fs").unwrap(),
+            None,
+            Some(DataLakeFileSystemClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn rename_sends_source_and_returns_destination() -> Result<()> {
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            assert_eq!(req.method(), Method::Put);
+            assert_eq!(req.url().path(), "/fs/new/name");
+            assert_eq!(
+                req.headers()
+                    .get_optional_str(&HeaderName::from_static("x-ms-rename-source")),
+                Some("/fs/old")
+            );
+            async move {
+                Ok(AsyncRawResponse::from_bytes(
+                    StatusCode::Created,
+                    Headers::new(),
+                    Bytes::new(),
+                ))
+            }
+            .boxed()
+        }));
+        let directory = file_system_client(mock_client).directory_client("old");
+        let renamed = directory.rename("new/name", None).await?;
+        assert_eq!(renamed.url().path(), "/fs/new/name");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn delete_follows_continuation() -> Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = {
+            let calls = calls.clone();
+            Arc::new(MockHttpClient::new(move |req| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                let query = req.url().query().unwrap_or_default().to_string();
+                async move {
+                    assert!(query.contains("recursive=true"), "got: {query}");
+                    let mut headers = Headers::new();
+                    if call == 0 {
+                        headers.insert(CONTINUATION, "more");
+                    } else {
+                        assert!(query.contains("continuation=more"), "got: {query}");
+                    }
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        headers,
+                        Bytes::new(),
+                    ))
+                }
+                .boxed()
+            }))
+        };
+        file_system_client(mock_client)
+            .directory_client("dir")
+            .delete(None)
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn recursive_acl_stops_at_failed_batch() -> Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = {
+            let calls = calls.clone();
+            Arc::new(MockHttpClient::new(move |req| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                let query = req.url().query().unwrap_or_default().to_string();
+                let acl = req
+                    .headers()
+                    .get_optional_string(&HeaderName::from_static("x-ms-acl"));
+                async move {
+                    assert!(query.contains("action=setAccessControlRecursive"));
+                    assert!(query.contains("mode=modify"), "got: {query}");
+                    assert_eq!(acl.as_deref(), Some("user:oid:r-x"));
+                    let mut headers = Headers::new();
+                    headers.insert(CONTINUATION, format!("token-{call}"));
+                    let body = if call == 0 {
+                        r#"{"directoriesSuccessful":1,"filesSuccessful":3,"failureCount":0,"failedEntries":[]}"#
+                    } else {
+                        r#"{"directoriesSuccessful":0,"filesSuccessful":1,"failureCount":1,"failedEntries":[{"errorMessage":"denied","name":"dir/x","type":"FILE"}]}"#
+                    };
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        headers,
+                        Bytes::from_static(body.as_bytes()),
+                    ))
+                }
+                .boxed()
+            }))
+        };
+        let directory = file_system_client(mock_client).directory_client("dir");
+        let acl = [PathAccessControlItem::new(
+            AccessControlType::User,
+            Some("oid".into()),
+            RolePermissions {
+                read: true,
+                write: false,
+                execute: true,
+            },
+        )];
+
+        let mut counters = AccessControlChangeCounters::default();
+        let mut pages = directory.update_access_control_recursive(
+            &acl,
+            Some(DataLakeDirectoryClientSetAccessControlRecursiveOptions {
+                batch_size: Some(10),
+                ..Default::default()
+            }),
+        )?;
+        let mut last_continuation = None;
+        while let Some(page) = pages.try_next().await? {
+            last_continuation = page.headers().get_optional_string(&CONTINUATION);
+            counters.add(&page.into_model()?);
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(counters.changed_files_count, 4);
+        assert_eq!(counters.failed_changes_count, 1);
+        assert_eq!(last_continuation.as_deref(), Some("token-1"));
+        Ok(())
+    }
+}
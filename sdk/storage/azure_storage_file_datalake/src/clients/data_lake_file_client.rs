@@ -0,0 +1,531 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, path_operations::PathOperations};
+use crate::models::{
+    DataLakeFileClientAppendOptions, DataLakeFileClientFlushOptions, DataLakeFileClientReadOptions,
+    DataLakeFileClientUploadOptions, DataLakePathCreateOptions, DataLakePathDeleteOptions,
+    DataLakePathGetAccessControlOptions, DataLakePathGetPropertiesOptions,
+    DataLakePathRenameOptions, DataLakePathSetAccessControlOptions, PathAccessControl,
+    PathAccessControlItem, PathProperties, PathResourceType,
+};
+use azure_core::{
+    base64,
+    error::CheckSuccessOptions,
+    http::{
+        AsyncResponse, Etag, Method, NoFormat, Pipeline, PipelineSendOptions,
+        PipelineStreamOptions, Request, RequestContent, Response, StatusCode, Url, UrlExt,
+    },
+    time::to_rfc7231,
+    tracing, Bytes, Result,
+};
+
+/// The default maximum number of bytes sent in a single append request.
+const DEFAULT_PARTITION_SIZE: usize = 4 * 1024 * 1024;
+
+/// A client for a file in a Data Lake Storage Gen2 file system.
+///
+/// Data is written to a file in two steps: [`append()`](DataLakeFileClient::append) stages bytes at a given
+/// offset and [`flush()`](DataLakeFileClient::flush) commits everything staged up to a position.
+/// [`upload()`](DataLakeFileClient::upload) combines both for data already in memory.
+#[tracing::client]
+pub struct DataLakeFileClient {
+    pub(crate) endpoint: Url,
+    pub(crate) file_system_endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+impl DataLakeFileClient {
+    fn operations(&self) -> PathOperations<'_> {
+        PathOperations {
+            endpoint: &self.endpoint,
+            pipeline: &self.pipeline,
+            version: &self.version,
+        }
+    }
+
+    /// Gets the URL of the file.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Creates an empty file. Missing parent directories are created implicitly.
+    ///
+    /// By default an existing file is replaced; use [`DataLakePathCreateOptions::if_not_exists()`] to fail instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.create")]
+    pub async fn create(
+        &self,
+        options: Option<DataLakePathCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .create(PathResourceType::File, options)
+            .await
+    }
+
+    /// Deletes the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<DataLakePathDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations().delete(None, options).await
+    }
+
+    /// Atomically renames the file.
+    ///
+    /// Returns a client for the renamed file.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_path` - The `/`-separated destination path relative to the file system root.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.rename")]
+    pub async fn rename(
+        &self,
+        new_path: &str,
+        options: Option<DataLakePathRenameOptions<'_>>,
+    ) -> Result<DataLakeFileClient> {
+        let destination = append_path(&self.file_system_endpoint, new_path);
+        self.operations().rename(&destination, options).await?;
+        Ok(DataLakeFileClient {
+            endpoint: destination,
+            file_system_endpoint: self.file_system_endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        })
+    }
+
+    /// Gets the system properties, user-defined properties and access control of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<DataLakePathGetPropertiesOptions<'_>>,
+    ) -> Result<PathProperties> {
+        self.operations().get_properties(options).await
+    }
+
+    /// Checks if the file exists.
+    ///
+    /// Returns `true` if the file exists, `false` if the file does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Gets the owner, group, permissions and access control list of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.getAccessControl")]
+    pub async fn get_access_control(
+        &self,
+        options: Option<DataLakePathGetAccessControlOptions<'_>>,
+    ) -> Result<PathAccessControl> {
+        self.operations().get_access_control(options).await
+    }
+
+    /// Replaces the access control list of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_control_list` - The complete access control list, including the owning user, owning group and other entries.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.setAccessControlList")]
+    pub async fn set_access_control_list(
+        &self,
+        access_control_list: &[PathAccessControlItem],
+        options: Option<DataLakePathSetAccessControlOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .set_access_control(
+                Some(PathAccessControlItem::format_list(access_control_list)),
+                None,
+                options,
+            )
+            .await
+    }
+
+    /// Sets the POSIX permissions of the file, and optionally its owner and group.
+    ///
+    /// # Arguments
+    ///
+    /// * `permissions` - The permissions in symbolic (`rw-r-----`) or octal (`0640`) notation.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.setPermissions")]
+    pub async fn set_permissions(
+        &self,
+        permissions: &str,
+        options: Option<DataLakePathSetAccessControlOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        self.operations()
+            .set_access_control(None, Some(permissions.to_string()), options)
+            .await
+    }
+
+    /// Stages data to be appended to the file at the given offset.
+    ///
+    /// Staged data is not readable until it is committed with [`DataLakeFileClient::flush()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The data to append.
+    /// * `offset` - The position in the file at which the data is written.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.append")]
+    pub async fn append(
+        &self,
+        data: RequestContent<Bytes, NoFormat>,
+        offset: u64,
+        options: Option<DataLakeFileClientAppendOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("action", "append");
+        if let Some(flush) = options.flush {
+            query_builder.set_pair("flush", flush.to_string());
+        }
+        query_builder.set_pair("position", offset.to_string());
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Patch);
+        request.insert_header("content-type", "application/octet-stream");
+        if let Some(transactional_content_md5) = options.transactional_content_md5.as_ref() {
+            request.insert_header("content-md5", base64::encode(transactional_content_md5));
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", &self.version);
+        request.set_body(data);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[202],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Commits data previously staged with [`DataLakeFileClient::append()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The length of the file after the flush, which must equal the end of the staged data being committed.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.flush")]
+    pub async fn flush(
+        &self,
+        position: u64,
+        options: Option<DataLakeFileClientFlushOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("action", "flush");
+        if let Some(close) = options.close {
+            query_builder.set_pair("close", close.to_string());
+        }
+        query_builder.set_pair("position", position.to_string());
+        if let Some(retain_uncommitted_data) = options.retain_uncommitted_data {
+            query_builder.set_pair("retainUncommittedData", retain_uncommitted_data.to_string());
+        }
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Patch);
+        request.insert_header("content-length", "0");
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        if let Some(content_type) = options.content_type.as_ref() {
+            request.insert_header("x-ms-content-type", content_type);
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Reads the contents of the file, or of a byte range within it, as a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.read")]
+    pub async fn read(
+        &self,
+        options: Option<DataLakeFileClientReadOptions<'_>>,
+    ) -> Result<AsyncResponse> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        if let Some(timeout) = options.timeout {
+            let mut query_builder = url.query_builder();
+            query_builder.set_pair("timeout", timeout.to_string());
+            query_builder.build();
+        }
+        let mut request = Request::new(url, Method::Get);
+        request.insert_header("accept", "application/octet-stream");
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_modified_since) = options.if_modified_since {
+            request.insert_header("if-modified-since", to_rfc7231(&if_modified_since));
+        }
+        if let Some(if_none_match) = options.if_none_match.as_ref() {
+            request.insert_header("if-none-match", if_none_match.to_string());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        if let Some(range) = options.range.as_ref() {
+            if range.is_empty() {
+                return Err(azure_core::Error::with_message(
+                    azure_core::error::ErrorKind::Other,
+                    "the range to read must not be empty",
+                ));
+            }
+            request.insert_header("range", format!("bytes={}-{}", range.start, range.end - 1));
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .stream(
+                &ctx,
+                &mut request,
+                Some(PipelineStreamOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200, 206],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Creates the file and writes `data` to it, appending in partitions and committing with a single flush.
+    ///
+    /// Fails if the file already exists unless [`DataLakeFileClientUploadOptions::overwrite`] is `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The contents of the file.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.File.upload")]
+    pub async fn upload(
+        &self,
+        data: Bytes,
+        options: Option<DataLakeFileClientUploadOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let partition_size = options
+            .partition_size
+            .unwrap_or(DEFAULT_PARTITION_SIZE)
+            .max(1);
+
+        let create_options = DataLakePathCreateOptions {
+            content_type: options.content_type.clone(),
+            if_none_match: (!options.overwrite.unwrap_or(false)).then_some(Etag::from("*")),
+            metadata: options.metadata,
+            method_options: options.method_options.clone(),
+            permissions: options.permissions,
+            umask: options.umask,
+            ..Default::default()
+        };
+        self.create(Some(create_options)).await?;
+
+        let mut offset = 0;
+        while offset < data.len() {
+            let end = usize::min(offset + partition_size, data.len());
+            self.append(
+                data.slice(offset..end).into(),
+                offset as u64,
+                Some(DataLakeFileClientAppendOptions {
+                    method_options: options.method_options.clone(),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+            offset = end;
+        }
+
+        self.flush(
+            data.len() as u64,
+            Some(DataLakeFileClientFlushOptions {
+                close: Some(true),
+                content_type: options.content_type,
+                method_options: options.method_options,
+                ..Default::default()
+            }),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{DataLakeFileClientReadOptions, DataLakeFileClientUploadOptions},
+        DataLakeFileSystemClient, DataLakeFileSystemClientOptions,
+    };
+    use azure_core::{
+        http::{
+            headers::Headers, AsyncRawResponse, ClientOptions, HttpClient, Method, StatusCode,
+            Transport, Url,
+        },
+        Bytes, Result,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use futures::FutureExt as _;
+    use std::sync::{Arc, Mutex};
+
+    fn file_system_client(mock_client: Arc<dyn HttpClient>) -> DataLakeFileSystemClient {
+        DataLakeFileSystemClient::new(
+            Url::parse("https://account.dfs.core.windows.net/fs").unwrap(),
+            None,
+            Some(DataLakeFileSystemClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn upload_appends_partitions_then_flushes() -> Result<()> {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let mock_client = {
+            let requests = requests.clone();
+            Arc::new(MockHttpClient::new(move |req| {
+                let method = req.method();
+                let query = req.url().query().unwrap_or_default().to_string();
+                let if_none_match = req.headers().get_optional_string(&"if-none-match".into());
+                requests
+                    .lock()
+                    .unwrap()
+                    .push((method, query.clone(), if_none_match));
+                async move {
+                    let status = match (method, query.as_str()) {
+                        (Method::Put, _) => StatusCode::Created,
+                        (Method::Patch, q) if q.contains("action=append") => StatusCode::Accepted,
+                        _ => StatusCode::Ok,
+                    };
+                    Ok(AsyncRawResponse::from_bytes(
+                        status,
+                        Headers::new(),
+                        Bytes::new(),
+                    ))
+                }
+                .boxed()
+            }))
+        };
+
+        file_system_client(mock_client)
+            .file_client("dir/file.txt")
+            .upload(
+                Bytes::from_static(b"0123456789"),
+                Some(DataLakeFileClientUploadOptions {
+                    partition_size: Some(4),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[0].0, Method::Put);
+        assert_eq!(requests[0].1, "resource=file");
+        assert_eq!(requests[0].2.as_deref(), Some("*"));
+        assert_eq!(requests[1].1, "action=append&position=0");
+        assert_eq!(requests[2].1, "action=append&position=4");
+        assert_eq!(requests[3].1, "action=append&position=8");
+        assert_eq!(requests[4].1, "action=flush&close=true&position=10");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_sends_inclusive_range() -> Result<()> {
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            assert_eq!(
+                req.headers().get_optional_str(&"range".into()),
+                Some("bytes=2-5")
+            );
+            async move {
+                Ok(AsyncRawResponse::from_bytes(
+                    StatusCode::PartialContent,
+                    Headers::new(),
+                    Bytes::from_static(b"2345"),
+                ))
+            }
+            .boxed()
+        }));
+
+        let body = file_system_client(mock_client)
+            .file_client("file.txt")
+            .read(Some(DataLakeFileClientReadOptions {
+                range: Some(2..6),
+                ..Default::default()
+            }))
+            .await?
+            .into_body()
+            .collect()
+            .await?;
+        assert_eq!(body.as_ref(), b"2345");
+        Ok(())
+    }
+}
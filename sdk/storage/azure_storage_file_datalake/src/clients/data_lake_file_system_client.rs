@@ -0,0 +1,415 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, new_pipeline, path_operations::CONTINUATION, DEFAULT_VERSION};
+use crate::{
+    models::{
+        encode_properties, DataLakeFileSystemClientCreateOptions,
+        DataLakeFileSystemClientDeleteOptions, DataLakeFileSystemClientGetPropertiesOptions,
+        DataLakeFileSystemClientListPathsOptions, PathList,
+    },
+    DataLakeDirectoryClient, DataLakeFileClient,
+};
+use azure_core::{
+    credentials::TokenCredential,
+    error::CheckSuccessOptions,
+    fmt::SafeDebug,
+    http::{
+        pager::{PagerResult, PagerState},
+        ClientOptions, Method, NoFormat, Pager, Pipeline, PipelineSendOptions, Request, Response,
+        StatusCode, Url, UrlExt,
+    },
+    time::to_rfc7231,
+    tracing, Result,
+};
+use std::sync::Arc;
+
+/// A client for a Data Lake Storage Gen2 file system.
+///
+/// File systems correspond to blob containers in accounts with a hierarchical namespace.
+#[tracing::client]
+pub struct DataLakeFileSystemClient {
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+/// Options used when creating a [`DataLakeFileSystemClient`].
+#[derive(Clone, SafeDebug)]
+pub struct DataLakeFileSystemClientOptions {
+    /// Allows customization of the client.
+    pub client_options: ClientOptions,
+    /// Specifies the version of the operation to use for this request.
+    pub version: String,
+}
+
+impl Default for DataLakeFileSystemClientOptions {
+    fn default() -> Self {
+        Self {
+            client_options: ClientOptions::default(),
+            version: String::from(DEFAULT_VERSION),
+        }
+    }
+}
+
+impl DataLakeFileSystemClient {
+    /// Creates a new `DataLakeFileSystemClient` from a file system URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_system_url` - The full URL of the file system on the DFS endpoint, for example `https://myaccount.dfs.core.windows.net/myfilesystem`.
+    ///   The caller is responsible for percent-encoding the URL correctly; it will be used as-is.
+    /// * `credential` - An optional implementation of [`TokenCredential`] that can provide an Entra ID token to use when authenticating.
+    /// * `options` - Optional configuration for the client.
+    #[tracing::new("Storage.DataLake.FileSystem")]
+    pub fn new(
+        file_system_url: Url,
+        credential: Option<Arc<dyn TokenCredential>>,
+        options: Option<DataLakeFileSystemClientOptions>,
+    ) -> Result<Self> {
+        let mut options = options.unwrap_or_default();
+        let pipeline = new_pipeline(&file_system_url, credential, &mut options.client_options)?;
+
+        Ok(Self {
+            endpoint: file_system_url,
+            version: options.version,
+            pipeline,
+        })
+    }
+
+    /// Gets the URL of the file system.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Returns a new instance of [`DataLakeDirectoryClient`].
+    ///
+    /// # Arguments
+    ///
+    /// * `directory_path` - The `/`-separated path of the directory relative to the file system root.
+    pub fn directory_client(&self, directory_path: &str) -> DataLakeDirectoryClient {
+        DataLakeDirectoryClient {
+            endpoint: append_path(&self.endpoint, directory_path),
+            file_system_endpoint: self.endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Returns a new instance of [`DataLakeFileClient`].
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The `/`-separated path of the file relative to the file system root.
+    pub fn file_client(&self, file_path: &str) -> DataLakeFileClient {
+        DataLakeFileClient {
+            endpoint: append_path(&self.endpoint, file_path),
+            file_system_endpoint: self.endpoint.clone(),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Creates the file system.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.FileSystem.create")]
+    pub async fn create(
+        &self,
+        options: Option<DataLakeFileSystemClientCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("resource", "filesystem");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(metadata) = options.metadata.as_ref() {
+            request.insert_header("x-ms-properties", encode_properties(metadata));
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Marks the file system for deletion. The file system and any paths it contains are removed later during garbage collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.FileSystem.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<DataLakeFileSystemClientDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("resource", "filesystem");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Delete);
+        if let Some(if_modified_since) = options.if_modified_since {
+            request.insert_header("if-modified-since", to_rfc7231(&if_modified_since));
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[202],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Gets the system and user-defined properties of the file system.
+    ///
+    /// User-defined properties are returned base64-encoded in the `x-ms-properties` response header.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.FileSystem.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<DataLakeFileSystemClientGetPropertiesOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("resource", "filesystem");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Head);
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Checks if the file system exists.
+    ///
+    /// Returns `true` if the file system exists, `false` if the file system does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns a list of the directories and files in the file system.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.DataLake.FileSystem.listPaths")]
+    pub fn list_paths(
+        &self,
+        options: Option<DataLakeFileSystemClientListPathsOptions<'_>>,
+    ) -> Result<Pager<PathList>> {
+        let options = options.unwrap_or_default().into_owned();
+        let pipeline = self.pipeline.clone();
+        let mut first_url = self.endpoint.clone();
+        let mut query_builder = first_url.query_builder();
+        query_builder.set_pair("resource", "filesystem").set_pair(
+            "recursive",
+            options.recursive.unwrap_or_default().to_string(),
+        );
+        if let Some(path) = options.path.as_ref() {
+            query_builder.set_pair("directory", path);
+        }
+        if let Some(max_results) = options.max_results {
+            query_builder.set_pair("maxResults", max_results.to_string());
+        }
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        if let Some(user_principal_name) = options.user_principal_name {
+            query_builder.set_pair("upn", user_principal_name.to_string());
+        }
+        query_builder.build();
+        let version = self.version.clone();
+        Ok(Pager::new(
+            move |continuation: PagerState, pager_options| {
+                let mut url = first_url.clone();
+                if let PagerState::More(continuation) = continuation {
+                    let mut query_builder = url.query_builder();
+                    query_builder.set_pair("continuation", continuation.as_ref());
+                    query_builder.build();
+                }
+                let mut request = Request::new(url, Method::Get);
+                request.insert_header("accept", "application/json");
+                request.insert_header("x-ms-version", &version);
+                let pipeline = pipeline.clone();
+                Box::pin(async move {
+                    let rsp: Response<PathList> = pipeline
+                        .send(
+                            &pager_options.context,
+                            &mut request,
+                            Some(PipelineSendOptions {
+                                check_success: CheckSuccessOptions {
+                                    success_codes: &[200],
+                                },
+                                ..Default::default()
+                            }),
+                        )
+                        .await?
+                        .into();
+                    Ok(PagerResult::from_response_header(rsp, &CONTINUATION))
+                })
+            },
+            Some(options.method_options),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, HttpClient, Transport},
+        Bytes,
+    };
+    use azure_core_test::{credentials::MockCredential, http::MockHttpClient};
+    use futures::{FutureExt as _, TryStreamExt as _};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn file_system_client(mock_client: Arc<dyn HttpClient>) -> DataLakeFileSystemClient {
+        DataLakeFileSystemClient::new(
+            Url::parse("https://account.dfs.core.windows.net/fs").unwrap(),
+            None,
+            Some(DataLakeFileSystemClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn new_requires_https_with_credential() {
+        let cred = MockCredential::new().unwrap();
+        let url = Url::parse("http://account.dfs.core.windows.net/fs").unwrap();
+        let err = DataLakeFileSystemClient::new(url, Some(cred), None)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("must use https"), "got: {err}");
+    }
+
+    #[test]
+    fn new_rejects_non_base_url() {
+        let url = Url::parse("data:text/plain,hello").unwrap();
+        assert!(DataLakeFileSystemClient::new(url, None, None).is_err());
+    }
+
+    #[test]
+    fn path_clients_encode_segments() {
+        let url = Url::parse("https://account.dfs.core.windows.net/fs").unwrap();
+        let client = DataLakeFileSystemClient::new(url, None, None).unwrap();
+        assert_eq!(
+            client.directory_client("a/b c/").url().path(),
+            "/fs/a/b%20c"
+        );
+        assert_eq!(
+            client.file_client("/a/file#1.txt").url().path(),
+            "/fs/a/file%231.txt"
+        );
+    }
+
+    #[tokio::test]
+    async fn list_paths_follows_continuation_header() -> Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = {
+            let calls = calls.clone();
+            Arc::new(MockHttpClient::new(move |req| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                let query = req.url().query().unwrap_or_default().to_string();
+                async move {
+                    assert!(query.contains("resource=filesystem"), "got: {query}");
+                    assert!(query.contains("recursive=true"), "got: {query}");
+                    let mut headers = Headers::new();
+                    let body = if call == 0 {
+                        assert!(!query.contains("continuation="), "got: {query}");
+                        headers.insert(CONTINUATION, "next-page");
+                        r#"{"paths":[{"name":"dir","isDirectory":"true"}]}"#
+                    } else {
+                        assert!(query.contains("continuation=next-page"), "got: {query}");
+                        r#"{"paths":[{"name":"dir/file","contentLength":"3"}]}"#
+                    };
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        headers,
+                        Bytes::from_static(body.as_bytes()),
+                    ))
+                }
+                .boxed()
+            }))
+        };
+        let client = file_system_client(mock_client);
+
+        let paths: Vec<_> = client
+            .list_paths(Some(DataLakeFileSystemClientListPathsOptions {
+                recursive: Some(true),
+                ..Default::default()
+            }))?
+            .try_collect()
+            .await?;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].is_directory, Some(true));
+        assert_eq!(paths[1].content_length, Some(3));
+        Ok(())
+    }
+}
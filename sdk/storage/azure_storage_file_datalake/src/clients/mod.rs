@@ -0,0 +1,78 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Clients used to communicate with Azure Data Lake Storage Gen2.
+
+use crate::logging::apply_storage_logging_defaults;
+use azure_core::{
+    credentials::TokenCredential,
+    http::{
+        policies::{auth::BearerTokenAuthorizationPolicy, Policy},
+        ClientOptions, Pipeline, Url,
+    },
+    Result,
+};
+use std::sync::Arc;
+
+mod data_lake_directory_client;
+mod data_lake_file_client;
+mod data_lake_file_system_client;
+mod path_operations;
+
+pub use data_lake_directory_client::DataLakeDirectoryClient;
+pub use data_lake_file_client::DataLakeFileClient;
+pub use data_lake_file_system_client::{DataLakeFileSystemClient, DataLakeFileSystemClientOptions};
+
+/// Default value for [`DataLakeFileSystemClientOptions::version`].
+pub(crate) const DEFAULT_VERSION: &str = "2026-04-06";
+
+/// Validates a Data Lake endpoint and creates the pipeline shared by all clients derived from it.
+fn new_pipeline(
+    url: &Url,
+    credential: Option<Arc<dyn TokenCredential>>,
+    client_options: &mut ClientOptions,
+) -> Result<Pipeline> {
+    // Storage endpoints must be base URLs.
+    if url.cannot_be_a_base() {
+        return Err(azure_core::Error::with_message(
+            azure_core::error::ErrorKind::Other,
+            format!("{url} is not a valid base URL"),
+        ));
+    }
+
+    apply_storage_logging_defaults(client_options);
+
+    let mut per_retry_policies: Vec<Arc<dyn Policy>> = Vec::default();
+    if let Some(token_credential) = credential {
+        if !url.scheme().starts_with("https") {
+            return Err(azure_core::Error::with_message(
+                azure_core::error::ErrorKind::Other,
+                format!("{url} must use https"),
+            ));
+        }
+        per_retry_policies.push(Arc::new(BearerTokenAuthorizationPolicy::new(
+            token_credential,
+            vec!["https://storage.azure.com/.default"],
+        )));
+    }
+
+    Ok(Pipeline::new(
+        option_env!("CARGO_PKG_NAME"),
+        option_env!("CARGO_PKG_VERSION"),
+        client_options.clone(),
+        Vec::default(),
+        per_retry_policies,
+        None,
+    ))
+}
+
+/// Appends a `/`-separated path relative to `base`, percent-encoding each segment.
+fn append_path(base: &Url, path: &str) -> Url {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        // This should not fail as the base URL has already been validated on client construction.
+        .expect("Invalid endpoint URL: Cannot append path to the Data Lake endpoint.")
+        .pop_if_empty()
+        .extend(path.split('/').filter(|s| !s.is_empty()));
+    url
+}
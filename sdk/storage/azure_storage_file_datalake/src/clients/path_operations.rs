@@ -0,0 +1,441 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Operations shared by directory and file clients, which both address a path in a file system.
+
+use crate::models::{
+    encode_properties, AccessControlChangeResult,
+    DataLakeDirectoryClientSetAccessControlRecursiveOptions, DataLakePathCreateOptions,
+    DataLakePathDeleteOptions, DataLakePathGetAccessControlOptions,
+    DataLakePathGetPropertiesOptions, DataLakePathRenameOptions,
+    DataLakePathSetAccessControlOptions, PathAccessControl, PathProperties, PathResourceType,
+    PathSetAccessControlRecursiveMode,
+};
+use azure_core::{
+    error::CheckSuccessOptions,
+    http::{
+        headers::HeaderName,
+        pager::{PageIterator, PagerContinuation, PagerResult, PagerState},
+        Method, NoFormat, Pipeline, PipelineSendOptions, RawResponse, Request, Response, Url,
+        UrlExt,
+    },
+    time::to_rfc7231,
+    Result,
+};
+
+pub(crate) const CONTINUATION: HeaderName = HeaderName::from_static("x-ms-continuation");
+
+/// A borrowed view of the state needed to send requests for a single path.
+pub(crate) struct PathOperations<'a> {
+    pub endpoint: &'a Url,
+    pub pipeline: &'a Pipeline,
+    pub version: &'a str,
+}
+
+impl PathOperations<'_> {
+    pub async fn create(
+        &self,
+        resource: PathResourceType,
+        options: Option<DataLakePathCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("resource", resource.as_ref());
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(content_type) = options.content_type.as_ref() {
+            request.insert_header("x-ms-content-type", content_type);
+        }
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_modified_since) = options.if_modified_since {
+            request.insert_header("if-modified-since", to_rfc7231(&if_modified_since));
+        }
+        if let Some(if_none_match) = options.if_none_match.as_ref() {
+            request.insert_header("if-none-match", if_none_match.to_string());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        if let Some(metadata) = options.metadata.as_ref() {
+            request.insert_header("x-ms-properties", encode_properties(metadata));
+        }
+        if let Some(permissions) = options.permissions.as_ref() {
+            request.insert_header("x-ms-permissions", permissions);
+        }
+        if let Some(umask) = options.umask.as_ref() {
+            request.insert_header("x-ms-umask", umask);
+        }
+        request.insert_header("x-ms-version", self.version.to_owned());
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Deletes the path, following `x-ms-continuation` until the service reports the delete is complete.
+    pub async fn delete(
+        &self,
+        recursive: Option<bool>,
+        options: Option<DataLakePathDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut continuation: Option<String> = None;
+        loop {
+            let mut url = self.endpoint.clone();
+            let mut query_builder = url.query_builder();
+            if let Some(continuation) = continuation.as_ref() {
+                query_builder.set_pair("continuation", continuation);
+            }
+            if let Some(recursive) = recursive {
+                query_builder.set_pair("recursive", recursive.to_string());
+            }
+            if let Some(timeout) = options.timeout {
+                query_builder.set_pair("timeout", timeout.to_string());
+            }
+            query_builder.build();
+            let mut request = Request::new(url, Method::Delete);
+            if let Some(if_match) = options.if_match.as_ref() {
+                request.insert_header("if-match", if_match.to_string());
+            }
+            if let Some(if_modified_since) = options.if_modified_since {
+                request.insert_header("if-modified-since", to_rfc7231(&if_modified_since));
+            }
+            if let Some(if_none_match) = options.if_none_match.as_ref() {
+                request.insert_header("if-none-match", if_none_match.to_string());
+            }
+            if let Some(if_unmodified_since) = options.if_unmodified_since {
+                request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+            }
+            if let Some(lease_id) = options.lease_id.as_ref() {
+                request.insert_header("x-ms-lease-id", lease_id);
+            }
+            request.insert_header("x-ms-version", self.version.to_owned());
+            let rsp: Response<(), NoFormat> = self
+                .pipeline
+                .send(
+                    &ctx,
+                    &mut request,
+                    Some(PipelineSendOptions {
+                        check_success: CheckSuccessOptions {
+                            success_codes: &[200, 202],
+                        },
+                        ..Default::default()
+                    }),
+                )
+                .await?
+                .into();
+            match rsp.headers().get_optional_string(&CONTINUATION) {
+                Some(next) if !next.is_empty() => continuation = Some(next),
+                _ => return Ok(rsp),
+            }
+        }
+    }
+
+    /// Renames this path to `destination`, which must be in the same storage account.
+    pub async fn rename(
+        &self,
+        destination: &Url,
+        options: Option<DataLakePathRenameOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = destination.clone();
+        let mut query_builder = url.query_builder();
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_none_match) = options.if_none_match.as_ref() {
+            request.insert_header("if-none-match", if_none_match.to_string());
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-rename-source", rename_source(self.endpoint));
+        if let Some(source_if_match) = options.source_if_match.as_ref() {
+            request.insert_header("x-ms-source-if-match", source_if_match.to_string());
+        }
+        if let Some(source_lease_id) = options.source_lease_id.as_ref() {
+            request.insert_header("x-ms-source-lease-id", source_lease_id);
+        }
+        request.insert_header("x-ms-version", self.version.to_owned());
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    pub async fn get_properties(
+        &self,
+        options: Option<DataLakePathGetPropertiesOptions<'_>>,
+    ) -> Result<PathProperties> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Head);
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", self.version.to_owned());
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        PathProperties::from_headers(rsp.headers())
+    }
+
+    pub async fn get_access_control(
+        &self,
+        options: Option<DataLakePathGetAccessControlOptions<'_>>,
+    ) -> Result<PathAccessControl> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("action", "getAccessControl");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        if let Some(user_principal_name) = options.user_principal_name {
+            query_builder.set_pair("upn", user_principal_name.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Head);
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", self.version.to_owned());
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        PathAccessControl::from_headers(rsp.headers())
+    }
+
+    /// Sets any combination of the ACL, POSIX permissions, owner and group. The service rejects
+    /// requests that set both an ACL and permissions.
+    pub async fn set_access_control(
+        &self,
+        acl: Option<String>,
+        permissions: Option<String>,
+        options: Option<DataLakePathSetAccessControlOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.set_pair("action", "setAccessControl");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Patch);
+        request.insert_header("content-length", "0");
+        if let Some(if_match) = options.if_match.as_ref() {
+            request.insert_header("if-match", if_match.to_string());
+        }
+        if let Some(if_unmodified_since) = options.if_unmodified_since {
+            request.insert_header("if-unmodified-since", to_rfc7231(&if_unmodified_since));
+        }
+        if let Some(acl) = acl {
+            request.insert_header("x-ms-acl", acl);
+        }
+        if let Some(group) = options.group.as_ref() {
+            request.insert_header("x-ms-group", group);
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        if let Some(owner) = options.owner.as_ref() {
+            request.insert_header("x-ms-owner", owner);
+        }
+        if let Some(permissions) = permissions {
+            request.insert_header("x-ms-permissions", permissions);
+        }
+        request.insert_header("x-ms-version", self.version.to_owned());
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Applies `acl` to this path and all paths beneath it, one batch per page.
+    ///
+    /// Each page carries the `x-ms-continuation` header returned by the service, if any.
+    /// When `continue_on_failure` is not set, paging stops after the first batch that reports failures
+    /// so the caller can inspect them and resume from that page's continuation.
+    pub fn set_access_control_recursive(
+        &self,
+        mode: PathSetAccessControlRecursiveMode,
+        acl: String,
+        options: Option<DataLakeDirectoryClientSetAccessControlRecursiveOptions<'_>>,
+    ) -> Result<PageIterator<Response<AccessControlChangeResult>>> {
+        let options = options.unwrap_or_default().into_owned();
+        let pipeline = self.pipeline.clone();
+        let mut first_url = self.endpoint.clone();
+        let mut query_builder = first_url.query_builder();
+        query_builder
+            .set_pair("action", "setAccessControlRecursive")
+            .set_pair("mode", mode.as_ref());
+        if let Some(continue_on_failure) = options.continue_on_failure {
+            query_builder.set_pair("forceFlag", continue_on_failure.to_string());
+        }
+        if let Some(batch_size) = options.batch_size {
+            query_builder.set_pair("maxRecords", batch_size.to_string());
+        }
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let continue_on_failure = options.continue_on_failure.unwrap_or_default();
+        let version = self.version.to_string();
+        Ok(PageIterator::new(
+            move |continuation: PagerState, pager_options| {
+                let mut url = first_url.clone();
+                if let PagerState::More(continuation) = continuation {
+                    let mut query_builder = url.query_builder();
+                    query_builder.set_pair("continuation", continuation.as_ref());
+                    query_builder.build();
+                }
+                let mut request = Request::new(url, Method::Patch);
+                request.insert_header("accept", "application/json");
+                request.insert_header("content-length", "0");
+                request.insert_header("x-ms-acl", acl.clone());
+                request.insert_header("x-ms-version", &version);
+                let pipeline = pipeline.clone();
+                Box::pin(async move {
+                    let rsp = pipeline
+                        .send(
+                            &pager_options.context,
+                            &mut request,
+                            Some(PipelineSendOptions {
+                                check_success: CheckSuccessOptions {
+                                    success_codes: &[200],
+                                },
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+                    let (status, headers, body) = rsp.deconstruct();
+                    let batch: AccessControlChangeResult = serde_json::from_slice(&body)?;
+                    let continuation = headers.get_optional_string(&CONTINUATION);
+                    let rsp: Response<AccessControlChangeResult> =
+                        RawResponse::from_bytes(status, headers, body).into();
+                    Ok(match continuation {
+                        Some(continuation)
+                            if !continuation.is_empty()
+                                && (continue_on_failure || batch.failure_count == 0) =>
+                        {
+                            PagerResult::More {
+                                response: rsp,
+                                continuation: PagerContinuation::Token(continuation),
+                            }
+                        }
+                        _ => PagerResult::Done { response: rsp },
+                    })
+                })
+            },
+            Some(options.method_options),
+        ))
+    }
+}
+
+/// Formats the `x-ms-rename-source` header value: the source path, plus its query string if the URL carries a SAS.
+fn rename_source(source: &Url) -> String {
+    match source.query() {
+        Some(query) if !query.is_empty() => format!("{}?{}", source.path(), query),
+        _ => source.path().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rename_source;
+    use azure_core::http::Url;
+
+    #[test]
+    fn rename_source_includes_sas() {
+        let url =
+            Url::parse("https://account.dfs.core.windows.net/fs/dir%20a/file?sv=1&sig=x").unwrap();
+        assert_eq!(rename_source(&url), "/fs/dir%20a/file?sv=1&sig=x");
+
+        let url = Url::parse("https://account.dfs.core.windows.net/fs/dir").unwrap();
+        assert_eq!(rename_source(&url), "/fs/dir");
+    }
+}
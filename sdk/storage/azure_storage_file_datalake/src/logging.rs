@@ -0,0 +1,96 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Logging configuration for Azure Data Lake Storage clients.
+//!
+//! These defaults are automatically applied to all Data Lake clients and merged with any user-specified logging options.
+
+use azure_core::http::ClientOptions;
+use std::borrow::Cow;
+
+/// Default allowed header names for Azure Data Lake Storage logging.
+pub static STORAGE_ALLOWED_HEADERS: &[&str] = &[
+    // CORS
+    "access-control-allow-origin",
+    // General Azure headers
+    "x-ms-date",
+    "x-ms-error-code",
+    "x-ms-version",
+    // Content headers
+    "accept-ranges",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-md5",
+    "content-range",
+    "vary",
+    // Data Lake-specific headers
+    "x-ms-continuation",
+    "x-ms-group",
+    "x-ms-lease-duration",
+    "x-ms-lease-state",
+    "x-ms-lease-status",
+    "x-ms-namespace-enabled",
+    "x-ms-owner",
+    "x-ms-permissions",
+    "x-ms-rename-source",
+    "x-ms-request-server-encrypted",
+    "x-ms-resource-type",
+    "x-ms-umask",
+];
+
+/// Default allowed query parameters for Azure Data Lake Storage logging.
+pub static STORAGE_ALLOWED_QUERY_PARAMETERS: &[&str] = &[
+    // SAS token parameters (values are time-limited or non-sensitive identifiers)
+    "se",
+    "si",
+    "sip",
+    "sp",
+    "spr",
+    "sr",
+    "srt",
+    "ss",
+    "st",
+    "sv",
+    "sdd",
+    // User delegation key parameters
+    "ske",
+    "skoid",
+    "sks",
+    "skt",
+    "sktid",
+    "skv",
+    // Operation parameters
+    "action",
+    "close",
+    "directory",
+    "flush",
+    "maxRecords",
+    "maxResults",
+    "mode",
+    "position",
+    "recursive",
+    "resource",
+    "retainUncommittedData",
+    "upn",
+    // Listing parameters
+    "continuation",
+];
+
+/// Applies the default Azure Data Lake Storage logging configuration to client options.
+///
+/// This function adds the storage-specific allowed headers and query parameters
+/// to the user's existing logging options. User-specified options are preserved and
+/// take effect in addition to the storage defaults.
+pub(crate) fn apply_storage_logging_defaults(options: &mut ClientOptions) {
+    options
+        .logging
+        .additional_allowed_header_names
+        .extend(STORAGE_ALLOWED_HEADERS.iter().map(|s| Cow::Borrowed(*s)));
+
+    options.logging.additional_allowed_query_params.extend(
+        STORAGE_ALLOWED_QUERY_PARAMETERS
+            .iter()
+            .map(|s| Cow::Borrowed(*s)),
+    );
+}
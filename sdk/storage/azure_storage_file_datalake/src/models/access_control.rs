@@ -0,0 +1,418 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use azure_core::{
+    error::ErrorKind,
+    http::headers::{HeaderName, Headers},
+    Error, Result,
+};
+use std::{fmt, str::FromStr};
+
+const OWNER: HeaderName = HeaderName::from_static("x-ms-owner");
+const GROUP: HeaderName = HeaderName::from_static("x-ms-group");
+const PERMISSIONS: HeaderName = HeaderName::from_static("x-ms-permissions");
+const ACL: HeaderName = HeaderName::from_static("x-ms-acl");
+
+/// The type of entity a [`PathAccessControlItem`] applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccessControlType {
+    /// The owning user, or a named user when an entity ID is present.
+    User,
+    /// The owning group, or a named group when an entity ID is present.
+    Group,
+    /// The mask that restricts permissions for named users, named groups and the owning group.
+    Mask,
+    /// All other users.
+    Other,
+}
+
+impl AsRef<str> for AccessControlType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::User => "user",
+            Self::Group => "group",
+            Self::Mask => "mask",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for AccessControlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+impl FromStr for AccessControlType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "user" => Ok(Self::User),
+            "group" => Ok(Self::Group),
+            "mask" => Ok(Self::Mask),
+            "other" => Ok(Self::Other),
+            _ => Err(Error::with_message_fn(ErrorKind::DataConversion, || {
+                format!("unknown access control type {s:?}")
+            })),
+        }
+    }
+}
+
+/// POSIX read, write and execute permissions for a single role.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RolePermissions {
+    /// Read permission.
+    pub read: bool,
+    /// Write permission.
+    pub write: bool,
+    /// Execute permission.
+    pub execute: bool,
+}
+
+impl RolePermissions {
+    /// Read, write and execute permissions.
+    pub const ALL: Self = Self {
+        read: true,
+        write: true,
+        execute: true,
+    };
+
+    /// No permissions.
+    pub const NONE: Self = Self {
+        read: false,
+        write: false,
+        execute: false,
+    };
+
+    /// Parses a three-character symbolic permission such as `r-x`.
+    ///
+    /// When `allow_sticky` is `true`, `t` and `T` are accepted in the execute position
+    /// and reported as execute set and unset, respectively.
+    fn parse(s: &str, allow_sticky: bool) -> Result<Self> {
+        let bytes = s.as_bytes();
+        if bytes.len() != 3 {
+            return Err(Error::with_message_fn(ErrorKind::DataConversion, || {
+                format!("invalid role permissions {s:?}")
+            }));
+        }
+        let flag = |c: u8, set: u8| match c {
+            c if c == set => Ok(true),
+            b'-' => Ok(false),
+            _ => Err(Error::with_message_fn(ErrorKind::DataConversion, || {
+                format!("invalid role permissions {s:?}")
+            })),
+        };
+        let execute = match bytes[2] {
+            b't' if allow_sticky => true,
+            b'T' if allow_sticky => false,
+            c => flag(c, b'x')?,
+        };
+        Ok(Self {
+            read: flag(bytes[0], b'r')?,
+            write: flag(bytes[1], b'w')?,
+            execute,
+        })
+    }
+
+    /// Gets the octal digit for these permissions.
+    pub fn to_octal(self) -> u8 {
+        (self.read as u8) << 2 | (self.write as u8) << 1 | self.execute as u8
+    }
+}
+
+impl fmt::Display for RolePermissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            if self.read { 'r' } else { '-' },
+            if self.write { 'w' } else { '-' },
+            if self.execute { 'x' } else { '-' },
+        )
+    }
+}
+
+impl FromStr for RolePermissions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s, false)
+    }
+}
+
+/// The POSIX permissions of a path for its owner, owning group and other users.
+///
+/// Formats as symbolic notation, for example `rwxr-x---`, with a trailing `+` when an extended ACL is present.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathPermissions {
+    /// Permissions of the owning user.
+    pub owner: RolePermissions,
+    /// Permissions of the owning group.
+    pub group: RolePermissions,
+    /// Permissions of all other users.
+    pub other: RolePermissions,
+    /// Whether the sticky bit is set.
+    pub sticky_bit: bool,
+    /// Whether the path has an extended access control list.
+    pub extended_acl: bool,
+}
+
+impl PathPermissions {
+    /// Gets the permissions in four-digit octal notation, for example `0750`.
+    pub fn to_octal(&self) -> String {
+        format!(
+            "{}{}{}{}",
+            if self.sticky_bit { 1 } else { 0 },
+            self.owner.to_octal(),
+            self.group.to_octal(),
+            self.other.to_octal()
+        )
+    }
+}
+
+impl fmt::Display for PathPermissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let other = self.other.to_string();
+        let other = match (self.sticky_bit, self.other.execute) {
+            (true, true) => format!("{}t", &other[..2]),
+            (true, false) => format!("{}T", &other[..2]),
+            _ => other,
+        };
+        write!(f, "{}{}{}", self.owner, self.group, other)?;
+        if self.extended_acl {
+            f.write_str("+")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for PathPermissions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (s, extended_acl) = match s.strip_suffix('+') {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        if s.len() != 9 || !s.is_ascii() {
+            return Err(Error::with_message_fn(ErrorKind::DataConversion, || {
+                format!("invalid path permissions {s:?}")
+            }));
+        }
+        Ok(Self {
+            owner: RolePermissions::parse(&s[0..3], false)?,
+            group: RolePermissions::parse(&s[3..6], false)?,
+            other: RolePermissions::parse(&s[6..9], true)?,
+            sticky_bit: matches!(s.as_bytes()[8], b't' | b'T'),
+            extended_acl,
+        })
+    }
+}
+
+/// A single access control entry, for example `user:4f2c...:r-x` or `default:group::rwx`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PathAccessControlItem {
+    /// Whether this is a default entry inherited by new children of a directory.
+    pub default_scope: bool,
+    /// The type of entity the entry applies to.
+    pub access_control_type: AccessControlType,
+    /// The user or group object ID, or `None` for the owning user, owning group, mask or other entries.
+    pub entity_id: Option<String>,
+    /// The permissions granted to the entity.
+    pub permissions: RolePermissions,
+}
+
+impl PathAccessControlItem {
+    /// Creates a new access scope entry.
+    pub fn new(
+        access_control_type: AccessControlType,
+        entity_id: Option<String>,
+        permissions: RolePermissions,
+    ) -> Self {
+        Self {
+            default_scope: false,
+            access_control_type,
+            entity_id,
+            permissions,
+        }
+    }
+
+    /// Parses a comma-separated access control list such as the value of the `x-ms-acl` header.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Formats a list of access control entries as a comma-separated string.
+    pub fn format_list(items: &[Self]) -> String {
+        items
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Formats a list of entries for removal, which omits permissions (e.g. `user:oid,default:mask`).
+    pub(crate) fn format_removal_list(items: &[Self]) -> String {
+        items
+            .iter()
+            .map(|item| {
+                let mut s = String::new();
+                if item.default_scope {
+                    s.push_str("default:");
+                }
+                s.push_str(item.access_control_type.as_ref());
+                if let Some(entity_id) = item.entity_id.as_deref() {
+                    s.push(':');
+                    s.push_str(entity_id);
+                }
+                s
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl fmt::Display for PathAccessControlItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.default_scope {
+            f.write_str("default:")?;
+        }
+        write!(
+            f,
+            "{}:{}:{}",
+            self.access_control_type,
+            self.entity_id.as_deref().unwrap_or_default(),
+            self.permissions
+        )
+    }
+}
+
+impl FromStr for PathAccessControlItem {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts: Vec<&str> = s.split(':').collect();
+        let default_scope = parts.first() == Some(&"default");
+        if default_scope {
+            parts.remove(0);
+        }
+        let [access_control_type, entity_id, permissions] = parts.as_slice() else {
+            return Err(Error::with_message_fn(ErrorKind::DataConversion, || {
+                format!("invalid access control entry {s:?}")
+            }));
+        };
+        Ok(Self {
+            default_scope,
+            access_control_type: access_control_type.parse()?,
+            entity_id: (!entity_id.is_empty()).then(|| entity_id.to_string()),
+            permissions: permissions.parse()?,
+        })
+    }
+}
+
+/// The owner, group, permissions and access control list of a path.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathAccessControl {
+    /// The owning user of the path.
+    pub owner: Option<String>,
+    /// The owning group of the path.
+    pub group: Option<String>,
+    /// The POSIX permissions of the path.
+    pub permissions: Option<PathPermissions>,
+    /// The POSIX access control list of the path.
+    pub access_control_list: Vec<PathAccessControlItem>,
+}
+
+impl PathAccessControl {
+    /// Reads the access control properties from the `x-ms-owner`, `x-ms-group`, `x-ms-permissions` and `x-ms-acl` response headers.
+    pub fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            owner: headers.get_optional_string(&OWNER),
+            group: headers.get_optional_string(&GROUP),
+            permissions: headers.get_optional_as(&PERMISSIONS)?,
+            access_control_list: headers
+                .get_optional_str(&ACL)
+                .map(PathAccessControlItem::parse_list)
+                .transpose()?
+                .unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_access_control_list() {
+        let acl = PathAccessControlItem::parse_list(
+            "user::rwx,user:4f2c:r-x,group::r--,mask::r-x,other::---,default:user::rwx",
+        )
+        .unwrap();
+        assert_eq!(acl.len(), 6);
+        assert_eq!(acl[1].access_control_type, AccessControlType::User);
+        assert_eq!(acl[1].entity_id.as_deref(), Some("4f2c"));
+        assert_eq!(
+            acl[1].permissions,
+            RolePermissions {
+                read: true,
+                write: false,
+                execute: true
+            }
+        );
+        assert!(!acl[4].permissions.read);
+        assert!(acl[5].default_scope);
+        assert_eq!(
+            PathAccessControlItem::format_list(&acl),
+            "user::rwx,user:4f2c:r-x,group::r--,mask::r-x,other::---,default:user::rwx"
+        );
+    }
+
+    #[test]
+    fn parse_access_control_item_rejects_malformed_entries() {
+        assert!("user:rwx".parse::<PathAccessControlItem>().is_err());
+        assert!("owner::rwx".parse::<PathAccessControlItem>().is_err());
+        assert!("user::rwz".parse::<PathAccessControlItem>().is_err());
+    }
+
+    #[test]
+    fn format_removal_list_omits_permissions() {
+        let acl = PathAccessControlItem::parse_list("user:4f2c:r-x,default:mask::rwx").unwrap();
+        assert_eq!(
+            PathAccessControlItem::format_removal_list(&acl),
+            "user:4f2c,default:mask"
+        );
+    }
+
+    #[test]
+    fn parse_path_permissions() {
+        let permissions: PathPermissions = "rwxr-x--T+".parse().unwrap();
+        assert!(permissions.sticky_bit);
+        assert!(permissions.extended_acl);
+        assert!(!permissions.other.execute);
+        assert_eq!(permissions.to_octal(), "1750");
+        assert_eq!(permissions.to_string(), "rwxr-x--T+");
+
+        let permissions: PathPermissions = "rw-r--r--".parse().unwrap();
+        assert_eq!(permissions.to_octal(), "0644");
+        assert!("rwxr-x".parse::<PathPermissions>().is_err());
+    }
+
+    #[test]
+    fn access_control_from_headers() {
+        let mut headers = Headers::new();
+        headers.insert(OWNER, "$superuser");
+        headers.insert(GROUP, "$superuser");
+        headers.insert(PERMISSIONS, "rwxr-x---");
+        headers.insert(ACL, "user::rwx,group::r-x,other::---");
+        let access_control = PathAccessControl::from_headers(&headers).unwrap();
+        assert_eq!(access_control.owner.as_deref(), Some("$superuser"));
+        assert_eq!(access_control.permissions.unwrap().to_octal(), "0750");
+        assert_eq!(access_control.access_control_list.len(), 3);
+    }
+}
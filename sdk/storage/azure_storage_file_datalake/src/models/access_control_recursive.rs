@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::path_list::string_or_value;
+use azure_core::fmt::SafeDebug;
+use serde::Deserialize;
+use std::fmt;
+
+/// How a recursive access control operation modifies the access control list of each path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSetAccessControlRecursiveMode {
+    /// Replaces the access control list of each path.
+    Set,
+    /// Adds or updates the given entries, leaving other entries unchanged.
+    Modify,
+    /// Removes the given entries.
+    Remove,
+}
+
+impl AsRef<str> for PathSetAccessControlRecursiveMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Set => "set",
+            Self::Modify => "modify",
+            Self::Remove => "remove",
+        }
+    }
+}
+
+impl fmt::Display for PathSetAccessControlRecursiveMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// A path that could not be updated by a recursive access control operation.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AccessControlChangeFailure {
+    /// The reason the path could not be updated.
+    #[serde(default)]
+    pub error_message: Option<String>,
+
+    /// The name of the path relative to the file system.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The type of the path, either `FILE` or `DIRECTORY`.
+    #[serde(default, rename = "type")]
+    pub path_type: Option<String>,
+}
+
+impl AccessControlChangeFailure {
+    /// Whether the failed path is a directory.
+    pub fn is_directory(&self) -> bool {
+        self.path_type
+            .as_deref()
+            .is_some_and(|t| t.eq_ignore_ascii_case("directory"))
+    }
+}
+
+/// The result of a single batch of a recursive access control operation.
+///
+/// Returned for each page by the recursive access control methods of `DataLakeDirectoryClient`.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AccessControlChangeResult {
+    /// The number of directories updated in this batch.
+    #[serde(default, deserialize_with = "string_or_value::deserialize")]
+    pub directories_successful: u64,
+
+    /// The paths that failed to update in this batch.
+    #[serde(default)]
+    pub failed_entries: Vec<AccessControlChangeFailure>,
+
+    /// The number of paths that failed to update in this batch.
+    #[serde(default, deserialize_with = "string_or_value::deserialize")]
+    pub failure_count: u64,
+
+    /// The number of files updated in this batch.
+    #[serde(default, deserialize_with = "string_or_value::deserialize")]
+    pub files_successful: u64,
+}
+
+/// Aggregated counters across all batches of a recursive access control operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccessControlChangeCounters {
+    /// The number of directories updated.
+    pub changed_directories_count: u64,
+    /// The number of files updated.
+    pub changed_files_count: u64,
+    /// The number of paths that failed to update.
+    pub failed_changes_count: u64,
+}
+
+impl AccessControlChangeCounters {
+    /// Adds the counts from a single batch.
+    pub fn add(&mut self, batch: &AccessControlChangeResult) {
+        self.changed_directories_count += batch.directories_successful;
+        self.changed_files_count += batch.files_successful;
+        self.failed_changes_count += batch.failure_count;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_change_result() {
+        let body = br#"{"directoriesSuccessful":2,"filesSuccessful":"5","failureCount":1,"failedEntries":[{"errorMessage":"This request is not authorized to perform this operation using this permission.","name":"dir/secret","type":"DIRECTORY"}]}"#;
+        let result: AccessControlChangeResult = serde_json::from_slice(body).unwrap();
+        assert_eq!(result.directories_successful, 2);
+        assert_eq!(result.files_successful, 5);
+        assert_eq!(result.failure_count, 1);
+        assert!(result.failed_entries[0].is_directory());
+
+        let mut counters = AccessControlChangeCounters::default();
+        counters.add(&result);
+        counters.add(&result);
+        assert_eq!(counters.changed_files_count, 10);
+        assert_eq!(counters.failed_changes_count, 2);
+    }
+}
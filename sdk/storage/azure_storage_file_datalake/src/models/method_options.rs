@@ -0,0 +1,377 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use azure_core::{
+    fmt::SafeDebug,
+    http::{pager::PagerOptions, ClientMethodOptions, Etag},
+    time::OffsetDateTime,
+};
+use std::{collections::HashMap, ops::Range};
+
+/// Options to be passed to `DataLakeFileSystemClient::create()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileSystemClientCreateOptions<'a> {
+    /// User-defined properties to store with the file system.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileSystemClient::delete()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileSystemClientDeleteOptions<'a> {
+    /// Specify this value to operate only on a file system if it has been modified since the specified date-time.
+    pub if_modified_since: Option<OffsetDateTime>,
+
+    /// Specify this value to operate only on a file system if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileSystemClient::get_properties()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileSystemClientGetPropertiesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileSystemClient::list_paths()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileSystemClientListPathsOptions<'a> {
+    /// The maximum number of paths to return in each page. The service returns at most 5000 paths per page.
+    pub max_results: Option<i32>,
+
+    /// Allows customization of the method call.
+    pub method_options: PagerOptions<'a>,
+
+    /// Lists only paths under this directory.
+    pub path: Option<String>,
+
+    /// Whether to list paths in all subdirectories. Defaults to `false`.
+    pub recursive: Option<bool>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+
+    /// If `true`, owner and group are returned as user principal names instead of object IDs.
+    pub user_principal_name: Option<bool>,
+}
+
+impl DataLakeFileSystemClientListPathsOptions<'_> {
+    /// Transforms this [`DataLakeFileSystemClientListPathsOptions`] into a new `DataLakeFileSystemClientListPathsOptions` that owns the underlying data, cloning it if necessary.
+    pub fn into_owned(self) -> DataLakeFileSystemClientListPathsOptions<'static> {
+        DataLakeFileSystemClientListPathsOptions {
+            max_results: self.max_results,
+            method_options: PagerOptions {
+                context: self.method_options.context.into_owned(),
+                ..self.method_options
+            },
+            path: self.path,
+            recursive: self.recursive,
+            timeout: self.timeout,
+            user_principal_name: self.user_principal_name,
+        }
+    }
+}
+
+/// Options to be passed to `DataLakeDirectoryClient::create()` and `DataLakeFileClient::create()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathCreateOptions<'a> {
+    /// The content type of the file. Ignored for directories.
+    pub content_type: Option<String>,
+
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a path if it has been modified since the specified date-time.
+    pub if_modified_since: Option<OffsetDateTime>,
+
+    /// An ETag value, or the wildcard character (*). Specify `*` to fail if the path already exists.
+    pub if_none_match: Option<Etag>,
+
+    /// Specify this value to operate only on a path if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Required if the path has an active lease.
+    pub lease_id: Option<String>,
+
+    /// User-defined properties to store with the path.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// POSIX permissions for the owner, owning group and others in symbolic (`rwxr-x---`) or octal (`0750`) notation.
+    pub permissions: Option<String>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+
+    /// A POSIX umask in octal notation, for example `0027`, applied to the permissions of the new path.
+    pub umask: Option<String>,
+}
+
+impl DataLakePathCreateOptions<'_> {
+    /// Fails the request if the path already exists.
+    pub fn if_not_exists(self) -> Self {
+        Self {
+            if_none_match: Some(Etag::from("*")),
+            ..self
+        }
+    }
+}
+
+/// Options to be passed to `DataLakeDirectoryClient::delete()` and `DataLakeFileClient::delete()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathDeleteOptions<'a> {
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a path if it has been modified since the specified date-time.
+    pub if_modified_since: Option<OffsetDateTime>,
+
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag does not match the value specified.
+    pub if_none_match: Option<Etag>,
+
+    /// Specify this value to operate only on a path if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Required if the path has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeDirectoryClient::rename()` and `DataLakeFileClient::rename()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathRenameOptions<'a> {
+    /// An ETag value the destination must match.
+    pub if_match: Option<Etag>,
+
+    /// An ETag value, or the wildcard character (*). Specify `*` to fail if the destination already exists.
+    pub if_none_match: Option<Etag>,
+
+    /// Required if the destination has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// An ETag value the source must match.
+    pub source_if_match: Option<Etag>,
+
+    /// Required if the source has an active lease.
+    pub source_lease_id: Option<String>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `get_properties()` on directory and file clients.
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathGetPropertiesOptions<'a> {
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Required if the path has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `get_access_control()` on directory and file clients.
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathGetAccessControlOptions<'a> {
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Required if the path has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+
+    /// If `true`, owner, group and ACL entity IDs are returned as user principal names instead of object IDs.
+    pub user_principal_name: Option<bool>,
+}
+
+/// Options to be passed to `set_access_control()` on directory and file clients.
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakePathSetAccessControlOptions<'a> {
+    /// The new owning group of the path.
+    pub group: Option<String>,
+
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a path if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Required if the path has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The new owning user of the path.
+    pub owner: Option<String>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to the recursive access control methods of `DataLakeDirectoryClient`.
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeDirectoryClientSetAccessControlRecursiveOptions<'a> {
+    /// The maximum number of paths processed in each batch. The service processes at most 2000 paths per batch.
+    pub batch_size: Option<i32>,
+
+    /// If `true`, continue past failures on individual paths instead of stopping at the first failed batch.
+    pub continue_on_failure: Option<bool>,
+
+    /// Allows customization of the method call.
+    ///
+    /// Set `method_options.continuation` to resume an operation that was interrupted.
+    pub method_options: PagerOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+impl DataLakeDirectoryClientSetAccessControlRecursiveOptions<'_> {
+    /// Transforms this [`DataLakeDirectoryClientSetAccessControlRecursiveOptions`] into a new `DataLakeDirectoryClientSetAccessControlRecursiveOptions` that owns the underlying data, cloning it if necessary.
+    pub fn into_owned(self) -> DataLakeDirectoryClientSetAccessControlRecursiveOptions<'static> {
+        DataLakeDirectoryClientSetAccessControlRecursiveOptions {
+            batch_size: self.batch_size,
+            continue_on_failure: self.continue_on_failure,
+            method_options: PagerOptions {
+                context: self.method_options.context.into_owned(),
+                ..self.method_options
+            },
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Options to be passed to `DataLakeFileClient::append()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileClientAppendOptions<'a> {
+    /// If `true`, the data is flushed immediately after it is appended.
+    pub flush: Option<bool>,
+
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The MD5 hash of the appended data, used to verify integrity during transport.
+    pub transactional_content_md5: Option<Vec<u8>>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileClient::flush()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileClientFlushOptions<'a> {
+    /// If `true`, a file-changed event is raised with the close flag set, indicating the final flush of a stream.
+    pub close: Option<bool>,
+
+    /// Sets the content type of the file.
+    pub content_type: Option<String>,
+
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a file if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// If `true`, uncommitted data beyond the flushed position is retained for a later flush.
+    pub retain_uncommitted_data: Option<bool>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileClient::read()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileClientReadOptions<'a> {
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag matches the value specified.
+    pub if_match: Option<Etag>,
+
+    /// Specify this value to operate only on a file if it has been modified since the specified date-time.
+    pub if_modified_since: Option<OffsetDateTime>,
+
+    /// An ETag value. Specify this header to perform the operation only if the resource's ETag does not match the value specified.
+    pub if_none_match: Option<Etag>,
+
+    /// Specify this value to operate only on a file if it has not been modified since the specified date-time.
+    pub if_unmodified_since: Option<OffsetDateTime>,
+
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The byte range of the file to read. Reads the whole file when `None`.
+    pub range: Option<Range<u64>>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `DataLakeFileClient::upload()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct DataLakeFileClientUploadOptions<'a> {
+    /// The content type of the file.
+    pub content_type: Option<String>,
+
+    /// User-defined properties to store with the file.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// If `true`, overwrites an existing file. Defaults to `false`, which fails if the file already exists.
+    pub overwrite: Option<bool>,
+
+    /// POSIX permissions for the new file in symbolic or octal notation.
+    pub permissions: Option<String>,
+
+    /// The maximum number of bytes sent in a single append request. Defaults to 4 MiB.
+    pub partition_size: Option<usize>,
+
+    /// A POSIX umask in octal notation applied to the permissions of the new file.
+    pub umask: Option<String>,
+}
@@ -0,0 +1,23 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Model types for Azure Data Lake Storage Gen2.
+
+mod access_control;
+mod access_control_recursive;
+mod method_options;
+mod path_list;
+mod path_properties;
+
+pub use access_control::{
+    AccessControlType, PathAccessControl, PathAccessControlItem, PathPermissions, RolePermissions,
+};
+pub use access_control_recursive::{
+    AccessControlChangeCounters, AccessControlChangeFailure, AccessControlChangeResult,
+    PathSetAccessControlRecursiveMode,
+};
+pub use method_options::*;
+pub use path_list::{PathItem, PathList};
+pub use path_properties::{PathProperties, PathResourceType};
+
+pub(crate) use path_properties::encode_properties;
@@ -0,0 +1,135 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use async_trait::async_trait;
+use azure_core::{fmt::SafeDebug, http::pager::Page, Result};
+use serde::{Deserialize, Deserializer};
+use std::str::FromStr;
+
+/// A path returned by `DataLakeFileSystemClient::list_paths()`.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct PathItem {
+    /// The content length of the path in bytes. Always zero for directories.
+    #[serde(default, deserialize_with = "string_or_value::option")]
+    pub content_length: Option<u64>,
+
+    /// The creation time of the path as a Windows file time.
+    #[serde(default, deserialize_with = "string_or_value::option")]
+    pub creation_time: Option<u64>,
+
+    /// The ETag of the path.
+    #[serde(default)]
+    pub etag: Option<String>,
+
+    /// The expiry time of the path as a Windows file time, or zero if the path does not expire.
+    #[serde(default, deserialize_with = "string_or_value::option")]
+    pub expiry_time: Option<u64>,
+
+    /// The owning group of the path.
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// Whether the path is a directory.
+    #[serde(default, deserialize_with = "string_or_value::option")]
+    pub is_directory: Option<bool>,
+
+    /// The last modified time of the path in RFC 1123 format.
+    #[serde(default)]
+    pub last_modified: Option<String>,
+
+    /// The name of the path relative to the file system.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// The owning user of the path.
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// The POSIX permissions of the path in symbolic notation.
+    #[serde(default)]
+    pub permissions: Option<String>,
+}
+
+/// A page of paths returned by `DataLakeFileSystemClient::list_paths()`.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+pub struct PathList {
+    /// The paths in this page.
+    #[serde(default)]
+    pub paths: Vec<PathItem>,
+}
+
+#[async_trait]
+impl Page for PathList {
+    type Item = PathItem;
+    type IntoIter = <Vec<PathItem> as IntoIterator>::IntoIter;
+    async fn into_items(self) -> Result<Self::IntoIter> {
+        Ok(self.paths.into_iter())
+    }
+}
+
+/// The Data Lake service encodes most scalar values in JSON responses as strings.
+/// These helpers accept either the string or the native JSON representation.
+pub(crate) mod string_or_value {
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrValue<T> {
+        String(String),
+        Value(T),
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr + Deserialize<'de>,
+        T::Err: std::fmt::Display,
+    {
+        match StringOrValue::<T>::deserialize(deserializer)? {
+            StringOrValue::String(s) => s.parse().map_err(serde::de::Error::custom),
+            StringOrValue::Value(v) => Ok(v),
+        }
+    }
+
+    pub fn option<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: FromStr + Deserialize<'de>,
+        T::Err: std::fmt::Display,
+    {
+        match Option::<StringOrValue<T>>::deserialize(deserializer)? {
+            Some(StringOrValue::String(s)) => s.parse().map(Some).map_err(serde::de::Error::custom),
+            Some(StringOrValue::Value(v)) => Ok(Some(v)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_path_list() {
+        let body = br#"{"paths":[
+            {"contentLength":"0","creationTime":"133500000000000000","etag":"0x8DC","group":"$superuser","isDirectory":"true","lastModified":"Tue, 02 Jan 2024 03:04:05 GMT","name":"dir","owner":"$superuser","permissions":"rwxr-x---"},
+            {"contentLength":42,"etag":"0x8DD","name":"dir/file.txt"}
+        ]}"#;
+        let list: PathList = serde_json::from_slice(body).unwrap();
+        assert_eq!(list.paths.len(), 2);
+        assert_eq!(list.paths[0].is_directory, Some(true));
+        assert_eq!(list.paths[0].content_length, Some(0));
+        assert_eq!(list.paths[0].creation_time, Some(133500000000000000));
+        assert_eq!(list.paths[1].is_directory, None);
+        assert_eq!(list.paths[1].content_length, Some(42));
+    }
+
+    #[test]
+    fn deserialize_empty_path_list() {
+        let list: PathList = serde_json::from_slice(b"{}").unwrap();
+        assert!(list.paths.is_empty());
+    }
+}
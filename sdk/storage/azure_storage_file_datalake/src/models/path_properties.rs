@@ -0,0 +1,149 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::PathAccessControl;
+use azure_core::{
+    base64,
+    error::ErrorKind,
+    fmt::SafeDebug,
+    http::{
+        headers::{HeaderName, Headers, CONTENT_LENGTH, CONTENT_TYPE, ETAG, LAST_MODIFIED},
+        Etag,
+    },
+    time::{parse_rfc7231, OffsetDateTime},
+    Error, Result,
+};
+use std::collections::HashMap;
+
+const RESOURCE_TYPE: HeaderName = HeaderName::from_static("x-ms-resource-type");
+const PROPERTIES: HeaderName = HeaderName::from_static("x-ms-properties");
+
+/// The type of resource a path refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathResourceType {
+    /// A directory.
+    Directory,
+    /// A file.
+    File,
+}
+
+impl AsRef<str> for PathResourceType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Directory => "directory",
+            Self::File => "file",
+        }
+    }
+}
+
+/// System properties, user-defined properties and access control of a path.
+#[derive(Clone, Default, SafeDebug)]
+#[non_exhaustive]
+pub struct PathProperties {
+    /// The access control of the path.
+    pub access_control: PathAccessControl,
+    /// The content length of the path in bytes.
+    pub content_length: Option<u64>,
+    /// The content type of the path.
+    pub content_type: Option<String>,
+    /// The ETag of the path.
+    pub etag: Option<Etag>,
+    /// The last modified time of the path.
+    pub last_modified: Option<OffsetDateTime>,
+    /// The user-defined properties of the path.
+    pub properties: HashMap<String, String>,
+    /// The type of resource the path refers to.
+    pub resource_type: Option<PathResourceType>,
+}
+
+impl PathProperties {
+    /// Reads the path properties from response headers.
+    pub fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            access_control: PathAccessControl::from_headers(headers)?,
+            content_length: headers.get_optional_as(&CONTENT_LENGTH)?,
+            content_type: headers.get_optional_string(&CONTENT_TYPE),
+            etag: headers.get_optional_as(&ETAG)?,
+            last_modified: headers
+                .get_optional_str(&LAST_MODIFIED)
+                .map(parse_rfc7231)
+                .transpose()?,
+            properties: headers
+                .get_optional_str(&PROPERTIES)
+                .map(decode_properties)
+                .transpose()?
+                .unwrap_or_default(),
+            resource_type: match headers.get_optional_str(&RESOURCE_TYPE) {
+                Some("directory") => Some(PathResourceType::Directory),
+                Some("file") => Some(PathResourceType::File),
+                _ => None,
+            },
+        })
+    }
+
+    /// Whether the path is a directory.
+    pub fn is_directory(&self) -> bool {
+        self.resource_type == Some(PathResourceType::Directory)
+    }
+}
+
+/// Encodes user-defined properties for the `x-ms-properties` header as `name=base64(value)` pairs.
+pub(crate) fn encode_properties(properties: &HashMap<String, String>) -> String {
+    let mut pairs: Vec<String> = properties
+        .iter()
+        .map(|(k, v)| format!("{k}={}", base64::encode(v)))
+        .collect();
+    pairs.sort();
+    pairs.join(",")
+}
+
+/// Decodes the `x-ms-properties` header.
+pub(crate) fn decode_properties(s: &str) -> Result<HashMap<String, String>> {
+    s.split(',')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (k, v) = pair.split_once('=').ok_or_else(|| {
+                Error::with_message_fn(ErrorKind::DataConversion, || {
+                    format!("invalid property {pair:?}")
+                })
+            })?;
+            let v = String::from_utf8(base64::decode(v)?)
+                .map_err(|e| Error::new(ErrorKind::DataConversion, e))?;
+            Ok((k.to_string(), v))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties_round_trip() {
+        let properties = HashMap::from([
+            ("project".to_string(), "lake".to_string()),
+            ("owner".to_string(), "data team".to_string()),
+        ]);
+        let encoded = encode_properties(&properties);
+        assert_eq!(encoded, "owner=ZGF0YSB0ZWFt,project=bGFrZQ==");
+        assert_eq!(decode_properties(&encoded).unwrap(), properties);
+    }
+
+    #[test]
+    fn path_properties_from_headers() {
+        let mut headers = Headers::new();
+        headers.insert(RESOURCE_TYPE, "directory");
+        headers.insert(ETAG, "\"0x8DC\"");
+        headers.insert(LAST_MODIFIED, "Tue, 02 Jan 2024 03:04:05 GMT");
+        headers.insert(PROPERTIES, "project=bGFrZQ==");
+        headers.insert(HeaderName::from_static("x-ms-permissions"), "rwxr-x---");
+        let properties = PathProperties::from_headers(&headers).unwrap();
+        assert!(properties.is_directory());
+        assert_eq!(properties.properties["project"], "lake");
+        assert_eq!(
+            properties.access_control.permissions.unwrap().to_octal(),
+            "0750"
+        );
+        assert!(properties.last_modified.is_some());
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+// Shared helpers for `azure_storage_file_datalake` integration tests. Each test binary that
+// declares `mod common;` compiles this module and uses only a subset of its helpers,
+// so unused items are expected.
+#![allow(dead_code)]
+
+use azure_core::{http::Url, Bytes, Result};
+use azure_core_test::Recording;
+use azure_storage_file_datalake::{
+    DataLakeFileClient, DataLakeFileSystemClient, DataLakeFileSystemClientOptions,
+};
+
+/// Takes in a Recording instance and returns a randomized file system name with prefix "filesystem" of length 16.
+///
+/// # Arguments
+///
+/// * `recording` - A reference to a Recording instance.
+pub fn get_file_system_name(recording: &Recording) -> String {
+    recording
+        .random_string::<16>(Some("filesystem"))
+        .to_ascii_lowercase()
+}
+
+/// Takes in a Recording instance and returns a randomized path name with the given prefix, of length 12.
+///
+/// # Arguments
+///
+/// * `recording` - A reference to a Recording instance.
+/// * `prefix` - The prefix of the path name, such as "dir" or "file".
+pub fn get_path_name(recording: &Recording, prefix: &str) -> String {
+    recording
+        .random_string::<12>(Some(prefix))
+        .to_ascii_lowercase()
+}
+
+/// Returns an instance of a DataLakeFileSystemClient on the hierarchical namespace test account.
+///
+/// # Arguments
+///
+/// * `recording` - A reference to a Recording instance.
+/// * `create` - Whether the file system should also be created.
+pub async fn get_file_system_client(
+    recording: &Recording,
+    create: bool,
+) -> Result<DataLakeFileSystemClient> {
+    let mut options = DataLakeFileSystemClientOptions::default();
+    recording.instrument(&mut options.client_options);
+    let account_name = recording.var("DATALAKE_AZURE_STORAGE_ACCOUNT_NAME", None);
+    let file_system_url = Url::parse(&format!(
+        "https://{}.dfs.core.windows.net/{}",
+        account_name,
+        get_file_system_name(recording)
+    ))?;
+    let file_system_client = DataLakeFileSystemClient::new(
+        file_system_url,
+        Some(recording.credential()),
+        Some(options),
+    )?;
+    if create {
+        file_system_client.create(None).await?;
+    }
+    Ok(file_system_client)
+}
+
+/// Creates a file containing the data "b'hello rusty world'", or the given data.
+///
+/// # Arguments
+///
+/// * `file_client` - A reference to the client of the file to create.
+/// * `data` - Optional data to write to the file.
+pub async fn create_test_file(file_client: &DataLakeFileClient, data: Option<&[u8]>) -> Result<()> {
+    let data = data.unwrap_or(b"hello rusty world");
+    file_client
+        .upload(Bytes::copy_from_slice(data), None)
+        .await?;
+    Ok(())
+}
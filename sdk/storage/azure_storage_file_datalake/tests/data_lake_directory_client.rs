@@ -0,0 +1,156 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+mod common;
+
+use azure_core::{http::StatusCode, Result};
+use azure_core_test::{recorded, TestContext};
+use azure_storage_file_datalake::{
+    models::{
+        AccessControlChangeCounters, AccessControlType,
+        DataLakeDirectoryClientSetAccessControlRecursiveOptions, PathAccessControlItem,
+        RolePermissions,
+    },
+    DataLakeDirectoryClient,
+};
+use common::{create_test_file, get_file_system_client, get_path_name};
+use futures::TryStreamExt;
+
+/// Creates `count` files in the directory, and `count` files in a subdirectory of it.
+async fn create_test_tree(directory_client: &DataLakeDirectoryClient, count: usize) -> Result<()> {
+    let subdirectory_client = directory_client.subdirectory_client("subdir");
+    subdirectory_client.create(None).await?;
+    for i in 0..count {
+        create_test_file(&directory_client.file_client(&format!("file{i}")), None).await?;
+        create_test_file(&subdirectory_client.file_client(&format!("file{i}")), None).await?;
+    }
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_create_directory(ctx: TestContext) -> Result<()> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_client = file_system_client.directory_client(&get_path_name(recording, "dir"));
+
+    // Act
+    directory_client.create(None).await?;
+    let subdirectory_client = directory_client.subdirectory_client("a/b");
+    subdirectory_client.create(None).await?;
+
+    // Assert
+    assert!(directory_client.get_properties(None).await?.is_directory());
+    assert!(subdirectory_client
+        .get_properties(None)
+        .await?
+        .is_directory());
+    // Intermediate directories are created implicitly
+    assert!(directory_client.subdirectory_client("a").exists().await?);
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_rename_directory(ctx: TestContext) -> Result<()> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_client = file_system_client.directory_client(&get_path_name(recording, "dir"));
+    directory_client.create(None).await?;
+    create_test_tree(&directory_client, 2).await?;
+
+    // Act
+    let new_name = get_path_name(recording, "renamed");
+    let renamed_client = directory_client.rename(&new_name, None).await?;
+
+    // Assert
+    assert!(!directory_client.exists().await?);
+    assert!(renamed_client.exists().await?);
+    assert!(renamed_client.file_client("file0").exists().await?);
+    assert!(
+        renamed_client
+            .subdirectory_client("subdir")
+            .file_client("file1")
+            .exists()
+            .await?
+    );
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_delete_directory(ctx: TestContext) -> Result<()> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_client = file_system_client.directory_client(&get_path_name(recording, "dir"));
+    directory_client.create(None).await?;
+    create_test_tree(&directory_client, 5).await?;
+
+    // Act
+    // The service may split the delete of a tree across requests linked by `x-ms-continuation`
+    directory_client.delete(None).await?;
+
+    // Assert
+    assert!(!directory_client.exists().await?);
+    let error = directory_client.delete(None).await.unwrap_err();
+    assert_eq!(Some(StatusCode::NotFound), error.http_status());
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_set_access_control_recursive(ctx: TestContext) -> Result<()> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_client = file_system_client.directory_client(&get_path_name(recording, "dir"));
+    directory_client.create(None).await?;
+    create_test_tree(&directory_client, 3).await?;
+    let access_control_list = vec![
+        PathAccessControlItem::new(AccessControlType::User, None, "rwx".parse()?),
+        PathAccessControlItem::new(AccessControlType::Group, None, "r-x".parse()?),
+        PathAccessControlItem::new(AccessControlType::Other, None, RolePermissions::default()),
+    ];
+
+    // Act
+    let mut pages = directory_client.set_access_control_recursive(
+        &access_control_list,
+        Some(DataLakeDirectoryClientSetAccessControlRecursiveOptions {
+            batch_size: Some(2),
+            ..Default::default()
+        }),
+    )?;
+    let mut page_count = 0;
+    let mut counters = AccessControlChangeCounters::default();
+    while let Some(page) = pages.try_next().await? {
+        page_count += 1;
+        counters.add(&page.into_model()?);
+    }
+
+    // Assert
+    // The directory, its subdirectory and 6 files, in batches of at most 2 paths
+    assert!(page_count >= 4, "expected several pages, got {page_count}");
+    assert_eq!(2, counters.changed_directories_count);
+    assert_eq!(6, counters.changed_files_count);
+    assert_eq!(0, counters.failed_changes_count);
+    let access_control = directory_client
+        .subdirectory_client("subdir")
+        .file_client("file2")
+        .get_access_control(None)
+        .await?;
+    assert_eq!(
+        Some("rwxr-x---"),
+        access_control
+            .permissions
+            .map(|permissions| permissions.to_string())
+            .as_deref()
+    );
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
@@ -0,0 +1,126 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+mod common;
+
+use azure_core::{http::StatusCode, Bytes};
+use azure_core_test::{recorded, TestContext};
+use azure_storage_file_datalake::models::{
+    DataLakeFileClientReadOptions, DataLakeFileClientUploadOptions, DataLakePathCreateOptions,
+};
+use common::{create_test_file, get_file_system_client, get_path_name};
+use std::error::Error;
+
+#[recorded::test]
+async fn test_create_file(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let file_client = file_system_client.file_client(&get_path_name(recording, "file"));
+
+    // Act
+    file_client.create(None).await?;
+
+    // Assert
+    let properties = file_client.get_properties(None).await?;
+    assert!(!properties.is_directory());
+    assert_eq!(Some(0), properties.content_length);
+
+    // Error Case (the file exists)
+    let error = file_client
+        .create(Some(DataLakePathCreateOptions::default().if_not_exists()))
+        .await
+        .unwrap_err();
+    assert_eq!(Some(StatusCode::Conflict), error.http_status());
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_upload_and_read(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let file_client = file_system_client.file_client(&get_path_name(recording, "file"));
+    let data = b"hello rusty world, appended in small partitions";
+
+    // Act
+    file_client
+        .upload(
+            Bytes::from_static(data),
+            Some(DataLakeFileClientUploadOptions {
+                partition_size: Some(8),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+    // Assert
+    let content = file_client.read(None).await?.into_body().collect().await?;
+    assert_eq!(Bytes::from_static(data), content);
+    let content = file_client
+        .read(Some(DataLakeFileClientReadOptions {
+            range: Some(6..11),
+            ..Default::default()
+        }))
+        .await?
+        .into_body()
+        .collect()
+        .await?;
+    assert_eq!(Bytes::from_static(b"rusty"), content);
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_rename_file(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_name = get_path_name(recording, "dir");
+    file_system_client
+        .directory_client(&directory_name)
+        .create(None)
+        .await?;
+    let file_client = file_system_client.file_client(&get_path_name(recording, "file"));
+    create_test_file(&file_client, None).await?;
+
+    // Act
+    let new_path = format!("{directory_name}/renamed");
+    let renamed_client = file_client.rename(&new_path, None).await?;
+
+    // Assert
+    assert!(!file_client.exists().await?);
+    let content = renamed_client
+        .read(None)
+        .await?
+        .into_body()
+        .collect()
+        .await?;
+    assert_eq!(Bytes::from_static(b"hello rusty world"), content);
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_delete_file(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let file_client = file_system_client.file_client(&get_path_name(recording, "file"));
+    create_test_file(&file_client, None).await?;
+
+    // Act
+    file_client.delete(None).await?;
+
+    // Assert
+    assert!(!file_client.exists().await?);
+    let error = file_client.delete(None).await.unwrap_err();
+    assert_eq!(Some(StatusCode::NotFound), error.http_status());
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
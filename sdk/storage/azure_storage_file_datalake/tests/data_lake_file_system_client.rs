@@ -0,0 +1,70 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+mod common;
+
+use azure_core::http::StatusCode;
+use azure_core_test::{recorded, TestContext};
+use azure_storage_file_datalake::models::DataLakeFileSystemClientListPathsOptions;
+use common::{create_test_file, get_file_system_client, get_path_name};
+use futures::TryStreamExt;
+use std::error::Error;
+
+#[recorded::test]
+async fn test_create_file_system(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, false).await?;
+
+    // Act
+    file_system_client.create(None).await?;
+
+    // Assert
+    assert!(file_system_client.exists().await?);
+    let error = file_system_client.create(None).await.unwrap_err();
+    assert_eq!(Some(StatusCode::Conflict), error.http_status());
+
+    file_system_client.delete(None).await?;
+    assert!(!file_system_client.exists().await?);
+    Ok(())
+}
+
+#[recorded::test]
+async fn test_list_paths(ctx: TestContext) -> Result<(), Box<dyn Error>> {
+    // Recording Setup
+    let recording = ctx.recording();
+    let file_system_client = get_file_system_client(recording, true).await?;
+    let directory_name = get_path_name(recording, "dir");
+    let directory_client = file_system_client.directory_client(&directory_name);
+    directory_client.create(None).await?;
+    for i in 0..3 {
+        create_test_file(&directory_client.file_client(&format!("file{i}")), None).await?;
+    }
+
+    // Act
+    let mut pager =
+        file_system_client.list_paths(Some(DataLakeFileSystemClientListPathsOptions {
+            recursive: Some(true),
+            max_results: Some(2),
+            ..Default::default()
+        }))?;
+    let mut names = Vec::new();
+    while let Some(path) = pager.try_next().await? {
+        names.push(path.name.unwrap());
+    }
+
+    // Assert
+    names.sort();
+    assert_eq!(
+        vec![
+            directory_name.clone(),
+            format!("{directory_name}/file0"),
+            format!("{directory_name}/file1"),
+            format!("{directory_name}/file2"),
+        ],
+        names
+    );
+
+    file_system_client.delete(None).await?;
+    Ok(())
+}
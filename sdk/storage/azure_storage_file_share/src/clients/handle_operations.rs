@@ -0,0 +1,157 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Handle operations shared by directory and file clients.
+
+use crate::models::{CloseHandlesResult, ListHandlesResponse};
+use azure_core::{
+    error::CheckSuccessOptions,
+    http::{
+        headers::HeaderName,
+        pager::{PagerContinuation, PagerOptions, PagerResult, PagerState},
+        ClientMethodOptions, Method, NoFormat, Pager, Pipeline, PipelineSendOptions, RawResponse,
+        Request, Response, Url, UrlExt, XmlFormat,
+    },
+    xml, Result,
+};
+
+const MARKER: HeaderName = HeaderName::from_static("x-ms-marker");
+const NUMBER_OF_HANDLES_CLOSED: HeaderName =
+    HeaderName::from_static("x-ms-number-of-handles-closed");
+const NUMBER_OF_HANDLES_FAILED: HeaderName =
+    HeaderName::from_static("x-ms-number-of-handles-failed");
+
+/// A borrowed view of the state needed to send handle requests for a directory or file.
+pub(crate) struct HandleOperations<'a> {
+    pub endpoint: &'a Url,
+    pub pipeline: &'a Pipeline,
+    pub version: &'a str,
+}
+
+impl HandleOperations<'_> {
+    pub fn list_handles(
+        &self,
+        recursive: Option<bool>,
+        max_results: Option<i32>,
+        timeout: Option<i32>,
+        method_options: PagerOptions<'static>,
+    ) -> Result<Pager<ListHandlesResponse, XmlFormat>> {
+        let pipeline = self.pipeline.clone();
+        let mut first_url = self.endpoint.clone();
+        let mut query_builder = first_url.query_builder();
+        query_builder.append_pair("comp", "listhandles");
+        if let Some(max_results) = max_results {
+            query_builder.set_pair("maxresults", max_results.to_string());
+        }
+        if let Some(timeout) = timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        #[derive(serde::Deserialize)]
+        struct ListHandlesPage {
+            #[serde(rename = "NextMarker")]
+            next_marker: Option<String>,
+        }
+
+        let version = self.version.to_owned();
+        Ok(Pager::new(
+            move |marker: PagerState, pager_options| {
+                let mut url = first_url.clone();
+                if let PagerState::More(marker) = marker {
+                    let mut query_builder = url.query_builder();
+                    query_builder.set_pair("marker", marker.as_ref());
+                    query_builder.build();
+                }
+                let mut request = Request::new(url, Method::Get);
+                request.insert_header("accept", "application/xml");
+                if let Some(recursive) = recursive {
+                    request.insert_header("x-ms-recursive", recursive.to_string());
+                }
+                request.insert_header("x-ms-version", &version);
+                let pipeline = pipeline.clone();
+                Box::pin(async move {
+                    let rsp = pipeline
+                        .send(
+                            &pager_options.context,
+                            &mut request,
+                            Some(PipelineSendOptions {
+                                check_success: CheckSuccessOptions {
+                                    success_codes: &[200],
+                                },
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+                    let (status, headers, body) = rsp.deconstruct();
+                    let res: ListHandlesPage = xml::from_xml(&body)?;
+                    let rsp = RawResponse::from_bytes(status, headers, body).into();
+                    Ok(match res.next_marker {
+                        Some(next_marker) if !next_marker.is_empty() => PagerResult::More {
+                            response: rsp,
+                            continuation: PagerContinuation::Token(next_marker),
+                        },
+                        _ => PagerResult::Done { response: rsp },
+                    })
+                })
+            },
+            Some(method_options),
+        ))
+    }
+
+    /// Closes handles, following `x-ms-marker` until every matching handle has been processed.
+    pub async fn force_close_handles(
+        &self,
+        handle_id: &str,
+        recursive: Option<bool>,
+        timeout: Option<i32>,
+        method_options: &ClientMethodOptions<'_>,
+    ) -> Result<CloseHandlesResult> {
+        let ctx = method_options.context.to_borrowed();
+        let mut result = CloseHandlesResult::default();
+        let mut marker: Option<String> = None;
+        loop {
+            let mut url = self.endpoint.clone();
+            let mut query_builder = url.query_builder();
+            query_builder.append_pair("comp", "forceclosehandles");
+            if let Some(marker) = marker.as_ref() {
+                query_builder.set_pair("marker", marker);
+            }
+            if let Some(timeout) = timeout {
+                query_builder.set_pair("timeout", timeout.to_string());
+            }
+            query_builder.build();
+            let mut request = Request::new(url, Method::Put);
+            request.insert_header("content-length", "0");
+            request.insert_header("x-ms-handle-id", handle_id.to_owned());
+            if let Some(recursive) = recursive {
+                request.insert_header("x-ms-recursive", recursive.to_string());
+            }
+            request.insert_header("x-ms-version", self.version.to_owned());
+            let rsp: Response<(), NoFormat> = self
+                .pipeline
+                .send(
+                    &ctx,
+                    &mut request,
+                    Some(PipelineSendOptions {
+                        check_success: CheckSuccessOptions {
+                            success_codes: &[200],
+                        },
+                        ..Default::default()
+                    }),
+                )
+                .await?
+                .into();
+            let headers = rsp.headers();
+            result.closed_handles_count += headers
+                .get_optional_as::<u64, _>(&NUMBER_OF_HANDLES_CLOSED)?
+                .unwrap_or_default();
+            result.failed_handles_count += headers
+                .get_optional_as::<u64, _>(&NUMBER_OF_HANDLES_FAILED)?
+                .unwrap_or_default();
+            match headers.get_optional_string(&MARKER) {
+                Some(next) if !next.is_empty() => marker = Some(next),
+                _ => return Ok(result),
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Clients used to communicate with Azure Files.
+
+use crate::{logging::apply_storage_logging_defaults, models::ShareTokenIntent};
+use async_trait::async_trait;
+use azure_core::{
+    credentials::TokenCredential,
+    http::{
+        policies::{auth::BearerTokenAuthorizationPolicy, Policy, PolicyResult},
+        ClientOptions, Context, Pipeline, Request, Url,
+    },
+    Result,
+};
+use std::{collections::HashMap, sync::Arc};
+
+mod handle_operations;
+mod share_client;
+mod share_directory_client;
+mod share_file_client;
+mod share_service_client;
+
+pub use share_client::{ShareClient, ShareClientOptions};
+pub use share_directory_client::ShareDirectoryClient;
+pub use share_file_client::ShareFileClient;
+pub use share_service_client::{ShareServiceClient, ShareServiceClientOptions};
+
+/// Default value for [`ShareClientOptions::version`] and [`ShareServiceClientOptions::version`].
+pub(crate) const DEFAULT_VERSION: &str = "2026-04-06";
+
+/// Adds the `x-ms-file-request-intent` header required for token-authorized data operations.
+#[derive(Debug)]
+struct FileRequestIntentPolicy(ShareTokenIntent);
+
+#[async_trait]
+impl Policy for FileRequestIntentPolicy {
+    async fn send(
+        &self,
+        ctx: &Context,
+        request: &mut Request,
+        next: &[Arc<dyn Policy>],
+    ) -> PolicyResult {
+        request.insert_header("x-ms-file-request-intent", self.0.to_string());
+        next[0].send(ctx, request, &next[1..]).await
+    }
+}
+
+/// Validates a Files endpoint and creates the pipeline shared by all clients derived from it.
+fn new_pipeline(
+    url: &Url,
+    credential: Option<Arc<dyn TokenCredential>>,
+    file_request_intent: Option<ShareTokenIntent>,
+    client_options: &mut ClientOptions,
+) -> Result<Pipeline> {
+    // Storage endpoints must be base URLs.
+    if url.cannot_be_a_base() {
+        return Err(azure_core::Error::with_message(
+            azure_core::error::ErrorKind::Other,
+            format!("{url} is not a valid base URL"),
+        ));
+    }
+
+    apply_storage_logging_defaults(client_options);
+
+    let mut per_call_policies: Vec<Arc<dyn Policy>> = Vec::default();
+    let mut per_retry_policies: Vec<Arc<dyn Policy>> = Vec::default();
+    if let Some(token_credential) = credential {
+        if !url.scheme().starts_with("https") {
+            return Err(azure_core::Error::with_message(
+                azure_core::error::ErrorKind::Other,
+                format!("{url} must use https"),
+            ));
+        }
+        per_call_policies.push(Arc::new(FileRequestIntentPolicy(
+            file_request_intent.unwrap_or(ShareTokenIntent::Backup),
+        )));
+        per_retry_policies.push(Arc::new(BearerTokenAuthorizationPolicy::new(
+            token_credential,
+            vec!["https://storage.azure.com/.default"],
+        )));
+    }
+
+    Ok(Pipeline::new(
+        option_env!("CARGO_PKG_NAME"),
+        option_env!("CARGO_PKG_VERSION"),
+        client_options.clone(),
+        per_call_policies,
+        per_retry_policies,
+        None,
+    ))
+}
+
+/// Appends a `/`-separated path relative to `base`, percent-encoding each segment.
+///
+/// The query of `base`, such as a SAS token or share snapshot, is preserved.
+fn append_path(base: &Url, path: &str) -> Url {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        // This should not fail as the base URL has already been validated on client construction.
+        .expect("Invalid endpoint URL: Cannot append path to the Files endpoint.")
+        .pop_if_empty()
+        .extend(path.split('/').filter(|s| !s.is_empty()));
+    url
+}
+
+/// Adds user-defined metadata to a request as `x-ms-meta-*` headers.
+fn insert_metadata(request: &mut Request, metadata: Option<&HashMap<String, String>>) {
+    for (k, v) in metadata.into_iter().flatten() {
+        request.insert_header(format!("x-ms-meta-{k}"), v.clone());
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, insert_metadata, new_pipeline, DEFAULT_VERSION};
+use crate::{
+    models::{
+        ShareClientCreateOptions, ShareClientCreateSnapshotOptions, ShareClientDeleteOptions,
+        ShareClientGetPropertiesOptions, ShareProperties, ShareTokenIntent,
+    },
+    ShareDirectoryClient, ShareFileClient,
+};
+use azure_core::{
+    credentials::TokenCredential,
+    error::{CheckSuccessOptions, ErrorKind},
+    fmt::SafeDebug,
+    http::{
+        headers::HeaderName, ClientOptions, Method, NoFormat, Pipeline, PipelineSendOptions,
+        Request, Response, StatusCode, Url, UrlExt,
+    },
+    tracing, Error, Result,
+};
+use std::sync::Arc;
+
+const SNAPSHOT: HeaderName = HeaderName::from_static("x-ms-snapshot");
+
+/// A client for a share in Azure Files.
+///
+/// Obtain a `ShareClient` from [`ShareServiceClient::share_client()`](crate::ShareServiceClient::share_client),
+/// or create one directly from a share URL with [`ShareClient::new()`].
+#[tracing::client]
+pub struct ShareClient {
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+/// Options used when creating a [`ShareClient`].
+#[derive(Clone, SafeDebug)]
+pub struct ShareClientOptions {
+    /// Allows customization of the client.
+    pub client_options: ClientOptions,
+    /// The intent sent with requests authorized with a token credential. Defaults to [`ShareTokenIntent::Backup`].
+    pub file_request_intent: Option<ShareTokenIntent>,
+    /// Specifies the version of the operation to use for this request.
+    pub version: String,
+}
+
+impl Default for ShareClientOptions {
+    fn default() -> Self {
+        Self {
+            client_options: ClientOptions::default(),
+            file_request_intent: None,
+            version: String::from(DEFAULT_VERSION),
+        }
+    }
+}
+
+impl ShareClient {
+    /// Creates a new `ShareClient` from a share URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `share_url` - The full URL of the share, for example `https://myaccount.file.core.windows.net/myshare`.
+    ///   The caller is responsible for percent-encoding the URL correctly; it will be used as-is.
+    /// * `credential` - An optional implementation of [`TokenCredential`] that can provide an Entra ID token to use when authenticating.
+    /// * `options` - Optional configuration for the client.
+    #[tracing::new("Storage.Files.Share")]
+    pub fn new(
+        share_url: Url,
+        credential: Option<Arc<dyn TokenCredential>>,
+        options: Option<ShareClientOptions>,
+    ) -> Result<Self> {
+        let mut options = options.unwrap_or_default();
+        let pipeline = new_pipeline(
+            &share_url,
+            credential,
+            options.file_request_intent,
+            &mut options.client_options,
+        )?;
+
+        Ok(Self {
+            endpoint: share_url,
+            version: options.version,
+            pipeline,
+        })
+    }
+
+    /// Gets the URL of the share.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Returns a new instance of [`ShareClient`] that addresses a snapshot of this share.
+    ///
+    /// Directory and file clients derived from the returned client read from the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot` - The snapshot timestamp returned by [`ShareClient::create_snapshot()`].
+    pub fn with_snapshot(&self, snapshot: &str) -> ShareClient {
+        let mut endpoint = self.endpoint.clone();
+        let mut query_builder = endpoint.query_builder();
+        query_builder.set_pair("sharesnapshot", snapshot);
+        query_builder.build();
+        ShareClient {
+            endpoint,
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Returns a new instance of [`ShareDirectoryClient`] for the root directory of the share.
+    pub fn root_directory_client(&self) -> ShareDirectoryClient {
+        self.directory_client("")
+    }
+
+    /// Returns a new instance of [`ShareDirectoryClient`] for a directory in this share.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory_path` - The `/`-separated path of the directory relative to the share root.
+    pub fn directory_client(&self, directory_path: &str) -> ShareDirectoryClient {
+        ShareDirectoryClient {
+            endpoint: append_path(&self.endpoint, directory_path),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Returns a new instance of [`ShareFileClient`] for a file in this share.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The `/`-separated path of the file relative to the share root.
+    pub fn file_client(&self, file_path: &str) -> ShareFileClient {
+        ShareFileClient {
+            endpoint: append_path(&self.endpoint, file_path),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Creates the share.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Share.create")]
+    pub async fn create(
+        &self,
+        options: Option<ShareClientCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "share");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(access_tier) = options.access_tier.as_ref() {
+            request.insert_header("x-ms-access-tier", access_tier);
+        }
+        insert_metadata(&mut request, options.metadata.as_ref());
+        if let Some(quota) = options.quota {
+            request.insert_header("x-ms-share-quota", quota.to_string());
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Marks the share for deletion. If this client addresses a snapshot, only the snapshot is deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Share.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<ShareClientDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "share");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Delete);
+        if let Some(delete_snapshots) = options.delete_snapshots {
+            request.insert_header("x-ms-delete-snapshots", delete_snapshots.to_string());
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[202],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Gets the system properties and user-defined metadata of the share.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Share.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<ShareClientGetPropertiesOptions<'_>>,
+    ) -> Result<ShareProperties> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "share");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Get);
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        ShareProperties::from_headers(rsp.headers())
+    }
+
+    /// Checks if the share exists.
+    ///
+    /// Returns `true` if the share exists, `false` if the share does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a read-only snapshot of the share.
+    ///
+    /// Returns the snapshot timestamp, which can be passed to [`ShareClient::with_snapshot()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Share.createSnapshot")]
+    pub async fn create_snapshot(
+        &self,
+        options: Option<ShareClientCreateSnapshotOptions<'_>>,
+    ) -> Result<String> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder
+            .append_pair("comp", "snapshot")
+            .append_pair("restype", "share");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        insert_metadata(&mut request, options.metadata.as_ref());
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        rsp.headers().get_optional_string(&SNAPSHOT).ok_or_else(|| {
+            Error::with_message(
+                ErrorKind::DataConversion,
+                "the service did not return the snapshot timestamp",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, HttpClient, Transport},
+        Bytes,
+    };
+    use azure_core_test::{credentials::MockCredential, http::MockHttpClient};
+    use futures::FutureExt as _;
+
+    fn share_client(mock_client: Arc<dyn HttpClient>) -> ShareClient {
+        ShareClient::new(
+            Url::parse("https://account.file.core.windows.net/share").unwrap(),
+            None,
+            Some(ShareClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn credential_requires_https() {
+        let err = ShareClient::new(
+            Url::parse("http://account.file.core.windows.net/share").unwrap(),
+            Some(MockCredential::new().unwrap()),
+            None,
+        )
+        .err()
+        .expect("expected an error");
+        assert!(err.to_string().contains("must use https"));
+    }
+
+    #[test]
+    fn snapshot_is_inherited_by_derived_clients() {
+        let mock_client = Arc::new(MockHttpClient::new(|_| {
+            async { panic!("no requests expected") }.boxed()
+        }));
+        let snapshot = share_client(mock_client).with_snapshot("2024-10-15T10:00:00.0000000Z");
+        let file = snapshot.directory_client("a/b c").file_client("d#1.txt");
+        assert_eq!(file.url().path(), "/share/a/b%20c/d%231.txt");
+        assert_eq!(
+            file.url().query(),
+            Some("sharesnapshot=2024-10-15T10%3A00%3A00.0000000Z")
+        );
+    }
+
+    #[tokio::test]
+    async fn create_snapshot_returns_timestamp() -> Result<()> {
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            assert_eq!(req.method(), Method::Put);
+            assert_eq!(req.url().query(), Some("comp=snapshot&restype=share"));
+            async {
+                let mut headers = Headers::new();
+                headers.insert(SNAPSHOT, "2024-10-15T10:00:00.0000000Z");
+                Ok(AsyncRawResponse::from_bytes(
+                    StatusCode::Created,
+                    headers,
+                    Bytes::new(),
+                ))
+            }
+            .boxed()
+        }));
+        let snapshot = share_client(mock_client).create_snapshot(None).await?;
+        assert_eq!(snapshot, "2024-10-15T10:00:00.0000000Z");
+        Ok(())
+    }
+}
@@ -0,0 +1,443 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, handle_operations::HandleOperations, insert_metadata};
+use crate::{
+    models::{
+        CloseHandlesResult, FileProperties, ListFilesAndDirectoriesResponse, ListHandlesResponse,
+        ShareDirectoryClientCreateOptions, ShareDirectoryClientDeleteOptions,
+        ShareDirectoryClientForceCloseHandlesOptions, ShareDirectoryClientGetPropertiesOptions,
+        ShareDirectoryClientListFilesAndDirectoriesOptions, ShareDirectoryClientListHandlesOptions,
+    },
+    ShareFileClient,
+};
+use azure_core::{
+    error::CheckSuccessOptions,
+    http::{
+        pager::{PagerContinuation, PagerResult, PagerState},
+        Method, NoFormat, Pager, Pipeline, PipelineSendOptions, RawResponse, Request, Response,
+        StatusCode, Url, UrlExt, XmlFormat,
+    },
+    tracing, xml, Result,
+};
+
+/// A client for a directory in an Azure Files share.
+///
+/// Obtain a `ShareDirectoryClient` from [`ShareClient::directory_client()`](crate::ShareClient::directory_client)
+/// or [`ShareClient::root_directory_client()`](crate::ShareClient::root_directory_client).
+#[tracing::client]
+pub struct ShareDirectoryClient {
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+impl ShareDirectoryClient {
+    /// Gets the URL of the directory.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Returns a new instance of [`ShareDirectoryClient`] for a directory beneath this one.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory_path` - The `/`-separated path of the subdirectory relative to this directory.
+    pub fn subdirectory_client(&self, directory_path: &str) -> ShareDirectoryClient {
+        ShareDirectoryClient {
+            endpoint: append_path(&self.endpoint, directory_path),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Returns a new instance of [`ShareFileClient`] for a file in this directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_path` - The `/`-separated path of the file relative to this directory.
+    pub fn file_client(&self, file_path: &str) -> ShareFileClient {
+        ShareFileClient {
+            endpoint: append_path(&self.endpoint, file_path),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Creates the directory. The parent directory must already exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.create")]
+    pub async fn create(
+        &self,
+        options: Option<ShareDirectoryClientCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "directory");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(file_attributes) = options.file_attributes.as_ref() {
+            request.insert_header("x-ms-file-attributes", file_attributes);
+        }
+        if let Some(file_permission) = options.file_permission.as_ref() {
+            request.insert_header("x-ms-file-permission", file_permission);
+        }
+        insert_metadata(&mut request, options.metadata.as_ref());
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Deletes the directory. The directory must be empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<ShareDirectoryClientDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "directory");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Delete);
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[202],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Gets the system properties and user-defined metadata of the directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<ShareDirectoryClientGetPropertiesOptions<'_>>,
+    ) -> Result<FileProperties> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("restype", "directory");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Get);
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        FileProperties::from_headers(rsp.headers())
+    }
+
+    /// Checks if the directory exists.
+    ///
+    /// Returns `true` if the directory exists, `false` if the directory does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the files and subdirectories directly beneath this directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.listFilesAndDirectories")]
+    pub fn list_files_and_directories(
+        &self,
+        options: Option<ShareDirectoryClientListFilesAndDirectoriesOptions<'_>>,
+    ) -> Result<Pager<ListFilesAndDirectoriesResponse, XmlFormat>> {
+        let options = options.unwrap_or_default().into_owned();
+        let pipeline = self.pipeline.clone();
+        let mut first_url = self.endpoint.clone();
+        let mut query_builder = first_url.query_builder();
+        query_builder
+            .append_pair("comp", "list")
+            .append_pair("restype", "directory");
+        if let Some(include) = options.include.as_ref() {
+            query_builder.set_pair(
+                "include",
+                include
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            );
+        }
+        if let Some(max_results) = options.max_results {
+            query_builder.set_pair("maxresults", max_results.to_string());
+        }
+        if let Some(prefix) = options.prefix.as_ref() {
+            query_builder.set_pair("prefix", prefix);
+        }
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        #[derive(serde::Deserialize)]
+        struct ListFilesAndDirectoriesPage {
+            #[serde(rename = "NextMarker")]
+            next_marker: Option<String>,
+        }
+
+        let include_extended_info = options.include_extended_info;
+        let version = self.version.clone();
+        Ok(Pager::new(
+            move |marker: PagerState, pager_options| {
+                let mut url = first_url.clone();
+                if let PagerState::More(marker) = marker {
+                    let mut query_builder = url.query_builder();
+                    query_builder.set_pair("marker", marker.as_ref());
+                    query_builder.build();
+                }
+                let mut request = Request::new(url, Method::Get);
+                request.insert_header("accept", "application/xml");
+                if let Some(include_extended_info) = include_extended_info {
+                    request.insert_header(
+                        "x-ms-file-extended-info",
+                        include_extended_info.to_string(),
+                    );
+                }
+                request.insert_header("x-ms-version", &version);
+                let pipeline = pipeline.clone();
+                Box::pin(async move {
+                    let rsp = pipeline
+                        .send(
+                            &pager_options.context,
+                            &mut request,
+                            Some(PipelineSendOptions {
+                                check_success: CheckSuccessOptions {
+                                    success_codes: &[200],
+                                },
+                                ..Default::default()
+                            }),
+                        )
+                        .await?;
+                    let (status, headers, body) = rsp.deconstruct();
+                    let res: ListFilesAndDirectoriesPage = xml::from_xml(&body)?;
+                    let rsp = RawResponse::from_bytes(status, headers, body).into();
+                    Ok(match res.next_marker {
+                        Some(next_marker) if !next_marker.is_empty() => PagerResult::More {
+                            response: rsp,
+                            continuation: PagerContinuation::Token(next_marker),
+                        },
+                        _ => PagerResult::Done { response: rsp },
+                    })
+                })
+            },
+            Some(options.method_options),
+        ))
+    }
+
+    /// Lists the open SMB handles on the directory, and optionally on the files and subdirectories beneath it.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.listHandles")]
+    pub fn list_handles(
+        &self,
+        options: Option<ShareDirectoryClientListHandlesOptions<'_>>,
+    ) -> Result<Pager<ListHandlesResponse, XmlFormat>> {
+        let options = options.unwrap_or_default().into_owned();
+        self.handle_operations().list_handles(
+            options.recursive,
+            options.max_results,
+            options.timeout,
+            options.method_options,
+        )
+    }
+
+    /// Force-closes an open SMB handle on the directory.
+    ///
+    /// Pass `"*"` as the `handle_id` to close all handles. The service may require several requests to close
+    /// every handle; this method follows the continuation until all handles have been processed and returns
+    /// the totals.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle_id` - The ID of the handle to close, as returned by [`ShareDirectoryClient::list_handles()`], or `"*"`.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.Directory.forceCloseHandles")]
+    pub async fn force_close_handles(
+        &self,
+        handle_id: &str,
+        options: Option<ShareDirectoryClientForceCloseHandlesOptions<'_>>,
+    ) -> Result<CloseHandlesResult> {
+        let options = options.unwrap_or_default();
+        self.handle_operations()
+            .force_close_handles(
+                handle_id,
+                options.recursive,
+                options.timeout,
+                &options.method_options,
+            )
+            .await
+    }
+
+    fn handle_operations(&self) -> HandleOperations<'_> {
+        HandleOperations {
+            endpoint: &self.endpoint,
+            pipeline: &self.pipeline,
+            version: &self.version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{
+            DirectoryEntry, ListFilesIncludeType,
+            ShareDirectoryClientListFilesAndDirectoriesOptions,
+        },
+        ShareClient, ShareClientOptions,
+    };
+    use azure_core::{
+        http::{
+            headers::Headers, AsyncRawResponse, ClientOptions, HttpClient, Method, StatusCode,
+            Transport, Url,
+        },
+        Bytes, Result,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use futures::{FutureExt as _, TryStreamExt as _};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn share_client(mock_client: Arc<dyn HttpClient>) -> ShareClient {
+        ShareClient::new(
+            Url::parse("https://account.file.core.windows.net/share").unwrap(),
+            None,
+            Some(ShareClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_files_and_directories_follows_marker() -> Result<()> {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mock_client = Arc::new(MockHttpClient::new({
+            let calls = calls.clone();
+            move |req| {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(req.method(), Method::Get);
+                let query = req.url().query().unwrap_or_default().to_owned();
+                async move {
+                    let body = if call == 0 {
+                        assert_eq!(
+                            query,
+                            "comp=list&include=Timestamps%2CETag&restype=directory"
+                        );
+                        r#"<?xml version="1.0" encoding="utf-8"?><EnumerationResults ServiceEndpoint="https://account.file.core.windows.net/" ShareName="share" DirectoryPath="dir"><Entries><Directory><Name>sub</Name></Directory></Entries><NextMarker>m1</NextMarker></EnumerationResults>"#
+                    } else {
+                        assert!(query.contains("&marker=m1&"), "{query}");
+                        r#"<?xml version="1.0" encoding="utf-8"?><EnumerationResults ServiceEndpoint="https://account.file.core.windows.net/" ShareName="share" DirectoryPath="dir"><Entries><File><Name>a.txt</Name><Properties><Content-Length>3</Content-Length></Properties></File></Entries><NextMarker /></EnumerationResults>"#
+                    };
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        Headers::new(),
+                        Bytes::from_static(body.as_bytes()),
+                    ))
+                }
+                .boxed()
+            }
+        }));
+
+        let directory = share_client(mock_client).directory_client("dir");
+        let mut pages = directory
+            .list_files_and_directories(Some(ShareDirectoryClientListFilesAndDirectoriesOptions {
+                include: Some(vec![
+                    ListFilesIncludeType::Timestamps,
+                    ListFilesIncludeType::Etag,
+                ]),
+                ..Default::default()
+            }))?
+            .into_pages();
+        let mut names = Vec::new();
+        while let Some(page) = pages.try_next().await? {
+            for entry in page.into_model()?.entries {
+                names.push((
+                    entry.name().unwrap_or_default().to_owned(),
+                    entry.is_directory(),
+                ));
+                if let DirectoryEntry::File(file) = entry {
+                    assert_eq!(file.properties.and_then(|p| p.content_length), Some(3));
+                }
+            }
+        }
+        assert_eq!(
+            names,
+            vec![("sub".to_owned(), true), ("a.txt".to_owned(), false)]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,717 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{handle_operations::HandleOperations, insert_metadata};
+use crate::models::{
+    CloseHandlesResult, FileProperties, ListHandlesResponse, ShareFileClientClearRangeOptions,
+    ShareFileClientCreateOptions, ShareFileClientDeleteOptions, ShareFileClientDownloadOptions,
+    ShareFileClientForceCloseHandlesOptions, ShareFileClientGetPropertiesOptions,
+    ShareFileClientListHandlesOptions, ShareFileClientUploadOptions,
+    ShareFileClientUploadRangeOptions,
+};
+use azure_core::{
+    base64,
+    error::CheckSuccessOptions,
+    http::{
+        headers::{HeaderName, CONTENT_LENGTH, ETAG},
+        AsyncRawResponse, AsyncResponse, Context, Method, NoFormat, Pager, Pipeline,
+        PipelineSendOptions, PipelineStreamOptions, Request, RequestContent, Response, StatusCode,
+        Url, UrlExt, XmlFormat,
+    },
+    tracing, Bytes, Result,
+};
+use futures::{stream, StreamExt as _, TryStreamExt as _};
+use std::{num::NonZero, ops::Range, sync::Arc};
+
+const CONTENT_RANGE: HeaderName = HeaderName::from_static("content-range");
+
+/// Default and maximum size of each range in a partitioned transfer (4 MiB).
+// unwrap evaluated at compile time
+const MAX_RANGE_SIZE: NonZero<usize> = NonZero::new(4 * 1024 * 1024).unwrap();
+
+/// Returns the default concurrency for partitioned uploads and downloads.
+///
+/// Formula: `min(max(available_parallelism, 8), 96)`
+fn default_concurrency() -> NonZero<usize> {
+    let cpus = std::thread::available_parallelism()
+        .map(NonZero::get)
+        .unwrap_or(1);
+    // SAFETY: clamp lower-bound is 8, always non-zero.
+    NonZero::new(cpus.clamp(8, 96)).unwrap()
+}
+
+/// Formats a half-open byte range as the inclusive `x-ms-range` header value.
+fn range_header(range: &Range<u64>) -> String {
+    format!("bytes={}-{}", range.start, range.end - 1)
+}
+
+/// A client for a file in an Azure Files share.
+///
+/// Obtain a `ShareFileClient` from [`ShareClient::file_client()`](crate::ShareClient::file_client)
+/// or [`ShareDirectoryClient::file_client()`](crate::ShareDirectoryClient::file_client).
+#[tracing::client]
+pub struct ShareFileClient {
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+impl ShareFileClient {
+    /// Gets the URL of the file.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Creates a new file, or replaces an existing file, with the given size.
+    ///
+    /// The file content is initialized to zeros; write content with [`ShareFileClient::upload_range()`].
+    ///
+    /// # Arguments
+    ///
+    /// * `file_size` - The size of the file in bytes.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.create")]
+    pub async fn create(
+        &self,
+        file_size: u64,
+        options: Option<ShareFileClientCreateOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        if let Some(timeout) = options.timeout {
+            let mut query_builder = url.query_builder();
+            query_builder.set_pair("timeout", timeout.to_string());
+            query_builder.build();
+        }
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        request.insert_header("x-ms-content-length", file_size.to_string());
+        if let Some(content_type) = options.content_type.as_ref() {
+            request.insert_header("x-ms-content-type", content_type);
+        }
+        if let Some(file_attributes) = options.file_attributes.as_ref() {
+            request.insert_header("x-ms-file-attributes", file_attributes);
+        }
+        if let Some(file_permission) = options.file_permission.as_ref() {
+            request.insert_header("x-ms-file-permission", file_permission);
+        }
+        insert_metadata(&mut request, options.metadata.as_ref());
+        request.insert_header("x-ms-type", "file");
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Deletes the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.delete")]
+    pub async fn delete(
+        &self,
+        options: Option<ShareFileClientDeleteOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        if let Some(timeout) = options.timeout {
+            let mut query_builder = url.query_builder();
+            query_builder.set_pair("timeout", timeout.to_string());
+            query_builder.build();
+        }
+        let mut request = Request::new(url, Method::Delete);
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[202],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Gets the system properties and user-defined metadata of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.getProperties")]
+    pub async fn get_properties(
+        &self,
+        options: Option<ShareFileClientGetPropertiesOptions<'_>>,
+    ) -> Result<FileProperties> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        if let Some(timeout) = options.timeout {
+            let mut query_builder = url.query_builder();
+            query_builder.set_pair("timeout", timeout.to_string());
+            query_builder.build();
+        }
+        let mut request = Request::new(url, Method::Head);
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-version", &self.version);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        FileProperties::from_headers(rsp.headers())
+    }
+
+    /// Checks if the file exists.
+    ///
+    /// Returns `true` if the file exists, `false` if the file does not exist, and propagates all other errors.
+    pub async fn exists(&self) -> Result<bool> {
+        match self.get_properties(None).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.http_status() == Some(StatusCode::NotFound) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes a range of bytes to the file. The range may not exceed 4 MiB.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The position in the file at which to write the data.
+    /// * `content_length` - The length of the data in bytes.
+    /// * `data` - The data to write.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.uploadRange")]
+    pub async fn upload_range(
+        &self,
+        offset: u64,
+        content_length: u64,
+        data: RequestContent<Bytes, NoFormat>,
+        options: Option<ShareFileClientUploadRangeOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("comp", "range");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", content_length.to_string());
+        if let Some(transactional_content_md5) = options.transactional_content_md5 {
+            request.insert_header("content-md5", base64::encode(transactional_content_md5));
+        }
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header(
+            "x-ms-range",
+            range_header(&(offset..offset + content_length)),
+        );
+        request.insert_header("x-ms-version", &self.version);
+        request.insert_header("x-ms-write", "update");
+        request.set_body(data);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Clears a range of the file, releasing the storage it used. Cleared ranges read as zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - The byte range to clear.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.clearRange")]
+    pub async fn clear_range(
+        &self,
+        range: Range<u64>,
+        options: Option<ShareFileClientClearRangeOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder.append_pair("comp", "range");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Put);
+        request.insert_header("content-length", "0");
+        if let Some(lease_id) = options.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        request.insert_header("x-ms-range", range_header(&range));
+        request.insert_header("x-ms-version", &self.version);
+        request.insert_header("x-ms-write", "clear");
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[201],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+
+    /// Downloads the file, or a range of it.
+    ///
+    /// Large files are downloaded as several ranges fetched concurrently and streamed back in order.
+    /// Every range after the first is conditioned on the ETag of the first response, so the download
+    /// fails rather than mixing content if the file changes part-way through.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.download")]
+    pub async fn download(
+        &self,
+        options: Option<ShareFileClientDownloadOptions<'_>>,
+    ) -> Result<AsyncResponse> {
+        let options = options.unwrap_or_default();
+        let partition_size = options.partition_size.unwrap_or(MAX_RANGE_SIZE).get() as u64;
+        let parallel = options.parallel.unwrap_or_else(default_concurrency).get();
+        let requested = options.range.clone().unwrap_or(0..u64::MAX);
+        let downloader = Arc::new(RangeDownloader {
+            context: options.method_options.context.into_owned(),
+            endpoint: self.endpoint.clone(),
+            lease_id: options.lease_id,
+            pipeline: self.pipeline.clone(),
+            timeout: options.timeout,
+            version: self.version.clone(),
+        });
+
+        // A ranged request on an empty file fails, so fall back to downloading it whole.
+        let first_end = requested
+            .end
+            .min(requested.start.saturating_add(partition_size));
+        let initial = match downloader.get(Some(requested.start..first_end), None).await {
+            Ok(rsp) => rsp,
+            Err(err)
+                if requested.start == 0
+                    && err.http_status() == Some(StatusCode::RequestedRangeNotSatisfiable) =>
+            {
+                return Ok(downloader.get(None, None).await?.into());
+            }
+            Err(err) => return Err(err),
+        };
+
+        let (status, mut headers, body) = initial.deconstruct();
+        let Some(file_size) = headers
+            .get_optional_str(&CONTENT_RANGE)
+            .and_then(|v| v.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse::<u64>().ok())
+        else {
+            return Ok(AsyncRawResponse::new(status, headers, Box::pin(body)).into());
+        };
+        let end = requested.end.min(file_size);
+        let etag = headers.get_optional_string(&ETAG);
+        let remaining = (first_end..end)
+            .step_by(partition_size as usize)
+            .map(move |start| start..end.min(start.saturating_add(partition_size)));
+
+        let rest = stream::iter(remaining)
+            .map(move |range| {
+                let downloader = downloader.clone();
+                let etag = etag.clone();
+                async move {
+                    downloader
+                        .get(Some(range), etag)
+                        .await?
+                        .into_body()
+                        .collect()
+                        .await
+                }
+            })
+            .buffered(parallel);
+
+        let status = if options.range.is_some() {
+            headers.insert(
+                CONTENT_RANGE,
+                format!("bytes {}-{}/{file_size}", requested.start, end - 1),
+            );
+            status
+        } else {
+            headers.remove(CONTENT_RANGE);
+            StatusCode::Ok
+        };
+        headers.insert(CONTENT_LENGTH, (end - requested.start).to_string());
+        Ok(AsyncRawResponse::new(status, headers, Box::pin(body.chain(rest))).into())
+    }
+
+    /// Uploads data as the entire content of the file, creating or replacing the file.
+    ///
+    /// The data is written as ranges of at most 4 MiB which are uploaded concurrently.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of the file.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.upload")]
+    pub async fn upload(
+        &self,
+        data: Bytes,
+        options: Option<ShareFileClientUploadOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let options = options.unwrap_or_default();
+        let partition_size = options
+            .partition_size
+            .unwrap_or(MAX_RANGE_SIZE)
+            .min(MAX_RANGE_SIZE)
+            .get();
+        let parallel = options.parallel.unwrap_or_else(default_concurrency).get();
+
+        let rsp = self
+            .create(
+                data.len() as u64,
+                Some(ShareFileClientCreateOptions {
+                    content_type: options.content_type,
+                    metadata: options.metadata,
+                    method_options: options.method_options.clone(),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+
+        let method_options = &options.method_options;
+        let data = &data;
+        stream::iter((0..data.len()).step_by(partition_size).map(Ok))
+            .try_for_each_concurrent(parallel, |offset| async move {
+                let end = usize::min(offset + partition_size, data.len());
+                self.upload_range(
+                    offset as u64,
+                    (end - offset) as u64,
+                    data.slice(offset..end).into(),
+                    Some(ShareFileClientUploadRangeOptions {
+                        method_options: method_options.clone(),
+                        ..Default::default()
+                    }),
+                )
+                .await
+                .map(|_| ())
+            })
+            .await?;
+        Ok(rsp)
+    }
+
+    /// Lists the open SMB handles on the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.listHandles")]
+    pub fn list_handles(
+        &self,
+        options: Option<ShareFileClientListHandlesOptions<'_>>,
+    ) -> Result<Pager<ListHandlesResponse, XmlFormat>> {
+        let options = options.unwrap_or_default().into_owned();
+        self.handle_operations().list_handles(
+            None,
+            options.max_results,
+            options.timeout,
+            options.method_options,
+        )
+    }
+
+    /// Force-closes an open SMB handle on the file.
+    ///
+    /// Pass `"*"` as the `handle_id` to close all handles on the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `handle_id` - The ID of the handle to close, as returned by [`ShareFileClient::list_handles()`], or `"*"`.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.File.forceCloseHandles")]
+    pub async fn force_close_handles(
+        &self,
+        handle_id: &str,
+        options: Option<ShareFileClientForceCloseHandlesOptions<'_>>,
+    ) -> Result<CloseHandlesResult> {
+        let options = options.unwrap_or_default();
+        self.handle_operations()
+            .force_close_handles(handle_id, None, options.timeout, &options.method_options)
+            .await
+    }
+
+    fn handle_operations(&self) -> HandleOperations<'_> {
+        HandleOperations {
+            endpoint: &self.endpoint,
+            pipeline: &self.pipeline,
+            version: &self.version,
+        }
+    }
+}
+
+/// The owned state needed to fetch ranges of a file from a detached download stream.
+struct RangeDownloader {
+    context: Context<'static>,
+    endpoint: Url,
+    lease_id: Option<String>,
+    pipeline: Pipeline,
+    timeout: Option<i32>,
+    version: String,
+}
+
+impl RangeDownloader {
+    async fn get(
+        &self,
+        range: Option<Range<u64>>,
+        if_match: Option<String>,
+    ) -> Result<AsyncRawResponse> {
+        let mut url = self.endpoint.clone();
+        if let Some(timeout) = self.timeout {
+            let mut query_builder = url.query_builder();
+            query_builder.set_pair("timeout", timeout.to_string());
+            query_builder.build();
+        }
+        let mut request = Request::new(url, Method::Get);
+        if let Some(if_match) = if_match {
+            request.insert_header("if-match", if_match);
+        }
+        if let Some(lease_id) = self.lease_id.as_ref() {
+            request.insert_header("x-ms-lease-id", lease_id);
+        }
+        if let Some(range) = range.as_ref() {
+            request.insert_header("x-ms-range", range_header(range));
+        }
+        request.insert_header("x-ms-version", &self.version);
+        self.pipeline
+            .stream(
+                &self.context,
+                &mut request,
+                Some(PipelineStreamOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200, 206],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{ShareFileClientDownloadOptions, ShareFileClientUploadOptions},
+        ShareClient, ShareClientOptions,
+    };
+    use azure_core::{
+        http::{
+            headers::{HeaderName, Headers},
+            AsyncRawResponse, ClientOptions, HttpClient, Method, StatusCode, Transport, Url,
+        },
+        Bytes, Result,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use futures::FutureExt as _;
+    use std::{
+        num::NonZero,
+        sync::{Arc, Mutex},
+    };
+
+    fn share_client(mock_client: Arc<dyn HttpClient>) -> ShareClient {
+        ShareClient::new(
+            Url::parse("https://account.file.core.windows.net/share").unwrap(),
+            None,
+            Some(ShareClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    fn header(req: &azure_core::http::Request, name: &'static str) -> Option<String> {
+        req.headers()
+            .get_optional_string(&HeaderName::from_static(name))
+    }
+
+    #[tokio::test]
+    async fn upload_creates_file_and_writes_ranges() -> Result<()> {
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let mock_client = Arc::new(MockHttpClient::new({
+            let ranges = ranges.clone();
+            move |req| {
+                assert_eq!(req.method(), Method::Put);
+                if req.url().query() == Some("comp=range") {
+                    assert_eq!(header(req, "x-ms-write").as_deref(), Some("update"));
+                    let body: Bytes = req.body().into();
+                    ranges
+                        .lock()
+                        .unwrap()
+                        .push((header(req, "x-ms-range").unwrap(), body));
+                } else {
+                    assert_eq!(header(req, "x-ms-type").as_deref(), Some("file"));
+                    assert_eq!(header(req, "x-ms-content-length").as_deref(), Some("10"));
+                }
+                async {
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Created,
+                        Headers::new(),
+                        Bytes::new(),
+                    ))
+                }
+                .boxed()
+            }
+        }));
+
+        let file = share_client(mock_client).file_client("dir/file.txt");
+        file.upload(
+            Bytes::from_static(b"0123456789"),
+            Some(ShareFileClientUploadOptions {
+                partition_size: NonZero::new(4),
+                ..Default::default()
+            }),
+        )
+        .await?;
+
+        let mut ranges = ranges.lock().unwrap().clone();
+        ranges.sort();
+        assert_eq!(
+            ranges,
+            vec![
+                ("bytes=0-3".to_owned(), Bytes::from_static(b"0123")),
+                ("bytes=4-7".to_owned(), Bytes::from_static(b"4567")),
+                ("bytes=8-9".to_owned(), Bytes::from_static(b"89")),
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn download_reassembles_ranges_in_order() -> Result<()> {
+        const DATA: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            assert_eq!(req.method(), Method::Get);
+            let range = header(req, "x-ms-range").unwrap();
+            let if_match = header(req, "if-match");
+            let (start, end) = range
+                .trim_start_matches("bytes=")
+                .split_once('-')
+                .map(|(s, e)| (s.parse::<usize>().unwrap(), e.parse::<usize>().unwrap()))
+                .unwrap();
+            async move {
+                if start > 0 {
+                    assert_eq!(if_match.as_deref(), Some("\"etag\""));
+                    // Delay earlier ranges so they complete out of order.
+                    tokio::time::sleep(std::time::Duration::from_millis((30 - start) as u64)).await;
+                }
+                let end = end.min(DATA.len() - 1);
+                let mut headers = Headers::new();
+                headers.insert(
+                    "content-range",
+                    format!("bytes {start}-{end}/{}", DATA.len()),
+                );
+                headers.insert("etag", "\"etag\"");
+                Ok(AsyncRawResponse::from_bytes(
+                    StatusCode::PartialContent,
+                    headers,
+                    Bytes::copy_from_slice(&DATA[start..=end]),
+                ))
+            }
+            .boxed()
+        }));
+
+        let file = share_client(mock_client).file_client("file.txt");
+        let rsp = file
+            .download(Some(ShareFileClientDownloadOptions {
+                parallel: NonZero::new(4),
+                partition_size: NonZero::new(5),
+                ..Default::default()
+            }))
+            .await?;
+        assert_eq!(rsp.status(), StatusCode::Ok);
+        assert_eq!(
+            rsp.headers()
+                .get_optional_string(&HeaderName::from_static("content-length"))
+                .as_deref(),
+            Some("26")
+        );
+        let body = rsp.into_body().collect().await?;
+        assert_eq!(body.as_ref(), DATA);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn download_empty_file_falls_back_to_unranged_get() -> Result<()> {
+        let mock_client = Arc::new(MockHttpClient::new(|req| {
+            let ranged = header(req, "x-ms-range").is_some();
+            async move {
+                if ranged {
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::RequestedRangeNotSatisfiable,
+                        Headers::new(),
+                        Bytes::new(),
+                    ))
+                } else {
+                    Ok(AsyncRawResponse::from_bytes(
+                        StatusCode::Ok,
+                        Headers::new(),
+                        Bytes::new(),
+                    ))
+                }
+            }
+            .boxed()
+        }));
+
+        let file = share_client(mock_client).file_client("empty.txt");
+        let body = file.download(None).await?.into_body().collect().await?;
+        assert!(body.is_empty());
+        Ok(())
+    }
+}
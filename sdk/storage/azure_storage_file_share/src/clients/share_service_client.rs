@@ -0,0 +1,146 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{append_path, new_pipeline, DEFAULT_VERSION};
+use crate::{
+    models::{
+        KeyInfo, ShareServiceClientGetUserDelegationKeyOptions, ShareTokenIntent, UserDelegationKey,
+    },
+    ShareClient,
+};
+use azure_core::{
+    credentials::TokenCredential,
+    error::CheckSuccessOptions,
+    fmt::SafeDebug,
+    http::{
+        ClientOptions, Method, Pipeline, PipelineSendOptions, Request, RequestContent, Response,
+        Url, UrlExt, XmlFormat,
+    },
+    tracing, Result,
+};
+use std::sync::Arc;
+
+/// A client for the Files service of a storage account.
+#[tracing::client]
+pub struct ShareServiceClient {
+    pub(crate) endpoint: Url,
+    pub(crate) pipeline: Pipeline,
+    pub(crate) version: String,
+}
+
+/// Options used when creating a [`ShareServiceClient`].
+#[derive(Clone, SafeDebug)]
+pub struct ShareServiceClientOptions {
+    /// Allows customization of the client.
+    pub client_options: ClientOptions,
+    /// The intent sent with requests authorized with a token credential. Defaults to [`ShareTokenIntent::Backup`].
+    pub file_request_intent: Option<ShareTokenIntent>,
+    /// Specifies the version of the operation to use for this request.
+    pub version: String,
+}
+
+impl Default for ShareServiceClientOptions {
+    fn default() -> Self {
+        Self {
+            client_options: ClientOptions::default(),
+            file_request_intent: None,
+            version: String::from(DEFAULT_VERSION),
+        }
+    }
+}
+
+impl ShareServiceClient {
+    /// Creates a new `ShareServiceClient` from the Files service URL of a storage account.
+    ///
+    /// # Arguments
+    ///
+    /// * `service_url` - The URL of the Files service, for example `https://myaccount.file.core.windows.net/`.
+    ///   The caller is responsible for percent-encoding the URL correctly; it will be used as-is.
+    /// * `credential` - An optional implementation of [`TokenCredential`] that can provide an Entra ID token to use when authenticating.
+    /// * `options` - Optional configuration for the client.
+    #[tracing::new("Storage.Files.ShareService")]
+    pub fn new(
+        service_url: Url,
+        credential: Option<Arc<dyn TokenCredential>>,
+        options: Option<ShareServiceClientOptions>,
+    ) -> Result<Self> {
+        let mut options = options.unwrap_or_default();
+        let pipeline = new_pipeline(
+            &service_url,
+            credential,
+            options.file_request_intent,
+            &mut options.client_options,
+        )?;
+
+        Ok(Self {
+            endpoint: service_url,
+            version: options.version,
+            pipeline,
+        })
+    }
+
+    /// Gets the URL of the Files service.
+    pub fn url(&self) -> &Url {
+        &self.endpoint
+    }
+
+    /// Returns a new instance of [`ShareClient`] for a share in this account.
+    ///
+    /// # Arguments
+    ///
+    /// * `share_name` - The name of the share.
+    pub fn share_client(&self, share_name: &str) -> ShareClient {
+        ShareClient {
+            endpoint: append_path(&self.endpoint, share_name),
+            pipeline: self.pipeline.clone(),
+            version: self.version.clone(),
+            tracer: self.tracer.clone(),
+        }
+    }
+
+    /// Retrieves a user delegation key for the Files service, which can be used to sign a user delegation SAS.
+    ///
+    /// This operation requires a token credential.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_info` - The start and expiry of the key.
+    /// * `options` - Optional parameters for the request.
+    #[tracing::function("Storage.Files.ShareServiceClient.getUserDelegationKey")]
+    pub async fn get_user_delegation_key(
+        &self,
+        key_info: RequestContent<KeyInfo, XmlFormat>,
+        options: Option<ShareServiceClientGetUserDelegationKeyOptions<'_>>,
+    ) -> Result<Response<UserDelegationKey, XmlFormat>> {
+        let options = options.unwrap_or_default();
+        let ctx = options.method_options.context.to_borrowed();
+        let mut url = self.endpoint.clone();
+        let mut query_builder = url.query_builder();
+        query_builder
+            .append_pair("comp", "userdelegationkey")
+            .append_pair("restype", "service");
+        if let Some(timeout) = options.timeout {
+            query_builder.set_pair("timeout", timeout.to_string());
+        }
+        query_builder.build();
+        let mut request = Request::new(url, Method::Post);
+        request.insert_header("accept", "application/xml");
+        request.insert_header("content-type", "application/xml");
+        request.insert_header("x-ms-version", &self.version);
+        request.set_body(key_info);
+        let rsp = self
+            .pipeline
+            .send(
+                &ctx,
+                &mut request,
+                Some(PipelineSendOptions {
+                    check_success: CheckSuccessOptions {
+                        success_codes: &[200],
+                    },
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        Ok(rsp.into())
+    }
+}
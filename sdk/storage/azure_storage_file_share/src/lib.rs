@@ -0,0 +1,15 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+#![doc = include_str!("../README.md")]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+mod logging;
+
+pub mod clients;
+pub mod models;
+
+pub use clients::{
+    ShareClient, ShareClientOptions, ShareDirectoryClient, ShareFileClient, ShareServiceClient,
+    ShareServiceClientOptions,
+};
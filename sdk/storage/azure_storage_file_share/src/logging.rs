@@ -0,0 +1,102 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Logging configuration for Azure Files clients.
+//!
+//! These defaults are automatically applied to all Files clients and merged with any user-specified logging options.
+
+use azure_core::http::ClientOptions;
+use std::borrow::Cow;
+
+/// Default allowed header names for Azure Files logging.
+pub static STORAGE_ALLOWED_HEADERS: &[&str] = &[
+    // CORS
+    "access-control-allow-origin",
+    // General Azure headers
+    "x-ms-date",
+    "x-ms-error-code",
+    "x-ms-version",
+    // Content headers
+    "accept-ranges",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-md5",
+    "content-range",
+    "vary",
+    // Files-specific headers
+    "x-ms-content-length",
+    "x-ms-delete-snapshots",
+    "x-ms-file-attributes",
+    "x-ms-file-change-time",
+    "x-ms-file-creation-time",
+    "x-ms-file-id",
+    "x-ms-file-last-write-time",
+    "x-ms-file-parent-id",
+    "x-ms-file-permission-key",
+    "x-ms-file-request-intent",
+    "x-ms-handle-id",
+    "x-ms-lease-duration",
+    "x-ms-lease-state",
+    "x-ms-lease-status",
+    "x-ms-marker",
+    "x-ms-number-of-handles-closed",
+    "x-ms-number-of-handles-failed",
+    "x-ms-range",
+    "x-ms-recursive",
+    "x-ms-request-server-encrypted",
+    "x-ms-share-quota",
+    "x-ms-snapshot",
+    "x-ms-type",
+    "x-ms-write",
+];
+
+/// Default allowed query parameters for Azure Files logging.
+pub static STORAGE_ALLOWED_QUERY_PARAMETERS: &[&str] = &[
+    // SAS token parameters (values are time-limited or non-sensitive identifiers)
+    "se",
+    "si",
+    "sip",
+    "sp",
+    "spr",
+    "sr",
+    "srt",
+    "ss",
+    "st",
+    "sv",
+    "sdd",
+    // User delegation key parameters
+    "ske",
+    "skoid",
+    "sks",
+    "skt",
+    "sktid",
+    "skv",
+    // Operation parameters
+    "comp",
+    "include",
+    "maxresults",
+    "prefix",
+    "restype",
+    "sharesnapshot",
+    // Listing parameters
+    "marker",
+];
+
+/// Applies the default Azure Files logging configuration to client options.
+///
+/// This function adds the storage-specific allowed headers and query parameters
+/// to the user's existing logging options. User-specified options are preserved and
+/// take effect in addition to the storage defaults.
+pub(crate) fn apply_storage_logging_defaults(options: &mut ClientOptions) {
+    options
+        .logging
+        .additional_allowed_header_names
+        .extend(STORAGE_ALLOWED_HEADERS.iter().map(|s| Cow::Borrowed(*s)));
+
+    options.logging.additional_allowed_query_params.extend(
+        STORAGE_ALLOWED_QUERY_PARAMETERS
+            .iter()
+            .map(|s| Cow::Borrowed(*s)),
+    );
+}
@@ -0,0 +1,263 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use async_trait::async_trait;
+use azure_core::{fmt::SafeDebug, http::pager::Page, Result};
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes an element whose text may be percent-encoded, as indicated by its `Encoded` attribute.
+pub(crate) mod encoded_string {
+    use percent_encoding::percent_decode_str;
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    struct StringEncoded {
+        #[serde(rename = "@Encoded", default)]
+        encoded: Option<bool>,
+        #[serde(rename = "$text", default)]
+        content: Option<String>,
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Some(value) = Option::<StringEncoded>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        match (value.encoded, value.content) {
+            (Some(true), Some(content)) => percent_decode_str(&content)
+                .decode_utf8()
+                .map(|s| Some(s.into_owned()))
+                .map_err(serde::de::Error::custom),
+            (_, content) => Ok(content),
+        }
+    }
+}
+
+/// The response of listing directories and files in a directory.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+#[serde(rename = "EnumerationResults")]
+pub struct ListFilesAndDirectoriesResponse {
+    /// The path of the listed directory.
+    #[serde(rename = "@DirectoryPath")]
+    pub directory_path: Option<String>,
+
+    /// The directories and files in this page, in the order returned by the service.
+    #[serde(default, deserialize_with = "unwrap_entries", rename = "Entries")]
+    pub entries: Vec<DirectoryEntry>,
+
+    /// Identifies the portion of the list to be returned with the next listing operation.
+    #[serde(rename = "NextMarker")]
+    pub next_marker: Option<String>,
+
+    /// The prefix the entries were filtered by.
+    #[serde(rename = "Prefix")]
+    pub prefix: Option<String>,
+
+    /// The name of the share.
+    #[serde(rename = "@ShareName")]
+    pub share_name: Option<String>,
+
+    /// The share snapshot the entries were listed from.
+    #[serde(rename = "@ShareSnapshot")]
+    pub share_snapshot: Option<String>,
+}
+
+fn unwrap_entries<'de, D>(deserializer: D) -> std::result::Result<Vec<DirectoryEntry>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Entries {
+        #[serde(default, rename = "$value")]
+        items: Vec<DirectoryEntry>,
+    }
+    Ok(Option::<Entries>::deserialize(deserializer)?
+        .map(|e| e.items)
+        .unwrap_or_default())
+}
+
+#[async_trait]
+impl Page for ListFilesAndDirectoriesResponse {
+    type Item = DirectoryEntry;
+    type IntoIter = <Vec<DirectoryEntry> as IntoIterator>::IntoIter;
+    async fn into_items(self) -> Result<Self::IntoIter> {
+        Ok(self.entries.into_iter())
+    }
+}
+
+/// A directory or file returned when listing a directory.
+#[derive(Clone, Deserialize, SafeDebug)]
+pub enum DirectoryEntry {
+    /// A subdirectory.
+    Directory(DirectoryItem),
+    /// A file.
+    File(FileItem),
+}
+
+impl DirectoryEntry {
+    /// The name of the directory or file.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Directory(d) => d.name.as_deref(),
+            Self::File(f) => f.name.as_deref(),
+        }
+    }
+
+    /// Whether the entry is a directory.
+    pub fn is_directory(&self) -> bool {
+        matches!(self, Self::Directory(_))
+    }
+}
+
+/// A directory returned when listing a directory.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+pub struct DirectoryItem {
+    /// The SMB attributes of the directory, if requested.
+    #[serde(rename = "Attributes")]
+    pub attributes: Option<String>,
+
+    /// The file ID of the directory.
+    #[serde(rename = "FileId")]
+    pub file_id: Option<String>,
+
+    /// The name of the directory.
+    #[serde(
+        default,
+        deserialize_with = "encoded_string::deserialize",
+        rename = "Name"
+    )]
+    pub name: Option<String>,
+
+    /// The key of the directory's security descriptor, if requested.
+    #[serde(rename = "PermissionKey")]
+    pub permission_key: Option<String>,
+
+    /// The properties of the directory.
+    #[serde(rename = "Properties")]
+    pub properties: Option<FileItemProperties>,
+}
+
+/// A file returned when listing a directory.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+pub struct FileItem {
+    /// The SMB attributes of the file, if requested.
+    #[serde(rename = "Attributes")]
+    pub attributes: Option<String>,
+
+    /// The file ID of the file.
+    #[serde(rename = "FileId")]
+    pub file_id: Option<String>,
+
+    /// The name of the file.
+    #[serde(
+        default,
+        deserialize_with = "encoded_string::deserialize",
+        rename = "Name"
+    )]
+    pub name: Option<String>,
+
+    /// The key of the file's security descriptor, if requested.
+    #[serde(rename = "PermissionKey")]
+    pub permission_key: Option<String>,
+
+    /// The properties of the file.
+    #[serde(rename = "Properties")]
+    pub properties: Option<FileItemProperties>,
+}
+
+/// Properties of a directory or file returned when listing a directory.
+///
+/// Only the content length is returned unless timestamps or ETags are requested.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+pub struct FileItemProperties {
+    /// The SMB change time.
+    #[serde(rename = "ChangeTime")]
+    pub change_time: Option<String>,
+
+    /// The content length of the file in bytes.
+    #[serde(rename = "Content-Length")]
+    pub content_length: Option<u64>,
+
+    /// The SMB creation time.
+    #[serde(rename = "CreationTime")]
+    pub creation_time: Option<String>,
+
+    /// The ETag of the directory or file.
+    #[serde(rename = "Etag")]
+    pub etag: Option<String>,
+
+    /// The last access time.
+    #[serde(rename = "LastAccessTime")]
+    pub last_access_time: Option<String>,
+
+    /// The last modified time.
+    #[serde(rename = "Last-Modified")]
+    pub last_modified: Option<String>,
+
+    /// The SMB last write time.
+    #[serde(rename = "LastWriteTime")]
+    pub last_write_time: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::xml::from_xml;
+
+    #[test]
+    fn deserialize_mixed_entries() -> Result<()> {
+        let xml = br#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://account.file.core.windows.net/" ShareName="share" DirectoryPath="dir">
+  <Marker />
+  <MaxResults>3</MaxResults>
+  <DirectoryId>13835128424026341376</DirectoryId>
+  <Entries>
+    <File>
+      <FileId>1</FileId>
+      <Name>a.txt</Name>
+      <Properties><Content-Length>42</Content-Length></Properties>
+    </File>
+    <Directory>
+      <FileId>2</FileId>
+      <Name Encoded="true">sub%20dir</Name>
+      <Properties />
+    </Directory>
+    <File>
+      <FileId>3</FileId>
+      <Name>b.txt</Name>
+      <Properties><Content-Length>0</Content-Length></Properties>
+    </File>
+  </Entries>
+  <NextMarker>next</NextMarker>
+</EnumerationResults>"#;
+        let response: ListFilesAndDirectoriesResponse = from_xml(xml)?;
+        assert_eq!(response.share_name.as_deref(), Some("share"));
+        assert_eq!(response.next_marker.as_deref(), Some("next"));
+        let names: Vec<_> = response.entries.iter().map(|e| e.name()).collect();
+        assert_eq!(names, [Some("a.txt"), Some("sub dir"), Some("b.txt")]);
+        assert!(response.entries[1].is_directory());
+        let DirectoryEntry::File(file) = &response.entries[0] else {
+            panic!("expected a file");
+        };
+        assert_eq!(
+            file.properties.as_ref().and_then(|p| p.content_length),
+            Some(42)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_empty_entries() -> Result<()> {
+        let xml = br#"<EnumerationResults ShareName="share"><Entries /><NextMarker /></EnumerationResults>"#;
+        let response: ListFilesAndDirectoriesResponse = from_xml(xml)?;
+        assert!(response.entries.is_empty());
+        assert!(response.next_marker.unwrap_or_default().is_empty());
+        Ok(())
+    }
+}
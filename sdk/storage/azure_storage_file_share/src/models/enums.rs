@@ -0,0 +1,84 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use std::fmt;
+
+/// Specifies whether share snapshots are deleted along with the share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeleteSnapshotsOptionType {
+    /// Delete the share and all of its snapshots.
+    Include,
+    /// Delete the share and all of its snapshots, including snapshots with an active lease.
+    IncludeLeased,
+}
+
+impl AsRef<str> for DeleteSnapshotsOptionType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Include => "include",
+            Self::IncludeLeased => "include-leased",
+        }
+    }
+}
+
+impl fmt::Display for DeleteSnapshotsOptionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// Additional properties to include when listing directories and files.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ListFilesIncludeType {
+    /// Include SMB attributes.
+    Attributes,
+    /// Include the ETag.
+    Etag,
+    /// Include the key of the security descriptor.
+    PermissionKey,
+    /// Include SMB timestamps.
+    Timestamps,
+}
+
+impl AsRef<str> for ListFilesIncludeType {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Attributes => "Attributes",
+            Self::Etag => "ETag",
+            Self::PermissionKey => "PermissionKey",
+            Self::Timestamps => "Timestamps",
+        }
+    }
+}
+
+impl fmt::Display for ListFilesIncludeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// The intent of a request authorized with a Microsoft Entra ID token.
+///
+/// The Files service requires an intent for every data operation authorized with a token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShareTokenIntent {
+    /// The request is made with backup semantics, which bypass file and directory level access control lists.
+    Backup,
+}
+
+impl AsRef<str> for ShareTokenIntent {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Backup => "backup",
+        }
+    }
+}
+
+impl fmt::Display for ShareTokenIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::directory_list::encoded_string;
+use async_trait::async_trait;
+use azure_core::{fmt::SafeDebug, http::pager::Page, time::OffsetDateTime, Result};
+use serde::{Deserialize, Deserializer};
+
+/// The response of listing the open handles on a directory or file.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+#[serde(rename = "EnumerationResults")]
+pub struct ListHandlesResponse {
+    /// The open handles in this page.
+    #[serde(default, deserialize_with = "unwrap_handles", rename = "HandleList")]
+    pub handles: Vec<HandleItem>,
+
+    /// Identifies the portion of the list to be returned with the next listing operation.
+    #[serde(rename = "NextMarker")]
+    pub next_marker: Option<String>,
+}
+
+fn unwrap_handles<'de, D>(deserializer: D) -> std::result::Result<Vec<HandleItem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct HandleList {
+        #[serde(default, rename = "Handle")]
+        handles: Vec<HandleItem>,
+    }
+    Ok(Option::<HandleList>::deserialize(deserializer)?
+        .map(|h| h.handles)
+        .unwrap_or_default())
+}
+
+#[async_trait]
+impl Page for ListHandlesResponse {
+    type Item = HandleItem;
+    type IntoIter = <Vec<HandleItem> as IntoIterator>::IntoIter;
+    async fn into_items(self) -> Result<Self::IntoIter> {
+        Ok(self.handles.into_iter())
+    }
+}
+
+/// An open SMB handle on a directory or file.
+#[derive(Clone, Default, Deserialize, SafeDebug)]
+#[non_exhaustive]
+pub struct HandleItem {
+    /// The access rights granted to the handle, such as `Read`, `Write` or `Delete`.
+    #[serde(
+        default,
+        deserialize_with = "unwrap_access_rights",
+        rename = "AccessRightList"
+    )]
+    pub access_rights: Vec<String>,
+
+    /// The IP address of the client that opened the handle.
+    #[serde(rename = "ClientIp")]
+    pub client_ip: Option<String>,
+
+    /// The name of the client machine that opened the handle.
+    #[serde(rename = "ClientName")]
+    pub client_name: Option<String>,
+
+    /// The file ID of the handle's directory or file.
+    #[serde(rename = "FileId")]
+    pub file_id: Option<String>,
+
+    /// The ID of the handle, which can be passed to a force-close operation.
+    #[serde(rename = "HandleId")]
+    pub handle_id: Option<String>,
+
+    /// The time the client last reconnected the handle.
+    #[serde(
+        default,
+        rename = "LastReconnectTime",
+        with = "azure_core::time::rfc7231::option"
+    )]
+    pub last_reconnect_time: Option<OffsetDateTime>,
+
+    /// The time the handle was opened.
+    #[serde(
+        default,
+        rename = "OpenTime",
+        with = "azure_core::time::rfc7231::option"
+    )]
+    pub open_time: Option<OffsetDateTime>,
+
+    /// The file ID of the parent directory.
+    #[serde(rename = "ParentId")]
+    pub parent_id: Option<String>,
+
+    /// The path of the directory or file relative to the share root.
+    #[serde(
+        default,
+        deserialize_with = "encoded_string::deserialize",
+        rename = "Path"
+    )]
+    pub path: Option<String>,
+
+    /// The SMB session ID the handle was opened in.
+    #[serde(rename = "SessionId")]
+    pub session_id: Option<String>,
+}
+
+fn unwrap_access_rights<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct AccessRightList {
+        #[serde(default, rename = "AccessRight")]
+        rights: Vec<String>,
+    }
+    Ok(Option::<AccessRightList>::deserialize(deserializer)?
+        .map(|l| l.rights)
+        .unwrap_or_default())
+}
+
+/// The result of force-closing open handles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CloseHandlesResult {
+    /// The number of handles that were closed.
+    pub closed_handles_count: u64,
+    /// The number of handles that could not be closed.
+    pub failed_handles_count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azure_core::xml::from_xml;
+
+    #[test]
+    fn deserialize_handles() -> Result<()> {
+        let xml = br#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults>
+  <HandleList>
+    <Handle>
+      <HandleId>12345</HandleId>
+      <Path Encoded="true">dir/file%20a.txt</Path>
+      <FileId>1</FileId>
+      <ParentId>0</ParentId>
+      <SessionId>999</SessionId>
+      <ClientIp>10.0.0.4:445</ClientIp>
+      <ClientName>host-1</ClientName>
+      <OpenTime>Tue, 15 Oct 2024 10:00:00 GMT</OpenTime>
+      <AccessRightList><AccessRight>Read</AccessRight><AccessRight>Write</AccessRight></AccessRightList>
+    </Handle>
+  </HandleList>
+  <NextMarker />
+</EnumerationResults>"#;
+        let response: ListHandlesResponse = from_xml(xml)?;
+        assert_eq!(response.handles.len(), 1);
+        let handle = &response.handles[0];
+        assert_eq!(handle.handle_id.as_deref(), Some("12345"));
+        assert_eq!(handle.path.as_deref(), Some("dir/file a.txt"));
+        assert_eq!(handle.access_rights, ["Read", "Write"]);
+        assert!(handle.open_time.is_some());
+        assert!(handle.last_reconnect_time.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use azure_core::{
+    fmt::SafeDebug,
+    http::{RequestContent, XmlFormat},
+    time::OffsetDateTime,
+    xml::to_xml,
+    Result,
+};
+use serde::Serialize;
+
+/// Key information for user delegation key.
+#[derive(Clone, Default, SafeDebug, Serialize)]
+pub struct KeyInfo {
+    /// The delegated user tenant ID in Entra ID.
+    #[serde(rename = "DelegatedUserTid", skip_serializing_if = "Option::is_none")]
+    pub delegated_user_tid: Option<String>,
+
+    /// The date-time the key expires in ISO 8601 UTC time.
+    #[serde(
+        rename = "Expiry",
+        serialize_with = "azure_storage_common::rfc3339::seconds_only::option::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub expiry: Option<OffsetDateTime>,
+
+    /// The date-time the key is active in ISO 8601 UTC time.
+    #[serde(
+        rename = "Start",
+        serialize_with = "azure_storage_common::rfc3339::seconds_only::option::serialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start: Option<OffsetDateTime>,
+}
+
+impl TryFrom<KeyInfo> for RequestContent<KeyInfo, XmlFormat> {
+    type Error = azure_core::Error;
+    fn try_from(value: KeyInfo) -> Result<Self> {
+        Ok(to_xml(&value)?.into())
+    }
+}
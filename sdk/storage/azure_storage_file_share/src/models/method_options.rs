@@ -0,0 +1,351 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use super::{DeleteSnapshotsOptionType, ListFilesIncludeType};
+use azure_core::{
+    fmt::SafeDebug,
+    http::{pager::PagerOptions, ClientMethodOptions},
+};
+use std::{collections::HashMap, num::NonZero, ops::Range};
+
+/// Options to be passed to `ShareServiceClient::get_user_delegation_key()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareServiceClientGetUserDelegationKeyOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareClient::create()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareClientCreateOptions<'a> {
+    /// The access tier of the share, such as `TransactionOptimized`, `Hot` or `Cool`.
+    pub access_tier: Option<String>,
+
+    /// User-defined metadata to store with the share.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The maximum size of the share in GiB.
+    pub quota: Option<u64>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareClient::delete()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareClientDeleteOptions<'a> {
+    /// Whether to delete the share's snapshots. Deleting a share that has snapshots fails unless this is set.
+    pub delete_snapshots: Option<DeleteSnapshotsOptionType>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareClient::get_properties()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareClientGetPropertiesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareClient::create_snapshot()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareClientCreateSnapshotOptions<'a> {
+    /// User-defined metadata to store with the snapshot. Defaults to the share's metadata.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareDirectoryClient::create()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientCreateOptions<'a> {
+    /// The SMB attributes of the directory, such as `Directory` or `Directory | Hidden`. Defaults to `Directory`.
+    pub file_attributes: Option<String>,
+
+    /// The security descriptor of the directory in SDDL format. Defaults to `inherit`.
+    pub file_permission: Option<String>,
+
+    /// User-defined metadata to store with the directory.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareDirectoryClient::delete()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientDeleteOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareDirectoryClient::get_properties()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientGetPropertiesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareDirectoryClient::list_files_and_directories()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientListFilesAndDirectoriesOptions<'a> {
+    /// Additional properties to include for each entry.
+    pub include: Option<Vec<ListFilesIncludeType>>,
+
+    /// If `true`, the file ID of each entry and of the listed directory are included.
+    pub include_extended_info: Option<bool>,
+
+    /// The maximum number of entries to return in each page.
+    pub max_results: Option<i32>,
+
+    /// Allows customization of the method call.
+    pub method_options: PagerOptions<'a>,
+
+    /// Lists only entries whose names begin with this prefix.
+    pub prefix: Option<String>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+impl ShareDirectoryClientListFilesAndDirectoriesOptions<'_> {
+    /// Transforms this [`ShareDirectoryClientListFilesAndDirectoriesOptions`] into a new `ShareDirectoryClientListFilesAndDirectoriesOptions` that owns the underlying data, cloning it if necessary.
+    pub fn into_owned(self) -> ShareDirectoryClientListFilesAndDirectoriesOptions<'static> {
+        ShareDirectoryClientListFilesAndDirectoriesOptions {
+            include: self.include,
+            include_extended_info: self.include_extended_info,
+            max_results: self.max_results,
+            method_options: PagerOptions {
+                context: self.method_options.context.into_owned(),
+                ..self.method_options
+            },
+            prefix: self.prefix,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Options to be passed to `ShareDirectoryClient::list_handles()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientListHandlesOptions<'a> {
+    /// The maximum number of handles to return in each page.
+    pub max_results: Option<i32>,
+
+    /// Allows customization of the method call.
+    pub method_options: PagerOptions<'a>,
+
+    /// If `true`, handles on all subdirectories and files are listed as well.
+    pub recursive: Option<bool>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+impl ShareDirectoryClientListHandlesOptions<'_> {
+    /// Transforms this [`ShareDirectoryClientListHandlesOptions`] into a new `ShareDirectoryClientListHandlesOptions` that owns the underlying data, cloning it if necessary.
+    pub fn into_owned(self) -> ShareDirectoryClientListHandlesOptions<'static> {
+        ShareDirectoryClientListHandlesOptions {
+            max_results: self.max_results,
+            method_options: PagerOptions {
+                context: self.method_options.context.into_owned(),
+                ..self.method_options
+            },
+            recursive: self.recursive,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Options to be passed to `ShareDirectoryClient::force_close_handles()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareDirectoryClientForceCloseHandlesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// If `true`, handles on all subdirectories and files are closed as well.
+    pub recursive: Option<bool>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::create()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientCreateOptions<'a> {
+    /// The content type of the file.
+    pub content_type: Option<String>,
+
+    /// The SMB attributes of the file, such as `ReadOnly | Archive`. Defaults to `None`.
+    pub file_attributes: Option<String>,
+
+    /// The security descriptor of the file in SDDL format. Defaults to `inherit`.
+    pub file_permission: Option<String>,
+
+    /// User-defined metadata to store with the file.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::delete()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientDeleteOptions<'a> {
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::get_properties()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientGetPropertiesOptions<'a> {
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::upload_range()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientUploadRangeOptions<'a> {
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+
+    /// The MD5 hash of the range content, used to verify integrity during transport.
+    pub transactional_content_md5: Option<Vec<u8>>,
+}
+
+/// Options to be passed to `ShareFileClient::clear_range()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientClearRangeOptions<'a> {
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::download()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientDownloadOptions<'a> {
+    /// Required if the file has an active lease.
+    pub lease_id: Option<String>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The maximum number of ranges downloaded concurrently.
+    pub parallel: Option<NonZero<usize>>,
+
+    /// The size of each range download. Defaults to 4 MiB.
+    pub partition_size: Option<NonZero<usize>>,
+
+    /// The byte range of the file to download. Downloads the whole file when `None`.
+    pub range: Option<Range<u64>>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+/// Options to be passed to `ShareFileClient::upload()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientUploadOptions<'a> {
+    /// The content type of the file.
+    pub content_type: Option<String>,
+
+    /// User-defined metadata to store with the file.
+    pub metadata: Option<HashMap<String, String>>,
+
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The maximum number of ranges uploaded concurrently.
+    pub parallel: Option<NonZero<usize>>,
+
+    /// The size of each range upload. Defaults to, and may not exceed, 4 MiB.
+    pub partition_size: Option<NonZero<usize>>,
+}
+
+/// Options to be passed to `ShareFileClient::list_handles()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientListHandlesOptions<'a> {
+    /// The maximum number of handles to return in each page.
+    pub max_results: Option<i32>,
+
+    /// Allows customization of the method call.
+    pub method_options: PagerOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
+
+impl ShareFileClientListHandlesOptions<'_> {
+    /// Transforms this [`ShareFileClientListHandlesOptions`] into a new `ShareFileClientListHandlesOptions` that owns the underlying data, cloning it if necessary.
+    pub fn into_owned(self) -> ShareFileClientListHandlesOptions<'static> {
+        ShareFileClientListHandlesOptions {
+            max_results: self.max_results,
+            method_options: PagerOptions {
+                context: self.method_options.context.into_owned(),
+                ..self.method_options
+            },
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Options to be passed to `ShareFileClient::force_close_handles()`
+#[derive(Clone, Default, SafeDebug)]
+pub struct ShareFileClientForceCloseHandlesOptions<'a> {
+    /// Allows customization of the method call.
+    pub method_options: ClientMethodOptions<'a>,
+
+    /// The timeout parameter is expressed in seconds.
+    pub timeout: Option<i32>,
+}
@@ -0,0 +1,21 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! Model types for Azure Files.
+
+mod directory_list;
+mod enums;
+mod handles;
+mod key_info;
+mod method_options;
+mod properties;
+
+pub use azure_storage_common::models::UserDelegationKey;
+pub use directory_list::{
+    DirectoryEntry, DirectoryItem, FileItem, FileItemProperties, ListFilesAndDirectoriesResponse,
+};
+pub use enums::{DeleteSnapshotsOptionType, ListFilesIncludeType, ShareTokenIntent};
+pub use handles::{CloseHandlesResult, HandleItem, ListHandlesResponse};
+pub use key_info::KeyInfo;
+pub use method_options::*;
+pub use properties::{FileProperties, ShareProperties};
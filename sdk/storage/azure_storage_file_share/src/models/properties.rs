@@ -0,0 +1,148 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use azure_core::{
+    fmt::SafeDebug,
+    http::{
+        headers::{HeaderName, Headers, CONTENT_LENGTH, CONTENT_TYPE, ETAG, LAST_MODIFIED},
+        Etag,
+    },
+    time::{parse_rfc7231, OffsetDateTime},
+    Result,
+};
+use std::collections::HashMap;
+
+const META: &str = "x-ms-meta-";
+const FILE_ATTRIBUTES: HeaderName = HeaderName::from_static("x-ms-file-attributes");
+const FILE_CHANGE_TIME: HeaderName = HeaderName::from_static("x-ms-file-change-time");
+const FILE_CREATION_TIME: HeaderName = HeaderName::from_static("x-ms-file-creation-time");
+const FILE_ID: HeaderName = HeaderName::from_static("x-ms-file-id");
+const FILE_LAST_WRITE_TIME: HeaderName = HeaderName::from_static("x-ms-file-last-write-time");
+const FILE_PARENT_ID: HeaderName = HeaderName::from_static("x-ms-file-parent-id");
+const SHARE_QUOTA: HeaderName = HeaderName::from_static("x-ms-share-quota");
+const SHARE_PROVISIONED_IOPS: HeaderName = HeaderName::from_static("x-ms-share-provisioned-iops");
+const ACCESS_TIER: HeaderName = HeaderName::from_static("x-ms-access-tier");
+
+/// Reads user-defined `x-ms-meta-*` metadata from response headers.
+pub(crate) fn metadata_from_headers(headers: &Headers) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    for h in headers.iter() {
+        let name = h.0.as_str();
+        if name.len() > META.len() && name.starts_with(META) {
+            values.insert(name[META.len()..].to_owned(), h.1.as_str().to_owned());
+        }
+    }
+    values
+}
+
+fn optional_time(headers: &Headers, name: &HeaderName) -> Result<Option<OffsetDateTime>> {
+    headers
+        .get_optional_str(name)
+        .map(parse_rfc7231)
+        .transpose()
+}
+
+/// System and user-defined properties of a share.
+#[derive(Clone, Default, SafeDebug)]
+#[non_exhaustive]
+pub struct ShareProperties {
+    /// The access tier of the share.
+    pub access_tier: Option<String>,
+    /// The ETag of the share.
+    pub etag: Option<Etag>,
+    /// The last modified time of the share.
+    pub last_modified: Option<OffsetDateTime>,
+    /// The user-defined metadata of the share.
+    pub metadata: HashMap<String, String>,
+    /// The provisioned IOPS of a premium share.
+    pub provisioned_iops: Option<u64>,
+    /// The quota of the share in GiB.
+    pub quota: Option<u64>,
+}
+
+impl ShareProperties {
+    /// Reads the share properties from response headers.
+    pub fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            access_tier: headers.get_optional_string(&ACCESS_TIER),
+            etag: headers.get_optional_as(&ETAG)?,
+            last_modified: optional_time(headers, &LAST_MODIFIED)?,
+            metadata: metadata_from_headers(headers),
+            provisioned_iops: headers.get_optional_as(&SHARE_PROVISIONED_IOPS)?,
+            quota: headers.get_optional_as(&SHARE_QUOTA)?,
+        })
+    }
+}
+
+/// System and user-defined properties of a directory or file.
+///
+/// File SMB times are returned by the service in ISO 8601 format with 100-nanosecond precision and are
+/// kept as strings so they can be round-tripped to the service unchanged.
+#[derive(Clone, Default, SafeDebug)]
+#[non_exhaustive]
+pub struct FileProperties {
+    /// The content length of the file in bytes. Not set for directories.
+    pub content_length: Option<u64>,
+    /// The content type of the file. Not set for directories.
+    pub content_type: Option<String>,
+    /// The ETag of the directory or file.
+    pub etag: Option<Etag>,
+    /// The SMB attributes, such as `Directory` or `ReadOnly | Archive`.
+    pub file_attributes: Option<String>,
+    /// The SMB change time.
+    pub file_change_time: Option<String>,
+    /// The SMB creation time.
+    pub file_creation_time: Option<String>,
+    /// The file ID of the directory or file.
+    pub file_id: Option<String>,
+    /// The SMB last write time.
+    pub file_last_write_time: Option<String>,
+    /// The file ID of the parent directory.
+    pub file_parent_id: Option<String>,
+    /// The last modified time of the directory or file.
+    pub last_modified: Option<OffsetDateTime>,
+    /// The user-defined metadata of the directory or file.
+    pub metadata: HashMap<String, String>,
+}
+
+impl FileProperties {
+    /// Reads the directory or file properties from response headers.
+    pub fn from_headers(headers: &Headers) -> Result<Self> {
+        Ok(Self {
+            content_length: headers.get_optional_as(&CONTENT_LENGTH)?,
+            content_type: headers.get_optional_string(&CONTENT_TYPE),
+            etag: headers.get_optional_as(&ETAG)?,
+            file_attributes: headers.get_optional_string(&FILE_ATTRIBUTES),
+            file_change_time: headers.get_optional_string(&FILE_CHANGE_TIME),
+            file_creation_time: headers.get_optional_string(&FILE_CREATION_TIME),
+            file_id: headers.get_optional_string(&FILE_ID),
+            file_last_write_time: headers.get_optional_string(&FILE_LAST_WRITE_TIME),
+            file_parent_id: headers.get_optional_string(&FILE_PARENT_ID),
+            last_modified: optional_time(headers, &LAST_MODIFIED)?,
+            metadata: metadata_from_headers(headers),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn share_properties_from_headers() -> Result<()> {
+        let mut headers = Headers::new();
+        headers.insert("etag", "\"0x8D\"");
+        headers.insert("last-modified", "Tue, 15 Oct 2024 10:00:00 GMT");
+        headers.insert("x-ms-share-quota", "100");
+        headers.insert("x-ms-meta-team", "storage");
+        let properties = ShareProperties::from_headers(&headers)?;
+        assert_eq!(properties.quota, Some(100));
+        assert_eq!(properties.etag, Some(Etag::from("\"0x8D\"")));
+        assert!(properties.last_modified.is_some());
+        assert_eq!(
+            properties.metadata.get("team").map(String::as_str),
+            Some("storage")
+        );
+        Ok(())
+    }
+}
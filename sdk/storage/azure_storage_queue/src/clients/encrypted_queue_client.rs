@@ -0,0 +1,371 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+use crate::{
+    models::{
+        ListOfSentMessage, PeekedMessages, QueueClientPeekMessagesOptions,
+        QueueClientReceiveMessagesOptions, QueueClientSendMessageOptions,
+        QueueClientUpdateMessageOptions, QueueMessage, ReceivedMessages,
+    },
+    QueueClient,
+};
+use azure_core::{
+    base64,
+    error::{Error, ErrorKind},
+    http::{NoFormat, RawResponse, RequestContent, Response, Url, XmlFormat},
+    xml, Bytes, Result,
+};
+use azure_storage_common::encryption::{
+    ClientSideEncryptionOptions, ContentCipher, EncryptionData,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+
+/// The message text of a client-side encrypted message.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct EncryptedMessage {
+    #[serde(with = "base64")]
+    encrypted_message_contents: Vec<u8>,
+    encryption_data: EncryptionData,
+}
+
+/// A client that encrypts message text before sending it and decrypts it when receiving or peeking.
+///
+/// Messages are encrypted with client-side (envelope) encryption using the version 2 format shared
+/// by the other Azure Storage SDKs: the message text is encrypted with AES-GCM using a content encryption key
+/// wrapped by the [`KeyEncryptionKey`](crate::encryption::KeyEncryptionKey) in [`ClientSideEncryptionOptions`],
+/// and replaced with a JSON document containing the ciphertext and the wrapped key.
+///
+/// Messages that were not encrypted are returned as-is.
+pub struct EncryptedQueueClient {
+    client: QueueClient,
+    options: ClientSideEncryptionOptions,
+}
+
+impl fmt::Debug for EncryptedQueueClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedQueueClient")
+            .field("url", self.url())
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptedQueueClient {
+    /// Creates a new `EncryptedQueueClient`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`QueueClient`] used to send requests.
+    /// * `options` - The keys used to encrypt and decrypt messages.
+    pub fn new(client: QueueClient, options: ClientSideEncryptionOptions) -> Self {
+        Self { client, options }
+    }
+
+    /// Gets the [`QueueClient`] used to send requests.
+    ///
+    /// Operations on this client do not encrypt or decrypt messages.
+    pub fn queue_client(&self) -> &QueueClient {
+        &self.client
+    }
+
+    /// Gets the URL of the resource this client is configured for.
+    pub fn url(&self) -> &Url {
+        self.client.url()
+    }
+
+    /// Encrypts a message and adds it to the back of the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue_message` - The queue message to encrypt and send.
+    /// * `options` - Optional parameters for the request.
+    pub async fn send_message(
+        &self,
+        queue_message: RequestContent<QueueMessage, XmlFormat>,
+        options: Option<QueueClientSendMessageOptions<'_>>,
+    ) -> Result<Response<ListOfSentMessage, XmlFormat>> {
+        let queue_message = self.encrypt(&queue_message).await?;
+        self.client.send_message(queue_message, options).await
+    }
+
+    /// Updates the visibility timeout of a message, encrypting its new contents if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_id` - The ID of the queue message.
+    /// * `pop_receipt` - The pop receipt returned when the message was received.
+    /// * `visibility_timeout` - The new visibility timeout, in seconds, relative to server time.
+    /// * `options` - Optional parameters for the request.
+    pub async fn update_message(
+        &self,
+        message_id: &str,
+        pop_receipt: &str,
+        visibility_timeout: i32,
+        options: Option<QueueClientUpdateMessageOptions<'_>>,
+    ) -> Result<Response<(), NoFormat>> {
+        let mut options = options.unwrap_or_default();
+        if let Some(queue_message) = options.queue_message.take() {
+            options.queue_message = Some(self.encrypt(&queue_message).await?);
+        }
+        self.client
+            .update_message(message_id, pop_receipt, visibility_timeout, Some(options))
+            .await
+    }
+
+    /// Retrieves one or more messages from the front of the queue and decrypts them.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    pub async fn receive_messages(
+        &self,
+        options: Option<QueueClientReceiveMessagesOptions<'_>>,
+    ) -> Result<Response<ReceivedMessages, XmlFormat>> {
+        let response = self.client.receive_messages(options).await?;
+        self.decrypt_response(response, |messages: &mut ReceivedMessages| {
+            messages
+                .items
+                .iter_mut()
+                .flatten()
+                .map(|m| &mut m.message_text)
+                .collect()
+        })
+        .await
+    }
+
+    /// Retrieves one or more messages from the front of the queue without altering their visibility, and decrypts them.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Optional parameters for the request.
+    pub async fn peek_messages(
+        &self,
+        options: Option<QueueClientPeekMessagesOptions<'_>>,
+    ) -> Result<Response<PeekedMessages, XmlFormat>> {
+        let response = self.client.peek_messages(options).await?;
+        self.decrypt_response(response, |messages: &mut PeekedMessages| {
+            messages
+                .items
+                .iter_mut()
+                .flatten()
+                .map(|m| &mut m.message_text)
+                .collect()
+        })
+        .await
+    }
+
+    async fn encrypt(
+        &self,
+        queue_message: &RequestContent<QueueMessage, XmlFormat>,
+    ) -> Result<RequestContent<QueueMessage, XmlFormat>> {
+        let mut message: QueueMessage = xml::from_xml(Bytes::from(queue_message.body()))?;
+        let plaintext = message.message_text.take().unwrap_or_default();
+        let (cipher, encryption_data) = ContentCipher::generate(&self.options).await?;
+        let encrypted = EncryptedMessage {
+            encrypted_message_contents: cipher.encrypt(plaintext.as_bytes())?,
+            encryption_data,
+        };
+        message.message_text = Some(serde_json::to_string(&encrypted)?);
+        message.try_into()
+    }
+
+    async fn decrypt_response<T>(
+        &self,
+        response: Response<T, XmlFormat>,
+        message_texts: impl FnOnce(&mut T) -> Vec<&mut Option<String>>,
+    ) -> Result<Response<T, XmlFormat>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let (status, headers, body) = response.deconstruct();
+        let mut messages: T = body.xml()?;
+        for text in message_texts(&mut messages) {
+            let Some(encrypted) = text
+                .as_deref()
+                .and_then(|t| serde_json::from_str::<EncryptedMessage>(t).ok())
+            else {
+                continue;
+            };
+            let cipher =
+                ContentCipher::from_encryption_data(&self.options, &encrypted.encryption_data)
+                    .await?;
+            let plaintext = cipher.decrypt(&encrypted.encrypted_message_contents)?;
+            *text = Some(String::from_utf8(plaintext).map_err(|e| {
+                Error::with_error(
+                    ErrorKind::DataConversion,
+                    e,
+                    "decrypted message text is not valid UTF-8",
+                )
+            })?);
+        }
+        Ok(RawResponse::from_bytes(status, headers, xml::to_xml(&messages)?).into())
+    }
+}
+
+impl QueueClient {
+    /// Creates an [`EncryptedQueueClient`] for this queue that encrypts messages before sending them and decrypts them when receiving.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The keys used to encrypt and decrypt messages.
+    pub fn encrypted_client(&self, options: ClientSideEncryptionOptions) -> EncryptedQueueClient {
+        EncryptedQueueClient::new(
+            QueueClient {
+                endpoint: self.endpoint.clone(),
+                pipeline: self.pipeline.clone(),
+                version: self.version.clone(),
+                tracer: self.tracer.clone(),
+            },
+            options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::LocalKeyEncryptionKey;
+    use azure_core::http::{
+        headers::Headers, AsyncRawResponse, ClientOptions, Method, StatusCode, Transport,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use futures::FutureExt as _;
+    use std::sync::{Arc, Mutex};
+
+    fn encryption_options() -> ClientSideEncryptionOptions {
+        let kek = LocalKeyEncryptionKey::new("local-key", vec![7; 32]).unwrap();
+        ClientSideEncryptionOptions::new(Arc::new(kek))
+    }
+
+    /// Creates a client for a mock queue that stores the text of the last message sent or updated.
+    fn client(stored: Arc<Mutex<Option<String>>>) -> QueueClient {
+        let mock_client = Arc::new(MockHttpClient::new(move |req| {
+            let stored = stored.clone();
+            let method = req.method();
+            let body = Bytes::from(req.body());
+            async move {
+                let mut stored = stored.lock().unwrap();
+                let (status, body) = match method {
+                    Method::Post | Method::Put => {
+                        let message: QueueMessage = xml::from_xml(&body).unwrap();
+                        *stored = message.message_text;
+                        let status = if method == Method::Post {
+                            StatusCode::Created
+                        } else {
+                            StatusCode::NoContent
+                        };
+                        (
+                            status,
+                            "<QueueMessagesList><QueueMessage><MessageId>1</MessageId></QueueMessage></QueueMessagesList>"
+                                .to_string(),
+                        )
+                    }
+                    Method::Get => (
+                        StatusCode::Ok,
+                        format!(
+                            "<QueueMessagesList><QueueMessage><MessageId>1</MessageId><MessageText>{}</MessageText></QueueMessage></QueueMessagesList>",
+                            stored.as_deref().unwrap_or_default().replace('"', "&quot;")
+                        ),
+                    ),
+                    _ => panic!("unexpected method {method:?}"),
+                };
+                Ok(AsyncRawResponse::from_bytes(status, Headers::new(), body))
+            }
+            .boxed()
+        }));
+        QueueClient::new(
+            "https://myaccount.queue.core.windows.net/myqueue"
+                .parse()
+                .unwrap(),
+            None,
+            Some(crate::QueueClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    fn message(text: &str) -> RequestContent<QueueMessage, XmlFormat> {
+        QueueMessage {
+            message_text: Some(text.to_string()),
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn send_and_receive_round_trip() -> Result<()> {
+        let stored = Arc::new(Mutex::new(None));
+        let client = client(stored.clone()).encrypted_client(encryption_options());
+
+        client.send_message(message("hello"), None).await?;
+        let text = stored.lock().unwrap().clone().unwrap();
+        assert!(!text.contains("hello"));
+        let encrypted: EncryptedMessage = serde_json::from_str(&text)?;
+        assert_eq!(
+            encrypted.encryption_data.wrapped_content_key.key_id,
+            "local-key"
+        );
+
+        let received = client.receive_messages(None).await?.into_model()?;
+        let items = received.items.unwrap();
+        assert_eq!(items[0].message_text.as_deref(), Some("hello"));
+
+        let peeked = client.peek_messages(None).await?.into_model()?;
+        let items = peeked.items.unwrap();
+        assert_eq!(items[0].message_text.as_deref(), Some("hello"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn update_message_encrypts_contents() -> Result<()> {
+        let stored = Arc::new(Mutex::new(None));
+        let client = client(stored.clone()).encrypted_client(encryption_options());
+
+        client
+            .update_message(
+                "1",
+                "receipt",
+                0,
+                Some(QueueClientUpdateMessageOptions {
+                    queue_message: Some(message("updated")),
+                    ..Default::default()
+                }),
+            )
+            .await?;
+        assert!(!stored
+            .lock()
+            .unwrap()
+            .as_deref()
+            .unwrap()
+            .contains("updated"));
+
+        let received = client.receive_messages(None).await?.into_model()?;
+        assert_eq!(
+            received.items.unwrap()[0].message_text.as_deref(),
+            Some("updated")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unencrypted_messages_pass_through() -> Result<()> {
+        let stored = Arc::new(Mutex::new(None));
+        let queue_client = client(stored.clone());
+        let client = queue_client.encrypted_client(encryption_options());
+
+        queue_client.send_message(message("plain"), None).await?;
+        let received = client.receive_messages(None).await?.into_model()?;
+        assert_eq!(
+            received.items.unwrap()[0].message_text.as_deref(),
+            Some("plain")
+        );
+        Ok(())
+    }
+}
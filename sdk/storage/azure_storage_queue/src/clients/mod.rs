@@ -3,6 +3,12 @@
 
 //! Clients used to communicate with Azure Storage Queue service.
 
+#[cfg(feature = "encryption")]
+mod encrypted_queue_client;
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub use encrypted_queue_client::EncryptedQueueClient;
+
 mod queue_client;
 pub use queue_client::{QueueClient, QueueClientOptions};
 
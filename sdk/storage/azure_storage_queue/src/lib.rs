@@ -10,6 +10,10 @@ mod generated;
 mod logging;
 
 pub mod clients;
+#[cfg(feature = "encryption")]
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+pub use azure_storage_common::encryption;
 pub mod models;
+pub mod processor;
 
 pub use clients::{QueueClient, QueueClientOptions, QueueServiceClient, QueueServiceClientOptions};
@@ -0,0 +1,727 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! A processor that receives messages from a queue and dispatches them to a handler.
+
+use crate::{
+    models::{QueueMessage, ReceivedMessage},
+    QueueClient,
+};
+use async_trait::async_trait;
+use azure_core::{
+    error::ErrorKind,
+    fmt::SafeDebug,
+    http::{headers::HeaderName, StatusCode},
+    sleep::sleep,
+    time::Duration,
+    Result,
+};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture, Either, Fuse, FusedFuture},
+    stream::FuturesUnordered,
+    FutureExt, StreamExt,
+};
+use std::{
+    convert::Infallible,
+    num::NonZero,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+use tracing::{debug, warn};
+
+const POP_RECEIPT: HeaderName = HeaderName::from_static("x-ms-popreceipt");
+
+/// The maximum number of messages the service returns from a single receive.
+const MAX_BATCH_SIZE: usize = 32;
+
+const DEFAULT_BATCH_SIZE: usize = 16;
+const DEFAULT_MAX_CONCURRENT_MESSAGES: usize = 32;
+const DEFAULT_VISIBILITY_TIMEOUT: Duration = Duration::seconds(30);
+const DEFAULT_MIN_POLLING_INTERVAL: Duration = Duration::milliseconds(100);
+const DEFAULT_MAX_POLLING_INTERVAL: Duration = Duration::minutes(1);
+const DEFAULT_MAX_DEQUEUE_COUNT: i64 = 5;
+
+/// Handles messages received by a [`QueueProcessor`].
+#[async_trait]
+pub trait QueueMessageHandler: Send + Sync {
+    /// Processes a received message.
+    ///
+    /// If this returns `Ok`, the message is deleted from the queue. If it returns an error,
+    /// the message is made visible again so that it can be retried.
+    async fn handle(&self, message: &ReceivedMessage) -> Result<()>;
+}
+
+/// Options for a [`QueueProcessor`].
+#[derive(Clone, Default, SafeDebug)]
+pub struct QueueProcessorOptions {
+    /// The number of messages to request from the service at a time, up to 32. Defaults to 16.
+    pub batch_size: Option<NonZero<usize>>,
+
+    /// The maximum number of messages handled concurrently. Defaults to 32.
+    pub max_concurrent_messages: Option<NonZero<usize>>,
+
+    /// How long received messages stay invisible to other consumers. Defaults to 30 seconds.
+    ///
+    /// The visibility timeout of a message is extended every half of this interval while its handler is running.
+    pub visibility_timeout: Option<Duration>,
+
+    /// The delay before polling again after an empty receive. Defaults to 100 milliseconds.
+    ///
+    /// The delay doubles after each consecutive empty receive, up to [`max_polling_interval`](Self::max_polling_interval).
+    pub min_polling_interval: Option<Duration>,
+
+    /// The maximum delay between polls of an empty queue. Defaults to 1 minute.
+    pub max_polling_interval: Option<Duration>,
+
+    /// The number of times a message may be dequeued before it is moved to the poison queue. Defaults to 5.
+    pub max_dequeue_count: Option<i64>,
+
+    /// The name of the queue that poison messages are moved to. Defaults to the name of the queue followed by `-poison`.
+    ///
+    /// The poison queue is created if it does not exist.
+    pub poison_queue_name: Option<String>,
+}
+
+/// Receives messages from a queue and dispatches them to a [`QueueMessageHandler`].
+///
+/// The processor polls the queue, backing off while it is empty, and runs handlers concurrently.
+/// While a handler is running, the visibility timeout of its message is extended so that other consumers
+/// do not receive it. Messages are deleted when their handler succeeds, and made visible again when it fails.
+/// Messages that have been dequeued more than [`max_dequeue_count`](QueueProcessorOptions::max_dequeue_count)
+/// times are moved to a poison queue without being handled.
+///
+/// Messages are processed at least once, so handlers should be idempotent.
+pub struct QueueProcessor {
+    client: QueueClient,
+    poison_queue: QueueClient,
+    handler: Arc<dyn QueueMessageHandler>,
+    batch_size: usize,
+    max_concurrent_messages: usize,
+    visibility_timeout: Duration,
+    min_polling_interval: Duration,
+    max_polling_interval: Duration,
+    max_dequeue_count: i64,
+    poison_queue_created: AtomicBool,
+    stop: Mutex<StopState>,
+}
+
+/// Whether [`QueueProcessor::stop`] was called, and how to signal a running processor.
+enum StopState {
+    Idle,
+    Requested,
+    Running(oneshot::Sender<()>),
+}
+
+impl QueueProcessor {
+    /// Creates a new `QueueProcessor`.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The [`QueueClient`] for the queue to process.
+    /// * `handler` - The handler called for each message.
+    /// * `options` - Optional configuration for the processor.
+    pub fn new(
+        client: QueueClient,
+        handler: Arc<dyn QueueMessageHandler>,
+        options: Option<QueueProcessorOptions>,
+    ) -> Result<Self> {
+        let options = options.unwrap_or_default();
+        let queue_name = client
+            .endpoint
+            .path_segments()
+            .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                azure_core::Error::with_message(
+                    ErrorKind::Other,
+                    format!("{} does not contain a queue name", client.endpoint),
+                )
+            })?;
+        let poison_queue_name = options
+            .poison_queue_name
+            .unwrap_or_else(|| format!("{queue_name}-poison"));
+        let mut poison_endpoint = client.endpoint.clone();
+        if let Ok(mut segments) = poison_endpoint.path_segments_mut() {
+            segments.pop_if_empty().pop().push(&poison_queue_name);
+        }
+        let poison_queue = QueueClient {
+            endpoint: poison_endpoint,
+            pipeline: client.pipeline.clone(),
+            version: client.version.clone(),
+            tracer: client.tracer.clone(),
+        };
+
+        let min_polling_interval = options
+            .min_polling_interval
+            .unwrap_or(DEFAULT_MIN_POLLING_INTERVAL);
+        Ok(Self {
+            client,
+            poison_queue,
+            handler,
+            batch_size: options
+                .batch_size
+                .map_or(DEFAULT_BATCH_SIZE, NonZero::get)
+                .min(MAX_BATCH_SIZE),
+            max_concurrent_messages: options
+                .max_concurrent_messages
+                .map_or(DEFAULT_MAX_CONCURRENT_MESSAGES, NonZero::get),
+            visibility_timeout: options
+                .visibility_timeout
+                .unwrap_or(DEFAULT_VISIBILITY_TIMEOUT),
+            min_polling_interval,
+            max_polling_interval: options
+                .max_polling_interval
+                .unwrap_or(DEFAULT_MAX_POLLING_INTERVAL)
+                .max(min_polling_interval),
+            max_dequeue_count: options
+                .max_dequeue_count
+                .unwrap_or(DEFAULT_MAX_DEQUEUE_COUNT),
+            poison_queue_created: AtomicBool::new(false),
+            stop: Mutex::new(StopState::Idle),
+        })
+    }
+
+    /// Gets the [`QueueClient`] for the queue being processed.
+    pub fn queue_client(&self) -> &QueueClient {
+        &self.client
+    }
+
+    /// Gets the [`QueueClient`] for the poison queue.
+    pub fn poison_queue_client(&self) -> &QueueClient {
+        &self.poison_queue
+    }
+
+    /// Processes messages until [`stop`](Self::stop) is called or receiving messages fails.
+    ///
+    /// After stopping, no more messages are received, and this returns once running handlers have completed.
+    /// Errors from handlers, and from deleting or renewing messages, are logged and do not stop the processor.
+    pub async fn run(&self) -> Result<()> {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let stopped = matches!(
+            std::mem::replace(&mut *self.stop.lock().unwrap(), StopState::Running(stop_tx)),
+            StopState::Requested
+        );
+        let result = self.process_until_stopped(stop_rx, stopped).await;
+        *self.stop.lock().unwrap() = StopState::Idle;
+        result
+    }
+
+    /// Stops the processor.
+    ///
+    /// [`run`](Self::run) returns once running handlers have completed. If the processor is not running,
+    /// the next call to `run` returns without receiving messages.
+    pub fn stop(&self) {
+        let mut stop = self.stop.lock().unwrap();
+        if let StopState::Running(stop) = std::mem::replace(&mut *stop, StopState::Requested) {
+            let _ = stop.send(());
+        }
+    }
+
+    async fn process_until_stopped(
+        &self,
+        stop_rx: oneshot::Receiver<()>,
+        mut stopped: bool,
+    ) -> Result<()> {
+        let mut stop_rx = stop_rx.fuse();
+        // Handlers and their visibility renewals are polled alongside receives and polling delays,
+        // so that a busy queue doesn't starve them.
+        let mut in_flight = FuturesUnordered::new();
+        let mut receive: Fuse<BoxFuture<'_, Result<Vec<ReceivedMessage>>>> = Fuse::terminated();
+        let mut poll: Fuse<BoxFuture<'_, ()>> = Fuse::terminated();
+        let mut polling_interval = self.min_polling_interval;
+        let mut error = None;
+        loop {
+            if stopped && receive.is_terminated() && in_flight.is_empty() {
+                return error.map_or(Ok(()), Err);
+            }
+            if !stopped
+                && receive.is_terminated()
+                && poll.is_terminated()
+                && in_flight.len() < self.max_concurrent_messages
+            {
+                let count = self
+                    .batch_size
+                    .min(self.max_concurrent_messages - in_flight.len());
+                receive = self.receive(count).boxed().fuse();
+            }
+            futures::select! {
+                _ = in_flight.select_next_some() => {}
+                result = receive => match result {
+                    // Messages that were received are handled even if the processor stopped meanwhile.
+                    Ok(messages) if messages.is_empty() => {
+                        if !stopped {
+                            debug!("queue is empty, polling again in {polling_interval}");
+                            poll = sleep(polling_interval).boxed().fuse();
+                            polling_interval =
+                                std::cmp::min(polling_interval * 2, self.max_polling_interval);
+                        }
+                    }
+                    Ok(messages) => {
+                        polling_interval = self.min_polling_interval;
+                        in_flight.extend(messages.into_iter().map(|message| self.process(message)));
+                    }
+                    Err(e) => {
+                        error = Some(e);
+                        stopped = true;
+                    }
+                },
+                _ = poll => {}
+                _ = stop_rx => stopped = true,
+            }
+        }
+    }
+
+    async fn receive(&self, count: usize) -> Result<Vec<ReceivedMessage>> {
+        let options = crate::models::QueueClientReceiveMessagesOptions {
+            number_of_messages: Some(count as i32),
+            visibility_timeout: Some(seconds(self.visibility_timeout)),
+            ..Default::default()
+        };
+        let messages = self
+            .client
+            .receive_messages(Some(options))
+            .await?
+            .into_model()?;
+        Ok(messages.items.unwrap_or_default())
+    }
+
+    async fn process(&self, message: ReceivedMessage) {
+        let (Some(message_id), Some(pop_receipt)) =
+            (message.message_id.as_deref(), message.pop_receipt.clone())
+        else {
+            warn!("received a message without an ID or pop receipt");
+            return;
+        };
+
+        if message.dequeue_count.unwrap_or_default() > self.max_dequeue_count {
+            if let Err(e) = self
+                .move_to_poison_queue(&message, message_id, &pop_receipt)
+                .await
+            {
+                warn!("failed to move message {message_id} to the poison queue: {e}");
+            }
+            return;
+        }
+
+        let pop_receipt = Mutex::new(pop_receipt);
+        let result = {
+            let handle = self.handler.handle(&message);
+            let renew = self.renew(message_id, &pop_receipt).boxed();
+            match future::select(handle, renew).await {
+                Either::Left((result, _)) => result,
+                Either::Right((never, _)) => match never {},
+            }
+        };
+        let pop_receipt = pop_receipt.into_inner().unwrap();
+
+        match result {
+            Ok(()) => {
+                if let Err(e) = self
+                    .client
+                    .delete_message(message_id, &pop_receipt, None)
+                    .await
+                {
+                    warn!("failed to delete message {message_id}: {e}");
+                }
+            }
+            Err(e) => {
+                warn!("handler failed for message {message_id}: {e}");
+                if let Err(e) = self
+                    .client
+                    .update_message(message_id, &pop_receipt, 0, None)
+                    .await
+                {
+                    warn!("failed to release message {message_id}: {e}");
+                }
+            }
+        }
+    }
+
+    /// Extends the visibility timeout of a message every half of the timeout until dropped.
+    async fn renew(&self, message_id: &str, pop_receipt: &Mutex<String>) -> Infallible {
+        loop {
+            sleep(self.visibility_timeout / 2).await;
+            let current = pop_receipt.lock().unwrap().clone();
+            match self
+                .client
+                .update_message(message_id, &current, seconds(self.visibility_timeout), None)
+                .await
+            {
+                Ok(response) => {
+                    if let Some(renewed) = response.headers().get_optional_string(&POP_RECEIPT) {
+                        *pop_receipt.lock().unwrap() = renewed;
+                    }
+                }
+                Err(e) => {
+                    warn!("failed to extend the visibility timeout of message {message_id}: {e}")
+                }
+            }
+        }
+    }
+
+    async fn move_to_poison_queue(
+        &self,
+        message: &ReceivedMessage,
+        message_id: &str,
+        pop_receipt: &str,
+    ) -> Result<()> {
+        if !self.poison_queue_created.load(Ordering::Acquire) {
+            match self.poison_queue.create(None).await {
+                Ok(_) => {}
+                Err(e) if e.http_status() == Some(StatusCode::Conflict) => {}
+                Err(e) => return Err(e),
+            }
+            self.poison_queue_created.store(true, Ordering::Release);
+        }
+        let poison_message = QueueMessage {
+            message_text: message.message_text.clone(),
+        };
+        self.poison_queue
+            .send_message(poison_message.try_into()?, None)
+            .await?;
+        self.client
+            .delete_message(message_id, pop_receipt, None)
+            .await?;
+        Ok(())
+    }
+}
+
+fn seconds(duration: Duration) -> i32 {
+    duration.whole_seconds().clamp(0, i32::MAX as i64) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QueueClientOptions;
+    use azure_core::{
+        http::{headers::Headers, AsyncRawResponse, ClientOptions, Method, Transport},
+        xml, Bytes,
+    };
+    use azure_core_test::http::MockHttpClient;
+    use std::collections::HashMap;
+
+    /// The state of the mock queue service.
+    #[derive(Default)]
+    struct MockQueues {
+        /// Visible messages by queue name, as (text, dequeue count).
+        messages: HashMap<String, Vec<(String, i64)>>,
+        /// Messages received but not yet deleted, by pop receipt.
+        received: HashMap<String, (String, String, i64)>,
+        deleted: Vec<String>,
+        released: Vec<String>,
+        renewals: usize,
+        next_receipt: usize,
+    }
+
+    fn client(state: Arc<Mutex<MockQueues>>) -> QueueClient {
+        let mock_client = Arc::new(MockHttpClient::new(move |req| {
+            let state = state.clone();
+            let method = req.method();
+            let url = req.url().clone();
+            let body = Bytes::from(req.body());
+            async move {
+                let mut state = state.lock().unwrap();
+                let segments: Vec<_> = url.path_segments().unwrap().collect();
+                let queue = segments[0].to_string();
+                let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+                let mut headers = Headers::new();
+                let (status, body) = match (method, segments.len()) {
+                    (Method::Put, 1) => (StatusCode::Created, String::new()),
+                    (Method::Post, 2) => {
+                        let message: QueueMessage = xml::from_xml(&body).unwrap();
+                        state
+                            .messages
+                            .entry(queue)
+                            .or_default()
+                            .push((message.message_text.unwrap(), 0));
+                        (StatusCode::Created, "<QueueMessagesList/>".to_string())
+                    }
+                    (Method::Get, 2) => {
+                        let count: usize = query["numofmessages"].parse().unwrap();
+                        let available = state.messages.entry(queue).or_default();
+                        let taken: Vec<_> = available
+                            .drain(..count.min(available.len()))
+                            .collect();
+                        let mut xml = String::from("<QueueMessagesList>");
+                        for (text, dequeue_count) in taken {
+                            state.next_receipt += 1;
+                            let receipt = format!("receipt-{}", state.next_receipt);
+                            let id = format!("id-{text}");
+                            xml += &format!(
+                                "<QueueMessage><MessageId>{id}</MessageId><PopReceipt>{receipt}</PopReceipt>\
+                                 <DequeueCount>{}</DequeueCount><MessageText>{text}</MessageText></QueueMessage>",
+                                dequeue_count + 1
+                            );
+                            state
+                                .received
+                                .insert(receipt, (id, text, dequeue_count + 1));
+                        }
+                        xml += "</QueueMessagesList>";
+                        (StatusCode::Ok, xml)
+                    }
+                    (Method::Put, 3) => {
+                        let receipt = &query["popreceipt"];
+                        let entry = state.received.remove(receipt).unwrap();
+                        if query["visibilitytimeout"] == "0" {
+                            state.released.push(entry.1.clone());
+                            state
+                                .messages
+                                .entry(queue)
+                                .or_default()
+                                .push((entry.1, entry.2));
+                        } else {
+                            state.renewals += 1;
+                            state.next_receipt += 1;
+                            let renewed = format!("receipt-{}", state.next_receipt);
+                            headers.insert(POP_RECEIPT, renewed.clone());
+                            state.received.insert(renewed, entry);
+                        }
+                        (StatusCode::NoContent, String::new())
+                    }
+                    (Method::Delete, 3) => {
+                        let entry = state.received.remove(&query["popreceipt"]).unwrap();
+                        state.deleted.push(entry.1);
+                        (StatusCode::NoContent, String::new())
+                    }
+                    _ => panic!("unexpected request {method:?} {url}"),
+                };
+                Ok(AsyncRawResponse::from_bytes(status, headers, body))
+            }
+            .boxed()
+        }));
+        QueueClient::new(
+            "https://myaccount.queue.core.windows.net/myqueue"
+                .parse()
+                .unwrap(),
+            None,
+            Some(QueueClientOptions {
+                client_options: ClientOptions {
+                    transport: Some(Transport::new(mock_client)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .unwrap()
+    }
+
+    /// A handler that fails messages starting with "fail", sleeps for messages starting with "slow",
+    /// and stops the processor once `expected` messages have been handled.
+    struct TestHandler {
+        handled: Mutex<Vec<String>>,
+        expected: usize,
+        processor: Mutex<Option<Arc<QueueProcessor>>>,
+    }
+
+    #[async_trait]
+    impl QueueMessageHandler for TestHandler {
+        async fn handle(&self, message: &ReceivedMessage) -> Result<()> {
+            let text = message.message_text.clone().unwrap();
+            if text.starts_with("slow") {
+                sleep(Duration::milliseconds(1500)).await;
+            }
+            let handled = {
+                let mut handled = self.handled.lock().unwrap();
+                handled.push(text.clone());
+                handled.len()
+            };
+            if handled >= self.expected {
+                if let Some(processor) = self.processor.lock().unwrap().as_ref() {
+                    processor.stop();
+                }
+            }
+            if text.starts_with("fail") {
+                return Err(azure_core::Error::with_message(
+                    ErrorKind::Other,
+                    "handler failed",
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    async fn run(
+        state: Arc<Mutex<MockQueues>>,
+        expected: usize,
+        options: QueueProcessorOptions,
+    ) -> Arc<TestHandler> {
+        let handler = Arc::new(TestHandler {
+            handled: Mutex::default(),
+            expected,
+            processor: Mutex::default(),
+        });
+        let processor = Arc::new(
+            QueueProcessor::new(
+                client(state),
+                handler.clone(),
+                Some(QueueProcessorOptions {
+                    min_polling_interval: Some(Duration::milliseconds(10)),
+                    max_polling_interval: Some(Duration::milliseconds(20)),
+                    ..options
+                }),
+            )
+            .unwrap(),
+        );
+        *handler.processor.lock().unwrap() = Some(processor.clone());
+        processor.run().await.unwrap();
+        handler.processor.lock().unwrap().take();
+        handler
+    }
+
+    fn enqueue(state: &Mutex<MockQueues>, queue: &str, text: &str, dequeue_count: i64) {
+        state
+            .lock()
+            .unwrap()
+            .messages
+            .entry(queue.to_string())
+            .or_default()
+            .push((text.to_string(), dequeue_count));
+    }
+
+    #[test]
+    fn poison_queue_name_defaults_to_queue_name() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        let handler = Arc::new(TestHandler {
+            handled: Mutex::default(),
+            expected: 0,
+            processor: Mutex::default(),
+        });
+        let processor = QueueProcessor::new(client(state.clone()), handler.clone(), None).unwrap();
+        assert_eq!(
+            processor.poison_queue_client().url().as_str(),
+            "https://myaccount.queue.core.windows.net/myqueue-poison"
+        );
+
+        let processor = QueueProcessor::new(
+            client(state),
+            handler,
+            Some(QueueProcessorOptions {
+                poison_queue_name: Some("dead-letters".into()),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            processor.poison_queue_client().url().as_str(),
+            "https://myaccount.queue.core.windows.net/dead-letters"
+        );
+    }
+
+    #[tokio::test]
+    async fn deletes_handled_messages() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        for text in ["a", "b", "c"] {
+            enqueue(&state, "myqueue", text, 0);
+        }
+
+        let handler = run(
+            state.clone(),
+            3,
+            QueueProcessorOptions {
+                batch_size: NonZero::new(2),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let mut handled = handler.handled.lock().unwrap().clone();
+        handled.sort();
+        assert_eq!(handled, ["a", "b", "c"]);
+        let mut deleted = state.lock().unwrap().deleted.clone();
+        deleted.sort();
+        assert_eq!(deleted, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn releases_failed_messages() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        enqueue(&state, "myqueue", "fail", 0);
+
+        let handler = run(
+            state.clone(),
+            2,
+            QueueProcessorOptions {
+                max_dequeue_count: Some(2),
+                ..Default::default()
+            },
+        )
+        .await;
+        let state = state.lock().unwrap();
+
+        assert_eq!(*handler.handled.lock().unwrap(), ["fail", "fail"]);
+        assert_eq!(state.released, ["fail", "fail"]);
+        assert!(state.deleted.is_empty());
+        assert_eq!(
+            state.messages["myqueue"],
+            [("fail".to_string(), 2)],
+            "the message is left for the next run once the processor stops"
+        );
+    }
+
+    #[tokio::test]
+    async fn moves_messages_over_max_dequeue_count_to_poison_queue() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        enqueue(&state, "myqueue", "poison", 5);
+        enqueue(&state, "myqueue", "ok", 0);
+
+        let handler = run(state.clone(), 1, QueueProcessorOptions::default()).await;
+
+        assert_eq!(*handler.handled.lock().unwrap(), ["ok"]);
+        let state = state.lock().unwrap();
+        let mut deleted = state.deleted.clone();
+        deleted.sort();
+        assert_eq!(deleted, ["ok", "poison"]);
+        assert_eq!(
+            state.messages["myqueue-poison"],
+            [("poison".to_string(), 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn stop_before_run_is_honored() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        enqueue(&state, "myqueue", "a", 0);
+        let handler = Arc::new(TestHandler {
+            handled: Mutex::default(),
+            expected: 1,
+            processor: Mutex::default(),
+        });
+        let processor = QueueProcessor::new(client(state.clone()), handler.clone(), None).unwrap();
+
+        processor.stop();
+        processor.run().await.unwrap();
+        assert!(handler.handled.lock().unwrap().is_empty());
+        assert_eq!(state.lock().unwrap().messages["myqueue"].len(), 1);
+
+        // The stop request was consumed by the run it stopped.
+        let processor = Arc::new(processor);
+        *handler.processor.lock().unwrap() = Some(processor.clone());
+        processor.run().await.unwrap();
+        handler.processor.lock().unwrap().take();
+        assert_eq!(*handler.handled.lock().unwrap(), ["a"]);
+    }
+
+    #[tokio::test]
+    async fn extends_visibility_of_long_running_handlers() {
+        let state = Arc::new(Mutex::new(MockQueues::default()));
+        enqueue(&state, "myqueue", "slow", 0);
+
+        run(
+            state.clone(),
+            1,
+            QueueProcessorOptions {
+                visibility_timeout: Some(Duration::seconds(1)),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let state = state.lock().unwrap();
+        assert!(state.renewals >= 2, "renewals: {}", state.renewals);
+        assert_eq!(state.deleted, ["slow"]);
+    }
+}
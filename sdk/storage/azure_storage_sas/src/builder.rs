@@ -6,6 +6,10 @@ use crate::blob::{
     ContainerResource, ContainerState, DirectoryResource, DirectoryState,
 };
 use crate::common::{sign, CommonFields, SasResource, ValidatedKey};
+use crate::file::{
+    FilePermissions, FileResource, FileSasOptions, FileState, SharePermissions, ShareResource,
+    ShareState,
+};
 use crate::ip_range::SasIpRange;
 use crate::protocol::SasProtocol;
 use crate::queue::{QueuePermissions, QueueResource, QueueState};
@@ -120,6 +124,37 @@ impl<'a> SasBuilder<'a, Untyped> {
             },
         }
     }
+
+    /// Selects a file share resource and transitions the builder to share state.
+    pub fn share(self, share: impl Into<String>) -> SasBuilder<'a, ShareState> {
+        SasBuilder {
+            key: self.key,
+            common: self.common,
+            state: ShareState {
+                resource: ShareResource::new(share),
+                permissions: SharePermissions::default(),
+            },
+        }
+    }
+
+    /// Selects a file resource and transitions the builder to file state.
+    ///
+    /// `path` is the `/`-separated path of the file relative to the share root.
+    pub fn file(
+        self,
+        share: impl Into<String>,
+        path: impl Into<String>,
+    ) -> SasBuilder<'a, FileState> {
+        SasBuilder {
+            key: self.key,
+            common: self.common,
+            state: FileState {
+                resource: FileResource::new(share, path),
+                permissions: FilePermissions::default(),
+                options: FileSasOptions::default(),
+            },
+        }
+    }
 }
 
 // Common setters available in any state.
@@ -246,6 +281,44 @@ mod tests {
         assert!(!qp.contains("rscc="));
     }
 
+    #[test]
+    fn share_build() {
+        let udk = test_udk();
+        let expiry = datetime!(2025-06-01 12:00:00 UTC);
+
+        let qp = SasBuilder::new("myaccount", &udk, expiry)
+            .unwrap()
+            .share("myshare")
+            .list()
+            .read()
+            .build();
+
+        assert!(qp.starts_with("sv=2026-04-06&sr=s&"), "got: {qp}");
+        assert!(qp.contains("sp=rl"), "got: {qp}");
+        assert!(qp.contains("sig="));
+    }
+
+    #[test]
+    fn file_build_with_response_headers() {
+        let udk = test_udk();
+        let expiry = datetime!(2025-06-01 12:00:00 UTC);
+
+        let qp = SasBuilder::new("myaccount", &udk, expiry)
+            .unwrap()
+            .file("myshare", "dir/report.csv")
+            .write()
+            .read()
+            .content_type("text/csv")
+            .build();
+
+        assert!(qp.starts_with("sv=2026-04-06&sr=f&"), "got: {qp}");
+        assert!(qp.contains("sp=rw"), "got: {qp}");
+        assert!(qp.contains("rsct=text%2Fcsv"), "got: {qp}");
+        for absent in ["saoid=", "ses=", "sdd=", "snapshot="] {
+            assert!(!qp.contains(absent), "unexpected `{absent}` in: {qp}");
+        }
+    }
+
     #[test]
     fn queue_delegated_setters_are_percent_encoded() {
         let mut udk = test_udk();
@@ -0,0 +1,463 @@
+// Copyright (c) Microsoft Corporation. All rights reserved.
+// Licensed under the MIT License.
+
+//! File-service resource types for user delegation SAS: share and file.
+//!
+//! # Example
+//!
+//! ```rust no_run
+//! use azure_storage_sas::{SasBuilder, SasProtocol, UserDelegationKey};
+//! use time::OffsetDateTime;
+//!
+//! # fn example(udk: UserDelegationKey) -> azure_core::Result<()> {
+//! let token = SasBuilder::new("myaccount", &udk,
+//!         OffsetDateTime::now_utc() + time::Duration::hours(1))?
+//!     .file("reports", "2024/summary.csv")
+//!     .read()
+//!     .content_disposition("attachment")
+//!     .protocol(SasProtocol::Https)
+//!     .build();
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::builder::SasBuilder;
+use crate::common::sealed::Sealed;
+use crate::common::{CommonFields, SasResource, ValidatedKey};
+use crate::SAS_VERSION;
+
+/// A share resource for user delegation SAS.
+#[derive(Debug)]
+pub(crate) struct ShareResource {
+    share: String,
+}
+
+impl ShareResource {
+    /// Creates a new share resource.
+    pub(crate) fn new(share: impl Into<String>) -> Self {
+        Self {
+            share: share.into(),
+        }
+    }
+
+    pub(crate) fn canonicalized_resource(&self, account: &str) -> String {
+        format!("/file/{}/{}", account, self.share)
+    }
+}
+
+/// A file resource for user delegation SAS.
+#[derive(Debug)]
+pub(crate) struct FileResource {
+    share: String,
+    path: String,
+}
+
+impl FileResource {
+    /// Creates a new file resource. Leading `/` characters in `path` are ignored.
+    pub(crate) fn new(share: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            share: share.into(),
+            path: path.into().trim_start_matches('/').to_owned(),
+        }
+    }
+
+    pub(crate) fn canonicalized_resource(&self, account: &str) -> String {
+        format!("/file/{}/{}/{}", account, self.share, self.path)
+    }
+}
+
+/// Permissions for a share SAS.
+///
+/// Serialization order: `rcwdl`. Flags are set through the permission setters on
+/// [`SasBuilder<ShareState>`](crate::SasBuilder).
+#[derive(Clone, Copy, Default)]
+pub(crate) struct SharePermissions {
+    pub(crate) read: bool,
+    pub(crate) create: bool,
+    pub(crate) write: bool,
+    pub(crate) delete: bool,
+    pub(crate) list: bool,
+}
+
+impl SharePermissions {
+    /// Serializes the enabled permissions to the SAS token format.
+    pub(crate) fn to_sas_str(&self) -> String {
+        let mut s = String::with_capacity(5);
+        if self.read {
+            s.push('r');
+        }
+        if self.create {
+            s.push('c');
+        }
+        if self.write {
+            s.push('w');
+        }
+        if self.delete {
+            s.push('d');
+        }
+        if self.list {
+            s.push('l');
+        }
+        s
+    }
+}
+
+/// Permissions for a file SAS.
+///
+/// Serialization order: `rcwd`. Flags are set through the permission setters on
+/// [`SasBuilder<FileState>`](crate::SasBuilder).
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FilePermissions {
+    pub(crate) read: bool,
+    pub(crate) create: bool,
+    pub(crate) write: bool,
+    pub(crate) delete: bool,
+}
+
+impl FilePermissions {
+    /// Serializes the enabled permissions to the SAS token format.
+    pub(crate) fn to_sas_str(&self) -> String {
+        let mut s = String::with_capacity(4);
+        if self.read {
+            s.push('r');
+        }
+        if self.create {
+            s.push('c');
+        }
+        if self.write {
+            s.push('w');
+        }
+        if self.delete {
+            s.push('d');
+        }
+        s
+    }
+}
+
+/// Response header overrides for a file SAS.
+#[derive(Default)]
+pub(crate) struct FileSasOptions {
+    pub cache_control: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    pub content_language: Option<String>,
+    pub content_type: Option<String>,
+}
+
+/// State after selecting a share resource.
+pub struct ShareState {
+    pub(crate) resource: ShareResource,
+    pub(crate) permissions: SharePermissions,
+}
+
+/// State after selecting a file resource.
+pub struct FileState {
+    pub(crate) resource: FileResource,
+    pub(crate) permissions: FilePermissions,
+    pub(crate) options: FileSasOptions,
+}
+
+impl Sealed for ShareState {}
+impl Sealed for FileState {}
+
+/// Permission setters for a share SAS, gated on [`ShareState`].
+impl SasBuilder<'_, ShareState> {
+    /// Enables read permission on any file in the share.
+    pub fn read(mut self) -> Self {
+        self.state.permissions.read = true;
+        self
+    }
+
+    /// Enables create permission on any file in the share.
+    pub fn create(mut self) -> Self {
+        self.state.permissions.create = true;
+        self
+    }
+
+    /// Enables write permission on any file in the share.
+    pub fn write(mut self) -> Self {
+        self.state.permissions.write = true;
+        self
+    }
+
+    /// Enables delete permission on any file in the share.
+    pub fn delete(mut self) -> Self {
+        self.state.permissions.delete = true;
+        self
+    }
+
+    /// Enables listing the files and directories in the share.
+    pub fn list(mut self) -> Self {
+        self.state.permissions.list = true;
+        self
+    }
+}
+
+/// Permission setters for a file SAS, gated on [`FileState`].
+impl SasBuilder<'_, FileState> {
+    /// Enables read permission.
+    pub fn read(mut self) -> Self {
+        self.state.permissions.read = true;
+        self
+    }
+
+    /// Enables create permission.
+    pub fn create(mut self) -> Self {
+        self.state.permissions.create = true;
+        self
+    }
+
+    /// Enables write permission.
+    pub fn write(mut self) -> Self {
+        self.state.permissions.write = true;
+        self
+    }
+
+    /// Enables delete permission.
+    pub fn delete(mut self) -> Self {
+        self.state.permissions.delete = true;
+        self
+    }
+
+    /// Sets the `Cache-Control` response header override.
+    pub fn cache_control(mut self, value: impl Into<String>) -> Self {
+        self.state.options.cache_control = Some(value.into());
+        self
+    }
+
+    /// Sets the `Content-Disposition` response header override.
+    pub fn content_disposition(mut self, value: impl Into<String>) -> Self {
+        self.state.options.content_disposition = Some(value.into());
+        self
+    }
+
+    /// Sets the `Content-Encoding` response header override.
+    pub fn content_encoding(mut self, value: impl Into<String>) -> Self {
+        self.state.options.content_encoding = Some(value.into());
+        self
+    }
+
+    /// Sets the `Content-Language` response header override.
+    pub fn content_language(mut self, value: impl Into<String>) -> Self {
+        self.state.options.content_language = Some(value.into());
+        self
+    }
+
+    /// Sets the `Content-Type` response header override.
+    pub fn content_type(mut self, value: impl Into<String>) -> Self {
+        self.state.options.content_type = Some(value.into());
+        self
+    }
+}
+
+impl SasResource for ShareState {
+    fn string_to_sign(&self, common: &CommonFields, key: &ValidatedKey<'_>) -> String {
+        let sp = self.permissions.to_sas_str();
+        let canonical = self.resource.canonicalized_resource(&common.account);
+        file_udk_string_to_sign(
+            &sp,
+            common,
+            &FileSasOptions::default(),
+            key,
+            "s",
+            &canonical,
+        )
+    }
+
+    fn query_parameters(
+        &self,
+        common: &CommonFields,
+        key: &ValidatedKey<'_>,
+        signature: &str,
+    ) -> String {
+        let sp = self.permissions.to_sas_str();
+        file_udk_query_parameters(&sp, common, &FileSasOptions::default(), key, "s", signature)
+    }
+}
+
+impl SasResource for FileState {
+    fn string_to_sign(&self, common: &CommonFields, key: &ValidatedKey<'_>) -> String {
+        let sp = self.permissions.to_sas_str();
+        let canonical = self.resource.canonicalized_resource(&common.account);
+        file_udk_string_to_sign(&sp, common, &self.options, key, "f", &canonical)
+    }
+
+    fn query_parameters(
+        &self,
+        common: &CommonFields,
+        key: &ValidatedKey<'_>,
+        signature: &str,
+    ) -> String {
+        let sp = self.permissions.to_sas_str();
+        file_udk_query_parameters(&sp, common, &self.options, key, "f", signature)
+    }
+}
+
+/// Builds the file-service user delegation SAS string-to-sign.
+///
+/// See <https://learn.microsoft.com/rest/api/storageservices/create-user-delegation-sas#specify-the-signature>.
+fn file_udk_string_to_sign(
+    permissions: &str,
+    common: &CommonFields,
+    options: &FileSasOptions,
+    key: &ValidatedKey<'_>,
+    sr: &str,
+    canonicalized_resource: &str,
+) -> String {
+    let skdutid = key.signed_delegated_user_tid.unwrap_or("");
+    let sduoid = common.delegated_user_object_id.as_deref().unwrap_or("");
+    let sip = common.ip_str();
+    let spr = common.protocol_str();
+    let st = common.start_str();
+    let se = common.expiry_str();
+    let skt = CommonFields::format_time(key.signed_start);
+    let ske = CommonFields::format_time(key.signed_expiry);
+    let rscc = options.cache_control.as_deref().unwrap_or("");
+    let rscd = options.content_disposition.as_deref().unwrap_or("");
+    let rsce = options.content_encoding.as_deref().unwrap_or("");
+    let rscl = options.content_language.as_deref().unwrap_or("");
+    let rsct = options.content_type.as_deref().unwrap_or("");
+
+    #[rustfmt::skip]
+    let parts: Vec<&str> = vec![
+        permissions,            // [0]  signedPermissions
+        &st,                    // [1]  signedStart
+        &se,                    // [2]  signedExpiry
+        canonicalized_resource, // [3]  canonicalizedResource
+        key.signed_oid,         // [4]  signedKeyObjectId
+        key.signed_tid,         // [5]  signedKeyTenantId
+        &skt,                   // [6]  signedKeyStart
+        &ske,                   // [7]  signedKeyExpiry
+        key.signed_service,     // [8]  signedKeyService
+        key.signed_version,     // [9]  signedKeyVersion
+        skdutid,                // [10] signedDelegatedUserTenantId
+        sduoid,                 // [11] signedDelegatedUserObjectId
+        &sip,                   // [12] signedIP
+        &spr,                   // [13] signedProtocol
+        SAS_VERSION,            // [14] signedVersion
+        sr,                     // [15] signedResource
+        rscc,                   // [16] rscc
+        rscd,                   // [17] rscd
+        rsce,                   // [18] rsce
+        rscl,                   // [19] rscl
+        rsct,                   // [20] rsct
+    ];
+    parts.join("\n")
+}
+
+/// Builds the file-service user delegation SAS query parameters.
+fn file_udk_query_parameters(
+    permissions: &str,
+    common: &CommonFields,
+    options: &FileSasOptions,
+    key: &ValidatedKey<'_>,
+    sr: &str,
+    signature: &str,
+) -> String {
+    let mut parts = Vec::with_capacity(21);
+    parts.push(format!("sv={SAS_VERSION}"));
+    parts.push(format!("sr={sr}"));
+    if let Some(ref start) = common.start {
+        parts.push(format!("st={}", CommonFields::format_time(start)));
+    }
+    parts.push(format!("se={}", common.expiry_str()));
+    parts.push(format!("sp={permissions}"));
+    if let Some(ref ip) = common.ip_range {
+        parts.push(format!("sip={}", ip.sip_value()));
+    }
+    if let Some(ref proto) = common.protocol {
+        parts.push(format!("spr={proto}"));
+    }
+    parts.push(format!("skoid={}", key.signed_oid));
+    parts.push(format!("sktid={}", key.signed_tid));
+    parts.push(format!(
+        "skt={}",
+        CommonFields::format_time(key.signed_start)
+    ));
+    parts.push(format!(
+        "ske={}",
+        CommonFields::format_time(key.signed_expiry)
+    ));
+    parts.push(format!("sks={}", key.signed_service));
+    parts.push(format!("skv={}", key.signed_version));
+    if let Some(v) = key.signed_delegated_user_tid {
+        parts.push(format!("skdutid={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = common.delegated_user_object_id {
+        parts.push(format!("sduoid={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = options.cache_control {
+        parts.push(format!("rscc={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = options.content_disposition {
+        parts.push(format!("rscd={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = options.content_encoding {
+        parts.push(format!("rsce={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = options.content_language {
+        parts.push(format!("rscl={}", CommonFields::encode(v)));
+    }
+    if let Some(ref v) = options.content_type {
+        parts.push(format!("rsct={}", CommonFields::encode(v)));
+    }
+    parts.push(format!("sig={}", CommonFields::encode(signature)));
+    parts.join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::test_support::{test_common, test_udk};
+    use time::macros::datetime;
+
+    #[test]
+    fn file_string_to_sign_has_21_fields_in_order() {
+        let mut udk = test_udk();
+        udk.signed_delegated_user_tid = Some("f-tenant".into());
+        let key = ValidatedKey::from_key(&udk).unwrap();
+        let mut common = test_common(datetime!(2025-06-01 12:00:00 UTC));
+        common.delegated_user_object_id = Some("duoid".into());
+        let options = FileSasOptions {
+            content_type: Some("text/csv".into()),
+            ..Default::default()
+        };
+
+        let sts = file_udk_string_to_sign("rw", &common, &options, &key, "f", "/file/acct/s/a.txt");
+        let lines: Vec<&str> = sts.split('\n').collect();
+        assert_eq!(lines.len(), 21, "file STS must have exactly 21 fields");
+        assert_eq!(lines[0], "rw"); // sp
+        assert_eq!(lines[3], "/file/acct/s/a.txt"); // cr
+        assert_eq!(lines[4], "oid-value"); // skoid
+        assert_eq!(lines[10], "f-tenant"); // skdutid (from key)
+        assert_eq!(lines[11], "duoid"); // sduoid (from builder)
+        assert_eq!(lines[14], "2026-04-06"); // sv
+        assert_eq!(lines[15], "f"); // sr
+        assert_eq!(lines[16], ""); // rscc
+        assert_eq!(lines[20], "text/csv"); // rsct
+    }
+
+    #[test]
+    fn canonicalized_resources() {
+        assert_eq!(
+            ShareResource::new("s").canonicalized_resource("acct"),
+            "/file/acct/s"
+        );
+        assert_eq!(
+            FileResource::new("s", "/dir/a.txt").canonicalized_resource("acct"),
+            "/file/acct/s/dir/a.txt"
+        );
+    }
+
+    #[test]
+    fn share_permissions_serialize_in_canonical_order() {
+        let permissions = SharePermissions {
+            read: true,
+            create: true,
+            write: true,
+            delete: true,
+            list: true,
+        };
+        assert_eq!(permissions.to_sas_str(), "rcwdl");
+    }
+}
@@ -12,6 +12,8 @@
 //! - [`SasBuilder::container`] — container-level user delegation SAS
 //! - [`SasBuilder::directory`] — directory-level (ADLS Gen2) user delegation SAS
 //! - [`SasBuilder::queue`] — queue-level user delegation SAS
+//! - [`SasBuilder::share`] — file share-level user delegation SAS
+//! - [`SasBuilder::file`] — file-level user delegation SAS
 
 mod builder;
 mod common;
@@ -19,6 +21,7 @@ mod ip_range;
 mod protocol;
 
 pub mod blob;
+pub mod file;
 pub mod queue;
 
 pub use azure_storage_common::models::UserDelegationKey;